@@ -11,6 +11,15 @@ pub struct SVRContext {
 }
 
 impl SVRContext {
+    /// 当前用户的键
+    pub const USER: &'static str = "USER";
+    /// 当前单位的键
+    pub const UNIT: &'static str = "UNIT";
+    /// 业务日期的键
+    pub const DATE: &'static str = "DATE";
+    /// 请求语言的键，值为语言标识，如 `en-US`，也可以是逗号分隔的回退列表
+    pub const LANG: &'static str = "LANG";
+
     /// 创建一个新的Context实例
     pub fn new() -> Self {
         SVRContext {
@@ -47,6 +56,27 @@ impl SVRContext {
         let mut data = self.data.write().unwrap();
         data.clear();
     }
+
+    /// 当前用户
+    pub fn user(&self) -> Option<CellValue> {
+        self.get(Self::USER)
+    }
+
+    /// 当前单位
+    pub fn unit(&self) -> Option<CellValue> {
+        self.get(Self::UNIT)
+    }
+
+    /// 请求的语言
+    pub fn lang(&self) -> Option<CellValue> {
+        self.get(Self::LANG)
+    }
+
+    /// 业务日期，未设置时为系统当天日期（`YYYY-MM-DD`）
+    pub fn date(&self) -> CellValue {
+        self.get(Self::DATE)
+            .unwrap_or_else(|| CellValue::String(chrono::Local::now().date_naive().format("%Y-%m-%d").to_string()))
+    }
 }
 
 impl Default for SVRContext {
@@ -86,4 +116,16 @@ mod tests {
         ctx.clear();
         assert!(!ctx.contains_key("age"));
     }
+
+    #[test]
+    fn test_well_known_keys() {
+        let ctx = SVRContext::new();
+        assert_eq!(ctx.user(), None);
+        assert!(matches!(ctx.date(), CellValue::String(date) if date.len() == 10));
+
+        ctx.set(SVRContext::USER, CellValue::String("admin".to_string()));
+        ctx.set(SVRContext::DATE, CellValue::String("2024-06-30".to_string()));
+        assert_eq!(ctx.user(), Some(CellValue::String("admin".to_string())));
+        assert_eq!(ctx.date(), CellValue::String("2024-06-30".to_string()));
+    }
 }
@@ -0,0 +1,455 @@
+//! # 分组聚合模块
+//!
+//! 为 `RowDataSet` 提供按一个或多个键列分组并计算聚合值的能力，结果是一个带有
+//! 完整列定义的新 `RowDataSet`，可以继续过滤、排序或再次聚合。
+//!
+//! ## 聚合函数与结果类型
+//!
+//! | 聚合函数 | 适用列类型 | 结果类型 |
+//! |----------|------------|----------|
+//! | `Sum` | 数值类型 | `F32`/`F64` 为 `F64`，其他为 `Decimal` |
+//! | `Avg` | 数值类型 | `F32`/`F64` 为 `F64`，其他为 `Decimal` |
+//! | `Count` / `CountDistinct` | 任意 | `I64` |
+//! | `Min` / `Max` / `First` / `Last` | 任意 | 与源列相同 |
+//!
+//! 整数与定点数的求和、求平均都通过 `rust_decimal::Decimal` 进行，结果是精确的。
+//! `Sum`、`Avg`、`Min`、`Max`、`Count`、`CountDistinct` 忽略 `NULL`；
+//! `First`/`Last` 取分组中第一行/最后一行的原值（包括 `NULL`）。
+//! 分组内全部为 `NULL` 时，`Sum`、`Avg`、`Min`、`Max` 的结果为 `NULL`。
+//!
+//! ## 示例
+//!
+//! ```rust
+//! use cmx_core::model::data::dataset::{rds::RowDataSet, ColumnType};
+//! use cmx_core::model::data::dataset::aggregate::Aggregate;
+//! use serde_json::json;
+//!
+//! let mut facts = RowDataSet::new("facts".to_string());
+//! facts.add_column("DW".to_string(), ColumnType::String).unwrap();
+//! facts.add_column("JE".to_string(), ColumnType::Decimal).unwrap();
+//! facts.add_row(vec![json!("01"), json!(0.1)]).unwrap();
+//! facts.add_row(vec![json!("01"), json!(0.2)]).unwrap();
+//! facts.add_row(vec![json!("02"), json!(5)]).unwrap();
+//!
+//! let totals = facts
+//!     .group_by(&["DW"], &[Aggregate::sum("JE"), Aggregate::count_all().alias("N")])
+//!     .unwrap();
+//! assert_eq!(totals.row_count(), 2);
+//! assert_eq!(totals.get_cell(0, "SUM_JE").unwrap(), &json!(0.3));
+//! assert_eq!(totals.get_cell(0, "N").unwrap(), &json!(2));
+//! ```
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use super::coerce::{decimal_to_cell, to_decimal};
+use super::filter::compare_values;
+use super::rds::RowDataSet;
+use super::{ColumnType, DataSetError};
+use crate::model::data::cell::CellValue;
+
+/// 聚合函数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AggFunc {
+    Sum,
+    Count,
+    CountDistinct,
+    Min,
+    Max,
+    Avg,
+    First,
+    Last,
+}
+
+impl AggFunc {
+    fn prefix(&self) -> &'static str {
+        match self {
+            AggFunc::Sum => "SUM",
+            AggFunc::Count => "COUNT",
+            AggFunc::CountDistinct => "COUNT_DISTINCT",
+            AggFunc::Min => "MIN",
+            AggFunc::Max => "MAX",
+            AggFunc::Avg => "AVG",
+            AggFunc::First => "FIRST",
+            AggFunc::Last => "LAST",
+        }
+    }
+}
+
+/// 聚合列定义
+///
+/// `column` 为 `None` 仅对 `Count` 有意义，表示统计分组的行数（`COUNT(*)`）。
+/// 未指定别名时，结果列名为 `<函数>_<列名>`，例如 `SUM_JE`；`COUNT(*)` 为 `COUNT`。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Aggregate {
+    pub func: AggFunc,
+    pub column: Option<String>,
+    pub alias: Option<String>,
+}
+
+impl Aggregate {
+    pub fn new(func: AggFunc, column: impl Into<String>) -> Self {
+        Self { func, column: Some(column.into()), alias: None }
+    }
+
+    pub fn sum(column: impl Into<String>) -> Self {
+        Self::new(AggFunc::Sum, column)
+    }
+
+    pub fn count(column: impl Into<String>) -> Self {
+        Self::new(AggFunc::Count, column)
+    }
+
+    /// 统计分组行数，相当于 `COUNT(*)`
+    pub fn count_all() -> Self {
+        Self { func: AggFunc::Count, column: None, alias: None }
+    }
+
+    pub fn count_distinct(column: impl Into<String>) -> Self {
+        Self::new(AggFunc::CountDistinct, column)
+    }
+
+    pub fn min(column: impl Into<String>) -> Self {
+        Self::new(AggFunc::Min, column)
+    }
+
+    pub fn max(column: impl Into<String>) -> Self {
+        Self::new(AggFunc::Max, column)
+    }
+
+    pub fn avg(column: impl Into<String>) -> Self {
+        Self::new(AggFunc::Avg, column)
+    }
+
+    pub fn first(column: impl Into<String>) -> Self {
+        Self::new(AggFunc::First, column)
+    }
+
+    pub fn last(column: impl Into<String>) -> Self {
+        Self::new(AggFunc::Last, column)
+    }
+
+    /// 设置结果列名
+    pub fn alias(mut self, alias: impl Into<String>) -> Self {
+        self.alias = Some(alias.into());
+        self
+    }
+
+    /// 结果列名
+    pub fn output_name(&self) -> String {
+        match (&self.alias, &self.column) {
+            (Some(alias), _) => alias.clone(),
+            (None, Some(column)) => format!("{}_{}", self.func.prefix(), column),
+            (None, None) => self.func.prefix().to_string(),
+        }
+    }
+}
+
+/// 绑定到源列之后的聚合计划
+struct AggPlan {
+    func: AggFunc,
+    source: Option<(usize, ColumnType)>,
+    name: String,
+    output_type: ColumnType,
+}
+
+impl AggPlan {
+    fn new(aggregate: &Aggregate, dataset: &RowDataSet) -> Result<Self, DataSetError> {
+        let name = aggregate.output_name();
+        let invalid = |reason: &str| DataSetError::InvalidAggregate { column: name.clone(), reason: reason.to_string() };
+
+        let source = match &aggregate.column {
+            Some(column) => {
+                let info = dataset.get_column_info(column).ok_or(DataSetError::ColumnNotFound)?;
+                Some((info.index, info.column_type))
+            }
+            None if aggregate.func == AggFunc::Count => None,
+            None => return Err(invalid("a source column is required")),
+        };
+
+        let output_type = match (aggregate.func, source) {
+            (AggFunc::Count | AggFunc::CountDistinct, _) => ColumnType::I64,
+            (AggFunc::Sum | AggFunc::Avg, Some((_, t))) if !t.is_numeric() => {
+                return Err(invalid(&format!("{:?} is not a numeric column type", t)));
+            }
+            (AggFunc::Sum | AggFunc::Avg, Some((_, ColumnType::F32 | ColumnType::F64))) => ColumnType::F64,
+            (AggFunc::Sum | AggFunc::Avg, _) => ColumnType::Decimal,
+            (_, Some((_, t))) => t,
+            (_, None) => unreachable!("only COUNT may omit the source column"),
+        };
+
+        Ok(Self { func: aggregate.func, source, name, output_type })
+    }
+
+    fn accumulator(&self) -> Accumulator {
+        let float = self.output_type == ColumnType::F64 && matches!(self.func, AggFunc::Sum | AggFunc::Avg);
+        match self.func {
+            AggFunc::Sum | AggFunc::Avg if float => Accumulator::Float { sum: 0.0, count: 0 },
+            AggFunc::Sum | AggFunc::Avg => Accumulator::Decimal { sum: Decimal::ZERO, count: 0 },
+            AggFunc::Count => Accumulator::Count(0),
+            AggFunc::CountDistinct => Accumulator::Distinct(HashSet::new()),
+            AggFunc::Min | AggFunc::Max => Accumulator::Extreme(None),
+            AggFunc::First | AggFunc::Last => Accumulator::Pick(None),
+        }
+    }
+}
+
+/// 单个分组中单个聚合的中间状态
+enum Accumulator {
+    Decimal { sum: Decimal, count: i64 },
+    Float { sum: f64, count: i64 },
+    Count(i64),
+    Distinct(HashSet<String>),
+    Extreme(Option<CellValue>),
+    Pick(Option<CellValue>),
+}
+
+impl Accumulator {
+    fn update(&mut self, plan: &AggPlan, values: &[CellValue]) -> Result<(), DataSetError> {
+        let Some((index, column_type)) = plan.source else {
+            if let Accumulator::Count(count) = self {
+                *count += 1;
+            }
+            return Ok(());
+        };
+        let value = values.get(index).unwrap_or(&CellValue::Null);
+        let overflow = || DataSetError::InvalidAggregate {
+            column: plan.name.clone(),
+            reason: format!("cannot add {} without overflow", value),
+        };
+
+        match self {
+            Accumulator::Pick(current) => {
+                if plan.func == AggFunc::Last || current.is_none() {
+                    *current = Some(value.clone());
+                }
+                return Ok(());
+            }
+            _ if value.is_null() => return Ok(()),
+            Accumulator::Decimal { sum, count } => {
+                *sum = to_decimal(value).and_then(|v| sum.checked_add(v)).ok_or_else(overflow)?;
+                *count += 1;
+            }
+            Accumulator::Float { sum, count } => {
+                *sum += value.as_f64().or_else(|| value.as_str().and_then(|s| s.parse().ok())).ok_or_else(overflow)?;
+                *count += 1;
+            }
+            Accumulator::Count(count) => *count += 1,
+            Accumulator::Distinct(seen) => {
+                seen.insert(value.to_string());
+            }
+            Accumulator::Extreme(current) => {
+                let wanted = if plan.func == AggFunc::Min { Ordering::Less } else { Ordering::Greater };
+                let replace = match current {
+                    None => true,
+                    Some(current) => compare_values(value, current, Some(column_type)) == Some(wanted),
+                };
+                if replace {
+                    *current = Some(value.clone());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self, plan: &AggPlan) -> CellValue {
+        match self {
+            Accumulator::Decimal { count: 0, .. } | Accumulator::Float { count: 0, .. } => CellValue::Null,
+            Accumulator::Decimal { sum, count } => match plan.func {
+                AggFunc::Avg => decimal_to_cell(sum / Decimal::from(count)),
+                _ => decimal_to_cell(sum),
+            },
+            Accumulator::Float { sum, count } => {
+                let result = if plan.func == AggFunc::Avg { sum / count as f64 } else { sum };
+                serde_json::Number::from_f64(result).map_or(CellValue::Null, CellValue::Number)
+            }
+            Accumulator::Count(count) => CellValue::from(count),
+            Accumulator::Distinct(seen) => CellValue::from(seen.len() as i64),
+            Accumulator::Extreme(value) | Accumulator::Pick(value) => value.unwrap_or(CellValue::Null),
+        }
+    }
+}
+
+impl RowDataSet {
+    /// 按键列分组并计算聚合值
+    ///
+    /// # 参数
+    ///
+    /// * `keys` - 分组键列名，可以为空（此时整个数据集为一个分组）
+    /// * `aggregates` - 聚合列定义
+    ///
+    /// # 返回值
+    ///
+    /// 返回 `Result<RowDataSet, DataSetError>`：
+    /// - `Ok(dataset)` - 新数据集，列依次为键列（类型与源列相同）和聚合列，
+    ///   每个分组一行，分组按首次出现的顺序排列
+    /// - `Err(DataSetError::ColumnNotFound)` - 键列或聚合列不存在
+    /// - `Err(DataSetError::InvalidAggregate)` - 对非数值列求和/求平均、
+    ///   结果列名重复或求和溢出
+    ///
+    /// # 行为说明
+    ///
+    /// - 键值相同（包括都为 `NULL`）的行归入同一分组
+    /// - `keys` 为空且数据集没有行时，仍返回一行聚合结果（例如 `COUNT` 为 0）
+    /// - 子数据集不参与聚合，也不会出现在结果中
+    pub fn group_by(&self, keys: &[&str], aggregates: &[Aggregate]) -> Result<RowDataSet, DataSetError> {
+        let key_columns = keys
+            .iter()
+            .map(|key| self.get_column_info(key).map(|info| (*key, info.index, info.column_type)))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(DataSetError::ColumnNotFound)?;
+        let plans = aggregates
+            .iter()
+            .map(|aggregate| AggPlan::new(aggregate, self))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut result = RowDataSet::new(self.dataset_id.clone());
+        let columns = key_columns
+            .iter()
+            .map(|(name, _, column_type)| (name.to_string(), *column_type))
+            .chain(plans.iter().map(|plan| (plan.name.clone(), plan.output_type)));
+        for (name, column_type) in columns {
+            if result.get_column_info(&name).is_some() {
+                return Err(DataSetError::InvalidAggregate { column: name, reason: "duplicate output column".to_string() });
+            }
+            result.add_column(name, column_type)?;
+        }
+
+        let mut group_index: HashMap<String, usize> = HashMap::new();
+        let mut groups: Vec<(Vec<CellValue>, Vec<Accumulator>)> = Vec::new();
+        if key_columns.is_empty() {
+            groups.push((Vec::new(), plans.iter().map(AggPlan::accumulator).collect()));
+        }
+
+        for row in &self.rows {
+            let values = row.values();
+            let key: Vec<CellValue> = key_columns
+                .iter()
+                .map(|(_, index, _)| values.get(*index).cloned().unwrap_or(CellValue::Null))
+                .collect();
+            let slot = if key_columns.is_empty() {
+                0
+            } else {
+                let hash_key = CellValue::from(key.clone()).to_string();
+                match group_index.get(&hash_key) {
+                    Some(slot) => *slot,
+                    None => {
+                        group_index.insert(hash_key, groups.len());
+                        groups.push((key, plans.iter().map(AggPlan::accumulator).collect()));
+                        groups.len() - 1
+                    }
+                }
+            };
+            for (accumulator, plan) in groups[slot].1.iter_mut().zip(&plans) {
+                accumulator.update(plan, values)?;
+            }
+        }
+
+        for (mut key, accumulators) in groups {
+            key.extend(accumulators.into_iter().zip(&plans).map(|(acc, plan)| acc.finish(plan)));
+            result.add_row(key)?;
+        }
+        Ok(result)
+    }
+
+    /// 对整个数据集计算聚合值，相当于不带键列的 [`RowDataSet::group_by`]
+    pub fn aggregate(&self, aggregates: &[Aggregate]) -> Result<RowDataSet, DataSetError> {
+        self.group_by(&[], aggregates)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn create_fact_dataset() -> RowDataSet {
+        let mut dataset = RowDataSet::new("facts".to_string());
+        dataset.add_column("DW".to_string(), ColumnType::String).unwrap();
+        dataset.add_column("KM".to_string(), ColumnType::String).unwrap();
+        dataset.add_column("JE".to_string(), ColumnType::Decimal).unwrap();
+        dataset.add_column("SL".to_string(), ColumnType::I32).unwrap();
+        dataset.add_column("ZB".to_string(), ColumnType::F64).unwrap();
+        dataset.add_column("RQ".to_string(), ColumnType::Date).unwrap();
+        for (dw, km, je, sl, zb, rq) in [
+            ("01", "1001", json!(0.1), json!(1), json!(0.5), json!("2024-03-01")),
+            ("01", "1002", json!(0.2), json!(2), json!(1.5), json!("2024-01-15")),
+            ("02", "1001", json!("12345678901234567890.12"), CellValue::Null, json!(2), json!("2024-02-01")),
+            ("01", "1001", CellValue::Null, json!(3), CellValue::Null, CellValue::Null),
+            ("02", "1001", json!("0.01"), json!(4), json!(1), json!("2023-12-31")),
+        ] {
+            dataset.add_row(vec![json!(dw), json!(km), je, sl, zb, rq]).unwrap();
+        }
+        dataset
+    }
+
+    #[test]
+    fn test_group_by_exact_decimal_sum() {
+        let dataset = create_fact_dataset();
+        let result = dataset
+            .group_by(&["DW"], &[Aggregate::sum("JE"), Aggregate::avg("JE"), Aggregate::sum("SL"), Aggregate::sum("ZB")])
+            .unwrap();
+
+        assert_eq!(result.row_count(), 2);
+        assert_eq!(result.get_column_info("SUM_JE").unwrap().column_type, ColumnType::Decimal);
+        assert_eq!(result.get_column_info("SUM_ZB").unwrap().column_type, ColumnType::F64);
+        assert_eq!(result.get_cell(0, "SUM_JE").unwrap(), &json!(0.3));
+        assert_eq!(result.get_cell(0, "AVG_JE").unwrap(), &json!(0.15));
+        assert_eq!(result.get_cell(0, "SUM_SL").unwrap(), &json!(6));
+        assert_eq!(result.get_cell(0, "SUM_ZB").unwrap(), &json!(2.0));
+        assert_eq!(result.get_cell(1, "SUM_JE").unwrap(), &json!("12345678901234567890.13"));
+    }
+
+    #[test]
+    fn test_group_by_counts_and_extremes() {
+        let dataset = create_fact_dataset();
+        let result = dataset
+            .group_by(
+                &["DW", "KM"],
+                &[
+                    Aggregate::count_all().alias("N"),
+                    Aggregate::count("JE"),
+                    Aggregate::min("RQ"),
+                    Aggregate::max("RQ"),
+                    Aggregate::first("SL"),
+                    Aggregate::last("JE"),
+                ],
+            )
+            .unwrap();
+
+        assert_eq!(result.row_count(), 3);
+        assert_eq!(result.get_row(0).unwrap().values(), &vec![
+            json!("01"), json!("1001"), json!(2), json!(1), json!("2024-03-01"), json!("2024-03-01"), json!(1), CellValue::Null,
+        ]);
+        assert_eq!(result.get_cell(2, "MIN_RQ").unwrap(), &json!("2023-12-31"));
+        assert_eq!(result.get_cell(2, "MAX_RQ").unwrap(), &json!("2024-02-01"));
+        assert_eq!(result.get_cell(2, "FIRST_SL").unwrap(), &CellValue::Null);
+
+        let distinct = dataset.aggregate(&[Aggregate::count_distinct("KM"), Aggregate::count_distinct("DW")]).unwrap();
+        assert_eq!(distinct.get_row(0).unwrap().values(), &vec![json!(2), json!(2)]);
+    }
+
+    #[test]
+    fn test_aggregate_empty_and_errors() {
+        let mut dataset = create_fact_dataset();
+        dataset.clear();
+        let result = dataset.aggregate(&[Aggregate::count_all(), Aggregate::sum("JE")]).unwrap();
+        assert_eq!(result.get_row(0).unwrap().values(), &vec![json!(0), CellValue::Null]);
+        assert_eq!(dataset.group_by(&["DW"], &[Aggregate::count_all()]).unwrap().row_count(), 0);
+
+        let dataset = create_fact_dataset();
+        assert!(matches!(dataset.group_by(&["NOPE"], &[]), Err(DataSetError::ColumnNotFound)));
+        assert!(matches!(
+            dataset.group_by(&["DW"], &[Aggregate::sum("KM")]),
+            Err(DataSetError::InvalidAggregate { .. })
+        ));
+        assert!(matches!(
+            dataset.group_by(&["DW"], &[Aggregate::max("JE").alias("DW")]),
+            Err(DataSetError::InvalidAggregate { .. })
+        ));
+    }
+}
@@ -0,0 +1,1066 @@
+//! # 二进制归档模块
+//!
+//! 基于 rkyv 把数据集和元数据编码为二进制归档。归档可以在校验后直接访问，
+//! 不需要先反序列化；元数据快照文件可以在服务启动时加载，见 [`crate::model::meta::snapshot`]。
+//! 大文件可以内存映射（[`ArchiveFile::open_mapped`]），调用方需要保证映射期间文件不被修改。
+//!
+//! 归档文件由 16 字节的文件头和 rkyv 数据组成，所有整数均为小端序：
+//!
+//! | 偏移 | 长度 | 内容 |
+//! |------|------|------|
+//! | 0 | 4 | 魔数 `CMXA` |
+//! | 4 | 2 | 格式版本，见 [`ARCHIVE_VERSION`] |
+//! | 6 | 1 | 内容类型，见 [`ArchiveKind`] |
+//! | 7 | 1 | 保留，为 0 |
+//! | 8 | 8 | rkyv 数据长度 |
+//!
+//! 文件头长度是 16 的倍数，内存映射的起始地址按页对齐，因此 rkyv 数据也满足对齐要求。
+//!
+//! 支持归档的类型实现了 [`Archivable`]：
+//!
+//! | 类型 | 归档表示 | 校验后访问 |
+//! |------|----------|------------|
+//! | `DataSet` | [`DataSetRepr`] | [`ArchivedDataSet`] |
+//! | `RowDataSet` | [`RowDataSetRepr`] | [`ArchivedRowDataSet`] |
+//! | `TableSchema` | [`Cell`] | [`ArchivedCell`] |
+//! | `DCTMeta`、`FCTMeta` | [`Cell`] | [`ArchivedCell`]，见 [`crate::model::meta::snapshot`] |
+//! | `DMEMeta`、元数据包的种子数据 | [`Cell`] | [`ArchivedCell`]，见 [`crate::model::meta::package`] |
+//!
+//! 元数据的字段以 `SYS_*` 字段枚举为键，归档时按 JSON 结构保存为 [`Cell`] 树，
+//! 与 JSON 序列化的结果一一对应。[`ArchivedCell`] 可以按字段读取而不反序列化，但加载元数据时
+//! 要先把 [`Cell`] 树转换为 JSON 值再反序列化为结构体，开销与从 JSON 值反序列化相同，只省去了文本解析。
+//!
+//! ## 示例
+//!
+//! ```rust
+//! use cmx_core::model::data::dataset::{rds::RowDataSet, ColumnType};
+//! use cmx_core::model::data::dataset::archive::{self, ArchiveFile};
+//! use serde_json::json;
+//!
+//! let mut dataset = RowDataSet::new("users".to_string());
+//! dataset.add_column("id".to_string(), ColumnType::I32).unwrap();
+//! dataset.add_column("name".to_string(), ColumnType::String).unwrap();
+//! dataset.add_row(vec![json!(1), json!("Alice")]).unwrap();
+//!
+//! let bytes = archive::encode(&dataset).unwrap();
+//! let file = ArchiveFile::from_bytes(&bytes).unwrap();
+//!
+//! // 校验后直接读取归档，不反序列化
+//! let archived = file.access::<RowDataSet>().unwrap();
+//! assert_eq!(archived.row_count(), 1);
+//! assert_eq!(archived.get(0, "name").and_then(|cell| cell.as_str()), Some("Alice"));
+//!
+//! // 需要可修改的数据集时再反序列化
+//! let restored: RowDataSet = file.load().unwrap();
+//! assert_eq!(restored.get_cell(0, "id").unwrap(), &json!(1));
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+
+use memmap2::Mmap;
+use rkyv::api::high::{HighDeserializer, HighSerializer, HighValidator};
+use rkyv::bytecheck::CheckBytes;
+use rkyv::rancor::Error as RkyvError;
+use rkyv::ser::allocator::ArenaHandle;
+use rkyv::util::AlignedVec;
+use rkyv::{Archive, Archived, Portable};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use thiserror::Error;
+
+use super::changes::RowState;
+use super::rds::{ColumnInfo, RowData, RowDataSet};
+use super::row::RowSet;
+use super::{ColumnType, DataSet, TableSchema};
+use crate::model::data::cell::CellValue;
+
+/// 归档文件魔数
+pub const ARCHIVE_MAGIC: [u8; 4] = *b"CMXA";
+
+/// 当前归档格式版本
+///
+/// 归档表示的结构发生不兼容的变化时递增，旧版本的快照文件会被拒绝，需要重新生成。
+pub const ARCHIVE_VERSION: u16 = 1;
+
+/// 归档文件头长度
+pub const HEADER_LEN: usize = 16;
+
+/// 归档内容类型，记录在文件头中
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ArchiveKind {
+    DataSet = 1,
+    RowDataSet = 2,
+    TableSchema = 3,
+    DCTMeta = 4,
+    FCTMeta = 5,
+    MetaSnapshot = 6,
+    DMEMeta = 7,
+    SeedRows = 8,
+}
+
+impl ArchiveKind {
+    fn from_u8(value: u8) -> Option<Self> {
+        Some(match value {
+            1 => Self::DataSet,
+            2 => Self::RowDataSet,
+            3 => Self::TableSchema,
+            4 => Self::DCTMeta,
+            5 => Self::FCTMeta,
+            6 => Self::MetaSnapshot,
+            7 => Self::DMEMeta,
+            8 => Self::SeedRows,
+            _ => return None,
+        })
+    }
+}
+
+/// 归档错误
+#[derive(Debug, Error)]
+pub enum ArchiveError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Not a cmx archive")]
+    BadMagic,
+    #[error("Unsupported archive version {found}, expected {expected}")]
+    UnsupportedVersion { found: u16, expected: u16 },
+    #[error("Unknown archive kind {0}")]
+    UnknownKind(u8),
+    #[error("Archive kind mismatch: expected {expected:?}, found {found:?}")]
+    KindMismatch { expected: ArchiveKind, found: ArchiveKind },
+    #[error("Archive length mismatch: header declares {expected} bytes, found {found}")]
+    LengthMismatch { expected: u64, found: u64 },
+    #[error("Invalid archive data: {0}")]
+    Invalid(#[from] RkyvError),
+    #[error("Metadata conversion failed: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// 归档文件头
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArchiveHeader {
+    /// 格式版本
+    pub version: u16,
+    /// 内容类型
+    pub kind: ArchiveKind,
+    /// rkyv 数据长度
+    pub payload_len: u64,
+}
+
+impl ArchiveHeader {
+    fn new(kind: ArchiveKind, payload_len: usize) -> Self {
+        Self {
+            version: ARCHIVE_VERSION,
+            kind,
+            payload_len: payload_len as u64,
+        }
+    }
+
+    fn to_bytes(self) -> [u8; HEADER_LEN] {
+        let mut bytes = [0u8; HEADER_LEN];
+        bytes[0..4].copy_from_slice(&ARCHIVE_MAGIC);
+        bytes[4..6].copy_from_slice(&self.version.to_le_bytes());
+        bytes[6] = self.kind as u8;
+        bytes[8..16].copy_from_slice(&self.payload_len.to_le_bytes());
+        bytes
+    }
+
+    /// 解析并校验文件头，`bytes` 为整个归档
+    ///
+    /// 魔数、版本、内容类型和数据长度都必须与当前格式一致。
+    pub fn parse(bytes: &[u8]) -> Result<Self, ArchiveError> {
+        if bytes.len() < HEADER_LEN || bytes[0..4] != ARCHIVE_MAGIC {
+            return Err(ArchiveError::BadMagic);
+        }
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        if version != ARCHIVE_VERSION {
+            return Err(ArchiveError::UnsupportedVersion {
+                found: version,
+                expected: ARCHIVE_VERSION,
+            });
+        }
+        let kind = ArchiveKind::from_u8(bytes[6]).ok_or(ArchiveError::UnknownKind(bytes[6]))?;
+        let mut len = [0u8; 8];
+        len.copy_from_slice(&bytes[8..16]);
+        let payload_len = u64::from_le_bytes(len);
+        let found = (bytes.len() - HEADER_LEN) as u64;
+        if payload_len != found {
+            return Err(ArchiveError::LengthMismatch {
+                expected: payload_len,
+                found,
+            });
+        }
+        Ok(Self {
+            version,
+            kind,
+            payload_len,
+        })
+    }
+}
+
+/// 可以写入归档的类型
+///
+/// 类型本身通常带有不参与序列化的运行时状态，归档时先转换为可归档的表示 `Repr`，
+/// 读取时再从表示恢复。[`ArchiveFile::access`] 得到的是 `Repr` 的归档类型。
+pub trait Archivable: Sized {
+    /// 文件头中记录的内容类型
+    const KIND: ArchiveKind;
+
+    /// 可归档的表示
+    type Repr: Archive + for<'a> rkyv::Serialize<HighSerializer<AlignedVec, ArenaHandle<'a>, RkyvError>>;
+
+    /// 转换为可归档的表示
+    fn to_repr(&self) -> Result<Self::Repr, ArchiveError>;
+
+    /// 从可归档的表示恢复
+    fn from_repr(repr: Self::Repr) -> Result<Self, ArchiveError>;
+}
+
+/// 编码为带文件头的归档
+///
+/// 返回的缓冲区按 16 字节对齐，可以直接交给 [`ArchiveFile::from_bytes`] 或写入文件。
+pub fn encode<T: Archivable>(value: &T) -> Result<AlignedVec, ArchiveError> {
+    let payload = rkyv::to_bytes::<RkyvError>(&value.to_repr()?)?;
+    let header = ArchiveHeader::new(T::KIND, payload.len());
+    let mut bytes = AlignedVec::with_capacity(HEADER_LEN + payload.len());
+    bytes.extend_from_slice(&header.to_bytes());
+    bytes.extend_from_slice(&payload);
+    Ok(bytes)
+}
+
+/// 从归档解码
+///
+/// 等价于 `ArchiveFile::from_bytes(bytes)?.load()`。
+pub fn decode<T>(bytes: &[u8]) -> Result<T, ArchiveError>
+where
+    T: Archivable,
+    Archived<T::Repr>: Portable
+        + for<'a> CheckBytes<HighValidator<'a, RkyvError>>
+        + rkyv::Deserialize<T::Repr, HighDeserializer<RkyvError>>,
+{
+    ArchiveFile::from_bytes(bytes)?.load()
+}
+
+/// 把归档写入文件
+///
+/// 先写入同目录下的临时文件再重命名，已经内存映射旧文件的进程不会读到写了一半的内容。
+pub fn write_file<T: Archivable>(value: &T, path: impl AsRef<Path>) -> Result<(), ArchiveError> {
+    let path = path.as_ref();
+    let bytes = encode(value)?;
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    {
+        let mut file = File::create(&tmp)?;
+        file.write_all(&bytes)?;
+        file.sync_all()?;
+    }
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+enum Storage {
+    Mapped(Mmap),
+    Owned(AlignedVec),
+}
+
+/// 已校验文件头的归档
+///
+/// 通过 [`ArchiveFile::open`] 读取文件、[`ArchiveFile::from_bytes`] 复制内存中的归档到对齐的缓冲区，
+/// 或通过 [`ArchiveFile::open_mapped`] 内存映射文件。
+/// [`ArchiveFile::access`] 校验 rkyv 数据后返回归档类型的引用，不做反序列化；
+/// [`ArchiveFile::load`] 反序列化为原始类型。
+pub struct ArchiveFile {
+    header: ArchiveHeader,
+    storage: Storage,
+}
+
+impl ArchiveFile {
+    /// 把归档文件读入对齐的缓冲区并校验文件头
+    ///
+    /// 读取完成后与文件无关，文件随后被替换或删除不影响已打开的归档。
+    /// 文件较大且能保证不被修改时可以使用 [`ArchiveFile::open_mapped`] 避免复制。
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ArchiveError> {
+        let mut file = File::open(path)?;
+        let mut buffer = AlignedVec::with_capacity(file.metadata()?.len() as usize);
+        buffer.extend_from_reader(&mut file)?;
+        let header = ArchiveHeader::parse(&buffer)?;
+        Ok(Self {
+            header,
+            storage: Storage::Owned(buffer),
+        })
+    }
+
+    /// 内存映射归档文件并校验文件头
+    ///
+    /// # Safety
+    ///
+    /// 映射期间（直到返回的 `ArchiveFile` 被释放）文件不能被截断或原地修改，包括其他进程的修改：
+    /// 原地修改会让已校验的归档读到不一致的数据，截断会在访问时触发 `SIGBUS`。
+    /// 本函数不对文件加锁，调用方需要保证：
+    ///
+    /// - 文件只通过 [`write_file`] 以写临时文件再重命名的方式整体替换，已有的映射继续指向旧文件
+    /// - 没有其他进程以写方式打开或截断该文件
+    pub unsafe fn open_mapped(path: impl AsRef<Path>) -> Result<Self, ArchiveError> {
+        let file = File::open(path)?;
+        if file.metadata()?.len() < HEADER_LEN as u64 {
+            return Err(ArchiveError::BadMagic);
+        }
+        // SAFETY: 调用方保证映射期间文件不会被截断或原地修改，见函数文档
+        let map = unsafe { Mmap::map(&file)? };
+        let header = ArchiveHeader::parse(&map)?;
+        Ok(Self {
+            header,
+            storage: Storage::Mapped(map),
+        })
+    }
+
+    /// 从内存中的归档创建，数据会被复制到对齐的缓冲区
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ArchiveError> {
+        let header = ArchiveHeader::parse(bytes)?;
+        let mut buffer = AlignedVec::with_capacity(bytes.len());
+        buffer.extend_from_slice(bytes);
+        Ok(Self {
+            header,
+            storage: Storage::Owned(buffer),
+        })
+    }
+
+    /// 文件头
+    pub fn header(&self) -> &ArchiveHeader {
+        &self.header
+    }
+
+    /// 内容类型
+    pub fn kind(&self) -> ArchiveKind {
+        self.header.kind
+    }
+
+    /// 是否为内存映射的文件
+    pub fn is_mapped(&self) -> bool {
+        matches!(self.storage, Storage::Mapped(_))
+    }
+
+    /// 文件头之后的 rkyv 数据
+    pub fn payload(&self) -> &[u8] {
+        let bytes: &[u8] = match &self.storage {
+            Storage::Mapped(map) => map,
+            Storage::Owned(buffer) => buffer,
+        };
+        &bytes[HEADER_LEN..]
+    }
+
+    /// 校验后直接访问归档，不反序列化
+    ///
+    /// # 返回值
+    ///
+    /// 内容类型与 `T` 不一致时返回 `ArchiveError::KindMismatch`，
+    /// 数据不完整或被篡改时返回 `ArchiveError::Invalid`。
+    pub fn access<T>(&self) -> Result<&Archived<T::Repr>, ArchiveError>
+    where
+        T: Archivable,
+        Archived<T::Repr>: Portable + for<'a> CheckBytes<HighValidator<'a, RkyvError>>,
+    {
+        if self.header.kind != T::KIND {
+            return Err(ArchiveError::KindMismatch {
+                expected: T::KIND,
+                found: self.header.kind,
+            });
+        }
+        Ok(rkyv::access::<Archived<T::Repr>, RkyvError>(self.payload())?)
+    }
+
+    /// 校验并反序列化归档
+    pub fn load<T>(&self) -> Result<T, ArchiveError>
+    where
+        T: Archivable,
+        Archived<T::Repr>: Portable
+            + for<'a> CheckBytes<HighValidator<'a, RkyvError>>
+            + rkyv::Deserialize<T::Repr, HighDeserializer<RkyvError>>,
+    {
+        let archived = self.access::<T>()?;
+        let repr = rkyv::deserialize::<T::Repr, RkyvError>(archived)?;
+        T::from_repr(repr)
+    }
+}
+
+/// 可归档的单元格值，与 `CellValue` 一一对应
+///
+/// 数字按能否无损表示依次保存为 `Int`、`UInt` 或 `Float`。
+#[derive(Debug, Clone, PartialEq, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+#[rkyv(serialize_bounds(
+    __S: rkyv::ser::Writer + rkyv::ser::Allocator,
+    __S::Error: rkyv::rancor::Source,
+))]
+#[rkyv(deserialize_bounds(__D::Error: rkyv::rancor::Source))]
+#[rkyv(bytecheck(bounds(
+    __C: rkyv::validation::ArchiveContext,
+    __C::Error: rkyv::rancor::Source,
+)))]
+#[rkyv(derive(Debug))]
+pub enum Cell {
+    Null,
+    Bool(bool),
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    String(String),
+    Array(#[rkyv(omit_bounds)] Vec<Cell>),
+    Object(#[rkyv(omit_bounds)] BTreeMap<String, Cell>),
+}
+
+impl From<&CellValue> for Cell {
+    fn from(value: &CellValue) -> Self {
+        match value {
+            CellValue::Null => Cell::Null,
+            CellValue::Bool(b) => Cell::Bool(*b),
+            CellValue::Number(n) => {
+                if let Some(i) = n.as_i64() {
+                    Cell::Int(i)
+                } else if let Some(u) = n.as_u64() {
+                    Cell::UInt(u)
+                } else {
+                    Cell::Float(n.as_f64().unwrap_or_default())
+                }
+            }
+            CellValue::String(s) => Cell::String(s.clone()),
+            CellValue::Array(items) => Cell::Array(items.iter().map(Cell::from).collect()),
+            CellValue::Object(map) => Cell::Object(
+                map.iter()
+                    .map(|(key, value)| (key.clone(), Cell::from(value)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl From<Cell> for CellValue {
+    fn from(cell: Cell) -> Self {
+        match cell {
+            Cell::Null => CellValue::Null,
+            Cell::Bool(b) => CellValue::Bool(b),
+            Cell::Int(i) => CellValue::from(i),
+            Cell::UInt(u) => CellValue::from(u),
+            Cell::Float(f) => serde_json::Number::from_f64(f).map_or(CellValue::Null, CellValue::Number),
+            Cell::String(s) => CellValue::String(s),
+            Cell::Array(items) => CellValue::Array(items.into_iter().map(CellValue::from).collect()),
+            Cell::Object(map) => CellValue::Object(
+                map.into_iter()
+                    .map(|(key, value)| (key, CellValue::from(value)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl ArchivedCell {
+    /// 转换为 `CellValue`
+    pub fn to_value(&self) -> CellValue {
+        match self {
+            ArchivedCell::Null => CellValue::Null,
+            ArchivedCell::Bool(b) => CellValue::Bool(*b),
+            ArchivedCell::Int(i) => CellValue::from(i.to_native()),
+            ArchivedCell::UInt(u) => CellValue::from(u.to_native()),
+            ArchivedCell::Float(f) => serde_json::Number::from_f64(f.to_native())
+                .map_or(CellValue::Null, CellValue::Number),
+            ArchivedCell::String(s) => CellValue::String(s.as_str().to_string()),
+            ArchivedCell::Array(items) => CellValue::Array(items.iter().map(ArchivedCell::to_value).collect()),
+            ArchivedCell::Object(map) => CellValue::Object(
+                map.iter()
+                    .map(|(key, value)| (key.as_str().to_string(), value.to_value()))
+                    .collect(),
+            ),
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, ArchivedCell::Null)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            ArchivedCell::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// 整数值，超出 `i64` 范围的无符号数返回 `None`
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            ArchivedCell::Int(i) => Some(i.to_native()),
+            ArchivedCell::UInt(u) => i64::try_from(u.to_native()).ok(),
+            _ => None,
+        }
+    }
+
+    /// 数值，整数会被转换为浮点数
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            ArchivedCell::Int(i) => Some(i.to_native() as f64),
+            ArchivedCell::UInt(u) => Some(u.to_native() as f64),
+            ArchivedCell::Float(f) => Some(f.to_native()),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            ArchivedCell::String(s) => Some(s.as_str()),
+            _ => None,
+        }
+    }
+
+    /// 数组元素
+    pub fn as_array(&self) -> Option<&[ArchivedCell]> {
+        match self {
+            ArchivedCell::Array(items) => Some(items.as_slice()),
+            _ => None,
+        }
+    }
+
+    /// 按键读取对象成员
+    pub fn get(&self, key: &str) -> Option<&ArchivedCell> {
+        match self {
+            ArchivedCell::Object(map) => map.get(key),
+            _ => None,
+        }
+    }
+}
+
+/// 通过 JSON 结构把可序列化的值转换为 [`Cell`]，用于以字段枚举为键的元数据
+pub(crate) fn to_cell<T: Serialize>(value: &T) -> Result<Cell, ArchiveError> {
+    Ok(Cell::from(&serde_json::to_value(value)?))
+}
+
+/// [`to_cell`] 的逆操作
+pub(crate) fn from_cell<T: DeserializeOwned>(cell: Cell) -> Result<T, ArchiveError> {
+    Ok(serde_json::from_value(CellValue::from(cell))?)
+}
+
+fn to_cells(values: &[CellValue]) -> Vec<Cell> {
+    values.iter().map(Cell::from).collect()
+}
+
+fn from_cells(cells: Vec<Cell>) -> Vec<CellValue> {
+    cells.into_iter().map(CellValue::from).collect()
+}
+
+/// `DataSet` 的可归档表示
+#[derive(Debug, Clone, PartialEq, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+#[rkyv(archived = ArchivedDataSet)]
+#[rkyv(serialize_bounds(
+    __S: rkyv::ser::Writer + rkyv::ser::Allocator,
+    __S::Error: rkyv::rancor::Source,
+))]
+#[rkyv(deserialize_bounds(__D::Error: rkyv::rancor::Source))]
+#[rkyv(bytecheck(bounds(
+    __C: rkyv::validation::ArchiveContext,
+    __C::Error: rkyv::rancor::Source,
+)))]
+pub struct DataSetRepr {
+    /// 未初始化的数据集为 `None`
+    #[rkyv(omit_bounds)]
+    pub rows: Option<Vec<RowSetRepr>>,
+}
+
+/// `RowSet` 的可归档表示
+#[derive(Debug, Clone, PartialEq, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+#[rkyv(archived = ArchivedRowSet)]
+#[rkyv(serialize_bounds(
+    __S: rkyv::ser::Writer + rkyv::ser::Allocator,
+    __S::Error: rkyv::rancor::Source,
+))]
+#[rkyv(deserialize_bounds(__D::Error: rkyv::rancor::Source))]
+#[rkyv(bytecheck(bounds(
+    __C: rkyv::validation::ArchiveContext,
+    __C::Error: rkyv::rancor::Source,
+)))]
+pub struct RowSetRepr {
+    pub values: Vec<Cell>,
+    #[rkyv(omit_bounds)]
+    pub children: Option<BTreeMap<String, DataSetRepr>>,
+}
+
+impl From<&DataSet> for DataSetRepr {
+    fn from(dataset: &DataSet) -> Self {
+        Self {
+            rows: dataset
+                .rows
+                .as_ref()
+                .map(|rows| rows.iter().map(RowSetRepr::from).collect()),
+        }
+    }
+}
+
+impl From<&RowSet> for RowSetRepr {
+    fn from(row: &RowSet) -> Self {
+        Self {
+            values: to_cells(row.values()),
+            children: row.children.as_ref().map(|children| {
+                children
+                    .iter()
+                    .map(|(id, child)| (id.clone(), DataSetRepr::from(child)))
+                    .collect()
+            }),
+        }
+    }
+}
+
+impl From<DataSetRepr> for DataSet {
+    fn from(repr: DataSetRepr) -> Self {
+        Self {
+            rows: repr
+                .rows
+                .map(|rows| rows.into_iter().map(RowSet::from).collect()),
+        }
+    }
+}
+
+impl From<RowSetRepr> for RowSet {
+    fn from(repr: RowSetRepr) -> Self {
+        let mut row = RowSet::from_values(from_cells(repr.values));
+        row.children = repr.children.map(|children| {
+            children
+                .into_iter()
+                .map(|(id, child)| (id, DataSet::from(child)))
+                .collect()
+        });
+        row
+    }
+}
+
+impl Archivable for DataSet {
+    const KIND: ArchiveKind = ArchiveKind::DataSet;
+    type Repr = DataSetRepr;
+
+    fn to_repr(&self) -> Result<Self::Repr, ArchiveError> {
+        Ok(DataSetRepr::from(self))
+    }
+
+    fn from_repr(repr: Self::Repr) -> Result<Self, ArchiveError> {
+        Ok(DataSet::from(repr))
+    }
+}
+
+impl ArchivedDataSet {
+    /// 行数，未初始化的数据集为 0
+    pub fn row_count(&self) -> usize {
+        self.rows.as_ref().map_or(0, |rows| rows.len())
+    }
+
+    pub fn row(&self, index: usize) -> Option<&ArchivedRowSet> {
+        self.rows.as_ref()?.get(index)
+    }
+
+    /// 按行号和列序号读取单元格
+    pub fn get(&self, row: usize, column: usize) -> Option<&ArchivedCell> {
+        self.row(row)?.values.get(column)
+    }
+}
+
+impl ArchivedRowSet {
+    pub fn values(&self) -> &[ArchivedCell] {
+        &self.values
+    }
+
+    pub fn child(&self, id: &str) -> Option<&ArchivedDataSet> {
+        self.children.as_ref()?.get(id)
+    }
+}
+
+/// `RowDataSet` 的可归档表示
+///
+/// 索引、公式、校验器和行工厂与 JSON 序列化一样不参与归档，恢复后需要重新设置。
+#[derive(Debug, Clone, PartialEq, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+#[rkyv(archived = ArchivedRowDataSet)]
+#[rkyv(serialize_bounds(
+    __S: rkyv::ser::Writer + rkyv::ser::Allocator,
+    __S::Error: rkyv::rancor::Source,
+))]
+#[rkyv(deserialize_bounds(__D::Error: rkyv::rancor::Source))]
+#[rkyv(bytecheck(bounds(
+    __C: rkyv::validation::ArchiveContext,
+    __C::Error: rkyv::rancor::Source,
+)))]
+pub struct RowDataSetRepr {
+    pub dataset_id: String,
+    /// 按列序号排列的列定义
+    pub columns: Vec<ColumnRepr>,
+    #[rkyv(omit_bounds)]
+    pub rows: Vec<RowDataRepr>,
+    #[rkyv(omit_bounds)]
+    pub deleted: Vec<RowDataRepr>,
+    pub tracking: bool,
+}
+
+/// 列定义的可归档表示
+#[derive(Debug, Clone, PartialEq, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+#[rkyv(archived = ArchivedColumn)]
+pub struct ColumnRepr {
+    pub name: String,
+    pub index: u32,
+    pub column_type: ColumnType,
+    pub multilingual: bool,
+}
+
+/// `RowData` 的可归档表示
+#[derive(Debug, Clone, PartialEq, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+#[rkyv(archived = ArchivedRowData)]
+#[rkyv(serialize_bounds(
+    __S: rkyv::ser::Writer + rkyv::ser::Allocator,
+    __S::Error: rkyv::rancor::Source,
+))]
+#[rkyv(deserialize_bounds(__D::Error: rkyv::rancor::Source))]
+#[rkyv(bytecheck(bounds(
+    __C: rkyv::validation::ArchiveContext,
+    __C::Error: rkyv::rancor::Source,
+)))]
+pub struct RowDataRepr {
+    pub values: Vec<Cell>,
+    #[rkyv(omit_bounds)]
+    pub children: Option<BTreeMap<String, RowDataSetRepr>>,
+    pub state: RowState,
+    pub original: Option<Vec<Cell>>,
+}
+
+impl From<&RowDataSet> for RowDataSetRepr {
+    fn from(dataset: &RowDataSet) -> Self {
+        let mut columns: Vec<ColumnRepr> = dataset
+            .schema
+            .iter()
+            .map(|(name, info)| ColumnRepr {
+                name: name.clone(),
+                index: info.index as u32,
+                column_type: info.column_type,
+                multilingual: info.multilingual,
+            })
+            .collect();
+        columns.sort_by_key(|column| column.index);
+        Self {
+            dataset_id: dataset.dataset_id.clone(),
+            columns,
+            rows: dataset.rows.iter().map(RowDataRepr::from).collect(),
+            deleted: dataset.deleted.iter().map(RowDataRepr::from).collect(),
+            tracking: dataset.tracking,
+        }
+    }
+}
+
+impl From<&RowData> for RowDataRepr {
+    fn from(row: &RowData) -> Self {
+        Self {
+            values: to_cells(&row.values),
+            children: row.children.as_ref().map(|children| {
+                children
+                    .iter()
+                    .map(|(name, child)| (name.clone(), RowDataSetRepr::from(child)))
+                    .collect()
+            }),
+            state: row.state,
+            original: row.original.as_deref().map(to_cells),
+        }
+    }
+}
+
+impl From<RowDataSetRepr> for RowDataSet {
+    fn from(repr: RowDataSetRepr) -> Self {
+        let mut dataset = RowDataSet::new(repr.dataset_id);
+        dataset.schema = repr
+            .columns
+            .into_iter()
+            .map(|column| {
+                let info = ColumnInfo {
+                    index: column.index as usize,
+                    column_type: column.column_type,
+                    multilingual: column.multilingual,
+                };
+                (column.name, info)
+            })
+            .collect::<HashMap<_, _>>();
+        dataset.rows = repr.rows.into_iter().map(RowData::from).collect();
+        dataset.deleted = repr.deleted.into_iter().map(RowData::from).collect();
+        dataset.tracking = repr.tracking;
+        dataset
+    }
+}
+
+impl From<RowDataRepr> for RowData {
+    fn from(repr: RowDataRepr) -> Self {
+        let mut row = RowData::new(from_cells(repr.values));
+        row.children = repr.children.map(|children| {
+            children
+                .into_iter()
+                .map(|(name, child)| (name, RowDataSet::from(child)))
+                .collect()
+        });
+        row.state = repr.state;
+        row.original = repr.original.map(from_cells);
+        row
+    }
+}
+
+impl Archivable for RowDataSet {
+    const KIND: ArchiveKind = ArchiveKind::RowDataSet;
+    type Repr = RowDataSetRepr;
+
+    fn to_repr(&self) -> Result<Self::Repr, ArchiveError> {
+        Ok(RowDataSetRepr::from(self))
+    }
+
+    fn from_repr(repr: Self::Repr) -> Result<Self, ArchiveError> {
+        Ok(RowDataSet::from(repr))
+    }
+}
+
+impl ArchivedRowDataSet {
+    pub fn dataset_id(&self) -> &str {
+        self.dataset_id.as_str()
+    }
+
+    /// 按列序号排列的列定义
+    pub fn columns(&self) -> &[ArchivedColumn] {
+        &self.columns
+    }
+
+    pub fn column_count(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// 列名对应的列序号
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        self.columns
+            .iter()
+            .find(|column| column.name.as_str() == name)
+            .map(|column| column.index())
+    }
+
+    pub fn row_count(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn row(&self, index: usize) -> Option<&ArchivedRowData> {
+        self.rows.get(index)
+    }
+
+    /// 已删除行的墓碑列表
+    pub fn deleted(&self) -> &[ArchivedRowData] {
+        &self.deleted
+    }
+
+    /// 按行号和列名读取单元格
+    pub fn get(&self, row: usize, column: &str) -> Option<&ArchivedCell> {
+        let index = self.column_index(column)?;
+        self.row(row)?.values.get(index)
+    }
+}
+
+impl ArchivedColumn {
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    pub fn index(&self) -> usize {
+        self.index.to_native() as usize
+    }
+
+    pub fn column_type(&self) -> ColumnType {
+        rkyv::deserialize::<ColumnType, RkyvError>(&self.column_type).unwrap_or_default()
+    }
+
+    pub fn is_multilingual(&self) -> bool {
+        self.multilingual
+    }
+}
+
+impl ArchivedRowData {
+    pub fn values(&self) -> &[ArchivedCell] {
+        &self.values
+    }
+
+    pub fn state(&self) -> RowState {
+        rkyv::deserialize::<RowState, RkyvError>(&self.state).unwrap_or_default()
+    }
+
+    /// 修改前的原始值，见 `RowData::original`
+    pub fn original(&self) -> Option<&[ArchivedCell]> {
+        self.original.as_ref().map(|values| values.as_slice())
+    }
+
+    pub fn child(&self, name: &str) -> Option<&ArchivedRowDataSet> {
+        self.children.as_ref()?.get(name)
+    }
+}
+
+impl Archivable for TableSchema {
+    const KIND: ArchiveKind = ArchiveKind::TableSchema;
+    type Repr = Cell;
+
+    fn to_repr(&self) -> Result<Self::Repr, ArchiveError> {
+        to_cell(self)
+    }
+
+    fn from_repr(repr: Self::Repr) -> Result<Self, ArchiveError> {
+        from_cell(repr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::model::data::dataset::col::ColumnDef;
+    use crate::model::data::dataset::TableSchemaBuilder;
+    use crate::model::meta::fields::SYS_OBJCOLS;
+
+    fn dataset() -> RowDataSet {
+        let mut dataset = RowDataSet::new("orders".to_string());
+        dataset.add_column("ID".to_string(), ColumnType::I64).unwrap();
+        dataset.add_column("NAME".to_string(), ColumnType::String).unwrap();
+        dataset.add_column("AMOUNT".to_string(), ColumnType::F64).unwrap();
+        dataset.add_row(vec![json!(1), json!("first"), json!(1.5)]).unwrap();
+        dataset.add_row(vec![json!(2), json!(null), json!(-3)]).unwrap();
+
+        let mut items = RowDataSet::new("items".to_string());
+        items.add_column("SKU".to_string(), ColumnType::String).unwrap();
+        items.add_row(vec![json!("A-1")]).unwrap();
+        dataset.add_child_dataset(0, "items".to_string(), items).unwrap();
+        dataset
+    }
+
+    #[test]
+    fn test_row_dataset_roundtrip() {
+        let mut original = dataset();
+        original.set_change_tracking(true);
+        original.set_cell(1, "NAME", json!("second")).unwrap();
+
+        let restored: RowDataSet = decode(&encode(&original).unwrap()).unwrap();
+        assert_eq!(serde_json::to_value(&restored).unwrap(), serde_json::to_value(&original).unwrap());
+        assert_eq!(restored.get_row(1).unwrap().state, RowState::Modified);
+        assert_eq!(restored.get_row(1).unwrap().original.as_ref().unwrap()[1], json!(null));
+    }
+
+    #[test]
+    fn test_access_without_deserializing() {
+        let bytes = encode(&dataset()).unwrap();
+        let file = ArchiveFile::from_bytes(&bytes).unwrap();
+        let archived = file.access::<RowDataSet>().unwrap();
+
+        assert_eq!(archived.dataset_id(), "orders");
+        assert_eq!(archived.column_count(), 3);
+        assert_eq!(archived.columns()[2].column_type(), ColumnType::F64);
+        assert_eq!(archived.get(0, "AMOUNT").and_then(ArchivedCell::as_f64), Some(1.5));
+        assert_eq!(archived.get(1, "AMOUNT").and_then(ArchivedCell::as_i64), Some(-3));
+        assert!(archived.get(1, "NAME").unwrap().is_null());
+        assert!(archived.get(0, "MISSING").is_none());
+
+        let items = archived.row(0).unwrap().child("items").unwrap();
+        assert_eq!(items.get(0, "SKU").and_then(ArchivedCell::as_str), Some("A-1"));
+    }
+
+    #[test]
+    fn test_dataset_roundtrip() {
+        let mut row = RowSet::from_values(vec![json!("a"), json!({"zh-CN": "甲"})]);
+        row.add_child(
+            "detail".to_string(),
+            DataSet { rows: Some(vec![RowSet::from_values(vec![json!(true)])]) },
+        );
+        let dataset = DataSet { rows: Some(vec![row]) };
+
+        let bytes = encode(&dataset).unwrap();
+        let file = ArchiveFile::from_bytes(&bytes).unwrap();
+        let archived = file.access::<DataSet>().unwrap();
+        assert_eq!(archived.row_count(), 1);
+        assert_eq!(archived.get(0, 1).and_then(|cell| cell.get("zh-CN")).and_then(ArchivedCell::as_str), Some("甲"));
+        let detail = archived.row(0).unwrap().child("detail").unwrap();
+        assert_eq!(detail.get(0, 0).and_then(ArchivedCell::as_bool), Some(true));
+
+        let restored: DataSet = file.load().unwrap();
+        assert_eq!(serde_json::to_value(&restored).unwrap(), serde_json::to_value(&dataset).unwrap());
+    }
+
+    #[test]
+    fn test_table_schema_roundtrip() {
+        let mut column = ColumnDef::default();
+        column.set(SYS_OBJCOLS::COL_ID, json!("DM"));
+        column.set(SYS_OBJCOLS::COL_LEN, json!(30));
+        let schema = TableSchemaBuilder::new()
+            .with_obj_id("T_ITEM".to_string())
+            .with_columns(vec![column])
+            .build();
+
+        let bytes = encode(&schema).unwrap();
+        let file = ArchiveFile::from_bytes(&bytes).unwrap();
+        let archived = file.access::<TableSchema>().unwrap();
+        let columns = archived.get("columns").and_then(ArchivedCell::as_array).unwrap();
+        assert_eq!(columns[0].get("data").and_then(|data| data.get("COL_ID")).and_then(ArchivedCell::as_str), Some("DM"));
+
+        let restored: TableSchema = file.load().unwrap();
+        assert_eq!(restored.get_column_index("DM"), Some(0));
+        assert_eq!(restored.columns[0].get(&SYS_OBJCOLS::COL_LEN), Some(&json!(30)));
+    }
+
+    #[test]
+    fn test_cell_numbers() {
+        for value in [json!(u64::MAX), json!(i64::MIN), json!(0.25), json!([1, "x", null])] {
+            assert_eq!(CellValue::from(Cell::from(&value)), value);
+        }
+    }
+
+    #[test]
+    fn test_header_checks() {
+        let bytes = encode(&dataset()).unwrap();
+
+        let mut bad = bytes.to_vec();
+        bad[0] = b'X';
+        assert!(matches!(ArchiveFile::from_bytes(&bad), Err(ArchiveError::BadMagic)));
+
+        let mut bad = bytes.to_vec();
+        bad[4..6].copy_from_slice(&(ARCHIVE_VERSION + 1).to_le_bytes());
+        assert!(matches!(ArchiveFile::from_bytes(&bad), Err(ArchiveError::UnsupportedVersion { .. })));
+
+        let truncated = &bytes[..bytes.len() - 1];
+        assert!(matches!(ArchiveFile::from_bytes(truncated), Err(ArchiveError::LengthMismatch { .. })));
+
+        let file = ArchiveFile::from_bytes(&bytes).unwrap();
+        assert!(matches!(
+            file.access::<DataSet>(),
+            Err(ArchiveError::KindMismatch { expected: ArchiveKind::DataSet, found: ArchiveKind::RowDataSet })
+        ));
+    }
+
+    #[test]
+    fn test_corrupted_payload_is_rejected() {
+        let mut bytes = encode(&dataset()).unwrap().to_vec();
+        let len = bytes.len();
+        // 根对象位于数据末尾，破坏其中的相对指针
+        for byte in &mut bytes[len - 8..] {
+            *byte = 0xff;
+        }
+        let file = ArchiveFile::from_bytes(&bytes).unwrap();
+        assert!(matches!(file.access::<RowDataSet>(), Err(ArchiveError::Invalid(_))));
+    }
+
+    #[test]
+    fn test_write_and_map_file() {
+        let path = std::env::temp_dir().join(format!("cmx-archive-{}.bin", uuid::Uuid::new_v4()));
+        write_file(&dataset(), &path).unwrap();
+
+        let file = ArchiveFile::open(&path).unwrap();
+        assert!(!file.is_mapped());
+        assert_eq!(file.access::<RowDataSet>().unwrap().row_count(), 2);
+
+        // SAFETY: 测试期间只有本进程访问该临时文件，映射释放前不会修改
+        let file = unsafe { ArchiveFile::open_mapped(&path) }.unwrap();
+        assert!(file.is_mapped());
+        assert_eq!(file.kind(), ArchiveKind::RowDataSet);
+        assert_eq!(file.access::<RowDataSet>().unwrap().row_count(), 2);
+        let restored: RowDataSet = file.load().unwrap();
+        assert_eq!(restored.get_cell(0, "NAME").unwrap(), &json!("first"));
+
+        drop(file);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,731 @@
+//! # Apache Arrow 导入导出模块
+//!
+//! 为 `ColDataSet` 和 `RowDataSet` 提供与 Arrow `RecordBatch` 的相互转换，
+//! 以及 Arrow IPC 文件格式（`.arrow`）和流格式的读写，用于与 BI 工具交换大批量数据。
+//!
+//! ## 类型映射
+//!
+//! | 列类型 | Arrow 类型 |
+//! |--------|-----------|
+//! | `Bool` | `Boolean` |
+//! | `I8`..`I64` / `U8`..`U64` | `Int8`..`Int64` / `UInt8`..`UInt64` |
+//! | `F32` / `F64` | `Float32` / `Float64` |
+//! | `Decimal` | `Decimal128(38, s)`，`s` 为列中最大的小数位数 |
+//! | `String` | `Utf8` |
+//! | `Date` | `Date32` |
+//! | `Time` | `Time64(Microsecond)` |
+//! | `DateTime` | `Timestamp(Microsecond, None)` |
+//! | `DateTimeTz` | `Timestamp(Microsecond, Some(偏移量))` |
+//! | `Uuid` | `FixedSizeBinary(16)`，扩展类型 `arrow.uuid` |
+//! | `Binary` | `Binary` |
+//! | `Json` | `Utf8`，扩展类型 `arrow.json` |
+//! | 多语言 `String` | `Utf8`，值为 JSON 文本，扩展类型 `arrow.json` |
+//!
+//! 每个字段的元数据中记录原始列类型（键 [`COLUMN_TYPE_KEY`]），导入时优先使用；
+//! 多语言列另外记录 [`MULTILINGUAL_KEY`]，导入后仍为多语言列。
+//! 导出时传入 `TableSchema` 则同时写入列定义中的 `COL_MC`、`COL_DES`。
+//! 导入其他工具生成的数据时按 Arrow 类型推断列类型，必要时先做类型转换
+//! （例如 `LargeUtf8` 转为 `Utf8`、秒级时间戳转为微秒级）。
+//!
+//! ## 注意事项
+//!
+//! - 时间和时间戳精度为微秒，更细的部分会被截断
+//! - `DateTimeTz` 列以 UTC 时刻存储；列中所有值偏移量相同时使用该偏移量作为时区，
+//!   否则使用 `+00:00`，导入后的值会换算到该时区
+//! - 子数据集不会导出
+//!
+//! ## 示例
+//!
+//! ```rust
+//! use cmx_core::model::data::dataset::{rds::RowDataSet, ColumnType};
+//! use serde_json::json;
+//!
+//! let mut facts = RowDataSet::new("facts".to_string());
+//! facts.add_column("KJND".to_string(), ColumnType::I32).unwrap();
+//! facts.add_column("JE".to_string(), ColumnType::Decimal).unwrap();
+//! facts.add_row(vec![json!(2024), json!("1234.56")]).unwrap();
+//!
+//! let mut buffer = Vec::new();
+//! facts.write_ipc_file(&mut buffer, None).unwrap();
+//!
+//! let restored = RowDataSet::read_ipc_file("facts".to_string(), std::io::Cursor::new(buffer)).unwrap();
+//! assert_eq!(restored.get_cell(0, "JE").unwrap(), &json!(1234.56));
+//! ```
+
+use std::collections::HashMap;
+use std::io::{Read, Seek, Write};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use arrow_array::cast::AsArray;
+use arrow_array::timezone::Tz;
+use arrow_array::types::{
+    Date32Type, Decimal128Type, Float32Type, Float64Type, Int8Type, Int16Type, Int32Type, Int64Type,
+    Time64MicrosecondType, TimestampMicrosecondType, UInt8Type, UInt16Type, UInt32Type, UInt64Type,
+};
+use arrow_array::{
+    Array, ArrayRef, BinaryArray, BooleanArray, Date32Array, Decimal128Array, FixedSizeBinaryArray, Float32Array,
+    Float64Array, Int8Array, Int16Array, Int32Array, Int64Array, RecordBatch, RecordBatchOptions, StringArray,
+    Time64MicrosecondArray, TimestampMicrosecondArray, UInt8Array, UInt16Array, UInt32Array, UInt64Array,
+};
+use arrow_ipc::reader::{FileReader, StreamReader};
+use arrow_ipc::writer::{FileWriter, StreamWriter};
+use arrow_schema::{ArrowError, DataType, Field, Schema, SchemaRef, TimeUnit};
+use chrono::{DateTime, Datelike, NaiveDate, NaiveTime, Timelike};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use super::cds::{ColDataSet, ColumnBuffer, ColumnDataArray};
+use super::col::ColumnDef;
+use super::lang::coerce_localized;
+use super::rds::RowDataSet;
+use super::{ColumnType, DataSetError, TableSchema};
+use crate::model::data::cell::CellValue;
+use crate::model::meta::fields::SYS_OBJCOLS;
+
+/// 字段元数据中记录原始列类型的键
+pub const COLUMN_TYPE_KEY: &str = "cmx.column_type";
+
+/// 字段元数据中标记多语言列的键，值为 `true`
+pub const MULTILINGUAL_KEY: &str = "cmx.multilingual";
+
+/// Arrow 规范扩展类型名的元数据键
+const EXTENSION_NAME_KEY: &str = "ARROW:extension:name";
+
+/// `Decimal128` 的最大精度
+const DECIMAL_PRECISION: u8 = 38;
+
+/// 导出时写入字段元数据的列定义属性
+const FIELD_METADATA_COLUMNS: [SYS_OBJCOLS; 2] = [SYS_OBJCOLS::COL_MC, SYS_OBJCOLS::COL_DES];
+
+/// 1970-01-01 距公元元年的天数
+const UNIX_EPOCH_DAYS_FROM_CE: i32 = 719_163;
+
+/// 列类型对应的 Arrow 类型
+///
+/// `Decimal` 固定返回 `Decimal128(38, 0)`，实际导出时小数位数由列中的数据决定；
+/// `DateTimeTz` 返回时区为 `+00:00` 的时间戳类型。
+pub fn arrow_data_type(column_type: ColumnType) -> DataType {
+    match column_type {
+        ColumnType::Bool => DataType::Boolean,
+        ColumnType::I8 => DataType::Int8,
+        ColumnType::I16 => DataType::Int16,
+        ColumnType::I32 => DataType::Int32,
+        ColumnType::I64 => DataType::Int64,
+        ColumnType::U8 => DataType::UInt8,
+        ColumnType::U16 => DataType::UInt16,
+        ColumnType::U32 => DataType::UInt32,
+        ColumnType::U64 => DataType::UInt64,
+        ColumnType::F32 => DataType::Float32,
+        ColumnType::F64 => DataType::Float64,
+        ColumnType::Decimal => DataType::Decimal128(DECIMAL_PRECISION, 0),
+        ColumnType::String | ColumnType::Json => DataType::Utf8,
+        ColumnType::Date => DataType::Date32,
+        ColumnType::Time => DataType::Time64(TimeUnit::Microsecond),
+        ColumnType::DateTime => DataType::Timestamp(TimeUnit::Microsecond, None),
+        ColumnType::DateTimeTz => DataType::Timestamp(TimeUnit::Microsecond, Some("+00:00".into())),
+        ColumnType::Uuid => DataType::FixedSizeBinary(16),
+        ColumnType::Binary => DataType::Binary,
+    }
+}
+
+/// 根据 Arrow 字段确定列类型
+///
+/// 优先使用字段元数据中记录的列类型，其次识别 `arrow.uuid` / `arrow.json` 扩展类型，
+/// 最后按 Arrow 类型推断。无法对应的类型（如列表、结构体）返回 `None`。
+pub fn column_type_from_field(field: &Field) -> Option<ColumnType> {
+    let metadata = field.metadata();
+    if let Some(column_type) = metadata
+        .get(COLUMN_TYPE_KEY)
+        .and_then(|name| serde_json::from_value(CellValue::String(name.clone())).ok())
+    {
+        return Some(column_type);
+    }
+    match metadata.get(EXTENSION_NAME_KEY).map(String::as_str) {
+        Some("arrow.uuid") if field.data_type() == &DataType::FixedSizeBinary(16) => return Some(ColumnType::Uuid),
+        Some("arrow.json") => return Some(ColumnType::Json),
+        _ => {}
+    }
+    let column_type = match field.data_type() {
+        DataType::Boolean => ColumnType::Bool,
+        DataType::Int8 => ColumnType::I8,
+        DataType::Int16 => ColumnType::I16,
+        DataType::Int32 => ColumnType::I32,
+        DataType::Int64 => ColumnType::I64,
+        DataType::UInt8 => ColumnType::U8,
+        DataType::UInt16 => ColumnType::U16,
+        DataType::UInt32 => ColumnType::U32,
+        DataType::UInt64 => ColumnType::U64,
+        DataType::Float16 | DataType::Float32 => ColumnType::F32,
+        DataType::Float64 => ColumnType::F64,
+        DataType::Decimal128(..) | DataType::Decimal256(..) => ColumnType::Decimal,
+        DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View => ColumnType::String,
+        DataType::Date32 | DataType::Date64 => ColumnType::Date,
+        DataType::Time32(_) | DataType::Time64(_) => ColumnType::Time,
+        DataType::Timestamp(_, None) => ColumnType::DateTime,
+        DataType::Timestamp(_, Some(_)) => ColumnType::DateTimeTz,
+        DataType::Binary | DataType::LargeBinary | DataType::BinaryView | DataType::FixedSizeBinary(_) => {
+            ColumnType::Binary
+        }
+        _ => return None,
+    };
+    Some(column_type)
+}
+
+/// 从 Arrow 模式还原列定义
+///
+/// 每个字段生成一个 `ColumnDef`，包含 `COL_ID` 以及字段元数据中的 `COL_MC`、`COL_DES`。
+pub fn column_defs_from_schema(schema: &Schema) -> Vec<ColumnDef> {
+    schema
+        .fields()
+        .iter()
+        .map(|field| {
+            let mut def = ColumnDef::default();
+            def.set(SYS_OBJCOLS::COL_ID, CellValue::String(field.name().clone()));
+            for key in FIELD_METADATA_COLUMNS {
+                if let Some(value) = field.metadata().get(key.as_ref()) {
+                    def.set(key, CellValue::String(value.clone()));
+                }
+            }
+            def
+        })
+        .collect()
+}
+
+fn arrow_error(message: String) -> DataSetError {
+    DataSetError::Arrow(ArrowError::InvalidArgumentError(message))
+}
+
+/// 构造导出字段，写入列类型和列定义元数据
+fn export_field(name: &str, data_type: DataType, data: &ColumnDataArray, meta: Option<&TableSchema>) -> Field {
+    let column_type = data.column_type();
+    let mut metadata = HashMap::from([(COLUMN_TYPE_KEY.to_string(), format!("{:?}", column_type))]);
+    match column_type {
+        ColumnType::Uuid => metadata.insert(EXTENSION_NAME_KEY.to_string(), "arrow.uuid".to_string()),
+        ColumnType::Json => metadata.insert(EXTENSION_NAME_KEY.to_string(), "arrow.json".to_string()),
+        _ => None,
+    };
+    if data.is_localized() {
+        metadata.insert(EXTENSION_NAME_KEY.to_string(), "arrow.json".to_string());
+        metadata.insert(MULTILINGUAL_KEY.to_string(), "true".to_string());
+    }
+    if let Some(def) = meta.and_then(|schema| schema.get_column(name)) {
+        for key in FIELD_METADATA_COLUMNS {
+            if let Some(value) = def.get(&key) {
+                let text = value.as_str().map(str::to_string).unwrap_or_else(|| value.to_string());
+                metadata.insert(key.as_ref().to_string(), text);
+            }
+        }
+    }
+    Field::new(name, data_type, true).with_metadata(metadata)
+}
+
+/// 把定点数列转换为 `Decimal128` 数组，小数位数取列中最大值
+fn decimal_array(name: &str, values: &[Option<Decimal>]) -> Result<Decimal128Array, DataSetError> {
+    let scale = values.iter().flatten().map(Decimal::scale).max().unwrap_or(0);
+    let limit = 10i128.pow(DECIMAL_PRECISION as u32);
+    let array = values
+        .iter()
+        .map(|value| {
+            value
+                .map(|d| {
+                    10i128
+                        .checked_pow(scale - d.scale())
+                        .and_then(|factor| d.mantissa().checked_mul(factor))
+                        .filter(|m| m.abs() < limit)
+                        .ok_or_else(|| {
+                            arrow_error(format!("value {} in column '{}' exceeds Decimal128({}, {})", d, name, DECIMAL_PRECISION, scale))
+                        })
+                })
+                .transpose()
+        })
+        .collect::<Result<Decimal128Array, _>>()?;
+    Ok(array.with_precision_and_scale(DECIMAL_PRECISION, scale as i8)?)
+}
+
+fn date_to_days(date: &NaiveDate) -> i32 {
+    date.num_days_from_ce() - UNIX_EPOCH_DAYS_FROM_CE
+}
+
+fn time_to_micros(time: &NaiveTime) -> i64 {
+    time.num_seconds_from_midnight() as i64 * 1_000_000 + (time.nanosecond() / 1_000) as i64
+}
+
+/// 把列数据转换为 Arrow 数组和对应的数据类型
+fn export_array(name: &str, data: &ColumnDataArray) -> Result<ArrayRef, DataSetError> {
+    let array: ArrayRef = match data {
+        ColumnDataArray::BoolArray(values) => Arc::new(values.iter().collect::<BooleanArray>()),
+        ColumnDataArray::I8Array(values) => Arc::new(values.iter().collect::<Int8Array>()),
+        ColumnDataArray::I16Array(values) => Arc::new(values.iter().collect::<Int16Array>()),
+        ColumnDataArray::I32Array(values) => Arc::new(values.iter().collect::<Int32Array>()),
+        ColumnDataArray::I64Array(values) => Arc::new(values.iter().collect::<Int64Array>()),
+        ColumnDataArray::U8Array(values) => Arc::new(values.iter().collect::<UInt8Array>()),
+        ColumnDataArray::U16Array(values) => Arc::new(values.iter().collect::<UInt16Array>()),
+        ColumnDataArray::U32Array(values) => Arc::new(values.iter().collect::<UInt32Array>()),
+        ColumnDataArray::U64Array(values) => Arc::new(values.iter().collect::<UInt64Array>()),
+        ColumnDataArray::F32Array(values) => Arc::new(values.iter().collect::<Float32Array>()),
+        ColumnDataArray::F64Array(values) => Arc::new(values.iter().collect::<Float64Array>()),
+        ColumnDataArray::DecimalArray(values) => Arc::new(decimal_array(name, values)?),
+        ColumnDataArray::StringArray(values) => Arc::new(values.iter().map(Option::as_deref).collect::<StringArray>()),
+        ColumnDataArray::DateArray(values) => {
+            Arc::new(values.iter().map(|v| v.as_ref().map(date_to_days)).collect::<Date32Array>())
+        }
+        ColumnDataArray::TimeArray(values) => {
+            Arc::new(values.iter().map(|v| v.as_ref().map(time_to_micros)).collect::<Time64MicrosecondArray>())
+        }
+        ColumnDataArray::DateTimeArray(values) => Arc::new(
+            values
+                .iter()
+                .map(|v| v.map(|dt| dt.and_utc().timestamp_micros()))
+                .collect::<TimestampMicrosecondArray>(),
+        ),
+        ColumnDataArray::DateTimeTzArray(values) => {
+            let mut offsets = values.iter().flatten().map(DateTime::offset);
+            let timezone = match offsets.next() {
+                Some(first) if offsets.all(|offset| offset == first) => first.to_string(),
+                _ => "+00:00".to_string(),
+            };
+            Arc::new(
+                values
+                    .iter()
+                    .map(|v| v.map(|dt| dt.timestamp_micros()))
+                    .collect::<TimestampMicrosecondArray>()
+                    .with_timezone(timezone),
+            )
+        }
+        ColumnDataArray::UuidArray(values) => Arc::new(FixedSizeBinaryArray::try_from_sparse_iter_with_size(
+            values.iter().map(|v| v.as_ref().map(Uuid::as_bytes)),
+            16,
+        )?),
+        ColumnDataArray::BinaryArray(values) => Arc::new(values.iter().map(Option::as_deref).collect::<BinaryArray>()),
+        ColumnDataArray::JsonArray(values) | ColumnDataArray::LocalizedArray(values) => Arc::new(
+            values
+                .iter()
+                .map(|v| v.as_ref().map(CellValue::to_string))
+                .collect::<StringArray>(),
+        ),
+    };
+    Ok(array)
+}
+
+/// 导入时列类型对应的目标 Arrow 类型，尽量保留源数组的小数位数和时区
+fn import_data_type(column_type: ColumnType, source: &DataType) -> DataType {
+    match (column_type, source) {
+        (ColumnType::Decimal, DataType::Decimal128(precision, scale)) if (0..=28).contains(scale) => {
+            DataType::Decimal128(*precision, *scale)
+        }
+        (ColumnType::Decimal, DataType::Decimal128(_, scale) | DataType::Decimal256(_, scale)) => {
+            DataType::Decimal128(DECIMAL_PRECISION, (*scale).clamp(0, 28))
+        }
+        (ColumnType::DateTimeTz, DataType::Timestamp(_, Some(timezone))) => {
+            DataType::Timestamp(TimeUnit::Microsecond, Some(timezone.clone()))
+        }
+        (ColumnType::Binary, DataType::FixedSizeBinary(_)) => DataType::Binary,
+        _ => arrow_data_type(column_type),
+    }
+}
+
+/// 把 Arrow 数组转换为指定列类型的列数据，`multilingual` 为真时按 JSON 文本读取多语言字符串列
+fn import_array(name: &str, column_type: ColumnType, multilingual: bool, array: &ArrayRef) -> Result<ColumnDataArray, DataSetError> {
+    let target = import_data_type(column_type, array.data_type());
+    let array = if array.data_type() == &target { Arc::clone(array) } else { arrow_cast::cast(array, &target)? };
+    let invalid = |value: &dyn std::fmt::Debug| arrow_error(format!("invalid {:?} value {:?} in column '{}'", column_type, value, name));
+
+    let data = match column_type {
+        ColumnType::String if multilingual => ColumnDataArray::LocalizedArray(
+            array
+                .as_string::<i32>()
+                .iter()
+                .map(|v| {
+                    v.map(|text| {
+                        serde_json::from_str(text)
+                            .ok()
+                            .and_then(|value| coerce_localized(column_type, value).ok())
+                            .ok_or_else(|| invalid(&text))
+                    })
+                    .transpose()
+                })
+                .collect::<Result<ColumnBuffer<_>, _>>()?,
+        ),
+        ColumnType::Bool => ColumnDataArray::BoolArray(array.as_boolean().iter().collect()),
+        ColumnType::I8 => ColumnDataArray::I8Array(array.as_primitive::<Int8Type>().iter().collect()),
+        ColumnType::I16 => ColumnDataArray::I16Array(array.as_primitive::<Int16Type>().iter().collect()),
+        ColumnType::I32 => ColumnDataArray::I32Array(array.as_primitive::<Int32Type>().iter().collect()),
+        ColumnType::I64 => ColumnDataArray::I64Array(array.as_primitive::<Int64Type>().iter().collect()),
+        ColumnType::U8 => ColumnDataArray::U8Array(array.as_primitive::<UInt8Type>().iter().collect()),
+        ColumnType::U16 => ColumnDataArray::U16Array(array.as_primitive::<UInt16Type>().iter().collect()),
+        ColumnType::U32 => ColumnDataArray::U32Array(array.as_primitive::<UInt32Type>().iter().collect()),
+        ColumnType::U64 => ColumnDataArray::U64Array(array.as_primitive::<UInt64Type>().iter().collect()),
+        ColumnType::F32 => ColumnDataArray::F32Array(array.as_primitive::<Float32Type>().iter().collect()),
+        ColumnType::F64 => ColumnDataArray::F64Array(array.as_primitive::<Float64Type>().iter().collect()),
+        ColumnType::Decimal => {
+            let decimals = array.as_primitive::<Decimal128Type>();
+            let scale = decimals.scale() as u32;
+            ColumnDataArray::DecimalArray(
+                decimals
+                    .iter()
+                    .map(|v| v.map(|m| Decimal::try_from_i128_with_scale(m, scale).map_err(|_| invalid(&m))).transpose())
+                    .collect::<Result<ColumnBuffer<_>, _>>()?,
+            )
+        }
+        ColumnType::String => {
+            ColumnDataArray::StringArray(array.as_string::<i32>().iter().map(|v| v.map(str::to_string)).collect())
+        }
+        ColumnType::Json => ColumnDataArray::JsonArray(
+            array
+                .as_string::<i32>()
+                .iter()
+                .map(|v| v.map(|text| serde_json::from_str(text).map_err(|_| invalid(&text))).transpose())
+                .collect::<Result<ColumnBuffer<_>, _>>()?,
+        ),
+        ColumnType::Date => ColumnDataArray::DateArray(
+            array
+                .as_primitive::<Date32Type>()
+                .iter()
+                .map(|v| {
+                    v.map(|days| {
+                        days.checked_add(UNIX_EPOCH_DAYS_FROM_CE)
+                            .and_then(NaiveDate::from_num_days_from_ce_opt)
+                            .ok_or_else(|| invalid(&days))
+                    })
+                    .transpose()
+                })
+                .collect::<Result<ColumnBuffer<_>, _>>()?,
+        ),
+        ColumnType::Time => ColumnDataArray::TimeArray(
+            array
+                .as_primitive::<Time64MicrosecondType>()
+                .iter()
+                .map(|v| {
+                    v.map(|micros| {
+                        u32::try_from(micros.div_euclid(1_000_000))
+                            .ok()
+                            .and_then(|secs| {
+                                NaiveTime::from_num_seconds_from_midnight_opt(secs, (micros.rem_euclid(1_000_000) * 1_000) as u32)
+                            })
+                            .ok_or_else(|| invalid(&micros))
+                    })
+                    .transpose()
+                })
+                .collect::<Result<ColumnBuffer<_>, _>>()?,
+        ),
+        ColumnType::DateTime => ColumnDataArray::DateTimeArray(
+            array
+                .as_primitive::<TimestampMicrosecondType>()
+                .iter()
+                .map(|v| {
+                    v.map(|micros| DateTime::from_timestamp_micros(micros).map(|dt| dt.naive_utc()).ok_or_else(|| invalid(&micros)))
+                        .transpose()
+                })
+                .collect::<Result<ColumnBuffer<_>, _>>()?,
+        ),
+        ColumnType::DateTimeTz => {
+            let timestamps = array.as_primitive::<TimestampMicrosecondType>();
+            let timezone = timestamps.timezone().unwrap_or("+00:00");
+            let tz = Tz::from_str(timezone)?;
+            ColumnDataArray::DateTimeTzArray(
+                timestamps
+                    .iter()
+                    .map(|v| {
+                        v.map(|micros| {
+                            DateTime::from_timestamp_micros(micros)
+                                .map(|dt| dt.with_timezone(&tz).fixed_offset())
+                                .ok_or_else(|| invalid(&micros))
+                        })
+                        .transpose()
+                    })
+                    .collect::<Result<ColumnBuffer<_>, _>>()?,
+            )
+        }
+        ColumnType::Uuid => ColumnDataArray::UuidArray(
+            array
+                .as_fixed_size_binary()
+                .iter()
+                .map(|v| v.map(|bytes| Uuid::from_slice(bytes).map_err(|_| invalid(&bytes))).transpose())
+                .collect::<Result<ColumnBuffer<_>, _>>()?,
+        ),
+        ColumnType::Binary => {
+            ColumnDataArray::BinaryArray(array.as_binary::<i32>().iter().map(|v| v.map(<[u8]>::to_vec)).collect())
+        }
+    };
+    Ok(data)
+}
+
+impl ColDataSet {
+    /// 转换为 Arrow `RecordBatch`
+    ///
+    /// # 参数
+    ///
+    /// * `meta` - 可选的表定义，其中列定义的 `COL_MC`、`COL_DES` 会写入字段元数据
+    ///
+    /// # 返回值
+    ///
+    /// - `Ok(RecordBatch)` - 列顺序与 `column_names` 一致
+    /// - `Err(DataSetError::Arrow)` - 定点数超出 `Decimal128(38, s)` 的范围等
+    pub fn to_record_batch(&self, meta: Option<&TableSchema>) -> Result<RecordBatch, DataSetError> {
+        let mut fields = Vec::with_capacity(self.column_count());
+        let mut arrays = Vec::with_capacity(self.column_count());
+        for name in &self.column_names {
+            let data = &self.columns[name];
+            let array = export_array(name, data)?;
+            fields.push(export_field(name, array.data_type().clone(), data, meta));
+            arrays.push(array);
+        }
+        let options = RecordBatchOptions::new().with_row_count(Some(self.row_count()));
+        Ok(RecordBatch::try_new_with_options(Arc::new(Schema::new(fields)), arrays, &options)?)
+    }
+
+    /// 从 Arrow `RecordBatch` 创建列式数据集
+    ///
+    /// 列类型由 [`column_type_from_field`] 确定，遇到无法对应的 Arrow 类型或无法转换的值时返回
+    /// `DataSetError::Arrow`。
+    pub fn from_record_batch(dataset_id: String, batch: &RecordBatch) -> Result<ColDataSet, DataSetError> {
+        let mut dataset = ColDataSet::new(dataset_id);
+        for (field, array) in batch.schema().fields().iter().zip(batch.columns()) {
+            let column_type = column_type_from_field(field).ok_or_else(|| {
+                arrow_error(format!("unsupported Arrow type {} for column '{}'", field.data_type(), field.name()))
+            })?;
+            if dataset.schema.contains_key(field.name()) {
+                return Err(DataSetError::DuplicateColumn(field.name().clone()));
+            }
+            let multilingual = field.metadata().get(MULTILINGUAL_KEY).is_some_and(|flag| flag == "true");
+            dataset.schema.insert(field.name().clone(), column_type);
+            dataset.column_names.push(field.name().clone());
+            dataset.columns.insert(field.name().clone(), import_array(field.name(), column_type, multilingual, array)?);
+        }
+        dataset.children = ColumnBuffer::nulls(batch.num_rows());
+        Ok(dataset)
+    }
+
+    /// 以 Arrow IPC 文件格式写出
+    pub fn write_ipc_file<W: Write>(&self, writer: W, meta: Option<&TableSchema>) -> Result<(), DataSetError> {
+        let batch = self.to_record_batch(meta)?;
+        let mut writer = FileWriter::try_new(writer, &batch.schema())?;
+        writer.write(&batch)?;
+        writer.finish()?;
+        Ok(())
+    }
+
+    /// 以 Arrow IPC 流格式写出
+    pub fn write_ipc_stream<W: Write>(&self, writer: W, meta: Option<&TableSchema>) -> Result<(), DataSetError> {
+        let batch = self.to_record_batch(meta)?;
+        let mut writer = StreamWriter::try_new(writer, &batch.schema())?;
+        writer.write(&batch)?;
+        writer.finish()?;
+        Ok(())
+    }
+
+    /// 读取 Arrow IPC 文件，所有批次合并为一个数据集
+    pub fn read_ipc_file<R: Read + Seek>(dataset_id: String, reader: R) -> Result<ColDataSet, DataSetError> {
+        let reader = FileReader::try_new(reader, None)?;
+        let schema = reader.schema();
+        let batches = reader.collect::<Result<Vec<_>, _>>()?;
+        ColDataSet::from_batches(dataset_id, schema, &batches)
+    }
+
+    /// 读取 Arrow IPC 流，所有批次合并为一个数据集
+    pub fn read_ipc_stream<R: Read>(dataset_id: String, reader: R) -> Result<ColDataSet, DataSetError> {
+        let reader = StreamReader::try_new(reader, None)?;
+        let schema = reader.schema();
+        let batches = reader.collect::<Result<Vec<_>, _>>()?;
+        ColDataSet::from_batches(dataset_id, schema, &batches)
+    }
+
+    fn from_batches(dataset_id: String, schema: SchemaRef, batches: &[RecordBatch]) -> Result<ColDataSet, DataSetError> {
+        let batch = arrow_select::concat::concat_batches(&schema, batches)?;
+        ColDataSet::from_record_batch(dataset_id, &batch)
+    }
+}
+
+impl RowDataSet {
+    /// 转换为 Arrow `RecordBatch`，参数和返回值同 [`ColDataSet::to_record_batch`]
+    ///
+    /// 数据集中有无法以列类型保存的值时返回 `DataSetError::TypeMismatch`，见 `ColDataSet::try_from`。
+    pub fn to_record_batch(&self, meta: Option<&TableSchema>) -> Result<RecordBatch, DataSetError> {
+        ColDataSet::try_from(self)?.to_record_batch(meta)
+    }
+
+    /// 从 Arrow `RecordBatch` 创建行式数据集
+    pub fn from_record_batch(dataset_id: String, batch: &RecordBatch) -> Result<RowDataSet, DataSetError> {
+        ColDataSet::from_record_batch(dataset_id, batch).map(RowDataSet::from)
+    }
+
+    /// 以 Arrow IPC 文件格式写出
+    pub fn write_ipc_file<W: Write>(&self, writer: W, meta: Option<&TableSchema>) -> Result<(), DataSetError> {
+        ColDataSet::try_from(self)?.write_ipc_file(writer, meta)
+    }
+
+    /// 以 Arrow IPC 流格式写出
+    pub fn write_ipc_stream<W: Write>(&self, writer: W, meta: Option<&TableSchema>) -> Result<(), DataSetError> {
+        ColDataSet::try_from(self)?.write_ipc_stream(writer, meta)
+    }
+
+    /// 读取 Arrow IPC 文件，所有批次合并为一个数据集
+    pub fn read_ipc_file<R: Read + Seek>(dataset_id: String, reader: R) -> Result<RowDataSet, DataSetError> {
+        ColDataSet::read_ipc_file(dataset_id, reader).map(RowDataSet::from)
+    }
+
+    /// 读取 Arrow IPC 流，所有批次合并为一个数据集
+    pub fn read_ipc_stream<R: Read>(dataset_id: String, reader: R) -> Result<RowDataSet, DataSetError> {
+        ColDataSet::read_ipc_stream(dataset_id, reader).map(RowDataSet::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use arrow_array::LargeStringArray;
+    use serde_json::json;
+
+    use super::*;
+    use crate::model::data::dataset::TableSchemaBuilder;
+
+    fn create_all_types() -> RowDataSet {
+        let columns = [
+            ("B", ColumnType::Bool, json!(true)),
+            ("I8", ColumnType::I8, json!(-8)),
+            ("I16", ColumnType::I16, json!(-16)),
+            ("I32", ColumnType::I32, json!(-32)),
+            ("I64", ColumnType::I64, json!(i64::MIN)),
+            ("U8", ColumnType::U8, json!(8)),
+            ("U16", ColumnType::U16, json!(16)),
+            ("U32", ColumnType::U32, json!(32)),
+            ("U64", ColumnType::U64, json!(u64::MAX)),
+            ("F32", ColumnType::F32, json!(0.1)),
+            ("F64", ColumnType::F64, json!(0.1)),
+            ("DEC", ColumnType::Decimal, json!("12345678901234567890.123")),
+            ("S", ColumnType::String, json!("文本")),
+            ("D", ColumnType::Date, json!("1969-12-31")),
+            ("T", ColumnType::Time, json!("08:30:00.5")),
+            ("DT", ColumnType::DateTime, json!("2024-02-29T08:30:00")),
+            ("DTZ", ColumnType::DateTimeTz, json!("2024-02-29T08:30:00+08:00")),
+            ("U", ColumnType::Uuid, json!("67e55044-10b1-426f-9247-bb680e5fe0c8")),
+            ("BIN", ColumnType::Binary, json!("AAEC/w==")),
+            ("J", ColumnType::Json, json!({"a": [1, 2]})),
+        ];
+        let mut dataset = RowDataSet::new("all_types".to_string());
+        for (name, column_type, _) in &columns {
+            dataset.add_column(name.to_string(), *column_type).unwrap();
+        }
+        dataset.add_row(columns.iter().map(|(_, _, value)| value.clone()).collect()).unwrap();
+        dataset.add_row(vec![CellValue::Null; columns.len()]).unwrap();
+        let mut third = vec![CellValue::Null; columns.len()];
+        third[11] = json!(-1);
+        dataset.add_row(third).unwrap();
+        dataset
+    }
+
+    #[test]
+    fn test_ipc_file_and_stream_round_trip() {
+        let source = create_all_types();
+
+        let mut file = Vec::new();
+        source.write_ipc_file(&mut file, None).unwrap();
+        let from_file = RowDataSet::read_ipc_file("all_types".to_string(), Cursor::new(file)).unwrap();
+
+        let mut stream = Vec::new();
+        source.write_ipc_stream(&mut stream, None).unwrap();
+        let from_stream = RowDataSet::read_ipc_stream("all_types".to_string(), stream.as_slice()).unwrap();
+
+        for restored in [from_file, from_stream] {
+            assert_eq!(restored.row_count(), 3);
+            for (name, info) in &source.schema {
+                assert_eq!(restored.schema[name].index, info.index);
+                assert_eq!(restored.schema[name].column_type, info.column_type);
+            }
+            for (restored_row, source_row) in restored.rows.iter().zip(&source.rows) {
+                assert_eq!(restored_row.values, source_row.values);
+            }
+        }
+    }
+
+    #[test]
+    fn test_arrow_types_and_field_metadata() {
+        let source = create_all_types();
+        let mut def = ColumnDef::default();
+        def.set(SYS_OBJCOLS::COL_ID, json!("DEC"));
+        def.set(SYS_OBJCOLS::COL_MC, json!("金额"));
+        def.set(SYS_OBJCOLS::COL_DES, json!("本年累计"));
+        let meta = TableSchemaBuilder::new().with_columns(vec![def]).build();
+
+        let batch = source.to_record_batch(Some(&meta)).unwrap();
+        let schema = batch.schema();
+        let field = schema.field_with_name("DEC").unwrap();
+        assert_eq!(field.data_type(), &DataType::Decimal128(38, 3));
+        assert_eq!(field.metadata()["COL_MC"], "金额");
+        assert_eq!(
+            schema.field_with_name("DTZ").unwrap().data_type(),
+            &DataType::Timestamp(TimeUnit::Microsecond, Some("+08:00".into()))
+        );
+        assert_eq!(schema.field_with_name("U").unwrap().data_type(), &DataType::FixedSizeBinary(16));
+
+        let defs = column_defs_from_schema(&schema);
+        assert_eq!(defs[11].col_id(), "DEC");
+        assert_eq!(defs[11].get(&SYS_OBJCOLS::COL_DES), Some(&json!("本年累计")));
+        assert_eq!(defs[0].get(&SYS_OBJCOLS::COL_MC), None);
+    }
+
+    #[test]
+    fn test_multilingual_round_trip() {
+        let mut accounts = RowDataSet::new("accounts".to_string());
+        accounts.add_column("KMMC".to_string(), ColumnType::String).unwrap();
+        accounts.set_multilingual("KMMC", true).unwrap();
+        accounts.add_row(vec![json!({"en-US": "Cash", "zh-CN": "库存现金"})]).unwrap();
+        accounts.add_row(vec![json!("银行存款")]).unwrap();
+        accounts.add_row(vec![CellValue::Null]).unwrap();
+
+        let batch = accounts.to_record_batch(None).unwrap();
+        let field = batch.schema().field(0).clone();
+        assert_eq!(field.metadata()[MULTILINGUAL_KEY], "true");
+        assert_eq!(field.metadata()[EXTENSION_NAME_KEY], "arrow.json");
+
+        let mut file = Vec::new();
+        accounts.write_ipc_file(&mut file, None).unwrap();
+        let restored = RowDataSet::read_ipc_file("accounts".to_string(), Cursor::new(file)).unwrap();
+        assert_eq!(restored.multilingual_columns(), vec!["KMMC"]);
+        assert_eq!(restored.get_cell(0, "KMMC").unwrap(), &json!({"en-US": "Cash", "zh-CN": "库存现金"}));
+        assert_eq!(restored.get_cell(1, "KMMC").unwrap(), &json!("银行存款"));
+        assert_eq!(restored.get_cell(2, "KMMC").unwrap(), &CellValue::Null);
+
+        // 绕过校验写入的非法值导出时报错
+        accounts.rows[1].values[0] = json!(["银行存款"]);
+        assert!(matches!(accounts.to_record_batch(None), Err(DataSetError::TypeMismatch { row: 1, .. })));
+    }
+
+    #[test]
+    fn test_import_foreign_batch() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("NAME", DataType::LargeUtf8, true),
+            Field::new("TS", DataType::Timestamp(TimeUnit::Second, None), true),
+            Field::new("AMT", DataType::Decimal128(10, 2), true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(LargeStringArray::from(vec![Some("a"), None])),
+                Arc::new(arrow_array::TimestampSecondArray::from(vec![Some(0), Some(86_400)])),
+                Arc::new(Decimal128Array::from(vec![Some(12345), None]).with_precision_and_scale(10, 2).unwrap()),
+            ],
+        )
+        .unwrap();
+
+        let dataset = RowDataSet::from_record_batch("foreign".to_string(), &batch).unwrap();
+        assert_eq!(dataset.schema["NAME"].column_type, ColumnType::String);
+        assert_eq!(dataset.schema["TS"].column_type, ColumnType::DateTime);
+        assert_eq!(dataset.get_cell(1, "TS").unwrap(), &json!("1970-01-02T00:00:00"));
+        assert_eq!(dataset.get_cell(0, "AMT").unwrap(), &json!(123.45));
+        assert_eq!(dataset.get_cell(1, "NAME").unwrap(), &CellValue::Null);
+
+        let unsupported = Schema::new(vec![Field::new(
+            "L",
+            DataType::List(Arc::new(Field::new("item", DataType::Int32, true))),
+            true,
+        )]);
+        let batch = RecordBatch::new_empty(Arc::new(unsupported));
+        assert!(matches!(
+            ColDataSet::from_record_batch("bad".to_string(), &batch),
+            Err(DataSetError::Arrow(_))
+        ));
+    }
+}
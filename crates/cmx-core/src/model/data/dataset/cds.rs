@@ -7,7 +7,9 @@
 //!
 //! - **列式存储**：数据按列组织，便于列级别的批量操作和分析
 //! - **NULL 值优化**：使用 `Option<T>` 显式处理缺失值
-//! - **类型安全**：严格的类型检查和数据完整性验证
+//! - **类型安全**：每种列类型使用原生类型数组存储，写入时严格校验
+//! - **零拷贝切片**：列数据通过 `Arc` 共享，切片不复制数据
+//! - **无损转换**：与 `RowDataSet` 相互转换（`TryFrom` / `From`），保留多语言列和嵌套子数据集
 //! - **层次结构**：支持行级别的子数据集嵌套
 //! - **内存高效**：列式存储适合大数据集的列级操作
 //!
@@ -21,8 +23,9 @@
 //!         "name" => String,
 //!         "active" => Bool,
 //!     },
+//!     column_names: ["id", "name", "active"],  // 列顺序
 //!     columns: {                     // 列数据存储
-//!         "id" => NumberArray([Some(1), Some(2), None]),
+//!         "id" => I32Array([Some(1), Some(2), None]),
 //!         "name" => StringArray([Some("Alice"), Some("Bob"), None]),
 //!         "active" => BoolArray([Some(true), Some(false), None]),
 //!     },
@@ -47,9 +50,23 @@
 //! - **内存数据库**：高效的列级数据访问和操作
 
 use std::collections::HashMap;
-
+use std::ops::Deref;
+use std::sync::Arc;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use super::coerce::{
+    decimal_to_cell, format_date, format_datetime, format_datetime_tz, format_time, parse_date, parse_datetime,
+    parse_datetime_tz, parse_time, to_decimal, to_f32,
+};
+use super::lang::coerce_localized;
+use super::rds::{ColumnInfo, RowData as RowRecord, RowDataSet};
 use crate::model::data::cell::CellValue;
-use crate::model::data::dataset::ColumnType;
+use crate::model::data::dataset::{ColumnType, DataSetError};
 
 /// 行数据结构，包含列值和子数据集
 ///
@@ -287,10 +304,8 @@ impl RowData {
         let result = self.children.as_mut().and_then(|children| children.remove(key));
 
         // 如果 HashMap 为空，将 children 设置为 None
-        if let Some(children) = &self.children {
-            if children.is_empty() {
-                self.children = None;
-            }
+        if self.children.as_ref().is_some_and(HashMap::is_empty) {
+            self.children = None;
         }
 
         result
@@ -307,16 +322,281 @@ impl Default for RowData {
     }
 }
 
-/// 列数据存储枚举 - 针对 NULL 值优化的列式存储
+/// 单元格值与列式存储类型之间的编解码
 ///
-/// `ColumnDataArray` 定义了列数据的存储方式，每种变体对应不同数据类型的列。
-/// 使用 `Option<T>` 包装数据类型，能够高效地处理 NULL 值和缺失数据。
+/// 每种列类型在 `ColumnDataArray` 中使用对应的 Rust 原生类型存储，
+/// 读写时与 `CellValue` 的规范表示（见 `coerce` 模块）相互转换。
+pub trait CellCodec: Clone + Sized {
+    /// 从已按列类型规整过的单元格值解码，`NULL` 或无法解码时返回 `None`
+    fn from_cell(value: &CellValue) -> Option<Self>;
+
+    /// 编码为规范表示的单元格值
+    fn to_cell(&self) -> CellValue;
+}
+
+macro_rules! impl_integer_codec {
+    ($($t:ty),*) => {
+        $(impl CellCodec for $t {
+            fn from_cell(value: &CellValue) -> Option<Self> {
+                match value {
+                    CellValue::Number(n) => n.as_i64().and_then(|v| <$t>::try_from(v).ok())
+                        .or_else(|| n.as_u64().and_then(|v| <$t>::try_from(v).ok())),
+                    _ => None,
+                }
+            }
+
+            fn to_cell(&self) -> CellValue {
+                CellValue::from(*self)
+            }
+        })*
+    };
+}
+
+impl_integer_codec!(i8, i16, i32, i64, u8, u16, u32, u64);
+
+impl CellCodec for bool {
+    fn from_cell(value: &CellValue) -> Option<Self> {
+        value.as_bool()
+    }
+
+    fn to_cell(&self) -> CellValue {
+        CellValue::Bool(*self)
+    }
+}
+
+impl CellCodec for f32 {
+    /// 只接受能以 `f32` 无损保存的值，与 `ColumnType::F32` 的校验规则相同
+    fn from_cell(value: &CellValue) -> Option<Self> {
+        value.as_f64().and_then(to_f32)
+    }
+
+    fn to_cell(&self) -> CellValue {
+        // 经由最短十进制表示转换，避免 0.1f32 变成 0.10000000149011612
+        self.to_string()
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .map_or(CellValue::Null, CellValue::Number)
+    }
+}
+
+impl CellCodec for f64 {
+    fn from_cell(value: &CellValue) -> Option<Self> {
+        value.as_f64()
+    }
+
+    fn to_cell(&self) -> CellValue {
+        serde_json::Number::from_f64(*self).map_or(CellValue::Null, CellValue::Number)
+    }
+}
+
+impl CellCodec for Decimal {
+    fn from_cell(value: &CellValue) -> Option<Self> {
+        to_decimal(value)
+    }
+
+    fn to_cell(&self) -> CellValue {
+        decimal_to_cell(*self)
+    }
+}
+
+impl CellCodec for String {
+    fn from_cell(value: &CellValue) -> Option<Self> {
+        value.as_str().map(str::to_string)
+    }
+
+    fn to_cell(&self) -> CellValue {
+        CellValue::String(self.clone())
+    }
+}
+
+impl CellCodec for NaiveDate {
+    fn from_cell(value: &CellValue) -> Option<Self> {
+        value.as_str().and_then(parse_date)
+    }
+
+    fn to_cell(&self) -> CellValue {
+        format_date(*self)
+    }
+}
+
+impl CellCodec for NaiveTime {
+    fn from_cell(value: &CellValue) -> Option<Self> {
+        value.as_str().and_then(parse_time)
+    }
+
+    fn to_cell(&self) -> CellValue {
+        format_time(*self)
+    }
+}
+
+impl CellCodec for NaiveDateTime {
+    fn from_cell(value: &CellValue) -> Option<Self> {
+        value.as_str().and_then(parse_datetime)
+    }
+
+    fn to_cell(&self) -> CellValue {
+        format_datetime(*self)
+    }
+}
+
+impl CellCodec for DateTime<FixedOffset> {
+    fn from_cell(value: &CellValue) -> Option<Self> {
+        value.as_str().and_then(parse_datetime_tz)
+    }
+
+    fn to_cell(&self) -> CellValue {
+        format_datetime_tz(*self)
+    }
+}
+
+impl CellCodec for Uuid {
+    fn from_cell(value: &CellValue) -> Option<Self> {
+        value.as_str().and_then(|s| Uuid::parse_str(s).ok())
+    }
+
+    fn to_cell(&self) -> CellValue {
+        CellValue::String(self.hyphenated().to_string())
+    }
+}
+
+impl CellCodec for Vec<u8> {
+    fn from_cell(value: &CellValue) -> Option<Self> {
+        value.as_str().and_then(|s| BASE64.decode(s).ok())
+    }
+
+    fn to_cell(&self) -> CellValue {
+        CellValue::String(BASE64.encode(self))
+    }
+}
+
+impl CellCodec for CellValue {
+    fn from_cell(value: &CellValue) -> Option<Self> {
+        Some(value.clone())
+    }
+
+    fn to_cell(&self) -> CellValue {
+        self.clone()
+    }
+}
+
+/// 可共享的列数据缓冲区
+///
+/// 底层存储为 `Arc<Vec<Option<T>>>`，外加偏移量和长度，因此 [`ColumnBuffer::slice`]
+/// 只复制引用计数而不复制数据。通过 `Deref` 可以直接当作 `&[Option<T>]` 扫描，
+/// 不需要为每行构造 `Vec<CellValue>`。
+///
+/// 修改操作采用写时复制：缓冲区被共享或只是切片视图时，先复制出独立的存储再修改，
+/// 不会影响其他持有者。
 ///
-/// ## 存储策略
+/// # 示例
 ///
-/// - **StringArray**: 存储字符串类型的列数据
-/// - **BoolArray**: 存储布尔类型的列数据
-/// - **NumberArray**: 存储所有数值类型的列数据（统一使用 `serde_json::Number`）
+/// ```rust
+/// use cmx_core::model::data::dataset::cds::ColumnBuffer;
+///
+/// let buffer: ColumnBuffer<i64> = vec![Some(1), None, Some(3), Some(4)].into();
+/// let tail = buffer.slice(2, 2).unwrap();
+/// assert_eq!(&*tail, &[Some(3), Some(4)]);
+/// assert_eq!(buffer.iter().flatten().sum::<i64>(), 8);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ColumnBuffer<T> {
+    data: Arc<Vec<Option<T>>>,
+    offset: usize,
+    len: usize,
+}
+
+impl<T> ColumnBuffer<T> {
+    /// 创建空缓冲区
+    pub fn new() -> Self {
+        Self { data: Arc::new(Vec::new()), offset: 0, len: 0 }
+    }
+
+    /// 返回从 `offset` 开始、长度为 `len` 的零拷贝切片，越界时返回 `None`
+    pub fn slice(&self, offset: usize, len: usize) -> Option<Self> {
+        if offset.checked_add(len)? > self.len {
+            return None;
+        }
+        Some(Self { data: Arc::clone(&self.data), offset: self.offset + offset, len })
+    }
+
+    /// 是否与其他缓冲区（例如切片）共享底层存储
+    pub fn is_shared(&self) -> bool {
+        Arc::strong_count(&self.data) > 1
+    }
+}
+
+impl<T: Clone> ColumnBuffer<T> {
+    /// 创建长度为 `len`、全部为 NULL 的缓冲区
+    pub fn nulls(len: usize) -> Self {
+        vec![None; len].into()
+    }
+
+    /// 获取可独占修改的底层存储，必要时复制
+    fn make_mut(&mut self) -> &mut Vec<Option<T>> {
+        if self.offset != 0 || self.len != self.data.len() {
+            self.data = Arc::new(self.data[self.offset..self.offset + self.len].to_vec());
+            self.offset = 0;
+        }
+        Arc::make_mut(&mut self.data)
+    }
+
+    pub fn push(&mut self, value: Option<T>) {
+        self.make_mut().push(value);
+        self.len += 1;
+    }
+
+    /// 在指定位置插入，`index` 大于长度时 panic
+    pub fn insert(&mut self, index: usize, value: Option<T>) {
+        self.make_mut().insert(index, value);
+        self.len += 1;
+    }
+
+    /// 移除指定位置的值，`index` 越界时 panic
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        let value = self.make_mut().remove(index);
+        self.len -= 1;
+        value
+    }
+
+    /// 设置指定位置的值，`index` 越界时 panic
+    pub fn set(&mut self, index: usize, value: Option<T>) {
+        self.make_mut()[index] = value;
+    }
+}
+
+impl<T> Default for ColumnBuffer<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Deref for ColumnBuffer<T> {
+    type Target = [Option<T>];
+
+    fn deref(&self) -> &Self::Target {
+        &self.data[self.offset..self.offset + self.len]
+    }
+}
+
+impl<T> From<Vec<Option<T>>> for ColumnBuffer<T> {
+    fn from(values: Vec<Option<T>>) -> Self {
+        let len = values.len();
+        Self { data: Arc::new(values), offset: 0, len }
+    }
+}
+
+impl<T> FromIterator<Option<T>> for ColumnBuffer<T> {
+    fn from_iter<I: IntoIterator<Item = Option<T>>>(iter: I) -> Self {
+        iter.into_iter().collect::<Vec<_>>().into()
+    }
+}
+
+/// 列数据存储枚举 - 针对 NULL 值优化的列式存储
+///
+/// `ColumnDataArray` 定义了列数据的存储方式，每个 `ColumnType` 对应一种变体，
+/// 使用该类型的 Rust 原生表示存储，例如 `I32` 列存储为 `i32`、`Decimal` 列存储为
+/// `rust_decimal::Decimal`、`Date` 列存储为 `chrono::NaiveDate`。
 ///
 /// ## NULL 值处理
 ///
@@ -324,12 +604,9 @@ impl Default for RowData {
 /// - `Some(value)` - 包含实际数据值
 /// - `None` - 表示 NULL 值或缺失数据
 ///
-/// ## 内存优化
+/// ## 零拷贝
 ///
-/// 相比于使用特殊标记值（如 0 表示 NULL），`Option<T>` 提供了：
-/// - **类型安全**：编译时保证 NULL 值处理正确
-/// - **清晰语义**：明确区分有效值和 NULL 值
-/// - **性能优化**：Rust 的 `Option<T>` 通常零成本抽象
+/// 每个变体内部都是 [`ColumnBuffer`]，切片只共享底层存储，不复制数据。
 ///
 /// ## 使用示例
 ///
@@ -341,43 +618,194 @@ impl Default for RowData {
 ///     Some("Alice".to_string()),
 ///     None,  // NULL 值
 ///     Some("Bob".to_string()),
-/// ]);
+/// ].into());
 ///
-/// // 创建数值数组
-/// let number_col = ColumnDataArray::NumberArray(vec![
-///     Some(1.into()),
-///     Some(2.into()),
-///     None,  // NULL 值
-/// ]);
+/// // 对数值列直接做列式扫描
+/// let amounts = ColumnDataArray::I64Array(vec![Some(1), Some(2), None].into());
+/// if let ColumnDataArray::I64Array(values) = &amounts {
+///     assert_eq!(values.iter().flatten().sum::<i64>(), 3);
+/// }
 /// ```
 #[derive(Debug, Clone)]
 pub enum ColumnDataArray {
-    /// 字符串数组 - 存储字符串类型的列数据
-    ///
-    /// 使用 `Vec<Option<String>>` 存储字符串值，支持 NULL 值。
-    /// 适用于存储文本数据、标识符等字符串类型字段。
-    StringArray(Vec<Option<String>>),
+    /// 布尔数组
+    BoolArray(ColumnBuffer<bool>),
+    /// 有符号整数数组
+    I8Array(ColumnBuffer<i8>),
+    I16Array(ColumnBuffer<i16>),
+    I32Array(ColumnBuffer<i32>),
+    I64Array(ColumnBuffer<i64>),
+    /// 无符号整数数组
+    U8Array(ColumnBuffer<u8>),
+    U16Array(ColumnBuffer<u16>),
+    U32Array(ColumnBuffer<u32>),
+    U64Array(ColumnBuffer<u64>),
+    /// 浮点数数组
+    F32Array(ColumnBuffer<f32>),
+    F64Array(ColumnBuffer<f64>),
+    /// 定点数数组
+    DecimalArray(ColumnBuffer<Decimal>),
+    /// 字符串数组
+    StringArray(ColumnBuffer<String>),
+    /// 日期数组
+    DateArray(ColumnBuffer<NaiveDate>),
+    /// 时间数组
+    TimeArray(ColumnBuffer<NaiveTime>),
+    /// 日期时间数组（不带时区）
+    DateTimeArray(ColumnBuffer<NaiveDateTime>),
+    /// 日期时间数组（带时区）
+    DateTimeTzArray(ColumnBuffer<DateTime<FixedOffset>>),
+    /// UUID 数组
+    UuidArray(ColumnBuffer<Uuid>),
+    /// 二进制数组
+    BinaryArray(ColumnBuffer<Vec<u8>>),
+    /// 任意 JSON 值数组
+    JsonArray(ColumnBuffer<CellValue>),
+    /// 多语言字符串数组，列类型为 `String`，值为字符串或以语言标识为键的对象，见 [`super::lang`]
+    LocalizedArray(ColumnBuffer<CellValue>),
+}
 
-    /// 布尔数组 - 存储布尔类型的列数据
-    ///
-    /// 使用 `Vec<Option<bool>>` 存储布尔值，支持 NULL 值。
-    /// 适用于存储 true/false 标志、开关状态等布尔类型字段。
-    BoolArray(Vec<Option<bool>>),
+/// 对 `ColumnDataArray` 的每个变体执行同一段泛型代码
+macro_rules! with_buffer {
+    ($array:expr, $buffer:ident => $body:expr) => {
+        match $array {
+            ColumnDataArray::BoolArray($buffer) => $body,
+            ColumnDataArray::I8Array($buffer) => $body,
+            ColumnDataArray::I16Array($buffer) => $body,
+            ColumnDataArray::I32Array($buffer) => $body,
+            ColumnDataArray::I64Array($buffer) => $body,
+            ColumnDataArray::U8Array($buffer) => $body,
+            ColumnDataArray::U16Array($buffer) => $body,
+            ColumnDataArray::U32Array($buffer) => $body,
+            ColumnDataArray::U64Array($buffer) => $body,
+            ColumnDataArray::F32Array($buffer) => $body,
+            ColumnDataArray::F64Array($buffer) => $body,
+            ColumnDataArray::DecimalArray($buffer) => $body,
+            ColumnDataArray::StringArray($buffer) => $body,
+            ColumnDataArray::DateArray($buffer) => $body,
+            ColumnDataArray::TimeArray($buffer) => $body,
+            ColumnDataArray::DateTimeArray($buffer) => $body,
+            ColumnDataArray::DateTimeTzArray($buffer) => $body,
+            ColumnDataArray::UuidArray($buffer) => $body,
+            ColumnDataArray::BinaryArray($buffer) => $body,
+            ColumnDataArray::JsonArray($buffer) => $body,
+            ColumnDataArray::LocalizedArray($buffer) => $body,
+        }
+    };
+}
 
-    /// 数值数组 - 存储所有数值类型的列数据
-    ///
-    /// 使用 `Vec<Option<serde_json::Number>>` 统一存储所有数值类型。
-    /// 支持整数（i8, i16, i32, i64, u8, u16, u32, u64）和浮点数（f32, f64）。
-    /// 这是最复杂的数组类型，因为需要处理多种数值类型的统一存储。
-    NumberArray(Vec<Option<serde_json::Number>>),
+/// 同 `with_buffer!`，但结果重新包装为相同的变体
+macro_rules! map_buffer {
+    ($array:expr, $buffer:ident => $body:expr) => {
+        match $array {
+            ColumnDataArray::BoolArray($buffer) => ColumnDataArray::BoolArray($body),
+            ColumnDataArray::I8Array($buffer) => ColumnDataArray::I8Array($body),
+            ColumnDataArray::I16Array($buffer) => ColumnDataArray::I16Array($body),
+            ColumnDataArray::I32Array($buffer) => ColumnDataArray::I32Array($body),
+            ColumnDataArray::I64Array($buffer) => ColumnDataArray::I64Array($body),
+            ColumnDataArray::U8Array($buffer) => ColumnDataArray::U8Array($body),
+            ColumnDataArray::U16Array($buffer) => ColumnDataArray::U16Array($body),
+            ColumnDataArray::U32Array($buffer) => ColumnDataArray::U32Array($body),
+            ColumnDataArray::U64Array($buffer) => ColumnDataArray::U64Array($body),
+            ColumnDataArray::F32Array($buffer) => ColumnDataArray::F32Array($body),
+            ColumnDataArray::F64Array($buffer) => ColumnDataArray::F64Array($body),
+            ColumnDataArray::DecimalArray($buffer) => ColumnDataArray::DecimalArray($body),
+            ColumnDataArray::StringArray($buffer) => ColumnDataArray::StringArray($body),
+            ColumnDataArray::DateArray($buffer) => ColumnDataArray::DateArray($body),
+            ColumnDataArray::TimeArray($buffer) => ColumnDataArray::TimeArray($body),
+            ColumnDataArray::DateTimeArray($buffer) => ColumnDataArray::DateTimeArray($body),
+            ColumnDataArray::DateTimeTzArray($buffer) => ColumnDataArray::DateTimeTzArray($body),
+            ColumnDataArray::UuidArray($buffer) => ColumnDataArray::UuidArray($body),
+            ColumnDataArray::BinaryArray($buffer) => ColumnDataArray::BinaryArray($body),
+            ColumnDataArray::JsonArray($buffer) => ColumnDataArray::JsonArray($body),
+            ColumnDataArray::LocalizedArray($buffer) => ColumnDataArray::LocalizedArray($body),
+        }
+    };
+}
+
+/// 解码已按列类型校验过的单元格值，NULL 解码为 `None`
+fn decode<T: CellCodec>(value: CellValue) -> Result<Option<T>, CellValue> {
+    if value.is_null() {
+        return Ok(None);
+    }
+    T::from_cell(&value).map(Some).ok_or(value)
 }
 
 impl ColumnDataArray {
+    /// 创建指定列类型、长度为 `len` 且全部为 NULL 的数组
+    pub fn nulls(column_type: ColumnType, len: usize) -> Self {
+        match column_type {
+            ColumnType::Bool => ColumnDataArray::BoolArray(ColumnBuffer::nulls(len)),
+            ColumnType::I8 => ColumnDataArray::I8Array(ColumnBuffer::nulls(len)),
+            ColumnType::I16 => ColumnDataArray::I16Array(ColumnBuffer::nulls(len)),
+            ColumnType::I32 => ColumnDataArray::I32Array(ColumnBuffer::nulls(len)),
+            ColumnType::I64 => ColumnDataArray::I64Array(ColumnBuffer::nulls(len)),
+            ColumnType::U8 => ColumnDataArray::U8Array(ColumnBuffer::nulls(len)),
+            ColumnType::U16 => ColumnDataArray::U16Array(ColumnBuffer::nulls(len)),
+            ColumnType::U32 => ColumnDataArray::U32Array(ColumnBuffer::nulls(len)),
+            ColumnType::U64 => ColumnDataArray::U64Array(ColumnBuffer::nulls(len)),
+            ColumnType::F32 => ColumnDataArray::F32Array(ColumnBuffer::nulls(len)),
+            ColumnType::F64 => ColumnDataArray::F64Array(ColumnBuffer::nulls(len)),
+            ColumnType::Decimal => ColumnDataArray::DecimalArray(ColumnBuffer::nulls(len)),
+            ColumnType::String => ColumnDataArray::StringArray(ColumnBuffer::nulls(len)),
+            ColumnType::Date => ColumnDataArray::DateArray(ColumnBuffer::nulls(len)),
+            ColumnType::Time => ColumnDataArray::TimeArray(ColumnBuffer::nulls(len)),
+            ColumnType::DateTime => ColumnDataArray::DateTimeArray(ColumnBuffer::nulls(len)),
+            ColumnType::DateTimeTz => ColumnDataArray::DateTimeTzArray(ColumnBuffer::nulls(len)),
+            ColumnType::Uuid => ColumnDataArray::UuidArray(ColumnBuffer::nulls(len)),
+            ColumnType::Binary => ColumnDataArray::BinaryArray(ColumnBuffer::nulls(len)),
+            ColumnType::Json => ColumnDataArray::JsonArray(ColumnBuffer::nulls(len)),
+        }
+    }
+
+    /// 创建长度为 `len` 且全部为 NULL 的多语言字符串数组
+    pub fn localized(len: usize) -> Self {
+        ColumnDataArray::LocalizedArray(ColumnBuffer::nulls(len))
+    }
+
+    /// 是否为多语言字符串数组
+    pub fn is_localized(&self) -> bool {
+        matches!(self, ColumnDataArray::LocalizedArray(_))
+    }
+
+    /// 数组对应的列类型
+    pub fn column_type(&self) -> ColumnType {
+        match self {
+            ColumnDataArray::BoolArray(_) => ColumnType::Bool,
+            ColumnDataArray::I8Array(_) => ColumnType::I8,
+            ColumnDataArray::I16Array(_) => ColumnType::I16,
+            ColumnDataArray::I32Array(_) => ColumnType::I32,
+            ColumnDataArray::I64Array(_) => ColumnType::I64,
+            ColumnDataArray::U8Array(_) => ColumnType::U8,
+            ColumnDataArray::U16Array(_) => ColumnType::U16,
+            ColumnDataArray::U32Array(_) => ColumnType::U32,
+            ColumnDataArray::U64Array(_) => ColumnType::U64,
+            ColumnDataArray::F32Array(_) => ColumnType::F32,
+            ColumnDataArray::F64Array(_) => ColumnType::F64,
+            ColumnDataArray::DecimalArray(_) => ColumnType::Decimal,
+            ColumnDataArray::StringArray(_) => ColumnType::String,
+            ColumnDataArray::DateArray(_) => ColumnType::Date,
+            ColumnDataArray::TimeArray(_) => ColumnType::Time,
+            ColumnDataArray::DateTimeArray(_) => ColumnType::DateTime,
+            ColumnDataArray::DateTimeTzArray(_) => ColumnType::DateTimeTz,
+            ColumnDataArray::UuidArray(_) => ColumnType::Uuid,
+            ColumnDataArray::BinaryArray(_) => ColumnType::Binary,
+            ColumnDataArray::JsonArray(_) => ColumnType::Json,
+            ColumnDataArray::LocalizedArray(_) => ColumnType::String,
+        }
+    }
+
+    /// 数组长度
+    pub fn len(&self) -> usize {
+        with_buffer!(self, buffer => buffer.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
     /// 检查指定位置的值是否为 NULL
     ///
-    /// 根据索引检查指定位置是否存储了 NULL 值或缺失数据。
-    /// 这个方法提供了统一的 NULL 值检查接口。
-    ///
     /// # 参数
     ///
     /// * `index` - 要检查的位置索引，从 0 开始
@@ -397,35 +825,17 @@ impl ColumnDataArray {
     ///
     /// ```rust
     /// # use cmx_core::model::data::dataset::cds::ColumnDataArray;
-    /// # let string_col = ColumnDataArray::StringArray(vec![Some("Alice".to_string()), None]);
+    /// # let string_col = ColumnDataArray::StringArray(vec![Some("Alice".to_string()), None].into());
     ///
     /// assert_eq!(string_col.is_null(0), false); // 包含 "Alice"
     /// assert_eq!(string_col.is_null(1), true);  // NULL 值
     /// ```
     pub fn is_null(&self, index: usize) -> bool {
-        match self {
-            ColumnDataArray::BoolArray(vec) => vec[index].is_none(),
-            ColumnDataArray::StringArray(vec) => vec[index].is_none(),
-            // 数值类型返回 false，因为 0 是有效值
-            ColumnDataArray::NumberArray(vec) => vec[index].is_none(),
-        }
+        with_buffer!(self, buffer => buffer[index].is_none())
     }
 
     /// 将指定位置的值设置为 NULL
     ///
-    /// 将指定索引位置的值设置为 NULL，清除该位置存储的任何有效数据。
-    /// 这个方法提供了统一的 NULL 值设置接口。
-    ///
-    /// # 参数
-    ///
-    /// * `index` - 要设置的位置索引，从 0 开始
-    ///
-    /// # 行为说明
-    ///
-    /// - 布尔和字符串类型：将 `Some(value)` 改为 `None`
-    /// - 数值类型：将 `Some(number)` 改为 `None`
-    /// - 原有的值会被丢弃
-    ///
     /// # 注意事项
     ///
     /// - 索引越界会导致 panic
@@ -435,7 +845,7 @@ impl ColumnDataArray {
     ///
     /// ```rust
     /// # use cmx_core::model::data::dataset::cds::ColumnDataArray;
-    /// # let mut string_col = ColumnDataArray::StringArray(vec![Some("Alice".to_string())]);
+    /// # let mut string_col = ColumnDataArray::StringArray(vec![Some("Alice".to_string())].into());
     ///
     /// assert_eq!(string_col.is_null(0), false);
     ///
@@ -443,26 +853,75 @@ impl ColumnDataArray {
     /// assert_eq!(string_col.is_null(0), true);
     /// ```
     pub fn set_null(&mut self, index: usize) {
+        with_buffer!(self, buffer => buffer.set(index, None))
+    }
+
+    /// 以 `CellValue` 的规范表示读取指定位置的值，越界时返回 `None`
+    pub fn get(&self, index: usize) -> Option<CellValue> {
+        with_buffer!(self, buffer => buffer.get(index).map(|v| v.as_ref().map_or(CellValue::Null, CellCodec::to_cell)))
+    }
+
+    /// 按列类型校验并追加一个值
+    ///
+    /// # 返回值
+    ///
+    /// - `Ok(())` - 追加成功
+    /// - `Err(value)` - 值无法转换为该列类型，数组保持不变
+    pub fn push(&mut self, value: CellValue) -> Result<(), CellValue> {
+        let value = self.coerce(value)?;
+        with_buffer!(self, buffer => buffer.push(decode(value)?));
+        Ok(())
+    }
+
+    /// 按列类型校验并在指定位置插入一个值，`index` 大于长度时 panic
+    pub fn insert(&mut self, index: usize, value: CellValue) -> Result<(), CellValue> {
+        let value = self.coerce(value)?;
+        with_buffer!(self, buffer => buffer.insert(index, decode(value)?));
+        Ok(())
+    }
+
+    /// 按列类型校验并设置指定位置的值，`index` 越界时 panic
+    pub fn set(&mut self, index: usize, value: CellValue) -> Result<(), CellValue> {
+        let value = self.coerce(value)?;
+        with_buffer!(self, buffer => buffer.set(index, decode(value)?));
+        Ok(())
+    }
+
+    /// 按列类型校验并转换单元格值，多语言数组逐个校验各语言的值
+    fn coerce(&self, value: CellValue) -> Result<CellValue, CellValue> {
         match self {
-            ColumnDataArray::BoolArray(vec) => vec[index] = None,
-            ColumnDataArray::StringArray(vec) => vec[index] = None,
-            // 数值类型设置为 NULL
-            ColumnDataArray::NumberArray(vec) => vec[index] = None,
+            ColumnDataArray::LocalizedArray(_) => coerce_localized(ColumnType::String, value),
+            _ => self.column_type().coerce(value),
         }
     }
+
+    /// 移除指定位置的值并以 `CellValue` 返回，`index` 越界时 panic
+    pub fn remove(&mut self, index: usize) -> CellValue {
+        with_buffer!(self, buffer => buffer.remove(index).map_or(CellValue::Null, |v| v.to_cell()))
+    }
+
+    /// 返回零拷贝切片，越界时返回 `None`
+    pub fn slice(&self, offset: usize, len: usize) -> Option<Self> {
+        Some(map_buffer!(self, buffer => buffer.slice(offset, len)?))
+    }
+
+    /// 依次以 `CellValue` 的规范表示读取所有值
+    pub fn iter_cells(&self) -> impl Iterator<Item = CellValue> + '_ {
+        (0..self.len()).map(|index| self.get(index).unwrap_or(CellValue::Null))
+    }
 }
 
 /// 列式存储的数据集结构
 ///
 /// `ColDataSet` 是基于列存储的数据集实现，提供了高效的列级数据操作和分析功能。
-/// 数据按列组织，每列使用专门的数组存储，能够提供优秀的列级查询和批量操作性能。
+/// 数据按列组织，每列使用对应类型的数组存储，能够提供优秀的列级查询和批量操作性能。
 ///
 /// ## 核心特性
 ///
 /// - **列式存储**：数据按列连续存储，便于列级别的向量化操作
-/// - **内存效率**：适合大数据集的列级分析和聚合计算
+/// - **类型化数组**：每个 `ColumnType` 使用原生类型存储，扫描时无需逐行构造 `CellValue`
+/// - **零拷贝切片**：[`ColDataSet::slice`] 只共享底层存储
 /// - **NULL 值优化**：使用 `Option<T>` 显式处理缺失数据
-/// - **类型安全**：严格的类型检查和数据完整性验证
 /// - **层次结构**：支持行级别的子数据集嵌套
 /// - **数据一致性**：自动验证和维护所有列的长度一致性
 ///
@@ -474,46 +933,25 @@ impl ColumnDataArray {
 ///     schema: {                        // 列模式定义
 ///         "product_id" => I32,
 ///         "product_name" => String,
-///         "price" => F64,
+///         "price" => Decimal,
 ///         "in_stock" => Bool,
 ///     },
+///     column_names: ["product_id", "product_name", "price", "in_stock"],  // 列顺序
 ///     columns: {                       // 列数据存储
-///         "product_id" => NumberArray([Some(1), Some(2), Some(3)]),
+///         "product_id" => I32Array([Some(1), Some(2), Some(3)]),
 ///         "product_name" => StringArray([Some("Apple"), Some("Banana"), None]),
-///         "price" => NumberArray([Some(1.50), Some(0.75), Some(2.00)]),
+///         "price" => DecimalArray([Some(1.50), Some(0.75), Some(2.00)]),
 ///         "in_stock" => BoolArray([Some(true), Some(false), Some(true)]),
 ///     },
 ///     children: [None, None, Some({...})]  // 每行的子数据集（可选）
 /// }
 /// ```
 ///
-/// ## 存储优势
-///
-/// ### 相比行式存储的优势：
-/// - **列级查询**：直接访问整个列，适合聚合和分析操作
-/// - **压缩友好**：相同类型的连续存储有利于压缩算法
-/// - **向量化优化**：便于 SIMD 指令优化
-/// - **缓存效率**：连续内存访问模式对 CPU 缓存更友好
-///
-/// ### 适用场景：
-/// - **数据仓库**：OLAP 分析、报表生成、大数据处理
-/// - **科学计算**：数值计算、统计分析、机器学习特征处理
-/// - **时间序列**：时序数据分析、趋势计算
-/// - **日志分析**：大规模日志数据的列式聚合分析
+/// ## 与 RowDataSet 的转换
 ///
-/// ## 设计权衡
-///
-/// ### 优势：
-/// - ✅ 优秀的列级查询性能
-/// - ✅ 内存访问模式优化
-/// - ✅ 便于数据压缩和编码
-/// - ✅ 适合分析型工作负载
-///
-/// ### 权衡：
-/// - ⚠️ 行级插入/更新相对较慢
-/// - ⚠️ 单行查询需要组合多个列
-/// - ⚠️ 内存布局较为复杂
-/// - ⚠️ 不适合频繁的单行操作
+/// `RowDataSet` 通过 `TryFrom` 转换为 `ColDataSet`，反向通过 `From` 转换。列顺序、列类型、
+/// 多语言列（存储为 [`ColumnDataArray::LocalizedArray`]）、单元格值（规范表示）和嵌套子数据集
+/// 都会保留；`RowDataSet` 的变更跟踪状态不会保留。
 ///
 /// ## 内存布局
 ///
@@ -524,50 +962,33 @@ impl ColumnDataArray {
 ///
 /// ## 线程安全
 ///
-/// 当前实现不是线程安全的。如果需要在多线程环境中使用，
-/// 需要额外的同步机制或使用线程安全的变体。
+/// 列数据通过 `Arc` 共享，`ColDataSet` 可以在线程间发送和共享只读引用；
+/// 并发修改需要额外的同步机制。
 #[derive(Debug, Clone)]
 pub struct ColDataSet {
     /// 数据集唯一标识符
-    ///
-    /// 用于区分不同的数据集实例，在整个系统中应该是唯一的。
-    /// 支持字母、数字、下划线等字符，通常用于数据集的逻辑标识。
     pub dataset_id: String,
 
     /// 列模式定义：列名 -> 列类型
-    ///
-    /// 存储数据集的模式信息，将列名映射到对应的数据类型。
-    /// 这是数据完整性验证和类型检查的基础。
-    /// 一旦数据集创建后，模式通常是只读的。
     pub schema: HashMap<String, ColumnType>,
 
+    /// 列名列表，按添加顺序排列
+    pub column_names: Vec<String>,
+
     /// 列数据存储：列名 -> 列数据数组
     ///
-    /// 核心数据存储结构，每列使用专门的数组存储。
     /// 所有列的数组长度必须保持一致，这是数据一致性的关键。
     pub columns: HashMap<String, ColumnDataArray>,
 
     /// 子数据集存储：每行可选择性包含子数据集
     ///
-    /// 为每一行数据提供可选的子数据集支持，实现层次化数据结构。
-    /// 数组长度与行数一致，每个元素都是可选的子数据集映射。
-    /// 支持复杂的数据关系和嵌套结构。
-    pub children: Vec<Option<HashMap<String, ColDataSet>>>,
+    /// 长度与行数一致，同样支持零拷贝切片。
+    pub children: ColumnBuffer<HashMap<String, ColDataSet>>,
 }
 
 impl ColDataSet {
     /// 创建新的列式数据集实例
     ///
-    /// 使用指定的数据集ID创建空的列式数据集。初始状态下不包含任何列定义、数据和子数据集。
-    ///
-    /// # 参数
-    ///
-    /// * `dataset_id` - 数据集的唯一标识符字符串
-    ///
-    /// # 返回值
-    ///
-    /// 返回新创建的空数据集实例
-    ///
     /// # 示例
     ///
     /// ```rust
@@ -582,119 +1003,52 @@ impl ColDataSet {
         Self {
             dataset_id,
             schema: HashMap::new(),
+            column_names: Vec::new(),
             columns: HashMap::new(),
-            children: Vec::new(),
+            children: ColumnBuffer::new(),
         }
     }
 
     /// 获取数据集的行数量
     ///
-    /// 返回当前数据集中存储的数据行数量。由于列式存储的特性，
-    /// 所有列的长度都必须保持一致，因此可以从任意一列获取长度。
-    ///
-    /// # 返回值
-    ///
-    /// 返回行数量：
-    /// - 如果数据集包含列，返回列的长度
-    /// - 如果数据集为空（没有列），返回 0
-    ///
-    /// # 实现说明
-    ///
-    /// 这个方法利用了列式存储的数据一致性特性：
-    /// 所有列的数组长度都相同，因此只需要检查任意一列即可。
-    /// 这种设计保证了数据完整性，同时提供了高效的长度查询。
+    /// 行数以子数据集数组的长度为准，各列的长度由 `validate_row_counts` 保证一致。
     ///
     /// # 示例
     ///
     /// ```rust
-    /// # use cmx_core::model::data::dataset::{cds::ColDataSet, ColumnType};
+    /// # use cmx_core::model::data::dataset::{cds::{ColDataSet, RowData}, ColumnType};
     /// # use cmx_core::model::data::cell::CellValue;
     /// # let mut dataset = ColDataSet::new("test".to_string());
     /// # dataset.add_column("id".to_string(), ColumnType::I32).unwrap();
-    /// # let mut row = dataset::cds::RowData::new();
+    /// # let mut row = RowData::new();
     /// # row.insert("id".to_string(), CellValue::Number(1.into()));
     /// # dataset.append_row(&row).unwrap();
     ///
     /// assert_eq!(dataset.row_count(), 1);
     /// ```
-    ///
-    /// # 性能特性
-    ///
-    /// - **时间复杂度**: O(1) - HashMap 查找 + 数组长度获取
-    /// - **空间复杂度**: O(1) - 不分配额外内存
     pub fn row_count(&self) -> usize {
-        // 从任意一列获取长度即可，因为所有列的长度都是相同的
-        self.columns.values().next()
-            .map(|col| match col {
-                ColumnDataArray::BoolArray(vec) => vec.len(),
-                ColumnDataArray::StringArray(vec) => vec.len(),
-                ColumnDataArray::NumberArray(vec) => vec.len(),
-                // ColumnDataArray::CHILD(vec) => vec.len(),
-            })
-            .unwrap_or(0) // 如果没有列，返回 0
+        self.children.len()
     }
 
-    /// 验证所有列的长度是否一致
-    ///
-    /// 检查数据集中的所有列是否具有相同的长度，以及子数据集数组的长度是否正确。
-    /// 这是维护数据完整性的关键方法，确保列式存储的一致性。
-    ///
-    /// # 验证内容
-    ///
-    /// 1. **列长度一致性**：所有列的数组长度必须相同
-    /// 2. **子数据集长度**：子数据集数组长度必须等于行数
-    /// 3. **数据完整性**：确保没有出现长度不匹配的情况
+    /// 获取列数量
+    pub fn column_count(&self) -> usize {
+        self.column_names.len()
+    }
+
+    /// 验证所有列的长度是否与行数一致
     ///
     /// # 返回值
     ///
-    /// 返回 `Result<(), ColDataSetError>`：
-    /// - `Ok(())` - 所有长度都一致，数据完整性良好
-    /// - `Err(ColDataSetError::InconsistentLength)` - 发现长度不一致的情况
-    ///
-    /// # 错误情况
-    ///
-    /// - 如果某一列的长度与其他列不一致
-    /// - 如果子数据集数组长度与行数不匹配
-    /// - 如果数据结构出现损坏
-    ///
-    /// # 设计理念
-    ///
-    /// 列式存储的核心要求是所有列必须保持相同的长度。
-    /// 这个方法在每次数据修改操作后都会被调用，确保数据一致性。
-    /// 这种设计避免了行式存储中可能出现的稀疏矩阵问题。
-    ///
-    /// # 性能考虑
-    ///
-    /// - 在大数据集上，这个方法会遍历所有列
-    /// - 建议在调试模式下频繁调用，在生产环境中按需调用
+    /// - `Ok(())` - 所有长度都一致
+    /// - `Err(ColDataSetError::InconsistentLength)` - 发现长度不一致的列
     fn validate_row_counts(&self) -> Result<(), ColDataSetError> {
-        if self.columns.is_empty() {
-            return Ok(());
-        }
-
-        let expected_len = self.row_count();
-
-        // Also validate children length
-        if self.children.len() != expected_len {
-            return Err(ColDataSetError::InconsistentLength {
-                column: "children".to_string(),
-                expected: expected_len,
-                actual: self.children.len(),
-            });
-        }
-
-        for (col_name, col_data) in &self.columns {
-            let actual_len = match col_data {
-                ColumnDataArray::BoolArray(vec) => vec.len(),
-                ColumnDataArray::StringArray(vec) => vec.len(),
-                ColumnDataArray::NumberArray(vec) => vec.len(),
-                // ColumnDataArray::CHILD(vec) => vec.len(),
-            };
-            if actual_len != expected_len {
+        let expected = self.row_count();
+        for (column, data) in &self.columns {
+            if data.len() != expected {
                 return Err(ColDataSetError::InconsistentLength {
-                    column: col_name.clone(),
-                    expected: expected_len,
-                    actual: actual_len,
+                    column: column.clone(),
+                    expected,
+                    actual: data.len(),
                 });
             }
         }
@@ -703,37 +1057,14 @@ impl ColDataSet {
 
     /// 添加列定义到数据集模式
     ///
-    /// 为数据集添加新的列定义，包括列名和数据类型。新添加的列会被初始化为适当的默认值数组。
-    /// 这是构建数据集模式的核心方法，必须在添加数据之前完成。
-    ///
-    /// # 参数
-    ///
-    /// * `name` - 列的唯一名称标识符，不能与现有列重复
-    /// * `col_type` - 列的数据类型，定义了该列可以存储的数据类型
+    /// 新列会为现有所有行填充 NULL 值。
     ///
     /// # 返回值
     ///
     /// 返回 `Result<(), ColDataSetError>`：
     /// - `Ok(())` - 列添加成功
-    /// - `Err(ColDataSetError::InconsistentLength)` - 如果数据一致性检查失败（通常不会发生）
-    ///
-    /// # 行为说明
-    ///
-    /// 1. **创建列数据数组**：根据列类型创建相应长度的数组，初始值为 NULL
-    /// 2. **更新模式定义**：将列名和类型添加到模式映射中
-    /// 3. **验证一致性**：确保所有列长度保持一致
-    ///
-    /// # 类型映射规则
-    ///
-    /// - `Bool` → `BoolArray` (布尔数组)
-    /// - `String` → `StringArray` (字符串数组)
-    /// - 所有数字类型 → `NumberArray` (数值数组)
-    ///
-    /// # 重要限制
-    ///
-    /// - **列名唯一性**：不能添加同名列（当前实现未检查，建议后续改进）
-    /// - **顺序相关性**：建议在添加数据前完成所有列定义
-    /// - **不可逆操作**：添加列后无法直接移除
+    /// - `Err(ColDataSetError::DuplicateColumn)` - 同名列已存在
+    /// - `Err(ColDataSetError::InconsistentLength)` - 数据一致性检查失败
     ///
     /// # 示例
     ///
@@ -742,448 +1073,271 @@ impl ColDataSet {
     ///
     /// let mut dataset = ColDataSet::new("products".to_string());
     ///
-    /// // 添加多列定义
     /// dataset.add_column("id".to_string(), ColumnType::I32).unwrap();
     /// dataset.add_column("name".to_string(), ColumnType::String).unwrap();
-    /// dataset.add_column("price".to_string(), ColumnType::F64).unwrap();
+    /// dataset.add_column("price".to_string(), ColumnType::Decimal).unwrap();
     /// dataset.add_column("in_stock".to_string(), ColumnType::Bool).unwrap();
     ///
     /// assert_eq!(dataset.schema.len(), 4);
+    /// assert!(dataset.add_column("id".to_string(), ColumnType::I64).is_err());
     /// ```
-    ///
-    /// # 内存影响
-    ///
-    /// 添加列会为现有所有行创建新的数组元素。如果数据集很大，
-    /// 这个操作可能会消耗显著的内存。
     pub fn add_column(&mut self, name: String, col_type: ColumnType) -> Result<(), ColDataSetError> {
-        let current_row_count = self.row_count();
-
-        // 根据类型创建对应的空向量，并填充到当前行数
-        let column_data = match col_type {
-            ColumnType::Bool => ColumnDataArray::BoolArray(vec![None; current_row_count]),
-            ColumnType::String => ColumnDataArray::StringArray(vec![None; current_row_count]),
-            ColumnType::I8 | ColumnType::I16 | ColumnType::I32 | ColumnType::I64 |
-            ColumnType::U8 | ColumnType::U16 | ColumnType::U32 | ColumnType::U64 |
-            ColumnType::F32 | ColumnType::F64 => ColumnDataArray::NumberArray(vec![None; current_row_count]),
-        };
+        self.validate_row_counts()?;
+        if self.schema.contains_key(&name) {
+            return Err(ColDataSetError::DuplicateColumn(name));
+        }
 
+        self.columns.insert(name.clone(), ColumnDataArray::nulls(col_type, self.row_count()));
         self.schema.insert(name.clone(), col_type);
-        self.columns.insert(name, column_data);
-
-        self.validate_row_counts()
+        self.column_names.push(name);
+        Ok(())
     }
 
-    /// 在数据集末尾添加一行数据
-    ///
-    /// 将包含完整列值的数据行添加到数据集的末尾。这是列式存储的核心数据插入方法。
-    /// 数据会被分散存储到对应的列数组中，实现了列式存储的本质。
-    ///
-    /// # 参数
+    /// 在数据集末尾追加一行
     ///
-    /// * `row` - 包含列值的行数据引用，必须包含数据集定义的所有列
+    /// 行中缺少的列按 NULL 处理，多余的键会被忽略。值会先按列类型校验并转换
+    /// （见 [`ColumnType::coerce`]），再解码为列的原生类型。
     ///
     /// # 返回值
     ///
     /// 返回 `Result<(), ColDataSetError>`：
-    /// - `Ok(())` - 行数据添加成功
-    /// - `Err(ColDataSetError::TypeMismatch)` - 数据类型与列定义不匹配
+    /// - `Ok(())` - 追加成功
+    /// - `Err(ColDataSetError::TypeMismatch)` - 某个值无法转换为对应列的类型，数据集保持不变
     /// - `Err(ColDataSetError::InconsistentLength)` - 数据一致性检查失败
-    ///
-    /// # 数据转换过程
-    ///
-    /// ```text
-    /// 输入: RowData { values: {"id": Number(1), "name": String("Alice")} }
-    ///
-    /// 处理后:
-    /// columns["id"] NumberArray: [...Some(1)]
-    /// columns["name"] StringArray: [...Some("Alice")]
-    /// ```
-    ///
-    /// # 类型匹配规则
-    ///
-    /// | RowData 值类型 | 列数组类型 | 处理方式 |
-    /// |---------------|-----------|---------|
-    /// | `CellValue::Bool` | `BoolArray` | 直接存储 |
-    /// | `CellValue::String` | `StringArray` | 直接存储 |
-    /// | `CellValue::Number` | `NumberArray` | 直接存储 |
-    /// | `CellValue::Null` | 任何类型 | 存储为 `None` |
-    /// | 其他 | - | 类型不匹配错误 |
-    ///
-    /// # 子数据集处理
-    ///
-    /// - 如果行数据包含子数据集，会被完整复制到数据集的子数据集数组中
-    /// - 如果行数据没有子数据集，对应位置存储 `None`
-    ///
-    /// # 性能特点
-    ///
-    /// - **时间复杂度**: O(C) - C 为列数量，需要更新每一列
-    /// - **空间复杂度**: O(C) - 每列添加一个元素
-    /// - **内存操作**: 高效的列式追加，避免了行式存储的内存重新分配
-    ///
-    /// # 示例
-    ///
-    /// ```rust
-    /// # use cmx_core::model::data::dataset::{cds::{ColDataSet, RowData}, ColumnType};
-    /// # use cmx_core::model::data::cell::CellValue;
-    /// # let mut dataset = ColDataSet::new("users".to_string());
-    /// # dataset.add_column("id".to_string(), ColumnType::I32).unwrap();
-    /// # dataset.add_column("name".to_string(), ColumnType::String).unwrap();
-    ///
-    /// // 创建行数据
-    /// let mut row = RowData::new();
-    /// row.insert("id".to_string(), CellValue::Number(1.into()));
-    /// row.insert("name".to_string(), CellValue::String("Alice".to_string()));
-    ///
-    /// // 添加到数据集
-    /// dataset.append_row(&row).unwrap();
-    /// assert_eq!(dataset.row_count(), 1);
-    /// ```
-    ///
-    /// # 错误处理
-    ///
-    /// - **缺失列**: 如果行数据缺少某个列，会使用 `CellValue::Null` 填充
-    /// - **类型不匹配**: 如果值的类型与列定义不匹配，返回类型错误
-    /// - **数据损坏**: 如果操作导致数据不一致，会在验证阶段检测出来
-    ///
-    /// # 设计优势
-    ///
-    /// 这种列式插入方式的优势：
-    /// - ✅ **缓存友好**: 连续内存写入
-    /// - ✅ **向量化就绪**: 便于后续的 SIMD 操作
-    /// - ✅ **压缩优化**: 有利于列级别的压缩算法
     pub fn append_row(&mut self, row: &RowData) -> Result<(), ColDataSetError> {
-        self.validate_row_counts()?;
-
-        // 执行添加操作
-        for (col_name, col_data) in &mut self.columns {
-            let value = row.get(col_name).cloned().unwrap_or(CellValue::Null);
-
-            match (col_data, value) {
-                // Bool 和 String 保持不变
-                (ColumnDataArray::BoolArray(vec), CellValue::Bool(v)) => {
-                    vec.push(Some(v));
-                }
-                (ColumnDataArray::BoolArray(vec), CellValue::Null) => {
-                    vec.push(None);
-                }
-                (ColumnDataArray::StringArray(vec), CellValue::String(v)) => {
-                    vec.push(Some(v));
-                }
-                (ColumnDataArray::StringArray(vec), CellValue::Null) => {
-                    vec.push(None);
-                }
-
-                // 数值类型的处理：从 Number 中提取值，NULL 设为 0
-                (ColumnDataArray::NumberArray(vec), CellValue::Number(v)) => {
-                    vec.push(Some(v.clone()));
-                }
-                (ColumnDataArray::NumberArray(vec), CellValue::Null) => {
-                    vec.push(None);
-                }
-
-                // ... 其他数值类型类似处理
-
-                // 类型不匹配的情况
-                (col_data, value) => {
-                    return Err(ColDataSetError::TypeMismatch {
-                        column: col_name.clone(),
-                        expected: format!("{:?}", col_data),
-                        actual: format!("{:?}", value),
-                    });
-                }
-            }
-        }
-
-        // Add children data
-        if let Some(row_children) = &row.children {
-            self.children.push(Some(row_children.clone()));
-        } else {
-            self.children.push(None);
-        }
-
-        self.validate_row_counts()
+        self.insert_row(self.row_count(), row)
     }
 
-    /// 获取指定列的数据数组引用
-    ///
-    /// 根据列名返回对应列的完整数据数组引用，提供对列级数据的直接访问。
-    /// 这是进行列级数据分析和处理的基础方法。
-    ///
-    /// # 参数
-    ///
-    /// * `name` - 要获取的列名
-    ///
-    /// # 返回值
-    ///
-    /// 返回 `Option<&ColumnDataArray>`：
-    /// - `Some(&array)` - 指定列的数据数组引用
-    /// - `None` - 指定的列不存在
-    ///
-    /// # 使用场景
-    ///
-    /// - **列级分析**: 对整个列进行统计计算
-    /// - **数据导出**: 提取特定列的数据
-    /// - **批量操作**: 对列数据进行向量化处理
-    ///
-    /// # 示例
-    ///
-    /// ```rust
-    /// # use cmx_core::model::data::dataset::cds::ColDataSet;
-    /// # let dataset = ColDataSet::new("test".to_string());
-    ///
-    /// if let Some(column_data) = dataset.get_column("price") {
-    ///     match column_data {
-    ///         cmx_core::model::data::dataset::cds::ColumnDataArray::NumberArray(vec) => {
-    ///             let prices: Vec<f64> = vec.iter()
-    ///                 .filter_map(|v| v.as_ref().and_then(|n| n.as_f64()))
-    ///                 .collect();
-    ///             println!("Prices: {:?}", prices);
-    ///         }
-    ///         _ => println!("Not a number column"),
-    ///     }
-    /// }
-    /// ```
-    ///
-    /// # 性能说明
-    ///
-    /// - **时间复杂度**: O(1) - HashMap 查找
-    /// - **内存效率**: 返回引用，不复制数据
-    /// - **线程安全**: 只读访问是线程安全的
+    /// 获取指定列的数据数组
     pub fn get_column(&self, name: &str) -> Option<&ColumnDataArray> {
         self.columns.get(name)
     }
 
-    /// 在指定位置插入一行数据
-    ///
-    /// 将数据行插入到数据集的指定位置。插入位置之后的所有行会向后移动，
-    /// 为新行腾出空间。这是列式存储中的复杂操作，需要同时更新所有列。
-    ///
-    /// # 参数
-    ///
-    /// * `index` - 插入位置的索引，从 0 开始，可以等于当前行数（追加）
-    /// * `row` - 要插入的行数据引用
+    /// 获取指定单元格的值，行或列不存在时返回 `None`
+    pub fn get_cell(&self, row_index: usize, column_name: &str) -> Option<CellValue> {
+        self.columns.get(column_name)?.get(row_index)
+    }
+
+    /// 在指定位置插入一行
     ///
     /// # 返回值
     ///
     /// 返回 `Result<(), ColDataSetError>`：
-    /// - `Ok(())` - 行插入成功
-    /// - `Err(ColDataSetError::IndexOutOfBounds)` - 索引超出有效范围
-    /// - `Err(ColDataSetError::TypeMismatch)` - 数据类型不匹配
-    ///
-    /// # 行为说明
-    ///
-    /// 1. **位置验证**: 检查插入位置的有效性
-    /// 2. **数据一致性**: 验证所有列的数据类型匹配
-    /// 3. **批量插入**: 同时在所有列的对应位置插入数据
-    /// 4. **子数据集处理**: 在子数据集数组中插入相应的子数据集
-    ///
-    /// # 性能特点
-    ///
-    /// - **时间复杂度**: O(C × N) - C 为列数，N 为受影响的行数
-    /// - **空间复杂度**: O(C) - 每列添加一个元素
-    /// - **最坏情况**: 在开头插入时，所有现有数据都需要移动
-    ///
-    /// # 示例
-    ///
-    /// ```rust
-    /// # use cmx_core::model::data::dataset::{cds::{ColDataSet, RowData}, ColumnType};
-    /// # use cmx_core::model::data::cell::CellValue;
-    /// # let mut dataset = ColDataSet::new("test".to_string());
-    /// # dataset.add_column("id".to_string(), ColumnType::I32).unwrap();
-    /// # dataset.add_row(RowData::new()).unwrap(); // 添加一行作为基准
-    ///
-    /// let mut new_row = RowData::new();
-    /// new_row.insert("id".to_string(), CellValue::Number(100.into()));
-    ///
-    /// // 在开头插入
-    /// dataset.insert_row(0, &new_row).unwrap();
-    /// assert_eq!(dataset.row_count(), 2);
-    /// ```
-    ///
-    /// # 注意事项
-    ///
-    /// - **性能影响**: 中间位置插入会移动大量数据
-    /// - **索引调整**: 插入操作会改变后续所有行的索引
-    /// - **内存重新分配**: 可能触发向量扩容
+    /// - `Ok(())` - 插入成功
+    /// - `Err(ColDataSetError::IndexOutOfBounds)` - `index` 大于行数
+    /// - `Err(ColDataSetError::TypeMismatch)` - 某个值无法转换为对应列的类型，数据集保持不变
+    /// - `Err(ColDataSetError::InconsistentLength)` - 数据一致性检查失败
     pub fn insert_row(&mut self, index: usize, row: &RowData) -> Result<(), ColDataSetError> {
         self.validate_row_counts()?;
 
         if index > self.row_count() {
             return Err(ColDataSetError::IndexOutOfBounds {
-                index: index,
+                index,
                 row_count: self.row_count(),
             });
         }
 
-        // 为每一列插入数据
-        for (col_name, col_data) in &mut self.columns {
-            let value = row.values.get(col_name).cloned().unwrap_or(CellValue::Null);
-
-            match (col_data, value) {
-                // Bool 类型处理
-                (ColumnDataArray::BoolArray(vec), CellValue::Bool(v)) => {
-                    vec.insert(index, Some(v));
-                }
-                (ColumnDataArray::BoolArray(vec), CellValue::Null) => {
-                    vec.insert(index, None);
-                }
-
-                // String 类型处理
-                (ColumnDataArray::StringArray(vec), CellValue::String(v)) => {
-                    vec.insert(index, Some(v));
-                }
-                (ColumnDataArray::StringArray(vec), CellValue::Null) => {
-                    vec.insert(index, None);
-                }
-
-                // 数值类型处理：从 Number 中提取值
-                (ColumnDataArray::NumberArray(vec), CellValue::Number(v)) => {
-                    vec.insert(index, Some(v.clone()));
-                }
-                (ColumnDataArray::NumberArray(vec), CellValue::Null) => {
-                    vec.insert(index, None);
-                }
-                // ... 其他数值类型类似处理
-
-                // 类型不匹配的情况
-                (col_data, value) => {
-                    return Err(ColDataSetError::TypeMismatch {
-                        column: col_name.clone(),
-                        expected: format!("{:?}", col_data),
-                        actual: format!("{:?}", value),
-                    });
+        for (position, name) in self.column_names.iter().enumerate() {
+            let value = row.values.get(name).cloned().unwrap_or(CellValue::Null);
+            let column = self.columns.get_mut(name).expect("column_names and columns are kept in sync");
+            if let Err(value) = column.insert(index, value) {
+                // 回滚已经插入的列，保持各列长度一致
+                for inserted in &self.column_names[..position] {
+                    if let Some(column) = self.columns.get_mut(inserted) {
+                        column.remove(index);
+                    }
                 }
+                return Err(ColDataSetError::TypeMismatch {
+                    column: name.clone(),
+                    expected: format!("{:?}", self.schema[name]),
+                    actual: format!("{:?}", value),
+                });
             }
         }
-
-        // Insert children data
-        if let Some(row_children) = &row.children {
-            self.children.insert(index, Some(row_children.clone()));
-        } else {
-            self.children.insert(index, None);
-        }
+        self.children.insert(index, row.children.clone());
 
         self.validate_row_counts()
     }
 
-    /// 删除指定行并返回被删除的行数据
-    ///
-    /// 根据行索引删除数据集中的指定行，并将删除的行数据重新组装后返回。
-    /// 删除位置之后的所有行会向前移动，填补空隙。
-    ///
-    /// # 参数
-    ///
-    /// * `index` - 要删除的行索引，从 0 开始
-    ///
-    /// # 返回值
-    ///
-    /// 返回 `Result<RowData, ColDataSetError>`：
-    /// - `Ok(row)` - 被删除的行数据，包含所有列值和子数据集
-    /// - `Err(ColDataSetError::IndexOutOfBounds)` - 索引超出有效范围
-    ///
-    /// # 数据重组过程
-    ///
-    /// ```text
-    /// 删除前: columns["id"] = [1, 2, 3], columns["name"] = ["A", "B", "C"]
-    /// 删除索引 1: 从每列移除索引 1 的元素
-    /// 删除后: columns["id"] = [1, 3], columns["name"] = ["A", "C"]
-    /// 返回: RowData { "id": 2, "name": "B" }
-    /// ```
-    ///
-    /// # 性能特点
-    ///
-    /// - **时间复杂度**: O(C × N) - C 为列数，N 为受影响的行数
-    /// - **最坏情况**: 删除开头行时，所有后续数据都需要移动
-    /// - **内存操作**: 向量元素移除，可能会触发内存重新分配
-    ///
-    /// # 示例
-    ///
-    /// ```rust
-    /// # use cmx_core::model::data::dataset::{cds::{ColDataSet, RowData}, ColumnType};
-    /// # use cmx_core::model::data::cell::CellValue;
-    /// # let mut dataset = ColDataSet::new("test".to_string());
-    /// # dataset.add_column("id".to_string(), ColumnType::I32).unwrap();
-    /// # let mut row = RowData::new();
-    /// # row.insert("id".to_string(), CellValue::Number(42.into()));
-    /// # dataset.append_row(&row).unwrap();
-    ///
-    /// // 删除行并获取删除的数据
-    /// let removed_row = dataset.remove_row(0).unwrap();
-    /// if let Some(id_value) = removed_row.get("id") {
-    ///     println!("Removed ID: {:?}", id_value);
-    /// }
-    /// ```
-    ///
-    /// # 重要说明
-    ///
-    /// - **数据完整性**: 返回的行数据包含删除前该行的完整状态
-    /// - **子数据集**: 如果行包含子数据集，也会被包含在返回的行数据中
-    /// - **不可逆操作**: 删除操作无法撤销，除非保存返回的行数据
+    /// 删除指定位置的行并返回其数据（包括子数据集）
     pub fn remove_row(&mut self, index: usize) -> Result<RowData, ColDataSetError> {
         self.validate_row_counts()?;
 
         if index >= self.row_count() {
             return Err(ColDataSetError::IndexOutOfBounds {
-                index: index,
+                index,
                 row_count: self.row_count(),
             });
         }
 
         let mut removed_row = RowData::new();
-
-        // 从每一列中删除对应行的数据并收集被删除的值
         for (col_name, col_data) in &mut self.columns {
-            let value = match col_data {
-                ColumnDataArray::BoolArray(vec) => {
-                    let v = vec.remove(index);
-                    v.map_or(CellValue::Null, CellValue::Bool)
-                }
-                ColumnDataArray::StringArray(vec) => {
-                    let v = vec.remove(index);
-                    v.map_or(CellValue::Null, CellValue::String)
-                }
-                ColumnDataArray::NumberArray(vec) => {
-                    let v = vec.remove(index);
-                    v.map_or(CellValue::Null, CellValue::Number)
-                }
-                // ColumnDataArray::CHILD(vec) => {
-                //     let v = vec.remove(index);
-                //     v.map_or(CellValue::Null, CellValue::Child)
-                // }
-            };
-            removed_row.insert(col_name.clone(), value);
-        }
-
-        // Remove and set children data
-        if let Some(children) = self.children.remove(index) {
-            removed_row.children = Some(children);
+            removed_row.insert(col_name.clone(), col_data.remove(index));
         }
+        removed_row.children = self.children.remove(index);
 
         self.validate_row_counts()?;
         Ok(removed_row)
     }
 
-    /// 获取数据集的ID标识符
-    ///
-    /// 返回数据集的唯一标识符字符串引用。这个ID在数据集生命周期内是只读的。
-    ///
-    /// # 返回值
+    /// 读取指定位置的行（包括子数据集）
+    pub fn get_row(&self, index: usize) -> Result<RowData, ColDataSetError> {
+        if index >= self.row_count() {
+            return Err(ColDataSetError::IndexOutOfBounds {
+                index,
+                row_count: self.row_count(),
+            });
+        }
+        let mut row = RowData::new();
+        for (col_name, col_data) in &self.columns {
+            row.insert(col_name.clone(), col_data.get(index).unwrap_or(CellValue::Null));
+        }
+        row.children = self.children[index].clone();
+        Ok(row)
+    }
+
+    /// 返回从 `offset` 开始、包含 `len` 行的零拷贝切片
     ///
-    /// 返回数据集ID的字符串切片引用
+    /// 切片与原数据集共享列数据和子数据集存储，之后对任意一方的修改都采用写时复制，
+    /// 不会相互影响。
     ///
     /// # 示例
     ///
     /// ```rust
-    /// # use cmx_core::model::data::dataset::cds::ColDataSet;
-    /// # let dataset = ColDataSet::new("sales_2024".to_string());
+    /// # use cmx_core::model::data::dataset::{cds::{ColDataSet, RowData}, ColumnType};
+    /// # use cmx_core::model::data::cell::CellValue;
+    /// let mut dataset = ColDataSet::new("facts".to_string());
+    /// dataset.add_column("id".to_string(), ColumnType::I64).unwrap();
+    /// for id in 0..10 {
+    ///     let mut row = RowData::new();
+    ///     row.insert("id".to_string(), CellValue::from(id));
+    ///     dataset.append_row(&row).unwrap();
+    /// }
     ///
-    /// assert_eq!(dataset.dataset_id(), "sales_2024");
+    /// let page = dataset.slice(4, 3).unwrap();
+    /// assert_eq!(page.row_count(), 3);
+    /// assert_eq!(page.get_cell(0, "id"), Some(CellValue::from(4)));
     /// ```
+    pub fn slice(&self, offset: usize, len: usize) -> Result<ColDataSet, ColDataSetError> {
+        let out_of_bounds = || ColDataSetError::IndexOutOfBounds {
+            index: offset.saturating_add(len),
+            row_count: self.row_count(),
+        };
+        let columns = self
+            .columns
+            .iter()
+            .map(|(name, data)| data.slice(offset, len).map(|data| (name.clone(), data)))
+            .collect::<Option<HashMap<_, _>>>()
+            .ok_or_else(out_of_bounds)?;
+
+        Ok(ColDataSet {
+            dataset_id: self.dataset_id.clone(),
+            schema: self.schema.clone(),
+            column_names: self.column_names.clone(),
+            columns,
+            children: self.children.slice(offset, len).ok_or_else(out_of_bounds)?,
+        })
+    }
+
     pub fn dataset_id(&self) -> &str {
         &self.dataset_id
     }
 }
 
+impl TryFrom<&RowDataSet> for ColDataSet {
+    type Error = DataSetError;
+
+    /// 行式数据集转换为列式数据集
+    ///
+    /// 列顺序、列类型、多语言列和子数据集（递归转换）都会保留。`RowDataSet` 写入时已按列类型
+    /// 规整，因此正常情况下所有值都能无损转换。
+    ///
+    /// # 返回值
+    ///
+    /// - `Err(DataSetError::TypeMismatch)` - 绕过校验直接写入的值无法以列类型保存，`row` 为出错的行
+    fn try_from(dataset: &RowDataSet) -> Result<Self, DataSetError> {
+        let mut columns: Vec<(&String, &ColumnInfo)> = dataset.schema.iter().collect();
+        columns.sort_by_key(|(_, info)| info.index);
+
+        let mut result = ColDataSet::new(dataset.dataset_id.clone());
+        for (name, info) in &columns {
+            let mut data = if info.multilingual {
+                ColumnDataArray::localized(0)
+            } else {
+                ColumnDataArray::nulls(info.column_type, 0)
+            };
+            for (row, record) in dataset.rows.iter().enumerate() {
+                let value = record.values().get(info.index).cloned().unwrap_or(CellValue::Null);
+                data.push(value).map_err(|value| DataSetError::TypeMismatch {
+                    row,
+                    column: name.to_string(),
+                    expected: info.column_type,
+                    value,
+                })?;
+            }
+            result.schema.insert(name.to_string(), info.column_type);
+            result.column_names.push(name.to_string());
+            result.columns.insert(name.to_string(), data);
+        }
+        result.children = dataset
+            .rows
+            .iter()
+            .map(|row| {
+                row.children
+                    .as_ref()
+                    .map(|children| {
+                        children
+                            .iter()
+                            .map(|(name, child)| Ok((name.clone(), ColDataSet::try_from(child)?)))
+                            .collect::<Result<HashMap<_, _>, DataSetError>>()
+                    })
+                    .transpose()
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(result)
+    }
+}
+
+impl TryFrom<RowDataSet> for ColDataSet {
+    type Error = DataSetError;
+
+    fn try_from(dataset: RowDataSet) -> Result<Self, DataSetError> {
+        ColDataSet::try_from(&dataset)
+    }
+}
+
+impl From<&ColDataSet> for RowDataSet {
+    /// 列式数据集转换为行式数据集
+    ///
+    /// 列顺序、列类型、多语言列和子数据集（递归转换）都会保留，单元格值为各类型的规范表示。
+    fn from(dataset: &ColDataSet) -> Self {
+        let mut result = RowDataSet::new(dataset.dataset_id.clone());
+        for (index, name) in dataset.column_names.iter().enumerate() {
+            let multilingual = dataset.columns[name].is_localized();
+            result.schema.insert(name.clone(), ColumnInfo { index, column_type: dataset.schema[name], multilingual });
+        }
+
+        let columns: Vec<&ColumnDataArray> = dataset.column_names.iter().map(|name| &dataset.columns[name]).collect();
+        result.rows = (0..dataset.row_count())
+            .map(|index| {
+                let values = columns.iter().map(|column| column.get(index).unwrap_or(CellValue::Null)).collect();
+                let mut row = RowRecord::new(values);
+                row.children = dataset.children[index].as_ref().map(|children| {
+                    children.iter().map(|(name, child)| (name.clone(), RowDataSet::from(child))).collect()
+                });
+                row
+            })
+            .collect();
+        result
+    }
+}
+
+impl From<ColDataSet> for RowDataSet {
+    fn from(dataset: ColDataSet) -> Self {
+        RowDataSet::from(&dataset)
+    }
+}
+
 /// 列式数据集操作错误类型
 ///
 /// 定义了列式数据集在各种操作过程中可能出现的错误情况。
@@ -1226,6 +1380,11 @@ pub enum ColDataSetError {
         /// 实际的数据类型描述
         actual: String,
     },
+
+    /// 列名重复错误
+    ///
+    /// 当添加的列名已存在于数据集中时发生。
+    DuplicateColumn(String),
 }
 
 impl std::fmt::Display for ColDataSetError {
@@ -1246,16 +1405,18 @@ impl std::fmt::Display for ColDataSetError {
                 write!(f, "Type mismatch for column {}: expected {}, got {}",
                     column, expected, actual)
             }
+            ColDataSetError::DuplicateColumn(column) => {
+                write!(f, "Column '{}' already exists", column)
+            }
         }
     }
 }
 
-impl std::error::Error for ColDataSetError {
-    /// 实现标准错误特征
-    ///
-    /// 使 ColDataSetError 符合 Rust 标准错误处理生态系统，
-    /// 可以与其他错误处理库和框架集成使用。
-}
+/// 实现标准错误特征
+///
+/// 使 ColDataSetError 符合 Rust 标准错误处理生态系统，
+/// 可以与其他错误处理库和框架集成使用。
+impl std::error::Error for ColDataSetError {}
 
 /// 单元测试模块
 ///
@@ -1305,16 +1466,16 @@ mod tests {
 
         // 验证数据
         match dataset.get_column("id").unwrap() {
-            ColumnDataArray::NumberArray(vec) => {
-                assert_eq!(vec[0].as_ref().unwrap().as_i64().unwrap(), 1);
-                assert_eq!(vec[1].as_ref().unwrap().as_i64().unwrap(), 2);
+            ColumnDataArray::I32Array(vec) => {
+                assert_eq!(vec[0], Some(1));
+                assert_eq!(vec[1], Some(2));
             }
             _ => panic!("Wrong type"),
         }
 
         match dataset.get_column("score").unwrap() {
-            ColumnDataArray::NumberArray(vec) => {
-                assert_eq!(vec[0].as_ref().unwrap().as_f64().unwrap(), 95.5);
+            ColumnDataArray::F64Array(vec) => {
+                assert_eq!(vec[0], Some(95.5));
                 assert!(vec[1].is_none()); // NULL value
             }
             _ => panic!("Wrong type"),
@@ -1368,10 +1529,10 @@ mod tests {
 
         // 验证插入结果
         match dataset.get_column("id").unwrap() {
-            ColumnDataArray::NumberArray(vec) => {
-                assert_eq!(vec[0].as_ref().unwrap().as_i64().unwrap(), 1);
-                assert_eq!(vec[1].as_ref().unwrap().as_i64().unwrap(), 2);
-                assert_eq!(vec[2].as_ref().unwrap().as_i64().unwrap(), 3);
+            ColumnDataArray::I32Array(vec) => {
+                assert_eq!(vec[0], Some(1));
+                assert_eq!(vec[1], Some(2));
+                assert_eq!(vec[2], Some(3));
             }
             _ => panic!("Wrong type"),
         }
@@ -1381,9 +1542,9 @@ mod tests {
 
         // 验证删除结果
         match dataset.get_column("id").unwrap() {
-            ColumnDataArray::NumberArray(vec) => {
-                assert_eq!(vec[0].as_ref().unwrap().as_i64().unwrap(), 1);
-                assert_eq!(vec[1].as_ref().unwrap().as_i64().unwrap(), 3);
+            ColumnDataArray::I32Array(vec) => {
+                assert_eq!(vec[0], Some(1));
+                assert_eq!(vec[1], Some(3));
                 assert_eq!(vec.len(), 2);
             }
             _ => panic!("Wrong type"),
@@ -1514,8 +1675,8 @@ mod tests {
         dataset.add_column("name".into(), ColumnType::String).unwrap();
 
         // 模拟长度不一致的情况
-        if let ColumnDataArray::NumberArray(vec) = dataset.columns.get_mut("id").unwrap() {
-            vec.push(Some(1.into())); // 人为制造不一致
+        if let ColumnDataArray::I32Array(vec) = dataset.columns.get_mut("id").unwrap() {
+            vec.push(Some(1)); // 人为制造不一致
         }
 
         // 所有操作都应该失败
@@ -1560,11 +1721,11 @@ mod tests {
         assert_eq!(parent_dataset.row_count(), 1);
         
         // 获取并验证子数据集
-        if let Some(removed_row) = parent_dataset.remove_row(0).ok() {
+        if let Ok(removed_row) = parent_dataset.remove_row(0) {
             if let Some(child_ds) = removed_row.get_child("items") {
                 assert_eq!(child_ds.row_count(), 1);
-                if let Some(ColumnDataArray::NumberArray(quantities)) = child_ds.get_column("quantity") {
-                    assert_eq!(quantities[0].as_ref().unwrap().as_i64().unwrap(), 5);
+                if let Some(ColumnDataArray::I32Array(quantities)) = child_ds.get_column("quantity") {
+                    assert_eq!(quantities[0], Some(5));
                 } else {
                     panic!("Wrong type for quantity column");
                 }
@@ -1631,7 +1792,7 @@ mod tests {
         // 验证移除后状态
         assert!(row.get_child("child1").is_none());
         // children 应该被设置为 None，因为 HashMap 为空
-        assert!(matches!(row.children, None));
+        assert!(row.children.is_none());
         
         // 测试移除不存在的子数据集
         assert!(row.remove_child("nonexistent").is_none());
@@ -1651,10 +1812,209 @@ mod tests {
         
         // 移除一个子数据集，children 应该仍然是 Some
         row.remove_child("child1");
-        assert!(matches!(row.children, Some(_)));
+        assert!(row.children.is_some());
         
         // 移除最后一个子数据集，children 应该变为 None
         row.remove_child("child2");
-        assert!(matches!(row.children, None));
+        assert!(row.children.is_none());
+    }
+
+    /// 类型不匹配时已写入的列应被回滚
+    #[test]
+    fn test_append_row_rolls_back_on_mismatch() {
+        let mut dataset = ColDataSet::new("test".into());
+        dataset.add_column("id".into(), ColumnType::I32).unwrap();
+        dataset.add_column("amount".into(), ColumnType::Decimal).unwrap();
+
+        let mut row = RowData::new();
+        row.insert("id".into(), CellValue::from(1));
+        row.insert("amount".into(), CellValue::String("abc".into()));
+        assert!(matches!(dataset.append_row(&row), Err(ColDataSetError::TypeMismatch { .. })));
+        assert_eq!(dataset.row_count(), 0);
+        assert!(dataset.get_column("id").unwrap().is_empty());
+
+        assert!(matches!(
+            dataset.add_column("id".into(), ColumnType::I64),
+            Err(ColDataSetError::DuplicateColumn(_))
+        ));
+    }
+
+    /// `F32` 列只接受能以单精度无损保存的值
+    #[test]
+    fn test_f32_rejects_lossy_values() {
+        use serde_json::json;
+
+        let mut dataset = ColDataSet::new("test".into());
+        dataset.add_column("rate".into(), ColumnType::F32).unwrap();
+
+        let mut row = RowData::new();
+        row.insert("rate".into(), json!(0.1));
+        dataset.append_row(&row).unwrap();
+        assert_eq!(dataset.get_cell(0, "rate").unwrap(), json!(0.1));
+
+        row.insert("rate".into(), json!(0.123456789));
+        assert!(matches!(dataset.append_row(&row), Err(ColDataSetError::TypeMismatch { .. })));
+        row.insert("rate".into(), json!(1e300));
+        assert!(matches!(dataset.append_row(&row), Err(ColDataSetError::TypeMismatch { .. })));
+        assert_eq!(dataset.row_count(), 1);
+    }
+
+    /// 切片共享底层存储，修改时写时复制
+    #[test]
+    fn test_slice_is_zero_copy() {
+        let mut dataset = ColDataSet::new("test".into());
+        dataset.add_column("id".into(), ColumnType::I64).unwrap();
+        for id in 0..5 {
+            let mut row = RowData::new();
+            row.insert("id".into(), CellValue::from(id));
+            dataset.append_row(&row).unwrap();
+        }
+
+        let mut page = dataset.slice(1, 3).unwrap();
+        let ColumnDataArray::I64Array(ids) = page.get_column("id").unwrap() else {
+            panic!("Wrong type");
+        };
+        assert!(ids.is_shared());
+        assert_eq!(&ids[..], &[Some(1), Some(2), Some(3)]);
+        assert!(dataset.slice(4, 2).is_err());
+
+        page.columns.get_mut("id").unwrap().set(0, CellValue::from(100)).unwrap();
+        page.remove_row(2).unwrap();
+        assert_eq!(page.get_cell(0, "id"), Some(CellValue::from(100)));
+        assert_eq!(page.row_count(), 2);
+        assert_eq!(dataset.get_cell(1, "id"), Some(CellValue::from(1)));
+        assert_eq!(dataset.row_count(), 5);
+    }
+
+    /// 与 RowDataSet 之间的往返转换应保留所有类型的值和嵌套子数据集
+    #[test]
+    fn test_row_dataset_round_trip() {
+        use serde_json::json;
+
+        let columns = [
+            ("B", ColumnType::Bool, json!(true)),
+            ("I8", ColumnType::I8, json!(-8)),
+            ("I16", ColumnType::I16, json!(-16)),
+            ("I32", ColumnType::I32, json!(-32)),
+            ("I64", ColumnType::I64, json!(i64::MIN)),
+            ("U8", ColumnType::U8, json!(8)),
+            ("U16", ColumnType::U16, json!(16)),
+            ("U32", ColumnType::U32, json!(32)),
+            ("U64", ColumnType::U64, json!(u64::MAX)),
+            ("F32", ColumnType::F32, json!(0.1)),
+            ("F64", ColumnType::F64, json!(0.1)),
+            ("DEC", ColumnType::Decimal, json!("12345678901234567890.123")),
+            ("S", ColumnType::String, json!("文本")),
+            ("D", ColumnType::Date, json!("2024-02-29")),
+            ("T", ColumnType::Time, json!("08:30:00.5")),
+            ("DT", ColumnType::DateTime, json!("2024-02-29T08:30:00")),
+            ("DTZ", ColumnType::DateTimeTz, json!("2024-02-29T08:30:00+08:00")),
+            ("U", ColumnType::Uuid, json!("67e55044-10b1-426f-9247-bb680e5fe0c8")),
+            ("BIN", ColumnType::Binary, json!("AAEC/w==")),
+            ("J", ColumnType::Json, json!({"a": [1, 2]})),
+        ];
+
+        let mut items = RowDataSet::new("items".into());
+        items.add_column("SL".into(), ColumnType::I32).unwrap();
+        items.add_row(vec![json!(3)]).unwrap();
+
+        let mut source = RowDataSet::new("all_types".into());
+        for (name, column_type, _) in &columns {
+            source.add_column(name.to_string(), *column_type).unwrap();
+        }
+        source.add_row(columns.iter().map(|(_, _, value)| value.clone()).collect()).unwrap();
+        source.add_row(vec![CellValue::Null; columns.len()]).unwrap();
+        source.add_child_dataset(0, "items".into(), items).unwrap();
+
+        let columnar = ColDataSet::try_from(&source).unwrap();
+        assert_eq!(columnar.column_count(), columns.len());
+        assert_eq!(columnar.column_names[9], "F32");
+        assert!(matches!(columnar.get_column("DTZ"), Some(ColumnDataArray::DateTimeTzArray(_))));
+        assert!(columnar.get_column("BIN").unwrap().is_null(1));
+
+        let restored = RowDataSet::from(columnar);
+        assert_eq!(restored.row_count(), 2);
+        for (name, column_type, _) in &columns {
+            assert_eq!(restored.schema[*name].index, source.schema[*name].index);
+            assert_eq!(restored.schema[*name].column_type, *column_type);
+        }
+        assert_eq!(restored.rows[0].values, source.rows[0].values);
+        assert_eq!(restored.rows[1].values, source.rows[1].values);
+        let child = restored.get_child_dataset(0, "items").unwrap().unwrap();
+        assert_eq!(child.get_cell(0, "SL").unwrap(), &json!(3));
+        assert!(restored.rows[1].children.is_none());
+    }
+
+    /// 行式数据集接受的 `F32` 值都能转换为列式存储，无法无损保存的值在写入行式数据集时就被拒绝
+    #[test]
+    fn test_f32_row_dataset_round_trip() {
+        use serde_json::json;
+
+        let mut source = RowDataSet::new("rates".into());
+        source.add_column("rate".into(), ColumnType::F32).unwrap();
+        for value in [json!(0.1), json!("2.5"), json!(-3), json!(16777216)] {
+            source.add_row(vec![value]).unwrap();
+        }
+        for value in [json!(0.123456789), json!(16777217), json!(1e300)] {
+            assert!(source.add_row(vec![value]).is_err());
+        }
+
+        let columnar = ColDataSet::try_from(&source).unwrap();
+        let restored = RowDataSet::from(columnar);
+        assert_eq!(restored.row_count(), 4);
+        for (restored, source) in restored.rows.iter().zip(&source.rows) {
+            assert_eq!(restored.values, source.values);
+        }
+    }
+
+    /// 多语言列转换后仍为多语言列，对象值原样保留
+    #[test]
+    fn test_multilingual_round_trip() {
+        use serde_json::json;
+
+        let mut accounts = RowDataSet::new("accounts".into());
+        accounts.add_column("KMDM".into(), ColumnType::String).unwrap();
+        accounts.add_column("KMMC".into(), ColumnType::String).unwrap();
+        accounts.set_multilingual("KMMC", true).unwrap();
+        accounts.add_row(vec![json!("1001"), json!({"en-US": "Cash", "zh-CN": "库存现金"})]).unwrap();
+        accounts.add_row(vec![json!("1002"), json!("银行存款")]).unwrap();
+        accounts.add_row(vec![json!("1003"), CellValue::Null]).unwrap();
+
+        let columnar = ColDataSet::try_from(&accounts).unwrap();
+        assert!(columnar.get_column("KMMC").unwrap().is_localized());
+        assert!(!columnar.get_column("KMDM").unwrap().is_localized());
+        assert_eq!(columnar.get_cell(0, "KMMC").unwrap(), json!({"en-US": "Cash", "zh-CN": "库存现金"}));
+
+        let restored = RowDataSet::from(&columnar);
+        assert_eq!(restored.multilingual_columns(), vec!["KMMC"]);
+        for (restored_row, source_row) in restored.rows.iter().zip(&accounts.rows) {
+            assert_eq!(restored_row.values, source_row.values);
+        }
+
+        // 多语言数组只接受字符串或字符串对象
+        let mut names = columnar.get_column("KMMC").unwrap().clone();
+        assert!(names.push(json!({"en-US": "Bank"})).is_ok());
+        assert!(names.push(json!({"en-US": [1]})).is_err());
+    }
+
+    /// 无法以列类型保存的值返回错误而不是变为 NULL
+    #[test]
+    fn test_try_from_rejects_invalid_values() {
+        use serde_json::json;
+
+        let mut dataset = RowDataSet::new("test".into());
+        dataset.add_column("ID".into(), ColumnType::I32).unwrap();
+        dataset.add_column("MC".into(), ColumnType::String).unwrap();
+        dataset.add_row(vec![json!(1), json!("a")]).unwrap();
+        dataset.add_row(vec![json!(2), json!("b")]).unwrap();
+        // 绕过校验写入多语言对象，但列未标记为多语言
+        dataset.rows[1].values[1] = json!({"en-US": "b"});
+
+        match ColDataSet::try_from(&dataset) {
+            Err(DataSetError::TypeMismatch { row, column, expected, .. }) => {
+                assert_eq!((row, column.as_str(), expected), (1, "MC", ColumnType::String));
+            }
+            other => panic!("expected type mismatch, got {:?}", other),
+        }
     }
 }
@@ -0,0 +1,309 @@
+//! # 变更跟踪模块
+//!
+//! 为 `RowDataSet` 提供可选的行状态跟踪，用于保存编辑后的表格时找出新增、
+//! 修改和删除的行以及修改前的原始值。
+//!
+//! ## 跟踪规则
+//!
+//! 调用 [`RowDataSet::set_change_tracking`] 开启跟踪后：
+//!
+//! - `add_row` / `insert_row` 添加的行状态为 `Added`
+//! - `set_cell` 第一次修改 `Unchanged` 行时保存原始值，状态变为 `Modified`；
+//!   `Added` 行被修改后仍为 `Added`
+//! - `remove_row` / `clear` 删除的非新增行以原始值记入墓碑列表 `deleted`，
+//!   状态为 `Deleted`；新增行被删除时直接丢弃
+//! - 通过 `get_row_mut` / `values_mut` 直接修改行值不会被跟踪
+//! - 开启跟踪的数据集上通过 `add_child_dataset` 添加的子数据集会自动开启跟踪
+//!
+//! ## 示例
+//!
+//! ```rust
+//! use cmx_core::model::data::dataset::{rds::RowDataSet, ColumnType};
+//! use cmx_core::model::data::dataset::changes::RowState;
+//! use serde_json::json;
+//!
+//! let mut dataset = RowDataSet::new("dept".to_string());
+//! dataset.add_column("BM".to_string(), ColumnType::String).unwrap();
+//! dataset.add_row(vec![json!("01")]).unwrap();
+//! dataset.add_row(vec![json!("02")]).unwrap();
+//! dataset.set_change_tracking(true);
+//!
+//! dataset.set_cell(0, "BM", json!("0101")).unwrap();
+//! dataset.remove_row(1).unwrap();
+//! dataset.add_row(vec![json!("03")]).unwrap();
+//!
+//! let changes = dataset.get_changes();
+//! assert_eq!(changes.row_count(), 2);
+//! assert_eq!(changes.rows()[0].state, RowState::Modified);
+//! assert_eq!(changes.rows()[0].original, Some(vec![json!("01")]));
+//! assert_eq!(changes.deleted[0].values(), &vec![json!("02")]);
+//!
+//! dataset.reject_changes();
+//! assert_eq!(dataset.get_column_values("BM").unwrap(), vec![&json!("01"), &json!("02")]);
+//! ```
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::rds::{RowData, RowDataSet};
+use crate::model::data::cell::CellValue;
+
+/// 行状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+#[rkyv(derive(Debug, Clone, Copy, PartialEq, Eq))]
+pub enum RowState {
+    /// 自上次接受变更以来未修改
+    #[default]
+    Unchanged,
+    /// 新增的行
+    Added,
+    /// 修改过的行，原始值保存在 `RowData::original`
+    Modified,
+    /// 已删除的行，只出现在墓碑列表中
+    Deleted,
+}
+
+impl RowState {
+    pub fn is_unchanged(&self) -> bool {
+        *self == RowState::Unchanged
+    }
+}
+
+impl RowData {
+    /// 在第一次修改前保存原始值并标记为已修改
+    pub(crate) fn mark_modified(&mut self) {
+        if self.state == RowState::Unchanged {
+            self.original = Some(self.values.clone());
+            self.state = RowState::Modified;
+        }
+    }
+
+    /// 行本身或任一子数据集是否有未接受的变更
+    pub fn has_changes(&self) -> bool {
+        !self.state.is_unchanged()
+            || self.children.as_ref().is_some_and(|children| children.values().any(RowDataSet::has_changes))
+    }
+}
+
+impl RowDataSet {
+    /// 按跟踪规则创建新行
+    pub(crate) fn new_row(&self, values: Vec<CellValue>) -> RowData {
+        let mut row = RowData::new(values);
+        if self.tracking {
+            row.state = RowState::Added;
+        }
+        row
+    }
+
+    /// 把被删除的行记入墓碑列表，新增行直接丢弃
+    pub(crate) fn tombstone(&mut self, row: &RowData) {
+        if row.state == RowState::Added {
+            return;
+        }
+        let mut tombstone = RowData::new(row.original.clone().unwrap_or_else(|| row.values.clone()));
+        tombstone.state = RowState::Deleted;
+        self.deleted.push(tombstone);
+    }
+
+    /// 开启或关闭变更跟踪
+    ///
+    /// 设置会递归应用到所有子数据集。开启时，当前数据作为跟踪的基线；
+    /// 关闭时，已记录的变更会被接受（清空行状态、原始值和墓碑列表）。
+    pub fn set_change_tracking(&mut self, enabled: bool) {
+        if !enabled {
+            self.accept_changes();
+        }
+        self.tracking = enabled;
+        for dataset in self.children_mut() {
+            dataset.set_change_tracking(enabled);
+        }
+    }
+
+    /// 是否开启了变更跟踪
+    pub fn is_tracking_changes(&self) -> bool {
+        self.tracking
+    }
+
+    /// 数据集（包括子数据集）是否有未接受的变更
+    pub fn has_changes(&self) -> bool {
+        !self.deleted.is_empty() || self.rows.iter().any(RowData::has_changes)
+    }
+
+    /// 接受所有变更
+    ///
+    /// 所有行的状态重置为 `Unchanged`，清空原始值和墓碑列表，递归应用到子数据集。
+    pub fn accept_changes(&mut self) {
+        self.deleted.clear();
+        for row in &mut self.rows {
+            row.state = RowState::Unchanged;
+            row.original = None;
+        }
+        for dataset in self.children_mut() {
+            dataset.accept_changes();
+        }
+    }
+
+    /// 回滚所有变更
+    ///
+    /// 移除新增行，已修改的行恢复原始值，墓碑列表中的行以原始值追加到数据集末尾，
+    /// 递归应用到子数据集，最后按恢复后的数据重建索引。
+    ///
+    /// # 注意事项
+    ///
+    /// - 恢复的删除行追加在末尾，不会回到原来的位置
+    /// - 删除行的子数据集不会被恢复
+    pub fn reject_changes(&mut self) {
+        self.rows.retain(|row| row.state != RowState::Added);
+        for row in &mut self.rows {
+            if let Some(original) = row.original.take() {
+                row.values = original;
+            }
+            row.state = RowState::Unchanged;
+        }
+        for mut row in std::mem::take(&mut self.deleted) {
+            row.state = RowState::Unchanged;
+            self.rows.push(row);
+        }
+        for dataset in self.children_mut() {
+            dataset.reject_changes();
+        }
+        // 唯一索引创建时已检查过回滚后恢复的行（见 `create_index`），重建不会出现重复键
+        let _ = self.rebuild_indexes();
+    }
+
+    /// 提取变更集
+    ///
+    /// # 返回值
+    ///
+    /// 返回与当前数据集 ID、列定义相同的新数据集，只包含：
+    /// - `Added` 行（连同完整的子数据集）
+    /// - `Modified` 行（带 `original` 原始值）
+    /// - 自身未修改但子数据集有变更的行（状态为 `Unchanged`，只保留有变更的子数据集）
+    /// - 墓碑列表中的删除行（在 `deleted` 中）
+    ///
+    /// 有变更的子数据集同样以变更集的形式出现。结果可以直接序列化传输。
+    pub fn get_changes(&self) -> RowDataSet {
+        let mut delta = RowDataSet::new(self.dataset_id.clone());
+        delta.schema = self.schema.clone();
+        delta.tracking = self.tracking;
+        delta.deleted = self.deleted.clone();
+
+        for row in self.rows.iter().filter(|row| row.has_changes()) {
+            if row.state == RowState::Added {
+                delta.rows.push(row.clone());
+                continue;
+            }
+            let children = row.children.as_ref().map(|children| {
+                children
+                    .iter()
+                    .filter(|(_, dataset)| dataset.has_changes())
+                    .map(|(name, dataset)| (name.clone(), dataset.get_changes()))
+                    .collect::<HashMap<_, _>>()
+            });
+            delta.rows.push(RowData {
+                values: row.values.clone(),
+                children: children.filter(|children| !children.is_empty()),
+                state: row.state,
+                original: row.original.clone(),
+            });
+        }
+        delta
+    }
+
+    fn children_mut(&mut self) -> impl Iterator<Item = &mut RowDataSet> {
+        self.rows
+            .iter_mut()
+            .filter_map(|row| row.children.as_mut())
+            .flat_map(|children| children.values_mut())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::model::data::dataset::ColumnType;
+
+    fn create_order_dataset() -> RowDataSet {
+        let mut orders = RowDataSet::new("orders".to_string());
+        orders.add_column("ID".to_string(), ColumnType::I32).unwrap();
+        orders.add_column("MC".to_string(), ColumnType::String).unwrap();
+        for (id, mc) in [(1, "a"), (2, "b"), (3, "c")] {
+            orders.add_row(vec![json!(id), json!(mc)]).unwrap();
+        }
+        let mut items = RowDataSet::new("items".to_string());
+        items.add_column("SL".to_string(), ColumnType::I32).unwrap();
+        items.add_row(vec![json!(10)]).unwrap();
+        orders.add_child_dataset(0, "items".to_string(), items).unwrap();
+        orders
+    }
+
+    #[test]
+    fn test_untracked_dataset_records_nothing() {
+        let mut orders = create_order_dataset();
+        orders.set_cell(0, "MC", json!("x")).unwrap();
+        orders.remove_row(1).unwrap();
+        assert!(!orders.has_changes());
+        assert!(orders.deleted.is_empty());
+    }
+
+    #[test]
+    fn test_track_and_extract_changes() {
+        let mut orders = create_order_dataset();
+        orders.set_change_tracking(true);
+        assert!(orders.get_child_dataset(0, "items").unwrap().unwrap().is_tracking_changes());
+
+        orders.set_cell(1, "MC", json!("b1")).unwrap();
+        orders.set_cell(1, "MC", json!("b2")).unwrap();
+        orders.set_cell(2, "MC", json!("c")).unwrap(); // 值未变化
+        orders.add_row(vec![json!(4), json!("d")]).unwrap();
+        orders.set_cell(3, "MC", json!("d1")).unwrap();
+        orders.add_row(vec![json!(5), json!("e")]).unwrap();
+        orders.remove_row(4).unwrap(); // 新增后删除，不留墓碑
+        orders.get_row_mut(0).unwrap().get_child_mut("items").unwrap().set_cell(0, "SL", json!(20)).unwrap();
+        orders.remove_row(2).unwrap();
+
+        let delta = orders.get_changes();
+        assert_eq!(delta.row_count(), 3);
+        assert_eq!(delta.rows[0].state, RowState::Unchanged);
+        let items = delta.rows[0].get_child("items").unwrap();
+        assert_eq!(items.rows[0].original, Some(vec![json!(10)]));
+        assert_eq!(delta.rows[1].state, RowState::Modified);
+        assert_eq!(delta.rows[1].original, Some(vec![json!(2), json!("b")]));
+        assert_eq!(delta.rows[1].values, vec![json!(2), json!("b2")]);
+        assert_eq!(delta.rows[2].state, RowState::Added);
+        assert_eq!(delta.rows[2].values, vec![json!(4), json!("d1")]);
+        assert_eq!(delta.deleted.len(), 1);
+        assert_eq!(delta.deleted[0].values, vec![json!(3), json!("c")]);
+
+        let json = serde_json::to_string(&delta).unwrap();
+        let restored: RowDataSet = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.rows[1].state, RowState::Modified);
+        assert_eq!(restored.deleted[0].state, RowState::Deleted);
+    }
+
+    #[test]
+    fn test_accept_and_reject_changes() {
+        let mut orders = create_order_dataset();
+        orders.set_change_tracking(true);
+        orders.set_cell(0, "MC", json!("a1")).unwrap();
+        orders.add_row(vec![json!(4), json!("d")]).unwrap();
+        orders.remove_row(1).unwrap();
+        orders.get_row_mut(0).unwrap().get_child_mut("items").unwrap().remove_row(0).unwrap();
+
+        let mut rejected = orders.clone();
+        rejected.reject_changes();
+        assert!(!rejected.has_changes());
+        assert_eq!(rejected.get_column_values("MC").unwrap(), vec![&json!("a"), &json!("c"), &json!("b")]);
+        assert_eq!(rejected.get_child_dataset(0, "items").unwrap().unwrap().row_count(), 1);
+
+        orders.accept_changes();
+        assert!(!orders.has_changes());
+        assert!(orders.is_tracking_changes());
+        assert_eq!(orders.get_column_values("MC").unwrap(), vec![&json!("a1"), &json!("c"), &json!("d")]);
+        assert_eq!(orders.get_changes().row_count(), 0);
+    }
+}
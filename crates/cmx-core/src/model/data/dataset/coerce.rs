@@ -0,0 +1,386 @@
+//! # 列类型校验与转换模块
+//!
+//! `CellValue` 本身是 `serde_json::Value`，无法区分日期、UUID、二进制等业务类型。
+//! 这个模块为 `ColumnType` 提供统一的校验与转换规则，写入数据集前把值规整为
+//! 每种类型约定的规范表示：
+//!
+//! | 列类型 | 规范表示 | 可接受的输入 |
+//! |--------|----------|--------------|
+//! | `Bool` | `Bool` | 布尔值、`0`/`1`、`"0"`/`"1"`/`"true"`/`"false"` |
+//! | 整数类型 | `Number` | 范围内的整数、无小数部分的浮点数、整数字符串 |
+//! | `F32` | `Number`（浮点数） | 能以单精度无损保存的数值、数值字符串 |
+//! | `F64` | `Number` | 数值、数值字符串 |
+//! | `Decimal` | `Number`（无损时）或规范化的数字字符串 | 数值、数值字符串 |
+//! | `String` | `String` | 字符串、数值、布尔值 |
+//! | `Date` | `"YYYY-MM-DD"` | `YYYY-MM-DD`、`YYYY/MM/DD`、`YYYYMMDD` |
+//! | `Time` | `"HH:MM:SS[.fff]"` | `HH:MM:SS[.fff]`、`HH:MM` |
+//! | `DateTime` | `"YYYY-MM-DDTHH:MM:SS[.fff]"` | ISO 日期时间（`T` 或空格分隔）、纯日期 |
+//! | `DateTimeTz` | RFC 3339 字符串 | RFC 3339、带偏移量的 ISO 日期时间 |
+//! | `Uuid` | 小写连字符格式字符串 | 任意 `uuid` 可解析的格式 |
+//! | `Binary` | 标准 Base64 字符串 | Base64 字符串、`0..=255` 的数值数组 |
+//! | `Json` | 原值 | 任意值 |
+//!
+//! `Null` 对所有类型都是合法值，是否允许为空由列约束决定。
+
+use std::str::FromStr;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, SecondsFormat};
+use rust_decimal::Decimal;
+use serde_json::Number;
+
+use super::ColumnType;
+use crate::model::data::cell::CellValue;
+
+/// `Date` 列可接受的日期格式
+const DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%Y/%m/%d", "%Y%m%d"];
+
+/// `Time` 列可接受的时间格式
+const TIME_FORMATS: &[&str] = &["%H:%M:%S%.f", "%H:%M"];
+
+/// `DateTime` 列可接受的日期时间格式
+const DATETIME_FORMATS: &[&str] = &[
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M",
+    "%Y/%m/%d %H:%M:%S%.f",
+];
+
+/// `DateTimeTz` 列在 RFC 3339 之外额外接受的格式
+const DATETIME_TZ_FORMATS: &[&str] = &[
+    "%Y-%m-%d %H:%M:%S%.f%:z",
+    "%Y-%m-%d %H:%M:%S%.f%z",
+    "%Y-%m-%dT%H:%M:%S%.f%z",
+];
+
+impl ColumnType {
+    /// 是否为整数类型
+    pub fn is_integer(&self) -> bool {
+        matches!(
+            self,
+            ColumnType::I8 | ColumnType::I16 | ColumnType::I32 | ColumnType::I64
+                | ColumnType::U8 | ColumnType::U16 | ColumnType::U32 | ColumnType::U64
+        )
+    }
+
+    /// 是否为数值类型（整数、浮点数和定点数）
+    pub fn is_numeric(&self) -> bool {
+        self.is_integer() || matches!(self, ColumnType::F32 | ColumnType::F64 | ColumnType::Decimal)
+    }
+
+    /// 是否为日期时间类型
+    pub fn is_temporal(&self) -> bool {
+        matches!(
+            self,
+            ColumnType::Date | ColumnType::Time | ColumnType::DateTime | ColumnType::DateTimeTz
+        )
+    }
+
+    /// 校验并转换单元格值
+    ///
+    /// 按模块文档中的规则把 `value` 转换为该列类型的规范表示。
+    ///
+    /// # 返回值
+    ///
+    /// - `Ok(value)` - 转换后的值
+    /// - `Err(value)` - 无法转换时原样返回输入值，便于调用方构造错误信息
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use cmx_core::model::data::dataset::ColumnType;
+    /// use serde_json::json;
+    ///
+    /// assert_eq!(ColumnType::Date.coerce(json!("2024/03/01")), Ok(json!("2024-03-01")));
+    /// assert_eq!(ColumnType::Decimal.coerce(json!("12.50")), Ok(json!(12.5)));
+    /// assert!(ColumnType::I8.coerce(json!(300)).is_err());
+    /// ```
+    pub fn coerce(&self, value: CellValue) -> Result<CellValue, CellValue> {
+        if value.is_null() {
+            return Ok(value);
+        }
+        let coerced = match self {
+            ColumnType::Bool => coerce_bool(&value),
+            ColumnType::I8 => coerce_signed(&value, i8::MIN as i64, i8::MAX as i64),
+            ColumnType::I16 => coerce_signed(&value, i16::MIN as i64, i16::MAX as i64),
+            ColumnType::I32 => coerce_signed(&value, i32::MIN as i64, i32::MAX as i64),
+            ColumnType::I64 => coerce_signed(&value, i64::MIN, i64::MAX),
+            ColumnType::U8 => coerce_unsigned(&value, u8::MAX as u64),
+            ColumnType::U16 => coerce_unsigned(&value, u16::MAX as u64),
+            ColumnType::U32 => coerce_unsigned(&value, u32::MAX as u64),
+            ColumnType::U64 => coerce_unsigned(&value, u64::MAX),
+            ColumnType::F32 => coerce_float(&value)
+                .and_then(|v| v.as_f64())
+                .filter(|v| to_f32(*v).is_some())
+                .and_then(Number::from_f64)
+                .map(CellValue::Number),
+            ColumnType::F64 => coerce_float(&value),
+            ColumnType::Decimal => coerce_decimal(&value),
+            ColumnType::String => coerce_string(&value),
+            ColumnType::Date => value.as_str().and_then(parse_date).map(format_date),
+            ColumnType::Time => value.as_str().and_then(parse_time).map(format_time),
+            ColumnType::DateTime => value.as_str().and_then(parse_datetime).map(format_datetime),
+            ColumnType::DateTimeTz => value.as_str().and_then(parse_datetime_tz).map(format_datetime_tz),
+            ColumnType::Uuid => value
+                .as_str()
+                .and_then(|s| uuid::Uuid::parse_str(s.trim()).ok())
+                .map(|u| CellValue::String(u.hyphenated().to_string())),
+            ColumnType::Binary => coerce_binary(&value),
+            ColumnType::Json => return Ok(value),
+        };
+        coerced.ok_or(value)
+    }
+}
+
+/// 将单元格值解析为定点数
+///
+/// 支持 `Number` 和数值字符串两种表示，`Decimal` 列的聚合计算都应通过这个函数取值，
+/// 以避免经过 `f64` 产生舍入误差。
+pub fn to_decimal(value: &CellValue) -> Option<Decimal> {
+    match value {
+        CellValue::Number(n) => parse_decimal(&n.to_string()),
+        CellValue::String(s) => parse_decimal(s),
+        _ => None,
+    }
+}
+
+/// 将定点数转换为单元格值
+///
+/// 能够无损地用 JSON 数值表示时返回 `Number`，否则返回规范化的数字字符串。
+pub fn decimal_to_cell(value: Decimal) -> CellValue {
+    let text = value.normalize().to_string();
+    match Number::from_str(&text) {
+        Ok(number) if parse_decimal(&number.to_string()) == Some(value) => CellValue::Number(number),
+        _ => CellValue::String(text),
+    }
+}
+
+/// 将浮点数转换为单精度浮点数
+///
+/// 只接受能以 `f32` 无损保存的值（按最短十进制表示比较），避免转换为列式存储时悄悄丢失精度。
+/// `F32` 列的校验与列式存储使用同一规则。
+pub(crate) fn to_f32(value: f64) -> Option<f32> {
+    Some(value as f32).filter(|f| f.is_finite() && f.to_string().parse::<f64>().ok() == Some(value))
+}
+
+/// 解析日期字符串
+pub fn parse_date(text: &str) -> Option<NaiveDate> {
+    let text = text.trim();
+    DATE_FORMATS
+        .iter()
+        .find_map(|fmt| NaiveDate::parse_from_str(text, fmt).ok())
+}
+
+/// 解析时间字符串
+pub fn parse_time(text: &str) -> Option<NaiveTime> {
+    let text = text.trim();
+    TIME_FORMATS
+        .iter()
+        .find_map(|fmt| NaiveTime::parse_from_str(text, fmt).ok())
+}
+
+/// 解析不带时区的日期时间字符串，纯日期视为当天零点
+pub fn parse_datetime(text: &str) -> Option<NaiveDateTime> {
+    let text = text.trim();
+    DATETIME_FORMATS
+        .iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(text, fmt).ok())
+        .or_else(|| parse_date(text).and_then(|d| d.and_hms_opt(0, 0, 0)))
+}
+
+/// 解析带时区的日期时间字符串
+pub fn parse_datetime_tz(text: &str) -> Option<DateTime<chrono::FixedOffset>> {
+    let text = text.trim();
+    DateTime::parse_from_rfc3339(text).ok().or_else(|| {
+        DATETIME_TZ_FORMATS
+            .iter()
+            .find_map(|fmt| DateTime::parse_from_str(text, fmt).ok())
+    })
+}
+
+pub(crate) fn format_date(date: NaiveDate) -> CellValue {
+    CellValue::String(date.format("%Y-%m-%d").to_string())
+}
+
+pub(crate) fn format_time(time: NaiveTime) -> CellValue {
+    CellValue::String(time.format("%H:%M:%S%.f").to_string())
+}
+
+pub(crate) fn format_datetime(datetime: NaiveDateTime) -> CellValue {
+    CellValue::String(datetime.format("%Y-%m-%dT%H:%M:%S%.f").to_string())
+}
+
+pub(crate) fn format_datetime_tz(datetime: DateTime<chrono::FixedOffset>) -> CellValue {
+    CellValue::String(datetime.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+}
+
+fn parse_decimal(text: &str) -> Option<Decimal> {
+    let text = text.trim();
+    Decimal::from_str_exact(text)
+        .or_else(|_| Decimal::from_scientific(text))
+        .ok()
+}
+
+fn coerce_bool(value: &CellValue) -> Option<CellValue> {
+    match value {
+        CellValue::Bool(_) => Some(value.clone()),
+        CellValue::Number(n) => match n.as_u64() {
+            Some(0) => Some(CellValue::Bool(false)),
+            Some(1) => Some(CellValue::Bool(true)),
+            _ => None,
+        },
+        CellValue::String(s) => match s.trim().to_ascii_lowercase().as_str() {
+            "1" | "true" => Some(CellValue::Bool(true)),
+            "0" | "false" => Some(CellValue::Bool(false)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// 取出整数值，允许无小数部分的浮点数和整数字符串
+fn integral_value(value: &CellValue) -> Option<i128> {
+    match value {
+        CellValue::Number(n) => n
+            .as_i64()
+            .map(i128::from)
+            .or_else(|| n.as_u64().map(i128::from))
+            .or_else(|| {
+                n.as_f64()
+                    .filter(|f| f.fract() == 0.0 && f.abs() < 2f64.powi(63))
+                    .map(|f| f as i128)
+            }),
+        CellValue::String(s) => s.trim().parse::<i128>().ok(),
+        _ => None,
+    }
+}
+
+fn coerce_signed(value: &CellValue, min: i64, max: i64) -> Option<CellValue> {
+    integral_value(value)
+        .filter(|v| *v >= min as i128 && *v <= max as i128)
+        .map(|v| CellValue::Number(Number::from(v as i64)))
+}
+
+fn coerce_unsigned(value: &CellValue, max: u64) -> Option<CellValue> {
+    integral_value(value)
+        .filter(|v| *v >= 0 && *v <= max as i128)
+        .map(|v| CellValue::Number(Number::from(v as u64)))
+}
+
+fn coerce_float(value: &CellValue) -> Option<CellValue> {
+    match value {
+        CellValue::Number(_) => Some(value.clone()),
+        CellValue::String(s) => s
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .and_then(Number::from_f64)
+            .map(CellValue::Number),
+        _ => None,
+    }
+}
+
+fn coerce_decimal(value: &CellValue) -> Option<CellValue> {
+    match value {
+        CellValue::Number(_) => to_decimal(value).map(|_| value.clone()),
+        CellValue::String(s) => parse_decimal(s).map(decimal_to_cell),
+        _ => None,
+    }
+}
+
+fn coerce_string(value: &CellValue) -> Option<CellValue> {
+    match value {
+        CellValue::String(_) => Some(value.clone()),
+        CellValue::Number(n) => Some(CellValue::String(n.to_string())),
+        CellValue::Bool(b) => Some(CellValue::String(b.to_string())),
+        _ => None,
+    }
+}
+
+fn coerce_binary(value: &CellValue) -> Option<CellValue> {
+    match value {
+        CellValue::String(s) => BASE64
+            .decode(s.trim())
+            .ok()
+            .map(|bytes| CellValue::String(BASE64.encode(bytes))),
+        CellValue::Array(items) => items
+            .iter()
+            .map(|item| item.as_u64().and_then(|b| u8::try_from(b).ok()))
+            .collect::<Option<Vec<u8>>>()
+            .map(|bytes| CellValue::String(BASE64.encode(bytes))),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_coerce_temporal() {
+        assert_eq!(ColumnType::Date.coerce(json!("20240301")), Ok(json!("2024-03-01")));
+        assert_eq!(ColumnType::Time.coerce(json!("08:30")), Ok(json!("08:30:00")));
+        assert_eq!(
+            ColumnType::DateTime.coerce(json!("2024-03-01 08:30:15.250")),
+            Ok(json!("2024-03-01T08:30:15.250"))
+        );
+        assert_eq!(
+            ColumnType::DateTime.coerce(json!("2024-03-01")),
+            Ok(json!("2024-03-01T00:00:00"))
+        );
+        assert_eq!(
+            ColumnType::DateTimeTz.coerce(json!("2024-03-01 08:30:00+08:00")),
+            Ok(json!("2024-03-01T08:30:00+08:00"))
+        );
+        assert!(ColumnType::Date.coerce(json!("2024-02-30")).is_err());
+        assert!(ColumnType::DateTimeTz.coerce(json!("2024-03-01T08:30:00")).is_err());
+    }
+
+    #[test]
+    fn test_coerce_numeric() {
+        assert_eq!(ColumnType::I32.coerce(json!("42")), Ok(json!(42)));
+        assert_eq!(ColumnType::I64.coerce(json!(7.0)), Ok(json!(7)));
+        assert!(ColumnType::U8.coerce(json!(-1)).is_err());
+        assert!(ColumnType::I16.coerce(json!(1.5)).is_err());
+        assert_eq!(ColumnType::F64.coerce(json!("3.25")), Ok(json!(3.25)));
+        assert_eq!(ColumnType::F32.coerce(json!("0.1")), Ok(json!(0.1)));
+        assert_eq!(ColumnType::F32.coerce(json!(-3)), Ok(json!(-3.0)));
+        // 单精度无法无损保存的值与列式存储一样被拒绝
+        assert!(ColumnType::F32.coerce(json!(0.123456789)).is_err());
+        assert!(ColumnType::F32.coerce(json!(1e300)).is_err());
+        assert_eq!(ColumnType::Decimal.coerce(json!("100.10")), Ok(json!(100.1)));
+        // 超出 f64 精度的定点数保留为字符串
+        assert_eq!(
+            ColumnType::Decimal.coerce(json!("12345678901234567.89")),
+            Ok(json!("12345678901234567.89"))
+        );
+        assert!(ColumnType::Decimal.coerce(json!("abc")).is_err());
+    }
+
+    #[test]
+    fn test_coerce_other_types() {
+        assert_eq!(ColumnType::Bool.coerce(json!("1")), Ok(json!(true)));
+        assert_eq!(ColumnType::String.coerce(json!(12)), Ok(json!("12")));
+        assert_eq!(
+            ColumnType::Uuid.coerce(json!("67E55044-10B1-426F-9247-BB680E5FE0C8")),
+            Ok(json!("67e55044-10b1-426f-9247-bb680e5fe0c8"))
+        );
+        assert_eq!(ColumnType::Binary.coerce(json!([104, 105])), Ok(json!("aGk=")));
+        assert!(ColumnType::Binary.coerce(json!("not base64!")).is_err());
+        assert_eq!(ColumnType::Json.coerce(json!({"a": 1})), Ok(json!({"a": 1})));
+        assert_eq!(ColumnType::Date.coerce(CellValue::Null), Ok(CellValue::Null));
+    }
+
+    #[test]
+    fn test_to_decimal_is_exact() {
+        let total: Decimal = [json!(0.1), json!(0.2), json!("0.3")]
+            .iter()
+            .filter_map(to_decimal)
+            .sum();
+        assert_eq!(total, Decimal::from_str("0.6").unwrap());
+    }
+}
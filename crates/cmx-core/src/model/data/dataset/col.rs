@@ -1,7 +1,10 @@
 use std::collections::HashMap;
+use std::str::FromStr;
 
 use crate::{model::data::cell::CellValue, model::meta::fields::SYS_OBJCOLS};
 use serde::{Deserialize, Serialize};
+
+use super::ColumnType;
 // use thiserror::Error;
 
 
@@ -17,13 +20,131 @@ impl ColumnDef {
     pub fn set(&mut self, field: SYS_OBJCOLS, value: CellValue) {
         self.data.insert(field, value);
     }
+    /// 所有已设置的属性
+    pub fn fields(&self) -> impl Iterator<Item = (&SYS_OBJCOLS, &CellValue)> {
+        self.data.iter()
+    }
     pub fn col_id(&self) -> String {
         self.get(&SYS_OBJCOLS::COL_ID)
-            .map(|v| v.to_string())
+            .map(|v| v.as_str().map(str::to_string).unwrap_or_else(|| v.to_string()))
             .unwrap_or_default()
     }
     pub fn col_isfkey(&self) -> Option<bool> {
         self.get(&SYS_OBJCOLS::COL_ISFKEY)
             .and_then(|v| v.as_bool())
     }
+
+    /// 字符串属性，非字符串值按 JSON 文本返回，空字符串视为未设置
+    pub fn get_str(&self, field: &SYS_OBJCOLS) -> Option<String> {
+        self.get(field)
+            .filter(|v| !v.is_null())
+            .map(|v| v.as_str().map(str::to_string).unwrap_or_else(|| v.to_string()))
+            .filter(|s| !s.is_empty())
+    }
+
+    /// 外键列引用的字典 ID：`COL_ISFKEY` 为真时的 `COL_FOBJ`，其他列返回 `None`
+    pub fn foreign_dict(&self) -> Option<String> {
+        let is_foreign = self.get(&SYS_OBJCOLS::COL_ISFKEY).and_then(flag_value).unwrap_or(false);
+        if !is_foreign {
+            return None;
+        }
+        self.get_str(&SYS_OBJCOLS::COL_FOBJ)
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+    }
+
+    /// 列名称（COL_MC）
+    pub fn col_mc(&self) -> Option<String> {
+        self.get_str(&SYS_OBJCOLS::COL_MC)
+    }
+
+    /// 列别名（COL_ALIAS），多个别名以逗号或分号分隔
+    pub fn col_aliases(&self) -> Vec<String> {
+        self.get_str(&SYS_OBJCOLS::COL_ALIAS)
+            .map(|s| {
+                s.split([',', ';', '，', '；'])
+                    .map(str::trim)
+                    .filter(|alias| !alias.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// 列类型（COL_TYPE），未设置或无法识别时为 `String`
+    ///
+    /// 接受 `ColumnType` 的名称（如 `"I32"`、`"Decimal"`）和常见的 SQL 类型名（如 `"varchar"`、`"numeric"`）。
+    pub fn column_type(&self) -> ColumnType {
+        self.get_str(&SYS_OBJCOLS::COL_TYPE)
+            .and_then(|s| s.parse().ok())
+            .unwrap_or_default()
+    }
+
+    /// 非负整数属性，如 COL_LEN、COL_PREC、COL_SCALE，接受数值和数字字符串
+    pub fn get_u32(&self, field: &SYS_OBJCOLS) -> Option<u32> {
+        match self.get(field)? {
+            CellValue::Number(n) => n.as_u64().and_then(|v| u32::try_from(v).ok()),
+            CellValue::String(s) => s.trim().parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// 是否多语言列（COL_LANG），未设置时不是
+    pub fn is_multilingual(&self) -> bool {
+        self.get(&SYS_OBJCOLS::COL_LANG).and_then(flag_value).unwrap_or(false)
+    }
+
+    /// 是否允许为空（COL_ISNULL），未设置时允许
+    pub fn is_nullable(&self) -> bool {
+        self.get(&SYS_OBJCOLS::COL_ISNULL).and_then(flag_value).unwrap_or(true)
+    }
+}
+
+/// 解析元数据中的标志位：布尔值、`0`/`1`、`"0"`/`"1"`、`"Y"`/`"N"`、`"true"`/`"false"`
+pub(crate) fn flag_value(value: &CellValue) -> Option<bool> {
+    match value {
+        CellValue::Bool(b) => Some(*b),
+        CellValue::Number(n) => n.as_i64().map(|v| v != 0),
+        CellValue::String(s) => match s.trim().to_ascii_uppercase().as_str() {
+            "1" | "Y" | "TRUE" => Some(true),
+            "0" | "N" | "FALSE" => Some(false),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+impl FromStr for ColumnType {
+    type Err = String;
+
+    /// 解析列类型名称，大小写不敏感，类型参数（如 `varchar(50)`）会被忽略
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.split('(').next().unwrap_or_default().trim().to_ascii_lowercase();
+        let column_type = match name.as_str() {
+            "bool" | "boolean" => ColumnType::Bool,
+            "i8" | "tinyint" => ColumnType::I8,
+            "i16" | "smallint" | "int2" => ColumnType::I16,
+            "i32" | "int" | "integer" | "int4" => ColumnType::I32,
+            "i64" | "bigint" | "int8" => ColumnType::I64,
+            "u8" => ColumnType::U8,
+            "u16" => ColumnType::U16,
+            "u32" => ColumnType::U32,
+            "u64" => ColumnType::U64,
+            "f32" | "real" | "float4" => ColumnType::F32,
+            "f64" | "float" | "double" | "double precision" | "float8" => ColumnType::F64,
+            "decimal" | "numeric" | "number" | "money" => ColumnType::Decimal,
+            "string" | "varchar" | "char" | "character" | "character varying" | "text" | "nvarchar" | "nchar" | "clob" => {
+                ColumnType::String
+            }
+            "date" => ColumnType::Date,
+            "time" => ColumnType::Time,
+            "datetime" | "timestamp" | "timestamp without time zone" => ColumnType::DateTime,
+            "datetimetz" | "timestamptz" | "timestamp with time zone" => ColumnType::DateTimeTz,
+            "uuid" | "guid" => ColumnType::Uuid,
+            "binary" | "bytea" | "blob" | "varbinary" => ColumnType::Binary,
+            "json" | "jsonb" => ColumnType::Json,
+            _ => return Err(format!("unknown column type '{}'", s)),
+        };
+        Ok(column_type)
+    }
 }
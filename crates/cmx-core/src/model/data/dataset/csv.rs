@@ -0,0 +1,754 @@
+//! # CSV 导入导出模块
+//!
+//! 读取业务人员提供的 CSV 文件生成 `RowDataSet`，以及把任意数据集写出为 CSV。
+//!
+//! ## 导入
+//!
+//! - [`read_csv_infer`]：从前若干行样本推断 `TableSchema`（列类型、是否可空）后导入
+//! - [`read_csv_with_schema`]：按已有 `TableSchema` 导入，表头依次与列的
+//!   `COL_ID`、`COL_ALIAS`、`COL_MC` 匹配（忽略首尾空白和大小写）
+//!
+//! 每个值按列类型校验（见 [`ColumnType::coerce`]），空字符串视为 NULL，并检查
+//! `COL_ISNULL`。有错误的行不会写入数据集，而是逐个值记入错误报告 [`CsvImport::errors`]。
+//!
+//! ## 导出
+//!
+//! `RowDataSet`、`ColDataSet` 和 `DataSet` 都可以写出为 CSV，支持自定义分隔符、
+//! 引号策略、编码（UTF-8 / GBK）以及按 `COL_MC` 输出本地化的表头。
+//!
+//! ## 示例
+//!
+//! ```rust
+//! use cmx_core::model::data::dataset::csv::{read_csv_infer, CsvOptions};
+//! use cmx_core::model::data::dataset::ColumnType;
+//! use serde_json::json;
+//!
+//! let data = "BM,MC,JE\n01,现金,100.50\n02,银行存款,abc\n";
+//! let import = read_csv_infer("accounts".to_string(), data.as_bytes(), &CsvOptions::default()).unwrap();
+//!
+//! // "abc" 使 JE 列推断为字符串；以 0 开头的编码保持为字符串
+//! assert_eq!(import.dataset.schema["BM"].column_type, ColumnType::String);
+//! assert_eq!(import.dataset.get_cell(0, "BM").unwrap(), &json!("01"));
+//! assert!(import.errors.is_empty());
+//! ```
+
+use std::collections::HashSet;
+use std::io::{Read, Write};
+
+use encoding_rs::GBK;
+use thiserror::Error;
+
+use super::cds::ColDataSet;
+use super::col::ColumnDef;
+use super::rds::RowDataSet;
+use super::{ColumnType, DataSet, DataSetError, TableSchema, TableSchemaBuilder};
+use crate::model::data::cell::CellValue;
+use crate::model::meta::fields::SYS_OBJCOLS;
+
+/// 推断列类型时的候选顺序，靠前的优先
+const INFER_CANDIDATES: [ColumnType; 8] = [
+    ColumnType::I32,
+    ColumnType::I64,
+    ColumnType::Decimal,
+    ColumnType::Bool,
+    ColumnType::Date,
+    ColumnType::DateTime,
+    ColumnType::DateTimeTz,
+    ColumnType::Time,
+];
+
+/// 文本编码
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CsvEncoding {
+    #[default]
+    Utf8,
+    /// GBK（兼容 GB2312），国内 Excel 默认导出的编码
+    Gbk,
+}
+
+/// 写出时的引号策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CsvQuoting {
+    /// 只在包含分隔符、引号或换行时加引号
+    #[default]
+    Necessary,
+    /// 所有字段都加引号
+    Always,
+    /// 非数值字段都加引号
+    NonNumeric,
+    /// 从不加引号
+    Never,
+}
+
+/// 写出时的表头
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CsvHeader {
+    /// 列 ID
+    #[default]
+    ColumnId,
+    /// 本地化列名称（`COL_MC`），未设置时使用列 ID
+    Localized,
+    /// 第一个列别名（`COL_ALIAS`），未设置时使用列 ID
+    Alias,
+    /// 不输出表头
+    None,
+}
+
+/// CSV 读写选项
+///
+/// # 示例
+///
+/// ```rust
+/// use cmx_core::model::data::dataset::csv::{CsvEncoding, CsvHeader, CsvOptions};
+///
+/// let options = CsvOptions::default()
+///     .delimiter(b';')
+///     .encoding(CsvEncoding::Gbk)
+///     .header(CsvHeader::Localized);
+/// assert_eq!(options.delimiter, b';');
+/// ```
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+    /// 分隔符，默认 `,`
+    pub delimiter: u8,
+    /// 引号字符，默认 `"`
+    pub quote: u8,
+    /// 写出时的引号策略
+    pub quoting: CsvQuoting,
+    /// 文本编码
+    pub encoding: CsvEncoding,
+    /// 读取时第一行是否为表头，默认是
+    pub has_header: bool,
+    /// 写出时的表头
+    pub header: CsvHeader,
+    /// 推断列类型时使用的样本行数，默认 1000
+    pub sample_rows: usize,
+    /// 写出 UTF-8 时是否带 BOM（便于 Excel 识别）
+    pub bom: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            quote: b'"',
+            quoting: CsvQuoting::Necessary,
+            encoding: CsvEncoding::Utf8,
+            has_header: true,
+            header: CsvHeader::ColumnId,
+            sample_rows: 1000,
+            bom: false,
+        }
+    }
+}
+
+impl CsvOptions {
+    pub fn delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    pub fn quote(mut self, quote: u8) -> Self {
+        self.quote = quote;
+        self
+    }
+
+    pub fn quoting(mut self, quoting: CsvQuoting) -> Self {
+        self.quoting = quoting;
+        self
+    }
+
+    pub fn encoding(mut self, encoding: CsvEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    pub fn has_header(mut self, has_header: bool) -> Self {
+        self.has_header = has_header;
+        self
+    }
+
+    pub fn header(mut self, header: CsvHeader) -> Self {
+        self.header = header;
+        self
+    }
+
+    pub fn sample_rows(mut self, sample_rows: usize) -> Self {
+        self.sample_rows = sample_rows;
+        self
+    }
+
+    pub fn bom(mut self, bom: bool) -> Self {
+        self.bom = bom;
+        self
+    }
+}
+
+/// CSV 读写错误
+#[derive(Error, Debug)]
+pub enum CsvError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("CSV error: {0}")]
+    Csv(#[from] csv::Error),
+    #[error("Text is not valid {0:?}")]
+    Encoding(CsvEncoding),
+    #[error("Required columns missing from CSV header: {0:?}")]
+    MissingColumns(Vec<String>),
+    #[error(transparent)]
+    DataSet(#[from] DataSetError),
+}
+
+/// 单个值的导入错误类型
+#[derive(Debug, Clone, PartialEq)]
+pub enum CsvErrorKind {
+    /// 值无法转换为列类型
+    TypeMismatch(ColumnType),
+    /// 不可为空的列（`COL_ISNULL` 为否）没有值
+    NotNull,
+    /// 行的字段数与表头不一致
+    FieldCount { expected: usize, actual: usize },
+}
+
+/// 导入错误报告中的一项
+#[derive(Debug, Clone, PartialEq, Error)]
+#[error("line {line}{}: {kind:?}", column.as_ref().map(|c| format!(", column '{}'", c)).unwrap_or_default())]
+pub struct CsvRowError {
+    /// CSV 文件中的行号，从 1 开始（包括表头行）
+    pub line: u64,
+    /// 出错的列 ID，字段数错误时为 `None`
+    pub column: Option<String>,
+    /// 原始文本
+    pub value: Option<String>,
+    pub kind: CsvErrorKind,
+}
+
+/// 导入结果
+#[derive(Debug, Clone)]
+pub struct CsvImport {
+    /// 通过校验的行
+    pub dataset: RowDataSet,
+    /// 导入使用的表定义（推断时为推断结果）
+    pub schema: TableSchema,
+    /// 未通过校验的值，按行号排序
+    pub errors: Vec<CsvRowError>,
+    /// 没有匹配到任何列的表头
+    pub unmapped_headers: Vec<String>,
+}
+
+impl CsvImport {
+    /// 出错的行号（去重）
+    pub fn error_lines(&self) -> Vec<u64> {
+        let mut lines: Vec<u64> = self.errors.iter().map(|e| e.line).collect();
+        lines.dedup();
+        lines
+    }
+}
+
+/// 解码后的 CSV 内容：表头和带行号的记录
+struct CsvContent {
+    headers: Option<Vec<String>>,
+    records: Vec<(u64, csv::StringRecord)>,
+}
+
+fn decode(bytes: &[u8], encoding: CsvEncoding) -> Result<String, CsvError> {
+    match encoding {
+        CsvEncoding::Utf8 => {
+            let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+            String::from_utf8(bytes.to_vec()).map_err(|_| CsvError::Encoding(encoding))
+        }
+        CsvEncoding::Gbk => GBK
+            .decode_without_bom_handling_and_without_replacement(bytes)
+            .map(|text| text.into_owned())
+            .ok_or(CsvError::Encoding(encoding)),
+    }
+}
+
+fn read_content<R: Read>(mut reader: R, options: &CsvOptions) -> Result<CsvContent, CsvError> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    let text = decode(&bytes, options.encoding)?;
+
+    let mut csv_reader = csv::ReaderBuilder::new()
+        .delimiter(options.delimiter)
+        .quote(options.quote)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(text.as_bytes());
+    let mut records = Vec::new();
+    for record in csv_reader.records() {
+        let record = record?;
+        // 跳过完全空白的行
+        if record.iter().all(|field| field.trim().is_empty()) {
+            continue;
+        }
+        let line = record.position().map_or(0, |p| p.line());
+        records.push((line, record));
+    }
+
+    let headers = if options.has_header && !records.is_empty() {
+        let (_, header) = records.remove(0);
+        Some(header.iter().map(|h| h.trim().to_string()).collect())
+    } else {
+        None
+    };
+    Ok(CsvContent { headers, records })
+}
+
+/// 以 0 开头的多位整数（如科目编码 "0101"）按字符串处理
+fn has_leading_zero(text: &str) -> bool {
+    let digits = text.strip_prefix(['-', '+']).unwrap_or(text);
+    digits.len() > 1 && digits.starts_with('0') && !digits.starts_with("0.")
+}
+
+/// 推断一列的类型，所有非空样本都能转换的第一个候选类型胜出
+fn infer_column_type<'a>(samples: impl Iterator<Item = &'a str> + Clone) -> ColumnType {
+    let mut values = samples.filter(|s| !s.is_empty()).peekable();
+    if values.peek().is_none() {
+        return ColumnType::String;
+    }
+    INFER_CANDIDATES
+        .into_iter()
+        .find(|column_type| {
+            values.clone().all(|text| {
+                (!column_type.is_numeric() || !has_leading_zero(text))
+                    && column_type.coerce(CellValue::String(text.to_string())).is_ok()
+            })
+        })
+        .unwrap_or(ColumnType::String)
+}
+
+/// 生成不重复的列 ID，空表头使用 `COL<序号>`
+fn unique_column_ids(headers: &[String]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    headers
+        .iter()
+        .enumerate()
+        .map(|(index, header)| {
+            let base = if header.is_empty() { format!("COL{}", index + 1) } else { header.clone() };
+            let mut id = base.clone();
+            let mut suffix = 2;
+            while !seen.insert(id.clone()) {
+                id = format!("{}_{}", base, suffix);
+                suffix += 1;
+            }
+            id
+        })
+        .collect()
+}
+
+fn infer_schema(content: &CsvContent, sample_rows: usize) -> TableSchema {
+    let width = content
+        .headers
+        .as_ref()
+        .map(Vec::len)
+        .unwrap_or_else(|| content.records.iter().map(|(_, r)| r.len()).max().unwrap_or(0));
+    let headers = content.headers.clone().unwrap_or_else(|| vec![String::new(); width]);
+    let samples = &content.records[..content.records.len().min(sample_rows)];
+
+    let columns = unique_column_ids(&headers)
+        .into_iter()
+        .enumerate()
+        .map(|(index, id)| {
+            let values = samples.iter().map(move |(_, record)| record.get(index).unwrap_or("").trim());
+            let nullable = values.clone().any(str::is_empty);
+            let mut def = ColumnDef::default();
+            def.set(SYS_OBJCOLS::COL_ID, CellValue::String(id));
+            if !headers[index].is_empty() {
+                def.set(SYS_OBJCOLS::COL_MC, CellValue::String(headers[index].clone()));
+            }
+            def.set(SYS_OBJCOLS::COL_TYPE, CellValue::String(format!("{:?}", infer_column_type(values))));
+            def.set(SYS_OBJCOLS::COL_ISNULL, CellValue::String(if nullable { "1" } else { "0" }.to_string()));
+            def
+        })
+        .collect();
+    TableSchemaBuilder::new().with_columns(columns).build()
+}
+
+/// 表头与列定义匹配，返回每个 CSV 字段对应的列序号
+fn map_headers(headers: &[String], schema: &TableSchema) -> Vec<Option<usize>> {
+    let matches = |header: &str, def: &ColumnDef| {
+        let header = header.to_lowercase();
+        def.col_id().to_lowercase() == header
+            || def.col_aliases().iter().any(|alias| alias.to_lowercase() == header)
+            || def.col_mc().is_some_and(|mc| mc.to_lowercase() == header)
+    };
+    let mut used = HashSet::new();
+    headers
+        .iter()
+        .map(|header| {
+            schema
+                .columns
+                .iter()
+                .position(|def| matches(header, def))
+                .filter(|index| used.insert(*index))
+        })
+        .collect()
+}
+
+fn import(
+    dataset_id: String,
+    content: CsvContent,
+    schema: TableSchema,
+    has_header: bool,
+) -> Result<CsvImport, CsvError> {
+    let mapping = match &content.headers {
+        Some(headers) => map_headers(headers, &schema),
+        None => (0..schema.column_count()).map(Some).collect(),
+    };
+    let unmapped_headers = content
+        .headers
+        .iter()
+        .flatten()
+        .zip(&mapping)
+        .filter(|(_, index)| index.is_none())
+        .map(|(header, _)| header.clone())
+        .collect();
+
+    let missing: Vec<String> = schema
+        .columns
+        .iter()
+        .enumerate()
+        .filter(|(index, def)| !def.is_nullable() && !mapping.contains(&Some(*index)))
+        .map(|(_, def)| def.col_id())
+        .collect();
+    if !missing.is_empty() {
+        return Err(CsvError::MissingColumns(missing));
+    }
+
+    let mut dataset = RowDataSet::new(dataset_id);
+    let columns: Vec<(String, ColumnType, bool)> =
+        schema.columns.iter().map(|def| (def.col_id(), def.column_type(), def.is_nullable())).collect();
+    for (id, column_type, _) in &columns {
+        dataset.add_column(id.clone(), *column_type)?;
+    }
+
+    let expected = if has_header { mapping.len() } else { columns.len() };
+    let mut errors = Vec::new();
+    for (line, record) in &content.records {
+        if record.len() != expected {
+            errors.push(CsvRowError {
+                line: *line,
+                column: None,
+                value: None,
+                kind: CsvErrorKind::FieldCount { expected, actual: record.len() },
+            });
+            continue;
+        }
+
+        let mut values = vec![CellValue::Null; columns.len()];
+        let mut texts = vec![None; columns.len()];
+        for (field, index) in record.iter().zip(&mapping) {
+            if let Some(index) = index {
+                texts[*index] = Some(field.trim());
+            }
+        }
+        let error_count = errors.len();
+        for (index, (id, column_type, nullable)) in columns.iter().enumerate() {
+            let text = texts[index].unwrap_or("");
+            if text.is_empty() {
+                if !nullable {
+                    errors.push(CsvRowError {
+                        line: *line,
+                        column: Some(id.clone()),
+                        value: None,
+                        kind: CsvErrorKind::NotNull,
+                    });
+                }
+                continue;
+            }
+            match column_type.coerce(CellValue::String(text.to_string())) {
+                Ok(value) => values[index] = value,
+                Err(_) => errors.push(CsvRowError {
+                    line: *line,
+                    column: Some(id.clone()),
+                    value: Some(text.to_string()),
+                    kind: CsvErrorKind::TypeMismatch(*column_type),
+                }),
+            }
+        }
+        if errors.len() == error_count {
+            dataset.add_row(values)?;
+        }
+    }
+
+    Ok(CsvImport { dataset, schema, errors, unmapped_headers })
+}
+
+/// 推断表定义并导入 CSV
+///
+/// # 参数
+///
+/// * `dataset_id` - 生成的数据集 ID
+/// * `reader` - CSV 数据来源
+/// * `options` - 读取选项，`sample_rows` 决定用于推断的行数
+///
+/// # 行为说明
+///
+/// - 列 ID 取表头文本（重复时追加 `_2`、`_3`，空表头或无表头时为 `COL<序号>`），`COL_MC` 为表头文本
+/// - 列类型依次尝试 `I32`、`I64`、`Decimal`、`Bool`、`Date`、`DateTime`、`DateTimeTz`、`Time`，
+///   所有非空样本都能转换的第一个类型胜出，否则为 `String`；以 0 开头的多位数字视为编码，不推断为数值
+/// - 样本中有空值的列 `COL_ISNULL` 为 `"1"`，否则为 `"0"`
+/// - 样本之外的行可能因类型不符或为空而进入错误报告
+pub fn read_csv_infer<R: Read>(dataset_id: String, reader: R, options: &CsvOptions) -> Result<CsvImport, CsvError> {
+    let content = read_content(reader, options)?;
+    let schema = infer_schema(&content, options.sample_rows);
+    import(dataset_id, content, schema, options.has_header)
+}
+
+/// 按已有表定义导入 CSV
+///
+/// # 行为说明
+///
+/// - 有表头时，表头依次与列的 `COL_ID`、`COL_ALIAS`、`COL_MC` 匹配，匹配不到的表头记入
+///   `unmapped_headers`，CSV 中缺少的列为 NULL
+/// - 无表头时，字段按列定义的顺序对应
+/// - 不可为空的列在表头中缺失时返回 `CsvError::MissingColumns`
+pub fn read_csv_with_schema<R: Read>(
+    dataset_id: String,
+    reader: R,
+    schema: &TableSchema,
+    options: &CsvOptions,
+) -> Result<CsvImport, CsvError> {
+    let content = read_content(reader, options)?;
+    import(dataset_id, content, schema.clone(), options.has_header)
+}
+
+/// 单元格值转为 CSV 文本：NULL 为空字符串，数组和对象为 JSON 文本
+fn cell_text(value: &CellValue) -> String {
+    match value {
+        CellValue::Null => String::new(),
+        CellValue::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// 按表头选项确定一列的表头文本
+fn header_text(column_id: &str, header: CsvHeader, meta: Option<&TableSchema>) -> String {
+    let def = meta.and_then(|schema| schema.get_column(column_id));
+    let text = match header {
+        CsvHeader::Localized => def.and_then(ColumnDef::col_mc),
+        CsvHeader::Alias => def.and_then(|def| def.col_aliases().into_iter().next()),
+        CsvHeader::ColumnId | CsvHeader::None => None,
+    };
+    text.unwrap_or_else(|| column_id.to_string())
+}
+
+fn write_rows<'a, W: Write>(
+    mut writer: W,
+    column_ids: &[String],
+    rows: impl Iterator<Item = &'a [CellValue]>,
+    options: &CsvOptions,
+    meta: Option<&TableSchema>,
+) -> Result<(), CsvError> {
+    let quote_style = match options.quoting {
+        CsvQuoting::Necessary => csv::QuoteStyle::Necessary,
+        CsvQuoting::Always => csv::QuoteStyle::Always,
+        CsvQuoting::NonNumeric => csv::QuoteStyle::NonNumeric,
+        CsvQuoting::Never => csv::QuoteStyle::Never,
+    };
+    let mut csv_writer = csv::WriterBuilder::new()
+        .delimiter(options.delimiter)
+        .quote(options.quote)
+        .quote_style(quote_style)
+        .from_writer(Vec::new());
+
+    if options.header != CsvHeader::None {
+        csv_writer.write_record(column_ids.iter().map(|id| header_text(id, options.header, meta)))?;
+    }
+    for row in rows {
+        csv_writer.write_record((0..column_ids.len()).map(|index| row.get(index).map(cell_text).unwrap_or_default()))?;
+    }
+    let bytes = csv_writer.into_inner().map_err(|e| CsvError::Io(e.into_error()))?;
+
+    match options.encoding {
+        CsvEncoding::Utf8 => {
+            if options.bom {
+                writer.write_all(b"\xEF\xBB\xBF")?;
+            }
+            writer.write_all(&bytes)?;
+        }
+        CsvEncoding::Gbk => {
+            let text = String::from_utf8(bytes).map_err(|_| CsvError::Encoding(CsvEncoding::Utf8))?;
+            let (encoded, _, unmappable) = GBK.encode(&text);
+            if unmappable {
+                return Err(CsvError::Encoding(CsvEncoding::Gbk));
+            }
+            writer.write_all(&encoded)?;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+impl RowDataSet {
+    /// 写出为 CSV
+    ///
+    /// # 参数
+    ///
+    /// * `writer` - 输出目标
+    /// * `options` - 写出选项
+    /// * `meta` - 可选的表定义，`CsvHeader::Localized` / `CsvHeader::Alias` 从中读取列名称和别名
+    ///
+    /// # 注意事项
+    ///
+    /// - 子数据集不会写出
+    /// - 选择 GBK 编码时，包含 GBK 无法表示的字符会返回 `CsvError::Encoding`
+    pub fn write_csv<W: Write>(&self, writer: W, options: &CsvOptions, meta: Option<&TableSchema>) -> Result<(), CsvError> {
+        let mut columns: Vec<(&String, usize)> = self.schema.iter().map(|(name, info)| (name, info.index)).collect();
+        columns.sort_by_key(|(_, index)| *index);
+        let column_ids: Vec<String> = columns.iter().map(|(name, _)| name.to_string()).collect();
+        write_rows(writer, &column_ids, self.rows.iter().map(|row| row.values.as_slice()), options, meta)
+    }
+}
+
+impl ColDataSet {
+    /// 写出为 CSV，参数同 [`RowDataSet::write_csv`]
+    pub fn write_csv<W: Write>(&self, writer: W, options: &CsvOptions, meta: Option<&TableSchema>) -> Result<(), CsvError> {
+        let rows: Vec<Vec<CellValue>> = (0..self.row_count())
+            .map(|index| {
+                self.column_names
+                    .iter()
+                    .map(|name| self.columns[name].get(index).unwrap_or(CellValue::Null))
+                    .collect()
+            })
+            .collect();
+        write_rows(writer, &self.column_names, rows.iter().map(Vec::as_slice), options, meta)
+    }
+}
+
+impl DataSet {
+    /// 写出为 CSV，列顺序和列 ID 取自 `schema`
+    pub fn write_csv<W: Write>(&self, writer: W, schema: &TableSchema, options: &CsvOptions) -> Result<(), CsvError> {
+        let column_ids: Vec<String> = schema.columns.iter().map(ColumnDef::col_id).collect();
+        let mut rows = Vec::with_capacity(self.row_count());
+        for index in 0..self.row_count() {
+            rows.push(self.get_row(index)?.values());
+        }
+        write_rows(writer, &column_ids, rows.into_iter(), options, Some(schema))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn create_account_schema() -> TableSchema {
+        let column = |id: &str, mc: &str, alias: Option<&str>, column_type: &str, nullable: &str| {
+            let mut def = ColumnDef::default();
+            def.set(SYS_OBJCOLS::COL_ID, json!(id));
+            def.set(SYS_OBJCOLS::COL_MC, json!(mc));
+            if let Some(alias) = alias {
+                def.set(SYS_OBJCOLS::COL_ALIAS, json!(alias));
+            }
+            def.set(SYS_OBJCOLS::COL_TYPE, json!(column_type));
+            def.set(SYS_OBJCOLS::COL_ISNULL, json!(nullable));
+            def
+        };
+        TableSchemaBuilder::new()
+            .with_columns(vec![
+                column("KM_BM", "科目编码", Some("code,account"), "varchar(30)", "0"),
+                column("KM_MC", "科目名称", None, "varchar(100)", "1"),
+                column("KM_JE", "金额", None, "numeric(18,2)", "0"),
+                column("KM_RQ", "日期", None, "date", "1"),
+            ])
+            .build()
+    }
+
+    #[test]
+    fn test_infer_schema() {
+        let data = "id,code,amount,flag,day,note,\n1,001,1.5,true,2024-01-31,x,\n2,002,-3,false,2024/02/01,,\n3,010,7,true,20240301,z,\n";
+        let import = read_csv_infer("t".to_string(), data.as_bytes(), &CsvOptions::default()).unwrap();
+        let types: Vec<ColumnType> = import.schema.columns.iter().map(ColumnDef::column_type).collect();
+        assert_eq!(
+            types,
+            vec![
+                ColumnType::I32,
+                ColumnType::String,
+                ColumnType::Decimal,
+                ColumnType::Bool,
+                ColumnType::Date,
+                ColumnType::String,
+                ColumnType::String,
+            ]
+        );
+        assert_eq!(import.schema.columns[6].col_id(), "COL7");
+        assert!(!import.schema.columns[0].is_nullable());
+        assert!(import.schema.columns[5].is_nullable());
+        assert_eq!(import.dataset.row_count(), 3);
+        assert_eq!(import.dataset.get_cell(1, "day").unwrap(), &json!("2024-02-01"));
+        assert_eq!(import.dataset.get_cell(1, "note").unwrap(), &CellValue::Null);
+
+        // 样本之外的行不符合推断结果时进入错误报告
+        let options = CsvOptions::default().sample_rows(1);
+        let import = read_csv_infer("t".to_string(), data.as_bytes(), &options).unwrap();
+        assert_eq!(import.dataset.row_count(), 2);
+        assert_eq!(import.error_lines(), vec![3]);
+        assert_eq!(import.errors[0].column.as_deref(), Some("note"));
+    }
+
+    #[test]
+    fn test_read_with_schema_and_error_report() {
+        let data = "Code;科目名称;金额;备注\n1001;现金;100.5;a\n1002;;abc;b\n;银行;1;c\n1003;x\n";
+        let options = CsvOptions::default().delimiter(b';');
+        let import = read_csv_with_schema("km".to_string(), data.as_bytes(), &create_account_schema(), &options).unwrap();
+
+        assert_eq!(import.unmapped_headers, vec!["备注".to_string()]);
+        assert_eq!(import.dataset.row_count(), 1);
+        assert_eq!(import.dataset.get_cell(0, "KM_BM").unwrap(), &json!("1001"));
+        assert_eq!(import.dataset.get_cell(0, "KM_JE").unwrap(), &json!(100.5));
+        assert_eq!(import.dataset.get_cell(0, "KM_RQ").unwrap(), &CellValue::Null);
+
+        assert_eq!(import.errors.len(), 3);
+        assert_eq!(import.errors[0].line, 3);
+        assert_eq!(import.errors[0].column.as_deref(), Some("KM_JE"));
+        assert_eq!(import.errors[0].kind, CsvErrorKind::TypeMismatch(ColumnType::Decimal));
+        assert_eq!(import.errors[1].line, 4);
+        assert_eq!(import.errors[1].kind, CsvErrorKind::NotNull);
+        assert_eq!(import.errors[2].kind, CsvErrorKind::FieldCount { expected: 4, actual: 2 });
+
+        let missing = read_csv_with_schema("km".to_string(), "科目名称\nx\n".as_bytes(), &create_account_schema(), &options);
+        assert!(matches!(missing, Err(CsvError::MissingColumns(columns)) if columns == vec!["KM_BM", "KM_JE"]));
+    }
+
+    #[test]
+    fn test_write_and_read_gbk_with_localized_header() {
+        let schema = create_account_schema();
+        let mut dataset = RowDataSet::new("km".to_string());
+        for def in &schema.columns {
+            dataset.add_column(def.col_id(), def.column_type()).unwrap();
+        }
+        dataset.add_row(vec![json!("1001"), json!("库存现金, 人民币"), json!("12.30"), json!("2024-01-31")]).unwrap();
+        dataset.add_row(vec![json!("1002"), CellValue::Null, json!(5), CellValue::Null]).unwrap();
+
+        let options = CsvOptions::default().encoding(CsvEncoding::Gbk).header(CsvHeader::Localized);
+        let mut buffer = Vec::new();
+        dataset.write_csv(&mut buffer, &options, Some(&schema)).unwrap();
+        assert!(String::from_utf8(buffer.clone()).is_err());
+        let (text, _, _) = GBK.decode(&buffer);
+        assert!(text.starts_with("科目编码,科目名称,金额,日期\n1001,\"库存现金, 人民币\",12.3,2024-01-31\n"));
+
+        let import = read_csv_with_schema("km".to_string(), buffer.as_slice(), &schema, &options).unwrap();
+        assert!(import.errors.is_empty());
+        for (restored, source) in import.dataset.rows.iter().zip(&dataset.rows) {
+            assert_eq!(restored.values, source.values);
+        }
+
+        let columnar = ColDataSet::try_from(&dataset).unwrap();
+        let mut quoted = Vec::new();
+        let options = CsvOptions::default().quoting(CsvQuoting::Always).header(CsvHeader::Alias).bom(true);
+        columnar.write_csv(&mut quoted, &options, Some(&schema)).unwrap();
+        assert!(String::from_utf8(quoted).unwrap().starts_with("\u{feff}\"code\",\"KM_MC\""));
+
+        let mut unmappable = RowDataSet::new("t".to_string());
+        unmappable.add_column("S".to_string(), ColumnType::String).unwrap();
+        unmappable.add_row(vec![json!("😀")]).unwrap();
+        let options = CsvOptions::default().encoding(CsvEncoding::Gbk);
+        assert!(matches!(
+            unmappable.write_csv(Vec::new(), &options, None),
+            Err(CsvError::Encoding(CsvEncoding::Gbk))
+        ));
+    }
+}
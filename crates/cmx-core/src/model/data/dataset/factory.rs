@@ -0,0 +1,529 @@
+//! # 行工厂模块
+//!
+//! 按表定义创建新行：填充列默认值（`SYS_OBJCOLS::COL_DEFAULT`）和系统列，并在行被修改时
+//! 刷新修改时间和修改人。
+//!
+//! ## 默认值
+//!
+//! | 写法 | 含义 |
+//! |------|------|
+//! | `=表达式` | 公式，语法见 [`super::formula`]，可以引用同一行的其他列 |
+//! | `@键` | 取 `SVRContext` 中的值，如 `@USER`、`@UNIT`、`@DATE`（业务日期） |
+//! | `'文本'` 或其他 | 常量，按列类型转换 |
+//!
+//! 常量和上下文默认值先填充，公式默认值随后按列顺序求值。
+//!
+//! ## 系统列
+//!
+//! 未设置 `COL_DEFAULT` 的系统列自动填充：
+//!
+//! | 列 | 新建时 | 修改时 |
+//! |----|--------|--------|
+//! | `F_GUID` | 新的 UUID | - |
+//! | `F_CRDATE`、`F_CHDATE` | 当前时间 | 刷新 `F_CHDATE` |
+//! | `F_CRUSER`、`F_CHUSER` | 当前用户 | 刷新 `F_CHUSER` |
+//! | `F_STAU` | `0` | - |
+//! | 单位列（多单位对象，见 [`TableSchema::unit_column`]） | 当前单位 | - |
+//!
+//! 只填充值为 `NULL` 的单元格，已经给出的值保持不变。上下文的值在创建行时读取，
+//! 因此同一个行工厂可以跟随 `SVRContext` 的变化。
+//!
+//! ## 示例
+//!
+//! ```rust
+//! use cmx_core::model::data::context::SVRContext;
+//! use cmx_core::model::data::dataset::col::ColumnDef;
+//! use cmx_core::model::data::dataset::TableSchemaBuilder;
+//! use cmx_core::model::meta::fields::SYS_OBJCOLS;
+//! use serde_json::json;
+//!
+//! let column = |name: &str, default: Option<&str>| {
+//!     let mut column = ColumnDef::default();
+//!     column.set(SYS_OBJCOLS::COL_ID, json!(name));
+//!     if let Some(default) = default {
+//!         column.set(SYS_OBJCOLS::COL_DEFAULT, json!(default));
+//!     }
+//!     column
+//! };
+//! let schema = TableSchemaBuilder::new()
+//!     .with_columns(vec![column("DWDM", Some("@UNIT")), column("BZ", Some("'RMB'")), column("F_CRUSER", None)])
+//!     .build();
+//!
+//! let ctx = SVRContext::new();
+//! ctx.set(SVRContext::USER, json!("admin"));
+//! ctx.set(SVRContext::UNIT, json!("0101"));
+//! let factory = schema.row_factory(ctx).unwrap();
+//! assert_eq!(factory.new_row().unwrap(), vec![json!("0101"), json!("RMB"), json!("admin")]);
+//! ```
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use chrono::{DateTime, FixedOffset, Local};
+use thiserror::Error;
+
+use super::coerce::{format_date, format_datetime, format_datetime_tz};
+use super::formula::{BoundFormula, Formula, FormulaError};
+use super::rds::RowDataSet;
+use super::{ColumnType, DataSetError, TableSchema};
+use crate::model::data::cell::CellValue;
+use crate::model::data::context::SVRContext;
+use crate::model::meta::fields::SYS_OBJCOLS;
+
+/// 列默认值，对应 `COL_DEFAULT`
+#[derive(Debug, Clone)]
+pub enum DefaultValue {
+    /// 常量
+    Literal(CellValue),
+    /// 上下文中的值，保存上下文的键
+    Context(String),
+    /// 公式
+    Formula(Formula),
+}
+
+impl FromStr for DefaultValue {
+    type Err = FormulaError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let text = s.trim();
+        if let Some(formula) = text.strip_prefix('=') {
+            return Ok(DefaultValue::Formula(formula.parse()?));
+        }
+        if let Some(key) = text.strip_prefix('@').filter(|key| !key.is_empty()) {
+            return Ok(DefaultValue::Context(key.to_string()));
+        }
+        let literal = text
+            .strip_prefix('\'')
+            .and_then(|t| t.strip_suffix('\''))
+            .map(|t| t.replace("''", "'"))
+            .unwrap_or_else(|| text.to_string());
+        Ok(DefaultValue::Literal(CellValue::String(literal)))
+    }
+}
+
+/// 自动填充的系统列
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SystemColumn {
+    Guid,
+    CreatedAt,
+    ChangedAt,
+    CreatedBy,
+    ChangedBy,
+    Status,
+}
+
+impl SystemColumn {
+    fn from_column(name: &str) -> Option<Self> {
+        Some(match name {
+            "F_GUID" => SystemColumn::Guid,
+            "F_CRDATE" => SystemColumn::CreatedAt,
+            "F_CHDATE" => SystemColumn::ChangedAt,
+            "F_CRUSER" => SystemColumn::CreatedBy,
+            "F_CHUSER" => SystemColumn::ChangedBy,
+            "F_STAU" => SystemColumn::Status,
+            _ => return None,
+        })
+    }
+
+    /// 行被修改时是否刷新
+    fn is_change_stamp(self) -> bool {
+        matches!(self, SystemColumn::ChangedAt | SystemColumn::ChangedBy)
+    }
+}
+
+/// 行工厂错误
+#[derive(Error, Debug)]
+pub enum RowFactoryError {
+    #[error("Default value {value} of column '{column}' does not fit type {column_type:?}")]
+    InvalidDefault { column: String, value: CellValue, column_type: ColumnType },
+    #[error("Default formula of column '{column}': {source}")]
+    Formula {
+        column: String,
+        #[source]
+        source: FormulaError,
+    },
+    #[error("Unknown column '{0}'")]
+    UnknownColumn(String),
+}
+
+#[derive(Debug, Clone)]
+enum Fill<F> {
+    Literal(CellValue),
+    Context(String),
+    Formula(F),
+    System(SystemColumn),
+}
+
+#[derive(Debug, Clone)]
+struct ColumnFill<F> {
+    column: String,
+    column_type: ColumnType,
+    index: usize,
+    fill: Fill<F>,
+}
+
+/// 按表定义创建新行的工厂，由 [`TableSchema::row_factory`] 创建
+#[derive(Debug, Clone)]
+pub struct RowFactory {
+    columns: Vec<ColumnFill<Formula>>,
+    width: usize,
+    bound: Arc<BoundRowFactory>,
+    context: SVRContext,
+}
+
+impl TableSchema {
+    /// 创建行工厂，默认值和系统列的规则见 [`super::factory`]
+    ///
+    /// # 返回值
+    ///
+    /// - `Err(RowFactoryError::InvalidDefault)` - 常量默认值无法转换为列类型
+    /// - `Err(RowFactoryError::Formula)` - 公式默认值无法解析、引用了不存在的列或类型不匹配
+    pub fn row_factory(&self, context: SVRContext) -> Result<RowFactory, RowFactoryError> {
+        let unit_column = self.unit_column().filter(|_| self.is_multi_unit());
+        let mut columns = Vec::new();
+        for (index, def) in self.columns.iter().enumerate() {
+            let column = def.col_id();
+            let column_type = def.column_type();
+            let fill = match def.get_str(&SYS_OBJCOLS::COL_DEFAULT) {
+                Some(text) => {
+                    let invalid = |source| RowFactoryError::Formula { column: column.clone(), source };
+                    match text.parse().map_err(invalid)? {
+                        DefaultValue::Literal(value) => Fill::Literal(column_type.coerce(value).map_err(|value| {
+                            RowFactoryError::InvalidDefault { column: column.clone(), value, column_type }
+                        })?),
+                        DefaultValue::Context(key) => Fill::Context(key),
+                        DefaultValue::Formula(formula) => {
+                            formula.check_schema(self).map_err(invalid)?;
+                            Fill::Formula(formula)
+                        }
+                    }
+                }
+                None if unit_column.as_ref() == Some(&column) => Fill::Context(SVRContext::UNIT.to_string()),
+                None => match SystemColumn::from_column(&column) {
+                    Some(system) => Fill::System(system),
+                    None => continue,
+                },
+            };
+            columns.push(ColumnFill { column, column_type, index, fill });
+        }
+
+        let width = self.columns.len();
+        let bound = bind(&columns, &context, |name| {
+            self.get_column_index(name).map(|index| (index, self.columns[index].column_type()))
+        })?;
+        Ok(RowFactory { columns, width, bound: Arc::new(bound), context })
+    }
+}
+
+impl RowFactory {
+    /// 创建一行，所有列按表定义的顺序排列，没有默认值的列为 `NULL`
+    ///
+    /// # 返回值
+    ///
+    /// - `Err(DataSetError::TypeMismatch)` - 上下文的值或公式结果无法转换为列类型
+    pub fn new_row(&self) -> Result<Vec<CellValue>, DataSetError> {
+        let mut values = vec![CellValue::Null; self.width];
+        self.bound.fill(0, &mut values, None)?;
+        Ok(values)
+    }
+
+    /// 为值为 `NULL` 的单元格填充默认值，`values` 按表定义的列顺序排列
+    pub fn fill_defaults(&self, values: &mut [CellValue]) -> Result<(), DataSetError> {
+        self.bound.fill(0, values, None)
+    }
+
+    /// 刷新修改时间（`F_CHDATE`）和修改人（`F_CHUSER`）
+    pub fn touch(&self, values: &mut [CellValue]) -> Result<(), DataSetError> {
+        self.bound.touch(0, values)
+    }
+
+    /// 行工厂读取的上下文
+    pub fn context(&self) -> &SVRContext {
+        &self.context
+    }
+
+    /// 有默认值或自动填充的列名
+    pub fn columns(&self) -> Vec<&str> {
+        self.columns.iter().map(|c| c.column.as_str()).collect()
+    }
+
+    /// 指定列的默认值，常量已按列类型转换；系统列和没有默认值的列返回 `None`
+    pub fn default_value(&self, column: &str) -> Option<DefaultValue> {
+        self.columns.iter().find(|c| c.column == column).and_then(|c| match &c.fill {
+            Fill::Literal(value) => Some(DefaultValue::Literal(value.clone())),
+            Fill::Context(key) => Some(DefaultValue::Context(key.clone())),
+            Fill::Formula(formula) => Some(DefaultValue::Formula(formula.clone())),
+            Fill::System(_) => None,
+        })
+    }
+}
+
+/// 将列名解析为列位置和类型，绑定公式默认值
+fn bind<F>(columns: &[ColumnFill<Formula>], context: &SVRContext, resolve: F) -> Result<BoundRowFactory, RowFactoryError>
+where
+    F: Fn(&str) -> Option<(usize, ColumnType)>,
+{
+    let mut bound = Vec::with_capacity(columns.len());
+    for column in columns {
+        let (index, column_type) = resolve(&column.column).ok_or_else(|| RowFactoryError::UnknownColumn(column.column.clone()))?;
+        let fill = match &column.fill {
+            Fill::Literal(value) => Fill::Literal(value.clone()),
+            Fill::Context(key) => Fill::Context(key.clone()),
+            Fill::Formula(formula) => Fill::Formula(formula.bind(|name| resolve(name).map(|(index, _)| index)).map_err(
+                |source| RowFactoryError::Formula { column: column.column.clone(), source },
+            )?),
+            Fill::System(system) => Fill::System(*system),
+        };
+        bound.push(ColumnFill { column: column.column.clone(), column_type, index, fill });
+    }
+    // 公式默认值在其他默认值之后求值
+    bound.sort_by_key(|c| matches!(c.fill, Fill::Formula(_)));
+    Ok(BoundRowFactory { columns: bound, context: context.clone() })
+}
+
+/// 绑定到列位置的行工厂
+#[derive(Debug)]
+pub(crate) struct BoundRowFactory {
+    columns: Vec<ColumnFill<BoundFormula>>,
+    context: SVRContext,
+}
+
+impl BoundRowFactory {
+    /// 为值为 `NULL` 的单元格填充默认值，`row_index` 仅用于错误信息
+    pub(crate) fn fill(&self, row_index: usize, values: &mut [CellValue], children: Option<&HashMap<String, RowDataSet>>) -> Result<(), DataSetError> {
+        let now = Local::now().fixed_offset();
+        for column in &self.columns {
+            if !values.get(column.index).is_some_and(CellValue::is_null) {
+                continue;
+            }
+            let value = match &column.fill {
+                Fill::Literal(value) => value.clone(),
+                Fill::Context(key) => self.context_value(key),
+                Fill::Formula(formula) => formula.eval(values, children),
+                Fill::System(system) => self.system_value(*system, column.column_type, now),
+            };
+            values[column.index] = coerce(row_index, column, value)?;
+        }
+        Ok(())
+    }
+
+    /// 刷新修改时间和修改人
+    pub(crate) fn touch(&self, row_index: usize, values: &mut [CellValue]) -> Result<(), DataSetError> {
+        let now = Local::now().fixed_offset();
+        for column in &self.columns {
+            if let Fill::System(system) = column.fill
+                && system.is_change_stamp()
+                && column.index < values.len()
+            {
+                values[column.index] = coerce(row_index, column, self.system_value(system, column.column_type, now))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn context_value(&self, key: &str) -> CellValue {
+        if key.eq_ignore_ascii_case(SVRContext::DATE) {
+            return self.context.date();
+        }
+        self.context.get(key).unwrap_or(CellValue::Null)
+    }
+
+    fn system_value(&self, system: SystemColumn, column_type: ColumnType, now: DateTime<FixedOffset>) -> CellValue {
+        match system {
+            SystemColumn::Guid => CellValue::String(uuid::Uuid::new_v4().to_string()),
+            SystemColumn::CreatedAt | SystemColumn::ChangedAt => timestamp(column_type, now),
+            SystemColumn::CreatedBy | SystemColumn::ChangedBy => self.context.user().unwrap_or(CellValue::Null),
+            SystemColumn::Status => CellValue::from(0),
+        }
+    }
+}
+
+/// 按列类型格式化当前时间，字符串列使用 `YYYY-MM-DD HH:MM:SS`
+fn timestamp(column_type: ColumnType, now: DateTime<FixedOffset>) -> CellValue {
+    match column_type {
+        ColumnType::Date => format_date(now.date_naive()),
+        ColumnType::DateTimeTz => format_datetime_tz(now),
+        ColumnType::DateTime => format_datetime(now.naive_local()),
+        _ => CellValue::String(now.format("%Y-%m-%d %H:%M:%S").to_string()),
+    }
+}
+
+fn coerce<F>(row_index: usize, column: &ColumnFill<F>, value: CellValue) -> Result<CellValue, DataSetError> {
+    column.column_type.coerce(value).map_err(|value| DataSetError::TypeMismatch {
+        row: row_index,
+        column: column.column.clone(),
+        expected: column.column_type,
+        value,
+    })
+}
+
+impl RowDataSet {
+    /// 设置行工厂，列按名称对应
+    ///
+    /// 之后 `add_row`、`insert_row` 为值为 `NULL` 的单元格填充默认值，`set_cell` 等修改操作
+    /// 刷新修改时间和修改人。已有的行保持不变。
+    ///
+    /// # 返回值
+    ///
+    /// - `Err(RowFactoryError::UnknownColumn)` - 有默认值的列或公式引用的列不在数据集中
+    pub fn set_row_factory(&mut self, factory: &RowFactory) -> Result<(), RowFactoryError> {
+        let bound = bind(&factory.columns, &factory.context, |name| {
+            self.schema.get(name).map(|info| (info.index, info.column_type))
+        })?;
+        self.factory = Some(Arc::new(bound));
+        Ok(())
+    }
+
+    /// 移除行工厂
+    pub fn clear_row_factory(&mut self) {
+        self.factory = None;
+    }
+
+    /// 按行工厂创建一行的值，列按数据集的列顺序排列，不会加入数据集
+    ///
+    /// 没有设置行工厂时所有值为 `NULL`。
+    pub fn new_row_values(&self) -> Result<Vec<CellValue>, DataSetError> {
+        let mut values = vec![CellValue::Null; self.schema.len()];
+        self.fill_defaults(self.rows.len(), &mut values)?;
+        Ok(values)
+    }
+
+    pub(crate) fn fill_defaults(&self, row_index: usize, values: &mut [CellValue]) -> Result<(), DataSetError> {
+        match &self.factory {
+            Some(factory) => factory.fill(row_index, values, None),
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) fn touch_values(&self, row_index: usize, values: &mut [CellValue]) -> Result<(), DataSetError> {
+        match &self.factory {
+            Some(factory) => factory.touch(row_index, values),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::model::data::dataset::col::ColumnDef;
+    use crate::model::data::dataset::TableSchemaBuilder;
+
+    fn column(name: &str, column_type: &str, default: Option<&str>) -> ColumnDef {
+        let mut column = ColumnDef::default();
+        column.set(SYS_OBJCOLS::COL_ID, json!(name));
+        column.set(SYS_OBJCOLS::COL_TYPE, json!(column_type));
+        if let Some(default) = default {
+            column.set(SYS_OBJCOLS::COL_DEFAULT, json!(default));
+        }
+        column
+    }
+
+    fn schema() -> TableSchema {
+        TableSchemaBuilder::new()
+            .with_columns(vec![
+                column("DM", "varchar", None),
+                column("SL", "int", Some("1")),
+                column("JE", "decimal", Some("=SL * 10")),
+                column("RQ", "date", Some("@DATE")),
+                column("F_GUID", "uuid", None),
+                column("F_STAU", "int", None),
+                column("F_CRUSER", "varchar", None),
+                column("F_CHUSER", "varchar", None),
+                column("F_CRDATE", "datetime", None),
+                column("F_CHDATE", "datetime", None),
+            ])
+            .build()
+    }
+
+    fn context() -> SVRContext {
+        let ctx = SVRContext::new();
+        ctx.set(SVRContext::USER, json!("admin"));
+        ctx.set(SVRContext::DATE, json!("2024-06-30"));
+        ctx
+    }
+
+    #[test]
+    fn test_parse_default_value() {
+        assert!(matches!("=SL + 1".parse(), Ok(DefaultValue::Formula(_))));
+        assert!(matches!("@UNIT".parse(), Ok(DefaultValue::Context(key)) if key == "UNIT"));
+        assert!(matches!("'it''s'".parse(), Ok(DefaultValue::Literal(v)) if v == json!("it's")));
+        assert!(matches!(" 0 ".parse(), Ok(DefaultValue::Literal(v)) if v == json!("0")));
+        assert!("=SL +".parse::<DefaultValue>().is_err());
+    }
+
+    #[test]
+    fn test_new_row() {
+        let factory = schema().row_factory(context()).unwrap();
+        assert_eq!(factory.columns().len(), 9);
+        assert!(matches!(factory.default_value("SL"), Some(DefaultValue::Literal(v)) if v == json!(1)));
+        assert!(factory.default_value("F_GUID").is_none());
+
+        let row = factory.new_row().unwrap();
+        assert_eq!(&row[..4], &[json!(null), json!(1), json!(10), json!("2024-06-30")]);
+        assert!(row[4].as_str().is_some_and(|guid| guid.len() == 36));
+        assert_eq!(&row[5..8], &[json!(0), json!("admin"), json!("admin")]);
+        assert!(row[8].is_string() && row[9].is_string());
+        assert_ne!(factory.new_row().unwrap()[4], row[4]);
+
+        let mut values = vec![json!("A"), json!(3), json!(null), json!(null), json!(null), json!(2), json!("u1"), json!(null), json!(null), json!(null)];
+        factory.fill_defaults(&mut values).unwrap();
+        assert_eq!(&values[..3], &[json!("A"), json!(3), json!(30)]);
+        assert_eq!(values[5], json!(2));
+        assert_eq!(values[6], json!("u1"));
+
+        factory.context().set(SVRContext::USER, json!("other"));
+        values[7] = json!("u1");
+        factory.touch(&mut values).unwrap();
+        assert_eq!(&values[6..8], &[json!("u1"), json!("other")]);
+    }
+
+    #[test]
+    fn test_invalid_defaults() {
+        let mut schema = schema();
+        schema.columns[1].set(SYS_OBJCOLS::COL_DEFAULT, json!("abc"));
+        assert!(matches!(schema.row_factory(context()), Err(RowFactoryError::InvalidDefault { column, .. }) if column == "SL"));
+        schema.columns[1].set(SYS_OBJCOLS::COL_DEFAULT, json!("1"));
+        schema.columns[2].set(SYS_OBJCOLS::COL_DEFAULT, json!("=XX * 2"));
+        assert!(matches!(schema.row_factory(context()), Err(RowFactoryError::Formula { column, .. }) if column == "JE"));
+    }
+
+    #[test]
+    fn test_row_factory_on_dataset() {
+        let factory = schema().row_factory(context()).unwrap();
+        let mut dataset = RowDataSet::new("test".to_string());
+        dataset.add_column("DM".to_string(), ColumnType::String).unwrap();
+        dataset.add_column("SL".to_string(), ColumnType::I32).unwrap();
+        dataset.add_column("JE".to_string(), ColumnType::Decimal).unwrap();
+        assert!(matches!(dataset.set_row_factory(&factory), Err(RowFactoryError::UnknownColumn(_))));
+
+        for (name, column_type) in [("RQ", ColumnType::Date), ("F_GUID", ColumnType::Uuid), ("F_STAU", ColumnType::I32)] {
+            dataset.add_column(name.to_string(), column_type).unwrap();
+        }
+        for name in ["F_CRUSER", "F_CHUSER", "F_CRDATE", "F_CHDATE"] {
+            dataset.add_column(name.to_string(), ColumnType::String).unwrap();
+        }
+        dataset.set_row_factory(&factory).unwrap();
+
+        let mut values = dataset.new_row_values().unwrap();
+        assert_eq!(values[2], json!(10));
+        values[0] = json!("A");
+        values[1] = json!(5);
+        values[2] = json!(null);
+        dataset.add_row(values).unwrap();
+        assert_eq!(dataset.get_cell(0, "JE").unwrap(), &json!(50));
+        assert_eq!(dataset.get_cell(0, "F_CHUSER").unwrap(), &json!("admin"));
+
+        factory.context().set(SVRContext::USER, json!("editor"));
+        dataset.set_cell(0, "DM", json!("B")).unwrap();
+        assert_eq!(dataset.get_cell(0, "F_CRUSER").unwrap(), &json!("admin"));
+        assert_eq!(dataset.get_cell(0, "F_CHUSER").unwrap(), &json!("editor"));
+
+        dataset.clear_row_factory();
+        dataset.add_row(vec![json!(null); 10]).unwrap();
+        assert_eq!(dataset.get_cell(1, "SL").unwrap(), &json!(null));
+    }
+}
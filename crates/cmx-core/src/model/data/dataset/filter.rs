@@ -0,0 +1,1086 @@
+//! # 过滤表达式模块
+//!
+//! 为 `RowDataSet` 和 `DataSet` 提供按条件筛选行的能力。过滤表达式既可以通过
+//! `FilterExpr` 的构造方法组合，也可以从类 SQL 的字符串解析得到，便于客户端通过
+//! API 传递过滤条件。
+//!
+//! ## 支持的语法
+//!
+//! - 比较：`=`、`!=`、`<>`、`<`、`<=`、`>`、`>=`
+//! - 逻辑：`AND`、`OR`、`NOT` 以及括号分组
+//! - 集合：`[NOT] IN ('01', '02')`
+//! - 模糊匹配：`[NOT] LIKE '01%'`（`%` 匹配任意串，`_` 匹配单个字符，`\` 转义）
+//! - 空值：`IS [NULL | NOT NULL]`
+//! - 区间：`[NOT] BETWEEN 1 AND 10`
+//!
+//! 关键字不区分大小写；列名可以是普通标识符，也可以用双引号包裹。
+//! 比较遵循 SQL 的三值逻辑：与 `NULL` 比较的结果为“未知”，未知的行不会被选中。
+//!
+//! ## 示例
+//!
+//! ```rust
+//! use cmx_core::model::data::dataset::filter::FilterExpr;
+//!
+//! let expr: FilterExpr = "F_ENABLE = '1' AND DCT_BMCOLID LIKE '01%'".parse().unwrap();
+//! assert_eq!(expr.to_string(), "(F_ENABLE = '1' AND DCT_BMCOLID LIKE '01%')");
+//! ```
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::coerce::{parse_datetime_tz, to_decimal};
+use super::row::RowSet;
+use super::rds::{RowData, RowDataSet};
+use super::{compare_cell_values, compare_numbers, ColumnType, DataSet, DataSetError, TableSchema};
+use crate::model::data::cell::CellValue;
+
+/// 比较运算符
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl CompareOp {
+    fn as_sql(&self) -> &'static str {
+        match self {
+            CompareOp::Eq => "=",
+            CompareOp::Ne => "<>",
+            CompareOp::Lt => "<",
+            CompareOp::Le => "<=",
+            CompareOp::Gt => ">",
+            CompareOp::Ge => ">=",
+        }
+    }
+
+    pub(crate) fn test(&self, ordering: Ordering) -> bool {
+        match self {
+            CompareOp::Eq => ordering == Ordering::Equal,
+            CompareOp::Ne => ordering != Ordering::Equal,
+            CompareOp::Lt => ordering == Ordering::Less,
+            CompareOp::Le => ordering != Ordering::Greater,
+            CompareOp::Gt => ordering == Ordering::Greater,
+            CompareOp::Ge => ordering != Ordering::Less,
+        }
+    }
+}
+
+/// 过滤表达式
+///
+/// 表达式中的列名在执行时才会解析为列索引，因此同一个表达式可以用于
+/// 任何包含相应列的数据集。表达式支持 serde 序列化，也可以通过 `Display`
+/// 输出为可再次解析的字符串。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum FilterExpr {
+    /// 列与常量比较
+    Compare {
+        column: String,
+        op: CompareOp,
+        value: CellValue,
+    },
+    /// 列值是否在常量列表中
+    In {
+        column: String,
+        values: Vec<CellValue>,
+        negated: bool,
+    },
+    /// 列值是否匹配 LIKE 模式
+    Like {
+        column: String,
+        pattern: String,
+        negated: bool,
+    },
+    /// 列值是否为空
+    IsNull { column: String, negated: bool },
+    /// 列值是否位于闭区间内
+    Between {
+        column: String,
+        low: CellValue,
+        high: CellValue,
+        negated: bool,
+    },
+    /// 所有子表达式都成立
+    And(Vec<FilterExpr>),
+    /// 任一子表达式成立
+    Or(Vec<FilterExpr>),
+    /// 子表达式不成立
+    Not(Box<FilterExpr>),
+}
+
+impl FilterExpr {
+    /// 创建比较表达式
+    pub fn compare(column: impl Into<String>, op: CompareOp, value: CellValue) -> Self {
+        FilterExpr::Compare { column: column.into(), op, value }
+    }
+
+    /// 创建 `IN` 表达式
+    pub fn in_list(column: impl Into<String>, values: Vec<CellValue>) -> Self {
+        FilterExpr::In { column: column.into(), values, negated: false }
+    }
+
+    /// 创建 `LIKE` 表达式
+    pub fn like(column: impl Into<String>, pattern: impl Into<String>) -> Self {
+        FilterExpr::Like { column: column.into(), pattern: pattern.into(), negated: false }
+    }
+
+    /// 创建 `IS NULL` 表达式
+    pub fn is_null(column: impl Into<String>) -> Self {
+        FilterExpr::IsNull { column: column.into(), negated: false }
+    }
+
+    /// 创建 `IS NOT NULL` 表达式
+    pub fn is_not_null(column: impl Into<String>) -> Self {
+        FilterExpr::IsNull { column: column.into(), negated: true }
+    }
+
+    /// 创建 `BETWEEN` 表达式
+    pub fn between(column: impl Into<String>, low: CellValue, high: CellValue) -> Self {
+        FilterExpr::Between { column: column.into(), low, high, negated: false }
+    }
+
+    /// 与另一个表达式组合为 `AND`，相邻的 `AND` 会被展平
+    pub fn and(self, other: FilterExpr) -> Self {
+        match self {
+            FilterExpr::And(mut items) => {
+                items.push(other);
+                FilterExpr::And(items)
+            }
+            expr => FilterExpr::And(vec![expr, other]),
+        }
+    }
+
+    /// 与另一个表达式组合为 `OR`，相邻的 `OR` 会被展平
+    pub fn or(self, other: FilterExpr) -> Self {
+        match self {
+            FilterExpr::Or(mut items) => {
+                items.push(other);
+                FilterExpr::Or(items)
+            }
+            expr => FilterExpr::Or(vec![expr, other]),
+        }
+    }
+
+    /// 从字符串解析过滤表达式
+    pub fn parse(text: &str) -> Result<Self, FilterError> {
+        Parser::new(text)?.parse()
+    }
+
+    /// 返回表达式引用的所有列名（按首次出现的顺序，去重）
+    pub fn columns(&self) -> Vec<&str> {
+        let mut columns = Vec::new();
+        self.collect_columns(&mut columns);
+        columns
+    }
+
+    fn collect_columns<'a>(&'a self, columns: &mut Vec<&'a str>) {
+        match self {
+            FilterExpr::Compare { column, .. }
+            | FilterExpr::In { column, .. }
+            | FilterExpr::Like { column, .. }
+            | FilterExpr::IsNull { column, .. }
+            | FilterExpr::Between { column, .. } => {
+                if !columns.contains(&column.as_str()) {
+                    columns.push(column);
+                }
+            }
+            FilterExpr::And(items) | FilterExpr::Or(items) => {
+                items.iter().for_each(|item| item.collect_columns(columns))
+            }
+            FilterExpr::Not(inner) => inner.collect_columns(columns),
+        }
+    }
+
+    /// 将列名解析为列索引，得到可以直接对行求值的过滤器
+    ///
+    /// `resolve` 根据列名返回列索引以及可选的列类型。提供列类型时，表达式中的
+    /// 常量会先按列类型转换，例如 `Date` 列上的 `'2024/01/01'` 会被规整为
+    /// `'2024-01-01'` 再比较。
+    ///
+    /// # 返回值
+    ///
+    /// - `Ok(filter)` - 绑定后的过滤器
+    /// - `Err(DataSetError::ColumnNotFound)` - 表达式引用了不存在的列
+    pub fn bind<F>(&self, resolve: F) -> Result<BoundFilter, DataSetError>
+    where
+        F: Fn(&str) -> Option<(usize, Option<ColumnType>)>,
+    {
+        Ok(BoundFilter { node: self.bind_node(&resolve)? })
+    }
+
+    fn bind_node<F>(&self, resolve: &F) -> Result<BoundNode, DataSetError>
+    where
+        F: Fn(&str) -> Option<(usize, Option<ColumnType>)>,
+    {
+        let lookup = |column: &str| resolve(column).ok_or(DataSetError::ColumnNotFound);
+        let literal = |value: &CellValue, column_type: Option<ColumnType>| match column_type {
+            Some(t) => t.coerce(value.clone()).unwrap_or_else(|v| v),
+            None => value.clone(),
+        };
+
+        Ok(match self {
+            FilterExpr::Compare { column, op, value } => {
+                let (index, column_type) = lookup(column)?;
+                BoundNode::Compare { index, column_type, op: *op, value: literal(value, column_type) }
+            }
+            FilterExpr::In { column, values, negated } => {
+                let (index, column_type) = lookup(column)?;
+                BoundNode::In {
+                    index,
+                    column_type,
+                    values: values.iter().map(|v| literal(v, column_type)).collect(),
+                    negated: *negated,
+                }
+            }
+            FilterExpr::Like { column, pattern, negated } => {
+                let (index, _) = lookup(column)?;
+                BoundNode::Like { index, pattern: LikePattern::new(pattern), negated: *negated }
+            }
+            FilterExpr::IsNull { column, negated } => {
+                let (index, _) = lookup(column)?;
+                BoundNode::IsNull { index, negated: *negated }
+            }
+            FilterExpr::Between { column, low, high, negated } => {
+                let (index, column_type) = lookup(column)?;
+                BoundNode::Between {
+                    index,
+                    column_type,
+                    low: literal(low, column_type),
+                    high: literal(high, column_type),
+                    negated: *negated,
+                }
+            }
+            FilterExpr::And(items) => {
+                BoundNode::And(items.iter().map(|i| i.bind_node(resolve)).collect::<Result<_, _>>()?)
+            }
+            FilterExpr::Or(items) => {
+                BoundNode::Or(items.iter().map(|i| i.bind_node(resolve)).collect::<Result<_, _>>()?)
+            }
+            FilterExpr::Not(inner) => BoundNode::Not(Box::new(inner.bind_node(resolve)?)),
+        })
+    }
+}
+
+impl FromStr for FilterExpr {
+    type Err = FilterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        FilterExpr::parse(s)
+    }
+}
+
+impl std::ops::Not for FilterExpr {
+    type Output = FilterExpr;
+
+    fn not(self) -> Self::Output {
+        FilterExpr::Not(Box::new(self))
+    }
+}
+
+impl fmt::Display for FilterExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let not = |negated: &bool| if *negated { "NOT " } else { "" };
+        match self {
+            FilterExpr::Compare { column, op, value } => {
+                write!(f, "{} {} {}", Ident(column), op.as_sql(), Literal(value))
+            }
+            FilterExpr::In { column, values, negated } => {
+                write!(f, "{} {}IN (", Ident(column), not(negated))?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", Literal(value))?;
+                }
+                write!(f, ")")
+            }
+            FilterExpr::Like { column, pattern, negated } => {
+                write!(f, "{} {}LIKE {}", Ident(column), not(negated), Literal(&CellValue::String(pattern.clone())))
+            }
+            FilterExpr::IsNull { column, negated } => {
+                write!(f, "{} IS {}NULL", Ident(column), not(negated))
+            }
+            FilterExpr::Between { column, low, high, negated } => {
+                write!(f, "{} {}BETWEEN {} AND {}", Ident(column), not(negated), Literal(low), Literal(high))
+            }
+            FilterExpr::And(items) | FilterExpr::Or(items) => {
+                let sep = if matches!(self, FilterExpr::And(_)) { " AND " } else { " OR " };
+                write!(f, "(")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, "{}", sep)?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, ")")
+            }
+            FilterExpr::Not(inner) => write!(f, "NOT ({})", inner),
+        }
+    }
+}
+
+/// 输出列名，必要时使用双引号包裹
+struct Ident<'a>(&'a str);
+
+impl fmt::Display for Ident<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let plain = self.0.chars().next().is_some_and(|c| c.is_alphabetic() || c == '_')
+            && self.0.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '.')
+            && Keyword::from_word(self.0).is_none();
+        if plain {
+            write!(f, "{}", self.0)
+        } else {
+            write!(f, "\"{}\"", self.0.replace('"', "\"\""))
+        }
+    }
+}
+
+/// 输出常量值
+struct Literal<'a>(&'a CellValue);
+
+impl fmt::Display for Literal<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            CellValue::Null => write!(f, "NULL"),
+            CellValue::Bool(true) => write!(f, "TRUE"),
+            CellValue::Bool(false) => write!(f, "FALSE"),
+            CellValue::Number(n) => write!(f, "{}", n),
+            CellValue::String(s) => write!(f, "'{}'", s.replace('\'', "''")),
+            other => write!(f, "'{}'", other.to_string().replace('\'', "''")),
+        }
+    }
+}
+
+/// 已绑定列索引的过滤器
+///
+/// 由 [`FilterExpr::bind`] 生成，可以反复对按列顺序排列的行值求值。
+#[derive(Debug, Clone)]
+pub struct BoundFilter {
+    node: BoundNode,
+}
+
+impl BoundFilter {
+    /// 判断一行是否满足过滤条件
+    ///
+    /// 结果为“未知”（例如与 `NULL` 比较）时视为不满足。
+    pub fn matches(&self, values: &[CellValue]) -> bool {
+        self.node.eval(values) == Some(true)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum BoundNode {
+    Compare { index: usize, column_type: Option<ColumnType>, op: CompareOp, value: CellValue },
+    In { index: usize, column_type: Option<ColumnType>, values: Vec<CellValue>, negated: bool },
+    Like { index: usize, pattern: LikePattern, negated: bool },
+    IsNull { index: usize, negated: bool },
+    Between { index: usize, column_type: Option<ColumnType>, low: CellValue, high: CellValue, negated: bool },
+    And(Vec<BoundNode>),
+    Or(Vec<BoundNode>),
+    Not(Box<BoundNode>),
+}
+
+impl BoundNode {
+    /// 三值逻辑求值：`None` 表示未知
+    fn eval(&self, values: &[CellValue]) -> Option<bool> {
+        let cell = |index: &usize| values.get(*index).unwrap_or(&CellValue::Null);
+        let negate = |result: Option<bool>, negated: &bool| result.map(|r| r != *negated);
+
+        match self {
+            BoundNode::Compare { index, column_type, op, value } => {
+                compare_values(cell(index), value, *column_type).map(|o| op.test(o))
+            }
+            BoundNode::In { index, column_type, values: list, negated } => {
+                let value = cell(index);
+                if value.is_null() {
+                    return None;
+                }
+                let mut unknown = false;
+                for item in list {
+                    match compare_values(value, item, *column_type) {
+                        Some(Ordering::Equal) => return Some(!*negated),
+                        Some(_) => {}
+                        None => unknown = true,
+                    }
+                }
+                if unknown { None } else { Some(*negated) }
+            }
+            BoundNode::Like { index, pattern, negated } => {
+                let text = match cell(index) {
+                    CellValue::Null => return None,
+                    CellValue::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                negate(Some(pattern.matches(&text)), negated)
+            }
+            BoundNode::IsNull { index, negated } => Some(cell(index).is_null() != *negated),
+            BoundNode::Between { index, column_type, low, high, negated } => {
+                let value = cell(index);
+                let above = compare_values(value, low, *column_type).map(|o| o != Ordering::Less);
+                let below = compare_values(value, high, *column_type).map(|o| o != Ordering::Greater);
+                negate(and3(above, below), negated)
+            }
+            BoundNode::And(items) => {
+                let mut result = Some(true);
+                for item in items {
+                    result = and3(result, item.eval(values));
+                    if result == Some(false) {
+                        break;
+                    }
+                }
+                result
+            }
+            BoundNode::Or(items) => {
+                let mut result = Some(false);
+                for item in items {
+                    result = or3(result, item.eval(values));
+                    if result == Some(true) {
+                        break;
+                    }
+                }
+                result
+            }
+            BoundNode::Not(inner) => inner.eval(values).map(|r| !r),
+        }
+    }
+}
+
+pub(crate) fn and3(a: Option<bool>, b: Option<bool>) -> Option<bool> {
+    match (a, b) {
+        (Some(false), _) | (_, Some(false)) => Some(false),
+        (Some(true), Some(true)) => Some(true),
+        _ => None,
+    }
+}
+
+pub(crate) fn or3(a: Option<bool>, b: Option<bool>) -> Option<bool> {
+    match (a, b) {
+        (Some(true), _) | (_, Some(true)) => Some(true),
+        (Some(false), Some(false)) => Some(false),
+        _ => None,
+    }
+}
+
+/// 比较单元格值与常量
+///
+/// 任一方为 `NULL` 时返回 `None`；数值与数值字符串按定点数比较，
+/// 布尔值与 `'1'`/`'0'` 等字符串按布尔值比较，带时区的日期时间按时间点比较。
+pub(crate) fn compare_values(a: &CellValue, b: &CellValue, column_type: Option<ColumnType>) -> Option<Ordering> {
+    match (a, b) {
+        (CellValue::Null, _) | (_, CellValue::Null) => None,
+        (CellValue::String(x), CellValue::String(y)) if column_type == Some(ColumnType::DateTimeTz) => {
+            match (parse_datetime_tz(x), parse_datetime_tz(y)) {
+                (Some(x), Some(y)) => Some(x.cmp(&y)),
+                _ => Some(x.cmp(y)),
+            }
+        }
+        (CellValue::Number(x), CellValue::Number(y)) => Some(compare_numbers(x, y)),
+        (CellValue::Number(_), CellValue::String(_)) | (CellValue::String(_), CellValue::Number(_)) => {
+            Some(to_decimal(a)?.cmp(&to_decimal(b)?))
+        }
+        (CellValue::String(x), CellValue::String(y))
+            if matches!(column_type, Some(t) if t.is_numeric()) =>
+        {
+            match (to_decimal(a), to_decimal(b)) {
+                (Some(x), Some(y)) => Some(x.cmp(&y)),
+                _ => Some(x.cmp(y)),
+            }
+        }
+        (CellValue::Bool(_), _) | (_, CellValue::Bool(_)) => {
+            let x = ColumnType::Bool.coerce(a.clone()).ok()?;
+            let y = ColumnType::Bool.coerce(b.clone()).ok()?;
+            Some(x.as_bool()?.cmp(&y.as_bool()?))
+        }
+        _ => Some(compare_cell_values(a, b)),
+    }
+}
+
+/// 预编译的 LIKE 模式
+#[derive(Debug, Clone)]
+struct LikePattern {
+    tokens: Vec<LikeToken>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum LikeToken {
+    Any,
+    One,
+    Char(char),
+}
+
+impl LikePattern {
+    fn new(pattern: &str) -> Self {
+        let mut tokens = Vec::new();
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            tokens.push(match c {
+                '%' => LikeToken::Any,
+                '_' => LikeToken::One,
+                '\\' => LikeToken::Char(chars.next().unwrap_or('\\')),
+                c => LikeToken::Char(c),
+            });
+        }
+        Self { tokens }
+    }
+
+    /// 带回溯的通配符匹配，时间复杂度为 O(n·m)
+    fn matches(&self, text: &str) -> bool {
+        let text: Vec<char> = text.chars().collect();
+        let (mut t, mut p) = (0, 0);
+        let mut backtrack: Option<(usize, usize)> = None;
+
+        while t < text.len() {
+            match self.tokens.get(p) {
+                Some(LikeToken::Any) => {
+                    backtrack = Some((p, t));
+                    p += 1;
+                }
+                Some(LikeToken::One) => {
+                    t += 1;
+                    p += 1;
+                }
+                Some(LikeToken::Char(c)) if *c == text[t] => {
+                    t += 1;
+                    p += 1;
+                }
+                _ => match backtrack {
+                    Some((bp, bt)) => {
+                        p = bp + 1;
+                        t = bt + 1;
+                        backtrack = Some((bp, bt + 1));
+                    }
+                    None => return false,
+                },
+            }
+        }
+        self.tokens[p..].iter().all(|token| *token == LikeToken::Any)
+    }
+}
+
+/// 过滤表达式解析错误
+#[derive(Error, Debug, PartialEq)]
+pub enum FilterError {
+    #[error("Syntax error at position {position}: {message}")]
+    Syntax { position: usize, message: String },
+    #[error("Unexpected end of filter expression")]
+    UnexpectedEnd,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Keyword {
+    And,
+    Or,
+    Not,
+    In,
+    Like,
+    Is,
+    Null,
+    Between,
+    True,
+    False,
+}
+
+impl Keyword {
+    fn from_word(word: &str) -> Option<Self> {
+        Some(match word.to_ascii_uppercase().as_str() {
+            "AND" => Keyword::And,
+            "OR" => Keyword::Or,
+            "NOT" => Keyword::Not,
+            "IN" => Keyword::In,
+            "LIKE" => Keyword::Like,
+            "IS" => Keyword::Is,
+            "NULL" => Keyword::Null,
+            "BETWEEN" => Keyword::Between,
+            "TRUE" => Keyword::True,
+            "FALSE" => Keyword::False,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Keyword(Keyword),
+    Str(String),
+    Number(serde_json::Number),
+    Op(CompareOp),
+    LParen,
+    RParen,
+    Comma,
+}
+
+/// 括号和 `NOT` 的最大嵌套层数，避免客户端传入的深层嵌套耗尽栈
+const MAX_DEPTH: usize = 128;
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn new(text: &str) -> Result<Self, FilterError> {
+        Ok(Self { tokens: tokenize(text)?, pos: 0, depth: 0 })
+    }
+
+    fn parse(mut self) -> Result<FilterExpr, FilterError> {
+        let expr = self.parse_or()?;
+        match self.tokens.get(self.pos) {
+            None => Ok(expr),
+            Some((token, position)) => Err(FilterError::Syntax {
+                position: *position,
+                message: format!("unexpected token {:?}", token),
+            }),
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(t, _)| t)
+    }
+
+    fn next(&mut self) -> Result<(Token, usize), FilterError> {
+        let token = self.tokens.get(self.pos).cloned().ok_or(FilterError::UnexpectedEnd)?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn eat_keyword(&mut self, keyword: Keyword) -> bool {
+        if self.peek() == Some(&Token::Keyword(keyword)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, expected: Token, what: &str) -> Result<(), FilterError> {
+        let (token, position) = self.next()?;
+        if token == expected {
+            Ok(())
+        } else {
+            Err(FilterError::Syntax { position, message: format!("expected {}, found {:?}", what, token) })
+        }
+    }
+
+    /// 进入一层嵌套解析，超过 [`MAX_DEPTH`] 层时报错
+    fn nested(&mut self, parse: fn(&mut Self) -> Result<FilterExpr, FilterError>) -> Result<FilterExpr, FilterError> {
+        if self.depth >= MAX_DEPTH {
+            let position = self.tokens.get(self.pos.saturating_sub(1)).map_or(0, |(_, position)| *position);
+            return Err(FilterError::Syntax { position, message: format!("nesting deeper than {} levels", MAX_DEPTH) });
+        }
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn parse_or(&mut self) -> Result<FilterExpr, FilterError> {
+        let mut items = vec![self.parse_and()?];
+        while self.eat_keyword(Keyword::Or) {
+            items.push(self.parse_and()?);
+        }
+        Ok(if items.len() == 1 { items.remove(0) } else { FilterExpr::Or(items) })
+    }
+
+    fn parse_and(&mut self) -> Result<FilterExpr, FilterError> {
+        let mut items = vec![self.parse_unary()?];
+        while self.eat_keyword(Keyword::And) {
+            items.push(self.parse_unary()?);
+        }
+        Ok(if items.len() == 1 { items.remove(0) } else { FilterExpr::And(items) })
+    }
+
+    fn parse_unary(&mut self) -> Result<FilterExpr, FilterError> {
+        if self.eat_keyword(Keyword::Not) {
+            return Ok(!self.nested(Self::parse_unary)?);
+        }
+        if self.peek() == Some(&Token::LParen) {
+            self.pos += 1;
+            let expr = self.nested(Self::parse_or)?;
+            self.expect(Token::RParen, "')'")?;
+            return Ok(expr);
+        }
+        self.parse_predicate()
+    }
+
+    fn parse_predicate(&mut self) -> Result<FilterExpr, FilterError> {
+        let column = match self.next()? {
+            (Token::Ident(name), _) => name,
+            (token, position) => {
+                return Err(FilterError::Syntax { position, message: format!("expected column name, found {:?}", token) })
+            }
+        };
+
+        let (token, position) = self.next()?;
+        match token {
+            Token::Op(op) => Ok(FilterExpr::compare(column, op, self.parse_literal()?)),
+            Token::Keyword(Keyword::Is) => {
+                let negated = self.eat_keyword(Keyword::Not);
+                self.expect(Token::Keyword(Keyword::Null), "NULL")?;
+                Ok(FilterExpr::IsNull { column, negated })
+            }
+            Token::Keyword(Keyword::Not) => {
+                let (token, position) = self.next()?;
+                self.parse_negatable(column, token, position, true)
+            }
+            token => self.parse_negatable(column, token, position, false),
+        }
+    }
+
+    fn parse_negatable(&mut self, column: String, token: Token, position: usize, negated: bool) -> Result<FilterExpr, FilterError> {
+        match token {
+            Token::Keyword(Keyword::In) => {
+                self.expect(Token::LParen, "'('")?;
+                let mut values = vec![self.parse_literal()?];
+                while self.peek() == Some(&Token::Comma) {
+                    self.pos += 1;
+                    values.push(self.parse_literal()?);
+                }
+                self.expect(Token::RParen, "')'")?;
+                Ok(FilterExpr::In { column, values, negated })
+            }
+            Token::Keyword(Keyword::Like) => match self.next()? {
+                (Token::Str(pattern), _) => Ok(FilterExpr::Like { column, pattern, negated }),
+                (token, position) => Err(FilterError::Syntax {
+                    position,
+                    message: format!("expected pattern string, found {:?}", token),
+                }),
+            },
+            Token::Keyword(Keyword::Between) => {
+                let low = self.parse_literal()?;
+                self.expect(Token::Keyword(Keyword::And), "AND")?;
+                let high = self.parse_literal()?;
+                Ok(FilterExpr::Between { column, low, high, negated })
+            }
+            token => Err(FilterError::Syntax {
+                position,
+                message: format!("expected operator, found {:?}", token),
+            }),
+        }
+    }
+
+    fn parse_literal(&mut self) -> Result<CellValue, FilterError> {
+        match self.next()? {
+            (Token::Str(s), _) => Ok(CellValue::String(s)),
+            (Token::Number(n), _) => Ok(CellValue::Number(n)),
+            (Token::Keyword(Keyword::True), _) => Ok(CellValue::Bool(true)),
+            (Token::Keyword(Keyword::False), _) => Ok(CellValue::Bool(false)),
+            (Token::Keyword(Keyword::Null), _) => Ok(CellValue::Null),
+            (token, position) => Err(FilterError::Syntax {
+                position,
+                message: format!("expected literal value, found {:?}", token),
+            }),
+        }
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<(Token, usize)>, FilterError> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let (position, c) = chars[i];
+        let syntax = |message: &str| FilterError::Syntax { position, message: message.to_string() };
+
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '(' | ')' | ',' => {
+                tokens.push((match c { '(' => Token::LParen, ')' => Token::RParen, _ => Token::Comma }, position));
+                i += 1;
+            }
+            '=' => {
+                tokens.push((Token::Op(CompareOp::Eq), position));
+                i += 1;
+            }
+            '!' | '<' | '>' => {
+                let next = chars.get(i + 1).map(|(_, c)| *c);
+                let (op, len) = match (c, next) {
+                    ('!', Some('=')) | ('<', Some('>')) => (CompareOp::Ne, 2),
+                    ('<', Some('=')) => (CompareOp::Le, 2),
+                    ('>', Some('=')) => (CompareOp::Ge, 2),
+                    ('<', _) => (CompareOp::Lt, 1),
+                    ('>', _) => (CompareOp::Gt, 1),
+                    _ => return Err(syntax("expected '!='")),
+                };
+                tokens.push((Token::Op(op), position));
+                i += len;
+            }
+            '\'' | '"' => {
+                // 单引号为字符串常量，双引号为列名，连续两个引号表示转义
+                let mut value = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err(syntax("unterminated quoted text")),
+                        Some((_, q)) if *q == c => {
+                            if chars.get(i + 1).map(|(_, n)| *n) == Some(c) {
+                                value.push(c);
+                                i += 2;
+                            } else {
+                                i += 1;
+                                break;
+                            }
+                        }
+                        Some((_, ch)) => {
+                            value.push(*ch);
+                            i += 1;
+                        }
+                    }
+                }
+                tokens.push((if c == '\'' { Token::Str(value) } else { Token::Ident(value) }, position));
+            }
+            c if c.is_ascii_digit() || c == '-' || c == '+' || c == '.' => {
+                let start = i;
+                i += 1;
+                while let Some((_, ch)) = chars.get(i) {
+                    let exponent_sign = (*ch == '-' || *ch == '+') && matches!(chars[i - 1].1, 'e' | 'E');
+                    if ch.is_ascii_digit() || *ch == '.' || *ch == 'e' || *ch == 'E' || exponent_sign {
+                        i += 1;
+                    } else {
+                        break;
+                    }
+                }
+                let literal: String = chars[start..i].iter().map(|(_, ch)| ch).collect();
+                let number = serde_json::Number::from_str(literal.trim_start_matches('+'))
+                    .map_err(|_| syntax(&format!("invalid number '{}'", literal)))?;
+                tokens.push((Token::Number(number), position));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while let Some((_, ch)) = chars.get(i) {
+                    if ch.is_alphanumeric() || *ch == '_' || *ch == '.' {
+                        i += 1;
+                    } else {
+                        break;
+                    }
+                }
+                let word: String = chars[start..i].iter().map(|(_, ch)| ch).collect();
+                tokens.push((Keyword::from_word(&word).map_or(Token::Ident(word), Token::Keyword), position));
+            }
+            _ => return Err(syntax(&format!("unexpected character '{}'", c))),
+        }
+    }
+    Ok(tokens)
+}
+
+impl RowDataSet {
+    /// 按列名绑定过滤表达式
+    ///
+    /// 表达式中的常量会按 `ColumnInfo` 中的列类型转换后再参与比较。
+    pub fn bind_filter(&self, expr: &FilterExpr) -> Result<BoundFilter, DataSetError> {
+        expr.bind(|name| self.schema.get(name).map(|info| (info.index, Some(info.column_type))))
+    }
+
+    /// 按过滤表达式筛选行，返回满足条件的行引用
+    ///
+    /// # 参数
+    ///
+    /// * `expr` - 过滤表达式
+    ///
+    /// # 返回值
+    ///
+    /// 返回 `Result<Vec<&RowData>, DataSetError>`：
+    /// - `Ok(rows)` - 满足条件的行引用，顺序与数据集中的行顺序一致
+    /// - `Err(DataSetError::ColumnNotFound)` - 表达式引用了不存在的列
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// # use cmx_core::model::data::dataset::{rds::RowDataSet, ColumnType};
+    /// # use cmx_core::model::data::dataset::filter::FilterExpr;
+    /// # use cmx_core::model::data::cell::CellValue;
+    /// # let mut dataset = RowDataSet::new("dept".to_string());
+    /// # dataset.add_column("BM".to_string(), ColumnType::String).unwrap();
+    /// # dataset.add_row(vec![CellValue::String("0101".to_string())]).unwrap();
+    /// # dataset.add_row(vec![CellValue::String("0201".to_string())]).unwrap();
+    /// let expr: FilterExpr = "BM LIKE '01%'".parse().unwrap();
+    /// let rows = dataset.filter_rows(&expr).unwrap();
+    /// assert_eq!(rows.len(), 1);
+    /// ```
+    pub fn filter_rows(&self, expr: &FilterExpr) -> Result<Vec<&RowData>, DataSetError> {
+        let filter = self.bind_filter(expr)?;
+        Ok(self.rows.iter().filter(|row| filter.matches(row.values())).collect())
+    }
+
+    /// 按过滤表达式筛选行，返回满足条件的行索引
+    pub fn filter_indices(&self, expr: &FilterExpr) -> Result<Vec<usize>, DataSetError> {
+        let filter = self.bind_filter(expr)?;
+        Ok(self
+            .rows
+            .iter()
+            .enumerate()
+            .filter(|(_, row)| filter.matches(row.values()))
+            .map(|(index, _)| index)
+            .collect())
+    }
+
+    /// 按过滤表达式筛选行，返回包含满足条件的行的新数据集
+    ///
+    /// 新数据集保留原数据集的 ID 和列定义，满足条件的行连同其子数据集一起被复制。
+    pub fn filter(&self, expr: &FilterExpr) -> Result<RowDataSet, DataSetError> {
+        let filter = self.bind_filter(expr)?;
+        let mut result = RowDataSet::new(self.dataset_id.clone());
+        result.schema = self.schema.clone();
+        result.rows = self.rows.iter().filter(|row| filter.matches(row.values())).cloned().collect();
+        Ok(result)
+    }
+}
+
+impl DataSet {
+    /// 按 `TableSchema` 中的列名绑定过滤表达式
+    pub fn bind_filter(schema: &TableSchema, expr: &FilterExpr) -> Result<BoundFilter, DataSetError> {
+        expr.bind(|name| schema.get_column_index(name).map(|index| (index, None)))
+    }
+
+    /// 按过滤表达式筛选行，返回满足条件的行引用
+    pub fn filter_rows<'a>(&'a self, schema: &TableSchema, expr: &FilterExpr) -> Result<Vec<&'a RowSet>, DataSetError> {
+        let filter = Self::bind_filter(schema, expr)?;
+        let rows = self.rows.as_ref().ok_or(DataSetError::RowsNotInitialized)?;
+        Ok(rows.iter().filter(|row| filter.matches(row.values())).collect())
+    }
+
+    /// 按过滤表达式筛选行，返回包含满足条件的行的新数据集
+    pub fn filter(&self, schema: &TableSchema, expr: &FilterExpr) -> Result<DataSet, DataSetError> {
+        let rows = self.filter_rows(schema, expr)?;
+        Ok(DataSet { rows: Some(rows.into_iter().cloned().collect()) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::model::data::dataset::col::ColumnDef;
+    use crate::model::data::dataset::TableSchemaBuilder;
+    use crate::model::meta::fields::SYS_OBJCOLS;
+
+    fn create_dept_dataset() -> RowDataSet {
+        let mut dataset = RowDataSet::new("dept".to_string());
+        dataset.add_column("DCT_BMCOLID".to_string(), ColumnType::String).unwrap();
+        dataset.add_column("F_ENABLE".to_string(), ColumnType::String).unwrap();
+        dataset.add_column("F_JE".to_string(), ColumnType::Decimal).unwrap();
+        dataset.add_column("F_DATE".to_string(), ColumnType::Date).unwrap();
+        for (bm, enable, je, date) in [
+            ("01", json!("1"), json!(10.5), json!("2024-01-01")),
+            ("0101", json!("1"), json!(20), json!("2024-02-01")),
+            ("0102", json!("0"), CellValue::Null, json!("2024-03-01")),
+            ("02", json!("1"), json!("30.25"), CellValue::Null),
+        ] {
+            dataset.add_row(vec![json!(bm), enable, je, date]).unwrap();
+        }
+        dataset
+    }
+
+    fn codes(rows: &[&RowData]) -> Vec<String> {
+        rows.iter().map(|r| r.values()[0].as_str().unwrap().to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_and_filter() {
+        let dataset = create_dept_dataset();
+        let expr = FilterExpr::parse("F_ENABLE = '1' AND DCT_BMCOLID LIKE '01%'").unwrap();
+        assert_eq!(codes(&dataset.filter_rows(&expr).unwrap()), vec!["01", "0101"]);
+
+        let expr = FilterExpr::parse("not (DCT_BMCOLID in ('01', '02')) or F_JE >= 30").unwrap();
+        assert_eq!(codes(&dataset.filter_rows(&expr).unwrap()), vec!["0101", "0102", "02"]);
+
+        let expr = FilterExpr::parse("F_JE IS NULL OR F_DATE BETWEEN '2024/02/01' AND '2024-12-31'").unwrap();
+        assert_eq!(codes(&dataset.filter_rows(&expr).unwrap()), vec!["0101", "0102"]);
+
+        let expr = FilterExpr::parse("DCT_BMCOLID NOT LIKE '0_0%' AND F_DATE IS NOT NULL").unwrap();
+        assert_eq!(dataset.filter_indices(&expr).unwrap(), vec![0]);
+    }
+
+    #[test]
+    fn test_null_semantics() {
+        let dataset = create_dept_dataset();
+        // 与 NULL 比较的结果为未知，NOT 之后仍为未知
+        let expr = FilterExpr::parse("NOT F_JE > 15").unwrap();
+        assert_eq!(codes(&dataset.filter_rows(&expr).unwrap()), vec!["01"]);
+        let expr = FilterExpr::parse("F_JE NOT IN (10.5, 20)").unwrap();
+        assert_eq!(codes(&dataset.filter_rows(&expr).unwrap()), vec!["02"]);
+    }
+
+    #[test]
+    fn test_filter_to_dataset() {
+        let dataset = create_dept_dataset();
+        let expr = FilterExpr::like("DCT_BMCOLID", "01%").and(!FilterExpr::is_null("F_JE"));
+        let filtered = dataset.filter(&expr).unwrap();
+        assert_eq!(filtered.row_count(), 2);
+        assert_eq!(filtered.column_count(), 4);
+        assert_eq!(filtered.get_cell(1, "DCT_BMCOLID").unwrap(), "0101");
+
+        let missing = FilterExpr::compare("NOPE", CompareOp::Eq, json!(1));
+        assert!(matches!(dataset.filter(&missing), Err(DataSetError::ColumnNotFound)));
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        let text = "(A = 'it''s' OR \"select\" <> -1.5) AND NOT (B IN (1, 2)) AND C IS NOT NULL AND D NOT BETWEEN 1 AND 2";
+        let expr = FilterExpr::parse(text).unwrap();
+        let reparsed = FilterExpr::parse(&expr.to_string()).unwrap();
+        assert_eq!(expr, reparsed);
+
+        let json = serde_json::to_string(&expr).unwrap();
+        assert_eq!(serde_json::from_str::<FilterExpr>(&json).unwrap(), expr);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(FilterExpr::parse("A ="), Err(FilterError::UnexpectedEnd));
+        assert!(matches!(FilterExpr::parse("A = 'x"), Err(FilterError::Syntax { position: 4, .. })));
+        assert!(matches!(FilterExpr::parse("A = 1 B"), Err(FilterError::Syntax { position: 6, .. })));
+        assert!(matches!(FilterExpr::parse("A LIKE 1"), Err(FilterError::Syntax { .. })));
+
+        // 嵌套层数有上限，深层嵌套不会耗尽栈
+        let nested = |open: &str, close: &str, depth: usize| format!("{}A = 1{}", open.repeat(depth), close.repeat(depth));
+        assert!(FilterExpr::parse(&nested("(", ")", MAX_DEPTH)).is_ok());
+        assert!(matches!(FilterExpr::parse(&nested("(", ")", MAX_DEPTH + 1)), Err(FilterError::Syntax { .. })));
+        assert!(matches!(FilterExpr::parse(&nested("(", ")", 10_000)), Err(FilterError::Syntax { .. })));
+        assert!(matches!(FilterExpr::parse(&nested("NOT ", "", 10_000)), Err(FilterError::Syntax { .. })));
+    }
+
+    #[test]
+    fn test_like_pattern() {
+        let pattern = LikePattern::new("a%b_c");
+        assert!(pattern.matches("abxc"));
+        assert!(pattern.matches("a123b4c"));
+        assert!(!pattern.matches("a123bc"));
+        assert!(LikePattern::new("100\\%").matches("100%"));
+        assert!(!LikePattern::new("100\\%").matches("1000"));
+        assert!(LikePattern::new("%").matches(""));
+    }
+
+    #[test]
+    fn test_filter_dataset_with_table_schema() {
+        let column = |id: &str| {
+            let mut col = ColumnDef::default();
+            col.set(SYS_OBJCOLS::COL_ID, json!(id));
+            col
+        };
+        let schema = TableSchemaBuilder::new()
+            .with_obj_id("DEPT".to_string())
+            .with_columns(vec![column("BM"), column("F_ENABLE")])
+            .build();
+
+        let mut dataset = DataSet::new();
+        dataset.add_row(&schema, vec![json!("01"), json!("1")]).unwrap();
+        dataset.add_row(&schema, vec![json!("02"), json!(0)]).unwrap();
+
+        let expr = FilterExpr::parse("F_ENABLE = 1").unwrap();
+        let rows = dataset.filter_rows(&schema, &expr).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].get_value(0).unwrap(), "01");
+        assert_eq!(dataset.filter(&schema, &expr).unwrap().row_count(), 1);
+    }
+}
@@ -0,0 +1,1484 @@
+//! # 公式模块
+//!
+//! 为计算列（`SYS_OBJCOLS::COL_ISCALC`）提供公式（`COL_COLGS`）的解析、类型检查和求值。
+//! 给 `RowDataSet` 设置公式后，`add_row`、`insert_row`、`set_cell` 以及增删子数据集时
+//! 会自动重新计算该行的所有计算列。
+//!
+//! ## 语法
+//!
+//! - 常量：数字、`'字符串'`（两个单引号表示转义）、`TRUE`、`FALSE`、`NULL`
+//! - 列引用：同一行的列名，可以用双引号包裹
+//! - 算术：`+`、`-`、`*`、`/`、`%`，字符串连接：`||`
+//! - 比较：`=`、`!=`、`<>`、`<`、`<=`、`>`、`>=`，逻辑：`AND`、`OR`、`NOT`
+//! - 子数据集聚合：`SUM(子数据集.列)`、`AVG`、`MIN`、`MAX`、`COUNT(子数据集.列)`，
+//!   `COUNT(子数据集)` 统计子数据集的行数
+//!
+//! ## 函数
+//!
+//! | 分类 | 函数 |
+//! |------|------|
+//! | 数值 | `ABS(x)`、`ROUND(x[, 小数位])`（四舍五入）、`CEIL(x)`、`FLOOR(x)`、`MOD(x, y)`、`MIN(x, ...)`、`MAX(x, ...)` |
+//! | 字符串 | `LEN(s)`、`UPPER(s)`、`LOWER(s)`、`TRIM(s)`、`LEFT(s, n)`、`RIGHT(s, n)`、`SUBSTR(s, 起始位置[, 长度])`、`CONCAT(s, ...)`、`REPLACE(s, 原串, 新串)` |
+//! | 日期 | `YEAR(d)`、`MONTH(d)`、`DAY(d)`、`DATE(年, 月, 日)`、`ADD_DAYS(d, n)`、`ADD_MONTHS(d, n)`、`DAYS_BETWEEN(起始日期, 结束日期)` |
+//! | 条件 | `IF(条件, 值1, 值2)`、`COALESCE(x, ...)`、`ISNULL(x)` |
+//!
+//! 函数名和关键字不区分大小写，`SUBSTR` 的起始位置从 1 开始。
+//!
+//! ## NULL 与错误
+//!
+//! 与 SQL 一致，运算数为 `NULL` 时算术、比较和函数的结果为 `NULL`，`||` 和 `CONCAT` 把
+//! `NULL` 视为空串，`IF` 的条件为 `NULL` 时取第二个值。除数为零、日期越界等无法计算的情况
+//! 结果也为 `NULL`。计算结果按计算列的类型转换，转换失败时写入行的操作返回
+//! `DataSetError::TypeMismatch`。
+//!
+//! ## 依赖顺序
+//!
+//! 计算列可以引用其他计算列，[`FormulaSet`] 按依赖关系排序，循环引用返回 [`FormulaError::Cycle`]。
+//! 计算列不能通过 `set_cell` 直接赋值；通过 `get_child_mut` 修改子数据集后需要调用
+//! [`RowDataSet::recalculate_row`]。
+//!
+//! ## 示例
+//!
+//! ```rust
+//! use cmx_core::model::data::dataset::{rds::RowDataSet, ColumnType};
+//! use cmx_core::model::data::dataset::formula::{Formula, FormulaSet};
+//! use serde_json::json;
+//!
+//! let mut orders = RowDataSet::new("orders".to_string());
+//! orders.add_column("SL".to_string(), ColumnType::Decimal).unwrap();
+//! orders.add_column("DJ".to_string(), ColumnType::Decimal).unwrap();
+//! orders.add_column("JE".to_string(), ColumnType::Decimal).unwrap();
+//!
+//! let formulas = vec![("JE".to_string(), "ROUND(SL * DJ, 2)".parse::<Formula>().unwrap())];
+//! let formulas = FormulaSet::new(formulas, |name| orders.get_column_info(name).map(|c| c.column_type)).unwrap();
+//! orders.set_formulas(&formulas).unwrap();
+//!
+//! orders.add_row(vec![json!(3), json!(1.255), json!(null)]).unwrap();
+//! assert_eq!(orders.get_cell(0, "JE").unwrap(), &json!(3.77));
+//! orders.set_cell(0, "SL", json!(2)).unwrap();
+//! assert_eq!(orders.get_cell(0, "JE").unwrap(), &json!(2.51));
+//! ```
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use chrono::{Datelike, Duration, Months, NaiveDate, NaiveDateTime, NaiveTime};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::{Decimal, RoundingStrategy};
+use thiserror::Error;
+
+use super::aggregate::AggFunc;
+use super::coerce::{decimal_to_cell, format_date, format_datetime, parse_date, parse_datetime, parse_datetime_tz, to_decimal};
+use super::col::flag_value;
+use super::filter::{and3, compare_values, or3, CompareOp};
+use super::rds::RowDataSet;
+use super::{ColumnType, DataSetError, TableSchema};
+use crate::model::data::cell::CellValue;
+use crate::model::meta::fields::SYS_OBJCOLS;
+
+/// 公式值的类型，用于类型检查
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueKind {
+    /// 类型未知：`NULL` 常量，或子数据集上的 `MIN`/`MAX`
+    Any,
+    Bool,
+    Number,
+    Text,
+    Date,
+    DateTime,
+}
+
+impl ValueKind {
+    /// 列类型对应的公式值类型
+    pub fn of(column_type: ColumnType) -> Self {
+        match column_type {
+            ColumnType::Bool => ValueKind::Bool,
+            t if t.is_numeric() => ValueKind::Number,
+            ColumnType::Date => ValueKind::Date,
+            ColumnType::DateTime | ColumnType::DateTimeTz => ValueKind::DateTime,
+            _ => ValueKind::Text,
+        }
+    }
+
+    /// 这个类型的结果能否写入指定类型的列
+    pub fn fits(self, column_type: ColumnType) -> bool {
+        match self {
+            ValueKind::Any => true,
+            ValueKind::Bool => matches!(column_type, ColumnType::Bool | ColumnType::String),
+            ValueKind::Number => column_type.is_numeric() || column_type == ColumnType::String,
+            ValueKind::Text => !column_type.is_numeric() && column_type != ColumnType::Bool,
+            ValueKind::Date => {
+                matches!(column_type, ColumnType::Date | ColumnType::DateTime | ColumnType::DateTimeTz | ColumnType::String)
+            }
+            ValueKind::DateTime => {
+                matches!(column_type, ColumnType::DateTime | ColumnType::DateTimeTz | ColumnType::String)
+            }
+        }
+    }
+
+    fn is_temporal(self) -> bool {
+        matches!(self, ValueKind::Date | ValueKind::DateTime)
+    }
+
+    /// 两个分支结果的公共类型
+    fn unify(self, other: ValueKind) -> Option<ValueKind> {
+        match (self, other) {
+            (ValueKind::Any, kind) | (kind, ValueKind::Any) => Some(kind),
+            (a, b) if a == b => Some(a),
+            (a, b) if a.is_temporal() && b.is_temporal() => Some(ValueKind::DateTime),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ValueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ValueKind::Any => "any",
+            ValueKind::Bool => "bool",
+            ValueKind::Number => "number",
+            ValueKind::Text => "text",
+            ValueKind::Date => "date",
+            ValueKind::DateTime => "datetime",
+        };
+        f.write_str(name)
+    }
+}
+
+/// 公式错误
+#[derive(Error, Debug)]
+pub enum FormulaError {
+    #[error("Syntax error at position {position}: {message}")]
+    Syntax { position: usize, message: String },
+    #[error("Unexpected end of formula")]
+    UnexpectedEnd,
+    #[error("Unknown function '{0}'")]
+    UnknownFunction(String),
+    #[error("Function {function} expects {expected} arguments, found {found}")]
+    ArgumentCount { function: String, expected: String, found: usize },
+    #[error("Unknown column '{0}'")]
+    UnknownColumn(String),
+    #[error("Type mismatch in {context}: expected {expected}, found {found}")]
+    TypeMismatch { context: String, expected: String, found: ValueKind },
+    #[error("Formula yields {kind}, which does not fit column type {column_type:?}")]
+    ResultType { kind: ValueKind, column_type: ColumnType },
+    #[error("Circular reference between calculated columns: {}", .0.join(" -> "))]
+    Cycle(Vec<String>),
+    #[error("Formula of column '{column}': {source}")]
+    Column {
+        column: String,
+        #[source]
+        source: Box<FormulaError>,
+    },
+    #[error("DataSet error: {0}")]
+    DataSet(#[from] DataSetError),
+}
+
+impl FormulaError {
+    fn in_column(self, column: &str) -> Self {
+        FormulaError::Column { column: column.to_string(), source: Box::new(self) }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum UnaryOp {
+    Neg,
+    Not,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Concat,
+    Compare(CompareOp),
+    And,
+    Or,
+}
+
+/// 内置函数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Func {
+    Abs,
+    Round,
+    Ceil,
+    Floor,
+    Mod,
+    Min,
+    Max,
+    Len,
+    Upper,
+    Lower,
+    Trim,
+    Left,
+    Right,
+    Substr,
+    Concat,
+    Replace,
+    Year,
+    Month,
+    Day,
+    Date,
+    AddDays,
+    AddMonths,
+    DaysBetween,
+    If,
+    Coalesce,
+    IsNull,
+}
+
+/// 函数名与内置函数的对应关系
+const FUNCTIONS: &[(&str, Func)] = &[
+    ("ABS", Func::Abs),
+    ("ROUND", Func::Round),
+    ("CEIL", Func::Ceil),
+    ("FLOOR", Func::Floor),
+    ("MOD", Func::Mod),
+    ("MIN", Func::Min),
+    ("MAX", Func::Max),
+    ("LEN", Func::Len),
+    ("UPPER", Func::Upper),
+    ("LOWER", Func::Lower),
+    ("TRIM", Func::Trim),
+    ("LEFT", Func::Left),
+    ("RIGHT", Func::Right),
+    ("SUBSTR", Func::Substr),
+    ("CONCAT", Func::Concat),
+    ("REPLACE", Func::Replace),
+    ("YEAR", Func::Year),
+    ("MONTH", Func::Month),
+    ("DAY", Func::Day),
+    ("DATE", Func::Date),
+    ("ADD_DAYS", Func::AddDays),
+    ("ADD_MONTHS", Func::AddMonths),
+    ("DAYS_BETWEEN", Func::DaysBetween),
+    ("IF", Func::If),
+    ("COALESCE", Func::Coalesce),
+    ("ISNULL", Func::IsNull),
+];
+
+impl Func {
+    fn from_name(name: &str) -> Option<Self> {
+        FUNCTIONS.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|(_, func)| *func)
+    }
+
+    fn name(&self) -> &'static str {
+        FUNCTIONS.iter().find(|(_, func)| func == self).map_or("", |(name, _)| name)
+    }
+
+    /// 参数个数范围，上限为 `None` 表示不限
+    fn arity(&self) -> (usize, Option<usize>) {
+        match self {
+            Func::Abs | Func::Ceil | Func::Floor | Func::Len | Func::Upper | Func::Lower | Func::Trim => (1, Some(1)),
+            Func::Year | Func::Month | Func::Day | Func::IsNull => (1, Some(1)),
+            Func::Round => (1, Some(2)),
+            Func::Mod | Func::Left | Func::Right | Func::AddDays | Func::AddMonths | Func::DaysBetween => (2, Some(2)),
+            Func::Substr => (2, Some(3)),
+            Func::Replace | Func::Date | Func::If => (3, Some(3)),
+            Func::Min | Func::Max | Func::Concat | Func::Coalesce => (1, None),
+        }
+    }
+}
+
+/// 公式语法树，`C` 为列引用：解析后是列名，绑定到数据集后是列索引
+#[derive(Debug, Clone, PartialEq)]
+enum Expr<C> {
+    Literal(CellValue),
+    Column(C),
+    Unary(UnaryOp, Box<Expr<C>>),
+    Binary(BinaryOp, Box<Expr<C>>, Box<Expr<C>>),
+    Call(Func, Vec<Expr<C>>),
+    /// 子数据集聚合，`column` 为 `None` 表示统计行数
+    Aggregate { func: AggFunc, child: String, column: Option<String> },
+}
+
+impl<C> Expr<C> {
+    fn map_columns<D, E>(&self, f: &impl Fn(&C) -> Result<D, E>) -> Result<Expr<D>, E> {
+        Ok(match self {
+            Expr::Literal(value) => Expr::Literal(value.clone()),
+            Expr::Column(column) => Expr::Column(f(column)?),
+            Expr::Unary(op, inner) => Expr::Unary(*op, Box::new(inner.map_columns(f)?)),
+            Expr::Binary(op, left, right) => {
+                Expr::Binary(*op, Box::new(left.map_columns(f)?), Box::new(right.map_columns(f)?))
+            }
+            Expr::Call(func, args) => Expr::Call(*func, args.iter().map(|a| a.map_columns(f)).collect::<Result<_, _>>()?),
+            Expr::Aggregate { func, child, column } => {
+                Expr::Aggregate { func: *func, child: child.clone(), column: column.clone() }
+            }
+        })
+    }
+
+    fn collect_columns<'a>(&'a self, columns: &mut Vec<&'a C>) {
+        match self {
+            Expr::Column(column) => columns.push(column),
+            Expr::Unary(_, inner) => inner.collect_columns(columns),
+            Expr::Binary(_, left, right) => {
+                left.collect_columns(columns);
+                right.collect_columns(columns);
+            }
+            Expr::Call(_, args) => args.iter().for_each(|a| a.collect_columns(columns)),
+            Expr::Literal(_) | Expr::Aggregate { .. } => {}
+        }
+    }
+
+    /// 类型检查，返回表达式的结果类型
+    fn check<F>(&self, kind_of: &F) -> Result<ValueKind, FormulaError>
+    where
+        F: Fn(&C) -> Result<ValueKind, FormulaError>,
+    {
+        let expect = |kind: ValueKind, expected: &[ValueKind], context: &str| {
+            if kind == ValueKind::Any || expected.contains(&kind) {
+                Ok(())
+            } else {
+                let names: Vec<String> = expected.iter().map(ValueKind::to_string).collect();
+                Err(FormulaError::TypeMismatch { context: context.to_string(), expected: names.join(" or "), found: kind })
+            }
+        };
+        const DATE_ARG: &[ValueKind] = &[ValueKind::Date, ValueKind::DateTime, ValueKind::Text];
+
+        Ok(match self {
+            Expr::Literal(value) => match value {
+                CellValue::Null => ValueKind::Any,
+                CellValue::Bool(_) => ValueKind::Bool,
+                CellValue::Number(_) => ValueKind::Number,
+                _ => ValueKind::Text,
+            },
+            Expr::Column(column) => kind_of(column)?,
+            Expr::Unary(UnaryOp::Neg, inner) => {
+                expect(inner.check(kind_of)?, &[ValueKind::Number], "unary '-'")?;
+                ValueKind::Number
+            }
+            Expr::Unary(UnaryOp::Not, inner) => {
+                expect(inner.check(kind_of)?, &[ValueKind::Bool], "NOT")?;
+                ValueKind::Bool
+            }
+            Expr::Binary(op, left, right) => {
+                let (left, right) = (left.check(kind_of)?, right.check(kind_of)?);
+                match op {
+                    BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div | BinaryOp::Rem => {
+                        expect(left, &[ValueKind::Number], "arithmetic")?;
+                        expect(right, &[ValueKind::Number], "arithmetic")?;
+                        ValueKind::Number
+                    }
+                    BinaryOp::Concat => ValueKind::Text,
+                    BinaryOp::And | BinaryOp::Or => {
+                        expect(left, &[ValueKind::Bool], "logical operator")?;
+                        expect(right, &[ValueKind::Bool], "logical operator")?;
+                        ValueKind::Bool
+                    }
+                    BinaryOp::Compare(_) => {
+                        // 日期以文本形式存储，允许与字符串常量比较
+                        let comparable = left.unify(right).is_some()
+                            || (left.is_temporal() && right == ValueKind::Text)
+                            || (right.is_temporal() && left == ValueKind::Text);
+                        if !comparable {
+                            return Err(FormulaError::TypeMismatch {
+                                context: "comparison".to_string(),
+                                expected: left.to_string(),
+                                found: right,
+                            });
+                        }
+                        ValueKind::Bool
+                    }
+                }
+            }
+            Expr::Call(func, args) => {
+                let name = func.name();
+                let (min, max) = func.arity();
+                if args.len() < min || max.is_some_and(|max| args.len() > max) {
+                    let expected = match max {
+                        Some(max) if max == min => min.to_string(),
+                        Some(max) => format!("{}..{}", min, max),
+                        None => format!("at least {}", min),
+                    };
+                    return Err(FormulaError::ArgumentCount { function: name.to_string(), expected, found: args.len() });
+                }
+                let kinds = args.iter().map(|a| a.check(kind_of)).collect::<Result<Vec<_>, _>>()?;
+                let numbers = |from: usize| kinds[from..].iter().try_for_each(|k| expect(*k, &[ValueKind::Number], name));
+                match func {
+                    Func::Abs | Func::Round | Func::Ceil | Func::Floor | Func::Mod => {
+                        numbers(0)?;
+                        ValueKind::Number
+                    }
+                    Func::Len => ValueKind::Number,
+                    Func::Upper | Func::Lower | Func::Trim | Func::Concat | Func::Replace => ValueKind::Text,
+                    Func::Left | Func::Right | Func::Substr => {
+                        numbers(1)?;
+                        ValueKind::Text
+                    }
+                    Func::Year | Func::Month | Func::Day => {
+                        expect(kinds[0], DATE_ARG, name)?;
+                        ValueKind::Number
+                    }
+                    Func::Date => {
+                        numbers(0)?;
+                        ValueKind::Date
+                    }
+                    Func::AddDays | Func::AddMonths => {
+                        expect(kinds[0], DATE_ARG, name)?;
+                        numbers(1)?;
+                        if kinds[0] == ValueKind::DateTime { ValueKind::DateTime } else { ValueKind::Date }
+                    }
+                    Func::DaysBetween => {
+                        expect(kinds[0], DATE_ARG, name)?;
+                        expect(kinds[1], DATE_ARG, name)?;
+                        ValueKind::Number
+                    }
+                    Func::IsNull => ValueKind::Bool,
+                    Func::If => {
+                        expect(kinds[0], &[ValueKind::Bool], "IF condition")?;
+                        unify_all(&kinds[1..], name)?
+                    }
+                    Func::Coalesce | Func::Min | Func::Max => unify_all(&kinds, name)?,
+                }
+            }
+            Expr::Aggregate { func, .. } => match func {
+                AggFunc::Min | AggFunc::Max => ValueKind::Any,
+                _ => ValueKind::Number,
+            },
+        })
+    }
+}
+
+fn unify_all(kinds: &[ValueKind], context: &str) -> Result<ValueKind, FormulaError> {
+    kinds.iter().try_fold(ValueKind::Any, |acc, kind| {
+        acc.unify(*kind).ok_or_else(|| FormulaError::TypeMismatch {
+            context: context.to_string(),
+            expected: acc.to_string(),
+            found: *kind,
+        })
+    })
+}
+
+/// 求值时的行上下文
+struct RowContext<'a> {
+    values: &'a [CellValue],
+    children: Option<&'a HashMap<String, RowDataSet>>,
+}
+
+fn text(value: &CellValue) -> Option<String> {
+    match value {
+        CellValue::Null => None,
+        CellValue::String(s) => Some(s.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+fn datetime(value: &CellValue) -> Option<NaiveDateTime> {
+    let text = value.as_str()?;
+    parse_datetime(text).or_else(|| parse_datetime_tz(text).map(|dt| dt.naive_local()))
+}
+
+/// 日期加减，结果保持输入的格式（日期或日期时间）
+fn shift_date(value: &CellValue, shift: impl Fn(NaiveDateTime) -> Option<NaiveDateTime>) -> Option<CellValue> {
+    let text = value.as_str()?;
+    match parse_date(text) {
+        Some(date) => shift(date.and_time(NaiveTime::MIN)).map(|dt| format_date(dt.date())),
+        None => datetime(value).and_then(shift).map(format_datetime),
+    }
+}
+
+impl Expr<usize> {
+    fn eval(&self, row: &RowContext) -> CellValue {
+        self.try_eval(row).unwrap_or(CellValue::Null)
+    }
+
+    /// 求值，`None` 表示结果为 `NULL`
+    fn try_eval(&self, row: &RowContext) -> Option<CellValue> {
+        Some(match self {
+            Expr::Literal(value) => value.clone(),
+            Expr::Column(index) => row.values.get(*index).cloned().unwrap_or(CellValue::Null),
+            Expr::Unary(UnaryOp::Neg, inner) => decimal_to_cell(-to_decimal(&inner.eval(row))?),
+            Expr::Unary(UnaryOp::Not, inner) => CellValue::Bool(!inner.eval(row).as_bool()?),
+            Expr::Binary(op, left, right) => {
+                let (left, right) = (left.eval(row), right.eval(row));
+                match op {
+                    BinaryOp::Concat => CellValue::String(text(&left).unwrap_or_default() + &text(&right).unwrap_or_default()),
+                    BinaryOp::And => CellValue::from(and3(left.as_bool(), right.as_bool())?),
+                    BinaryOp::Or => CellValue::from(or3(left.as_bool(), right.as_bool())?),
+                    BinaryOp::Compare(op) => CellValue::Bool(op.test(compare_values(&left, &right, None)?)),
+                    _ => {
+                        let (x, y) = (to_decimal(&left)?, to_decimal(&right)?);
+                        decimal_to_cell(match op {
+                            BinaryOp::Add => x.checked_add(y)?,
+                            BinaryOp::Sub => x.checked_sub(y)?,
+                            BinaryOp::Mul => x.checked_mul(y)?,
+                            BinaryOp::Div => x.checked_div(y)?,
+                            _ => x.checked_rem(y)?,
+                        })
+                    }
+                }
+            }
+            Expr::Call(Func::If, args) => {
+                let branch = if args[0].eval(row).as_bool() == Some(true) { &args[1] } else { &args[2] };
+                branch.eval(row)
+            }
+            Expr::Call(Func::Coalesce, args) => args.iter().map(|a| a.eval(row)).find(|v| !v.is_null())?,
+            Expr::Call(func, args) => {
+                let values: Vec<CellValue> = args.iter().map(|a| a.eval(row)).collect();
+                call(*func, &values)?
+            }
+            Expr::Aggregate { func, child, column } => {
+                let dataset = row.children.and_then(|children| children.get(child));
+                let Some(column) = column else {
+                    return Some(CellValue::from(dataset.map_or(0, RowDataSet::row_count)));
+                };
+                let values: Vec<&CellValue> = dataset
+                    .and_then(|d| d.get_column_values(column).ok())
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|v| !v.is_null())
+                    .collect();
+                match func {
+                    AggFunc::Count => CellValue::from(values.len()),
+                    AggFunc::Sum | AggFunc::Avg => {
+                        let numbers = values.iter().map(|v| to_decimal(v)).collect::<Option<Vec<_>>>()?;
+                        let sum = numbers.iter().try_fold(Decimal::ZERO, |acc, n| acc.checked_add(*n))?;
+                        match func {
+                            AggFunc::Sum if !numbers.is_empty() => decimal_to_cell(sum),
+                            AggFunc::Avg => decimal_to_cell(sum.checked_div(Decimal::from(numbers.len()))?),
+                            _ => return None,
+                        }
+                    }
+                    // 解析器只为子数据集生成 SUM、AVG、COUNT、MIN、MAX
+                    _ => extreme(values.into_iter(), *func == AggFunc::Max)?,
+                }
+            }
+        })
+    }
+}
+
+/// 忽略 NULL 的最小值或最大值
+fn extreme<'a>(values: impl Iterator<Item = &'a CellValue>, max: bool) -> Option<CellValue> {
+    let wanted = if max { Ordering::Greater } else { Ordering::Less };
+    values
+        .filter(|v| !v.is_null())
+        .fold(None, |best: Option<&CellValue>, v| match best {
+            Some(b) if compare_values(v, b, None) != Some(wanted) => Some(b),
+            _ => Some(v),
+        })
+        .cloned()
+}
+
+/// 计算普通函数，参数已求值
+fn call(func: Func, args: &[CellValue]) -> Option<CellValue> {
+    let number = |i: usize| args.get(i).and_then(to_decimal);
+    let integer = |i: usize| number(i).and_then(|n| n.trunc().to_i64());
+    let string = |i: usize| args.get(i).and_then(text);
+    let date = |i: usize| args.get(i).and_then(datetime);
+    let count = |n: i64| usize::try_from(n.max(0)).ok();
+
+    Some(match func {
+        Func::Abs => decimal_to_cell(number(0)?.abs()),
+        Func::Round => {
+            let digits = if args.len() > 1 { u32::try_from(integer(1)?).ok()? } else { 0 };
+            decimal_to_cell(number(0)?.round_dp_with_strategy(digits, RoundingStrategy::MidpointAwayFromZero))
+        }
+        Func::Ceil => decimal_to_cell(number(0)?.ceil()),
+        Func::Floor => decimal_to_cell(number(0)?.floor()),
+        Func::Mod => decimal_to_cell(number(0)?.checked_rem(number(1)?)?),
+        Func::Min | Func::Max => extreme(args.iter(), func == Func::Max)?,
+        Func::Len => CellValue::from(string(0)?.chars().count()),
+        Func::Upper => CellValue::String(string(0)?.to_uppercase()),
+        Func::Lower => CellValue::String(string(0)?.to_lowercase()),
+        Func::Trim => CellValue::String(string(0)?.trim().to_string()),
+        Func::Left => CellValue::String(string(0)?.chars().take(count(integer(1)?)?).collect()),
+        Func::Right => {
+            let chars: Vec<char> = string(0)?.chars().collect();
+            let n = count(integer(1)?)?.min(chars.len());
+            CellValue::String(chars[chars.len() - n..].iter().collect())
+        }
+        Func::Substr => {
+            // 起始位置不大于 1 时从头开始
+            let start = count(integer(1)?.saturating_sub(1))?;
+            let source = string(0)?;
+            let chars = source.chars().skip(start);
+            CellValue::String(if args.len() > 2 { chars.take(count(integer(2)?)?).collect() } else { chars.collect() })
+        }
+        Func::Concat => CellValue::String(args.iter().filter_map(text).collect()),
+        Func::Replace => {
+            let (source, from) = (string(0)?, string(1)?);
+            CellValue::String(if from.is_empty() { source } else { source.replace(&from, &string(2).unwrap_or_default()) })
+        }
+        Func::Year => CellValue::from(date(0)?.year()),
+        Func::Month => CellValue::from(date(0)?.month()),
+        Func::Day => CellValue::from(date(0)?.day()),
+        Func::Date => format_date(NaiveDate::from_ymd_opt(
+            i32::try_from(integer(0)?).ok()?,
+            u32::try_from(integer(1)?).ok()?,
+            u32::try_from(integer(2)?).ok()?,
+        )?),
+        Func::AddDays => {
+            let days = Duration::try_days(integer(1)?)?;
+            shift_date(&args[0], |dt| dt.checked_add_signed(days))?
+        }
+        Func::AddMonths => {
+            let months = integer(1)?;
+            let delta = Months::new(u32::try_from(months.unsigned_abs()).ok()?);
+            shift_date(&args[0], |dt| if months >= 0 { dt.checked_add_months(delta) } else { dt.checked_sub_months(delta) })?
+        }
+        Func::DaysBetween => CellValue::from((date(1)?.date() - date(0)?.date()).num_days()),
+        Func::IsNull => CellValue::Bool(args[0].is_null()),
+        // 条件函数按需求值，在 `try_eval` 中处理
+        Func::If | Func::Coalesce => return None,
+    })
+}
+
+/// 解析后的公式
+///
+/// 公式文本通过 `FromStr` 解析，`Display` 输出原始文本。
+#[derive(Debug, Clone, PartialEq)]
+pub struct Formula {
+    text: String,
+    expr: Expr<String>,
+}
+
+impl Formula {
+    pub fn parse(text: &str) -> Result<Self, FormulaError> {
+        text.parse()
+    }
+
+    /// 公式引用的同一行的列（不含子数据集聚合），按出现顺序，可能重复
+    pub fn columns(&self) -> Vec<&str> {
+        let mut columns = Vec::new();
+        self.expr.collect_columns(&mut columns);
+        columns.into_iter().map(String::as_str).collect()
+    }
+
+    /// 按列类型检查公式，返回结果类型
+    ///
+    /// `column_type` 根据列名返回列类型，列不存在时返回 `None`。
+    pub fn check<F>(&self, column_type: F) -> Result<ValueKind, FormulaError>
+    where
+        F: Fn(&str) -> Option<ColumnType>,
+    {
+        self.expr.check(&|name: &String| {
+            column_type(name).map(ValueKind::of).ok_or_else(|| FormulaError::UnknownColumn(name.clone()))
+        })
+    }
+
+    /// 按 `TableSchema` 的列定义检查公式，返回结果类型
+    pub fn check_schema(&self, schema: &TableSchema) -> Result<ValueKind, FormulaError> {
+        self.check(|name| schema.get_column(name).map(|column| column.column_type()))
+    }
+
+    /// 将列名解析为列索引，得到可以直接对行求值的公式
+    ///
+    /// # 返回值
+    ///
+    /// - `Err(FormulaError::UnknownColumn)` - 公式引用了 `resolve` 找不到的列
+    pub fn bind<F>(&self, resolve: F) -> Result<BoundFormula, FormulaError>
+    where
+        F: Fn(&str) -> Option<usize>,
+    {
+        let expr = self.expr.map_columns(&|name: &String| resolve(name).ok_or_else(|| FormulaError::UnknownColumn(name.clone())))?;
+        Ok(BoundFormula { expr })
+    }
+}
+
+/// 已绑定列索引的公式
+///
+/// 由 [`Formula::bind`] 生成，可以反复对按列顺序排列的行值求值。
+#[derive(Debug, Clone)]
+pub struct BoundFormula {
+    expr: Expr<usize>,
+}
+
+impl BoundFormula {
+    /// 对一行求值，`children` 为该行的子数据集
+    pub fn eval(&self, values: &[CellValue], children: Option<&HashMap<String, RowDataSet>>) -> CellValue {
+        self.expr.eval(&RowContext { values, children })
+    }
+}
+
+impl FromStr for Formula {
+    type Err = FormulaError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let expr = Parser { tokens: tokenize(s)?, pos: 0, depth: 0 }.parse()?;
+        Ok(Self { text: s.trim().to_string(), expr })
+    }
+}
+
+impl fmt::Display for Formula {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+/// 一组计算列公式，按依赖关系排好求值顺序
+#[derive(Debug, Clone, Default)]
+pub struct FormulaSet {
+    formulas: Vec<(String, Formula)>,
+}
+
+impl FormulaSet {
+    /// 检查并排序计算列公式
+    ///
+    /// # 参数
+    ///
+    /// * `formulas` - `(计算列名, 公式)` 列表
+    /// * `column_type` - 根据列名返回列类型
+    ///
+    /// # 返回值
+    ///
+    /// - `Err(FormulaError::Column)` - 某个公式引用了不存在的列、类型不匹配，或结果无法写入计算列
+    /// - `Err(FormulaError::Cycle)` - 计算列之间存在循环引用
+    pub fn new<F>(formulas: Vec<(String, Formula)>, column_type: F) -> Result<Self, FormulaError>
+    where
+        F: Fn(&str) -> Option<ColumnType>,
+    {
+        for (column, formula) in &formulas {
+            let target = column_type(column).ok_or_else(|| FormulaError::UnknownColumn(column.clone()))?;
+            let kind = formula.check(&column_type).map_err(|e| e.in_column(column))?;
+            if !kind.fits(target) {
+                return Err(FormulaError::ResultType { kind, column_type: target }.in_column(column));
+            }
+        }
+        Ok(Self { formulas: order_formulas(formulas)? })
+    }
+
+    /// 从表定义中读取计算列（`COL_ISCALC` 为真且 `COL_COLGS` 非空）的公式
+    pub fn from_schema(schema: &TableSchema) -> Result<Self, FormulaError> {
+        let mut formulas = Vec::new();
+        for column in &schema.columns {
+            let calculated = column.get(&SYS_OBJCOLS::COL_ISCALC).and_then(flag_value).unwrap_or(false);
+            if let Some(text) = column.get_str(&SYS_OBJCOLS::COL_COLGS).filter(|_| calculated) {
+                let name = column.col_id();
+                let formula = text.parse().map_err(|e: FormulaError| e.in_column(&name))?;
+                formulas.push((name, formula));
+            }
+        }
+        Self::new(formulas, |name| schema.get_column(name).map(|column| column.column_type()))
+    }
+
+    /// 计算列名，按求值顺序
+    pub fn columns(&self) -> Vec<&str> {
+        self.formulas.iter().map(|(column, _)| column.as_str()).collect()
+    }
+
+    pub fn get(&self, column: &str) -> Option<&Formula> {
+        self.formulas.iter().find(|(name, _)| name == column).map(|(_, formula)| formula)
+    }
+
+    pub fn len(&self) -> usize {
+        self.formulas.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.formulas.is_empty()
+    }
+}
+
+/// 按依赖关系对计算列做拓扑排序
+fn order_formulas(formulas: Vec<(String, Formula)>) -> Result<Vec<(String, Formula)>, FormulaError> {
+    let positions: HashMap<&str, usize> = formulas.iter().enumerate().map(|(i, (name, _))| (name.as_str(), i)).collect();
+    let dependencies: Vec<Vec<usize>> = formulas
+        .iter()
+        .map(|(_, formula)| formula.columns().into_iter().filter_map(|c| positions.get(c).copied()).collect())
+        .collect();
+
+    #[derive(Clone, Copy, PartialEq)]
+    enum Mark {
+        New,
+        Visiting,
+        Done,
+    }
+
+    fn visit(i: usize, dependencies: &[Vec<usize>], marks: &mut [Mark], stack: &mut Vec<usize>, order: &mut Vec<usize>) -> Result<(), Vec<usize>> {
+        match marks[i] {
+            Mark::Done => return Ok(()),
+            Mark::Visiting => {
+                let start = stack.iter().position(|s| *s == i).unwrap_or(0);
+                let mut cycle = stack[start..].to_vec();
+                cycle.push(i);
+                return Err(cycle);
+            }
+            Mark::New => {}
+        }
+        marks[i] = Mark::Visiting;
+        stack.push(i);
+        for dependency in &dependencies[i] {
+            visit(*dependency, dependencies, marks, stack, order)?;
+        }
+        stack.pop();
+        marks[i] = Mark::Done;
+        order.push(i);
+        Ok(())
+    }
+
+    let mut marks = vec![Mark::New; formulas.len()];
+    let mut order = Vec::with_capacity(formulas.len());
+    for i in 0..formulas.len() {
+        visit(i, &dependencies, &mut marks, &mut Vec::new(), &mut order)
+            .map_err(|cycle| FormulaError::Cycle(cycle.into_iter().map(|c| formulas[c].0.clone()).collect()))?;
+    }
+
+    let mut slots: Vec<Option<(String, Formula)>> = formulas.into_iter().map(Some).collect();
+    Ok(order.into_iter().filter_map(|i| slots[i].take()).collect())
+}
+
+/// 绑定到数据集列索引的计算列公式
+#[derive(Debug)]
+pub(crate) struct BoundFormulas {
+    formulas: Vec<CalcColumn>,
+}
+
+#[derive(Debug)]
+struct CalcColumn {
+    column: String,
+    index: usize,
+    column_type: ColumnType,
+    expr: Expr<usize>,
+}
+
+impl BoundFormulas {
+    pub(crate) fn is_calculated(&self, index: usize) -> bool {
+        self.formulas.iter().any(|formula| formula.index == index)
+    }
+
+    /// 按求值顺序计算一行的所有计算列，结果按列类型转换后写回 `values`
+    pub(crate) fn apply(
+        &self,
+        row_index: usize,
+        values: &mut [CellValue],
+        children: Option<&HashMap<String, RowDataSet>>,
+    ) -> Result<(), DataSetError> {
+        for formula in &self.formulas {
+            let value = formula.expr.eval(&RowContext { values: &*values, children });
+            let value = formula.column_type.coerce(value).map_err(|value| DataSetError::TypeMismatch {
+                row: row_index,
+                column: formula.column.clone(),
+                expected: formula.column_type,
+                value,
+            })?;
+            if let Some(cell) = values.get_mut(formula.index) {
+                *cell = value;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl RowDataSet {
+    /// 设置计算列公式，并重新计算所有行
+    ///
+    /// 公式按数据集的列类型重新检查。替换之前设置的公式；计算失败时保留之前的公式，
+    /// 已经重新计算的行不会回滚。
+    ///
+    /// # 返回值
+    ///
+    /// - `Err(FormulaError::Column)` - 公式引用了数据集中不存在的列，或类型不匹配
+    /// - `Err(FormulaError::DataSet)` - 计算结果无法转换为计算列的类型，或违反唯一索引
+    pub fn set_formulas(&mut self, formulas: &FormulaSet) -> Result<(), FormulaError> {
+        let mut bound = Vec::with_capacity(formulas.len());
+        for (column, formula) in &formulas.formulas {
+            let info = self.schema.get(column).ok_or_else(|| FormulaError::UnknownColumn(column.clone()))?;
+            let kind = formula.check(|name| self.schema.get(name).map(|c| c.column_type)).map_err(|e| e.in_column(column))?;
+            if !kind.fits(info.column_type) {
+                return Err(FormulaError::ResultType { kind, column_type: info.column_type }.in_column(column));
+            }
+            let expr = formula.bind(|name| self.schema.get(name).map(|c| c.index))?.expr;
+            bound.push(CalcColumn { column: column.clone(), index: info.index, column_type: info.column_type, expr });
+        }
+
+        let previous = self.formulas.replace(Arc::new(BoundFormulas { formulas: bound }));
+        if let Err(e) = self.recalculate() {
+            self.formulas = previous;
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    /// 按表定义设置计算列公式，见 [`FormulaSet::from_schema`]
+    pub fn set_formulas_from_schema(&mut self, schema: &TableSchema) -> Result<(), FormulaError> {
+        self.set_formulas(&FormulaSet::from_schema(schema)?)
+    }
+
+    /// 移除所有计算列公式，计算列的现有值保留
+    pub fn clear_formulas(&mut self) {
+        self.formulas = None;
+    }
+
+    /// 计算列名，按求值顺序
+    pub fn formula_columns(&self) -> Vec<&str> {
+        self.formulas
+            .as_ref()
+            .map(|formulas| formulas.formulas.iter().map(|f| f.column.as_str()).collect())
+            .unwrap_or_default()
+    }
+
+    /// 重新计算所有行的计算列
+    pub fn recalculate(&mut self) -> Result<(), DataSetError> {
+        for row_index in 0..self.rows.len() {
+            self.recalculate_row(row_index)?;
+        }
+        Ok(())
+    }
+
+    /// 重新计算指定行的计算列
+    ///
+    /// 通过 `get_child_mut` 等方式直接修改了子数据集后调用。
+    pub fn recalculate_row(&mut self, row_index: usize) -> Result<(), DataSetError> {
+        if self.formulas.is_none() {
+            return Ok(());
+        }
+        let row = self.get_row(row_index)?;
+        let mut values = row.values().clone();
+        self.apply_formulas(row_index, &mut values, row.children.as_ref())?;
+        self.replace_values(row_index, values)
+    }
+
+    pub(crate) fn apply_formulas(
+        &self,
+        row_index: usize,
+        values: &mut [CellValue],
+        children: Option<&HashMap<String, RowDataSet>>,
+    ) -> Result<(), DataSetError> {
+        match &self.formulas {
+            Some(formulas) => formulas.apply(row_index, values, children),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Keyword {
+    And,
+    Or,
+    Not,
+    True,
+    False,
+    Null,
+}
+
+impl Keyword {
+    fn from_word(word: &str) -> Option<Self> {
+        Some(match word.to_ascii_uppercase().as_str() {
+            "AND" => Keyword::And,
+            "OR" => Keyword::Or,
+            "NOT" => Keyword::Not,
+            "TRUE" => Keyword::True,
+            "FALSE" => Keyword::False,
+            "NULL" => Keyword::Null,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Keyword(Keyword),
+    Str(String),
+    Number(serde_json::Number),
+    Compare(CompareOp),
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Percent,
+    Concat,
+    LParen,
+    RParen,
+    Comma,
+}
+
+/// 括号、函数调用、`NOT` 和负号的最大嵌套层数，避免深层嵌套的公式耗尽栈
+const MAX_DEPTH: usize = 128;
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn parse(mut self) -> Result<Expr<String>, FormulaError> {
+        let expr = self.parse_or()?;
+        match self.tokens.get(self.pos) {
+            None => Ok(expr),
+            Some((token, position)) => Err(FormulaError::Syntax {
+                position: *position,
+                message: format!("unexpected token {:?}", token),
+            }),
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(t, _)| t)
+    }
+
+    fn next(&mut self) -> Result<(Token, usize), FormulaError> {
+        let token = self.tokens.get(self.pos).cloned().ok_or(FormulaError::UnexpectedEnd)?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, expected: Token, what: &str) -> Result<(), FormulaError> {
+        let (token, position) = self.next()?;
+        if token == expected {
+            Ok(())
+        } else {
+            Err(FormulaError::Syntax { position, message: format!("expected {}, found {:?}", what, token) })
+        }
+    }
+
+    /// 进入一层嵌套解析，超过 [`MAX_DEPTH`] 层时报错
+    fn nested(&mut self, parse: fn(&mut Self) -> Result<Expr<String>, FormulaError>) -> Result<Expr<String>, FormulaError> {
+        if self.depth >= MAX_DEPTH {
+            let position = self.tokens.get(self.pos.saturating_sub(1)).map_or(0, |(_, position)| *position);
+            return Err(FormulaError::Syntax { position, message: format!("nesting deeper than {} levels", MAX_DEPTH) });
+        }
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    /// 解析左结合的二元运算
+    fn parse_binary(
+        &mut self,
+        operand: fn(&mut Self) -> Result<Expr<String>, FormulaError>,
+        operator: fn(&Token) -> Option<BinaryOp>,
+    ) -> Result<Expr<String>, FormulaError> {
+        let mut left = operand(self)?;
+        while let Some(op) = self.peek().and_then(operator) {
+            self.pos += 1;
+            left = Expr::Binary(op, Box::new(left), Box::new(operand(self)?));
+        }
+        Ok(left)
+    }
+
+    fn parse_or(&mut self) -> Result<Expr<String>, FormulaError> {
+        self.parse_binary(Self::parse_and, |t| (*t == Token::Keyword(Keyword::Or)).then_some(BinaryOp::Or))
+    }
+
+    fn parse_and(&mut self) -> Result<Expr<String>, FormulaError> {
+        self.parse_binary(Self::parse_not, |t| (*t == Token::Keyword(Keyword::And)).then_some(BinaryOp::And))
+    }
+
+    fn parse_not(&mut self) -> Result<Expr<String>, FormulaError> {
+        if self.eat(&Token::Keyword(Keyword::Not)) {
+            return Ok(Expr::Unary(UnaryOp::Not, Box::new(self.nested(Self::parse_not)?)));
+        }
+        let left = self.parse_concat()?;
+        if let Some(Token::Compare(op)) = self.peek().cloned() {
+            self.pos += 1;
+            return Ok(Expr::Binary(BinaryOp::Compare(op), Box::new(left), Box::new(self.parse_concat()?)));
+        }
+        Ok(left)
+    }
+
+    fn parse_concat(&mut self) -> Result<Expr<String>, FormulaError> {
+        self.parse_binary(Self::parse_additive, |t| (*t == Token::Concat).then_some(BinaryOp::Concat))
+    }
+
+    fn parse_additive(&mut self) -> Result<Expr<String>, FormulaError> {
+        self.parse_binary(Self::parse_term, |t| match t {
+            Token::Plus => Some(BinaryOp::Add),
+            Token::Minus => Some(BinaryOp::Sub),
+            _ => None,
+        })
+    }
+
+    fn parse_term(&mut self) -> Result<Expr<String>, FormulaError> {
+        self.parse_binary(Self::parse_unary, |t| match t {
+            Token::Star => Some(BinaryOp::Mul),
+            Token::Slash => Some(BinaryOp::Div),
+            Token::Percent => Some(BinaryOp::Rem),
+            _ => None,
+        })
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr<String>, FormulaError> {
+        if self.eat(&Token::Minus) {
+            return Ok(match self.nested(Self::parse_unary)? {
+                Expr::Literal(CellValue::Number(n)) => {
+                    let negated = to_decimal(&CellValue::Number(n)).map(|d| decimal_to_cell(-d));
+                    Expr::Literal(negated.unwrap_or(CellValue::Null))
+                }
+                expr => Expr::Unary(UnaryOp::Neg, Box::new(expr)),
+            });
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr<String>, FormulaError> {
+        let (token, position) = self.next()?;
+        Ok(match token {
+            Token::Number(n) => Expr::Literal(CellValue::Number(n)),
+            Token::Str(s) => Expr::Literal(CellValue::String(s)),
+            Token::Keyword(Keyword::True) => Expr::Literal(CellValue::Bool(true)),
+            Token::Keyword(Keyword::False) => Expr::Literal(CellValue::Bool(false)),
+            Token::Keyword(Keyword::Null) => Expr::Literal(CellValue::Null),
+            Token::LParen => {
+                let expr = self.nested(Self::parse_or)?;
+                self.expect(Token::RParen, "')'")?;
+                expr
+            }
+            Token::Ident(name) if self.peek() == Some(&Token::LParen) => {
+                self.pos += 1;
+                let mut args = Vec::new();
+                if !self.eat(&Token::RParen) {
+                    args.push(self.nested(Self::parse_or)?);
+                    while self.eat(&Token::Comma) {
+                        args.push(self.nested(Self::parse_or)?);
+                    }
+                    self.expect(Token::RParen, "')'")?;
+                }
+                parse_call(&name, args, position)?
+            }
+            Token::Ident(name) => Expr::Column(name),
+            token => {
+                return Err(FormulaError::Syntax { position, message: format!("expected value, found {:?}", token) });
+            }
+        })
+    }
+}
+
+/// 构造函数调用，单个 `子数据集.列` 参数的 SUM/AVG/COUNT/MIN/MAX 为子数据集聚合
+fn parse_call(name: &str, args: Vec<Expr<String>>, position: usize) -> Result<Expr<String>, FormulaError> {
+    let aggregate = match name.to_ascii_uppercase().as_str() {
+        "SUM" => Some(AggFunc::Sum),
+        "AVG" => Some(AggFunc::Avg),
+        "COUNT" => Some(AggFunc::Count),
+        "MIN" => Some(AggFunc::Min),
+        "MAX" => Some(AggFunc::Max),
+        _ => None,
+    };
+    if let Some(func) = aggregate {
+        if let [Expr::Column(reference)] = args.as_slice() {
+            match reference.split_once('.') {
+                Some((child, column)) => {
+                    return Ok(Expr::Aggregate { func, child: child.to_string(), column: Some(column.to_string()) });
+                }
+                None if func == AggFunc::Count => {
+                    return Ok(Expr::Aggregate { func, child: reference.clone(), column: None });
+                }
+                None => {}
+            }
+        }
+        if matches!(func, AggFunc::Sum | AggFunc::Avg | AggFunc::Count) {
+            return Err(FormulaError::Syntax {
+                position,
+                message: format!("{} expects a child dataset column such as ITEMS.JE", name.to_ascii_uppercase()),
+            });
+        }
+    }
+    let func = Func::from_name(name).ok_or_else(|| FormulaError::UnknownFunction(name.to_string()))?;
+    Ok(Expr::Call(func, args))
+}
+
+fn tokenize(text: &str) -> Result<Vec<(Token, usize)>, FormulaError> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let (position, c) = chars[i];
+        let next = chars.get(i + 1).map(|(_, c)| *c);
+        let syntax = |message: &str| FormulaError::Syntax { position, message: message.to_string() };
+
+        let single = match c {
+            '(' => Some(Token::LParen),
+            ')' => Some(Token::RParen),
+            ',' => Some(Token::Comma),
+            '+' => Some(Token::Plus),
+            '-' => Some(Token::Minus),
+            '*' => Some(Token::Star),
+            '/' => Some(Token::Slash),
+            '%' => Some(Token::Percent),
+            '=' => Some(Token::Compare(CompareOp::Eq)),
+            _ => None,
+        };
+        if let Some(token) = single {
+            tokens.push((token, position));
+            i += 1;
+            continue;
+        }
+
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '|' if next == Some('|') => {
+                tokens.push((Token::Concat, position));
+                i += 2;
+            }
+            '!' | '<' | '>' => {
+                let (op, len) = match (c, next) {
+                    ('!', Some('=')) | ('<', Some('>')) => (CompareOp::Ne, 2),
+                    ('<', Some('=')) => (CompareOp::Le, 2),
+                    ('>', Some('=')) => (CompareOp::Ge, 2),
+                    ('<', _) => (CompareOp::Lt, 1),
+                    ('>', _) => (CompareOp::Gt, 1),
+                    _ => return Err(syntax("expected '!='")),
+                };
+                tokens.push((Token::Compare(op), position));
+                i += len;
+            }
+            '\'' | '"' => {
+                // 单引号为字符串常量，双引号为列名，连续两个引号表示转义
+                let mut value = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err(syntax("unterminated quoted text")),
+                        Some((_, q)) if *q == c => {
+                            if chars.get(i + 1).map(|(_, n)| *n) == Some(c) {
+                                value.push(c);
+                                i += 2;
+                            } else {
+                                i += 1;
+                                break;
+                            }
+                        }
+                        Some((_, ch)) => {
+                            value.push(*ch);
+                            i += 1;
+                        }
+                    }
+                }
+                tokens.push((if c == '\'' { Token::Str(value) } else { Token::Ident(value) }, position));
+            }
+            c if c.is_ascii_digit() || c == '.' => {
+                let start = i;
+                i += 1;
+                while let Some((_, ch)) = chars.get(i) {
+                    let exponent_sign = (*ch == '-' || *ch == '+') && matches!(chars[i - 1].1, 'e' | 'E');
+                    if ch.is_ascii_digit() || *ch == '.' || *ch == 'e' || *ch == 'E' || exponent_sign {
+                        i += 1;
+                    } else {
+                        break;
+                    }
+                }
+                let literal: String = chars[start..i].iter().map(|(_, ch)| ch).collect();
+                let number = serde_json::Number::from_str(&literal)
+                    .map_err(|_| syntax(&format!("invalid number '{}'", literal)))?;
+                tokens.push((Token::Number(number), position));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while let Some((_, ch)) = chars.get(i) {
+                    if ch.is_alphanumeric() || *ch == '_' || *ch == '.' {
+                        i += 1;
+                    } else {
+                        break;
+                    }
+                }
+                let word: String = chars[start..i].iter().map(|(_, ch)| ch).collect();
+                tokens.push((Keyword::from_word(&word).map_or(Token::Ident(word), Token::Keyword), position));
+            }
+            _ => return Err(syntax(&format!("unexpected character '{}'", c))),
+        }
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::model::data::dataset::col::ColumnDef;
+    use crate::model::data::dataset::validate::Validator;
+    use crate::model::data::dataset::TableSchemaBuilder;
+
+    fn eval(text: &str, values: &[CellValue]) -> CellValue {
+        let formula: Formula = text.parse().unwrap();
+        let bound = formula.bind(|name| name.trim_start_matches('C').parse().ok()).unwrap();
+        bound.eval(values, None)
+    }
+
+    fn column(name: &str, column_type: &str, formula: Option<&str>) -> ColumnDef {
+        let mut column = ColumnDef::default();
+        column.set(SYS_OBJCOLS::COL_ID, json!(name));
+        column.set(SYS_OBJCOLS::COL_TYPE, json!(column_type));
+        if let Some(formula) = formula {
+            column.set(SYS_OBJCOLS::COL_ISCALC, json!("1"));
+            column.set(SYS_OBJCOLS::COL_COLGS, json!(formula));
+        }
+        column
+    }
+
+    #[test]
+    fn test_eval_functions() {
+        let row = [json!(10), json!("2024-01-31"), json!(null), json!(" Ab ")];
+        assert_eq!(eval("C0 * 1.5 - -2", &row), json!(17));
+        assert_eq!(eval("C0 / 3", &row), decimal_to_cell(Decimal::from(10) / Decimal::from(3)));
+        assert_eq!(eval("C0 / 0", &row), json!(null));
+        assert_eq!(eval("C0 + C2", &row), json!(null));
+        assert_eq!(eval("ROUND(2.345, 2) + ABS(-1) + MOD(C0, 4)", &row), json!(5.35));
+        assert_eq!(eval("UPPER(TRIM(C3)) || C2 || LEN(C3)", &row), json!("AB4"));
+        assert_eq!(eval("SUBSTR('财务会计', 3) || LEFT('abc', 2) || RIGHT('abc', 5)", &row), json!("会计ababc"));
+        assert_eq!(eval("SUBSTR('abc', 0) || SUBSTR('abc', -1, 2) || SUBSTR('abc', -9223372036854775808)", &row), json!("abcababc"));
+        assert_eq!(eval("ADD_MONTHS(C1, 1)", &row), json!("2024-02-29"));
+        assert_eq!(eval("DAYS_BETWEEN(C1, ADD_DAYS(C1, 30)) + YEAR(C1)", &row), json!(2054));
+        assert_eq!(eval("DATE(2024, 2, 30)", &row), json!(null));
+        assert_eq!(eval("IF(C2 > 1, 'a', IF(C0 >= 10 AND NOT ISNULL(C1), 'b', 'c'))", &row), json!("b"));
+        assert_eq!(eval("COALESCE(C2, C0) = 10 OR C2 = 1", &row), json!(true));
+        assert_eq!(eval("MAX(C2, 3, C0)", &row), json!(10));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(Formula::parse("1 +"), Err(FormulaError::UnexpectedEnd)));
+        assert!(matches!(Formula::parse("FOO(1)"), Err(FormulaError::UnknownFunction(_))));
+        assert!(matches!(Formula::parse("SUM(JE)"), Err(FormulaError::Syntax { .. })));
+        assert!(matches!(Formula::parse("(1 + 2"), Err(FormulaError::UnexpectedEnd)));
+        assert!(matches!(Formula::parse("1 ? 2"), Err(FormulaError::Syntax { position: 2, .. })));
+    }
+
+    #[test]
+    fn test_parse_rejects_deep_nesting() {
+        let nested = |open: &str, close: &str, depth: usize| format!("{}1{}", open.repeat(depth), close.repeat(depth));
+        assert!(Formula::parse(&nested("(", ")", MAX_DEPTH)).is_ok());
+        for text in [
+            nested("(", ")", MAX_DEPTH + 1),
+            nested("ABS(", ")", 10_000),
+            nested("NOT ", "", 10_000),
+            nested("-", "", 10_000),
+        ] {
+            assert!(matches!(Formula::parse(&text), Err(FormulaError::Syntax { .. })), "{}", &text[..10]);
+        }
+    }
+
+    #[test]
+    fn test_check_against_schema() {
+        let schema = TableSchemaBuilder::new()
+            .with_columns(vec![
+                column("MC", "String", None),
+                column("SL", "Decimal", None),
+                column("RQ", "Date", None),
+                column("JE", "Decimal", Some("SL * MC")),
+            ])
+            .build();
+        let check = |text: &str| Formula::parse(text).unwrap().check_schema(&schema);
+        assert_eq!(check("SL * 2").unwrap(), ValueKind::Number);
+        assert_eq!(check("ADD_DAYS(RQ, SL)").unwrap(), ValueKind::Date);
+        assert_eq!(check("IF(SL > 0, RQ, NULL)").unwrap(), ValueKind::Date);
+        assert_eq!(check("SUM(MX.JE) + COUNT(MX)").unwrap(), ValueKind::Number);
+        assert!(matches!(check("SL * MC"), Err(FormulaError::TypeMismatch { found: ValueKind::Text, .. })));
+        assert!(matches!(check("IF(SL, 1, 2)"), Err(FormulaError::TypeMismatch { .. })));
+        assert!(matches!(check("ROUND(1, 2, 3)"), Err(FormulaError::ArgumentCount { .. })));
+        assert!(matches!(check("XX + 1"), Err(FormulaError::UnknownColumn(_))));
+        assert!(matches!(FormulaSet::from_schema(&schema), Err(FormulaError::Column { .. })));
+    }
+
+    #[test]
+    fn test_dependency_order_and_cycles() {
+        let types = |name: &str| (name != "XX").then_some(ColumnType::Decimal);
+        let formulas = vec![
+            ("C".to_string(), Formula::parse("B * 2").unwrap()),
+            ("B".to_string(), Formula::parse("A + 1").unwrap()),
+            ("D".to_string(), Formula::parse("C + B").unwrap()),
+        ];
+        assert_eq!(FormulaSet::new(formulas, types).unwrap().columns(), vec!["B", "C", "D"]);
+
+        let cyclic = vec![
+            ("A".to_string(), Formula::parse("C + 1").unwrap()),
+            ("B".to_string(), Formula::parse("A + 1").unwrap()),
+            ("C".to_string(), Formula::parse("B + 1").unwrap()),
+        ];
+        match FormulaSet::new(cyclic, types) {
+            Err(FormulaError::Cycle(path)) => assert_eq!(path, vec!["A", "C", "B", "A"]),
+            other => panic!("expected cycle, got {:?}", other),
+        }
+        let own = vec![("A".to_string(), Formula::parse("A + 1").unwrap())];
+        assert!(matches!(FormulaSet::new(own, types), Err(FormulaError::Cycle(_))));
+    }
+
+    #[test]
+    fn test_dataset_recalculation() {
+        let schema = TableSchemaBuilder::new()
+            .with_columns(vec![
+                column("SL", "Decimal", None),
+                column("DJ", "Decimal", None),
+                column("JE", "Decimal", Some("ROUND(SL * DJ, 2)")),
+                column("HJ", "Decimal", Some("JE + COALESCE(SUM(MX.JE), 0)")),
+                column("MXS", "I32", Some("COUNT(MX)")),
+            ])
+            .build();
+        let mut orders = RowDataSet::new("orders".to_string());
+        for (name, column_type) in [("SL", ColumnType::Decimal), ("DJ", ColumnType::Decimal), ("JE", ColumnType::Decimal), ("HJ", ColumnType::Decimal), ("MXS", ColumnType::I32)] {
+            orders.add_column(name.to_string(), column_type).unwrap();
+        }
+        orders.add_row(vec![json!(2), json!(3), json!(null), json!(null), json!(null)]).unwrap();
+        orders.set_formulas_from_schema(&schema).unwrap();
+        assert_eq!(orders.formula_columns(), vec!["JE", "HJ", "MXS"]);
+        assert_eq!(orders.get_row(0).unwrap().values(), &vec![json!(2), json!(3), json!(6), json!(6), json!(0)]);
+
+        orders.set_change_tracking(true);
+        orders.set_cell(0, "DJ", json!(4)).unwrap();
+        assert_eq!(orders.get_cell(0, "HJ").unwrap(), &json!(8));
+        assert_eq!(orders.get_row(0).unwrap().original.as_ref().unwrap()[3], json!(6));
+        assert!(matches!(orders.set_cell(0, "JE", json!(1)), Err(DataSetError::CalculatedColumn(_))));
+
+        let mut details = RowDataSet::new("MX".to_string());
+        details.add_column("JE".to_string(), ColumnType::Decimal).unwrap();
+        details.add_row(vec![json!(1.5)]).unwrap();
+        details.add_row(vec![json!(2)]).unwrap();
+        orders.add_child_dataset(0, "MX".to_string(), details).unwrap();
+        assert_eq!(orders.get_cell(0, "HJ").unwrap(), &json!(11.5));
+        assert_eq!(orders.get_cell(0, "MXS").unwrap(), &json!(2));
+
+        orders.get_row_mut(0).unwrap().get_child_mut("MX").unwrap().add_row(vec![json!(10)]).unwrap();
+        orders.recalculate_row(0).unwrap();
+        assert_eq!(orders.get_cell(0, "HJ").unwrap(), &json!(21.5));
+
+        orders.insert_row(0, vec![json!(1), json!(0.5), json!(99), json!(null), json!(null)]).unwrap();
+        assert_eq!(orders.get_cell(0, "JE").unwrap(), &json!(0.5));
+        orders.remove_child_dataset(1, "MX").unwrap();
+        assert_eq!(orders.get_cell(1, "HJ").unwrap(), &json!(8));
+    }
+
+    #[test]
+    fn test_child_change_is_atomic() {
+        let mut total = column("HJ", "Decimal", Some("SUM(MX.JE)"));
+        total.set(SYS_OBJCOLS::COL_CHECK, json!("HJ > 0"));
+        let schema = TableSchemaBuilder::new().with_columns(vec![total]).build();
+        let mut orders = RowDataSet::new("orders".to_string());
+        orders.add_column("HJ".to_string(), ColumnType::Decimal).unwrap();
+        orders.add_row(vec![json!(null)]).unwrap();
+        orders.set_formulas_from_schema(&schema).unwrap();
+        orders.set_validator(&Validator::new(&schema).unwrap()).unwrap();
+
+        let details = |amount: i32| {
+            let mut details = RowDataSet::new("MX".to_string());
+            details.add_column("JE".to_string(), ColumnType::Decimal).unwrap();
+            details.add_row(vec![json!(amount)]).unwrap();
+            details
+        };
+        orders.add_child_dataset(0, "MX".to_string(), details(5)).unwrap();
+        assert_eq!(orders.get_cell(0, "HJ").unwrap(), &json!(5));
+
+        // 新的子数据集使合计违反约束，保留原来的子数据集
+        let result = orders.add_child_dataset(0, "MX".to_string(), details(-1));
+        assert!(matches!(result, Err(DataSetError::ConstraintViolation(_))));
+        assert_eq!(orders.get_child_dataset(0, "MX").unwrap().unwrap().get_cell(0, "JE").unwrap(), &json!(5));
+
+        // 校验表达式结果为 NULL 时视为通过，改用非空约束让移除后的重算失败
+        let mut total = column("HJ", "Decimal", Some("SUM(MX.JE)"));
+        total.set(SYS_OBJCOLS::COL_ISNULL, json!(false));
+        let schema = TableSchemaBuilder::new().with_columns(vec![total]).build();
+        orders.set_validator(&Validator::new(&schema).unwrap()).unwrap();
+        let result = orders.remove_child_dataset(0, "MX");
+        assert!(matches!(result, Err(DataSetError::ConstraintViolation(_))));
+        assert!(orders.get_child_dataset(0, "MX").unwrap().is_some());
+        assert_eq!(orders.get_cell(0, "HJ").unwrap(), &json!(5));
+    }
+}
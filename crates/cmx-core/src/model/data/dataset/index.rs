@@ -0,0 +1,641 @@
+//! # 数据集索引模块
+//!
+//! 为 `RowDataSet` 提供可选的哈希索引和有序（B 树）索引，把按键查找从逐行扫描
+//! 变为 O(1) / O(log n)。
+//!
+//! ## 索引来源
+//!
+//! - 手动声明：[`RowDataSet::create_index`]
+//! - 按表定义自动创建：[`RowDataSet::create_indexes_from_schema`]，SYS_KEYS 的主索引列
+//!   （KEY_PINDEX1..16）生成唯一哈希索引，SYS_INDEXS 的定义生成有序索引（`INX_TYPE` 为
+//!   `HASH` 时为哈希索引）
+//!
+//! ## 一致性
+//!
+//! `add_row`、`insert_row`、`remove_row`、`set_cell`、`clear` 和 `reject_changes` 会同步维护索引，
+//! 唯一索引上的重复键会使写入失败并返回 `DataSetError::DuplicateKey`，数据集保持不变。
+//! 行的列值只能通过这些方法修改。有未接受的变更时创建的唯一索引同时要求回滚后恢复的行
+//! 不重复，因此 `reject_changes` 不会违反唯一约束。索引不参与序列化。
+//!
+//! 键值先按列类型转换再比较，因此 `"1"` 可以查到 `I32` 列中的 `1`，`Decimal` 列中的
+//! `1.0` 与 `1.00` 视为相同的键。包含 NULL 的键不会进入索引，也就不受唯一约束限制。
+//!
+//! ## 示例
+//!
+//! ```rust
+//! use std::ops::Bound;
+//! use cmx_core::model::data::dataset::{rds::RowDataSet, index::IndexSpec, ColumnType};
+//! use serde_json::json;
+//!
+//! let mut dept = RowDataSet::new("dept".to_string());
+//! dept.add_column("BM".to_string(), ColumnType::String).unwrap();
+//! dept.add_column("JS".to_string(), ColumnType::I32).unwrap();
+//! dept.create_index(IndexSpec::hash("PK", &["BM"]).unique()).unwrap();
+//! dept.create_index(IndexSpec::sorted("IX_JS", &["JS"])).unwrap();
+//!
+//! dept.add_row(vec![json!("01"), json!(1)]).unwrap();
+//! dept.add_row(vec![json!("0101"), json!(2)]).unwrap();
+//! dept.add_row(vec![json!("02"), json!(1)]).unwrap();
+//! assert!(dept.add_row(vec![json!("01"), json!(1)]).is_err());
+//!
+//! assert_eq!(dept.find_by_key(&[json!("0101")]).unwrap(), Some(1));
+//! let level_one = dept.range_by_index("IX_JS", Bound::Unbounded, Bound::Included(&[json!(1)])).unwrap();
+//! assert_eq!(level_one, vec![0, 2]);
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Bound;
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+
+use super::coerce::{parse_datetime_tz, to_decimal};
+use super::changes::RowState;
+use super::rds::RowDataSet;
+use super::{ColumnType, DataSetError, TableSchema};
+use crate::model::data::cell::CellValue;
+
+/// 索引类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexKind {
+    /// 哈希索引，只支持等值查找
+    Hash,
+    /// 有序索引，支持等值查找和范围查找
+    Sorted,
+}
+
+/// 索引声明
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexSpec {
+    /// 索引名称，在数据集内唯一
+    pub name: String,
+    /// 索引列
+    pub columns: Vec<String>,
+    pub kind: IndexKind,
+    /// 是否唯一索引
+    pub unique: bool,
+}
+
+impl IndexSpec {
+    pub fn new(name: &str, columns: &[&str], kind: IndexKind) -> Self {
+        Self {
+            name: name.to_string(),
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+            kind,
+            unique: false,
+        }
+    }
+
+    /// 哈希索引
+    pub fn hash(name: &str, columns: &[&str]) -> Self {
+        Self::new(name, columns, IndexKind::Hash)
+    }
+
+    /// 有序索引
+    pub fn sorted(name: &str, columns: &[&str]) -> Self {
+        Self::new(name, columns, IndexKind::Sorted)
+    }
+
+    /// 设置为唯一索引
+    pub fn unique(mut self) -> Self {
+        self.unique = true;
+        self
+    }
+}
+
+/// 索引键的组成部分，按列类型规整后可以哈希和排序
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum KeyPart {
+    Bool(bool),
+    Number(Decimal),
+    Instant(DateTime<Utc>),
+    Text(String),
+}
+
+type IndexKey = Vec<KeyPart>;
+
+/// 把单元格值转换为键的组成部分，NULL 返回 `None`
+fn key_part(value: &CellValue, column_type: ColumnType) -> Option<KeyPart> {
+    let value = column_type.coerce(value.clone()).unwrap_or_else(|value| value);
+    let part = match &value {
+        CellValue::Null => return None,
+        CellValue::Bool(b) => KeyPart::Bool(*b),
+        CellValue::Number(_) => to_decimal(&value).map_or_else(|| KeyPart::Text(value.to_string()), KeyPart::Number),
+        CellValue::String(s) if column_type == ColumnType::DateTimeTz => parse_datetime_tz(s)
+            .map_or_else(|| KeyPart::Text(s.clone()), |dt| KeyPart::Instant(dt.with_timezone(&Utc))),
+        CellValue::String(s) if column_type.is_numeric() => {
+            to_decimal(&value).map_or_else(|| KeyPart::Text(s.clone()), KeyPart::Number)
+        }
+        CellValue::String(s) => KeyPart::Text(s.clone()),
+        other => KeyPart::Text(other.to_string()),
+    };
+    Some(part)
+}
+
+#[derive(Debug, Clone)]
+enum IndexEntries {
+    Hash(HashMap<IndexKey, Vec<usize>>),
+    Sorted(BTreeMap<IndexKey, Vec<usize>>),
+}
+
+/// 数据集上的一个索引：键 -> 行号列表（升序）
+#[derive(Debug, Clone)]
+pub(crate) struct DataSetIndex {
+    spec: IndexSpec,
+    /// 索引列在行中的位置和列类型
+    columns: Vec<(usize, ColumnType)>,
+    entries: IndexEntries,
+}
+
+impl DataSetIndex {
+    /// 按行值创建索引，唯一索引上出现重复键时返回 `DataSetError::DuplicateKey`
+    fn build<'a>(
+        spec: IndexSpec,
+        columns: Vec<(usize, ColumnType)>,
+        rows: impl Iterator<Item = &'a Vec<CellValue>>,
+    ) -> Result<Self, DataSetError> {
+        let entries = match spec.kind {
+            IndexKind::Hash => IndexEntries::Hash(HashMap::new()),
+            IndexKind::Sorted => IndexEntries::Sorted(BTreeMap::new()),
+        };
+        let mut index = DataSetIndex { spec, columns, entries };
+        for (row_index, values) in rows.enumerate() {
+            if let Some(key) = index.key_of(values) {
+                if index.conflicts(&key, None) {
+                    return Err(DataSetError::DuplicateKey {
+                        index: index.spec.name.clone(),
+                        key: format_key(values, &index.columns),
+                    });
+                }
+                index.insert(key, row_index);
+            }
+        }
+        Ok(index)
+    }
+
+    /// 计算一行的键，任一列为 NULL 时返回 `None`
+    fn key_of(&self, values: &[CellValue]) -> Option<IndexKey> {
+        self.columns
+            .iter()
+            .map(|(position, column_type)| values.get(*position).and_then(|value| key_part(value, *column_type)))
+            .collect()
+    }
+
+    /// 把查找用的键值转换为索引键，可以只给出前几列
+    fn lookup_key(&self, key: &[CellValue]) -> Option<IndexKey> {
+        key.iter()
+            .zip(&self.columns)
+            .map(|(value, (_, column_type))| key_part(value, *column_type))
+            .collect()
+    }
+
+    fn get(&self, key: &IndexKey) -> Option<&Vec<usize>> {
+        match &self.entries {
+            IndexEntries::Hash(map) => map.get(key),
+            IndexEntries::Sorted(map) => map.get(key),
+        }
+    }
+
+    fn insert(&mut self, key: IndexKey, row_index: usize) {
+        let rows = match &mut self.entries {
+            IndexEntries::Hash(map) => map.entry(key).or_default(),
+            IndexEntries::Sorted(map) => map.entry(key).or_default(),
+        };
+        let position = rows.partition_point(|row| *row < row_index);
+        rows.insert(position, row_index);
+    }
+
+    fn remove(&mut self, key: &IndexKey, row_index: usize) {
+        let now_empty = match &mut self.entries {
+            IndexEntries::Hash(map) => map.get_mut(key).map(|rows| {
+                rows.retain(|row| *row != row_index);
+                rows.is_empty()
+            }),
+            IndexEntries::Sorted(map) => map.get_mut(key).map(|rows| {
+                rows.retain(|row| *row != row_index);
+                rows.is_empty()
+            }),
+        };
+        if now_empty == Some(true) {
+            match &mut self.entries {
+                IndexEntries::Hash(map) => map.remove(key),
+                IndexEntries::Sorted(map) => map.remove(key),
+            };
+        }
+    }
+
+    /// 行号不小于 `from` 的条目整体平移
+    fn shift(&mut self, from: usize, inserted: bool) {
+        let rows: Box<dyn Iterator<Item = &mut Vec<usize>>> = match &mut self.entries {
+            IndexEntries::Hash(map) => Box::new(map.values_mut()),
+            IndexEntries::Sorted(map) => Box::new(map.values_mut()),
+        };
+        for row in rows.flatten().filter(|row| **row >= from) {
+            if inserted {
+                *row += 1;
+            } else {
+                *row -= 1;
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        match &mut self.entries {
+            IndexEntries::Hash(map) => map.clear(),
+            IndexEntries::Sorted(map) => map.clear(),
+        }
+    }
+
+    /// 唯一索引上是否已有其他行使用这个键
+    fn conflicts(&self, key: &IndexKey, exclude: Option<usize>) -> bool {
+        self.spec.unique && self.get(key).is_some_and(|rows| rows.iter().any(|row| Some(*row) != exclude))
+    }
+}
+
+fn format_key(values: &[CellValue], columns: &[(usize, ColumnType)]) -> String {
+    let parts: Vec<String> = columns
+        .iter()
+        .map(|(position, _)| values.get(*position).map_or_else(String::new, |v| v.to_string()))
+        .collect();
+    format!("({})", parts.join(", "))
+}
+
+/// 比较键的前缀与边界
+fn prefix_cmp(key: &IndexKey, bound: &IndexKey) -> std::cmp::Ordering {
+    key[..bound.len().min(key.len())].cmp(bound.as_slice())
+}
+
+impl RowDataSet {
+    /// 在数据集上创建索引，并用现有行填充
+    ///
+    /// # 返回值
+    ///
+    /// - `Ok(())` - 创建成功
+    /// - `Err(DataSetError::ColumnNotFound)` - 索引列不存在
+    /// - `Err(DataSetError::InvalidIndex)` - 索引名称重复或没有索引列
+    /// - `Err(DataSetError::DuplicateKey)` - 唯一索引上现有数据存在重复键，索引不会创建
+    pub fn create_index(&mut self, spec: IndexSpec) -> Result<(), DataSetError> {
+        let invalid = |reason: &str| DataSetError::InvalidIndex { index: spec.name.clone(), reason: reason.to_string() };
+        if spec.columns.is_empty() {
+            return Err(invalid("no columns"));
+        }
+        if self.has_index(&spec.name) {
+            return Err(invalid("index already exists"));
+        }
+        let columns = spec
+            .columns
+            .iter()
+            .map(|name| self.schema.get(name).map(|info| (info.index, info.column_type)))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(DataSetError::ColumnNotFound)?;
+        let index = DataSetIndex::build(spec, columns, self.rows.iter().map(|row| &row.values))?;
+        // 有未接受的变更时，回滚后恢复的行也要满足唯一约束
+        if index.spec.unique && self.has_changes() {
+            let restored = self
+                .rows
+                .iter()
+                .filter(|row| row.state != RowState::Added)
+                .map(|row| row.original.as_ref().unwrap_or(&row.values))
+                .chain(self.deleted.iter().map(|row| &row.values));
+            DataSetIndex::build(index.spec.clone(), index.columns.clone(), restored)?;
+        }
+        self.indexes.push(index);
+        Ok(())
+    }
+
+    /// 按表定义创建索引
+    ///
+    /// # 行为说明
+    ///
+    /// - 每个 SYS_KEYS 键定义的主索引列生成唯一哈希索引，名称为 `KEY_ID`（为空时为 `PRIMARY`）
+    /// - 每个 SYS_INDEXS 索引定义生成有序索引（`INX_TYPE` 为 `HASH` 时为哈希索引），名称为 `INX_NAME`，
+    ///   `INX_TYPE` 为 `UNIQUE` 时为唯一索引
+    /// - 已存在的同名索引会被替换；引用了数据集中不存在的列的定义会被跳过
+    /// - 第一个键定义生成的索引即 [`RowDataSet::find_by_key`] 使用的主键索引
+    pub fn create_indexes_from_schema(&mut self, schema: &TableSchema) -> Result<(), DataSetError> {
+        let key_specs = schema.keys.iter().map(|key| {
+            let name = Some(key.key_id()).filter(|id| !id.is_empty()).unwrap_or_else(|| "PRIMARY".to_string());
+            IndexSpec { name, columns: key.primary_columns(), kind: IndexKind::Hash, unique: true }
+        });
+        let index_specs = schema.indexes.iter().map(|index| IndexSpec {
+            name: index.inx_name(),
+            columns: index.columns(),
+            kind: if index.is_hash() { IndexKind::Hash } else { IndexKind::Sorted },
+            unique: index.is_unique(),
+        });
+
+        for spec in key_specs.chain(index_specs) {
+            if spec.columns.is_empty() || !spec.columns.iter().all(|c| self.schema.contains_key(c)) {
+                continue;
+            }
+            self.drop_index(&spec.name);
+            self.create_index(spec)?;
+        }
+        Ok(())
+    }
+
+    /// 删除索引，返回索引是否存在
+    pub fn drop_index(&mut self, name: &str) -> bool {
+        let count = self.indexes.len();
+        self.indexes.retain(|index| index.spec.name != name);
+        self.indexes.len() != count
+    }
+
+    pub fn has_index(&self, name: &str) -> bool {
+        self.indexes.iter().any(|index| index.spec.name == name)
+    }
+
+    /// 所有索引的声明
+    pub fn index_specs(&self) -> Vec<&IndexSpec> {
+        self.indexes.iter().map(|index| &index.spec).collect()
+    }
+
+    /// 按主键查找行号
+    ///
+    /// 主键索引为第一个唯一索引（通常由 SYS_KEYS 生成）。
+    ///
+    /// # 返回值
+    ///
+    /// - `Ok(Some(row_index))` - 找到的行号
+    /// - `Ok(None)` - 没有匹配的行
+    /// - `Err(DataSetError::InvalidIndex)` - 数据集没有唯一索引，或键的列数与索引不一致
+    pub fn find_by_key(&self, key: &[CellValue]) -> Result<Option<usize>, DataSetError> {
+        let index = self.indexes.iter().find(|index| index.spec.unique).ok_or_else(|| DataSetError::InvalidIndex {
+            index: "PRIMARY".to_string(),
+            reason: "dataset has no unique index".to_string(),
+        })?;
+        Ok(self.lookup(index, key)?.first().copied())
+    }
+
+    /// 按指定索引等值查找，返回升序的行号
+    pub fn find_by_index(&self, name: &str, key: &[CellValue]) -> Result<Vec<usize>, DataSetError> {
+        let index = self.index(name)?;
+        self.lookup(index, key)
+    }
+
+    /// 按有序索引范围查找，返回按键排序的行号
+    ///
+    /// 边界可以只给出索引的前几列，此时按前缀比较，例如在 `(KJND, KJQJ)` 索引上
+    /// `Included(&[2024])..Included(&[2024])` 返回 2024 年的所有行。
+    ///
+    /// # 返回值
+    ///
+    /// - `Err(DataSetError::InvalidIndex)` - 索引不存在或不是有序索引
+    pub fn range_by_index(
+        &self,
+        name: &str,
+        lower: Bound<&[CellValue]>,
+        upper: Bound<&[CellValue]>,
+    ) -> Result<Vec<usize>, DataSetError> {
+        let index = self.index(name)?;
+        let IndexEntries::Sorted(map) = &index.entries else {
+            return Err(DataSetError::InvalidIndex { index: name.to_string(), reason: "not a sorted index".to_string() });
+        };
+        // NULL 不参与比较，包含 NULL 的边界没有匹配的行
+        let convert = |bound: Bound<&[CellValue]>| match bound {
+            Bound::Included(key) => index.lookup_key(key).map(Bound::Included),
+            Bound::Excluded(key) => index.lookup_key(key).map(Bound::Excluded),
+            Bound::Unbounded => Some(Bound::Unbounded),
+        };
+        let (Some(lower), Some(upper)) = (convert(lower), convert(upper)) else {
+            return Ok(Vec::new());
+        };
+
+        let start = match &lower {
+            Bound::Included(key) | Bound::Excluded(key) => Bound::Included(key.clone()),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+        let rows = map
+            .range((start, Bound::Unbounded))
+            .skip_while(|(key, _)| matches!(&lower, Bound::Excluded(bound) if prefix_cmp(key, bound).is_le()))
+            .take_while(|(key, _)| match &upper {
+                Bound::Included(bound) => prefix_cmp(key, bound).is_le(),
+                Bound::Excluded(bound) => prefix_cmp(key, bound).is_lt(),
+                Bound::Unbounded => true,
+            })
+            .flat_map(|(_, rows)| rows.iter().copied())
+            .collect();
+        Ok(rows)
+    }
+
+    /// 按当前数据重建所有索引
+    ///
+    /// 唯一索引上出现重复键时返回 `DataSetError::DuplicateKey`，该索引及之后的索引会被删除。
+    pub fn rebuild_indexes(&mut self) -> Result<(), DataSetError> {
+        for index in std::mem::take(&mut self.indexes) {
+            self.create_index(index.spec)?;
+        }
+        Ok(())
+    }
+
+    fn index(&self, name: &str) -> Result<&DataSetIndex, DataSetError> {
+        self.indexes.iter().find(|index| index.spec.name == name).ok_or_else(|| DataSetError::InvalidIndex {
+            index: name.to_string(),
+            reason: "index not found".to_string(),
+        })
+    }
+
+    fn lookup(&self, index: &DataSetIndex, key: &[CellValue]) -> Result<Vec<usize>, DataSetError> {
+        if key.len() != index.columns.len() {
+            return Err(DataSetError::InvalidIndex {
+                index: index.spec.name.clone(),
+                reason: format!("expected {} key values, got {}", index.columns.len(), key.len()),
+            });
+        }
+        Ok(index.lookup_key(key).and_then(|key| index.get(&key)).cloned().unwrap_or_default())
+    }
+
+    /// 检查一行值在唯一索引上是否与其他行冲突，`exclude` 为正在修改的行
+    pub(crate) fn check_unique(&self, values: &[CellValue], exclude: Option<usize>) -> Result<(), DataSetError> {
+        for index in &self.indexes {
+            if let Some(key) = index.key_of(values)
+                && index.conflicts(&key, exclude)
+            {
+                return Err(DataSetError::DuplicateKey {
+                    index: index.spec.name.clone(),
+                    key: format_key(values, &index.columns),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// 行已插入到 `row_index` 之后更新索引
+    pub(crate) fn index_inserted(&mut self, row_index: usize) {
+        let at_end = row_index + 1 == self.rows.len();
+        for index in &mut self.indexes {
+            if !at_end {
+                index.shift(row_index, true);
+            }
+            if let Some(key) = index.key_of(&self.rows[row_index].values) {
+                index.insert(key, row_index);
+            }
+        }
+    }
+
+    /// 位于 `row_index` 的行（值为 `values`）已删除之后更新索引
+    pub(crate) fn index_removed(&mut self, row_index: usize, values: &[CellValue]) {
+        for index in &mut self.indexes {
+            if let Some(key) = index.key_of(values) {
+                index.remove(&key, row_index);
+            }
+            index.shift(row_index + 1, false);
+        }
+    }
+
+    /// 位于 `row_index` 的行原值为 `old_values`，修改之后更新索引
+    pub(crate) fn index_updated(&mut self, row_index: usize, old_values: &[CellValue]) {
+        let values = &self.rows[row_index].values;
+        for index in &mut self.indexes {
+            let old_key = index.key_of(old_values);
+            let new_key = index.key_of(values);
+            if old_key != new_key {
+                if let Some(key) = old_key {
+                    index.remove(&key, row_index);
+                }
+                if let Some(key) = new_key {
+                    index.insert(key, row_index);
+                }
+            }
+        }
+    }
+
+    pub(crate) fn index_cleared(&mut self) {
+        for index in &mut self.indexes {
+            index.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::model::data::dataset::key::{IndexDef, KeyDef};
+    use crate::model::data::dataset::TableSchemaBuilder;
+    use crate::model::meta::fields::{SYS_INDEXS, SYS_KEYS};
+
+    fn create_voucher_dataset() -> RowDataSet {
+        let mut vouchers = RowDataSet::new("vouchers".to_string());
+        vouchers.add_column("KJND".to_string(), ColumnType::I32).unwrap();
+        vouchers.add_column("PZBH".to_string(), ColumnType::String).unwrap();
+        vouchers.add_column("JE".to_string(), ColumnType::Decimal).unwrap();
+        for (year, number, amount) in [(2023, "001", "10"), (2024, "001", "20.5"), (2024, "002", "5"), (2025, "001", "1")] {
+            vouchers.add_row(vec![json!(year), json!(number), json!(amount)]).unwrap();
+        }
+        vouchers
+    }
+
+    fn create_voucher_schema() -> TableSchema {
+        let mut key = KeyDef::default();
+        key.set(SYS_KEYS::KEY_ID, json!("PK_VOUCHER"));
+        key.set(SYS_KEYS::KEY_PINDEX1, json!("KJND"));
+        key.set(SYS_KEYS::KEY_PINDEX2, json!("PZBH"));
+        let mut amount = IndexDef::default();
+        amount.set(SYS_INDEXS::INX_NAME, json!("IX_JE"));
+        amount.set(SYS_INDEXS::INX_COLS, json!("JE DESC"));
+        let mut missing = IndexDef::default();
+        missing.set(SYS_INDEXS::INX_NAME, json!("IX_MISSING"));
+        missing.set(SYS_INDEXS::INX_COLS, json!("KMBM"));
+        TableSchemaBuilder::new().with_keys(vec![key]).with_indexes(vec![amount, missing]).build()
+    }
+
+    #[test]
+    fn test_indexes_from_schema() {
+        let mut vouchers = create_voucher_dataset();
+        vouchers.create_indexes_from_schema(&create_voucher_schema()).unwrap();
+
+        let names: Vec<&str> = vouchers.index_specs().iter().map(|spec| spec.name.as_str()).collect();
+        assert_eq!(names, vec!["PK_VOUCHER", "IX_JE"]);
+        assert_eq!(vouchers.find_by_key(&[json!("2024"), json!("002")]).unwrap(), Some(2));
+        assert_eq!(vouchers.find_by_key(&[json!(2026), json!("001")]).unwrap(), None);
+        assert!(vouchers.find_by_key(&[json!(2024)]).is_err());
+        assert_eq!(vouchers.find_by_index("IX_JE", &[json!(20.50)]).unwrap(), vec![1]);
+
+        let range = vouchers.range_by_index("IX_JE", Bound::Excluded(&[json!(1)]), Bound::Included(&[json!(10)])).unwrap();
+        assert_eq!(range, vec![2, 0]);
+        assert!(vouchers.range_by_index("PK_VOUCHER", Bound::Unbounded, Bound::Unbounded).is_err());
+    }
+
+    #[test]
+    fn test_prefix_range() {
+        let mut vouchers = create_voucher_dataset();
+        vouchers.create_index(IndexSpec::sorted("IX_YEAR_NO", &["KJND", "PZBH"])).unwrap();
+        let year = [json!(2024)];
+        let rows = vouchers.range_by_index("IX_YEAR_NO", Bound::Included(&year), Bound::Included(&year)).unwrap();
+        assert_eq!(rows, vec![1, 2]);
+        let rows = vouchers.range_by_index("IX_YEAR_NO", Bound::Excluded(&year), Bound::Unbounded).unwrap();
+        assert_eq!(rows, vec![3]);
+        let rows = vouchers.range_by_index("IX_YEAR_NO", Bound::Unbounded, Bound::Excluded(&year)).unwrap();
+        assert_eq!(rows, vec![0]);
+    }
+
+    #[test]
+    fn test_indexes_follow_mutations() {
+        let mut vouchers = create_voucher_dataset();
+        vouchers.create_index(IndexSpec::hash("PK", &["KJND", "PZBH"]).unique()).unwrap();
+        vouchers.create_index(IndexSpec::sorted("IX_JE", &["JE"])).unwrap();
+
+        // 唯一约束冲突时数据集保持不变
+        let duplicate = vouchers.insert_row(0, vec![json!(2024), json!("001"), json!(1)]);
+        assert!(matches!(duplicate, Err(DataSetError::DuplicateKey { .. })));
+        assert_eq!(vouchers.row_count(), 4);
+        assert!(vouchers.set_cell(0, "KJND", json!(2024)).is_err());
+        assert_eq!(vouchers.get_cell(0, "KJND").unwrap(), &json!(2023));
+
+        vouchers.insert_row(0, vec![json!(2022), json!("001"), json!(7)]).unwrap();
+        assert_eq!(vouchers.find_by_key(&[json!(2024), json!("002")]).unwrap(), Some(3));
+
+        vouchers.remove_row(1).unwrap();
+        assert_eq!(vouchers.find_by_key(&[json!(2023), json!("001")]).unwrap(), None);
+        assert_eq!(vouchers.find_by_key(&[json!(2025), json!("001")]).unwrap(), Some(3));
+
+        vouchers.set_cell(3, "PZBH", json!("009")).unwrap();
+        assert_eq!(vouchers.find_by_key(&[json!(2025), json!("009")]).unwrap(), Some(3));
+        assert_eq!(vouchers.find_by_key(&[json!(2025), json!("001")]).unwrap(), None);
+
+        // NULL 键不进入索引，也不受唯一约束限制
+        vouchers.add_row(vec![json!(2024), CellValue::Null, CellValue::Null]).unwrap();
+        vouchers.add_row(vec![json!(2024), CellValue::Null, CellValue::Null]).unwrap();
+        assert_eq!(vouchers.range_by_index("IX_JE", Bound::Unbounded, Bound::Unbounded).unwrap(), vec![3, 2, 0, 1]);
+
+        vouchers.set_change_tracking(true);
+        vouchers.add_row(vec![json!(2030), json!("001"), json!(0)]).unwrap();
+        vouchers.remove_row(0).unwrap();
+        vouchers.reject_changes();
+        assert_eq!(vouchers.find_by_key(&[json!(2030), json!("001")]).unwrap(), None);
+        assert_eq!(vouchers.find_by_key(&[json!(2022), json!("001")]).unwrap(), Some(5));
+
+        vouchers.clear();
+        assert_eq!(vouchers.find_by_key(&[json!(2024), json!("002")]).unwrap(), None);
+        vouchers.add_row(vec![json!(2024), json!("002"), json!(1)]).unwrap();
+        assert_eq!(vouchers.find_by_key(&[json!(2024), json!("002")]).unwrap(), Some(0));
+    }
+
+    #[test]
+    fn test_unique_index_covers_rows_restored_by_reject() {
+        let mut vouchers = create_voucher_dataset();
+        vouchers.add_row(vec![json!(2024), json!("001"), json!(3)]).unwrap();
+        vouchers.set_change_tracking(true);
+        vouchers.remove_row(1).unwrap();
+        vouchers.set_cell(3, "PZBH", json!("003")).unwrap();
+
+        // 回滚会恢复重复的键，有未接受的变更时不能创建这样的唯一索引
+        let pk = IndexSpec::hash("PK", &["KJND", "PZBH"]).unique();
+        assert!(matches!(vouchers.create_index(pk.clone()), Err(DataSetError::DuplicateKey { .. })));
+        assert!(vouchers.index_specs().is_empty());
+        vouchers.create_index(IndexSpec::hash("IX_ND", &["KJND"])).unwrap();
+
+        vouchers.reject_changes();
+        assert_eq!(vouchers.row_count(), 5);
+        assert_eq!(vouchers.find_by_index("IX_ND", &[json!(2024)]).unwrap(), vec![1, 3, 4]);
+        assert!(vouchers.create_index(pk.clone()).is_err());
+
+        // 接受变更后只按当前的行检查
+        vouchers.remove_row(4).unwrap();
+        vouchers.accept_changes();
+        vouchers.create_index(pk).unwrap();
+        assert_eq!(vouchers.find_by_key(&[json!(2024), json!("001")]).unwrap(), Some(3));
+    }
+}
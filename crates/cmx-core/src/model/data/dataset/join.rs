@@ -0,0 +1,370 @@
+//! # 连接模块
+//!
+//! 为两个 `RowDataSet` 提供按键列连接的能力，支持内连接、左/右/全外连接以及
+//! 半连接和反连接，结果是一个合并了列定义的新 `RowDataSet`。
+//!
+//! ## 实现
+//!
+//! 使用哈希连接：先以右侧数据集的键值建立哈希表，再逐行探测左侧数据集，
+//! 时间复杂度为 O(左行数 + 右行数 + 结果行数)。
+//!
+//! ## 键值比较
+//!
+//! - 键值为 `NULL` 的行不与任何行匹配（与 SQL 一致）
+//! - 任一侧的键列为数值类型时，按数值比较，`1`、`1.0`、`"1.00"` 相互匹配
+//! - 带时区的日期时间按时间点比较
+//! - 其他类型按规范表示比较（写入时已经过 `ColumnType::coerce` 规整）
+//!
+//! ## 列名冲突
+//!
+//! 结果先包含左侧的所有列，再包含右侧的列（半连接和反连接只包含左侧的列）。
+//! 右侧列可以通过 [`JoinSpec::alias`] 显式改名；未改名且与已有列重名时，
+//! 加上右侧前缀（默认为 `<右侧数据集ID>_`，可通过 [`JoinSpec::right_prefix`] 修改）。
+//! 加前缀后仍然重名时返回 `DataSetError::DuplicateColumn`。
+//!
+//! ## 示例
+//!
+//! ```rust
+//! use cmx_core::model::data::dataset::{rds::RowDataSet, ColumnType};
+//! use cmx_core::model::data::dataset::join::{JoinSpec, JoinType};
+//! use serde_json::json;
+//!
+//! let mut facts = RowDataSet::new("facts".to_string());
+//! facts.add_column("DW".to_string(), ColumnType::String).unwrap();
+//! facts.add_column("JE".to_string(), ColumnType::Decimal).unwrap();
+//! facts.add_row(vec![json!("01"), json!(10)]).unwrap();
+//! facts.add_row(vec![json!("09"), json!(20)]).unwrap();
+//!
+//! let mut units = RowDataSet::new("dw".to_string());
+//! units.add_column("DW".to_string(), ColumnType::String).unwrap();
+//! units.add_column("MC".to_string(), ColumnType::String).unwrap();
+//! units.add_row(vec![json!("01"), json!("总部")]).unwrap();
+//!
+//! let joined = facts.join(&units, &JoinSpec::new(JoinType::Left).on("DW", "DW")).unwrap();
+//! assert_eq!(joined.column_count(), 4);
+//! assert_eq!(joined.get_cell(0, "MC").unwrap(), &json!("总部"));
+//! assert!(joined.get_cell(1, "dw_DW").unwrap().is_null());
+//! ```
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::coerce::{parse_datetime_tz, to_decimal};
+use super::rds::{ColumnInfo, RowData, RowDataSet};
+use super::{ColumnType, DataSetError};
+use crate::model::data::cell::CellValue;
+
+/// 连接类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JoinType {
+    /// 只保留两侧都匹配的行
+    Inner,
+    /// 保留左侧所有行，右侧无匹配时补 `NULL`
+    Left,
+    /// 保留右侧所有行，左侧无匹配时补 `NULL`
+    Right,
+    /// 保留两侧所有行
+    Full,
+    /// 保留在右侧有匹配的左侧行，只包含左侧的列
+    Semi,
+    /// 保留在右侧没有匹配的左侧行，只包含左侧的列
+    Anti,
+}
+
+/// 连接定义
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JoinSpec {
+    pub join_type: JoinType,
+    /// 键列对：(左侧列名, 右侧列名)
+    pub keys: Vec<(String, String)>,
+    /// 右侧重名列的前缀，`None` 时使用 `<右侧数据集ID>_`
+    pub right_prefix: Option<String>,
+    /// 右侧列改名：右侧列名 -> 结果列名
+    pub aliases: HashMap<String, String>,
+}
+
+impl JoinSpec {
+    pub fn new(join_type: JoinType) -> Self {
+        Self { join_type, keys: Vec::new(), right_prefix: None, aliases: HashMap::new() }
+    }
+
+    /// 添加一对键列，多次调用表示多列键
+    pub fn on(mut self, left: impl Into<String>, right: impl Into<String>) -> Self {
+        self.keys.push((left.into(), right.into()));
+        self
+    }
+
+    /// 设置右侧重名列的前缀
+    pub fn right_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.right_prefix = Some(prefix.into());
+        self
+    }
+
+    /// 为右侧列指定结果列名
+    pub fn alias(mut self, right_column: impl Into<String>, name: impl Into<String>) -> Self {
+        self.aliases.insert(right_column.into(), name.into());
+        self
+    }
+}
+
+/// 键值的比较方式
+#[derive(Clone, Copy)]
+enum KeyMode {
+    Numeric,
+    Instant,
+    Exact,
+}
+
+impl KeyMode {
+    fn for_pair(left: ColumnType, right: ColumnType) -> Self {
+        if left.is_numeric() || right.is_numeric() {
+            KeyMode::Numeric
+        } else if left == ColumnType::DateTimeTz || right == ColumnType::DateTimeTz {
+            KeyMode::Instant
+        } else {
+            KeyMode::Exact
+        }
+    }
+
+    /// 把键值规整为可以直接比较相等的字符串，`NULL` 返回 `None`
+    fn normalize(&self, value: &CellValue, out: &mut String) -> Option<()> {
+        if value.is_null() {
+            return None;
+        }
+        let text = match self {
+            KeyMode::Numeric => to_decimal(value).map(|d| format!("n{}", d.normalize())),
+            KeyMode::Instant => value
+                .as_str()
+                .and_then(parse_datetime_tz)
+                .map(|t| format!("t{}", t.timestamp_nanos_opt().unwrap_or_default())),
+            KeyMode::Exact => None,
+        };
+        out.push_str(&text.unwrap_or_else(|| value.to_string()));
+        // 分隔符使用控制字符，避免多列键拼接后产生歧义
+        out.push('\u{1f}');
+        Some(())
+    }
+}
+
+/// 按索引顺序返回数据集的列
+fn ordered_columns(dataset: &RowDataSet) -> Vec<(&String, &ColumnInfo)> {
+    let mut columns: Vec<_> = dataset.schema.iter().collect();
+    columns.sort_by_key(|(_, info)| info.index);
+    columns
+}
+
+fn build_key(values: &[CellValue], keys: &[(usize, KeyMode)]) -> Option<String> {
+    let mut key = String::new();
+    for (index, mode) in keys {
+        mode.normalize(values.get(*index).unwrap_or(&CellValue::Null), &mut key)?;
+    }
+    Some(key)
+}
+
+impl RowDataSet {
+    /// 与另一个数据集按键列连接
+    ///
+    /// # 参数
+    ///
+    /// * `right` - 右侧数据集
+    /// * `spec` - 连接类型、键列和列名冲突的处理方式
+    ///
+    /// # 返回值
+    ///
+    /// 返回 `Result<RowDataSet, DataSetError>`：
+    /// - `Ok(dataset)` - 连接结果，数据集 ID 与左侧相同
+    /// - `Err(DataSetError::InvalidJoin)` - 没有指定键列
+    /// - `Err(DataSetError::ColumnNotFound)` - 键列不存在
+    /// - `Err(DataSetError::DuplicateColumn)` - 右侧列处理后仍与已有列重名
+    ///
+    /// # 行为说明
+    ///
+    /// - 结果按左侧行的顺序排列，同一左侧行的多个匹配按右侧行的顺序排列；
+    ///   右连接和全连接中右侧未匹配的行追加在最后
+    /// - 半连接和反连接的结果保留左侧行的子数据集，其他连接的结果不包含子数据集
+    pub fn join(&self, right: &RowDataSet, spec: &JoinSpec) -> Result<RowDataSet, DataSetError> {
+        if spec.keys.is_empty() {
+            return Err(DataSetError::InvalidJoin("at least one key column pair is required".to_string()));
+        }
+
+        let mut left_keys = Vec::with_capacity(spec.keys.len());
+        let mut right_keys = Vec::with_capacity(spec.keys.len());
+        for (left_name, right_name) in &spec.keys {
+            let left_info = self.get_column_info(left_name).ok_or(DataSetError::ColumnNotFound)?;
+            let right_info = right.get_column_info(right_name).ok_or(DataSetError::ColumnNotFound)?;
+            let mode = KeyMode::for_pair(left_info.column_type, right_info.column_type);
+            left_keys.push((left_info.index, mode));
+            right_keys.push((right_info.index, mode));
+        }
+
+        let mut result = RowDataSet::new(self.dataset_id.clone());
+        for (name, info) in ordered_columns(self) {
+            result.add_column(name.clone(), info.column_type)?;
+        }
+        let filter_only = matches!(spec.join_type, JoinType::Semi | JoinType::Anti);
+        if !filter_only {
+            let prefix = spec.right_prefix.clone().unwrap_or_else(|| format!("{}_", right.dataset_id));
+            for (name, info) in ordered_columns(right) {
+                let output = match spec.aliases.get(name) {
+                    Some(alias) => alias.clone(),
+                    None if result.schema.contains_key(name) => format!("{}{}", prefix, name),
+                    None => name.clone(),
+                };
+                if result.schema.contains_key(&output) {
+                    return Err(DataSetError::DuplicateColumn(output));
+                }
+                result.add_column(output, info.column_type)?;
+            }
+        }
+
+        // 以右侧建立哈希表
+        let mut table: HashMap<String, Vec<usize>> = HashMap::with_capacity(right.rows.len());
+        for (index, row) in right.rows.iter().enumerate() {
+            if let Some(key) = build_key(row.values(), &right_keys) {
+                table.entry(key).or_default().push(index);
+            }
+        }
+
+        let left_width = self.column_count();
+        let right_width = right.column_count();
+        let combine = |left: Option<&RowData>, right: Option<&RowData>| {
+            let mut values = Vec::with_capacity(left_width + right_width);
+            match left {
+                Some(row) => values.extend(row.values().iter().cloned()),
+                None => values.resize(left_width, CellValue::Null),
+            }
+            match right {
+                Some(row) => values.extend(row.values().iter().cloned()),
+                None => values.resize(left_width + right_width, CellValue::Null),
+            }
+            RowData::new(values)
+        };
+
+        let mut right_matched = vec![false; right.rows.len()];
+        for row in &self.rows {
+            let matches = build_key(row.values(), &left_keys).and_then(|key| table.get(&key));
+            match (spec.join_type, matches) {
+                (JoinType::Semi, Some(_)) | (JoinType::Anti, None) => result.rows.push(row.clone()),
+                (JoinType::Semi | JoinType::Anti, _) => {}
+                (_, Some(indices)) => {
+                    for &index in indices {
+                        right_matched[index] = true;
+                        result.rows.push(combine(Some(row), Some(&right.rows[index])));
+                    }
+                }
+                (JoinType::Left | JoinType::Full, None) => result.rows.push(combine(Some(row), None)),
+                (_, None) => {}
+            }
+        }
+
+        if matches!(spec.join_type, JoinType::Right | JoinType::Full) {
+            for (row, _) in right.rows.iter().zip(&right_matched).filter(|(_, matched)| !**matched) {
+                result.rows.push(combine(None, Some(row)));
+            }
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn create_facts() -> RowDataSet {
+        let mut dataset = RowDataSet::new("facts".to_string());
+        dataset.add_column("DW".to_string(), ColumnType::String).unwrap();
+        dataset.add_column("ND".to_string(), ColumnType::I32).unwrap();
+        dataset.add_column("MC".to_string(), ColumnType::String).unwrap();
+        for (dw, nd, mc) in [
+            (json!("01"), json!(2024), "a"),
+            (json!("02"), json!(2024), "b"),
+            (json!("01"), json!(2023), "c"),
+            (CellValue::Null, json!(2024), "d"),
+        ] {
+            dataset.add_row(vec![dw, nd, json!(mc)]).unwrap();
+        }
+        dataset
+    }
+
+    fn create_units() -> RowDataSet {
+        let mut dataset = RowDataSet::new("dw".to_string());
+        dataset.add_column("DW".to_string(), ColumnType::String).unwrap();
+        dataset.add_column("ND".to_string(), ColumnType::Decimal).unwrap();
+        dataset.add_column("MC".to_string(), ColumnType::String).unwrap();
+        for (dw, nd, mc) in [
+            (json!("01"), json!("2024.00"), "总部"),
+            (json!("03"), json!(2024), "分部"),
+            (json!("01"), json!(2024), "总部2"),
+            (CellValue::Null, json!(2024), "空"),
+        ] {
+            dataset.add_row(vec![dw, nd, json!(mc)]).unwrap();
+        }
+        dataset
+    }
+
+    fn column(dataset: &RowDataSet, name: &str) -> Vec<CellValue> {
+        dataset.get_column_values(name).unwrap().into_iter().cloned().collect()
+    }
+
+    #[test]
+    fn test_inner_join_multi_key() {
+        let spec = JoinSpec::new(JoinType::Inner).on("DW", "DW").on("ND", "ND").alias("MC", "DW_MC");
+        let joined = create_facts().join(&create_units(), &spec).unwrap();
+
+        assert_eq!(joined.column_count(), 6);
+        assert_eq!(column(&joined, "MC"), vec![json!("a"), json!("a")]);
+        assert_eq!(column(&joined, "DW_MC"), vec![json!("总部"), json!("总部2")]);
+        assert_eq!(joined.get_column_info("dw_ND").unwrap().column_type, ColumnType::Decimal);
+    }
+
+    #[test]
+    fn test_outer_joins() {
+        let facts = create_facts();
+        let units = create_units();
+        let spec = |join_type| JoinSpec::new(join_type).on("DW", "DW").right_prefix("R_");
+
+        let left = facts.join(&units, &spec(JoinType::Left)).unwrap();
+        assert_eq!(column(&left, "MC"), vec![json!("a"), json!("a"), json!("b"), json!("c"), json!("c"), json!("d")]);
+        assert_eq!(left.get_cell(2, "R_MC").unwrap(), &CellValue::Null);
+
+        let right = facts.join(&units, &spec(JoinType::Right)).unwrap();
+        assert_eq!(column(&right, "R_MC"), vec![
+            json!("总部"), json!("总部2"), json!("总部"), json!("总部2"), json!("分部"), json!("空"),
+        ]);
+        assert_eq!(right.get_cell(4, "MC").unwrap(), &CellValue::Null);
+
+        let full = facts.join(&units, &spec(JoinType::Full)).unwrap();
+        assert_eq!(full.row_count(), 8);
+    }
+
+    #[test]
+    fn test_semi_and_anti_joins() {
+        let facts = create_facts();
+        let units = create_units();
+
+        let semi = facts.join(&units, &JoinSpec::new(JoinType::Semi).on("DW", "DW")).unwrap();
+        assert_eq!(semi.column_count(), 3);
+        assert_eq!(column(&semi, "MC"), vec![json!("a"), json!("c")]);
+
+        let anti = facts.join(&units, &JoinSpec::new(JoinType::Anti).on("DW", "DW")).unwrap();
+        assert_eq!(column(&anti, "MC"), vec![json!("b"), json!("d")]);
+    }
+
+    #[test]
+    fn test_join_errors() {
+        let facts = create_facts();
+        let units = create_units();
+        assert!(matches!(facts.join(&units, &JoinSpec::new(JoinType::Inner)), Err(DataSetError::InvalidJoin(_))));
+        assert!(matches!(
+            facts.join(&units, &JoinSpec::new(JoinType::Inner).on("DW", "NOPE")),
+            Err(DataSetError::ColumnNotFound)
+        ));
+        assert!(matches!(
+            facts.join(&units, &JoinSpec::new(JoinType::Inner).on("DW", "DW").alias("ND", "MC")),
+            Err(DataSetError::DuplicateColumn(name)) if name == "MC"
+        ));
+    }
+}
@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use crate::{model::data::cell::CellValue, model::meta::fields::{SYS_INDEXS, SYS_KEYS}};
+use serde::{Deserialize, Serialize};
+
+use super::col::flag_value;
+
+/// 主索引列字段，按顺序对应 KEY_PINDEX1..16
+const PRIMARY_INDEX_FIELDS: [SYS_KEYS; 16] = [
+    SYS_KEYS::KEY_PINDEX1,
+    SYS_KEYS::KEY_PINDEX2,
+    SYS_KEYS::KEY_PINDEX3,
+    SYS_KEYS::KEY_PINDEX4,
+    SYS_KEYS::KEY_PINDEX5,
+    SYS_KEYS::KEY_PINDEX6,
+    SYS_KEYS::KEY_PINDEX7,
+    SYS_KEYS::KEY_PINDEX8,
+    SYS_KEYS::KEY_PINDEX9,
+    SYS_KEYS::KEY_PINDEX10,
+    SYS_KEYS::KEY_PINDEX11,
+    SYS_KEYS::KEY_PINDEX12,
+    SYS_KEYS::KEY_PINDEX13,
+    SYS_KEYS::KEY_PINDEX14,
+    SYS_KEYS::KEY_PINDEX15,
+    SYS_KEYS::KEY_PINDEX16,
+];
+
+/// 外键索引列字段，按顺序对应 KEY_FINDEX1..8
+const FOREIGN_INDEX_FIELDS: [SYS_KEYS; 8] = [
+    SYS_KEYS::KEY_FINDEX1,
+    SYS_KEYS::KEY_FINDEX2,
+    SYS_KEYS::KEY_FINDEX3,
+    SYS_KEYS::KEY_FINDEX4,
+    SYS_KEYS::KEY_FINDEX5,
+    SYS_KEYS::KEY_FINDEX6,
+    SYS_KEYS::KEY_FINDEX7,
+    SYS_KEYS::KEY_FINDEX8,
+];
+
+fn text_value(value: &CellValue) -> Option<String> {
+    match value {
+        CellValue::Null => None,
+        CellValue::String(s) => Some(s.trim().to_string()),
+        other => Some(other.to_string()),
+    }
+    .filter(|s| !s.is_empty())
+}
+
+/// 键定义，对应 SYS_KEYS 中的一行
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct KeyDef {
+    data: HashMap<SYS_KEYS, CellValue>,
+}
+
+impl KeyDef {
+    pub fn get(&self, field: &SYS_KEYS) -> Option<&CellValue> {
+        self.data.get(field)
+    }
+    pub fn set(&mut self, field: SYS_KEYS, value: CellValue) {
+        self.data.insert(field, value);
+    }
+    /// 所有已设置的属性
+    pub fn fields(&self) -> impl Iterator<Item = (&SYS_KEYS, &CellValue)> {
+        self.data.iter()
+    }
+    pub fn key_id(&self) -> String {
+        self.get(&SYS_KEYS::KEY_ID).and_then(text_value).unwrap_or_default()
+    }
+
+    /// 主键列（KEY_PINDEX1..16 中非空的列）
+    ///
+    /// 设置了 KEY_CNT 时只取前 KEY_CNT 个。
+    pub fn primary_columns(&self) -> Vec<String> {
+        let count = self
+            .get(&SYS_KEYS::KEY_CNT)
+            .and_then(|v| v.as_u64().or_else(|| v.as_str().and_then(|s| s.trim().parse().ok())))
+            .filter(|count| *count > 0)
+            .map_or(PRIMARY_INDEX_FIELDS.len(), |count| count as usize);
+        PRIMARY_INDEX_FIELDS
+            .iter()
+            .take(count)
+            .filter_map(|field| self.get(field).and_then(text_value))
+            .collect()
+    }
+
+    /// 外键索引列（KEY_FINDEX1..8 中非空的列）
+    pub fn foreign_columns(&self) -> Vec<String> {
+        FOREIGN_INDEX_FIELDS
+            .iter()
+            .filter_map(|field| self.get(field).and_then(text_value))
+            .collect()
+    }
+}
+
+/// 索引定义，对应 SYS_INDEXS 中的一行
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct IndexDef {
+    data: HashMap<SYS_INDEXS, CellValue>,
+}
+
+impl IndexDef {
+    pub fn get(&self, field: &SYS_INDEXS) -> Option<&CellValue> {
+        self.data.get(field)
+    }
+    pub fn set(&mut self, field: SYS_INDEXS, value: CellValue) {
+        self.data.insert(field, value);
+    }
+    /// 所有已设置的属性
+    pub fn fields(&self) -> impl Iterator<Item = (&SYS_INDEXS, &CellValue)> {
+        self.data.iter()
+    }
+    pub fn inx_id(&self) -> String {
+        self.get(&SYS_INDEXS::INX_ID).and_then(text_value).unwrap_or_default()
+    }
+
+    /// 索引名称，未设置时使用索引 ID
+    pub fn inx_name(&self) -> String {
+        self.get(&SYS_INDEXS::INX_NAME).and_then(text_value).unwrap_or_else(|| self.inx_id())
+    }
+
+    /// 索引列（INX_COLS），以逗号或分号分隔，列名后的 `ASC`/`DESC` 会被去掉
+    pub fn columns(&self) -> Vec<String> {
+        self.get(&SYS_INDEXS::INX_COLS)
+            .and_then(text_value)
+            .map(|cols| {
+                cols.split([',', ';'])
+                    .filter_map(|col| col.split_whitespace().next())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// 是否唯一索引（INX_TYPE 为 `UNIQUE` 或 `U`）
+    pub fn is_unique(&self) -> bool {
+        self.inx_type().is_some_and(|t| t == "UNIQUE" || t == "U")
+    }
+
+    /// 是否哈希索引（INX_TYPE 为 `HASH`）
+    pub fn is_hash(&self) -> bool {
+        self.inx_type().is_some_and(|t| t == "HASH")
+    }
+
+    /// 是否聚集索引（INX_CLT）
+    pub fn is_clustered(&self) -> bool {
+        self.get(&SYS_INDEXS::INX_CLT).and_then(flag_value).unwrap_or(false)
+    }
+
+    fn inx_type(&self) -> Option<String> {
+        self.get(&SYS_INDEXS::INX_TYPE).and_then(text_value).map(|t| t.to_ascii_uppercase())
+    }
+}
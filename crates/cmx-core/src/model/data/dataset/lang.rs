@@ -0,0 +1,549 @@
+//! # 多语言模块
+//!
+//! 为多语言列（表的 `OBJ_LANG` 为 `1` 且列的 `COL_LANG` 为真）保存每种语言的值，并按
+//! `SVRContext` 中请求的语言读取。
+//!
+//! ## 存储
+//!
+//! 多语言列的单元格保存为以语言标识为键的 JSON 对象，如 `{"zh-CN": "现金", "en-US": "Cash"}`；
+//! 普通字符串视为默认语言（[`DEFAULT_LOCALE`]）的值，因此已有数据无需转换。过滤、排序和索引
+//! 看到的是保存的对象，需要按某种语言处理时先调用 [`RowDataSet::localize`]。
+//!
+//! ## 回退顺序
+//!
+//! [`LocaleChain`] 依次尝试：请求的语言、同一语种的其他地区（`en-GB` → `en-US`）、默认语言，
+//! 最后取任意一个已有的值。`SVRContext::LANG` 可以是单个语言，也可以是逗号分隔的列表。
+//!
+//! ## 翻译包
+//!
+//! [`RowDataSet::export_translations`] 导出某种语言的翻译包，每一项包含行键、列名、默认语言的
+//! 原文和当前译文；填写译文后用 [`RowDataSet::import_translations`] 导回。
+//!
+//! ## 示例
+//!
+//! ```rust
+//! use cmx_core::model::data::context::SVRContext;
+//! use cmx_core::model::data::dataset::{rds::RowDataSet, ColumnType};
+//! use cmx_core::model::data::dataset::lang::LocaleChain;
+//! use serde_json::json;
+//!
+//! let mut accounts = RowDataSet::new("accounts".to_string());
+//! accounts.add_column("KMDM".to_string(), ColumnType::String).unwrap();
+//! accounts.add_column("KMMC".to_string(), ColumnType::String).unwrap();
+//! accounts.set_multilingual("KMMC", true).unwrap();
+//! accounts.add_row(vec![json!("1001"), json!("库存现金")]).unwrap();
+//! accounts.set_text(0, "KMMC", "en-US", "Cash").unwrap();
+//!
+//! let ctx = SVRContext::new();
+//! ctx.set(SVRContext::LANG, json!("en-GB"));
+//! let chain = LocaleChain::from_context(&ctx);
+//! assert_eq!(accounts.get_text(0, "KMMC", &chain).unwrap(), Some("Cash"));
+//! assert_eq!(accounts.get_text(0, "KMMC", &LocaleChain::new("ja-JP")).unwrap(), Some("库存现金"));
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use super::rds::RowDataSet;
+use super::{ColumnType, DataSetError, TableSchema};
+use crate::model::data::cell::CellValue;
+use crate::model::data::context::SVRContext;
+
+/// 默认语言，普通字符串值属于这种语言
+pub const DEFAULT_LOCALE: &str = "zh-CN";
+
+/// 语种部分，如 `en-US` 的 `en`
+fn language(locale: &str) -> &str {
+    locale.split(['-', '_']).next().unwrap_or(locale)
+}
+
+/// 一个多语言单元格的各语言值
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct LocalizedText(BTreeMap<String, String>);
+
+impl LocalizedText {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 从单元格读取：对象按语言拆分，字符串视为默认语言的值，`NULL` 为空
+    ///
+    /// 其他值或对象中有非字符串的值时返回 `None`。
+    pub fn from_cell(value: &CellValue) -> Option<Self> {
+        match value {
+            CellValue::Null => Some(Self::new()),
+            CellValue::String(text) => Some(Self(BTreeMap::from([(DEFAULT_LOCALE.to_string(), text.clone())]))),
+            CellValue::Object(map) => map
+                .iter()
+                .map(|(locale, text)| text.as_str().map(|text| (locale.clone(), text.to_string())))
+                .collect::<Option<_>>()
+                .map(Self),
+            _ => None,
+        }
+    }
+
+    /// 转换为单元格：没有值时为 `NULL`，只有默认语言时为字符串，否则为对象
+    pub fn to_cell(&self) -> CellValue {
+        match self.0.len() {
+            0 => CellValue::Null,
+            1 if self.0.contains_key(DEFAULT_LOCALE) => CellValue::String(self.0[DEFAULT_LOCALE].clone()),
+            _ => CellValue::Object(self.0.iter().map(|(k, v)| (k.clone(), CellValue::String(v.clone()))).collect()),
+        }
+    }
+
+    /// 指定语言的值，不回退
+    pub fn get(&self, locale: &str) -> Option<&str> {
+        self.0.get(locale).map(String::as_str)
+    }
+
+    pub fn set(&mut self, locale: &str, text: impl Into<String>) {
+        self.0.insert(locale.to_string(), text.into());
+    }
+
+    pub fn remove(&mut self, locale: &str) -> Option<String> {
+        self.0.remove(locale)
+    }
+
+    /// 已有值的语言
+    pub fn locales(&self) -> Vec<&str> {
+        self.0.keys().map(String::as_str).collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// 按回退顺序取值
+    pub fn resolve(&self, chain: &LocaleChain) -> Option<&str> {
+        let entries: Vec<(&str, &str)> = self.0.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+        resolve_entries(&entries, chain)
+    }
+}
+
+/// 在 `(语言, 文本)` 列表中按回退顺序取值，都没有时取第一个
+fn resolve_entries<'a>(entries: &[(&str, &'a str)], chain: &LocaleChain) -> Option<&'a str> {
+    for locale in &chain.locales {
+        if let Some((_, text)) = entries.iter().find(|(key, _)| key.eq_ignore_ascii_case(locale)) {
+            return Some(text);
+        }
+        let wanted = language(locale);
+        if let Some((_, text)) = entries.iter().find(|(key, _)| language(key).eq_ignore_ascii_case(wanted)) {
+            return Some(text);
+        }
+    }
+    entries.first().map(|(_, text)| *text)
+}
+
+/// 读取多语言值时的语言回退顺序
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocaleChain {
+    locales: Vec<String>,
+}
+
+impl LocaleChain {
+    /// 先取 `locale`，再回退到默认语言
+    pub fn new(locale: &str) -> Self {
+        Self::with_fallbacks([locale])
+    }
+
+    /// 按给定顺序尝试，最后回退到默认语言
+    pub fn with_fallbacks<'a>(locales: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut chain: Vec<String> = Vec::new();
+        for locale in locales.into_iter().map(str::trim).filter(|l| !l.is_empty()).chain([DEFAULT_LOCALE]) {
+            if !chain.iter().any(|l| l.eq_ignore_ascii_case(locale)) {
+                chain.push(locale.to_string());
+            }
+        }
+        Self { locales: chain }
+    }
+
+    /// 按 `SVRContext::LANG` 构建，值可以是逗号分隔的列表；未设置时只使用默认语言
+    pub fn from_context(context: &SVRContext) -> Self {
+        match context.lang() {
+            Some(CellValue::String(langs)) => Self::with_fallbacks(langs.split(',')),
+            _ => Self::with_fallbacks([]),
+        }
+    }
+
+    /// 请求的语言，即回退顺序中的第一个
+    pub fn locale(&self) -> &str {
+        &self.locales[0]
+    }
+
+    pub fn locales(&self) -> &[String] {
+        &self.locales
+    }
+}
+
+impl Default for LocaleChain {
+    fn default() -> Self {
+        Self::with_fallbacks([])
+    }
+}
+
+/// 按列类型校验多语言单元格：对象中的每个值都必须能转换为列类型的字符串
+pub(crate) fn coerce_localized(column_type: ColumnType, value: CellValue) -> Result<CellValue, CellValue> {
+    let CellValue::Object(map) = value else {
+        return column_type.coerce(value);
+    };
+    let mut result = serde_json::Map::with_capacity(map.len());
+    for (locale, text) in &map {
+        match column_type.coerce(text.clone()) {
+            Ok(text @ CellValue::String(_)) => {
+                result.insert(locale.clone(), text);
+            }
+            _ => return Err(CellValue::Object(map)),
+        }
+    }
+    Ok(CellValue::Object(result))
+}
+
+/// 翻译包中的一项
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TranslationEntry {
+    /// 行键，键列的值以 `|` 连接
+    pub key: String,
+    pub column: String,
+    /// 默认语言的原文
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// 译文，为空时导入会跳过
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
+/// 某种语言的翻译包
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TranslationBundle {
+    pub dataset_id: String,
+    pub locale: String,
+    pub entries: Vec<TranslationEntry>,
+}
+
+impl TranslationBundle {
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+/// 导入翻译包的结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TranslationReport {
+    /// 写入的译文数量
+    pub applied: usize,
+    /// 数据集中找不到的行键
+    pub unmatched: Vec<String>,
+}
+
+fn key_text(value: &CellValue) -> String {
+    match value {
+        CellValue::Null => String::new(),
+        CellValue::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+impl TableSchema {
+    /// 多语言列：表支持多语言（`OBJ_LANG`）时 `COL_LANG` 为真的列
+    pub fn multilingual_columns(&self) -> Vec<String> {
+        if !self.is_multilingual() {
+            return Vec::new();
+        }
+        self.columns.iter().filter(|column| column.is_multilingual()).map(|column| column.col_id()).collect()
+    }
+}
+
+impl RowDataSet {
+    /// 设置列是否为多语言列
+    ///
+    /// 多语言列接受以语言为键的对象；取消时已有的对象值保持不变。
+    ///
+    /// # 返回值
+    ///
+    /// - `Err(DataSetError::ColumnNotFound)` - 列不存在
+    /// - `Err(DataSetError::NotMultilingual)` - 列不是字符串类型
+    pub fn set_multilingual(&mut self, column: &str, multilingual: bool) -> Result<(), DataSetError> {
+        let info = self.schema.get_mut(column).ok_or(DataSetError::ColumnNotFound)?;
+        if multilingual && info.column_type != ColumnType::String {
+            return Err(DataSetError::NotMultilingual(column.to_string()));
+        }
+        info.multilingual = multilingual;
+        Ok(())
+    }
+
+    /// 按表定义标记多语言列，见 [`TableSchema::multilingual_columns`]，数据集中没有的列被忽略
+    pub fn set_multilingual_from_schema(&mut self, schema: &TableSchema) -> Result<(), DataSetError> {
+        for column in schema.multilingual_columns() {
+            if self.schema.contains_key(&column) {
+                self.set_multilingual(&column, true)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 多语言列名，按列顺序
+    pub fn multilingual_columns(&self) -> Vec<&str> {
+        let mut columns: Vec<(&String, usize)> =
+            self.schema.iter().filter(|(_, info)| info.multilingual).map(|(name, info)| (name, info.index)).collect();
+        columns.sort_by_key(|(_, index)| *index);
+        columns.into_iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    /// 按回退顺序读取单元格的文本，普通字符串列直接返回字符串值
+    pub fn get_text(&self, row_index: usize, column: &str, chain: &LocaleChain) -> Result<Option<&str>, DataSetError> {
+        let value = self.get_cell(row_index, column)?;
+        Ok(match value {
+            CellValue::String(text) => Some(text.as_str()),
+            CellValue::Object(map) => {
+                let entries: Vec<(&str, &str)> = map
+                    .iter()
+                    .map(|(locale, text)| text.as_str().map(|text| (locale.as_str(), text)))
+                    .collect::<Option<_>>()
+                    .ok_or_else(|| DataSetError::NotMultilingual(column.to_string()))?;
+                resolve_entries(&entries, chain)
+            }
+            _ => None,
+        })
+    }
+
+    /// 读取多语言单元格的所有语言值
+    pub fn get_localized(&self, row_index: usize, column: &str) -> Result<LocalizedText, DataSetError> {
+        LocalizedText::from_cell(self.get_cell(row_index, column)?).ok_or_else(|| DataSetError::NotMultilingual(column.to_string()))
+    }
+
+    /// 设置多语言单元格某种语言的值，其他语言保持不变
+    ///
+    /// # 返回值
+    ///
+    /// - `Err(DataSetError::NotMultilingual)` - 列不是多语言列
+    pub fn set_text(&mut self, row_index: usize, column: &str, locale: &str, text: &str) -> Result<(), DataSetError> {
+        let mut localized = self.localized_cell(row_index, column)?;
+        localized.set(locale, text);
+        self.set_cell(row_index, column, localized.to_cell())
+    }
+
+    /// 按语言解析所有多语言列，返回普通字符串列的副本，用于展示和导出
+    ///
+    /// 计算列、索引、校验器和行工厂不会复制到结果中。
+    pub fn localize(&self, chain: &LocaleChain) -> RowDataSet {
+        let mut result = RowDataSet::new(self.dataset_id.clone());
+        result.schema = self.schema.clone();
+        let columns: Vec<usize> = result
+            .schema
+            .values_mut()
+            .filter(|info| info.multilingual)
+            .map(|info| {
+                info.multilingual = false;
+                info.index
+            })
+            .collect();
+        result.rows = self.rows.clone();
+        for row in &mut result.rows {
+            for index in &columns {
+                let value = &mut row.values_mut()[*index];
+                if let Some(text) = LocalizedText::from_cell(value) {
+                    *value = text.resolve(chain).map_or(CellValue::Null, |t| CellValue::String(t.to_string()));
+                }
+            }
+        }
+        result
+    }
+
+    /// 导出某种语言的翻译包，包含所有多语言列中有默认语言原文或已有译文的单元格
+    ///
+    /// # 参数
+    ///
+    /// * `locale` - 目标语言
+    /// * `key_columns` - 行键列，通常为主键列
+    pub fn export_translations(&self, locale: &str, key_columns: &[&str]) -> Result<TranslationBundle, DataSetError> {
+        let keys = self.key_indexes(key_columns)?;
+        let columns = self.multilingual_columns();
+        let mut entries = Vec::new();
+        for row in 0..self.rows.len() {
+            let key = self.row_key(row, &keys);
+            for column in &columns {
+                let text = self.get_localized(row, column)?;
+                let source = text.get(DEFAULT_LOCALE).map(str::to_string);
+                let translated = text.get(locale).map(str::to_string);
+                if source.is_some() || translated.is_some() {
+                    entries.push(TranslationEntry { key: key.clone(), column: column.to_string(), source, text: translated });
+                }
+            }
+        }
+        Ok(TranslationBundle { dataset_id: self.dataset_id.clone(), locale: locale.to_string(), entries })
+    }
+
+    /// 导入翻译包，把非空译文写入对应行的多语言列
+    ///
+    /// 行键按 `key_columns` 匹配，找不到的行记入 [`TranslationReport::unmatched`]。
+    ///
+    /// # 返回值
+    ///
+    /// - `Err(DataSetError::NotMultilingual)` - 翻译项的列不是多语言列
+    pub fn import_translations(&mut self, bundle: &TranslationBundle, key_columns: &[&str]) -> Result<TranslationReport, DataSetError> {
+        let keys = self.key_indexes(key_columns)?;
+        let rows: HashMap<String, usize> = (0..self.rows.len()).map(|row| (self.row_key(row, &keys), row)).collect();
+        let mut report = TranslationReport::default();
+        for entry in &bundle.entries {
+            let Some(text) = entry.text.as_deref().filter(|text| !text.is_empty()) else {
+                continue;
+            };
+            match rows.get(&entry.key) {
+                Some(row) => {
+                    self.set_text(*row, &entry.column, &bundle.locale, text)?;
+                    report.applied += 1;
+                }
+                None => {
+                    if !report.unmatched.contains(&entry.key) {
+                        report.unmatched.push(entry.key.clone());
+                    }
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    fn localized_cell(&self, row_index: usize, column: &str) -> Result<LocalizedText, DataSetError> {
+        let info = self.schema.get(column).ok_or(DataSetError::ColumnNotFound)?;
+        if !info.multilingual {
+            return Err(DataSetError::NotMultilingual(column.to_string()));
+        }
+        self.get_localized(row_index, column)
+    }
+
+    fn key_indexes(&self, key_columns: &[&str]) -> Result<Vec<usize>, DataSetError> {
+        key_columns
+            .iter()
+            .map(|column| self.schema.get(*column).map(|info| info.index).ok_or(DataSetError::ColumnNotFound))
+            .collect()
+    }
+
+    fn row_key(&self, row: usize, keys: &[usize]) -> String {
+        let values = self.rows[row].values();
+        keys.iter().map(|index| key_text(&values[*index])).collect::<Vec<_>>().join("|")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::model::data::dataset::col::ColumnDef;
+    use crate::model::data::dataset::TableSchemaBuilder;
+    use crate::model::meta::fields::{SYS_OBJCOLS, SYS_OBJECTS};
+
+    fn accounts() -> RowDataSet {
+        let mut dataset = RowDataSet::new("accounts".to_string());
+        dataset.add_column("KMDM".to_string(), ColumnType::String).unwrap();
+        dataset.add_column("KMMC".to_string(), ColumnType::String).unwrap();
+        dataset.add_column("JD".to_string(), ColumnType::I32).unwrap();
+        dataset.set_multilingual("KMMC", true).unwrap();
+        dataset.add_row(vec![json!("1001"), json!("库存现金"), json!(1)]).unwrap();
+        dataset
+            .add_row(vec![json!("1002"), json!({"zh-CN": "银行存款", "en-US": "Bank", "ja-JP": "銀行預金"}), json!(1)])
+            .unwrap();
+        dataset
+    }
+
+    #[test]
+    fn test_locale_chain() {
+        let chain = LocaleChain::with_fallbacks(["ja-JP", " en-US", "zh-cn"]);
+        assert_eq!(chain.locales(), ["ja-JP", "en-US", "zh-cn"]);
+        assert_eq!(LocaleChain::default().locale(), DEFAULT_LOCALE);
+
+        let ctx = SVRContext::new();
+        ctx.set(SVRContext::LANG, json!("fr-FR,en-US"));
+        assert_eq!(LocaleChain::from_context(&ctx).locales(), ["fr-FR", "en-US", "zh-CN"]);
+
+        let mut text = LocalizedText::new();
+        text.set("en-US", "Cash");
+        text.set("ja-JP", "現金");
+        assert_eq!(text.resolve(&LocaleChain::new("ja-JP")), Some("現金"));
+        assert_eq!(text.resolve(&LocaleChain::new("en-AU")), Some("Cash"));
+        assert_eq!(text.resolve(&LocaleChain::new("fr-FR")), Some("Cash"));
+        text.set("zh-CN", "现金");
+        assert_eq!(text.resolve(&LocaleChain::new("fr-FR")), Some("现金"));
+        assert_eq!(LocalizedText::from_cell(&text.to_cell()), Some(text));
+        assert_eq!(LocalizedText::from_cell(&json!("现金")).unwrap().to_cell(), json!("现金"));
+        assert_eq!(LocalizedText::from_cell(&json!({"en-US": 1})), None);
+    }
+
+    #[test]
+    fn test_multilingual_cells() {
+        let mut dataset = accounts();
+        let en = LocaleChain::new("en-US");
+        assert_eq!(dataset.get_text(0, "KMMC", &en).unwrap(), Some("库存现金"));
+        assert_eq!(dataset.get_text(1, "KMMC", &en).unwrap(), Some("Bank"));
+        assert_eq!(dataset.get_text(1, "KMDM", &en).unwrap(), Some("1002"));
+
+        dataset.set_text(0, "KMMC", "en-US", "Cash").unwrap();
+        assert_eq!(dataset.get_text(0, "KMMC", &en).unwrap(), Some("Cash"));
+        assert_eq!(dataset.get_localized(0, "KMMC").unwrap().locales(), vec!["en-US", "zh-CN"]);
+        assert!(matches!(dataset.set_text(0, "KMDM", "en-US", "x"), Err(DataSetError::NotMultilingual(_))));
+        assert!(matches!(dataset.set_multilingual("JD", true), Err(DataSetError::NotMultilingual(_))));
+        assert!(matches!(
+            dataset.set_cell(0, "KMMC", json!({"en-US": [1]})),
+            Err(DataSetError::TypeMismatch { .. })
+        ));
+        assert!(matches!(dataset.set_cell(0, "KMDM", json!({"en-US": "x"})), Err(DataSetError::TypeMismatch { .. })));
+
+        let localized = dataset.localize(&LocaleChain::new("ja-JP"));
+        assert_eq!(localized.get_cell(0, "KMMC").unwrap(), &json!("库存现金"));
+        assert_eq!(localized.get_cell(1, "KMMC").unwrap(), &json!("銀行預金"));
+        assert!(localized.multilingual_columns().is_empty());
+
+        let json = serde_json::to_string(&dataset).unwrap();
+        let restored: RowDataSet = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.multilingual_columns(), vec!["KMMC"]);
+    }
+
+    #[test]
+    fn test_translation_bundles() {
+        let mut dataset = accounts();
+        let bundle = dataset.export_translations("en-US", &["KMDM"]).unwrap();
+        assert_eq!(bundle.entries.len(), 2);
+        assert_eq!(bundle.entries[0].source.as_deref(), Some("库存现金"));
+        assert_eq!(bundle.entries[0].text, None);
+        assert_eq!(bundle.entries[1].text.as_deref(), Some("Bank"));
+
+        let mut bundle = TranslationBundle::from_json(&bundle.to_json().unwrap()).unwrap();
+        bundle.locale = "ja-JP".to_string();
+        bundle.entries[0].text = Some("現金".to_string());
+        bundle.entries[1].text = Some(String::new());
+        bundle.entries.push(TranslationEntry {
+            key: "9999".to_string(),
+            column: "KMMC".to_string(),
+            source: None,
+            text: Some("不明".to_string()),
+        });
+        let report = dataset.import_translations(&bundle, &["KMDM"]).unwrap();
+        assert_eq!(report, TranslationReport { applied: 1, unmatched: vec!["9999".to_string()] });
+        assert_eq!(dataset.get_text(0, "KMMC", &LocaleChain::new("ja-JP")).unwrap(), Some("現金"));
+        assert!(matches!(dataset.export_translations("en-US", &["XX"]), Err(DataSetError::ColumnNotFound)));
+    }
+
+    #[test]
+    fn test_multilingual_columns_from_schema() {
+        let column = |name: &str, lang: bool| {
+            let mut column = ColumnDef::default();
+            column.set(SYS_OBJCOLS::COL_ID, json!(name));
+            column.set(SYS_OBJCOLS::COL_LANG, json!(if lang { "1" } else { "0" }));
+            column
+        };
+        let mut schema = TableSchemaBuilder::new().with_columns(vec![column("KMDM", false), column("KMMC", true)]).build();
+        assert!(schema.multilingual_columns().is_empty());
+        schema.set(SYS_OBJECTS::OBJ_LANG, json!("1"));
+        assert_eq!(schema.multilingual_columns(), vec!["KMMC"]);
+
+        let mut dataset = RowDataSet::new("accounts".to_string());
+        dataset.add_column("KMMC".to_string(), ColumnType::String).unwrap();
+        dataset.set_multilingual_from_schema(&schema).unwrap();
+        assert_eq!(dataset.multilingual_columns(), vec!["KMMC"]);
+    }
+}
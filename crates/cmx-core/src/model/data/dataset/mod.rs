@@ -1,15 +1,30 @@
 pub mod rds;
+pub mod coerce;
+pub mod filter;
+pub mod aggregate;
+pub mod join;
+pub mod changes;
+pub mod formula;
+pub mod validate;
+pub mod factory;
+pub mod lang;
+pub mod archive;
+pub mod arrow;
+pub mod csv;
 // pub mod db;
 // pub mod seaorm;
 pub mod col;
+pub mod key;
+pub mod index;
 pub mod row;
-// pub mod cds;
+pub mod cds;
 // pub mod idme_metamodel;
 
 
 
 // use chrono::NaiveDateTime;
 use col::ColumnDef;
+use key::{IndexDef, KeyDef};
 use row::RowSet;
 // use rust_decimal::Decimal;
 use thiserror::Error;
@@ -20,13 +35,15 @@ use serde::{Serialize, Deserialize};
 use std::collections::HashMap;
 // use strum_macros::{EnumString, Display};
 
-use crate::model::meta::fields::SYS_OBJECTS;
+use crate::model::meta::fields::{SYS_OBJCOLS, SYS_OBJECTS};
 
 use super::cell::CellValue;
 
 
 
-#[derive(Debug, Serialize, Deserialize,Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+#[rkyv(derive(Debug, Clone, Copy, PartialEq, Eq))]
 pub enum ColumnType {
     Bool,
     I8,
@@ -41,6 +58,13 @@ pub enum ColumnType {
     F64,
     Decimal,
     String,
+    Date,               // 日期
+    Time,               // 时间
+    DateTime,           // 日期时间（不带时区）
+    DateTimeTz,         // 日期时间（带时区）
+    Uuid,               // UUID
+    Binary,             // 二进制（Base64 编码）
+    Json,               // 任意 JSON 值
 }
 impl Default for ColumnType {
     fn default() -> Self {
@@ -52,19 +76,38 @@ impl Default for ColumnType {
 pub struct TableSchema {
     data: HashMap<SYS_OBJECTS, CellValue>,
     pub columns: Vec<ColumnDef>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keys: Vec<KeyDef>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub indexes: Vec<IndexDef>,
     #[serde(skip)]
     column_indices: Vec<String>,
 }
 
 impl TableSchema {
     pub fn get_column_index(&self, column_name: &str) -> Option<usize> {
-        self.column_indices.iter().position(|name| name == column_name)
+        // 反序列化得到的 schema 没有列索引缓存，此时直接按列定义查找
+        if self.column_indices.len() == self.columns.len() {
+            self.column_indices.iter().position(|name| name == column_name)
+        } else {
+            self.columns.iter().position(|col| col.col_id() == column_name)
+        }
     }
 
     pub fn column_count(&self) -> usize {
         self.columns.len()
     }
 
+    /// 按列 ID 查找列定义
+    pub fn get_column(&self, column_name: &str) -> Option<&ColumnDef> {
+        self.get_column_index(column_name).and_then(|index| self.columns.get(index))
+    }
+
+    /// 主键列，取第一个键定义（SYS_KEYS）的 KEY_PINDEX 列
+    pub fn primary_key_columns(&self) -> Vec<String> {
+        self.keys.first().map(KeyDef::primary_columns).unwrap_or_default()
+    }
+
     // 添加便捷访问方法
     pub fn get(&self, field: &SYS_OBJECTS) -> Option<&CellValue> {
         self.data.get(field)
@@ -74,6 +117,11 @@ impl TableSchema {
         self.data.insert(field, value);
     }
 
+    /// 所有已设置的对象属性（SYS_OBJECTS）
+    pub fn fields(&self) -> impl Iterator<Item = (&SYS_OBJECTS, &CellValue)> {
+        self.data.iter()
+    }
+
     // 常用字段的专用访问器
     pub fn obj_id(&self) -> Option<&str> {
         self.get(&SYS_OBJECTS::OBJ_ID).and_then(|v| v.as_str())
@@ -101,6 +149,16 @@ impl TableSchema {
             .map_or(false, |s| s == "1") 
     }
     
+    /// 多单位对象的单位列：`COL_MUNIT` 为真的列，没有时取 `UNIT_ID` 列
+    pub fn unit_column(&self) -> Option<String> {
+        let flagged = self.columns.iter().find(|column| {
+            column.get(&SYS_OBJCOLS::COL_MUNIT).and_then(col::flag_value).unwrap_or(false)
+        });
+        flagged
+            .map(|column| column.col_id())
+            .or_else(|| self.get_column("UNIT_ID").map(|column| column.col_id()))
+    }
+
     /// 检查是否有自定义列
     pub fn has_custom_columns(&self) -> bool {
         self.get(&SYS_OBJECTS::OBJ_CSTCOL)
@@ -121,6 +179,8 @@ impl TableSchema {
 pub struct TableSchemaBuilder {
     data: HashMap<SYS_OBJECTS, CellValue>,
     columns: Vec<ColumnDef>,
+    keys: Vec<KeyDef>,
+    indexes: Vec<IndexDef>,
 }
 
 impl TableSchemaBuilder {
@@ -145,6 +205,16 @@ impl TableSchemaBuilder {
         self
     }
 
+    pub fn with_keys(mut self, keys: Vec<KeyDef>) -> Self {
+        self.keys = keys;
+        self
+    }
+
+    pub fn with_indexes(mut self, indexes: Vec<IndexDef>) -> Self {
+        self.indexes = indexes;
+        self
+    }
+
     pub fn build(self) -> TableSchema {
         let column_indices: Vec<String> = self.columns.iter()
             .map(|col| col.col_id())
@@ -153,6 +223,8 @@ impl TableSchemaBuilder {
         TableSchema {
             data: self.data,
             columns: self.columns,
+            keys: self.keys,
+            indexes: self.indexes,
             column_indices,
         }
     }
@@ -533,6 +605,31 @@ pub enum DataSetError {
     ColumnNotFound,
     #[error("Column count mismatch")]
     ColumnCountMismatch,
+    #[error("Type mismatch at row {row}, column '{column}': expected {expected:?}, got {value}")]
+    TypeMismatch {
+        row: usize,
+        column: String,
+        expected: ColumnType,
+        value: CellValue,
+    },
+    #[error("Invalid aggregate '{column}': {reason}")]
+    InvalidAggregate { column: String, reason: String },
+    #[error("Invalid join: {0}")]
+    InvalidJoin(String),
+    #[error("Duplicate column '{0}'")]
+    DuplicateColumn(String),
+    #[error("Invalid index '{index}': {reason}")]
+    InvalidIndex { index: String, reason: String },
+    #[error("Duplicate key {key} in unique index '{index}'")]
+    DuplicateKey { index: String, key: String },
+    #[error("Column '{0}' is calculated and cannot be set directly")]
+    CalculatedColumn(String),
+    #[error("Constraint violation: {}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join("; "))]
+    ConstraintViolation(Vec<validate::Violation>),
+    #[error("Column '{0}' is not multilingual")]
+    NotMultilingual(String),
+    #[error("Arrow error: {0}")]
+    Arrow(#[from] arrow_schema::ArrowError),
 }
 
 // // 添加测试
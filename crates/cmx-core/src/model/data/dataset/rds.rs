@@ -47,8 +47,15 @@
 //! ```
 
 use std::collections::HashMap;
+use std::sync::Arc;
 use serde::{Deserialize, Serialize};
 
+use super::changes::RowState;
+use super::formula::BoundFormulas;
+use super::validate::BoundValidator;
+use super::factory::BoundRowFactory;
+use super::lang::coerce_localized;
+use super::index::DataSetIndex;
 use super::{ColumnType, DataSetError};
 use crate::model::data::cell::CellValue;
 
@@ -67,6 +74,23 @@ pub struct ColumnInfo {
     ///
     /// 决定了该列可以存储的数据类型，以及相应的验证规则
     pub column_type: ColumnType,
+
+    /// 是否为多语言列，见 [`super::lang`]
+    ///
+    /// 多语言列除了普通值外，还接受以语言标识为键的对象。
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub multilingual: bool,
+}
+
+impl ColumnInfo {
+    /// 按列类型校验并转换单元格值，多语言列逐个校验各语言的值
+    pub fn coerce(&self, value: CellValue) -> Result<CellValue, CellValue> {
+        if self.multilingual {
+            coerce_localized(self.column_type, value)
+        } else {
+            self.column_type.coerce(value)
+        }
+    }
 }
 
 /// 行数据结构，包含值和子数据集
@@ -103,7 +127,8 @@ pub struct RowData {
     ///
     /// 按照数据集列定义的顺序存储每个列的值。数组长度必须与数据集的列数一致。
     /// 支持所有 `CellValue` 类型的值，包括字符串、数字、布尔值等。
-    pub values: Vec<CellValue>,
+    /// 数据集中的行只能通过 [`RowDataSet::set_cell`] 等方法修改，以便同步维护索引。
+    pub(crate) values: Vec<CellValue>,
 
     /// 子数据集映射：名称 -> 数据集
     ///
@@ -111,6 +136,18 @@ pub struct RowData {
     /// 每个子数据集都有唯一的名称标识，可以存储相关联的子数据。
     /// 这使得一行数据可以包含复杂的关系型数据结构。
     pub children: Option<HashMap<String, RowDataSet>>,
+
+    /// 行状态
+    ///
+    /// 仅在数据集开启变更跟踪时维护，见 [`RowDataSet::set_change_tracking`]。
+    #[serde(default, skip_serializing_if = "RowState::is_unchanged")]
+    pub state: RowState,
+
+    /// 行的原始值
+    ///
+    /// 开启变更跟踪后，行第一次被修改时保存修改前的值，接受或回滚变更后清空。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original: Option<Vec<CellValue>>,
 }
 
 impl RowData {
@@ -138,6 +175,8 @@ impl RowData {
         Self {
             values,
             children: None,
+            state: RowState::Unchanged,
+            original: None,
         }
     }
 
@@ -154,12 +193,13 @@ impl RowData {
 
     /// 获取行值的可变引用
     ///
-    /// 返回行中所有列值的可变向量引用，可以用于修改列数据。
+    /// 返回行中所有列值的可变向量引用，可以用于修改列数据。直接修改不会维护数据集的索引，
+    /// 修改后需要调用 [`RowDataSet::rebuild_indexes`]。
     ///
     /// # 返回值
     ///
     /// 返回包含所有列值的可变向量引用
-    pub fn values_mut(&mut self) -> &mut Vec<CellValue> {
+    pub(crate) fn values_mut(&mut self) -> &mut Vec<CellValue> {
         &mut self.values
     }
 
@@ -322,8 +362,43 @@ pub struct RowDataSet {
     /// 行数据存储数组
     ///
     /// 存储所有数据行的向量，每行包含列值和可选的子数据集。
-    /// 行数据按添加顺序存储，支持随机访问和修改。
-    pub rows: Vec<RowData>,
+    /// 行数据按添加顺序存储，支持随机访问。外部通过 [`RowDataSet::rows`] 读取，
+    /// 通过 `add_row`、`set_cell` 等方法修改，以便同步维护索引。
+    pub(crate) rows: Vec<RowData>,
+
+    /// 已删除行的墓碑列表
+    ///
+    /// 开启变更跟踪后，删除的非新增行以原始值保存在这里，用于生成变更集和回滚。
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deleted: Vec<RowData>,
+
+    /// 是否开启变更跟踪
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) tracking: bool,
+
+    /// 行索引，见 [`super::index`]
+    ///
+    /// 索引不参与序列化，反序列化后需要重新创建。
+    #[serde(skip)]
+    pub(crate) indexes: Vec<DataSetIndex>,
+
+    /// 计算列公式，见 [`super::formula`]
+    ///
+    /// 公式不参与序列化，反序列化后需要重新设置。
+    #[serde(skip)]
+    pub(crate) formulas: Option<Arc<BoundFormulas>>,
+
+    /// 列约束校验器，见 [`super::validate`]
+    ///
+    /// 校验器不参与序列化，反序列化后需要重新设置。
+    #[serde(skip)]
+    pub(crate) validator: Option<Arc<BoundValidator>>,
+
+    /// 行工厂，见 [`super::factory`]
+    ///
+    /// 行工厂不参与序列化，反序列化后需要重新设置。
+    #[serde(skip)]
+    pub(crate) factory: Option<Arc<BoundRowFactory>>,
 }
 
 impl RowDataSet {
@@ -354,6 +429,12 @@ impl RowDataSet {
             dataset_id,
             schema: HashMap::new(),
             rows: Vec::new(),
+            deleted: Vec::new(),
+            tracking: false,
+            indexes: Vec::new(),
+            formulas: None,
+            validator: None,
+            factory: None,
         }
     }
 
@@ -460,11 +541,14 @@ impl RowDataSet {
         }
 
         let index = self.schema.len();
-        self.schema.insert(name, ColumnInfo { index, column_type });
+        self.schema.insert(name, ColumnInfo { index, column_type, multilingual: false });
 
         // 为现有行添加 NULL 值
-        for row in &mut self.rows {
+        for row in self.rows.iter_mut().chain(self.deleted.iter_mut()) {
             row.values_mut().push(CellValue::Null);
+            if let Some(original) = row.original.as_mut() {
+                original.push(CellValue::Null);
+            }
         }
 
         Ok(())
@@ -515,12 +599,15 @@ impl RowDataSet {
     /// 返回 `Result<(), DataSetError>`：
     /// - `Ok(())` - 行添加成功
     /// - `Err(DataSetError::ColumnCountMismatch)` - 如果值的数量与列的数量不匹配
+    /// - `Err(DataSetError::TypeMismatch)` - 如果某个值无法转换为对应列的类型
+    /// - `Err(DataSetError::ConstraintViolation)` - 如果设置了校验器且该行违反列约束
     ///
     /// # 行为说明
     ///
     /// - 新行会被添加到数据集的末尾
     /// - 行索引从 0 开始，新行的索引等于之前的行数
-    /// - 所有列值都会被验证格式（但不验证类型）
+    /// - 所有列值都会按列类型校验并转换（见 [`ColumnType::coerce`]），
+    ///   例如 ISO 日期字符串会被规整为 `YYYY-MM-DD`
     ///
     /// # 示例
     ///
@@ -550,7 +637,14 @@ impl RowDataSet {
         if values.len() != self.schema.len() {
             return Err(DataSetError::ColumnCountMismatch);
         }
-        self.rows.push(RowData::new(values));
+        let mut values = self.coerce_values(self.rows.len(), values)?;
+        self.fill_defaults(self.rows.len(), &mut values)?;
+        self.apply_formulas(self.rows.len(), &mut values, None)?;
+        self.check_constraints(self.rows.len(), &values, None)?;
+        self.check_unique(&values, None)?;
+        let row = self.new_row(values);
+        self.rows.push(row);
+        self.index_inserted(self.rows.len() - 1);
         Ok(())
     }
 
@@ -570,10 +664,13 @@ impl RowDataSet {
     /// - `Ok(())` - 行插入成功
     /// - `Err(DataSetError::ColumnCountMismatch)` - 如果值的数量与列的数量不匹配
     /// - `Err(DataSetError::IndexOutOfBounds)` - 如果索引超出有效范围
+    /// - `Err(DataSetError::TypeMismatch)` - 如果某个值无法转换为对应列的类型
+    /// - `Err(DataSetError::ConstraintViolation)` - 如果设置了校验器且该行违反列约束
     ///
     /// # 行为说明
     ///
     /// - 插入位置可以等于当前行数，表示在末尾追加
+    /// - 列值的校验和转换规则与 `add_row` 相同
     /// - 所有后续行的索引会自动调整
     /// - 插入操作的时间复杂度为 O(n)，其中 n 为行数
     ///
@@ -606,7 +703,14 @@ impl RowDataSet {
         if index > self.rows.len() {
             return Err(DataSetError::IndexOutOfBounds);
         }
-        self.rows.insert(index, RowData::new(values));
+        let mut values = self.coerce_values(index, values)?;
+        self.fill_defaults(index, &mut values)?;
+        self.apply_formulas(index, &mut values, None)?;
+        self.check_constraints(index, &values, None)?;
+        self.check_unique(&values, None)?;
+        let row = self.new_row(values);
+        self.rows.insert(index, row);
+        self.index_inserted(index);
         Ok(())
     }
 
@@ -642,10 +746,15 @@ impl RowDataSet {
         self.rows.get(index).ok_or(DataSetError::IndexOutOfBounds)
     }
 
+    /// 所有行，按添加顺序排列
+    pub fn rows(&self) -> &[RowData] {
+        &self.rows
+    }
+
     /// 获取指定行的可变引用
     ///
     /// 根据行索引返回数据集中指定行的可变引用。
-    /// 可以用于修改行数据，包括列值和子数据集。
+    /// 可以用于修改行的子数据集，列值需要通过 [`RowDataSet::set_cell`] 修改。
     ///
     /// # 参数
     ///
@@ -667,8 +776,8 @@ impl RowDataSet {
     /// # dataset.add_row(vec![CellValue::String("Alice".to_string())]).unwrap();
     ///
     /// let row = dataset.get_row_mut(0).unwrap();
-    /// // 现在可以修改行中的值
-    /// row.values_mut()[0] = CellValue::String("Bob".to_string());
+    /// // 现在可以修改行的子数据集
+    /// assert!(row.get_child_mut("items").is_none());
     /// ```
     pub fn get_row_mut(&mut self, index: usize) -> Result<&mut RowData, DataSetError> {
         self.rows.get_mut(index).ok_or(DataSetError::IndexOutOfBounds)
@@ -714,13 +823,19 @@ impl RowDataSet {
     ///
     /// # 注意事项
     ///
-    /// - 删除操作不可逆，除非保存返回的行数据
+    /// - 删除操作不可逆，除非保存返回的行数据；开启变更跟踪时，
+    ///   非新增行会以原始值记入墓碑列表，可以通过 `reject_changes` 恢复
     /// - 频繁的删除操作会影响性能
     pub fn remove_row(&mut self, index: usize) -> Result<RowData, DataSetError> {
         if index >= self.rows.len() {
             return Err(DataSetError::IndexOutOfBounds);
         }
-        Ok(self.rows.remove(index))
+        let row = self.rows.remove(index);
+        self.index_removed(index, &row.values);
+        if self.tracking {
+            self.tombstone(&row);
+        }
+        Ok(row)
     }
 
     /// 获取指定单元格的值
@@ -789,12 +904,16 @@ impl RowDataSet {
     /// - `Ok(())` - 值设置成功
     /// - `Err(DataSetError::ColumnNotFound)` - 如果列名不存在
     /// - `Err(DataSetError::IndexOutOfBounds)` - 如果行索引超出范围
+    /// - `Err(DataSetError::TypeMismatch)` - 如果值无法转换为该列的类型
+    /// - `Err(DataSetError::CalculatedColumn)` - 如果该列是计算列
+    /// - `Err(DataSetError::ConstraintViolation)` - 如果设置了校验器且新值违反列约束
+    /// - `Err(DataSetError::DuplicateKey)` - 如果新值违反唯一索引
     ///
     /// # 行为说明
     ///
     /// - 原有的单元格值会被新值完全替换
-    /// - 不进行类型验证（依赖运行时检查）
-    /// - 支持设置任何有效的 `CellValue` 类型
+    /// - 新值会按列类型校验并转换，转换失败时原值保持不变
+    /// - 设置了计算列公式时，同一行的计算列会随之重新计算
     ///
     /// # 示例
     ///
@@ -822,14 +941,68 @@ impl RowDataSet {
             .ok_or(DataSetError::ColumnNotFound)?;
 
         let col_info_index = col_info.index;
-        let row = self.get_row_mut(row_index)?;
+        if self.formulas.as_ref().is_some_and(|formulas| formulas.is_calculated(col_info_index)) {
+            return Err(DataSetError::CalculatedColumn(column_name.to_string()));
+        }
+        let row = self.get_row(row_index)?;
         if col_info_index >= row.values().len() {
             return Err(DataSetError::IndexOutOfBounds);
         }
-        row.values_mut()[col_info_index] = value;
+        let value = col_info.coerce(value).map_err(|value| DataSetError::TypeMismatch {
+            row: row_index,
+            column: column_name.to_string(),
+            expected: col_info.column_type,
+            value,
+        })?;
+        let mut values = row.values().clone();
+        values[col_info_index] = value;
+        self.apply_formulas(row_index, &mut values, row.children.as_ref())?;
+        self.replace_values(row_index, values)
+    }
+
+    /// 用新的值替换一整行，维护变更跟踪和索引
+    ///
+    /// 值与原值相同时不做任何修改；设置了行工厂时刷新修改时间和修改人。
+    /// 违反列约束或唯一索引冲突时返回错误，行保持不变。
+    pub(crate) fn replace_values(&mut self, row_index: usize, mut values: Vec<CellValue>) -> Result<(), DataSetError> {
+        let row = self.get_row(row_index)?;
+        if row.values == values {
+            return Ok(());
+        }
+        self.touch_values(row_index, &mut values)?;
+        self.check_constraints(row_index, &values, row.children.as_ref())?;
+        self.check_unique(&values, Some(row_index))?;
+        let tracking = self.tracking;
+        let row = &mut self.rows[row_index];
+        if tracking {
+            row.mark_modified();
+        }
+        let old_values = std::mem::replace(&mut row.values, values);
+        self.index_updated(row_index, &old_values);
         Ok(())
     }
 
+    /// 按列类型校验并转换一整行的值
+    ///
+    /// `row_index` 仅用于错误信息，指出出错的行位置。
+    fn coerce_values(&self, row_index: usize, values: Vec<CellValue>) -> Result<Vec<CellValue>, DataSetError> {
+        let mut columns: Vec<(&String, &ColumnInfo)> = self.schema.iter().collect();
+        columns.sort_by_key(|(_, info)| info.index);
+
+        values
+            .into_iter()
+            .zip(columns)
+            .map(|(value, (name, info))| {
+                info.coerce(value).map_err(|value| DataSetError::TypeMismatch {
+                    row: row_index,
+                    column: name.clone(),
+                    expected: info.column_type,
+                    value,
+                })
+            })
+            .collect()
+    }
+
     /// 获取指定列的所有值
     ///
     /// 返回指定列中所有行的值的向量引用。
@@ -904,6 +1077,7 @@ impl RowDataSet {
     /// 返回 `Result<(), DataSetError>`：
     /// - `Ok(())` - 子数据集添加成功
     /// - `Err(DataSetError::IndexOutOfBounds)` - 如果行索引超出范围
+    /// - 按新的子数据集重算计算列失败（如违反约束）时返回相应错误，行保持原来的子数据集
     ///
     /// # 行为说明
     ///
@@ -938,9 +1112,22 @@ impl RowDataSet {
     /// - 主从表关系：订单和订单项
     /// - 树形结构：组织架构、文件系统
     /// - 复杂对象：包含多个子对象的实体
-    pub fn add_child_dataset(&mut self, row_index: usize, name: String, dataset: RowDataSet) -> Result<(), DataSetError> {
+    pub fn add_child_dataset(&mut self, row_index: usize, name: String, mut dataset: RowDataSet) -> Result<(), DataSetError> {
+        if self.tracking && !dataset.is_tracking_changes() {
+            dataset.set_change_tracking(true);
+        }
         let row = self.get_row_mut(row_index)?;
-        row.add_child(name, dataset);
+        let previous = row.children.as_mut().and_then(|children| children.remove(&name));
+        row.add_child(name.clone(), dataset);
+        if let Err(e) = self.recalculate_row(row_index) {
+            // 计算列或约束校验失败时恢复原来的子数据集
+            let row = &mut self.rows[row_index];
+            row.remove_child(&name);
+            if let Some(previous) = previous {
+                row.add_child(name, previous);
+            }
+            return Err(e);
+        }
         Ok(())
     }
 
@@ -997,6 +1184,7 @@ impl RowDataSet {
     /// - `Ok(Some(dataset))` - 成功移除并返回子数据集
     /// - `Ok(None)` - 行存在但没有指定的子数据集
     /// - `Err(DataSetError::IndexOutOfBounds)` - 如果行索引超出范围
+    /// - 移除后重算计算列失败（如违反约束）时返回相应错误，子数据集保留在行中
     ///
     /// # 行为说明
     ///
@@ -1025,7 +1213,15 @@ impl RowDataSet {
     /// ```
     pub fn remove_child_dataset(&mut self, row_index: usize, name: &str) -> Result<Option<RowDataSet>, DataSetError> {
         let row = self.get_row_mut(row_index)?;
-        Ok(row.remove_child(name))
+        let Some(removed) = row.remove_child(name) else {
+            return Ok(None);
+        };
+        if let Err(e) = self.recalculate_row(row_index) {
+            // 计算列或约束校验失败时放回子数据集
+            self.rows[row_index].add_child(name.to_string(), removed);
+            return Err(e);
+        }
+        Ok(Some(removed))
     }
 
     /// 清空数据集中的所有数据
@@ -1061,11 +1257,17 @@ impl RowDataSet {
     ///
     /// # 注意事项
     ///
-    /// - 这是一个破坏性操作，无法撤销
+    /// - 这是一个破坏性操作，无法撤销（开启变更跟踪时，被清空的行会记入墓碑列表）
     /// - 如果需要保留数据，建议先备份或导出
     /// - 列定义仍然有效，可以继续添加新数据
     pub fn clear(&mut self) {
+        if self.tracking {
+            for row in std::mem::take(&mut self.rows) {
+                self.tombstone(&row);
+            }
+        }
         self.rows.clear();
+        self.index_cleared();
     }
 }
 
@@ -1166,4 +1368,46 @@ mod tests {
         assert!(parent_dataset.get_child_dataset(0, "child1").unwrap().is_none());
         assert!(parent_dataset.get_child_dataset(0, "child2").unwrap().is_some());
     }
+
+    /// 测试写入时的类型校验与转换
+    ///
+    /// 验证 `add_row`、`insert_row`、`set_cell` 会把值规整为列类型的规范表示，
+    /// 并在类型不匹配时返回带有行号和列名的错误。
+    #[test]
+    fn test_typed_columns() {
+        let mut dataset = RowDataSet::new("vouchers".to_string());
+        dataset.add_column("F_DATE".to_string(), ColumnType::Date).unwrap();
+        dataset.add_column("F_JE".to_string(), ColumnType::Decimal).unwrap();
+        dataset.add_column("F_CRDATE".to_string(), ColumnType::DateTime).unwrap();
+
+        dataset.add_row(vec![
+            CellValue::String("2024/01/31".to_string()),
+            CellValue::String("1024.50".to_string()),
+            CellValue::String("2024-01-31 10:00:00".to_string()),
+        ]).unwrap();
+        assert_eq!(dataset.get_cell(0, "F_DATE").unwrap(), "2024-01-31");
+        assert_eq!(dataset.get_cell(0, "F_JE").unwrap(), &serde_json::json!(1024.5));
+        assert_eq!(dataset.get_cell(0, "F_CRDATE").unwrap(), "2024-01-31T10:00:00");
+
+        let err = dataset.insert_row(0, vec![
+            CellValue::String("not a date".to_string()),
+            CellValue::Null,
+            CellValue::Null,
+        ]).unwrap_err();
+        assert!(matches!(
+            err,
+            DataSetError::TypeMismatch { row: 0, ref column, expected: ColumnType::Date, .. } if column == "F_DATE"
+        ));
+        assert_eq!(dataset.row_count(), 1);
+
+        let err = dataset.set_cell(0, "F_JE", CellValue::Bool(true)).unwrap_err();
+        assert!(matches!(err, DataSetError::TypeMismatch { row: 0, .. }));
+        assert_eq!(dataset.get_cell(0, "F_JE").unwrap(), &serde_json::json!(1024.5));
+        // 行不存在时报告越界，而不是类型不匹配
+        let err = dataset.set_cell(5, "F_JE", CellValue::Bool(true)).unwrap_err();
+        assert!(matches!(err, DataSetError::IndexOutOfBounds));
+
+        dataset.set_cell(0, "F_DATE", CellValue::String("20240201".to_string())).unwrap();
+        assert_eq!(dataset.get_cell(0, "F_DATE").unwrap(), "2024-02-01");
+    }
 }
@@ -24,6 +24,14 @@ impl RowSet {
         }
     }
 
+    /// 用已有的列值创建行，不做校验
+    pub(crate) fn from_values(values: Vec<CellValue>) -> Self {
+        Self {
+            values,
+            children: None,
+        }
+    }
+
     // 子数据集操作
     pub fn add_child(&mut self, id: String, dataset: DataSet) {
         if self.children.is_none() {
@@ -87,6 +95,10 @@ impl RowSet {
         }
     }
 
+    pub fn values(&self) -> &[CellValue] {
+        &self.values
+    }
+
     pub fn get_value(&self, index: usize) -> Result<&CellValue, RowSetError> {
         self.values.get(index)
             .ok_or(RowSetError::IndexOutOfBounds)
@@ -0,0 +1,576 @@
+//! # 列约束校验模块
+//!
+//! 按 `SYS_OBJCOLS` 中的列约束校验数据集，返回所有违反约束的单元格：
+//!
+//! | 字段 | 规则 |
+//! |------|------|
+//! | `COL_ISNULL` | 为假时列值不能为 `NULL` |
+//! | `COL_LEN` | 字符串列的最大长度，按字符计 |
+//! | `COL_PREC`、`COL_SCALE` | 数值列的总位数和小数位数 |
+//! | `COL_REGEXREF` | 正则表达式，整个值必须匹配 |
+//! | `COL_CHECK`、`COL_COLJY` | 校验表达式，语法与计算列公式相同（见 [`super::formula`]），结果必须为布尔值 |
+//!
+//! 与 SQL 的 `CHECK` 约束一致，`NULL` 值只检查非空约束，校验表达式结果为 `NULL` 时视为通过。
+//!
+//! 给 `RowDataSet` 设置校验器后，`add_row`、`insert_row`、`set_cell` 等写入操作在写入前校验整行，
+//! 违反约束时返回 `DataSetError::ConstraintViolation`，数据集保持不变。
+//!
+//! ## 示例
+//!
+//! ```rust
+//! use cmx_core::model::data::dataset::{rds::RowDataSet, ColumnType, DataSetError, TableSchemaBuilder};
+//! use cmx_core::model::data::dataset::col::ColumnDef;
+//! use cmx_core::model::data::dataset::validate::{Rule, Validator};
+//! use cmx_core::model::meta::fields::SYS_OBJCOLS;
+//! use serde_json::json;
+//!
+//! let mut name = ColumnDef::default();
+//! name.set(SYS_OBJCOLS::COL_ID, json!("NAME"));
+//! name.set(SYS_OBJCOLS::COL_MC, json!("名称"));
+//! name.set(SYS_OBJCOLS::COL_ISNULL, json!(false));
+//! name.set(SYS_OBJCOLS::COL_LEN, json!(4));
+//! let schema = TableSchemaBuilder::new().with_columns(vec![name]).build();
+//!
+//! let mut dataset = RowDataSet::new("items".to_string());
+//! dataset.add_column("NAME".to_string(), ColumnType::String).unwrap();
+//! dataset.add_row(vec![json!("过长的名称")]).unwrap();
+//!
+//! let validator = Validator::new(&schema).unwrap();
+//! let violations = validator.validate(&dataset).unwrap();
+//! assert_eq!(violations[0].rule, Rule::Length { max: 4 });
+//! assert_eq!(violations[0].message, "名称长度不能超过4个字符");
+//!
+//! dataset.set_validator(&validator).unwrap();
+//! assert!(matches!(dataset.add_row(vec![json!(null)]), Err(DataSetError::ConstraintViolation(_))));
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use regex::Regex;
+use serde::Serialize;
+use thiserror::Error;
+
+use super::coerce::to_decimal;
+use super::formula::{BoundFormula, Formula, FormulaError, ValueKind};
+use super::rds::RowDataSet;
+use super::{ColumnType, DataSet, DataSetError, TableSchema};
+use crate::model::data::cell::CellValue;
+use crate::model::meta::fields::SYS_OBJCOLS;
+
+/// 约束规则
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Rule {
+    /// 不能为空（`COL_ISNULL` 为假）
+    NotNull,
+    /// 最大字符数（`COL_LEN`）
+    Length { max: u32 },
+    /// 数值精度（`COL_PREC`、`COL_SCALE`）
+    Precision { precision: u32, scale: u32 },
+    /// 正则表达式（`COL_REGEXREF`）
+    Pattern { pattern: String },
+    /// 校验表达式（`COL_CHECK`、`COL_COLJY`）
+    Check { expr: String },
+}
+
+impl Rule {
+    /// 按语言生成违反规则时的提示信息，`label` 为列的显示名称
+    pub fn message(&self, label: &str, locale: MessageLocale) -> String {
+        match (locale, self) {
+            (MessageLocale::ZhCn, Rule::NotNull) => format!("{label}不能为空"),
+            (MessageLocale::ZhCn, Rule::Length { max }) => format!("{label}长度不能超过{max}个字符"),
+            (MessageLocale::ZhCn, Rule::Precision { precision, scale }) => {
+                format!("{label}超出精度范围（{precision}位，{scale}位小数）")
+            }
+            (MessageLocale::ZhCn, Rule::Pattern { .. }) => format!("{label}格式不正确"),
+            (MessageLocale::ZhCn, Rule::Check { expr }) => format!("{label}不满足校验条件：{expr}"),
+            (MessageLocale::EnUs, Rule::NotNull) => format!("{label} is required"),
+            (MessageLocale::EnUs, Rule::Length { max }) => format!("{label} must not exceed {max} characters"),
+            (MessageLocale::EnUs, Rule::Precision { precision, scale }) => {
+                format!("{label} exceeds precision ({precision} digits, {scale} decimal places)")
+            }
+            (MessageLocale::EnUs, Rule::Pattern { .. }) => format!("{label} has an invalid format"),
+            (MessageLocale::EnUs, Rule::Check { expr }) => format!("{label} does not satisfy check: {expr}"),
+        }
+    }
+}
+
+/// 提示信息的语言
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub enum MessageLocale {
+    /// 简体中文，列名使用 `COL_MC`
+    #[default]
+    ZhCn,
+    /// 英文，列名使用 `COL_ID`
+    EnUs,
+}
+
+/// 一处约束违反
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Violation {
+    /// 行位置
+    pub row: usize,
+    /// 列名
+    pub column: String,
+    pub rule: Rule,
+    /// 违反约束的值
+    pub value: CellValue,
+    /// 本地化的提示信息
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "row {}, column '{}': {}", self.row, self.column, self.message)
+    }
+}
+
+/// 约束定义错误
+#[derive(Error, Debug)]
+pub enum ConstraintError {
+    #[error("Invalid pattern '{pattern}' on column '{column}': {message}")]
+    InvalidPattern { column: String, pattern: String, message: String },
+    #[error("Invalid check on column '{column}': {source}")]
+    InvalidCheck {
+        column: String,
+        #[source]
+        source: FormulaError,
+    },
+    #[error("Unknown column '{0}'")]
+    UnknownColumn(String),
+}
+
+#[derive(Debug, Clone)]
+enum Constraint<C> {
+    NotNull,
+    Length(u32),
+    Precision { precision: u32, scale: u32 },
+    Pattern { pattern: String, regex: Regex },
+    Check { expr: String, formula: C },
+}
+
+#[derive(Debug, Clone)]
+struct ColumnConstraints<C> {
+    column: String,
+    label: Option<String>,
+    /// 列位置，绑定前为表定义中的位置
+    index: usize,
+    constraints: Vec<Constraint<C>>,
+}
+
+/// 按表定义构建的列约束校验器
+#[derive(Debug, Clone)]
+pub struct Validator {
+    columns: Vec<ColumnConstraints<Formula>>,
+    /// 表定义中的列名，按列的位置排列
+    schema_columns: Vec<String>,
+    locale: MessageLocale,
+}
+
+impl Validator {
+    /// 读取表定义中每一列的约束
+    ///
+    /// 长度约束只用于字符串列，精度约束只用于 `COL_PREC` 大于零的数值列。
+    ///
+    /// # 返回值
+    ///
+    /// - `Err(ConstraintError::InvalidPattern)` - `COL_REGEXREF` 不是有效的正则表达式
+    /// - `Err(ConstraintError::InvalidCheck)` - 校验表达式无法解析、引用了不存在的列或结果不是布尔值
+    pub fn new(schema: &TableSchema) -> Result<Self, ConstraintError> {
+        let mut columns = Vec::new();
+        for (index, def) in schema.columns.iter().enumerate() {
+            let column = def.col_id();
+            let column_type = def.column_type();
+            let mut constraints = Vec::new();
+            if !def.is_nullable() {
+                constraints.push(Constraint::NotNull);
+            }
+            if let Some(max) = def.get_u32(&SYS_OBJCOLS::COL_LEN).filter(|max| *max > 0)
+                && column_type == ColumnType::String
+            {
+                constraints.push(Constraint::Length(max));
+            }
+            if let Some(precision) = def.get_u32(&SYS_OBJCOLS::COL_PREC).filter(|p| *p > 0)
+                && column_type.is_numeric()
+            {
+                let scale = def.get_u32(&SYS_OBJCOLS::COL_SCALE).unwrap_or(0).min(precision);
+                constraints.push(Constraint::Precision { precision, scale });
+            }
+            if let Some(pattern) = def.get_str(&SYS_OBJCOLS::COL_REGEXREF) {
+                let regex = Regex::new(&format!("^(?:{pattern})$")).map_err(|e| ConstraintError::InvalidPattern {
+                    column: column.clone(),
+                    pattern: pattern.clone(),
+                    message: e.to_string(),
+                })?;
+                constraints.push(Constraint::Pattern { pattern, regex });
+            }
+            for field in [SYS_OBJCOLS::COL_CHECK, SYS_OBJCOLS::COL_COLJY] {
+                if let Some(expr) = def.get_str(&field) {
+                    let invalid = |source| ConstraintError::InvalidCheck { column: column.clone(), source };
+                    let formula: Formula = expr.parse().map_err(invalid)?;
+                    check_kind(formula.check_schema(schema)).map_err(invalid)?;
+                    constraints.push(Constraint::Check { expr, formula });
+                }
+            }
+            if !constraints.is_empty() {
+                columns.push(ColumnConstraints { column, label: def.col_mc(), index, constraints });
+            }
+        }
+        let schema_columns = schema.columns.iter().map(|def| def.col_id()).collect();
+        Ok(Self { columns, schema_columns, locale: MessageLocale::default() })
+    }
+
+    /// 设置提示信息的语言
+    pub fn with_locale(mut self, locale: MessageLocale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    /// 有约束的列名
+    pub fn columns(&self) -> Vec<&str> {
+        self.columns.iter().map(|c| c.column.as_str()).collect()
+    }
+
+    /// 指定列的约束规则
+    pub fn rules(&self, column: &str) -> Vec<Rule> {
+        self.columns
+            .iter()
+            .filter(|c| c.column == column)
+            .flat_map(|c| c.constraints.iter().map(Constraint::rule))
+            .collect()
+    }
+
+    /// 校验 `RowDataSet` 的所有行，列按名称对应
+    ///
+    /// # 返回值
+    ///
+    /// - `Ok(violations)` - 所有违反约束的单元格，按行和列的顺序排列
+    /// - `Err(ConstraintError::UnknownColumn)` - 有约束的列或校验表达式引用的列不在数据集中
+    pub fn validate(&self, dataset: &RowDataSet) -> Result<Vec<Violation>, ConstraintError> {
+        let bound = self.bind(|name| dataset.schema.get(name).map(|info| info.index))?;
+        Ok(dataset
+            .rows
+            .iter()
+            .enumerate()
+            .flat_map(|(row, data)| bound.check(row, data.values(), data.children.as_ref()))
+            .collect())
+    }
+
+    /// 校验 `DataSet` 的所有行，列按表定义中的位置对应
+    ///
+    /// `DataSet` 的子数据集不参与校验表达式中的子数据集聚合。
+    pub fn validate_dataset(&self, dataset: &DataSet) -> Result<Vec<Violation>, ConstraintError> {
+        let bound = self.bind(|name| self.schema_columns.iter().position(|c| c == name))?;
+        let mut violations = Vec::new();
+        for row in 0..dataset.row_count() {
+            if let Ok(data) = dataset.get_row(row) {
+                violations.extend(bound.check(row, data.values(), None));
+            }
+        }
+        Ok(violations)
+    }
+
+    /// 将列名解析为列索引
+    pub(crate) fn bind<F>(&self, resolve: F) -> Result<BoundValidator, ConstraintError>
+    where
+        F: Fn(&str) -> Option<usize>,
+    {
+        let mut columns = Vec::with_capacity(self.columns.len());
+        for column in &self.columns {
+            let index = resolve(&column.column).ok_or_else(|| ConstraintError::UnknownColumn(column.column.clone()))?;
+            columns.push(bind_column(column, index, &resolve)?);
+        }
+        Ok(BoundValidator { columns, locale: self.locale })
+    }
+}
+
+fn check_kind(kind: Result<ValueKind, FormulaError>) -> Result<(), FormulaError> {
+    match kind? {
+        ValueKind::Bool | ValueKind::Any => Ok(()),
+        found => Err(FormulaError::TypeMismatch { context: "check".to_string(), expected: "Bool".to_string(), found }),
+    }
+}
+
+fn bind_column<F>(
+    column: &ColumnConstraints<Formula>,
+    index: usize,
+    resolve: F,
+) -> Result<ColumnConstraints<BoundFormula>, ConstraintError>
+where
+    F: Fn(&str) -> Option<usize>,
+{
+    let constraints = column
+        .constraints
+        .iter()
+        .map(|constraint| {
+            Ok(match constraint {
+                Constraint::NotNull => Constraint::NotNull,
+                Constraint::Length(max) => Constraint::Length(*max),
+                Constraint::Precision { precision, scale } => Constraint::Precision { precision: *precision, scale: *scale },
+                Constraint::Pattern { pattern, regex } => Constraint::Pattern { pattern: pattern.clone(), regex: regex.clone() },
+                Constraint::Check { expr, formula } => Constraint::Check {
+                    expr: expr.clone(),
+                    formula: formula.bind(&resolve).map_err(|source| ConstraintError::InvalidCheck {
+                        column: column.column.clone(),
+                        source,
+                    })?,
+                },
+            })
+        })
+        .collect::<Result<_, ConstraintError>>()?;
+    Ok(ColumnConstraints { column: column.column.clone(), label: column.label.clone(), index, constraints })
+}
+
+impl<C> Constraint<C> {
+    fn rule(&self) -> Rule {
+        match self {
+            Constraint::NotNull => Rule::NotNull,
+            Constraint::Length(max) => Rule::Length { max: *max },
+            Constraint::Precision { precision, scale } => Rule::Precision { precision: *precision, scale: *scale },
+            Constraint::Pattern { pattern, .. } => Rule::Pattern { pattern: pattern.clone() },
+            Constraint::Check { expr, .. } => Rule::Check { expr: expr.clone() },
+        }
+    }
+}
+
+impl Constraint<BoundFormula> {
+    fn holds(&self, value: &CellValue, values: &[CellValue], children: Option<&HashMap<String, RowDataSet>>) -> bool {
+        if value.is_null() {
+            return !matches!(self, Constraint::NotNull);
+        }
+        match self {
+            Constraint::NotNull => true,
+            Constraint::Length(max) => value.as_str().is_none_or(|s| s.chars().count() <= *max as usize),
+            Constraint::Precision { precision, scale } => to_decimal(value).is_none_or(|d| {
+                let integer = d.trunc().abs();
+                let integer_digits = if integer.is_zero() { 0 } else { integer.to_string().len() as u32 };
+                integer_digits <= precision - scale && d.normalize().scale() <= *scale
+            }),
+            Constraint::Pattern { regex, .. } => match value {
+                CellValue::String(s) => regex.is_match(s),
+                other => regex.is_match(&other.to_string()),
+            },
+            Constraint::Check { formula, .. } => !matches!(formula.eval(values, children), CellValue::Bool(false)),
+        }
+    }
+}
+
+/// 绑定到数据集列索引的校验器
+#[derive(Debug)]
+pub(crate) struct BoundValidator {
+    columns: Vec<ColumnConstraints<BoundFormula>>,
+    locale: MessageLocale,
+}
+
+impl BoundValidator {
+    /// 校验一行，返回所有违反约束的单元格
+    pub(crate) fn check(
+        &self,
+        row: usize,
+        values: &[CellValue],
+        children: Option<&HashMap<String, RowDataSet>>,
+    ) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        for column in &self.columns {
+            let value = values.get(column.index).unwrap_or(&CellValue::Null);
+            for constraint in &column.constraints {
+                if constraint.holds(value, values, children) {
+                    continue;
+                }
+                let rule = constraint.rule();
+                let label = match self.locale {
+                    MessageLocale::ZhCn => column.label.as_deref().unwrap_or(&column.column),
+                    MessageLocale::EnUs => &column.column,
+                };
+                let message = rule.message(label, self.locale);
+                violations.push(Violation { row, column: column.column.clone(), rule, value: value.clone(), message });
+            }
+        }
+        violations
+    }
+}
+
+impl RowDataSet {
+    /// 设置列约束校验器，之后的每次写入都会先校验整行
+    ///
+    /// 已有的行不会重新校验，需要时调用 [`Validator::validate`]。
+    ///
+    /// # 返回值
+    ///
+    /// - `Err(ConstraintError::UnknownColumn)` - 有约束的列或校验表达式引用的列不在数据集中
+    pub fn set_validator(&mut self, validator: &Validator) -> Result<(), ConstraintError> {
+        let bound = validator.bind(|name| self.schema.get(name).map(|info| info.index))?;
+        self.validator = Some(Arc::new(bound));
+        Ok(())
+    }
+
+    /// 移除列约束校验器
+    pub fn clear_validator(&mut self) {
+        self.validator = None;
+    }
+
+    pub(crate) fn check_constraints(
+        &self,
+        row_index: usize,
+        values: &[CellValue],
+        children: Option<&HashMap<String, RowDataSet>>,
+    ) -> Result<(), DataSetError> {
+        let Some(validator) = &self.validator else {
+            return Ok(());
+        };
+        let violations = validator.check(row_index, values, children);
+        if violations.is_empty() { Ok(()) } else { Err(DataSetError::ConstraintViolation(violations)) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::model::data::dataset::col::ColumnDef;
+    use crate::model::data::dataset::TableSchemaBuilder;
+
+    fn column(name: &str, column_type: &str, constraints: &[(SYS_OBJCOLS, CellValue)]) -> ColumnDef {
+        let mut column = ColumnDef::default();
+        column.set(SYS_OBJCOLS::COL_ID, json!(name));
+        column.set(SYS_OBJCOLS::COL_TYPE, json!(column_type));
+        for (field, value) in constraints {
+            column.set(field.clone(), value.clone());
+        }
+        column
+    }
+
+    fn schema() -> TableSchema {
+        TableSchemaBuilder::new()
+            .with_columns(vec![
+                column(
+                    "DM",
+                    "varchar(6)",
+                    &[
+                        (SYS_OBJCOLS::COL_MC, json!("代码")),
+                        (SYS_OBJCOLS::COL_ISNULL, json!("0")),
+                        (SYS_OBJCOLS::COL_LEN, json!("6")),
+                        (SYS_OBJCOLS::COL_REGEXREF, json!("[0-9]+")),
+                    ],
+                ),
+                column(
+                    "JE",
+                    "decimal",
+                    &[
+                        (SYS_OBJCOLS::COL_MC, json!("金额")),
+                        (SYS_OBJCOLS::COL_PREC, json!(5)),
+                        (SYS_OBJCOLS::COL_SCALE, json!(2)),
+                        (SYS_OBJCOLS::COL_CHECK, json!("JE >= 0")),
+                    ],
+                ),
+                column("SL", "int", &[(SYS_OBJCOLS::COL_COLJY, json!("ISNULL(JE) OR SL <= JE"))]),
+            ])
+            .build()
+    }
+
+    fn dataset() -> RowDataSet {
+        let mut dataset = RowDataSet::new("test".to_string());
+        dataset.add_column("DM".to_string(), ColumnType::String).unwrap();
+        dataset.add_column("JE".to_string(), ColumnType::Decimal).unwrap();
+        dataset.add_column("SL".to_string(), ColumnType::I32).unwrap();
+        dataset
+    }
+
+    #[test]
+    fn test_rules_from_schema() {
+        let mut schema = schema();
+        let validator = Validator::new(&schema).unwrap();
+        assert_eq!(validator.columns(), vec!["DM", "JE", "SL"]);
+        assert_eq!(
+            validator.rules("DM"),
+            vec![Rule::NotNull, Rule::Length { max: 6 }, Rule::Pattern { pattern: "[0-9]+".to_string() }]
+        );
+        assert_eq!(
+            validator.rules("JE"),
+            vec![Rule::Precision { precision: 5, scale: 2 }, Rule::Check { expr: "JE >= 0".to_string() }]
+        );
+
+        schema.columns[0].set(SYS_OBJCOLS::COL_REGEXREF, json!("[0-9"));
+        assert!(matches!(Validator::new(&schema), Err(ConstraintError::InvalidPattern { .. })));
+        schema.columns[0].set(SYS_OBJCOLS::COL_REGEXREF, json!("[0-9]+"));
+        schema.columns[1].set(SYS_OBJCOLS::COL_CHECK, json!("JE + 1"));
+        assert!(matches!(Validator::new(&schema), Err(ConstraintError::InvalidCheck { .. })));
+        schema.columns[1].set(SYS_OBJCOLS::COL_CHECK, json!("XX > 0"));
+        assert!(matches!(Validator::new(&schema), Err(ConstraintError::InvalidCheck { .. })));
+    }
+
+    #[test]
+    fn test_validate_reports_every_violation() {
+        let schema = schema();
+        let validator = Validator::new(&schema).unwrap();
+
+        let mut dataset = dataset();
+        dataset.add_row(vec![json!("001"), json!(123.45), json!(3)]).unwrap();
+        dataset.add_row(vec![json!(null), json!(null), json!(null)]).unwrap();
+        dataset.add_row(vec![json!("12345678"), json!(1234.5), json!(1)]).unwrap();
+        dataset.add_row(vec![json!("A1"), json!(-0.125), json!(2)]).unwrap();
+
+        let violations = validator.validate(&dataset).unwrap();
+        let found: Vec<(usize, &str, &Rule)> = violations.iter().map(|v| (v.row, v.column.as_str(), &v.rule)).collect();
+        assert_eq!(
+            found,
+            vec![
+                (1, "DM", &Rule::NotNull),
+                (2, "DM", &Rule::Length { max: 6 }),
+                (2, "JE", &Rule::Precision { precision: 5, scale: 2 }),
+                (3, "DM", &Rule::Pattern { pattern: "[0-9]+".to_string() }),
+                (3, "JE", &Rule::Precision { precision: 5, scale: 2 }),
+                (3, "JE", &Rule::Check { expr: "JE >= 0".to_string() }),
+                (3, "SL", &Rule::Check { expr: "ISNULL(JE) OR SL <= JE".to_string() }),
+            ]
+        );
+        assert_eq!(violations[0].message, "代码不能为空");
+        assert_eq!(violations[2].message, "金额超出精度范围（5位，2位小数）");
+        assert_eq!(violations[6].message, "SL不满足校验条件：ISNULL(JE) OR SL <= JE");
+
+        let english = validator.clone().with_locale(MessageLocale::EnUs).validate(&dataset).unwrap();
+        assert_eq!(english[0].message, "DM is required");
+        assert_eq!(english[1].message, "DM must not exceed 6 characters");
+
+        let mut rows = DataSet::new();
+        rows.add_row(&schema, vec![json!("12"), json!(-1), json!(null)]).unwrap();
+        let violations = validator.validate_dataset(&rows).unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule, Rule::Check { expr: "JE >= 0".to_string() });
+    }
+
+    #[test]
+    fn test_validator_on_write() {
+        let schema = schema();
+        let validator = Validator::new(&schema).unwrap();
+
+        let mut dataset = dataset();
+        dataset.add_row(vec![json!(null), json!(1), json!(1)]).unwrap();
+        dataset.set_validator(&validator).unwrap();
+
+        assert!(matches!(
+            dataset.add_row(vec![json!(null), json!(1), json!(1)]),
+            Err(DataSetError::ConstraintViolation(v)) if v.len() == 1 && v[0].row == 1
+        ));
+        assert!(matches!(
+            dataset.insert_row(0, vec![json!("1"), json!(1), json!(5)]),
+            Err(DataSetError::ConstraintViolation(_))
+        ));
+        dataset.add_row(vec![json!("1"), json!(10), json!(5)]).unwrap();
+        assert_eq!(dataset.row_count(), 2);
+
+        assert!(matches!(dataset.set_cell(1, "JE", json!(4)), Err(DataSetError::ConstraintViolation(_))));
+        assert_eq!(dataset.get_cell(1, "JE").unwrap(), &json!(10));
+        dataset.set_cell(1, "JE", json!(5)).unwrap();
+
+        dataset.clear_validator();
+        dataset.set_cell(1, "JE", json!(-1)).unwrap();
+
+        let mut missing = RowDataSet::new("missing".to_string());
+        missing.add_column("DM".to_string(), ColumnType::String).unwrap();
+        assert!(matches!(missing.set_validator(&validator), Err(ConstraintError::UnknownColumn(c)) if c == "JE"));
+    }
+}
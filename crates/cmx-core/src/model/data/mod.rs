@@ -56,6 +56,11 @@ impl KeyValue {
     pub fn exists(&self, key: &str) -> bool {
         self.attributes.contains_key(key)
     }
+
+    // 遍历所有键值对
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &CellValue)> {
+        self.attributes.iter()
+    }
 }
 
 
@@ -0,0 +1,840 @@
+//! # 元数据目录
+//!
+//! 从目录表（`SYS_OBJECTS`、`SYS_OBJCOLS`、`SYS_KEYS`、`SYS_INDEXS`、`SYS_DICTS`、`SYS_FACTS`、
+//! `SYS_MODEL`、`SYS_MDL_CTN`）的行构建完整关联的元数据：
+//!
+//! - 每个对象（`OBJ_ID`）构建一个 `TableSchema`，列按 `COL_DISP` 排序，附带键和索引定义
+//! - 每个字典（`DCT_ID`）构建一个 `DCTMeta`，`dct_metas` 按字典 ID 关联外键字典，来源为外键列
+//!   （`COL_ISFKEY`、`COL_FOBJ`）和 `DCT_FKEYDCT1..8`；被引用的字典先于引用方构建，
+//!   因此关联到的字典本身也已经关联好外键字典（循环引用处使用未关联的字典，并记录为问题）
+//! - 每个事实表（`FCT_ID`）构建一个 `FCTMeta`，`dct_metas` 按列 ID 关联外键字典
+//! - 每个模型（`MDL_ID`）构建一个 `DMEMeta`，关联关键指标字典（`MDL_KEYDCT`，可以是逗号分隔的列表）、
+//!   单位字典（`MDL_UNITDCT`）以及 `SYS_MDL_CTN` 中 `CTN_FCT1..16` 引用的事实表
+//!
+//! 目录行中不完整的部分不会中断构建，而是记录在 [`Catalog::issues`] 中，例如引用了不存在的对象或字典。
+//!
+//! 目录行通常由 cmx-infra 从 Postgres 读取，也可以从 JSON 构造，见 [`CatalogRows::push_json`]。
+//!
+//! ## 示例
+//!
+//! ```rust
+//! use cmx_core::model::meta::catalog::{Catalog, CatalogRows};
+//! use cmx_core::model::meta::registry::MetaRegistry;
+//! use cmx_core::model::meta::tables::SYS_TABLE_NAMES;
+//! use serde_json::json;
+//!
+//! let mut rows = CatalogRows::new();
+//! rows.push_json(SYS_TABLE_NAMES::SYS_OBJECTS, json!({"obj_id": "T_DEPT", "obj_mc": "部门"}));
+//! rows.push_json(SYS_TABLE_NAMES::SYS_OBJCOLS, json!({"obj_id": "T_DEPT", "col_id": "DM", "col_disp": 1}));
+//! rows.push_json(SYS_TABLE_NAMES::SYS_OBJCOLS, json!({"obj_id": "T_DEPT", "col_id": "MC", "col_disp": 2}));
+//! rows.push_json(SYS_TABLE_NAMES::SYS_DICTS, json!({"dct_id": "DCT_DEPT", "obj_id": "T_DEPT"}));
+//!
+//! let catalog = Catalog::build(&rows);
+//! assert!(catalog.issues.is_empty());
+//! let dept = &catalog.dct_metas["DCT_DEPT"];
+//! assert_eq!(dept.table_schema.get_column_index("MC"), Some(1));
+//!
+//! // 一次注册到注册表中的租户
+//! let registry = MetaRegistry::new();
+//! catalog.install(&registry, "tenant_a");
+//! assert!(registry.get_dct_meta("tenant_a", "DCT_DEPT").is_some());
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::hash::Hash;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::model::data::cell::CellValue;
+use crate::model::data::dataset::col::ColumnDef;
+use crate::model::data::dataset::key::{IndexDef, KeyDef};
+use crate::model::data::dataset::{TableSchema, TableSchemaBuilder};
+use crate::model::data::KeyValue;
+
+use super::dct::{DCTMeta, ForeignKeyError, ForeignKeyRef};
+use super::dme::DMEMeta;
+use super::fct::FCTMeta;
+use super::fields::{SYS_DICTS, SYS_FACTS, SYS_INDEXS, SYS_KEYS, SYS_MODEL, SYS_OBJCOLS, SYS_OBJECTS};
+use super::registry::{MetaCatalog, MetaRegistry};
+use super::tables::SYS_TABLE_NAMES;
+
+/// 目录行：列名（大写）-> 值
+pub type CatalogRecord = HashMap<String, CellValue>;
+
+/// 目录表中读取的行
+#[derive(Debug, Clone, Default)]
+pub struct CatalogRows {
+    tables: HashMap<SYS_TABLE_NAMES, Vec<CatalogRecord>>,
+}
+
+impl CatalogRows {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加一行，列名统一转为大写
+    pub fn push(&mut self, table: SYS_TABLE_NAMES, record: CatalogRecord) {
+        let record = record
+            .into_iter()
+            .map(|(column, value)| (column.to_ascii_uppercase(), value))
+            .collect();
+        self.tables.entry(table).or_default().push(record);
+    }
+
+    /// 添加一行 JSON 对象，非对象的值被忽略
+    ///
+    /// Postgres 的 `row_to_json` 返回的列名是小写的，这里统一转为大写。
+    pub fn push_json(&mut self, table: SYS_TABLE_NAMES, row: CellValue) {
+        if let CellValue::Object(map) = row {
+            self.push(table, map.into_iter().collect());
+        }
+    }
+
+    /// 某个目录表的所有行
+    pub fn rows(&self, table: &SYS_TABLE_NAMES) -> &[CatalogRecord] {
+        self.tables.get(table).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// 构建目录时发现的问题
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CatalogIssue {
+    /// 目录行缺少 ID 列，该行被忽略
+    MissingId { table: SYS_TABLE_NAMES, row: usize, field: String },
+    /// 字典或事实表引用的对象在 `SYS_OBJECTS` 中不存在
+    MissingObject { owner: String, obj_id: String },
+    /// 引用的字典不存在，`column` 为引用所在的列
+    MissingDictionary { owner: String, column: Option<String>, dct_id: String },
+    /// 模型引用的事实表不存在
+    MissingFact { model: String, fct_id: String },
+    /// 字典经外键字典又引用回自身，`path` 首尾是同一个字典；循环处使用未关联外键字典的版本
+    ReferenceCycle { path: Vec<String> },
+}
+
+impl fmt::Display for CatalogIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CatalogIssue::MissingId { table, row, field } => {
+                write!(f, "{} row {} has no {}", table, row, field)
+            }
+            CatalogIssue::MissingObject { owner, obj_id } => {
+                write!(f, "'{}' references unknown object '{}'", owner, obj_id)
+            }
+            CatalogIssue::MissingDictionary { owner, column: Some(column), dct_id } => {
+                write!(f, "column '{}' of '{}' references unknown dictionary '{}'", column, owner, dct_id)
+            }
+            CatalogIssue::MissingDictionary { owner, column: None, dct_id } => {
+                write!(f, "'{}' references unknown dictionary '{}'", owner, dct_id)
+            }
+            CatalogIssue::MissingFact { model, fct_id } => {
+                write!(f, "model '{}' references unknown fact '{}'", model, fct_id)
+            }
+            CatalogIssue::ReferenceCycle { path } => {
+                write!(f, "dictionaries reference each other: {}", path.join(" -> "))
+            }
+        }
+    }
+}
+
+/// 完整关联的元数据目录
+#[derive(Debug, Default)]
+pub struct Catalog {
+    /// 对象 ID -> 表定义
+    pub schemas: HashMap<String, TableSchema>,
+    /// 字典 ID -> 字典元数据
+    pub dct_metas: HashMap<String, Arc<DCTMeta>>,
+    /// 事实表 ID -> 事实表元数据
+    pub fct_metas: HashMap<String, Arc<FCTMeta>>,
+    /// 模型 ID -> 模型元数据
+    pub dme_metas: HashMap<String, Arc<DMEMeta>>,
+    /// 构建时发现的问题
+    pub issues: Vec<CatalogIssue>,
+}
+
+/// 文本值，空串视为未设置
+fn text(value: &CellValue) -> Option<String> {
+    match value {
+        CellValue::Null => None,
+        CellValue::String(s) => Some(s.trim().to_string()),
+        other => Some(other.to_string()),
+    }
+    .filter(|s| !s.is_empty())
+}
+
+fn record_text(record: &CatalogRecord, field: &str) -> Option<String> {
+    record.get(field).and_then(text)
+}
+
+/// 以逗号或分号分隔的 ID 列表
+fn id_list(value: Option<String>) -> Vec<String> {
+    value
+        .map(|s| {
+            s.split([',', ';'])
+                .map(str::trim)
+                .filter(|id| !id.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 按字段枚举取出目录行中的已知列，未知列被忽略
+fn fields<F: FromStr + Eq + Hash>(record: &CatalogRecord) -> impl Iterator<Item = (F, CellValue)> + '_ {
+    record
+        .iter()
+        .filter_map(|(column, value)| column.parse::<F>().ok().map(|field| (field, value.clone())))
+}
+
+const DICT_FOREIGN_FIELDS: [SYS_DICTS; 8] = [
+    SYS_DICTS::DCT_FKEYDCT1,
+    SYS_DICTS::DCT_FKEYDCT2,
+    SYS_DICTS::DCT_FKEYDCT3,
+    SYS_DICTS::DCT_FKEYDCT4,
+    SYS_DICTS::DCT_FKEYDCT5,
+    SYS_DICTS::DCT_FKEYDCT6,
+    SYS_DICTS::DCT_FKEYDCT7,
+    SYS_DICTS::DCT_FKEYDCT8,
+];
+
+const MODEL_FACT_FIELDS: [&str; 16] = [
+    "CTN_FCT1", "CTN_FCT2", "CTN_FCT3", "CTN_FCT4", "CTN_FCT5", "CTN_FCT6", "CTN_FCT7", "CTN_FCT8",
+    "CTN_FCT9", "CTN_FCT10", "CTN_FCT11", "CTN_FCT12", "CTN_FCT13", "CTN_FCT14", "CTN_FCT15", "CTN_FCT16",
+];
+
+/// 按 ID 分组目录行，缺少 ID 的行记录为问题
+fn group_by<'a>(
+    rows: &'a CatalogRows,
+    table: SYS_TABLE_NAMES,
+    field: &str,
+    issues: &mut Vec<CatalogIssue>,
+) -> HashMap<String, Vec<&'a CatalogRecord>> {
+    let mut groups: HashMap<String, Vec<&CatalogRecord>> = HashMap::new();
+    for (row, record) in rows.rows(&table).iter().enumerate() {
+        match record_text(record, field) {
+            Some(id) => groups.entry(id).or_default().push(record),
+            None => issues.push(CatalogIssue::MissingId { table: table.clone(), row, field: field.to_string() }),
+        }
+    }
+    groups
+}
+
+/// 字典构建状态，用于按依赖顺序关联外键字典
+struct DictLinker<'a> {
+    pending: HashMap<String, DCTMeta>,
+    linked: HashMap<String, Arc<DCTMeta>>,
+    /// 正在关联的字典，依次引用
+    visiting: Vec<String>,
+    issues: &'a mut Vec<CatalogIssue>,
+}
+
+impl DictLinker<'_> {
+    fn link(&mut self, dct_id: &str) -> Option<Arc<DCTMeta>> {
+        if let Some(meta) = self.linked.get(dct_id) {
+            return Some(meta.clone());
+        }
+        let unlinked = self.pending.get(dct_id)?;
+        if let Some(start) = self.visiting.iter().position(|id| id == dct_id) {
+            // 循环引用，使用未关联外键字典的版本；引用自身的外键列（树形字典）不算循环
+            if start + 1 < self.visiting.len() {
+                let mut path = self.visiting[start..].to_vec();
+                path.push(dct_id.to_string());
+                self.issues.push(CatalogIssue::ReferenceCycle { path });
+            }
+            let mut meta = unlinked.clone();
+            meta.dct_metas = None;
+            return Some(Arc::new(meta));
+        }
+
+        self.visiting.push(dct_id.to_string());
+        let mut dct_metas = HashMap::new();
+        for (column, target) in dict_dependencies(unlinked) {
+            match self.link(&target) {
+                Some(meta) => {
+                    dct_metas.insert(target, meta);
+                }
+                None => self.issues.push(CatalogIssue::MissingDictionary {
+                    owner: dct_id.to_string(),
+                    column,
+                    dct_id: target,
+                }),
+            }
+        }
+
+        let mut meta = self.pending.remove(dct_id)?;
+        meta.dct_metas = (!dct_metas.is_empty()).then_some(dct_metas);
+        let meta = Arc::new(meta);
+        self.visiting.pop();
+        self.linked.insert(dct_id.to_string(), meta.clone());
+        Some(meta)
+    }
+}
+
+impl Catalog {
+    /// 从目录行构建元数据目录
+    pub fn build(rows: &CatalogRows) -> Self {
+        let mut issues = Vec::new();
+        let schemas = Self::build_schemas(rows, &mut issues);
+        let dct_metas = Self::build_dicts(rows, &schemas, &mut issues);
+        let fct_metas = Self::build_facts(rows, &schemas, &dct_metas, &mut issues);
+        let dme_metas = Self::build_models(rows, &dct_metas, &fct_metas, &mut issues);
+        Self {
+            schemas,
+            dct_metas,
+            fct_metas,
+            dme_metas,
+            issues,
+        }
+    }
+
+    /// 把目录中的字典、事实表和模型一次注册到 `registry` 的租户 `tenant`
+    ///
+    /// 与租户中已有的元数据合并，同 ID 的以目录为准。
+    ///
+    /// # 返回值
+    /// 新注册的目录版本
+    pub fn install(&self, registry: &MetaRegistry, tenant: &str) -> Arc<MetaCatalog> {
+        let installed = MetaCatalog::from(self);
+        registry.update(tenant, |catalog| catalog.extend(installed.clone()))
+    }
+
+    /// 按依赖顺序关联一组字典的外键字典，被引用的字典先关联
+    ///
+    /// # 参数
+    /// - `dicts`: 需要关联的字典，原有的外键关联会被替换
+    /// - `linked`: 已关联好的字典，例如注册表中的字典，被引用时直接使用；同 ID 的以 `dicts` 为准
+    ///
+    /// # 返回值
+    /// - `Ok(HashMap)` - `linked` 与关联好的 `dicts` 的合集
+    /// - `Err(ForeignKeyError)` - 所有缺少的外键字典和循环引用
+    pub fn resolve_dicts(
+        dicts: impl IntoIterator<Item = DCTMeta>,
+        linked: &HashMap<String, Arc<DCTMeta>>,
+    ) -> Result<HashMap<String, Arc<DCTMeta>>, ForeignKeyError> {
+        let pending: HashMap<String, DCTMeta> = dicts.into_iter().map(|meta| (meta.dct_id.clone(), meta)).collect();
+        let linked = linked
+            .iter()
+            .filter(|(dct_id, _)| !pending.contains_key(*dct_id))
+            .map(|(dct_id, meta)| (dct_id.clone(), meta.clone()))
+            .collect();
+        let mut issues = Vec::new();
+        let resolved = Self::link_dicts(pending, linked, &mut issues);
+        let error = foreign_key_error(issues);
+        if error.is_empty() { Ok(resolved) } else { Err(error) }
+    }
+
+    pub(super) fn build_schemas(rows: &CatalogRows, issues: &mut Vec<CatalogIssue>) -> HashMap<String, TableSchema> {
+        let mut columns = group_by(rows, SYS_TABLE_NAMES::SYS_OBJCOLS, "OBJ_ID", issues);
+        let mut keys = group_by(rows, SYS_TABLE_NAMES::SYS_KEYS, "OBJ_ID", issues);
+        let mut indexes = group_by(rows, SYS_TABLE_NAMES::SYS_INDEXS, "OBJ_ID", issues);
+        let objects = group_by(rows, SYS_TABLE_NAMES::SYS_OBJECTS, "OBJ_ID", issues);
+
+        let mut schemas = HashMap::new();
+        for (obj_id, records) in objects {
+            let mut column_records = columns.remove(&obj_id).unwrap_or_default();
+            // 稳定排序，没有 COL_DISP 的列保持读取顺序
+            column_records.sort_by_key(|record| {
+                record
+                    .get("COL_DISP")
+                    .and_then(|v| v.as_i64().or_else(|| v.as_str().and_then(|s| s.trim().parse().ok())))
+                    .unwrap_or(i64::MAX)
+            });
+            let column_defs = column_records
+                .into_iter()
+                .map(|record| {
+                    let mut column = ColumnDef::default();
+                    for (field, value) in fields::<SYS_OBJCOLS>(record) {
+                        column.set(field, value);
+                    }
+                    column
+                })
+                .collect();
+            let key_defs = keys
+                .remove(&obj_id)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|record| {
+                    let mut key = KeyDef::default();
+                    for (field, value) in fields::<SYS_KEYS>(record) {
+                        key.set(field, value);
+                    }
+                    key
+                })
+                .collect();
+            let index_defs = indexes
+                .remove(&obj_id)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|record| {
+                    let mut index = IndexDef::default();
+                    for (field, value) in fields::<SYS_INDEXS>(record) {
+                        index.set(field, value);
+                    }
+                    index
+                })
+                .collect();
+
+            let mut schema = TableSchemaBuilder::new()
+                .with_columns(column_defs)
+                .with_keys(key_defs)
+                .with_indexes(index_defs)
+                .build();
+            // 同一对象出现多行时以最后一行为准
+            for record in records {
+                for (field, value) in fields::<SYS_OBJECTS>(record) {
+                    schema.set(field, value);
+                }
+            }
+            schema.set(SYS_OBJECTS::OBJ_ID, CellValue::String(obj_id.clone()));
+            schemas.insert(obj_id, schema);
+        }
+        schemas
+    }
+
+    pub(super) fn schema_for(
+        owner: &str,
+        obj_id: Option<String>,
+        schemas: &HashMap<String, TableSchema>,
+        issues: &mut Vec<CatalogIssue>,
+    ) -> TableSchema {
+        match obj_id {
+            Some(obj_id) => match schemas.get(&obj_id) {
+                Some(schema) => schema.clone(),
+                None => {
+                    issues.push(CatalogIssue::MissingObject { owner: owner.to_string(), obj_id });
+                    TableSchemaBuilder::new().build()
+                }
+            },
+            None => TableSchemaBuilder::new().build(),
+        }
+    }
+
+    /// 从目录行构建未关联外键字典的字典元数据
+    pub(super) fn unlinked_dicts(
+        rows: &CatalogRows,
+        schemas: &HashMap<String, TableSchema>,
+        issues: &mut Vec<CatalogIssue>,
+    ) -> HashMap<String, DCTMeta> {
+        let mut dicts = HashMap::new();
+        for (dct_id, records) in group_by(rows, SYS_TABLE_NAMES::SYS_DICTS, "DCT_ID", issues) {
+            let record = records[records.len() - 1];
+            let schema = Self::schema_for(&dct_id, record_text(record, "OBJ_ID"), schemas, issues);
+            let mut meta = DCTMeta::new(dct_id.clone(), schema);
+            for (field, value) in fields::<SYS_DICTS>(record) {
+                meta.set(field, value);
+            }
+            meta.set(SYS_DICTS::DCT_ID, CellValue::String(dct_id.clone()));
+            dicts.insert(dct_id, meta);
+        }
+        dicts
+    }
+
+    /// 按依赖顺序关联字典的外键字典
+    ///
+    /// # 参数
+    /// - `pending`: 需要关联的字典，原有的外键关联会被替换
+    /// - `linked`: 已关联好的字典，被引用时直接使用
+    ///
+    /// # 返回值
+    /// `linked` 与关联好的 `pending` 的合集
+    pub(super) fn link_dicts(
+        pending: HashMap<String, DCTMeta>,
+        linked: HashMap<String, Arc<DCTMeta>>,
+        issues: &mut Vec<CatalogIssue>,
+    ) -> HashMap<String, Arc<DCTMeta>> {
+        let mut ids: Vec<String> = pending.keys().cloned().collect();
+        ids.sort();
+        let mut linker = DictLinker {
+            pending,
+            linked,
+            visiting: Vec::new(),
+            issues,
+        };
+        for id in ids {
+            linker.link(&id);
+        }
+        linker.linked
+    }
+
+    fn build_dicts(
+        rows: &CatalogRows,
+        schemas: &HashMap<String, TableSchema>,
+        issues: &mut Vec<CatalogIssue>,
+    ) -> HashMap<String, Arc<DCTMeta>> {
+        let pending = Self::unlinked_dicts(rows, schemas, issues);
+        Self::link_dicts(pending, HashMap::new(), issues)
+    }
+
+    /// 从目录行构建未关联外键字典的事实表元数据
+    pub(super) fn unlinked_facts(
+        rows: &CatalogRows,
+        schemas: &HashMap<String, TableSchema>,
+        issues: &mut Vec<CatalogIssue>,
+    ) -> HashMap<String, FCTMeta> {
+        let mut facts = HashMap::new();
+        for (fct_id, records) in group_by(rows, SYS_TABLE_NAMES::SYS_FACTS, "FCT_ID", issues) {
+            let record = records[records.len() - 1];
+            let table_schema = Self::schema_for(&fct_id, record_text(record, "OBJ_ID"), schemas, issues);
+            let mut info = KeyValue::new();
+            for (field, value) in fields::<SYS_FACTS>(record) {
+                info.put(field.to_string(), value);
+            }
+            let name = record_text(record, "FCT_MC")
+                .or_else(|| record_text(record, "FCT_NAME"))
+                .unwrap_or_else(|| fct_id.clone());
+            let meta = FCTMeta {
+                id: fct_id.clone(),
+                name,
+                info: Some(info),
+                table_schema,
+                dct_metas: None,
+                settings: None,
+            };
+            facts.insert(fct_id, meta);
+        }
+        facts
+    }
+
+    /// 按外键列关联事实表的外键字典，原有的外键关联会被替换
+    pub(super) fn link_fact(
+        mut meta: FCTMeta,
+        dct_metas: &HashMap<String, Arc<DCTMeta>>,
+        issues: &mut Vec<CatalogIssue>,
+    ) -> FCTMeta {
+        let mut linked = HashMap::new();
+        for (column, target) in fact_dependencies(&meta) {
+            match dct_metas.get(&target) {
+                Some(dict) => {
+                    linked.insert(column, dict.clone());
+                }
+                None => issues.push(CatalogIssue::MissingDictionary {
+                    owner: meta.id.clone(),
+                    column: Some(column),
+                    dct_id: target,
+                }),
+            }
+        }
+        meta.dct_metas = (!linked.is_empty()).then_some(linked);
+        meta
+    }
+
+    fn build_facts(
+        rows: &CatalogRows,
+        schemas: &HashMap<String, TableSchema>,
+        dct_metas: &HashMap<String, Arc<DCTMeta>>,
+        issues: &mut Vec<CatalogIssue>,
+    ) -> HashMap<String, Arc<FCTMeta>> {
+        Self::unlinked_facts(rows, schemas, issues)
+            .into_iter()
+            .map(|(fct_id, meta)| (fct_id, Arc::new(Self::link_fact(meta, dct_metas, issues))))
+            .collect()
+    }
+
+    pub(super) fn build_models(
+        rows: &CatalogRows,
+        dct_metas: &HashMap<String, Arc<DCTMeta>>,
+        fct_metas: &HashMap<String, Arc<FCTMeta>>,
+        issues: &mut Vec<CatalogIssue>,
+    ) -> HashMap<String, Arc<DMEMeta>> {
+        let mut contents = group_by(rows, SYS_TABLE_NAMES::SYS_MDL_CTN, "MDL_ID", issues);
+        let mut models = HashMap::new();
+        for (mdl_id, records) in group_by(rows, SYS_TABLE_NAMES::SYS_MODEL, "MDL_ID", issues) {
+            let record = records[records.len() - 1];
+            let name = record_text(record, "MDL_MC").unwrap_or_else(|| mdl_id.clone());
+            let mut meta = DMEMeta::new(mdl_id.clone(), name);
+
+            let mut info = KeyValue::new();
+            for (field, value) in fields::<SYS_MODEL>(record) {
+                info.put(field.to_string(), value);
+            }
+            meta.info = Some(info);
+
+            let fct_ids: Vec<String> = contents
+                .remove(&mdl_id)
+                .unwrap_or_default()
+                .into_iter()
+                .flat_map(|content| MODEL_FACT_FIELDS.iter().filter_map(|field| record_text(content, field)))
+                .collect();
+            Self::link_model(&mut meta, &fct_ids, dct_metas, fct_metas, issues);
+            models.insert(mdl_id, Arc::new(meta));
+        }
+        models
+    }
+
+    /// 重新关联模型的关键指标字典、单位字典和事实表
+    ///
+    /// 字典按模型信息中的 `MDL_KEYDCT` 和 `MDL_UNITDCT` 查找，事实表沿用模型原有的事实表。
+    pub(super) fn relink_model(
+        model: &DMEMeta,
+        dct_metas: &HashMap<String, Arc<DCTMeta>>,
+        fct_metas: &HashMap<String, Arc<FCTMeta>>,
+        issues: &mut Vec<CatalogIssue>,
+    ) -> DMEMeta {
+        let mut meta = DMEMeta::new(model.id.clone(), model.name.clone());
+        meta.info = model.info.clone();
+        meta.settings = model.settings.clone();
+        meta.fct_list = model.fct_list.clone();
+        let mut fct_ids: Vec<String> = model.fct_meta_map.iter().flat_map(|map| map.keys().cloned()).collect();
+        fct_ids.sort();
+        Self::link_model(&mut meta, &fct_ids, dct_metas, fct_metas, issues);
+        meta
+    }
+
+    fn link_model(
+        meta: &mut DMEMeta,
+        fct_ids: &[String],
+        dct_metas: &HashMap<String, Arc<DCTMeta>>,
+        fct_metas: &HashMap<String, Arc<FCTMeta>>,
+        issues: &mut Vec<CatalogIssue>,
+    ) {
+        let info_text = |field: &str| meta.info.as_ref().and_then(|info| info.get(field)).and_then(text);
+        let key_dicts = id_list(info_text("MDL_KEYDCT"));
+        let unit_dict = info_text("MDL_UNITDCT");
+
+        let mut dictionary = |dct_id: String, field: &str| match dct_metas.get(&dct_id) {
+            Some(dict) => Some(DCTMeta::clone(dict)),
+            None => {
+                issues.push(CatalogIssue::MissingDictionary {
+                    owner: meta.id.clone(),
+                    column: Some(field.to_string()),
+                    dct_id,
+                });
+                None
+            }
+        };
+        let key_metrics: Vec<DCTMeta> = key_dicts
+            .into_iter()
+            .filter_map(|dct_id| dictionary(dct_id, "MDL_KEYDCT"))
+            .collect();
+        let unit_dct = unit_dict.and_then(|dct_id| dictionary(dct_id, "MDL_UNITDCT"));
+        for key_dict in key_metrics {
+            meta.add_key_metric(key_dict);
+        }
+        if let Some(unit_dct) = unit_dct {
+            meta.set_unit_dct(unit_dct);
+        }
+
+        for fct_id in fct_ids {
+            match fct_metas.get(fct_id) {
+                Some(fact) => meta.add_fct_meta(fct_id.clone(), FCTMeta::clone(fact)),
+                None => issues.push(CatalogIssue::MissingFact { model: meta.id.clone(), fct_id: fct_id.clone() }),
+            }
+        }
+    }
+}
+
+/// 关联字典时发现的缺少的字典和循环引用，其他问题被忽略
+pub(super) fn foreign_key_error(issues: Vec<CatalogIssue>) -> ForeignKeyError {
+    let mut error = ForeignKeyError::default();
+    for issue in issues {
+        match issue {
+            CatalogIssue::MissingDictionary { owner, column, dct_id } => {
+                error.missing.push(ForeignKeyRef { owner, column, dct_id })
+            }
+            CatalogIssue::ReferenceCycle { path } => error.cycles.push(path),
+            _ => {}
+        }
+    }
+    error
+}
+
+/// 字典引用的外键字典：外键列（`COL_ISFKEY`、`COL_FOBJ`）和 `DCT_FKEYDCT1..8`，按字典去重
+///
+/// 返回 (引用所在的列, 字典 ID)，来自 `DCT_FKEYDCTn` 的引用没有列。
+pub(super) fn dict_dependencies(meta: &DCTMeta) -> Vec<(Option<String>, String)> {
+    let mut targets: Vec<(Option<String>, String)> = meta
+        .table_schema
+        .columns
+        .iter()
+        .filter_map(|column| column.foreign_dict().map(|target| (Some(column.col_id()), target)))
+        .collect();
+    for field in DICT_FOREIGN_FIELDS {
+        if let Some(target) = meta.get(&field).and_then(text) {
+            targets.push((None, target));
+        }
+    }
+    let mut seen = HashSet::new();
+    targets.retain(|(_, target)| seen.insert(target.clone()));
+    targets
+}
+
+/// 事实表外键列引用的字典，返回 (列 ID, 字典 ID)
+pub(super) fn fact_dependencies(meta: &FCTMeta) -> Vec<(String, String)> {
+    meta.table_schema
+        .columns
+        .iter()
+        .filter_map(|column| column.foreign_dict().map(|target| (column.col_id(), target)))
+        .collect()
+}
+
+/// 模型引用的字典（`MDL_KEYDCT`、`MDL_UNITDCT`）
+pub(super) fn model_dictionaries(meta: &DMEMeta) -> Vec<String> {
+    let info_text = |field: &str| meta.info.as_ref().and_then(|info| info.get(field)).and_then(text);
+    let mut ids = id_list(info_text("MDL_KEYDCT"));
+    ids.extend(info_text("MDL_UNITDCT"));
+    ids
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn rows() -> CatalogRows {
+        let mut rows = CatalogRows::new();
+        let objects = SYS_TABLE_NAMES::SYS_OBJECTS;
+        let columns = SYS_TABLE_NAMES::SYS_OBJCOLS;
+        rows.push_json(objects.clone(), json!({"OBJ_ID": "T_DEPT", "OBJ_MC": "部门"}));
+        rows.push_json(objects.clone(), json!({"OBJ_ID": "T_STAFF", "OBJ_MC": "职员"}));
+        rows.push_json(objects.clone(), json!({"OBJ_ID": "T_SALES"}));
+        rows.push_json(columns.clone(), json!({"OBJ_ID": "T_DEPT", "COL_ID": "MC", "COL_DISP": 2}));
+        rows.push_json(columns.clone(), json!({"OBJ_ID": "T_DEPT", "COL_ID": "DM", "COL_DISP": "1"}));
+        rows.push_json(columns.clone(), json!({"OBJ_ID": "T_STAFF", "COL_ID": "DM"}));
+        rows.push_json(
+            columns.clone(),
+            json!({"OBJ_ID": "T_STAFF", "COL_ID": "BM", "COL_ISFKEY": "1", "COL_FOBJ": "DCT_DEPT"}),
+        );
+        rows.push_json(columns.clone(), json!({"OBJ_ID": "T_SALES", "COL_ID": "ZY", "COL_ISFKEY": true, "COL_FOBJ": "DCT_STAFF"}));
+        rows.push_json(columns.clone(), json!({"OBJ_ID": "T_SALES", "COL_ID": "KH", "COL_ISFKEY": true, "COL_FOBJ": "DCT_CUST"}));
+        rows.push_json(columns, json!({"COL_ID": "ORPHAN"}));
+        rows.push_json(SYS_TABLE_NAMES::SYS_KEYS, json!({"OBJ_ID": "T_DEPT", "KEY_ID": "PK", "KEY_PINDEX1": "DM"}));
+        rows.push_json(
+            SYS_TABLE_NAMES::SYS_INDEXS,
+            json!({"OBJ_ID": "T_DEPT", "INX_ID": "IX_MC", "INX_COLS": "MC"}),
+        );
+        rows.push_json(SYS_TABLE_NAMES::SYS_DICTS, json!({"DCT_ID": "DCT_DEPT", "OBJ_ID": "T_DEPT", "DCT_MC": "部门"}));
+        rows.push_json(SYS_TABLE_NAMES::SYS_DICTS, json!({"DCT_ID": "DCT_STAFF", "OBJ_ID": "T_STAFF"}));
+        rows.push_json(SYS_TABLE_NAMES::SYS_DICTS, json!({"DCT_ID": "DCT_YEAR", "OBJ_ID": "T_YEAR"}));
+        rows.push_json(SYS_TABLE_NAMES::SYS_FACTS, json!({"FCT_ID": "FCT_SALES", "FCT_MC": "销售", "OBJ_ID": "T_SALES"}));
+        rows.push_json(
+            SYS_TABLE_NAMES::SYS_MODEL,
+            json!({"MDL_ID": "MDL_SALES", "MDL_MC": "销售模型", "MDL_KEYDCT": "DCT_STAFF, DCT_DEPT", "MDL_UNITDCT": "DCT_DEPT"}),
+        );
+        rows.push_json(
+            SYS_TABLE_NAMES::SYS_MDL_CTN,
+            json!({"MDL_ID": "MDL_SALES", "CTN_ID": "1", "CTN_FCT1": "FCT_SALES", "CTN_FCT2": "FCT_NONE"}),
+        );
+        rows
+    }
+
+    #[test]
+    fn test_build_schemas() {
+        let catalog = Catalog::build(&rows());
+        let dept = &catalog.schemas["T_DEPT"];
+        assert_eq!(dept.obj_id(), Some("T_DEPT"));
+        assert_eq!(dept.get_column_index("DM"), Some(0));
+        assert_eq!(dept.get_column_index("MC"), Some(1));
+        assert_eq!(dept.primary_key_columns(), vec!["DM".to_string()]);
+        assert_eq!(dept.indexes[0].columns(), vec!["MC".to_string()]);
+    }
+
+    #[test]
+    fn test_link_dictionaries_in_dependency_order() {
+        let catalog = Catalog::build(&rows());
+        let staff = &catalog.dct_metas["DCT_STAFF"];
+        let dept = &staff.dct_metas.as_ref().unwrap()["DCT_DEPT"];
+        assert!(Arc::ptr_eq(dept, &catalog.dct_metas["DCT_DEPT"]));
+        assert_eq!(dept.get_string(&SYS_DICTS::DCT_MC).as_deref(), Some("部门"));
+
+        let sales = &catalog.fct_metas["FCT_SALES"];
+        assert_eq!(sales.name, "销售");
+        let zy = &sales.dct_metas.as_ref().unwrap()["ZY"];
+        assert!(zy.dct_metas.as_ref().unwrap().contains_key("DCT_DEPT"));
+    }
+
+    #[test]
+    fn test_build_models() {
+        let catalog = Catalog::build(&rows());
+        let model = &catalog.dme_metas["MDL_SALES"];
+        assert_eq!(model.name, "销售模型");
+        assert_eq!(model.get_key_metric(0).unwrap().dct_id, "DCT_STAFF");
+        assert_eq!(model.get_key_metric(1).unwrap().dct_id, "DCT_DEPT");
+        assert_eq!(model.get_unit_dct().unwrap().dct_id, "DCT_DEPT");
+        assert_eq!(model.get_fct_meta("FCT_SALES").unwrap().id, "FCT_SALES");
+    }
+
+    #[test]
+    fn test_report_issues() {
+        let catalog = Catalog::build(&rows());
+        let mut issues: Vec<String> = catalog.issues.iter().map(ToString::to_string).collect();
+        issues.sort();
+        assert_eq!(
+            issues,
+            vec![
+                "'DCT_YEAR' references unknown object 'T_YEAR'",
+                "SYS_OBJCOLS row 6 has no OBJ_ID",
+                "column 'KH' of 'FCT_SALES' references unknown dictionary 'DCT_CUST'",
+                "model 'MDL_SALES' references unknown fact 'FCT_NONE'",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cyclic_references_do_not_loop() {
+        let mut rows = CatalogRows::new();
+        rows.push_json(SYS_TABLE_NAMES::SYS_DICTS, json!({"DCT_ID": "A", "DCT_FKEYDCT1": "B"}));
+        rows.push_json(SYS_TABLE_NAMES::SYS_DICTS, json!({"DCT_ID": "B", "DCT_FKEYDCT1": "A"}));
+        let catalog = Catalog::build(&rows);
+        assert_eq!(catalog.dct_metas.len(), 2);
+        assert!(catalog.dct_metas["A"].dct_metas.as_ref().unwrap().contains_key("B"));
+        assert_eq!(catalog.issues, vec![CatalogIssue::ReferenceCycle { path: vec!["A".into(), "B".into(), "A".into()] }]);
+    }
+
+    #[test]
+    fn test_self_reference_is_not_a_cycle() {
+        let mut rows = CatalogRows::new();
+        rows.push_json(SYS_TABLE_NAMES::SYS_OBJECTS, json!({"OBJ_ID": "T_DEPT"}));
+        rows.push_json(
+            SYS_TABLE_NAMES::SYS_OBJCOLS,
+            json!({"OBJ_ID": "T_DEPT", "COL_ID": "SJDM", "COL_ISFKEY": "Y", "COL_FOBJ": "DCT_DEPT"}),
+        );
+        rows.push_json(SYS_TABLE_NAMES::SYS_DICTS, json!({"DCT_ID": "DCT_DEPT", "OBJ_ID": "T_DEPT"}));
+        let catalog = Catalog::build(&rows);
+        assert!(catalog.issues.is_empty());
+        assert!(catalog.dct_metas["DCT_DEPT"].dct_metas.as_ref().unwrap().contains_key("DCT_DEPT"));
+    }
+
+    #[test]
+    fn test_resolve_dicts_reports_missing_and_cycles() {
+        let dict = |dct_id: &str, refs: &[&str]| {
+            let mut meta = DCTMeta::new(dct_id.to_string(), TableSchemaBuilder::new().build());
+            for (field, target) in DICT_FOREIGN_FIELDS.iter().zip(refs) {
+                meta.set(field.clone(), json!(target));
+            }
+            meta
+        };
+        let linked = HashMap::from([("L".to_string(), Arc::new(dict("L", &[])))]);
+
+        let resolved = Catalog::resolve_dicts(vec![dict("A", &["B"]), dict("B", &["L"])], &linked).unwrap();
+        assert_eq!(resolved.len(), 3);
+        let b = &resolved["A"].dct_metas.as_ref().unwrap()["B"];
+        assert!(Arc::ptr_eq(b, &resolved["B"]));
+        assert!(Arc::ptr_eq(&b.dct_metas.as_ref().unwrap()["L"], &linked["L"]));
+
+        let error = Catalog::resolve_dicts(
+            vec![dict("A", &["B", "X"]), dict("B", &["C"]), dict("C", &["A"])],
+            &linked,
+        )
+        .unwrap_err();
+        assert_eq!(error.missing, vec![ForeignKeyRef { owner: "A".into(), column: None, dct_id: "X".into() }]);
+        assert_eq!(error.cycles, vec![vec!["A".to_string(), "B".into(), "C".into(), "A".into()]]);
+        assert_eq!(
+            error.to_string(),
+            "Missing referenced dictionaries: 'A' -> 'X'; Dictionary reference cycles: A -> B -> C -> A"
+        );
+    }
+
+    #[test]
+    fn test_install() {
+        let mut rows = CatalogRows::new();
+        rows.push_json(SYS_TABLE_NAMES::SYS_DICTS, json!({"dct_id": "CATALOG_INSTALL"}));
+        rows.push_json(SYS_TABLE_NAMES::SYS_MODEL, json!({"mdl_id": "CATALOG_MODEL"}));
+        let registry = MetaRegistry::new();
+        let installed = Catalog::build(&rows).install(&registry, "t");
+        assert_eq!(installed.version(), 1);
+        assert!(registry.get_dct_meta("t", "CATALOG_INSTALL").is_some());
+        assert!(registry.get_dme_meta("t", "CATALOG_MODEL").is_some());
+        assert!(registry.snapshot("other").is_none());
+    }
+}
@@ -0,0 +1,1133 @@
+//! # 多维模型查询模块
+//!
+//! 多维模型（`DMEMeta`）把事实表和维度字典组织在一起：年度、月度、日字典（`MDL_NDCT`、`MDL_YDCT`、`MDL_RDCT`）、
+//! 单位字典（`MDL_UNITDCT`）和关键指标字典（`MDL_KEYDCT`）。[`Cube`] 在模型上按维度分组、按度量聚合：
+//!
+//! - 维度（[`Dimension`]）对应一个字典，事实表中外键引用该字典的列（`COL_ISFKEY`、`COL_FOBJ`）就是维度列
+//! - 分级字典（`DCT_BMSTRU`）可以按级次汇总（[`DimensionSpec::level`]），编码截取到该级的长度后再分组
+//! - 切片（[`Slice`]）只保留维度编码在给定编码中的行，分级字典同时包含下级编码
+//! - 度量（[`Measure`]）是事实表列上的聚合，只有一个事实表有该列时自动确定事实表
+//!
+//! 每个涉及的事实表生成一个 [`FactQuery`]，由 [`FactSource`] 执行：数据库中执行 [`FactQuery::statement`]
+//! 生成的 SQL，内存中用 [`FactQuery::evaluate`] 计算。各事实表的结果按维度合并为一个 `RowDataSet`，
+//! 列依次为维度列（编码文本）和度量列，按维度排序；[`Crosstab`] 再把结果转为交叉表。
+//!
+//! ## 示例
+//!
+//! ```rust
+//! use std::collections::HashMap;
+//!
+//! use cmx_core::model::data::dataset::{rds::RowDataSet, ColumnType};
+//! use cmx_core::model::meta::catalog::{Catalog, CatalogRows};
+//! use cmx_core::model::meta::cube::{Crosstab, Cube, CubeQuery, Dimension, DimensionSpec, Measure};
+//! use cmx_core::model::meta::registry::MetaCatalog;
+//! use cmx_core::model::meta::tables::SYS_TABLE_NAMES;
+//! use serde_json::json;
+//!
+//! let mut rows = CatalogRows::new();
+//! rows.push_json(SYS_TABLE_NAMES::SYS_OBJECTS, json!({"OBJ_ID": "T_SALES"}));
+//! rows.push_json(SYS_TABLE_NAMES::SYS_OBJCOLS, json!({"OBJ_ID": "T_SALES", "COL_ID": "ND", "COL_ISFKEY": true, "COL_FOBJ": "DCT_YEAR"}));
+//! rows.push_json(SYS_TABLE_NAMES::SYS_OBJCOLS, json!({"OBJ_ID": "T_SALES", "COL_ID": "DW", "COL_ISFKEY": true, "COL_FOBJ": "DCT_UNIT"}));
+//! rows.push_json(SYS_TABLE_NAMES::SYS_OBJCOLS, json!({"OBJ_ID": "T_SALES", "COL_ID": "JE", "COL_TYPE": "decimal"}));
+//! rows.push_json(SYS_TABLE_NAMES::SYS_DICTS, json!({"DCT_ID": "DCT_YEAR"}));
+//! rows.push_json(SYS_TABLE_NAMES::SYS_DICTS, json!({"DCT_ID": "DCT_UNIT", "DCT_BMSTRU": "2-2"}));
+//! rows.push_json(SYS_TABLE_NAMES::SYS_FACTS, json!({"FCT_ID": "FCT_SALES", "OBJ_ID": "T_SALES"}));
+//! rows.push_json(SYS_TABLE_NAMES::SYS_MODEL, json!({"MDL_ID": "MDL_SALES", "MDL_NDCT": "DCT_YEAR", "MDL_UNITDCT": "DCT_UNIT"}));
+//! rows.push_json(SYS_TABLE_NAMES::SYS_MDL_CTN, json!({"MDL_ID": "MDL_SALES", "CTN_ID": "1", "CTN_FCT1": "FCT_SALES"}));
+//! let catalog = MetaCatalog::from(&Catalog::build(&rows));
+//! let model = catalog.dme_meta("MDL_SALES").unwrap();
+//!
+//! let mut sales = RowDataSet::new("T_SALES".to_string());
+//! sales.add_column("ND".to_string(), ColumnType::String).unwrap();
+//! sales.add_column("DW".to_string(), ColumnType::String).unwrap();
+//! sales.add_column("JE".to_string(), ColumnType::Decimal).unwrap();
+//! sales.add_row(vec![json!("2024"), json!("0101"), json!(10)]).unwrap();
+//! sales.add_row(vec![json!("2024"), json!("0102"), json!(5)]).unwrap();
+//! sales.add_row(vec![json!("2025"), json!("0201"), json!(7)]).unwrap();
+//! let facts = HashMap::from([("FCT_SALES".to_string(), sales)]);
+//!
+//! // 单位按一级汇总
+//! let query = CubeQuery::new()
+//!     .with_dimension(DimensionSpec::new(Dimension::Unit).level(1))
+//!     .with_dimension(Dimension::Year)
+//!     .with_measure(Measure::sum("JE"));
+//! let result = futures::executor::block_on(Cube::new(&model, &catalog).query(&query, &facts)).unwrap();
+//! assert_eq!(result.row_count(), 2);
+//! assert_eq!(result.get_cell(0, "UNIT").unwrap(), &json!("01"));
+//! assert_eq!(result.get_cell(0, "SUM_JE").unwrap(), &json!(15));
+//!
+//! let crosstab = Crosstab::build(&result, &["UNIT"], &["YEAR"], "SUM_JE").unwrap();
+//! assert_eq!(crosstab.column_headers, vec![vec![json!("2024")], vec![json!("2025")]]);
+//! assert_eq!(crosstab.cells, vec![vec![json!(15), json!(null)], vec![json!(null), json!(7)]]);
+//! ```
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::dct::DCTMeta;
+use super::ddl::{qualified_name, quote_ident};
+use super::dme::DMEMeta;
+use super::dml::{RowValues, Statement};
+use super::fct::FCTMeta;
+use super::fields::SYS_MODEL;
+use super::registry::MetaCatalog;
+use super::tree::CodeTreeError;
+use crate::model::data::cell::CellValue;
+use crate::model::data::dataset::aggregate::{AggFunc, Aggregate};
+use crate::model::data::dataset::filter::compare_values;
+use crate::model::data::dataset::rds::{ColumnInfo, RowDataSet};
+use crate::model::data::dataset::{ColumnType, DataSetError};
+
+/// 维度
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Dimension {
+    /// 年度字典（`MDL_NDCT`）
+    Year,
+    /// 月度字典（`MDL_YDCT`）
+    Month,
+    /// 日字典（`MDL_RDCT`）
+    Day,
+    /// 单位字典（`MDL_UNITDCT`）
+    Unit,
+    /// 第 n 个（从 0 开始）关键指标字典（`MDL_KEYDCT`）
+    Key(usize),
+    /// 按字典 ID 指定的任意字典
+    Dict(String),
+}
+
+impl Dimension {
+    /// 模型信息中定义该维度字典的字段
+    fn model_field(&self) -> Option<SYS_MODEL> {
+        match self {
+            Dimension::Year => Some(SYS_MODEL::MDL_NDCT),
+            Dimension::Month => Some(SYS_MODEL::MDL_YDCT),
+            Dimension::Day => Some(SYS_MODEL::MDL_RDCT),
+            Dimension::Unit => Some(SYS_MODEL::MDL_UNITDCT),
+            Dimension::Key(_) | Dimension::Dict(_) => None,
+        }
+    }
+
+    /// 未指定别名时的结果列名：`YEAR`、`MONTH`、`DAY`、`UNIT`，其他维度为字典 ID
+    fn default_name(&self, dct_id: &str) -> String {
+        match self {
+            Dimension::Year => "YEAR".to_string(),
+            Dimension::Month => "MONTH".to_string(),
+            Dimension::Day => "DAY".to_string(),
+            Dimension::Unit => "UNIT".to_string(),
+            Dimension::Key(_) | Dimension::Dict(_) => dct_id.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Dimension {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Dimension::Year => write!(f, "year"),
+            Dimension::Month => write!(f, "month"),
+            Dimension::Day => write!(f, "day"),
+            Dimension::Unit => write!(f, "unit"),
+            Dimension::Key(index) => write!(f, "key {}", index),
+            Dimension::Dict(dct_id) => write!(f, "dictionary '{}'", dct_id),
+        }
+    }
+}
+
+/// 分组维度
+///
+/// `level` 为分级字典的级次（从 1 开始），编码截取到该级的长度，下级编码汇总到上级。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DimensionSpec {
+    pub dimension: Dimension,
+    pub level: Option<usize>,
+    pub alias: Option<String>,
+}
+
+impl DimensionSpec {
+    pub fn new(dimension: Dimension) -> Self {
+        Self { dimension, level: None, alias: None }
+    }
+
+    /// 按分级字典的第 `level` 级汇总
+    pub fn level(mut self, level: usize) -> Self {
+        self.level = Some(level);
+        self
+    }
+
+    /// 设置结果列名
+    pub fn alias(mut self, alias: impl Into<String>) -> Self {
+        self.alias = Some(alias.into());
+        self
+    }
+}
+
+impl From<Dimension> for DimensionSpec {
+    fn from(dimension: Dimension) -> Self {
+        Self::new(dimension)
+    }
+}
+
+/// 度量：事实表列上的聚合
+///
+/// 未指定 `fact` 时，在模型中唯一包含该列的事实表上计算。结果列名与 [`Aggregate::output_name`] 相同，
+/// 例如 `SUM_JE`。不支持依赖行顺序的 `First`、`Last`。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Measure {
+    pub func: AggFunc,
+    pub column: String,
+    pub fact: Option<String>,
+    pub alias: Option<String>,
+}
+
+impl Measure {
+    pub fn new(func: AggFunc, column: impl Into<String>) -> Self {
+        Self { func, column: column.into(), fact: None, alias: None }
+    }
+
+    pub fn sum(column: impl Into<String>) -> Self {
+        Self::new(AggFunc::Sum, column)
+    }
+
+    pub fn count(column: impl Into<String>) -> Self {
+        Self::new(AggFunc::Count, column)
+    }
+
+    pub fn count_distinct(column: impl Into<String>) -> Self {
+        Self::new(AggFunc::CountDistinct, column)
+    }
+
+    pub fn min(column: impl Into<String>) -> Self {
+        Self::new(AggFunc::Min, column)
+    }
+
+    pub fn max(column: impl Into<String>) -> Self {
+        Self::new(AggFunc::Max, column)
+    }
+
+    pub fn avg(column: impl Into<String>) -> Self {
+        Self::new(AggFunc::Avg, column)
+    }
+
+    /// 指定计算度量的事实表
+    pub fn fact(mut self, fct_id: impl Into<String>) -> Self {
+        self.fact = Some(fct_id.into());
+        self
+    }
+
+    /// 设置结果列名
+    pub fn alias(mut self, alias: impl Into<String>) -> Self {
+        self.alias = Some(alias.into());
+        self
+    }
+
+    /// 结果列名
+    pub fn output_name(&self) -> String {
+        self.aggregate(self.column.clone()).output_name()
+    }
+
+    fn aggregate(&self, column: String) -> Aggregate {
+        Aggregate { func: self.func, column: Some(column), alias: self.alias.clone() }
+    }
+}
+
+/// 切片：只保留维度编码为 `codes` 之一的行，分级字典同时包含这些编码的下级编码
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Slice {
+    pub dimension: Dimension,
+    pub codes: Vec<String>,
+}
+
+impl Slice {
+    pub fn new<I, S>(dimension: Dimension, codes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self { dimension, codes: codes.into_iter().map(Into::into).collect() }
+    }
+}
+
+/// 多维查询：分组维度、度量和切片
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CubeQuery {
+    #[serde(default)]
+    pub dimensions: Vec<DimensionSpec>,
+    #[serde(default)]
+    pub measures: Vec<Measure>,
+    #[serde(default)]
+    pub slices: Vec<Slice>,
+}
+
+impl CubeQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_dimension(mut self, dimension: impl Into<DimensionSpec>) -> Self {
+        self.dimensions.push(dimension.into());
+        self
+    }
+
+    pub fn with_measure(mut self, measure: Measure) -> Self {
+        self.measures.push(measure);
+        self
+    }
+
+    pub fn with_slice(mut self, slice: Slice) -> Self {
+        self.slices.push(slice);
+        self
+    }
+}
+
+/// 事实表上的分组列
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FactGroup {
+    /// 维度列
+    pub column: String,
+    /// 编码截取的长度，`None` 为完整编码
+    pub length: Option<usize>,
+    /// 结果列名
+    pub name: String,
+}
+
+/// 事实表上的切片条件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FactSlice {
+    /// 维度列
+    pub column: String,
+    pub codes: Vec<String>,
+    /// 是否按编码前缀匹配（分级字典）
+    pub prefix: bool,
+}
+
+/// 单个事实表上的分组聚合
+///
+/// 维度列按编码文本分组，结果是每个分组一行：分组列名 -> 编码，度量结果列名 -> 聚合值。
+#[derive(Debug, Clone)]
+pub struct FactQuery<'a> {
+    pub fact: &'a FCTMeta,
+    pub groups: Vec<FactGroup>,
+    /// 聚合列为事实表列，别名为度量结果列名
+    pub measures: Vec<Aggregate>,
+    pub slices: Vec<FactSlice>,
+}
+
+impl FactQuery<'_> {
+    /// 生成 Postgres 查询，每个分组返回一行 JSON 文本
+    ///
+    /// # 参数
+    ///
+    /// * `schema` - 事实表所在的 Postgres schema，`None` 时使用连接的 `search_path`
+    ///
+    /// # 返回值
+    ///
+    /// - `Ok(statement)` - 切片编码为参数
+    /// - `Err(CubeError::MissingTable)` - 事实表没有 `OBJ_ID`
+    pub fn statement(&self, schema: Option<&str>) -> Result<Statement, CubeError> {
+        let table = self
+            .fact
+            .table_schema
+            .obj_id()
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .ok_or_else(|| CubeError::MissingTable(self.fact.id.clone()))?;
+        let code = |column: &str| format!("t.{}::text", quote_ident(column));
+
+        let mut select: Vec<String> = self
+            .groups
+            .iter()
+            .map(|group| {
+                let value = match group.length {
+                    Some(length) => format!("left({}, {})", code(&group.column), length),
+                    None => code(&group.column),
+                };
+                format!("{} AS {}", value, quote_ident(&group.name))
+            })
+            .collect();
+        for measure in &self.measures {
+            let column = format!("t.{}", quote_ident(measure.column.as_deref().unwrap_or_default()));
+            let value = match measure.func {
+                AggFunc::Sum => format!("sum({})", column),
+                AggFunc::Count => format!("count({})", column),
+                AggFunc::CountDistinct => format!("count(DISTINCT {})", column),
+                AggFunc::Min => format!("min({})", column),
+                AggFunc::Max => format!("max({})", column),
+                AggFunc::Avg => format!("avg({})", column),
+                AggFunc::First | AggFunc::Last => {
+                    return Err(CubeError::UnsupportedAggregate { func: measure.func, column: measure.output_name() });
+                }
+            };
+            select.push(format!("{} AS {}", value, quote_ident(&measure.output_name())));
+        }
+
+        let mut params = Vec::new();
+        let conditions: Vec<String> = self
+            .slices
+            .iter()
+            .map(|slice| {
+                let column = code(&slice.column);
+                let placeholders: Vec<String> = slice
+                    .codes
+                    .iter()
+                    .map(|value| {
+                        params.push(Some(value.clone()));
+                        format!("${}::text", params.len())
+                    })
+                    .collect();
+                match (placeholders.is_empty(), slice.prefix) {
+                    (true, _) => "false".to_string(),
+                    (false, true) => {
+                        let matches: Vec<String> =
+                            placeholders.iter().map(|p| format!("starts_with({}, {})", column, p)).collect();
+                        format!("({})", matches.join(" OR "))
+                    }
+                    (false, false) => format!("{} IN ({})", column, placeholders.join(", ")),
+                }
+            })
+            .collect();
+
+        let mut sql = format!("SELECT {} FROM {} AS t", select.join(", "), qualified_name(schema, table));
+        if !conditions.is_empty() {
+            sql.push_str(&format!(" WHERE {}", conditions.join(" AND ")));
+        }
+        if !self.groups.is_empty() {
+            let positions: Vec<String> = (1..=self.groups.len()).map(|i| i.to_string()).collect();
+            sql.push_str(&format!(" GROUP BY {}", positions.join(", ")));
+        }
+        Ok(Statement { sql: format!("SELECT to_jsonb(q)::text FROM ({}) AS q", sql), params })
+    }
+
+    /// 在内存中的事实表数据上计算，结果与 [`FactQuery::statement`] 相同
+    ///
+    /// # 返回值
+    ///
+    /// - `Err(DataSetError::ColumnNotFound)` - 数据集缺少维度列或度量列
+    /// - `Err(DataSetError::InvalidAggregate)` - 对非数值列求和/求平均
+    pub fn evaluate(&self, data: &RowDataSet) -> Result<Vec<RowValues>, DataSetError> {
+        let column = |name: &str| data.get_column_info(name).ok_or(DataSetError::ColumnNotFound);
+        let groups = self
+            .groups
+            .iter()
+            .map(|group| Ok((column(&group.column)?.index, group.length)))
+            .collect::<Result<Vec<_>, DataSetError>>()?;
+        let slices = self
+            .slices
+            .iter()
+            .map(|slice| Ok((column(&slice.column)?.index, slice)))
+            .collect::<Result<Vec<_>, DataSetError>>()?;
+        let sources = self
+            .measures
+            .iter()
+            .map(|measure| {
+                let info = column(measure.column.as_deref().unwrap_or_default())?;
+                Ok((info.index, info.column_type))
+            })
+            .collect::<Result<Vec<_>, DataSetError>>()?;
+
+        // 分组列为编码文本，度量源列按位置命名，避免与分组列重名
+        let mut projected = RowDataSet::new(data.dataset_id().to_string());
+        for group in &self.groups {
+            projected.add_column(group.name.clone(), ColumnType::String)?;
+        }
+        for (position, (_, column_type)) in sources.iter().enumerate() {
+            projected.add_column(format!("#{}", position), *column_type)?;
+        }
+        for index in 0..data.row_count() {
+            let values = data.get_row(index)?.values();
+            let code = |position: usize| values.get(position).and_then(code_text);
+            let selected = slices.iter().all(|(position, slice)| {
+                code(*position).is_some_and(|code| {
+                    slice.codes.iter().any(|value| if slice.prefix { code.starts_with(value.as_str()) } else { code == *value })
+                })
+            });
+            if !selected {
+                continue;
+            }
+            let mut row: Vec<CellValue> = groups
+                .iter()
+                .map(|(position, length)| match (code(*position), length) {
+                    (Some(code), Some(length)) => CellValue::String(code.chars().take(*length).collect()),
+                    (Some(code), None) => CellValue::String(code),
+                    (None, _) => CellValue::Null,
+                })
+                .collect();
+            row.extend(sources.iter().map(|(position, _)| values.get(*position).cloned().unwrap_or(CellValue::Null)));
+            projected.add_row(row)?;
+        }
+
+        let keys: Vec<&str> = self.groups.iter().map(|group| group.name.as_str()).collect();
+        let aggregates: Vec<Aggregate> = self
+            .measures
+            .iter()
+            .enumerate()
+            .map(|(position, measure)| Aggregate {
+                func: measure.func,
+                column: Some(format!("#{}", position)),
+                alias: Some(measure.output_name()),
+            })
+            .collect();
+        let result = projected.group_by(&keys, &aggregates)?;
+        let names: Vec<String> = keys.iter().map(|key| key.to_string()).chain(aggregates.iter().map(Aggregate::output_name)).collect();
+        (0..result.row_count())
+            .map(|index| {
+                let values = result.get_row(index)?.values();
+                Ok(names.iter().cloned().zip(values.iter().cloned()).collect())
+            })
+            .collect()
+    }
+}
+
+/// 执行单个事实表的分组聚合
+///
+/// 数据库实现执行 [`FactQuery::statement`]，内存实现见 `HashMap<String, RowDataSet>`。
+pub trait FactSource {
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    /// 执行 `query`，每个分组返回一行：分组列名 -> 编码，度量结果列名 -> 聚合值
+    fn aggregate(&self, query: &FactQuery<'_>) -> impl Future<Output = Result<Vec<RowValues>, Self::Error>> + Send;
+}
+
+/// 内存中的事实表数据：事实表 ID -> 数据，主要用于测试和已加载到内存的数据，没有数据的事实表结果为空
+impl FactSource for HashMap<String, RowDataSet> {
+    type Error = DataSetError;
+
+    fn aggregate(&self, query: &FactQuery<'_>) -> impl Future<Output = Result<Vec<RowValues>, Self::Error>> + Send {
+        let rows = match self.get(&query.fact.id) {
+            Some(data) => query.evaluate(data),
+            None => Ok(Vec::new()),
+        };
+        std::future::ready(rows)
+    }
+}
+
+/// 查询计划：结果列和每个事实表上的查询
+#[derive(Debug, Clone)]
+pub struct CubePlan<'a> {
+    /// 维度结果列名
+    pub dimensions: Vec<String>,
+    /// 度量结果列名和类型
+    pub measures: Vec<(String, ColumnType)>,
+    /// 按度量的顺序，每个涉及的事实表一个查询
+    pub facts: Vec<FactQuery<'a>>,
+}
+
+/// 多维模型查询
+#[derive(Debug, Clone, Copy)]
+pub struct Cube<'a> {
+    model: &'a DMEMeta,
+    catalog: &'a MetaCatalog,
+}
+
+impl<'a> Cube<'a> {
+    /// # 参数
+    ///
+    /// * `model` - 已关联事实表的模型
+    /// * `catalog` - 查找维度字典的目录
+    pub fn new(model: &'a DMEMeta, catalog: &'a MetaCatalog) -> Self {
+        Self { model, catalog }
+    }
+
+    /// 模型的事实表，按 ID 排序
+    pub fn facts(&self) -> Vec<&'a FCTMeta> {
+        let mut facts: Vec<&FCTMeta> = self.model.fct_meta_map.iter().flat_map(|map| map.values()).collect();
+        facts.sort_by(|a, b| a.id.cmp(&b.id));
+        facts
+    }
+
+    /// 维度对应的字典
+    ///
+    /// # 返回值
+    ///
+    /// - `Err(CubeError::DimensionNotDefined)` - 模型没有定义该维度的字典
+    /// - `Err(CubeError::UnknownDictionary)` - 目录中没有该字典
+    pub fn dictionary(&self, dimension: &Dimension) -> Result<Arc<DCTMeta>, CubeError> {
+        let dct_id = match dimension {
+            Dimension::Dict(dct_id) => Some(dct_id.trim().to_string()),
+            Dimension::Key(index) => self.model.get_key_metric(*index).map(|dict| dict.dct_id.clone()),
+            other => other
+                .model_field()
+                .and_then(|field| self.model.info.as_ref()?.get(field.as_ref()).and_then(code_text))
+                .map(|dct_id| dct_id.trim().to_string()),
+        };
+        let dct_id = dct_id.filter(|id| !id.is_empty()).ok_or_else(|| CubeError::DimensionNotDefined {
+            model: self.model.id.clone(),
+            dimension: dimension.to_string(),
+        })?;
+        self.catalog.dct_meta(&dct_id).ok_or(CubeError::UnknownDictionary(dct_id))
+    }
+
+    /// 解析维度、度量和切片，为每个涉及的事实表生成查询
+    ///
+    /// # 返回值
+    ///
+    /// - `Err(CubeError::NoMeasures)` - 没有度量
+    /// - `Err(CubeError::UnknownMeasure)`、`Err(CubeError::AmbiguousMeasure)` - 无法确定度量所在的事实表
+    /// - `Err(CubeError::DimensionNotInFact)` - 度量所在的事实表没有分组或切片维度的列
+    /// - `Err(CubeError::CodeTree)`、`Err(CubeError::InvalidLevel)` - 按级次汇总的字典不是分级字典或级次超出范围
+    /// - `Err(CubeError::DuplicateColumn)` - 结果列名重复
+    pub fn plan(&self, query: &CubeQuery) -> Result<CubePlan<'a>, CubeError> {
+        if query.measures.is_empty() {
+            return Err(CubeError::NoMeasures);
+        }
+
+        let mut dimensions = Vec::new();
+        for spec in &query.dimensions {
+            let dict = self.dictionary(&spec.dimension)?;
+            let length = match spec.level {
+                Some(level) => {
+                    let structure = dict.code_structure()?;
+                    let length = structure
+                        .code_length(level)
+                        .ok_or_else(|| CubeError::InvalidLevel { dct_id: dict.dct_id.clone(), level })?;
+                    Some(length)
+                }
+                None => None,
+            };
+            let name = spec.alias.clone().unwrap_or_else(|| spec.dimension.default_name(&dict.dct_id));
+            dimensions.push((spec.dimension.clone(), dict, length, name));
+        }
+        let mut slices = Vec::new();
+        for slice in &query.slices {
+            let dict = self.dictionary(&slice.dimension)?;
+            let prefix = dict.code_structure().is_ok();
+            slices.push((slice.dimension.clone(), dict, slice.codes.clone(), prefix));
+        }
+
+        let facts = self.facts();
+        let mut measures = Vec::new();
+        let mut fact_measures: Vec<(&'a FCTMeta, Vec<Aggregate>)> = Vec::new();
+        for measure in &query.measures {
+            let fact = self.measure_fact(&facts, measure)?;
+            let column_type = fact
+                .table_schema
+                .get_column(&measure.column)
+                .map(|column| column.column_type())
+                .unwrap_or_default();
+            let output_type = measure_type(measure, column_type)?;
+            let aggregate = measure.aggregate(measure.column.clone()).alias(measure.output_name());
+            match fact_measures.iter_mut().find(|(f, _)| f.id == fact.id) {
+                Some((_, aggregates)) => aggregates.push(aggregate),
+                None => fact_measures.push((fact, vec![aggregate])),
+            }
+            measures.push((measure.output_name(), output_type));
+        }
+
+        let dimension_names: Vec<String> = dimensions.iter().map(|(.., name)| name.clone()).collect();
+        let mut seen = std::collections::HashSet::new();
+        for name in dimension_names.iter().chain(measures.iter().map(|(name, _)| name)) {
+            if !seen.insert(name.as_str()) {
+                return Err(CubeError::DuplicateColumn(name.clone()));
+            }
+        }
+
+        let mut fact_queries = Vec::new();
+        for (fact, aggregates) in fact_measures {
+            let groups = dimensions
+                .iter()
+                .map(|(dimension, dict, length, name)| {
+                    Ok(FactGroup { column: dimension_column(fact, dimension, dict)?, length: *length, name: name.clone() })
+                })
+                .collect::<Result<Vec<_>, CubeError>>()?;
+            let slices = slices
+                .iter()
+                .map(|(dimension, dict, codes, prefix)| {
+                    Ok(FactSlice { column: dimension_column(fact, dimension, dict)?, codes: codes.clone(), prefix: *prefix })
+                })
+                .collect::<Result<Vec<_>, CubeError>>()?;
+            fact_queries.push(FactQuery { fact, groups, measures: aggregates, slices });
+        }
+        Ok(CubePlan { dimensions: dimension_names, measures, facts: fact_queries })
+    }
+
+    /// 执行多维查询
+    ///
+    /// 各事实表的结果按维度值合并为一行，某个事实表没有的分组，其度量为 `NULL`。
+    ///
+    /// # 返回值
+    ///
+    /// 返回 `Result<RowDataSet, QueryError<S::Error>>`：
+    /// - `Ok(dataset)` - 数据集 ID 为模型 ID，列依次为维度列（`String`，编码文本）和度量列，
+    ///   度量列类型与 `RowDataSet::group_by` 的结果类型相同；行按维度排序，`NULL` 在最后
+    /// - `Err(QueryError::Cube)` - 查询无法解析，见 [`Cube::plan`]
+    /// - `Err(QueryError::Source)` - 事实表查询失败
+    pub async fn query<S: FactSource>(&self, query: &CubeQuery, source: &S) -> Result<RowDataSet, QueryError<S::Error>> {
+        let plan = self.plan(query)?;
+
+        let mut index: HashMap<String, usize> = HashMap::new();
+        let mut rows: Vec<(Vec<CellValue>, Vec<CellValue>)> = Vec::new();
+        let mut offset = 0;
+        for fact_query in &plan.facts {
+            let results = source
+                .aggregate(fact_query)
+                .await
+                .map_err(|source| QueryError::Source { fct_id: fact_query.fact.id.clone(), source })?;
+            for result in results {
+                let key: Vec<CellValue> =
+                    plan.dimensions.iter().map(|name| result.get(name).cloned().unwrap_or(CellValue::Null)).collect();
+                let hash_key = CellValue::from(key.clone()).to_string();
+                let slot = *index.entry(hash_key).or_insert_with(|| {
+                    rows.push((key, vec![CellValue::Null; plan.measures.len()]));
+                    rows.len() - 1
+                });
+                for (position, measure) in fact_query.measures.iter().enumerate() {
+                    if let Some(value) = result.get(&measure.output_name()) {
+                        rows[slot].1[offset + position] = value.clone();
+                    }
+                }
+            }
+            offset += fact_query.measures.len();
+        }
+        rows.sort_by(|(a, _), (b, _)| {
+            a.iter().zip(b).map(|(x, y)| compare_codes(x, y)).find(|ordering| ordering.is_ne()).unwrap_or(Ordering::Equal)
+        });
+
+        // 计划中度量按事实表分组，结果列按查询中度量的顺序
+        let positions: Vec<usize> = plan
+            .measures
+            .iter()
+            .map(|(name, _)| {
+                plan.facts.iter().flat_map(|fact| &fact.measures).position(|m| m.output_name() == *name).unwrap_or_default()
+            })
+            .collect();
+        let mut dataset = RowDataSet::new(self.model.id.clone());
+        for name in &plan.dimensions {
+            dataset.add_column(name.clone(), ColumnType::String).map_err(CubeError::from)?;
+        }
+        for (name, column_type) in &plan.measures {
+            dataset.add_column(name.clone(), *column_type).map_err(CubeError::from)?;
+        }
+        for (mut key, values) in rows {
+            key.extend(positions.iter().map(|position| values[*position].clone()));
+            dataset.add_row(key).map_err(CubeError::from)?;
+        }
+        Ok(dataset)
+    }
+
+    /// 度量所在的事实表
+    fn measure_fact(&self, facts: &[&'a FCTMeta], measure: &Measure) -> Result<&'a FCTMeta, CubeError> {
+        let has_column = |fact: &&FCTMeta| fact.table_schema.get_column_index(&measure.column).is_some();
+        if let Some(fct_id) = &measure.fact {
+            let fact = facts.iter().find(|fact| fact.id == *fct_id).ok_or_else(|| CubeError::UnknownFact(fct_id.clone()))?;
+            if !has_column(fact) {
+                return Err(CubeError::MeasureNotInFact { column: measure.column.clone(), fct_id: fct_id.clone() });
+            }
+            return Ok(fact);
+        }
+        let candidates: Vec<&'a FCTMeta> = facts.iter().copied().filter(has_column).collect();
+        match candidates.as_slice() {
+            [] => Err(CubeError::UnknownMeasure(measure.column.clone())),
+            [fact] => Ok(fact),
+            _ => Err(CubeError::AmbiguousMeasure {
+                column: measure.column.clone(),
+                facts: candidates.iter().map(|fact| fact.id.clone()).collect(),
+            }),
+        }
+    }
+}
+
+/// 度量结果类型，与 `RowDataSet::group_by` 相同
+fn measure_type(measure: &Measure, column_type: ColumnType) -> Result<ColumnType, CubeError> {
+    let unsupported = || CubeError::UnsupportedAggregate { func: measure.func, column: measure.column.clone() };
+    match measure.func {
+        AggFunc::Count | AggFunc::CountDistinct => Ok(ColumnType::I64),
+        AggFunc::Sum | AggFunc::Avg if !column_type.is_numeric() => Err(unsupported()),
+        AggFunc::Sum | AggFunc::Avg if matches!(column_type, ColumnType::F32 | ColumnType::F64) => Ok(ColumnType::F64),
+        AggFunc::Sum | AggFunc::Avg => Ok(ColumnType::Decimal),
+        AggFunc::Min | AggFunc::Max => Ok(column_type),
+        AggFunc::First | AggFunc::Last => Err(unsupported()),
+    }
+}
+
+/// 事实表中引用维度字典的第一列
+fn dimension_column(fact: &FCTMeta, dimension: &Dimension, dict: &DCTMeta) -> Result<String, CubeError> {
+    fact.table_schema
+        .columns
+        .iter()
+        .find(|column| column.foreign_dict().as_deref() == Some(dict.dct_id.as_str()))
+        .map(|column| column.col_id())
+        .ok_or_else(|| CubeError::DimensionNotInFact { dimension: dimension.to_string(), fct_id: fact.id.clone() })
+}
+
+/// 编码文本，与 SQL 中的 `::text` 一致
+fn code_text(value: &CellValue) -> Option<String> {
+    match value {
+        CellValue::Null => None,
+        CellValue::String(s) => Some(s.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+/// 维度值排序，`NULL` 在最后
+fn compare_codes(a: &CellValue, b: &CellValue) -> Ordering {
+    match (a.is_null(), b.is_null()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Greater,
+        (false, true) => Ordering::Less,
+        (false, false) => compare_values(a, b, Some(ColumnType::String)).unwrap_or(Ordering::Equal),
+    }
+}
+
+/// 交叉表：行表头、列表头为维度值的组合，单元格为度量值
+///
+/// 表头按在数据集中首次出现的顺序排列，没有数据的单元格为 `NULL`。
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Crosstab {
+    pub row_dimensions: Vec<String>,
+    pub column_dimensions: Vec<String>,
+    pub measure: String,
+    pub row_headers: Vec<Vec<CellValue>>,
+    pub column_headers: Vec<Vec<CellValue>>,
+    /// `cells[行][列]`
+    pub cells: Vec<Vec<CellValue>>,
+    #[serde(skip)]
+    row_types: Vec<ColumnType>,
+    #[serde(skip)]
+    measure_type: ColumnType,
+}
+
+impl Crosstab {
+    /// 由多维查询结果构建交叉表
+    ///
+    /// # 参数
+    ///
+    /// * `data` - 查询结果，通常来自 [`Cube::query`]
+    /// * `rows` - 行表头的维度列
+    /// * `columns` - 列表头的维度列
+    /// * `measure` - 单元格的度量列
+    ///
+    /// # 返回值
+    ///
+    /// - `Err(CubeError::DataSet(DataSetError::ColumnNotFound))` - 列不存在
+    /// - `Err(CubeError::DuplicateCell)` - 两行落在同一单元格，行列维度没有覆盖查询的所有维度
+    pub fn build(data: &RowDataSet, rows: &[&str], columns: &[&str], measure: &str) -> Result<Self, CubeError> {
+        let info = |name: &str| data.get_column_info(name).ok_or(DataSetError::ColumnNotFound);
+        let row_columns = rows.iter().map(|name| info(name)).collect::<Result<Vec<_>, _>>()?;
+        let column_columns = columns.iter().map(|name| info(name)).collect::<Result<Vec<_>, _>>()?;
+        let measure_column = info(measure)?;
+
+        let mut row_index: HashMap<String, usize> = HashMap::new();
+        let mut column_index: HashMap<String, usize> = HashMap::new();
+        let mut row_headers = Vec::new();
+        let mut column_headers = Vec::new();
+        let mut values: HashMap<(usize, usize), CellValue> = HashMap::new();
+        for index in 0..data.row_count() {
+            let row = data.get_row(index)?.values();
+            let header = |columns: &[&ColumnInfo]| -> Vec<CellValue> {
+                columns.iter().map(|info| row.get(info.index).cloned().unwrap_or(CellValue::Null)).collect()
+            };
+            let row_header = header(&row_columns);
+            let column_header = header(&column_columns);
+            let row_key = CellValue::from(row_header.clone()).to_string();
+            let column_key = CellValue::from(column_header.clone()).to_string();
+            let r = *row_index.entry(row_key.clone()).or_insert_with(|| {
+                row_headers.push(row_header);
+                row_headers.len() - 1
+            });
+            let c = *column_index.entry(column_key.clone()).or_insert_with(|| {
+                column_headers.push(column_header);
+                column_headers.len() - 1
+            });
+            let value = row.get(measure_column.index).cloned().unwrap_or(CellValue::Null);
+            if values.insert((r, c), value).is_some() {
+                return Err(CubeError::DuplicateCell { row: row_key, column: column_key });
+            }
+        }
+
+        let cells = (0..row_headers.len())
+            .map(|r| (0..column_headers.len()).map(|c| values.remove(&(r, c)).unwrap_or(CellValue::Null)).collect())
+            .collect();
+        Ok(Self {
+            row_dimensions: rows.iter().map(|name| name.to_string()).collect(),
+            column_dimensions: columns.iter().map(|name| name.to_string()).collect(),
+            measure: measure.to_string(),
+            row_headers,
+            column_headers,
+            cells,
+            row_types: row_columns.iter().map(|info| info.column_type).collect(),
+            measure_type: measure_column.column_type,
+        })
+    }
+
+    /// 单元格的值
+    pub fn get(&self, row: usize, column: usize) -> Option<&CellValue> {
+        self.cells.get(row)?.get(column)
+    }
+
+    /// 列表头的列名：维度值以 `_` 连接，`NULL` 为空字符串
+    pub fn column_names(&self) -> Vec<String> {
+        self.column_headers
+            .iter()
+            .map(|header| header.iter().map(|value| code_text(value).unwrap_or_default()).collect::<Vec<_>>().join("_"))
+            .collect()
+    }
+
+    /// 转为数据集：行表头列之后，每个列表头一列
+    ///
+    /// # 返回值
+    ///
+    /// - `Err(CubeError::DuplicateColumn)` - 列表头的列名与其他列重复
+    pub fn to_dataset(&self) -> Result<RowDataSet, CubeError> {
+        let mut dataset = RowDataSet::new(self.measure.clone());
+        let columns = self
+            .row_dimensions
+            .iter()
+            .cloned()
+            .zip(self.row_types.iter().copied())
+            .chain(self.column_names().into_iter().map(|name| (name, self.measure_type)));
+        for (name, column_type) in columns {
+            if dataset.get_column_info(&name).is_some() {
+                return Err(CubeError::DuplicateColumn(name));
+            }
+            dataset.add_column(name, column_type)?;
+        }
+        for (header, cells) in self.row_headers.iter().zip(&self.cells) {
+            dataset.add_row(header.iter().chain(cells).cloned().collect())?;
+        }
+        Ok(dataset)
+    }
+}
+
+/// 多维查询的错误
+#[derive(Debug, Error)]
+pub enum CubeError {
+    #[error("Model '{model}' has no {dimension} dictionary")]
+    DimensionNotDefined { model: String, dimension: String },
+    #[error("Unknown dictionary '{0}'")]
+    UnknownDictionary(String),
+    #[error("Fact '{fct_id}' has no column for the {dimension}")]
+    DimensionNotInFact { dimension: String, fct_id: String },
+    #[error("Model has no fact '{0}'")]
+    UnknownFact(String),
+    #[error("No fact of the model has the measure column '{0}'")]
+    UnknownMeasure(String),
+    #[error("Fact '{fct_id}' has no measure column '{column}'")]
+    MeasureNotInFact { column: String, fct_id: String },
+    #[error("Measure column '{column}' is in several facts: {}", .facts.join(", "))]
+    AmbiguousMeasure { column: String, facts: Vec<String> },
+    #[error("Query has no measures")]
+    NoMeasures,
+    #[error("Cannot compute {func:?} of '{column}'")]
+    UnsupportedAggregate { func: AggFunc, column: String },
+    #[error("Dictionary '{dct_id}' has no level {level}")]
+    InvalidLevel { dct_id: String, level: usize },
+    #[error("Duplicate result column '{0}'")]
+    DuplicateColumn(String),
+    #[error("Fact '{0}' has no OBJ_ID")]
+    MissingTable(String),
+    #[error("Several rows for crosstab cell {row} / {column}")]
+    DuplicateCell { row: String, column: String },
+    #[error(transparent)]
+    CodeTree(#[from] CodeTreeError),
+    #[error(transparent)]
+    DataSet(#[from] DataSetError),
+}
+
+/// 执行多维查询的错误
+#[derive(Debug, Error)]
+pub enum QueryError<E: std::error::Error + 'static> {
+    #[error(transparent)]
+    Cube(#[from] CubeError),
+    #[error("Query of fact '{fct_id}' failed")]
+    Source {
+        fct_id: String,
+        #[source]
+        source: E,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::model::meta::catalog::{Catalog, CatalogRows};
+    use crate::model::meta::tables::SYS_TABLE_NAMES;
+
+    fn catalog() -> MetaCatalog {
+        let mut rows = CatalogRows::new();
+        let columns = SYS_TABLE_NAMES::SYS_OBJCOLS;
+        for obj_id in ["T_SALES", "T_STOCK"] {
+            rows.push_json(SYS_TABLE_NAMES::SYS_OBJECTS, json!({"OBJ_ID": obj_id}));
+            rows.push_json(columns.clone(), json!({"OBJ_ID": obj_id, "COL_ID": "ND", "COL_ISFKEY": true, "COL_FOBJ": "DCT_YEAR"}));
+            rows.push_json(columns.clone(), json!({"OBJ_ID": obj_id, "COL_ID": "DW", "COL_ISFKEY": true, "COL_FOBJ": "DCT_UNIT"}));
+            rows.push_json(columns.clone(), json!({"OBJ_ID": obj_id, "COL_ID": "BZ", "COL_TYPE": "varchar"}));
+        }
+        rows.push_json(columns.clone(), json!({"OBJ_ID": "T_SALES", "COL_ID": "KM", "COL_ISFKEY": true, "COL_FOBJ": "DCT_KM"}));
+        rows.push_json(columns.clone(), json!({"OBJ_ID": "T_SALES", "COL_ID": "JE", "COL_TYPE": "decimal"}));
+        rows.push_json(columns, json!({"OBJ_ID": "T_STOCK", "COL_ID": "SL", "COL_TYPE": "int"}));
+        rows.push_json(SYS_TABLE_NAMES::SYS_DICTS, json!({"DCT_ID": "DCT_YEAR"}));
+        rows.push_json(SYS_TABLE_NAMES::SYS_DICTS, json!({"DCT_ID": "DCT_UNIT", "DCT_BMSTRU": "2-2"}));
+        rows.push_json(SYS_TABLE_NAMES::SYS_DICTS, json!({"DCT_ID": "DCT_KM", "DCT_BMSTRU": "4-2"}));
+        rows.push_json(SYS_TABLE_NAMES::SYS_FACTS, json!({"FCT_ID": "FCT_SALES", "OBJ_ID": "T_SALES"}));
+        rows.push_json(SYS_TABLE_NAMES::SYS_FACTS, json!({"FCT_ID": "FCT_STOCK", "OBJ_ID": "T_STOCK"}));
+        rows.push_json(
+            SYS_TABLE_NAMES::SYS_MODEL,
+            json!({"MDL_ID": "MDL_FIN", "MDL_NDCT": "DCT_YEAR", "MDL_UNITDCT": "DCT_UNIT", "MDL_KEYDCT": "DCT_KM"}),
+        );
+        rows.push_json(
+            SYS_TABLE_NAMES::SYS_MDL_CTN,
+            json!({"MDL_ID": "MDL_FIN", "CTN_ID": "1", "CTN_FCT1": "FCT_SALES", "CTN_FCT2": "FCT_STOCK"}),
+        );
+        MetaCatalog::from(&Catalog::build(&rows))
+    }
+
+    fn facts() -> HashMap<String, RowDataSet> {
+        let mut sales = RowDataSet::new("T_SALES".to_string());
+        for (name, column_type) in [("ND", ColumnType::I32), ("DW", ColumnType::String), ("KM", ColumnType::String), ("JE", ColumnType::Decimal), ("BZ", ColumnType::String)] {
+            sales.add_column(name.to_string(), column_type).unwrap();
+        }
+        for (nd, dw, km, je) in [
+            (2024, "0101", "100101", json!(10)),
+            (2024, "0102", "100102", json!(5)),
+            (2024, "0201", "100201", json!(2.5)),
+            (2025, "0101", "200101", json!(7)),
+        ] {
+            sales.add_row(vec![json!(nd), json!(dw), json!(km), je, json!(km)]).unwrap();
+        }
+        let mut stock = RowDataSet::new("T_STOCK".to_string());
+        for (name, column_type) in [("ND", ColumnType::I32), ("DW", ColumnType::String), ("SL", ColumnType::I32)] {
+            stock.add_column(name.to_string(), column_type).unwrap();
+        }
+        for (nd, dw, sl) in [(2023, "0101", 1), (2024, "0101", 3), (2024, "0202", 4)] {
+            stock.add_row(vec![json!(nd), json!(dw), json!(sl)]).unwrap();
+        }
+        HashMap::from([("FCT_SALES".to_string(), sales), ("FCT_STOCK".to_string(), stock)])
+    }
+
+    fn run(query: &CubeQuery) -> Result<RowDataSet, QueryError<DataSetError>> {
+        let catalog = catalog();
+        let model = catalog.dme_meta("MDL_FIN").unwrap();
+        futures::executor::block_on(Cube::new(&model, &catalog).query(query, &facts()))
+    }
+
+    fn column(data: &RowDataSet, name: &str) -> Vec<CellValue> {
+        data.get_column_values(name).unwrap().into_iter().cloned().collect()
+    }
+
+    #[test]
+    fn test_statement() {
+        let catalog = catalog();
+        let model = catalog.dme_meta("MDL_FIN").unwrap();
+        let query = CubeQuery::new()
+            .with_dimension(DimensionSpec::new(Dimension::Unit).level(1))
+            .with_dimension(Dimension::Year)
+            .with_measure(Measure::sum("JE"))
+            .with_measure(Measure::count_distinct("KM").alias("N"))
+            .with_slice(Slice::new(Dimension::Key(0), ["1001", "2001"]))
+            .with_slice(Slice::new(Dimension::Year, ["2024"]));
+        let plan = Cube::new(&model, &catalog).plan(&query).unwrap();
+        assert_eq!(plan.dimensions, vec!["UNIT".to_string(), "YEAR".to_string()]);
+        assert_eq!(plan.measures, vec![("SUM_JE".to_string(), ColumnType::Decimal), ("N".to_string(), ColumnType::I64)]);
+
+        let statement = plan.facts[0].statement(Some("fin")).unwrap();
+        assert_eq!(
+            statement.sql,
+            "SELECT to_jsonb(q)::text FROM (SELECT left(t.\"DW\"::text, 2) AS \"UNIT\", t.\"ND\"::text AS \"YEAR\", \
+             sum(t.\"JE\") AS \"SUM_JE\", count(DISTINCT t.\"KM\") AS \"N\" FROM \"fin\".\"T_SALES\" AS t \
+             WHERE (starts_with(t.\"KM\"::text, $1::text) OR starts_with(t.\"KM\"::text, $2::text)) \
+             AND t.\"ND\"::text IN ($3::text) GROUP BY 1, 2) AS q"
+        );
+        assert_eq!(statement.params, vec![Some("1001".to_string()), Some("2001".to_string()), Some("2024".to_string())]);
+    }
+
+    #[test]
+    fn test_roll_up_and_slice() {
+        let query = CubeQuery::new()
+            .with_dimension(DimensionSpec::new(Dimension::Key(0)).level(1).alias("KM"))
+            .with_measure(Measure::sum("JE"))
+            .with_slice(Slice::new(Dimension::Unit, ["01"]));
+        let result = run(&query).unwrap();
+        assert_eq!(column(&result, "KM"), vec![json!("1001"), json!("2001")]);
+        assert_eq!(column(&result, "SUM_JE"), vec![json!(15), json!(7)]);
+
+        // 非分级字典按编码相等切片
+        let query = CubeQuery::new().with_measure(Measure::count("JE")).with_slice(Slice::new(Dimension::Year, ["202"]));
+        assert_eq!(column(&run(&query).unwrap(), "COUNT_JE"), vec![json!(0)]);
+    }
+
+    #[test]
+    fn test_merge_facts() {
+        let query = CubeQuery::new()
+            .with_dimension(Dimension::Year)
+            .with_measure(Measure::sum("SL"))
+            .with_measure(Measure::sum("JE"))
+            .with_measure(Measure::max("BZ").fact("FCT_SALES"));
+        let result = run(&query).unwrap();
+        assert_eq!(result.dataset_id(), "MDL_FIN");
+        assert_eq!(column(&result, "YEAR"), vec![json!("2023"), json!("2024"), json!("2025")]);
+        assert_eq!(column(&result, "SUM_SL"), vec![json!(1), json!(7), json!(null)]);
+        assert_eq!(column(&result, "SUM_JE"), vec![json!(null), json!(17.5), json!(7)]);
+        assert_eq!(result.get_column_info("SUM_SL").unwrap().column_type, ColumnType::Decimal);
+        assert_eq!(result.get_column_info("SUM_SL").unwrap().index, 1);
+        assert_eq!(result.get_column_info("MAX_BZ").unwrap().index, 3);
+        assert_eq!(column(&result, "MAX_BZ"), vec![json!(null), json!("100201"), json!("200101")]);
+    }
+
+    #[test]
+    fn test_plan_errors() {
+        let catalog = catalog();
+        let model = catalog.dme_meta("MDL_FIN").unwrap();
+        let cube = Cube::new(&model, &catalog);
+        let plan = |query: CubeQuery| cube.plan(&query).unwrap_err();
+
+        assert!(matches!(plan(CubeQuery::new()), CubeError::NoMeasures));
+        assert!(matches!(plan(CubeQuery::new().with_measure(Measure::sum("XX"))), CubeError::UnknownMeasure(c) if c == "XX"));
+        assert!(matches!(
+            plan(CubeQuery::new().with_measure(Measure::max("BZ"))),
+            CubeError::AmbiguousMeasure { facts, .. } if facts == ["FCT_SALES", "FCT_STOCK"]
+        ));
+        assert!(matches!(
+            plan(CubeQuery::new().with_measure(Measure::sum("BZ").fact("FCT_SALES"))),
+            CubeError::UnsupportedAggregate { func: AggFunc::Sum, .. }
+        ));
+        assert!(matches!(
+            plan(CubeQuery::new().with_dimension(Dimension::Key(0)).with_measure(Measure::sum("SL"))),
+            CubeError::DimensionNotInFact { fct_id, .. } if fct_id == "FCT_STOCK"
+        ));
+        assert!(matches!(
+            plan(CubeQuery::new().with_dimension(Dimension::Month).with_measure(Measure::sum("SL"))),
+            CubeError::DimensionNotDefined { dimension, .. } if dimension == "month"
+        ));
+        assert!(matches!(
+            plan(CubeQuery::new().with_dimension(DimensionSpec::new(Dimension::Year).level(1)).with_measure(Measure::sum("SL"))),
+            CubeError::CodeTree(CodeTreeError::NotHierarchical(_))
+        ));
+        assert!(matches!(
+            plan(CubeQuery::new().with_dimension(DimensionSpec::new(Dimension::Unit).level(3)).with_measure(Measure::sum("SL"))),
+            CubeError::InvalidLevel { level: 3, .. }
+        ));
+        assert!(matches!(
+            plan(CubeQuery::new().with_dimension(DimensionSpec::new(Dimension::Year).alias("SUM_SL")).with_measure(Measure::sum("SL"))),
+            CubeError::DuplicateColumn(c) if c == "SUM_SL"
+        ));
+    }
+
+    #[test]
+    fn test_crosstab() {
+        let query = CubeQuery::new()
+            .with_dimension(DimensionSpec::new(Dimension::Unit).level(1))
+            .with_dimension(Dimension::Year)
+            .with_measure(Measure::sum("SL"));
+        let result = run(&query).unwrap();
+        let crosstab = Crosstab::build(&result, &["UNIT"], &["YEAR"], "SUM_SL").unwrap();
+        assert_eq!(crosstab.row_headers, vec![vec![json!("01")], vec![json!("02")]]);
+        assert_eq!(crosstab.column_names(), vec!["2023", "2024"]);
+        assert_eq!(crosstab.get(1, 1), Some(&json!(4)));
+        assert!(crosstab.get(1, 0).unwrap().is_null());
+
+        let table = crosstab.to_dataset().unwrap();
+        assert_eq!(table.get_column_info("2024").unwrap().column_type, ColumnType::Decimal);
+        assert_eq!(table.get_cell(0, "2024").unwrap(), &json!(3));
+
+        assert!(matches!(
+            Crosstab::build(&result, &[], &["YEAR"], "SUM_SL"),
+            Err(CubeError::DuplicateCell { .. })
+        ));
+    }
+}
@@ -1,17 +1,20 @@
-use std::sync::{Arc, RwLock};
-use std::collections::HashMap;
+use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 // use chrono::NaiveDateTime;
 // use rust_decimal::Decimal;
 use serde::{Serialize, Deserialize};
+use thiserror::Error;
 use crate::model::data::cell::CellValue;
 // use crate::data::cell::SimpleCellValue;
 // use crate::data::dataset::col::SYS_OBJCOLS;
 use crate::model::data::dataset::TableSchema;
 use crate::model::data::KeyValue;
 // use ospbase::data::dataset::ColumnDef;
-use lazy_static::lazy_static;
 
-use super::fields::{SYS_DICTS, SYS_OBJCOLS};
+use super::catalog::dict_dependencies;
+use super::fields::SYS_DICTS;
+use super::registry::MetaCatalog;
 
 // #[allow(non_camel_case_types)]
 // // Add new enum for DCT fields
@@ -71,11 +74,25 @@ pub struct DCTMeta {
     pub info: Option<KeyValue>,    
     pub table_schema: TableSchema,
     pub settings: Option<KeyValue>,
-    #[serde(skip)]  // 这个字段不需要序列化,直接从DCTMetaManager中获取
+    #[serde(skip)]  // 这个字段不需要序列化,加载时从元数据目录中关联
     pub dct_metas: Option<HashMap<String, Arc<DCTMeta>>>,
 }
 
 impl DCTMeta {
+    /// 创建字典元数据，字典属性（SYS_DICTS）通过 `set` 设置
+    pub fn new(dct_id: String, table_schema: TableSchema) -> Self {
+        let mut data = HashMap::new();
+        data.insert(SYS_DICTS::DCT_ID, CellValue::String(dct_id.clone()));
+        Self {
+            dct_id,
+            data,
+            info: None,
+            table_schema,
+            settings: None,
+            dct_metas: None,
+        }
+    }
+
     // pub fn new(
     //     id: String,
     //     name: String,
@@ -99,6 +116,11 @@ impl DCTMeta {
     pub fn set(&mut self, field: SYS_DICTS, value: CellValue) {
         self.data.insert(field, value);
     }
+
+    /// 所有已设置的字典属性（SYS_DICTS）
+    pub fn fields(&self) -> impl Iterator<Item = (&SYS_DICTS, &CellValue)> {
+        self.data.iter()
+    }
     pub fn get_string(&self, field: &SYS_DICTS) -> Option<String> {
         self.get(field).and_then(|v| v.as_str().map(|s| s.to_string()))
     }
@@ -120,66 +142,144 @@ impl DCTMeta {
         serde_json::to_string(self)
     }
 
-    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+    /// 从 JSON 解析字典元数据，并关联 `catalog` 中的外键字典
+    ///
+    /// # 返回值
+    ///
+    /// - `Ok(DCTMeta)` - 解析成功，所有外键字典都已关联
+    /// - `Err(DCTMetaError::Json)` - JSON 无效
+    /// - `Err(DCTMetaError::ForeignKey)` - 缺少外键字典或存在循环引用
+    ///
+    /// 原来的 `from_json(json)` 从全局的 `DCTMetaManager` 查找外键字典，迁移方式见
+    /// [`crate::model::meta::registry`]。
+    pub fn from_json(json: &str, catalog: &MetaCatalog) -> Result<Self, DCTMetaError> {
         let mut meta: DCTMeta = serde_json::from_str(json)?;
-        meta.process_foreign_keys();
+        meta.process_foreign_keys(catalog)?;
         Ok(meta)
     }
-    // 新增函数：处理外键关联
-    pub fn process_foreign_keys(&mut self) {
+
+    /// 引用的外键字典：外键列（`COL_ISFKEY`、`COL_FOBJ`）和 `DCT_FKEYDCT1..8`，按字典去重
+    ///
+    /// 返回 (引用所在的列, 字典 ID)，来自 `DCT_FKEYDCTn` 的引用没有列。
+    pub fn foreign_dicts(&self) -> Vec<(Option<String>, String)> {
+        dict_dependencies(self)
+    }
+
+    /// 从 `catalog` 关联外键字典，见 [`DCTMeta::resolve_foreign_keys`]
+    ///
+    /// 原来的 `process_foreign_keys()` 从全局的 `DCTMetaManager` 查找外键字典，迁移方式见
+    /// [`crate::model::meta::registry`]。
+    pub fn process_foreign_keys(&mut self, catalog: &MetaCatalog) -> Result<(), ForeignKeyError> {
+        self.resolve_foreign_keys(|dct_id| catalog.dct_meta(dct_id))
+    }
+
+    /// 按 `lookup` 关联外键字典，替换原有的 `dct_metas`
+    ///
+    /// 引用自身的外键列（树形字典的上级列）不算循环引用。
+    ///
+    /// # 返回值
+    ///
+    /// - `Ok(())` - 所有外键字典都已关联
+    /// - `Err(ForeignKeyError)` - 缺少的外键字典，以及经外键字典又引用回本字典的路径；
+    ///   找到的字典仍然关联
+    pub fn resolve_foreign_keys(&mut self, lookup: impl Fn(&str) -> Option<Arc<DCTMeta>>) -> Result<(), ForeignKeyError> {
+        let mut error = ForeignKeyError::default();
         let mut dct_metas = HashMap::new();
-        
-        // 遍历所有列定义
-        for column in &self.table_schema.columns {
-            // 检查是否是外键列
-            if column.col_isfkey().unwrap() {
-                // 如果有外键表名，获取对应的 DCTMeta
-                if let Some(foreign_table) = column.get(&SYS_OBJCOLS::COL_FOBJ) {
-                    if let Some(foreign_meta) = DCTMetaManager::get_meta(foreign_table.as_str().unwrap()) {
-                        dct_metas.insert(foreign_table.as_str().unwrap().to_string(), foreign_meta);
-                    }
-                }
+        let mut visited = HashSet::new();
+        for (column, dct_id) in dict_dependencies(self) {
+            let Some(meta) = lookup(&dct_id) else {
+                error.missing.push(ForeignKeyRef { owner: self.dct_id.clone(), column, dct_id });
+                continue;
+            };
+            if dct_id != self.dct_id
+                && let Some(mut path) = reference_path(&meta, &self.dct_id, &mut visited)
+            {
+                path.insert(0, self.dct_id.clone());
+                error.cycles.push(path);
             }
+            dct_metas.insert(dct_id, meta);
         }
-        
-        // 如果找到了相关的外键 meta，设置到 dct_metas
-        if !dct_metas.is_empty() {
-            self.dct_metas = Some(dct_metas);
-        }
+        self.dct_metas = (!dct_metas.is_empty()).then_some(dct_metas);
+        if error.is_empty() { Ok(()) } else { Err(error) }
     }
 }
 
-lazy_static! {
-    static ref DCT_METAS: RwLock<HashMap<String, Arc<DCTMeta>>> = RwLock::new(HashMap::new());
+/// `from` 经关联的外键字典到达 `target` 的路径，从 `from` 开始，到 `target` 结束
+fn reference_path(from: &DCTMeta, target: &str, visited: &mut HashSet<String>) -> Option<Vec<String>> {
+    let mut linked: Vec<_> = from.dct_metas.iter().flatten().collect();
+    linked.sort_by(|a, b| a.0.cmp(b.0));
+    for (dct_id, meta) in linked {
+        if dct_id == target {
+            return Some(vec![from.dct_id.clone(), target.to_string()]);
+        }
+        if visited.insert(dct_id.clone())
+            && let Some(mut path) = reference_path(meta, target, visited)
+        {
+            path.insert(0, from.dct_id.clone());
+            return Some(path);
+        }
+    }
+    None
 }
 
-#[derive(Debug)]
-pub struct DCTMetaManager;
+/// 外键引用：`owner` 的 `column` 列引用字典 `dct_id`，来自 `DCT_FKEYDCTn` 的引用没有列
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ForeignKeyRef {
+    pub owner: String,
+    pub column: Option<String>,
+    pub dct_id: String,
+}
 
-impl DCTMetaManager {
-    pub fn add_meta(meta: Arc<DCTMeta>) {
-        let mut metas = DCT_METAS.write().unwrap();
-        metas.insert(meta.dct_id.clone(), meta);
+impl fmt::Display for ForeignKeyRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.column {
+            Some(column) => write!(f, "column '{}' of '{}' -> '{}'", column, self.owner, self.dct_id),
+            None => write!(f, "'{}' -> '{}'", self.owner, self.dct_id),
+        }
     }
+}
 
-    pub fn get_meta(id: &str) -> Option<Arc<DCTMeta>> {
-        let metas = DCT_METAS.read().unwrap();
-        metas.get(id).cloned()
-    }
+/// 外键字典关联错误，列出所有缺少的字典和循环引用
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ForeignKeyError {
+    /// 引用了不存在的字典
+    pub missing: Vec<ForeignKeyRef>,
+    /// 循环引用的路径，首尾是同一个字典，例如 `[A, B, A]`
+    pub cycles: Vec<Vec<String>>,
+}
 
-    // 新增：清理所有元数据（主要用于测试）
-    pub fn clear() {
-        let mut metas = DCT_METAS.write().unwrap();
-        metas.clear();
+impl ForeignKeyError {
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty() && self.cycles.is_empty()
     }
+}
 
-    // 新增：获取所有元数据
-    pub fn get_all() -> Vec<Arc<DCTMeta>> {
-        let metas = DCT_METAS.read().unwrap();
-        metas.values().cloned().collect()
+impl fmt::Display for ForeignKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if !self.missing.is_empty() {
+            let missing: Vec<String> = self.missing.iter().map(ToString::to_string).collect();
+            parts.push(format!("Missing referenced dictionaries: {}", missing.join(", ")));
+        }
+        if !self.cycles.is_empty() {
+            let cycles: Vec<String> = self.cycles.iter().map(|path| path.join(" -> ")).collect();
+            parts.push(format!("Dictionary reference cycles: {}", cycles.join(", ")));
+        }
+        write!(f, "{}", parts.join("; "))
     }
 }
 
+impl std::error::Error for ForeignKeyError {}
+
+/// 解析字典元数据的错误
+#[derive(Debug, Error)]
+pub enum DCTMetaError {
+    #[error("Invalid dictionary JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    ForeignKey(#[from] ForeignKeyError),
+}
+
 // #[cfg(test)]
 // mod tests {
 //     use crate::data::dataset::col::{ColumnDef, ColumnType};
@@ -366,3 +466,82 @@ impl DCTMetaManager {
 //     }
 // }
 
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::model::data::dataset::col::ColumnDef;
+    use crate::model::data::dataset::TableSchemaBuilder;
+    use crate::model::meta::fields::SYS_OBJCOLS;
+
+    fn column(id: &str, foreign: Option<&str>) -> ColumnDef {
+        let mut column = ColumnDef::default();
+        column.set(SYS_OBJCOLS::COL_ID, json!(id));
+        if let Some(dct_id) = foreign {
+            column.set(SYS_OBJCOLS::COL_ISFKEY, json!("1"));
+            column.set(SYS_OBJCOLS::COL_FOBJ, json!(dct_id));
+        }
+        column
+    }
+
+    fn dict(dct_id: &str, columns: Vec<ColumnDef>) -> DCTMeta {
+        DCTMeta::new(dct_id.to_string(), TableSchemaBuilder::new().with_columns(columns).build())
+    }
+
+    #[test]
+    fn test_resolve_foreign_keys_lists_missing_dictionaries() {
+        // 没有 COL_ISFKEY 或 COL_FOBJ 的列不是外键列
+        let mut no_target = column("LB", None);
+        no_target.set(SYS_OBJCOLS::COL_ISFKEY, json!(true));
+        let mut staff = dict(
+            "DCT_STAFF",
+            vec![column("DM", None), no_target, column("BM", Some("DCT_DEPT")), column("GW", Some("DCT_POST"))],
+        );
+        staff.set(SYS_DICTS::DCT_FKEYDCT1, json!("DCT_UNIT"));
+        let dept = Arc::new(dict("DCT_DEPT", vec![column("DM", None)]));
+
+        let error = staff
+            .resolve_foreign_keys(|dct_id| (dct_id == "DCT_DEPT").then(|| dept.clone()))
+            .unwrap_err();
+        let missing: Vec<String> = error.missing.iter().map(ToString::to_string).collect();
+        assert_eq!(missing, vec!["column 'GW' of 'DCT_STAFF' -> 'DCT_POST'", "'DCT_STAFF' -> 'DCT_UNIT'"]);
+        assert!(error.cycles.is_empty());
+        // 找到的字典仍然关联
+        assert!(staff.dct_metas.as_ref().unwrap().contains_key("DCT_DEPT"));
+    }
+
+    #[test]
+    fn test_from_json_reports_missing_dictionaries() {
+        let staff = dict("DCT_STAFF", vec![column("DM", None), column("BM", Some("DCT_DEPT"))]);
+        let json = staff.to_json().unwrap();
+
+        let error = DCTMeta::from_json(&json, &MetaCatalog::new()).unwrap_err();
+        let DCTMetaError::ForeignKey(error) = error else { panic!("unexpected error: {error}") };
+        assert_eq!(error.to_string(), "Missing referenced dictionaries: column 'BM' of 'DCT_STAFF' -> 'DCT_DEPT'");
+
+        let mut catalog = MetaCatalog::new();
+        catalog.insert_dct_meta(Arc::new(dict("DCT_DEPT", vec![column("DM", None)])));
+        let staff = DCTMeta::from_json(&json, &catalog).unwrap();
+        assert!(staff.dct_metas.unwrap().contains_key("DCT_DEPT"));
+        assert!(matches!(DCTMeta::from_json("{", &catalog), Err(DCTMetaError::Json(_))));
+    }
+
+    #[test]
+    fn test_resolve_foreign_keys_detects_cycles() {
+        let mut a = dict("A", vec![column("B", Some("B"))]);
+        let mut b = dict("B", vec![column("A", Some("A"))]);
+        b.dct_metas = Some(HashMap::from([("A".to_string(), Arc::new(a.clone()))]));
+        let b = Arc::new(b);
+
+        let error = a.resolve_foreign_keys(|_| Some(b.clone())).unwrap_err();
+        assert_eq!(error.cycles, vec![vec!["A".to_string(), "B".into(), "A".into()]]);
+        assert_eq!(error.to_string(), "Dictionary reference cycles: A -> B -> A");
+
+        // 引用自身的上级列不算循环
+        let mut dept = dict("DCT_DEPT", vec![column("DM", None), column("SJDM", Some("DCT_DEPT"))]);
+        let registered = Arc::new(dept.clone());
+        assert!(dept.resolve_foreign_keys(|_| Some(registered.clone())).is_ok());
+    }
+}
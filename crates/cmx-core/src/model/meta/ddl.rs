@@ -0,0 +1,991 @@
+//! # Postgres DDL 生成模块
+//!
+//! 按对象定义（`SYS_OBJECTS`、`SYS_OBJCOLS`、`SYS_KEYS`、`SYS_INDEXS`）生成 Postgres 的建表语句，
+//! 并比较同一对象的两个版本，生成有序的 `ALTER TABLE` 迁移脚本。
+//!
+//! 列类型按 `COL_TYPE`、`COL_LEN`、`COL_PREC`、`COL_SCALE` 映射，见 [`PgType::of`]。多语言列
+//! （`COL_LANG`）保存各语言的文本，映射为 `jsonb`。`COL_DEFAULT` 中的常量默认值生成 `DEFAULT` 子句，
+//! 上下文默认值和公式默认值由行工厂（见 [`crate::model::data::dataset::factory`]）在应用中填充，不写入数据库。
+//!
+//! 标识符一律加双引号，保留元数据中 ID 的大小写。
+//!
+//! ## 迁移
+//!
+//! [`PgDdl::diff`] 按列 ID 和索引名称匹配两个版本，迁移步骤按以下顺序排列：
+//!
+//! 1. 表改名
+//! 2. 删除已删除或已修改的索引，删除已修改的主键
+//! 3. 新增列
+//! 4. 修改列类型、默认值、是否可空
+//! 5. 删除列
+//! 6. 重建主键，创建索引
+//! 7. 更新注释
+//!
+//! 可能失败或丢失数据的步骤带有 [`Hazard`] 标记：删除列、收窄列类型、把列改为非空，
+//! 以及新增没有默认值的非空列。列改名无法识别，会表现为删除旧列和新增新列。
+//!
+//! ## 示例
+//!
+//! ```rust
+//! use cmx_core::model::data::dataset::col::ColumnDef;
+//! use cmx_core::model::data::dataset::TableSchemaBuilder;
+//! use cmx_core::model::meta::ddl::{Hazard, PgDdl};
+//! use cmx_core::model::meta::fields::SYS_OBJCOLS;
+//! use serde_json::json;
+//!
+//! fn column(id: &str, column_type: &str, len: u32) -> ColumnDef {
+//!     let mut column = ColumnDef::default();
+//!     column.set(SYS_OBJCOLS::COL_ID, json!(id));
+//!     column.set(SYS_OBJCOLS::COL_TYPE, json!(column_type));
+//!     column.set(SYS_OBJCOLS::COL_LEN, json!(len));
+//!     column
+//! }
+//!
+//! let old = TableSchemaBuilder::new()
+//!     .with_obj_id("T_ITEM".to_string())
+//!     .with_columns(vec![column("DM", "varchar", 30), column("MC", "varchar", 100)])
+//!     .build();
+//! let new = TableSchemaBuilder::new()
+//!     .with_obj_id("T_ITEM".to_string())
+//!     .with_columns(vec![column("DM", "varchar", 20), column("SL", "int", 0)])
+//!     .build();
+//!
+//! let ddl = PgDdl::new();
+//! assert_eq!(
+//!     ddl.create_table(&old).unwrap()[0],
+//!     "CREATE TABLE \"T_ITEM\" (\n    \"DM\" varchar(30),\n    \"MC\" varchar(100)\n)"
+//! );
+//!
+//! let migration = ddl.diff(&old, &new).unwrap();
+//! assert_eq!(migration.statements(), vec![
+//!     "ALTER TABLE \"T_ITEM\" ADD COLUMN \"SL\" integer",
+//!     "ALTER TABLE \"T_ITEM\" ALTER COLUMN \"DM\" TYPE varchar(20) USING \"DM\"::varchar(20)",
+//!     "ALTER TABLE \"T_ITEM\" DROP COLUMN \"MC\"",
+//! ]);
+//! let hazards: Vec<_> = migration.unsafe_steps().filter_map(|step| step.hazard).collect();
+//! assert_eq!(hazards, vec![Hazard::NarrowType, Hazard::DropColumn]);
+//! ```
+
+use std::collections::HashSet;
+use std::fmt;
+
+use thiserror::Error;
+
+use crate::model::data::cell::CellValue;
+use crate::model::data::dataset::col::{flag_value, ColumnDef};
+use crate::model::data::dataset::factory::DefaultValue;
+use crate::model::data::dataset::key::IndexDef;
+use crate::model::data::dataset::{ColumnType, TableSchema};
+use crate::model::meta::fields::{SYS_OBJCOLS, SYS_OBJECTS};
+
+/// DDL 生成错误
+#[derive(Debug, Error)]
+pub enum DdlError {
+    #[error("Object has no OBJ_ID")]
+    MissingObjectId,
+    #[error("Column {0} of '{1}' has no COL_ID")]
+    MissingColumnId(usize, String),
+    #[error("Duplicate column '{column}' in '{table}'")]
+    DuplicateColumn { table: String, column: String },
+    #[error("Key or index of '{table}' references unknown column '{column}'")]
+    UnknownColumn { table: String, column: String },
+    #[error("Invalid default value for column '{column}': {value}")]
+    InvalidDefault { column: String, value: String },
+}
+
+/// Postgres 列类型
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PgType {
+    Boolean,
+    SmallInt,
+    Integer,
+    BigInt,
+    Real,
+    Double,
+    Numeric { precision: Option<u32>, scale: Option<u32> },
+    Varchar(u32),
+    Text,
+    Date,
+    Time,
+    Timestamp,
+    TimestampTz,
+    Uuid,
+    Bytea,
+    Jsonb,
+}
+
+impl PgType {
+    /// 列定义对应的 Postgres 类型
+    ///
+    /// | 列类型 | Postgres 类型 |
+    /// |--------|---------------|
+    /// | `Bool` | `boolean` |
+    /// | `I8`、`I16`、`U8` | `smallint` |
+    /// | `I32`、`U16` | `integer` |
+    /// | `I64`、`U32` | `bigint` |
+    /// | `U64` | `numeric(20,0)` |
+    /// | `F32`、`F64` | `real`、`double precision` |
+    /// | `Decimal` | `numeric(COL_PREC,COL_SCALE)`，未设置精度时为 `numeric` |
+    /// | `String` | `varchar(COL_LEN)`，未设置长度时为 `text` |
+    /// | `Date`、`Time`、`DateTime`、`DateTimeTz` | `date`、`time`、`timestamp`、`timestamptz` |
+    /// | `Uuid`、`Binary`、`Json` | `uuid`、`bytea`、`jsonb` |
+    ///
+    /// 多语言列为 `jsonb`。
+    pub fn of(column: &ColumnDef) -> Self {
+        if column.is_multilingual() {
+            return PgType::Jsonb;
+        }
+        let positive = |field| column.get_u32(field).filter(|v| *v > 0);
+        match column.column_type() {
+            ColumnType::Bool => PgType::Boolean,
+            ColumnType::I8 | ColumnType::I16 | ColumnType::U8 => PgType::SmallInt,
+            ColumnType::I32 | ColumnType::U16 => PgType::Integer,
+            ColumnType::I64 | ColumnType::U32 => PgType::BigInt,
+            ColumnType::U64 => PgType::Numeric { precision: Some(20), scale: Some(0) },
+            ColumnType::F32 => PgType::Real,
+            ColumnType::F64 => PgType::Double,
+            ColumnType::Decimal => match positive(&SYS_OBJCOLS::COL_PREC) {
+                Some(precision) => PgType::Numeric {
+                    precision: Some(precision),
+                    scale: column.get_u32(&SYS_OBJCOLS::COL_SCALE),
+                },
+                None => PgType::Numeric { precision: None, scale: None },
+            },
+            ColumnType::String => positive(&SYS_OBJCOLS::COL_LEN).map_or(PgType::Text, PgType::Varchar),
+            ColumnType::Date => PgType::Date,
+            ColumnType::Time => PgType::Time,
+            ColumnType::DateTime => PgType::Timestamp,
+            ColumnType::DateTimeTz => PgType::TimestampTz,
+            ColumnType::Uuid => PgType::Uuid,
+            ColumnType::Binary => PgType::Bytea,
+            ColumnType::Json => PgType::Jsonb,
+        }
+    }
+
+    /// 整数类型的宽度等级和最大十进制位数
+    fn integer_rank(&self) -> Option<(u8, u32)> {
+        match self {
+            PgType::SmallInt => Some((1, 5)),
+            PgType::Integer => Some((2, 10)),
+            PgType::BigInt => Some((3, 19)),
+            _ => None,
+        }
+    }
+
+    /// 把列从当前类型改为 `to` 时，已有的值是否都能无损转换
+    pub fn widens_to(&self, to: &PgType) -> bool {
+        if self == to || *to == PgType::Text {
+            return true;
+        }
+        if let Some((from_rank, digits)) = self.integer_rank() {
+            return match to {
+                PgType::SmallInt | PgType::Integer | PgType::BigInt => {
+                    to.integer_rank().is_some_and(|(to_rank, _)| to_rank >= from_rank)
+                }
+                PgType::Numeric { precision: None, .. } => true,
+                PgType::Numeric { precision: Some(p), scale } => p.saturating_sub(scale.unwrap_or(0)) >= digits,
+                PgType::Real => from_rank == 1,
+                PgType::Double => from_rank <= 2,
+                _ => false,
+            };
+        }
+        match (self, to) {
+            (PgType::Real, PgType::Double) => true,
+            (PgType::Numeric { .. }, PgType::Numeric { precision: None, .. }) => true,
+            (PgType::Numeric { precision: None, .. }, PgType::Numeric { .. }) => false,
+            (
+                PgType::Numeric { precision: Some(p1), scale: s1 },
+                PgType::Numeric { precision: Some(p2), scale: s2 },
+            ) => {
+                let (s1, s2) = (s1.unwrap_or(0), s2.unwrap_or(0));
+                p2.saturating_sub(s2) >= p1.saturating_sub(s1) && s2 >= s1
+            }
+            (PgType::Varchar(from), PgType::Varchar(to)) => to >= from,
+            (PgType::Date, PgType::Timestamp | PgType::TimestampTz) => true,
+            (PgType::Timestamp, PgType::TimestampTz) => true,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for PgType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PgType::Boolean => write!(f, "boolean"),
+            PgType::SmallInt => write!(f, "smallint"),
+            PgType::Integer => write!(f, "integer"),
+            PgType::BigInt => write!(f, "bigint"),
+            PgType::Real => write!(f, "real"),
+            PgType::Double => write!(f, "double precision"),
+            PgType::Numeric { precision: None, .. } => write!(f, "numeric"),
+            PgType::Numeric { precision: Some(p), scale: None } => write!(f, "numeric({})", p),
+            PgType::Numeric { precision: Some(p), scale: Some(s) } => write!(f, "numeric({},{})", p, s),
+            PgType::Varchar(len) => write!(f, "varchar({})", len),
+            PgType::Text => write!(f, "text"),
+            PgType::Date => write!(f, "date"),
+            PgType::Time => write!(f, "time"),
+            PgType::Timestamp => write!(f, "timestamp"),
+            PgType::TimestampTz => write!(f, "timestamptz"),
+            PgType::Uuid => write!(f, "uuid"),
+            PgType::Bytea => write!(f, "bytea"),
+            PgType::Jsonb => write!(f, "jsonb"),
+        }
+    }
+}
+
+/// 迁移步骤的风险
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hazard {
+    /// 删除列，列中的数据会丢失
+    DropColumn,
+    /// 收窄列类型，已有的值可能无法转换或被截断
+    NarrowType,
+    /// 把列改为非空，已有的 `NULL` 值会使迁移失败
+    SetNotNull,
+    /// 新增没有默认值的非空列，表中已有数据时迁移失败
+    RequiredColumn,
+}
+
+impl fmt::Display for Hazard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            Hazard::DropColumn => "删除列，列中的数据会丢失",
+            Hazard::NarrowType => "收窄列类型，已有的值可能无法转换",
+            Hazard::SetNotNull => "改为非空，已有的空值会使迁移失败",
+            Hazard::RequiredColumn => "新增没有默认值的非空列，表中已有数据时迁移失败",
+        };
+        f.write_str(text)
+    }
+}
+
+/// 结构变化
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaChange {
+    RenameTable { from: String, to: String },
+    DropIndex(String),
+    DropPrimaryKey,
+    AddColumn(String),
+    AlterType { column: String, from: PgType, to: PgType },
+    SetDefault(String),
+    DropDefault(String),
+    SetNotNull(String),
+    DropNotNull(String),
+    DropColumn(String),
+    AddPrimaryKey(Vec<String>),
+    CreateIndex(String),
+    /// 表注释（`None`）或列注释
+    Comment(Option<String>),
+}
+
+/// 迁移步骤
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationStep {
+    pub change: SchemaChange,
+    pub sql: String,
+    pub hazard: Option<Hazard>,
+}
+
+/// 按执行顺序排列的迁移步骤
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Migration {
+    pub steps: Vec<MigrationStep>,
+}
+
+impl Migration {
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// 是否没有带风险的步骤
+    pub fn is_safe(&self) -> bool {
+        self.steps.iter().all(|step| step.hazard.is_none())
+    }
+
+    /// 带风险的步骤
+    pub fn unsafe_steps(&self) -> impl Iterator<Item = &MigrationStep> {
+        self.steps.iter().filter(|step| step.hazard.is_some())
+    }
+
+    /// 按顺序排列的 SQL 语句
+    pub fn statements(&self) -> Vec<&str> {
+        self.steps.iter().map(|step| step.sql.as_str()).collect()
+    }
+
+    /// 迁移脚本，带风险的语句前加注释说明
+    ///
+    /// Postgres 的 DDL 支持事务，需要原子执行时由调用方包在 `BEGIN`/`COMMIT` 中。
+    pub fn to_sql(&self) -> String {
+        let mut script = String::new();
+        for step in &self.steps {
+            if let Some(hazard) = step.hazard {
+                script.push_str(&format!("-- 不安全：{}\n", hazard));
+            }
+            script.push_str(&step.sql);
+            script.push_str(";\n");
+        }
+        script
+    }
+
+    fn push(&mut self, change: SchemaChange, sql: String, hazard: Option<Hazard>) {
+        self.steps.push(MigrationStep { change, sql, hazard });
+    }
+}
+
+/// 给标识符加双引号
+pub fn quote_ident(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
+/// 表名，`schema` 不为空时加上 schema 前缀
+pub(super) fn qualified_name(schema: Option<&str>, name: &str) -> String {
+    match schema {
+        Some(schema) => format!("{}.{}", quote_ident(schema), quote_ident(name)),
+        None => quote_ident(name),
+    }
+}
+
+/// 主键列：取第一个键定义的主键列，没有键定义时取 `COL_ISKEY` 为真的列
+pub(super) fn primary_key_columns(schema: &TableSchema) -> Vec<String> {
+    let primary_key = schema.primary_key_columns();
+    if !primary_key.is_empty() {
+        return primary_key;
+    }
+    schema
+        .columns
+        .iter()
+        .filter(|column| column.get(&SYS_OBJCOLS::COL_ISKEY).and_then(flag_value).unwrap_or(false))
+        .map(ColumnDef::col_id)
+        .collect()
+}
+
+/// 字符串常量
+pub fn quote_literal(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+/// 单元格值对应的 SQL 常量
+fn sql_literal(value: &CellValue) -> String {
+    match value {
+        CellValue::Null => "NULL".to_string(),
+        CellValue::Bool(true) => "TRUE".to_string(),
+        CellValue::Bool(false) => "FALSE".to_string(),
+        CellValue::Number(n) => n.to_string(),
+        CellValue::String(s) => quote_literal(s),
+        other => quote_literal(&other.to_string()),
+    }
+}
+
+/// 整理后的列定义
+struct PgColumn {
+    id: String,
+    pg_type: PgType,
+    nullable: bool,
+    default: Option<String>,
+    comment: Option<String>,
+}
+
+impl PgColumn {
+    fn definition(&self) -> String {
+        let mut sql = format!("{} {}", quote_ident(&self.id), self.pg_type);
+        if let Some(default) = &self.default {
+            sql.push_str(&format!(" DEFAULT {}", default));
+        }
+        if !self.nullable {
+            sql.push_str(" NOT NULL");
+        }
+        sql
+    }
+}
+
+/// 整理后的索引定义
+#[derive(PartialEq)]
+struct PgIndex {
+    name: String,
+    columns: Vec<String>,
+    unique: bool,
+    hash: bool,
+    clustered: bool,
+}
+
+/// 整理并校验后的对象定义
+struct PgTable {
+    name: String,
+    comment: Option<String>,
+    table_space: Option<String>,
+    index_space: Option<String>,
+    columns: Vec<PgColumn>,
+    primary_key: Vec<String>,
+    indexes: Vec<PgIndex>,
+}
+
+fn object_text(schema: &TableSchema, field: &SYS_OBJECTS) -> Option<String> {
+    match schema.get(field)? {
+        CellValue::String(s) => Some(s.trim().to_string()).filter(|s| !s.is_empty()),
+        _ => None,
+    }
+}
+
+impl PgTable {
+    fn new(schema: &TableSchema) -> Result<Self, DdlError> {
+        let name = schema
+            .obj_id()
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .ok_or(DdlError::MissingObjectId)?
+            .to_string();
+
+        let mut seen = HashSet::new();
+        let mut columns = Vec::with_capacity(schema.columns.len());
+        for (index, column) in schema.columns.iter().enumerate() {
+            let id = column.col_id();
+            if id.is_empty() {
+                return Err(DdlError::MissingColumnId(index, name));
+            }
+            if !seen.insert(id.clone()) {
+                return Err(DdlError::DuplicateColumn { table: name, column: id });
+            }
+            let default = Self::default_sql(column, &id)?;
+            columns.push(PgColumn {
+                pg_type: PgType::of(column),
+                nullable: column.is_nullable(),
+                default,
+                comment: column.col_mc(),
+                id,
+            });
+        }
+
+        let primary_key = primary_key_columns(schema);
+
+        let indexes = schema
+            .indexes
+            .iter()
+            .map(|index| Self::index(&name, index))
+            .collect::<Vec<_>>();
+
+        let referenced = primary_key.iter().chain(indexes.iter().flat_map(|index| index.columns.iter()));
+        for column in referenced {
+            if !seen.contains(column) {
+                return Err(DdlError::UnknownColumn { table: name, column: column.clone() });
+            }
+        }
+
+        Ok(Self {
+            comment: object_text(schema, &SYS_OBJECTS::OBJ_MC),
+            table_space: object_text(schema, &SYS_OBJECTS::TABLE_SPACE),
+            index_space: object_text(schema, &SYS_OBJECTS::INDEX_SPACE),
+            name,
+            columns,
+            primary_key,
+            indexes,
+        })
+    }
+
+    /// 常量默认值对应的 `DEFAULT` 表达式，上下文和公式默认值返回 `None`
+    fn default_sql(column: &ColumnDef, id: &str) -> Result<Option<String>, DdlError> {
+        let Some(text) = column.get_str(&SYS_OBJCOLS::COL_DEFAULT) else {
+            return Ok(None);
+        };
+        let invalid = || DdlError::InvalidDefault { column: id.to_string(), value: text.clone() };
+        match text.parse::<DefaultValue>().map_err(|_| invalid())? {
+            DefaultValue::Literal(value) => {
+                let value = column.column_type().coerce(value).map_err(|_| invalid())?;
+                Ok(Some(sql_literal(&value)))
+            }
+            DefaultValue::Context(_) | DefaultValue::Formula(_) => Ok(None),
+        }
+    }
+
+    fn index(table: &str, index: &IndexDef) -> PgIndex {
+        let columns = index.columns();
+        let mut name = index.inx_name();
+        if name.is_empty() {
+            name = format!("{}_{}_idx", table, columns.join("_"));
+        }
+        PgIndex {
+            name,
+            columns,
+            unique: index.is_unique(),
+            hash: index.is_hash(),
+            clustered: index.is_clustered(),
+        }
+    }
+
+    fn column(&self, id: &str) -> Option<&PgColumn> {
+        self.columns.iter().find(|column| column.id == id)
+    }
+
+    fn index_by_name(&self, name: &str) -> Option<&PgIndex> {
+        self.indexes.iter().find(|index| index.name == name)
+    }
+}
+
+/// Postgres DDL 生成器
+#[derive(Debug, Clone, Default)]
+pub struct PgDdl {
+    schema: Option<String>,
+}
+
+impl PgDdl {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 表和索引所在的 Postgres schema，未设置时使用连接的 `search_path`
+    pub fn with_schema(mut self, schema: impl Into<String>) -> Self {
+        self.schema = Some(schema.into());
+        self
+    }
+
+    fn qualified(&self, name: &str) -> String {
+        qualified_name(self.schema.as_deref(), name)
+    }
+
+    /// 建表语句：`CREATE TABLE`、`CREATE INDEX`、聚集索引的 `CLUSTER` 和注释
+    ///
+    /// # 返回值
+    ///
+    /// 按执行顺序排列的语句，不带结尾的分号。对象没有 `OBJ_ID`、列没有 `COL_ID`、列 ID 重复，
+    /// 或者主键、索引引用了不存在的列时返回错误。
+    pub fn create_table(&self, schema: &TableSchema) -> Result<Vec<String>, DdlError> {
+        let table = PgTable::new(schema)?;
+        let name = self.qualified(&table.name);
+
+        let mut lines: Vec<String> = table.columns.iter().map(PgColumn::definition).collect();
+        if !table.primary_key.is_empty() {
+            lines.push(self.primary_key_clause(&table));
+        }
+        let mut create = format!("CREATE TABLE {} (\n    {}\n)", name, lines.join(",\n    "));
+        if let Some(space) = &table.table_space {
+            create.push_str(&format!(" TABLESPACE {}", quote_ident(space)));
+        }
+
+        let mut statements = vec![create];
+        for index in &table.indexes {
+            statements.extend(self.create_index(&table, index));
+        }
+        if let Some(comment) = &table.comment {
+            statements.push(format!("COMMENT ON TABLE {} IS {}", name, quote_literal(comment)));
+        }
+        for column in &table.columns {
+            if let Some(comment) = &column.comment {
+                statements.push(self.column_comment(&table, &column.id, Some(comment)));
+            }
+        }
+        Ok(statements)
+    }
+
+    /// 建表脚本，语句以分号和换行分隔
+    pub fn create_script(&self, schema: &TableSchema) -> Result<String, DdlError> {
+        Ok(self
+            .create_table(schema)?
+            .into_iter()
+            .map(|statement| statement + ";\n")
+            .collect())
+    }
+
+    /// 比较同一对象的两个版本，生成从 `old` 迁移到 `new` 的步骤
+    pub fn diff(&self, old: &TableSchema, new: &TableSchema) -> Result<Migration, DdlError> {
+        let old = PgTable::new(old)?;
+        let new = PgTable::new(new)?;
+        let name = self.qualified(&new.name);
+        let alter = |action: String| format!("ALTER TABLE {} {}", name, action);
+        let mut migration = Migration::default();
+
+        if old.name != new.name {
+            migration.push(
+                SchemaChange::RenameTable { from: old.name.clone(), to: new.name.clone() },
+                format!("ALTER TABLE {} RENAME TO {}", self.qualified(&old.name), quote_ident(&new.name)),
+                None,
+            );
+        }
+
+        for index in &old.indexes {
+            if new.index_by_name(&index.name) != Some(index) {
+                migration.push(
+                    SchemaChange::DropIndex(index.name.clone()),
+                    format!("DROP INDEX {}", self.qualified(&index.name)),
+                    None,
+                );
+            }
+        }
+        let primary_key_changed = old.primary_key != new.primary_key;
+        if primary_key_changed && !old.primary_key.is_empty() {
+            migration.push(
+                SchemaChange::DropPrimaryKey,
+                alter(format!("DROP CONSTRAINT {}", quote_ident(&format!("{}_pkey", old.name)))),
+                None,
+            );
+        }
+
+        for column in &new.columns {
+            if old.column(&column.id).is_none() {
+                let hazard = (!column.nullable && column.default.is_none()).then_some(Hazard::RequiredColumn);
+                migration.push(
+                    SchemaChange::AddColumn(column.id.clone()),
+                    alter(format!("ADD COLUMN {}", column.definition())),
+                    hazard,
+                );
+            }
+        }
+
+        for column in &new.columns {
+            let Some(previous) = old.column(&column.id) else {
+                continue;
+            };
+            let ident = quote_ident(&column.id);
+            if previous.pg_type != column.pg_type {
+                let hazard = (!previous.pg_type.widens_to(&column.pg_type)).then_some(Hazard::NarrowType);
+                migration.push(
+                    SchemaChange::AlterType {
+                        column: column.id.clone(),
+                        from: previous.pg_type.clone(),
+                        to: column.pg_type.clone(),
+                    },
+                    alter(format!("ALTER COLUMN {} TYPE {} USING {}::{}", ident, column.pg_type, ident, column.pg_type)),
+                    hazard,
+                );
+            }
+            if previous.default != column.default {
+                match &column.default {
+                    Some(default) => migration.push(
+                        SchemaChange::SetDefault(column.id.clone()),
+                        alter(format!("ALTER COLUMN {} SET DEFAULT {}", ident, default)),
+                        None,
+                    ),
+                    None => migration.push(
+                        SchemaChange::DropDefault(column.id.clone()),
+                        alter(format!("ALTER COLUMN {} DROP DEFAULT", ident)),
+                        None,
+                    ),
+                }
+            }
+            if previous.nullable && !column.nullable {
+                migration.push(
+                    SchemaChange::SetNotNull(column.id.clone()),
+                    alter(format!("ALTER COLUMN {} SET NOT NULL", ident)),
+                    Some(Hazard::SetNotNull),
+                );
+            } else if !previous.nullable && column.nullable {
+                migration.push(
+                    SchemaChange::DropNotNull(column.id.clone()),
+                    alter(format!("ALTER COLUMN {} DROP NOT NULL", ident)),
+                    None,
+                );
+            }
+        }
+
+        for column in &old.columns {
+            if new.column(&column.id).is_none() {
+                migration.push(
+                    SchemaChange::DropColumn(column.id.clone()),
+                    alter(format!("DROP COLUMN {}", quote_ident(&column.id))),
+                    Some(Hazard::DropColumn),
+                );
+            }
+        }
+
+        if primary_key_changed && !new.primary_key.is_empty() {
+            migration.push(
+                SchemaChange::AddPrimaryKey(new.primary_key.clone()),
+                alter(format!("ADD {}", self.primary_key_clause(&new))),
+                None,
+            );
+        }
+        for index in &new.indexes {
+            if old.index_by_name(&index.name) != Some(index) {
+                for sql in self.create_index(&new, index) {
+                    migration.push(SchemaChange::CreateIndex(index.name.clone()), sql, None);
+                }
+            }
+        }
+
+        if old.comment != new.comment {
+            let comment = new.comment.as_deref().map_or("NULL".to_string(), quote_literal);
+            migration.push(
+                SchemaChange::Comment(None),
+                format!("COMMENT ON TABLE {} IS {}", name, comment),
+                None,
+            );
+        }
+        for column in &new.columns {
+            let previous = old.column(&column.id).and_then(|previous| previous.comment.as_ref());
+            if previous != column.comment.as_ref() && (previous.is_some() || column.comment.is_some()) {
+                migration.push(
+                    SchemaChange::Comment(Some(column.id.clone())),
+                    self.column_comment(&new, &column.id, column.comment.as_deref()),
+                    None,
+                );
+            }
+        }
+        Ok(migration)
+    }
+
+    fn primary_key_clause(&self, table: &PgTable) -> String {
+        let columns: Vec<String> = table.primary_key.iter().map(|column| quote_ident(column)).collect();
+        let mut clause = format!(
+            "CONSTRAINT {} PRIMARY KEY ({})",
+            quote_ident(&format!("{}_pkey", table.name)),
+            columns.join(", ")
+        );
+        if let Some(space) = &table.index_space {
+            clause.push_str(&format!(" USING INDEX TABLESPACE {}", quote_ident(space)));
+        }
+        clause
+    }
+
+    fn create_index(&self, table: &PgTable, index: &PgIndex) -> Vec<String> {
+        let columns: Vec<String> = index.columns.iter().map(|column| quote_ident(column)).collect();
+        let mut sql = format!(
+            "CREATE {}INDEX {} ON {}{} ({})",
+            if index.unique { "UNIQUE " } else { "" },
+            quote_ident(&index.name),
+            self.qualified(&table.name),
+            if index.hash { " USING hash" } else { "" },
+            columns.join(", ")
+        );
+        if let Some(space) = &table.index_space {
+            sql.push_str(&format!(" TABLESPACE {}", quote_ident(space)));
+        }
+        let mut statements = vec![sql];
+        if index.clustered {
+            statements.push(format!(
+                "CLUSTER {} USING {}",
+                self.qualified(&table.name),
+                quote_ident(&index.name)
+            ));
+        }
+        statements
+    }
+
+    fn column_comment(&self, table: &PgTable, column: &str, comment: Option<&str>) -> String {
+        format!(
+            "COMMENT ON COLUMN {}.{} IS {}",
+            self.qualified(&table.name),
+            quote_ident(column),
+            comment.map_or("NULL".to_string(), quote_literal)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::model::data::dataset::key::KeyDef;
+    use crate::model::data::dataset::TableSchemaBuilder;
+    use crate::model::meta::fields::{SYS_INDEXS, SYS_KEYS};
+
+    fn column(id: &str, column_type: &str) -> ColumnDef {
+        let mut column = ColumnDef::default();
+        column.set(SYS_OBJCOLS::COL_ID, json!(id));
+        column.set(SYS_OBJCOLS::COL_TYPE, json!(column_type));
+        column
+    }
+
+    fn with(mut column: ColumnDef, field: SYS_OBJCOLS, value: CellValue) -> ColumnDef {
+        column.set(field, value);
+        column
+    }
+
+    fn index(name: &str, cols: &str, inx_type: &str) -> IndexDef {
+        let mut index = IndexDef::default();
+        index.set(SYS_INDEXS::INX_ID, json!(name));
+        index.set(SYS_INDEXS::INX_COLS, json!(cols));
+        index.set(SYS_INDEXS::INX_TYPE, json!(inx_type));
+        index
+    }
+
+    fn primary_key(columns: &[&str]) -> KeyDef {
+        let mut key = KeyDef::default();
+        key.set(SYS_KEYS::KEY_ID, json!("PK"));
+        key.set(SYS_KEYS::KEY_PINDEX1, json!(columns[0]));
+        if let Some(second) = columns.get(1) {
+            key.set(SYS_KEYS::KEY_PINDEX2, json!(second));
+        }
+        key
+    }
+
+    fn schema(id: &str, columns: Vec<ColumnDef>, keys: Vec<KeyDef>, indexes: Vec<IndexDef>) -> TableSchema {
+        TableSchemaBuilder::new()
+            .with_obj_id(id.to_string())
+            .with_columns(columns)
+            .with_keys(keys)
+            .with_indexes(indexes)
+            .build()
+    }
+
+    fn item_schema() -> TableSchema {
+        let columns = vec![
+            with(with(column("DM", "varchar"), SYS_OBJCOLS::COL_LEN, json!(30)), SYS_OBJCOLS::COL_ISNULL, json!("0")),
+            with(column("MC", "varchar"), SYS_OBJCOLS::COL_LANG, json!("1")),
+            with(
+                with(column("JE", "decimal"), SYS_OBJCOLS::COL_PREC, json!(18)),
+                SYS_OBJCOLS::COL_SCALE,
+                json!(2),
+            ),
+            with(column("SL", "int"), SYS_OBJCOLS::COL_DEFAULT, json!("1")),
+            with(column("BZ", "text"), SYS_OBJCOLS::COL_DEFAULT, json!("'it''s'")),
+            with(column("RQ", "date"), SYS_OBJCOLS::COL_DEFAULT, json!("@DATE")),
+        ];
+        let mut schema = schema("T_ITEM", columns, vec![primary_key(&["DM"])], vec![index("IX_ITEM_RQ", "RQ DESC, SL", "")]);
+        schema.set(SYS_OBJECTS::OBJ_MC, json!("物料"));
+        schema
+    }
+
+    #[test]
+    fn test_pg_type_mapping() {
+        assert_eq!(PgType::of(&column("A", "bool")).to_string(), "boolean");
+        assert_eq!(PgType::of(&column("A", "u64")).to_string(), "numeric(20,0)");
+        assert_eq!(PgType::of(&column("A", "decimal")).to_string(), "numeric");
+        assert_eq!(PgType::of(&column("A", "varchar")).to_string(), "text");
+        assert_eq!(PgType::of(&column("A", "datetime")).to_string(), "timestamp");
+        assert_eq!(PgType::of(&column("A", "uuid")).to_string(), "uuid");
+        let precise = with(column("A", "decimal"), SYS_OBJCOLS::COL_PREC, json!("10"));
+        assert_eq!(PgType::of(&precise).to_string(), "numeric(10)");
+    }
+
+    #[test]
+    fn test_create_table() {
+        let statements = PgDdl::new().create_table(&item_schema()).unwrap();
+        assert_eq!(
+            statements,
+            vec![
+                "CREATE TABLE \"T_ITEM\" (\n    \"DM\" varchar(30) NOT NULL,\n    \"MC\" jsonb,\n    \"JE\" numeric(18,2),\n    \
+                 \"SL\" integer DEFAULT 1,\n    \"BZ\" text DEFAULT 'it''s',\n    \"RQ\" date,\n    \
+                 CONSTRAINT \"T_ITEM_pkey\" PRIMARY KEY (\"DM\")\n)",
+                "CREATE INDEX \"IX_ITEM_RQ\" ON \"T_ITEM\" (\"RQ\", \"SL\")",
+                "COMMENT ON TABLE \"T_ITEM\" IS '物料'",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_create_table_with_schema_and_index_options() {
+        let mut clustered = index("UX_CODE", "DM", "UNIQUE");
+        clustered.set(SYS_INDEXS::INX_CLT, json!("1"));
+        let mut schema = schema(
+            "T_CODE",
+            vec![with(column("DM", "varchar"), SYS_OBJCOLS::COL_ISKEY, json!(true)), column("HZ", "varchar")],
+            vec![],
+            vec![clustered, index("IX_HZ", "HZ", "HASH")],
+        );
+        schema.set(SYS_OBJECTS::INDEX_SPACE, json!("idx"));
+
+        let script = PgDdl::new().with_schema("app").create_script(&schema).unwrap();
+        assert_eq!(
+            script,
+            "CREATE TABLE \"app\".\"T_CODE\" (\n    \"DM\" text,\n    \"HZ\" text,\n    \
+             CONSTRAINT \"T_CODE_pkey\" PRIMARY KEY (\"DM\") USING INDEX TABLESPACE \"idx\"\n);\n\
+             CREATE UNIQUE INDEX \"UX_CODE\" ON \"app\".\"T_CODE\" (\"DM\") TABLESPACE \"idx\";\n\
+             CLUSTER \"app\".\"T_CODE\" USING \"UX_CODE\";\n\
+             CREATE INDEX \"IX_HZ\" ON \"app\".\"T_CODE\" USING hash (\"HZ\") TABLESPACE \"idx\";\n"
+        );
+    }
+
+    #[test]
+    fn test_create_table_errors() {
+        let ddl = PgDdl::new();
+        assert!(matches!(
+            ddl.create_table(&TableSchemaBuilder::new().build()),
+            Err(DdlError::MissingObjectId)
+        ));
+        let duplicate = schema("T", vec![column("A", "int"), column("A", "int")], vec![], vec![]);
+        assert!(matches!(ddl.create_table(&duplicate), Err(DdlError::DuplicateColumn { .. })));
+        let unknown = schema("T", vec![column("A", "int")], vec![primary_key(&["B"])], vec![]);
+        assert!(matches!(ddl.create_table(&unknown), Err(DdlError::UnknownColumn { column, .. }) if column == "B"));
+        let bad_default = schema("T", vec![with(column("A", "int"), SYS_OBJCOLS::COL_DEFAULT, json!("abc"))], vec![], vec![]);
+        assert!(matches!(ddl.create_table(&bad_default), Err(DdlError::InvalidDefault { .. })));
+    }
+
+    #[test]
+    fn test_widening() {
+        let numeric = |p, s| PgType::Numeric { precision: Some(p), scale: Some(s) };
+        assert!(PgType::Integer.widens_to(&PgType::BigInt));
+        assert!(!PgType::BigInt.widens_to(&PgType::Integer));
+        assert!(PgType::Integer.widens_to(&numeric(12, 2)));
+        assert!(!PgType::BigInt.widens_to(&numeric(12, 2)));
+        assert!(numeric(10, 2).widens_to(&numeric(12, 4)));
+        assert!(!numeric(10, 2).widens_to(&numeric(10, 4)));
+        assert!(PgType::Varchar(10).widens_to(&PgType::Varchar(20)));
+        assert!(PgType::Varchar(20).widens_to(&PgType::Text));
+        assert!(!PgType::Text.widens_to(&PgType::Varchar(20)));
+        assert!(PgType::Date.widens_to(&PgType::Timestamp));
+        assert!(!PgType::Timestamp.widens_to(&PgType::Date));
+    }
+
+    #[test]
+    fn test_diff_orders_steps_and_flags_hazards() {
+        let old = item_schema();
+        let columns = vec![
+            with(with(column("DM", "varchar"), SYS_OBJCOLS::COL_LEN, json!(40)), SYS_OBJCOLS::COL_ISNULL, json!("0")),
+            with(column("MC", "varchar"), SYS_OBJCOLS::COL_LANG, json!("1")),
+            with(with(column("JE", "decimal"), SYS_OBJCOLS::COL_PREC, json!(12)), SYS_OBJCOLS::COL_SCALE, json!(2)),
+            with(column("SL", "int"), SYS_OBJCOLS::COL_ISNULL, json!(false)),
+            with(column("RQ", "date"), SYS_OBJCOLS::COL_MC, json!("日期")),
+            with(column("ZT", "int"), SYS_OBJCOLS::COL_ISNULL, json!(false)),
+            with(column("DW", "varchar"), SYS_OBJCOLS::COL_DEFAULT, json!("个")),
+        ];
+        let mut new = schema(
+            "T_ITEM",
+            columns,
+            vec![primary_key(&["DM", "RQ"])],
+            vec![index("IX_ITEM_RQ", "RQ DESC, SL", ""), index("UX_ITEM_DW", "DW", "U")],
+        );
+        new.set(SYS_OBJECTS::OBJ_MC, json!("物料"));
+
+        let migration = PgDdl::new().diff(&old, &new).unwrap();
+        assert_eq!(
+            migration.statements(),
+            vec![
+                "ALTER TABLE \"T_ITEM\" DROP CONSTRAINT \"T_ITEM_pkey\"",
+                "ALTER TABLE \"T_ITEM\" ADD COLUMN \"ZT\" integer NOT NULL",
+                "ALTER TABLE \"T_ITEM\" ADD COLUMN \"DW\" text DEFAULT '个'",
+                "ALTER TABLE \"T_ITEM\" ALTER COLUMN \"DM\" TYPE varchar(40) USING \"DM\"::varchar(40)",
+                "ALTER TABLE \"T_ITEM\" ALTER COLUMN \"JE\" TYPE numeric(12,2) USING \"JE\"::numeric(12,2)",
+                "ALTER TABLE \"T_ITEM\" ALTER COLUMN \"SL\" DROP DEFAULT",
+                "ALTER TABLE \"T_ITEM\" ALTER COLUMN \"SL\" SET NOT NULL",
+                "ALTER TABLE \"T_ITEM\" DROP COLUMN \"BZ\"",
+                "ALTER TABLE \"T_ITEM\" ADD CONSTRAINT \"T_ITEM_pkey\" PRIMARY KEY (\"DM\", \"RQ\")",
+                "CREATE UNIQUE INDEX \"UX_ITEM_DW\" ON \"T_ITEM\" (\"DW\")",
+                "COMMENT ON COLUMN \"T_ITEM\".\"RQ\" IS '日期'",
+            ]
+        );
+        let hazards: Vec<_> = migration.steps.iter().map(|step| step.hazard).collect();
+        assert_eq!(
+            hazards,
+            vec![
+                None,
+                Some(Hazard::RequiredColumn),
+                None,
+                None,
+                Some(Hazard::NarrowType),
+                None,
+                Some(Hazard::SetNotNull),
+                Some(Hazard::DropColumn),
+                None,
+                None,
+                None,
+            ]
+        );
+        assert!(!migration.is_safe());
+        assert!(migration.to_sql().contains("-- 不安全：删除列，列中的数据会丢失\nALTER TABLE \"T_ITEM\" DROP COLUMN \"BZ\";\n"));
+    }
+
+    #[test]
+    fn test_diff_rename_and_index_change() {
+        let old = schema("T_OLD", vec![column("A", "int")], vec![], vec![index("IX_A", "A", "")]);
+        let new = schema("T_NEW", vec![column("A", "int")], vec![], vec![index("IX_A", "A", "UNIQUE")]);
+        let migration = PgDdl::new().diff(&old, &new).unwrap();
+        assert_eq!(
+            migration.statements(),
+            vec![
+                "ALTER TABLE \"T_OLD\" RENAME TO \"T_NEW\"",
+                "DROP INDEX \"IX_A\"",
+                "CREATE UNIQUE INDEX \"IX_A\" ON \"T_NEW\" (\"A\")",
+            ]
+        );
+        assert!(migration.is_safe());
+        assert!(PgDdl::new().diff(&new, &new).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,823 @@
+//! # 外键名称翻译模块
+//!
+//! 外键列（`COL_ISFKEY`、`COL_FOBJ`）只保存引用字典的代码，显示时需要字典名称列（`DCT_MCCOLID`）的值。
+//! [`FkDecorator`] 按外键关联的字典（`DCTMeta::dct_metas`、`FCTMeta::dct_metas`）一次翻译整个数据集：
+//!
+//! - 收集所有外键列中不重复的代码，按字典合并后分批查询（见 [`NameSource`]、[`FkDecorator::with_batch_size`]）
+//! - 查询结果保存在 [`NameCache`] 中，找不到的代码也会缓存，共享缓存的多次翻译不会重复查询
+//! - 每个外键列增加一列翻译结果，列名为外键列 ID 加后缀，形式见 [`Decoration`]
+//! - 字典中找不到的代码（悬空引用）翻译为 `null`，和关联不到字典的外键列一起记录在 [`DecorateReport`] 中
+//!
+//! 翻译结果不计入数据集的变更跟踪。只检查外键值是否存在时使用 [`FkDecorator::verify`]，不修改数据集。
+//!
+//! ## 示例
+//!
+//! ```rust
+//! use std::collections::HashMap;
+//! use std::sync::Arc;
+//!
+//! use cmx_core::model::data::dataset::col::ColumnDef;
+//! use cmx_core::model::data::dataset::{rds::RowDataSet, ColumnType, TableSchemaBuilder};
+//! use cmx_core::model::meta::dct::DCTMeta;
+//! use cmx_core::model::meta::decorate::{DecorateIssue, FkDecorator};
+//! use cmx_core::model::meta::fields::{SYS_DICTS, SYS_OBJCOLS};
+//! use serde_json::json;
+//!
+//! fn column(id: &str) -> ColumnDef {
+//!     let mut column = ColumnDef::default();
+//!     column.set(SYS_OBJCOLS::COL_ID, json!(id));
+//!     column
+//! }
+//!
+//! let mut dept = DCTMeta::new("DCT_DEPT".to_string(), TableSchemaBuilder::new().with_columns(vec![column("DM"), column("MC")]).build());
+//! dept.set(SYS_DICTS::DCT_MCCOLID, json!("MC"));
+//!
+//! let mut fkey = column("DEPT");
+//! fkey.set(SYS_OBJCOLS::COL_ISFKEY, json!(true));
+//! fkey.set(SYS_OBJCOLS::COL_FOBJ, json!("DCT_DEPT"));
+//! let mut staff = DCTMeta::new("DCT_STAFF".to_string(), TableSchemaBuilder::new().with_columns(vec![column("DM"), fkey]).build());
+//! staff.dct_metas = Some(HashMap::from([("DCT_DEPT".to_string(), Arc::new(dept))]));
+//!
+//! let mut dataset = RowDataSet::new("staff".to_string());
+//! dataset.add_column("DM".to_string(), ColumnType::String).unwrap();
+//! dataset.add_column("DEPT".to_string(), ColumnType::String).unwrap();
+//! dataset.add_row(vec![json!("001"), json!("10")]).unwrap();
+//! dataset.add_row(vec![json!("002"), json!("99")]).unwrap();
+//!
+//! // 字典 ID -> 代码 -> 名称
+//! let names = HashMap::from([("DCT_DEPT".to_string(), HashMap::from([("10".to_string(), json!("财务部"))]))]);
+//! let report = futures::executor::block_on(FkDecorator::for_dict(&staff).decorate(&mut dataset, &names)).unwrap();
+//!
+//! assert_eq!(dataset.get_cell(0, "DEPT_MC").unwrap(), &json!("财务部"));
+//! assert!(dataset.get_cell(1, "DEPT_MC").unwrap().is_null());
+//! assert!(matches!(&report.issues[0], DecorateIssue::Dangling { row: 1, code, .. } if code == "99"));
+//! ```
+
+use std::collections::{BTreeSet, HashMap};
+use std::convert::Infallible;
+use std::fmt;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+
+use crate::model::data::cell::CellValue;
+use crate::model::data::dataset::col::ColumnDef;
+use crate::model::data::dataset::rds::RowDataSet;
+use crate::model::data::dataset::{ColumnType, DataSetError, TableSchema};
+
+use super::dct::DCTMeta;
+use super::dml::RowValues;
+use super::fct::FCTMeta;
+use super::fields::SYS_DICTS;
+use super::registry::MetaCatalog;
+
+/// 默认每次查询的代码数
+pub const DEFAULT_BATCH_SIZE: usize = 500;
+
+/// 字典 ID -> (字典, 不重复的代码)
+type Wanted<'a> = HashMap<&'a str, (&'a Arc<DCTMeta>, BTreeSet<String>)>;
+
+/// (字典 ID, 代码) -> 名称，字典中不存在的代码为 `None`
+type Names = HashMap<(String, String), Option<CellValue>>;
+
+/// 字典名称的来源，通常是字典所在的数据库
+pub trait NameSource {
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    /// 批量查询 `dict` 中 `codes` 对应的名称
+    ///
+    /// # 返回值
+    ///
+    /// 代码 -> 名称，结果中没有的代码视为字典中不存在
+    fn lookup_names(
+        &self,
+        dict: &DCTMeta,
+        codes: &[String],
+    ) -> impl Future<Output = Result<HashMap<String, CellValue>, Self::Error>> + Send;
+}
+
+/// 内存中的名称表：字典 ID -> 代码 -> 名称，主要用于测试和已加载到内存的字典
+impl NameSource for HashMap<String, HashMap<String, CellValue>> {
+    type Error = Infallible;
+
+    fn lookup_names(
+        &self,
+        dict: &DCTMeta,
+        codes: &[String],
+    ) -> impl Future<Output = Result<HashMap<String, CellValue>, Self::Error>> + Send {
+        let names = self
+            .get(&dict.dct_id)
+            .map(|names| {
+                codes
+                    .iter()
+                    .filter_map(|code| names.get(code).map(|name| (code.clone(), name.clone())))
+                    .collect()
+            })
+            .unwrap_or_default();
+        std::future::ready(Ok(names))
+    }
+}
+
+/// 字典名称缓存，键为字典 ID 和代码，字典中不存在的代码缓存为 `None`
+///
+/// 克隆的缓存共享同一份数据。字典数据变化后需要调用 [`NameCache::invalidate`]。
+#[derive(Debug, Clone, Default)]
+pub struct NameCache {
+    names: Arc<Mutex<CachedNames>>,
+}
+
+/// 字典 ID -> 代码 -> 名称
+type CachedNames = HashMap<String, HashMap<String, Option<CellValue>>>;
+
+impl NameCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 清除一个字典的缓存
+    pub fn invalidate(&self, dct_id: &str) {
+        self.lock().remove(dct_id);
+    }
+
+    /// 清除所有缓存
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// 缓存的代码数
+    pub fn len(&self) -> usize {
+        self.lock().values().map(HashMap::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn get(&self, dct_id: &str, code: &str) -> Option<Option<CellValue>> {
+        self.lock().get(dct_id).and_then(|names| names.get(code).cloned())
+    }
+
+    fn insert(&self, dct_id: &str, code: String, name: Option<CellValue>) {
+        self.lock().entry(dct_id.to_string()).or_default().insert(code, name);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CachedNames> {
+        // 缓存只有插入和删除，持锁线程 panic 后数据仍然可用
+        self.names.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// 翻译结果的形式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Decoration {
+    /// 名称列，默认后缀 `_MC`，列类型与字典名称列相同
+    #[default]
+    Name,
+    /// 引用对象列，默认后缀 `_REF`，值为 `{"code": 代码, "name": 名称}`
+    Lookup,
+}
+
+impl Decoration {
+    /// 翻译列的默认后缀
+    pub fn default_suffix(&self) -> &'static str {
+        match self {
+            Decoration::Name => "_MC",
+            Decoration::Lookup => "_REF",
+        }
+    }
+}
+
+/// 翻译时发现的问题
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "issue", rename_all = "snake_case")]
+pub enum DecorateIssue {
+    /// 外键列引用的字典没有关联，该列不翻译
+    MissingDictionary { column: String, dct_id: String },
+    /// 引用的字典没有设置名称列（`DCT_MCCOLID`），该列不翻译
+    MissingNameColumn { column: String, dct_id: String },
+    /// 代码在引用的字典中不存在，`row` 为行号
+    Dangling { row: usize, column: String, dct_id: String, code: String },
+}
+
+impl fmt::Display for DecorateIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecorateIssue::MissingDictionary { column, dct_id } => {
+                write!(f, "column '{}' references unknown dictionary '{}'", column, dct_id)
+            }
+            DecorateIssue::MissingNameColumn { column, dct_id } => {
+                write!(f, "dictionary '{}' referenced by column '{}' has no name column", dct_id, column)
+            }
+            DecorateIssue::Dangling { row, column, dct_id, code } => {
+                write!(f, "row {} column '{}': code '{}' does not exist in dictionary '{}'", row, column, code, dct_id)
+            }
+        }
+    }
+}
+
+/// 一次翻译的结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DecorateReport {
+    /// 增加或更新的翻译列
+    pub columns: Vec<String>,
+    /// 发现的问题
+    pub issues: Vec<DecorateIssue>,
+    /// 向名称来源查询的代码数
+    pub looked_up: usize,
+    /// 由缓存得到的代码数
+    pub cached: usize,
+}
+
+/// 翻译错误
+#[derive(Debug, Error)]
+pub enum DecorateError<E: std::error::Error + 'static> {
+    #[error("Name lookup in dictionary '{dct_id}' failed")]
+    Lookup {
+        dct_id: String,
+        #[source]
+        source: E,
+    },
+    #[error(transparent)]
+    DataSet(#[from] DataSetError),
+}
+
+/// 需要翻译的外键列
+#[derive(Debug, Clone)]
+struct ForeignColumn {
+    column: String,
+    dict: Arc<DCTMeta>,
+    /// 字典名称列，决定名称列的类型，没有名称列的外键列只能检查（见 [`FkDecorator::verify`]）
+    name: Option<ColumnDef>,
+}
+
+/// 外键名称翻译器
+///
+/// 由表定义和外键关联的字典创建，可以重复用于多个数据集。
+#[derive(Debug, Clone)]
+pub struct FkDecorator {
+    columns: Vec<ForeignColumn>,
+    issues: Vec<DecorateIssue>,
+    decoration: Decoration,
+    suffix: Option<String>,
+    cache: NameCache,
+    batch_size: usize,
+}
+
+impl FkDecorator {
+    /// 按 `resolve` 关联外键列引用的字典，参数为外键列和字典 ID
+    pub fn new(schema: &TableSchema, resolve: impl Fn(&ColumnDef, &str) -> Option<Arc<DCTMeta>>) -> Self {
+        let mut columns = Vec::new();
+        let mut issues = Vec::new();
+        for column in &schema.columns {
+            let Some(dct_id) = column.foreign_dict() else {
+                continue;
+            };
+            let col_id = column.col_id();
+            let Some(dict) = resolve(column, &dct_id) else {
+                issues.push(DecorateIssue::MissingDictionary { column: col_id, dct_id });
+                continue;
+            };
+            let name = dict
+                .get_string(&SYS_DICTS::DCT_MCCOLID)
+                .and_then(|name| dict.table_schema.get_column(name.trim()).cloned());
+            if name.is_none() {
+                issues.push(DecorateIssue::MissingNameColumn { column: col_id.clone(), dct_id });
+            }
+            columns.push(ForeignColumn { column: col_id, dict, name });
+        }
+        Self {
+            columns,
+            issues,
+            decoration: Decoration::default(),
+            suffix: None,
+            cache: NameCache::new(),
+            batch_size: DEFAULT_BATCH_SIZE,
+        }
+    }
+
+    /// 字典的外键列，引用的字典从 `DCTMeta::dct_metas` 中按字典 ID 查找
+    pub fn for_dict(meta: &DCTMeta) -> Self {
+        Self::new(&meta.table_schema, |_, dct_id| meta.dct_metas.as_ref()?.get(dct_id).cloned())
+    }
+
+    /// 事实表的外键列，引用的字典从 `FCTMeta::dct_metas` 中按列 ID 查找
+    pub fn for_fact(meta: &FCTMeta) -> Self {
+        Self::new(&meta.table_schema, |column, _| meta.dct_metas.as_ref()?.get(&column.col_id()).cloned())
+    }
+
+    /// 任意表定义的外键列，引用的字典从目录中查找
+    pub fn for_schema(schema: &TableSchema, catalog: &MetaCatalog) -> Self {
+        Self::new(schema, |_, dct_id| catalog.dct_meta(dct_id))
+    }
+
+    /// 翻译结果的形式，默认为名称列
+    pub fn with_decoration(mut self, decoration: Decoration) -> Self {
+        self.decoration = decoration;
+        self
+    }
+
+    /// 翻译列名的后缀，默认见 [`Decoration::default_suffix`]
+    pub fn with_suffix(mut self, suffix: impl Into<String>) -> Self {
+        self.suffix = Some(suffix.into());
+        self
+    }
+
+    /// 使用共享的缓存，默认每个翻译器有自己的缓存
+    pub fn with_cache(mut self, cache: NameCache) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// 每次查询的最多代码数，默认为 [`DEFAULT_BATCH_SIZE`]
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// 翻译列名：外键列 ID 加后缀
+    pub fn target_column(&self, column: &str) -> String {
+        let suffix = self.suffix.as_deref().unwrap_or(self.decoration.default_suffix());
+        format!("{}{}", column, suffix)
+    }
+
+    /// 翻译数据集，数据集中没有的外键列被忽略
+    ///
+    /// 翻译列不存在时追加到数据集末尾，已存在时覆盖原来的值。
+    ///
+    /// # 返回值
+    ///
+    /// - `Ok(DecorateReport)` - 增加的列和发现的问题
+    /// - `Err(DecorateError::Lookup)` - 名称来源查询失败，数据集保持不变
+    /// - `Err(DecorateError::DataSet)` - 翻译列与数据集中已有的列冲突
+    pub async fn decorate<S: NameSource>(
+        &self,
+        dataset: &mut RowDataSet,
+        source: &S,
+    ) -> Result<DecorateReport, DecorateError<S::Error>> {
+        let columns = Self::present(self.named(), dataset);
+        let wanted = Self::wanted(columns.iter().flat_map(|(foreign, index)| {
+            dataset.rows.iter().flat_map(move |row| {
+                let original = row.original.iter().map(move |original| &original[*index]);
+                std::iter::once(&row.values[*index]).chain(original).map(move |value| (*foreign, value))
+            })
+        }));
+        let mut report = self.report();
+        let names = self.resolve(wanted, source, &mut report).await?;
+
+        for (foreign, index) in columns {
+            let target = self.target_column(&foreign.column);
+            let target_index = self.add_target(dataset, &target, foreign)?;
+            for (row_index, row) in dataset.rows.iter_mut().enumerate() {
+                row.values[target_index] = self.value(foreign, &row.values[index], &names, row_index, &mut report);
+                // 原始值按原始代码翻译，翻译结果不计入变更
+                if let Some(original) = row.original.as_mut() {
+                    original[target_index] = self.translate(foreign, &original[index], &names).unwrap_or(CellValue::Null);
+                }
+            }
+            report.columns.push(target);
+        }
+        Ok(report)
+    }
+
+    /// 翻译 JSON 对象形式的行，见 [`FkDecorator::decorate`]
+    pub async fn decorate_rows<S: NameSource>(
+        &self,
+        rows: &mut [RowValues],
+        source: &S,
+    ) -> Result<DecorateReport, DecorateError<S::Error>> {
+        let wanted = Self::wanted(self.named().flat_map(|foreign| {
+            rows.iter().filter_map(move |row| row.get(&foreign.column).map(|value| (foreign, value)))
+        }));
+        let mut report = self.report();
+        let names = self.resolve(wanted, source, &mut report).await?;
+
+        for foreign in self.named() {
+            let target = self.target_column(&foreign.column);
+            let mut decorated = false;
+            for (row_index, row) in rows.iter_mut().enumerate() {
+                let Some(code) = row.get(&foreign.column) else {
+                    continue;
+                };
+                let value = self.value(foreign, code, &names, row_index, &mut report);
+                row.insert(target.clone(), value);
+                decorated = true;
+            }
+            if decorated {
+                report.columns.push(target);
+            }
+        }
+        Ok(report)
+    }
+
+    /// 检查数据集中的外键值是否都存在于引用的字典中，不修改数据集
+    ///
+    /// 只检查代码是否存在，引用的字典不需要名称列；查询结果同样进入缓存。
+    ///
+    /// # 返回值
+    ///
+    /// - `Ok(DecorateReport)` - `issues` 为悬空引用和关联不到字典的外键列，`columns` 为空
+    /// - `Err(DecorateError::Lookup)` - 名称来源查询失败
+    pub async fn verify<S: NameSource>(
+        &self,
+        dataset: &RowDataSet,
+        source: &S,
+    ) -> Result<DecorateReport, DecorateError<S::Error>> {
+        let columns = Self::present(self.columns.iter(), dataset);
+        let wanted = Self::wanted(
+            columns
+                .iter()
+                .flat_map(|(foreign, index)| dataset.rows.iter().map(move |row| (*foreign, &row.values[*index]))),
+        );
+        let issues = self.issues.iter().filter(|issue| !matches!(issue, DecorateIssue::MissingNameColumn { .. }));
+        let mut report = DecorateReport { issues: issues.cloned().collect(), ..DecorateReport::default() };
+        let names = self.resolve(wanted, source, &mut report).await?;
+
+        for (foreign, index) in columns {
+            for (row, values) in dataset.rows.iter().enumerate() {
+                if let Err(code) = self.translate(foreign, &values.values[index], &names) {
+                    let (column, dct_id) = (foreign.column.clone(), foreign.dict.dct_id.clone());
+                    report.issues.push(DecorateIssue::Dangling { row, column, dct_id, code });
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// 有名称列、可以翻译的外键列
+    fn named(&self) -> impl Iterator<Item = &ForeignColumn> {
+        self.columns.iter().filter(|foreign| foreign.name.is_some())
+    }
+
+    /// 数据集中有的外键列及其位置
+    fn present<'a>(
+        columns: impl Iterator<Item = &'a ForeignColumn>,
+        dataset: &RowDataSet,
+    ) -> Vec<(&'a ForeignColumn, usize)> {
+        columns
+            .filter_map(|foreign| dataset.get_column_info(&foreign.column).map(|info| (foreign, info.index)))
+            .collect()
+    }
+
+    fn report(&self) -> DecorateReport {
+        DecorateReport { issues: self.issues.clone(), ..DecorateReport::default() }
+    }
+
+    /// 按字典合并不重复的代码
+    fn wanted<'a>(
+        codes: impl Iterator<Item = (&'a ForeignColumn, &'a CellValue)>,
+    ) -> Wanted<'a> {
+        let mut wanted = Wanted::new();
+        for (foreign, value) in codes {
+            if let Some(code) = code_text(value) {
+                wanted.entry(&foreign.dict.dct_id).or_insert_with(|| (&foreign.dict, BTreeSet::new())).1.insert(code);
+            }
+        }
+        wanted
+    }
+
+    /// 先查缓存，再分批查询名称来源
+    async fn resolve<S: NameSource>(
+        &self,
+        wanted: Wanted<'_>,
+        source: &S,
+        report: &mut DecorateReport,
+    ) -> Result<Names, DecorateError<S::Error>> {
+        let mut names = HashMap::new();
+        for (dct_id, (dict, codes)) in wanted {
+            let mut missing = Vec::new();
+            for code in codes {
+                match self.cache.get(dct_id, &code) {
+                    Some(name) => {
+                        report.cached += 1;
+                        names.insert((dct_id.to_string(), code), name);
+                    }
+                    None => missing.push(code),
+                }
+            }
+            for batch in missing.chunks(self.batch_size) {
+                let mut found = source
+                    .lookup_names(dict, batch)
+                    .await
+                    .map_err(|source| DecorateError::Lookup { dct_id: dct_id.to_string(), source })?;
+                report.looked_up += batch.len();
+                for code in batch {
+                    let name = found.remove(code);
+                    self.cache.insert(dct_id, code.clone(), name.clone());
+                    names.insert((dct_id.to_string(), code.clone()), name);
+                }
+            }
+        }
+        Ok(names)
+    }
+
+    /// 追加翻译列，返回列的位置
+    fn add_target(&self, dataset: &mut RowDataSet, target: &str, foreign: &ForeignColumn) -> Result<usize, DataSetError> {
+        if let Some(info) = dataset.get_column_info(target) {
+            return Ok(info.index);
+        }
+        let name = foreign.name.as_ref();
+        let multilingual = self.decoration == Decoration::Name && name.is_some_and(ColumnDef::is_multilingual);
+        let column_type = match self.decoration {
+            Decoration::Name if multilingual => ColumnType::String,
+            Decoration::Name => name.map(ColumnDef::column_type).unwrap_or_default(),
+            Decoration::Lookup => ColumnType::Json,
+        };
+        dataset.add_column(target.to_string(), column_type)?;
+        if multilingual {
+            dataset.set_multilingual(target, true)?;
+        }
+        Ok(dataset.column_count() - 1)
+    }
+
+    /// 一个单元格的翻译结果，悬空引用记录到 `report`
+    fn value(
+        &self,
+        foreign: &ForeignColumn,
+        code: &CellValue,
+        names: &Names,
+        row: usize,
+        report: &mut DecorateReport,
+    ) -> CellValue {
+        self.translate(foreign, code, names).unwrap_or_else(|code| {
+            let (column, dct_id) = (foreign.column.clone(), foreign.dict.dct_id.clone());
+            report.issues.push(DecorateIssue::Dangling { row, column, dct_id, code });
+            CellValue::Null
+        })
+    }
+
+    /// 翻译一个代码，悬空引用返回 `Err(代码)`
+    fn translate(
+        &self,
+        foreign: &ForeignColumn,
+        code: &CellValue,
+        names: &Names,
+    ) -> Result<CellValue, String> {
+        let Some(code) = code_text(code) else {
+            return Ok(CellValue::Null);
+        };
+        let key = (foreign.dict.dct_id.clone(), code);
+        let Some(name) = names.get(&key).cloned().flatten() else {
+            return Err(key.1);
+        };
+        Ok(match self.decoration {
+            Decoration::Name => name,
+            Decoration::Lookup => json!({"code": key.1, "name": name}),
+        })
+    }
+}
+
+/// 外键代码的文本，`null` 和空串不是代码
+fn code_text(value: &CellValue) -> Option<String> {
+    match value {
+        CellValue::String(s) => Some(s.trim().to_string()).filter(|s| !s.is_empty()),
+        CellValue::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::model::data::dataset::TableSchemaBuilder;
+    use crate::model::meta::fields::SYS_OBJCOLS;
+
+    fn column(id: &str) -> ColumnDef {
+        let mut column = ColumnDef::default();
+        column.set(SYS_OBJCOLS::COL_ID, json!(id));
+        column
+    }
+
+    fn fkey(id: &str, dct_id: &str) -> ColumnDef {
+        let mut column = column(id);
+        column.set(SYS_OBJCOLS::COL_ISFKEY, json!("Y"));
+        column.set(SYS_OBJCOLS::COL_FOBJ, json!(dct_id));
+        column
+    }
+
+    fn dict(dct_id: &str, name: ColumnDef) -> Arc<DCTMeta> {
+        let name_id = name.col_id();
+        let mut meta = DCTMeta::new(dct_id.to_string(), TableSchemaBuilder::new().with_columns(vec![column("DM"), name]).build());
+        meta.set(SYS_DICTS::DCT_MCCOLID, json!(name_id));
+        Arc::new(meta)
+    }
+
+    /// 员工字典：部门和上级部门引用部门字典，岗位引用岗位字典
+    fn staff() -> DCTMeta {
+        let mut staff = DCTMeta::new(
+            "DCT_STAFF".to_string(),
+            TableSchemaBuilder::new()
+                .with_columns(vec![column("DM"), fkey("DEPT", "DCT_DEPT"), fkey("PARENT", "DCT_DEPT"), fkey("POST", "DCT_POST")])
+                .build(),
+        );
+        staff.dct_metas = Some(HashMap::from([
+            ("DCT_DEPT".to_string(), dict("DCT_DEPT", column("MC"))),
+            ("DCT_POST".to_string(), dict("DCT_POST", column("MC"))),
+        ]));
+        staff
+    }
+
+    fn dataset() -> RowDataSet {
+        let mut dataset = RowDataSet::new("staff".to_string());
+        for id in ["DM", "DEPT", "PARENT", "POST"] {
+            dataset.add_column(id.to_string(), ColumnType::String).unwrap();
+        }
+        dataset.add_row(vec![json!("001"), json!("10"), json!("1"), json!("P1")]).unwrap();
+        dataset.add_row(vec![json!("002"), json!("11"), json!("1"), json!(null)]).unwrap();
+        dataset.add_row(vec![json!("003"), json!("99"), json!("1"), json!("P1")]).unwrap();
+        dataset
+    }
+
+    /// 记录查询次数的名称来源
+    struct Counting {
+        names: HashMap<String, HashMap<String, CellValue>>,
+        calls: AtomicUsize,
+    }
+
+    impl Counting {
+        fn new() -> Self {
+            let names = HashMap::from([
+                (
+                    "DCT_DEPT".to_string(),
+                    HashMap::from([
+                        ("1".to_string(), json!("总部")),
+                        ("10".to_string(), json!("财务部")),
+                        ("11".to_string(), json!("人事部")),
+                    ]),
+                ),
+                ("DCT_POST".to_string(), HashMap::from([("P1".to_string(), json!("会计"))])),
+            ]);
+            Self { names, calls: AtomicUsize::new(0) }
+        }
+
+        fn calls(&self) -> usize {
+            self.calls.load(Ordering::SeqCst)
+        }
+    }
+
+    impl NameSource for Counting {
+        type Error = Infallible;
+
+        fn lookup_names(
+            &self,
+            dict: &DCTMeta,
+            codes: &[String],
+        ) -> impl Future<Output = Result<HashMap<String, CellValue>, Self::Error>> + Send {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            self.names.lookup_names(dict, codes)
+        }
+    }
+
+    #[derive(Debug, Error)]
+    #[error("connection lost")]
+    struct Broken;
+
+    impl NameSource for Broken {
+        type Error = Broken;
+
+        async fn lookup_names(&self, _: &DCTMeta, _: &[String]) -> Result<HashMap<String, CellValue>, Broken> {
+            Err(Broken)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_decorate_adds_name_columns_with_one_lookup_per_dictionary() {
+        let source = Counting::new();
+        let mut dataset = dataset();
+        let report = FkDecorator::for_dict(&staff()).decorate(&mut dataset, &source).await.unwrap();
+
+        assert_eq!(report.columns, vec!["DEPT_MC", "PARENT_MC", "POST_MC"]);
+        assert_eq!(dataset.get_cell(0, "DEPT_MC").unwrap(), &json!("财务部"));
+        assert_eq!(dataset.get_cell(1, "PARENT_MC").unwrap(), &json!("总部"));
+        assert_eq!(dataset.get_cell(0, "POST_MC").unwrap(), &json!("会计"));
+        assert!(dataset.get_cell(1, "POST_MC").unwrap().is_null());
+        // 两个列引用同一个字典，代码合并后每个字典只查询一次
+        assert_eq!(source.calls(), 2);
+        assert_eq!(report.looked_up, 5);
+        assert_eq!(
+            report.issues,
+            vec![DecorateIssue::Dangling { row: 2, column: "DEPT".into(), dct_id: "DCT_DEPT".into(), code: "99".into() }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_decorate_uses_shared_cache_and_batches() {
+        let source = Counting::new();
+        let cache = NameCache::new();
+        let decorator = FkDecorator::for_dict(&staff()).with_cache(cache.clone()).with_batch_size(2);
+
+        decorator.decorate(&mut dataset(), &source).await.unwrap();
+        // DCT_DEPT 有 4 个代码，分两批
+        assert_eq!(source.calls(), 3);
+        assert_eq!(cache.len(), 5);
+
+        let report = decorator.decorate(&mut dataset(), &source).await.unwrap();
+        assert_eq!(source.calls(), 3);
+        assert_eq!((report.looked_up, report.cached), (0, 5));
+        // 不存在的代码也会缓存
+        assert_eq!(report.issues.len(), 1);
+
+        cache.invalidate("DCT_POST");
+        decorator.decorate(&mut dataset(), &source).await.unwrap();
+        assert_eq!(source.calls(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_decorate_lookup_objects_keep_changes_clean() {
+        let mut dataset = dataset();
+        dataset.set_change_tracking(true);
+        dataset.set_cell(0, "DEPT", json!("11")).unwrap();
+
+        let decorator = FkDecorator::for_dict(&staff()).with_decoration(Decoration::Lookup);
+        decorator.decorate(&mut dataset, &Counting::new()).await.unwrap();
+
+        assert_eq!(dataset.get_cell(0, "DEPT_REF").unwrap(), &json!({"code": "11", "name": "人事部"}));
+        assert!(dataset.get_cell(2, "DEPT_REF").unwrap().is_null());
+        assert_eq!(dataset.rows[0].original.as_ref().unwrap()[4], json!({"code": "10", "name": "财务部"}));
+        assert!(dataset.rows[1].state.is_unchanged());
+    }
+
+    #[tokio::test]
+    async fn test_decorate_reports_unlinked_columns() {
+        let mut staff = staff();
+        staff.dct_metas.as_mut().unwrap().remove("DCT_POST");
+        let mut unnamed = DCTMeta::new("DCT_DEPT".to_string(), TableSchemaBuilder::new().with_columns(vec![column("DM")]).build());
+        unnamed.set(SYS_DICTS::DCT_MCCOLID, json!("MC"));
+        staff.dct_metas.as_mut().unwrap().insert("DCT_DEPT".to_string(), Arc::new(unnamed));
+
+        let mut dataset = dataset();
+        let report = FkDecorator::for_dict(&staff).decorate(&mut dataset, &Counting::new()).await.unwrap();
+
+        assert!(report.columns.is_empty());
+        assert_eq!(dataset.column_count(), 4);
+        let messages: Vec<String> = report.issues.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "dictionary 'DCT_DEPT' referenced by column 'DEPT' has no name column",
+                "dictionary 'DCT_DEPT' referenced by column 'PARENT' has no name column",
+                "column 'POST' references unknown dictionary 'DCT_POST'",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_decorate_rows_for_fact_columns() {
+        let mut fact = FCTMeta {
+            id: "FCT_PAY".to_string(),
+            name: "工资".to_string(),
+            info: None,
+            table_schema: TableSchemaBuilder::new().with_columns(vec![fkey("BM", "DCT_DEPT"), column("JE")]).build(),
+            dct_metas: None,
+            settings: None,
+        };
+        fact.dct_metas = Some(HashMap::from([("BM".to_string(), dict("DCT_DEPT", column("MC")))]));
+
+        let mut rows = vec![
+            json!({"BM": 10, "JE": 5}).as_object().cloned().unwrap(),
+            json!({"JE": 6}).as_object().cloned().unwrap(),
+        ];
+        let decorator = FkDecorator::for_fact(&fact).with_suffix("_NAME");
+        let report = decorator.decorate_rows(&mut rows, &Counting::new()).await.unwrap();
+
+        assert_eq!(report.columns, vec!["BM_NAME"]);
+        assert_eq!(rows[0]["BM_NAME"], json!("财务部"));
+        assert!(!rows[1].contains_key("BM_NAME"));
+    }
+
+    #[tokio::test]
+    async fn test_verify_reports_dangling_codes_without_name_columns() {
+        let mut staff = staff();
+        let unnamed = DCTMeta::new("DCT_POST".to_string(), TableSchemaBuilder::new().with_columns(vec![column("DM")]).build());
+        staff.dct_metas.as_mut().unwrap().insert("DCT_POST".to_string(), Arc::new(unnamed));
+        let mut dataset = dataset();
+        dataset.set_cell(0, "POST", json!("P9")).unwrap();
+
+        let report = FkDecorator::for_dict(&staff).verify(&dataset, &Counting::new()).await.unwrap();
+
+        let issues: Vec<String> = report.issues.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            issues,
+            vec![
+                "row 2 column 'DEPT': code '99' does not exist in dictionary 'DCT_DEPT'",
+                "row 0 column 'POST': code 'P9' does not exist in dictionary 'DCT_POST'",
+            ]
+        );
+        assert!(report.columns.is_empty());
+        assert_eq!(dataset.column_count(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_decorate_lookup_failure_leaves_dataset_unchanged() {
+        let mut dataset = dataset();
+        let error = FkDecorator::for_dict(&staff()).decorate(&mut dataset, &Broken).await.unwrap_err();
+
+        assert!(matches!(error, DecorateError::Lookup { .. }));
+        assert_eq!(dataset.column_count(), 4);
+    }
+}
@@ -1,6 +1,5 @@
 // use std::sync::Arc;
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
 // use ospbase::data::dataset::row::RowSet;
 use crate::model::data::dataset::DataSet;
 use serde::{Serialize, Deserialize};
@@ -151,47 +150,6 @@ impl DMEMeta {
 }
 
 
-use lazy_static::lazy_static;
-
-lazy_static! {
-    static ref FD_METAS: RwLock<HashMap<String, Arc<DMEMeta>>> = RwLock::new(HashMap::new());
-}
-
-#[derive(Debug)]
-pub struct FDMetaManager;
-
-impl FDMetaManager {
-    /// Add a FDMeta to the cache
-    pub fn add_meta(meta: Arc<DMEMeta>) {
-        let mut metas = FD_METAS.write().unwrap();
-        metas.insert(meta.id.clone(), meta);
-    }
-
-    /// Get a FDMeta from the cache by id
-    pub fn get_meta(id: &str) -> Option<Arc<DMEMeta>> {
-        let metas = FD_METAS.read().unwrap();
-        metas.get(id).cloned()
-    }
-
-    /// Remove a FDMeta from the cache
-    pub fn remove_meta(id: &str) -> Option<Arc<DMEMeta>> {
-        let mut metas = FD_METAS.write().unwrap();
-        metas.remove(id)
-    }
-
-    /// Get all cached FDMetas
-    pub fn get_all_metas() -> Vec<Arc<DMEMeta>> {
-        let metas = FD_METAS.read().unwrap();
-        metas.values().cloned().collect()
-    }
-
-    #[cfg(test)]
-    pub fn clear() {
-        let mut metas = FD_METAS.write().unwrap();
-        metas.clear();
-    }
-}
-
 // #[cfg(test)]
 // mod tests {
 //     use super::*;
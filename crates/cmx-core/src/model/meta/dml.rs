@@ -0,0 +1,709 @@
+//! # Postgres DML 生成模块
+//!
+//! 按对象定义生成单表增删改查的参数化语句，供通用的行接口使用：
+//!
+//! - 写入的值先按列类型转换（见 [`ColumnType::coerce`]），再按列约束校验（见 [`Validator`]），
+//!   多语言列按 JSON 原样写入
+//! - 新增时由行工厂填充默认值和系统列，修改时刷新修改时间和修改人（见 [`crate::model::data::dataset::factory`]）
+//! - 行按主键定位，复合主键的各列值以逗号分隔，顺序与主键定义一致；值中含有逗号时
+//!   以 JSON 数组给出，如 `["01,02",3]`
+//! - 参数一律以文本传递，在语句中转换为列的 Postgres 类型（见 [`PgType::of`]），二进制列以 Base64 传递
+//! - 查询和写入语句返回每行一个 JSON 对象的文本，用 [`PgDml::decode_row`] 解析
+//!
+//! 表名和主键的规则与 [`super::ddl`] 相同。
+//!
+//! ## 示例
+//!
+//! ```rust
+//! use cmx_core::model::data::dataset::col::ColumnDef;
+//! use cmx_core::model::data::dataset::TableSchemaBuilder;
+//! use cmx_core::model::meta::dml::PgDml;
+//! use cmx_core::model::meta::fields::SYS_OBJCOLS;
+//! use serde_json::json;
+//!
+//! fn column(id: &str, column_type: &str) -> ColumnDef {
+//!     let mut column = ColumnDef::default();
+//!     column.set(SYS_OBJCOLS::COL_ID, json!(id));
+//!     column.set(SYS_OBJCOLS::COL_TYPE, json!(column_type));
+//!     column
+//! }
+//!
+//! let mut id = column("ID", "int");
+//! id.set(SYS_OBJCOLS::COL_ISKEY, json!(true));
+//! let table = TableSchemaBuilder::new()
+//!     .with_obj_id("T_ITEM".to_string())
+//!     .with_columns(vec![id, column("MC", "varchar")])
+//!     .build();
+//!
+//! let dml = PgDml::new();
+//! let row = json!({"ID": "7", "MC": "螺丝"});
+//! let insert = dml.insert(&table, row.as_object().unwrap()).unwrap();
+//! assert_eq!(
+//!     insert.sql,
+//!     "INSERT INTO \"T_ITEM\" AS t (\"ID\", \"MC\") VALUES ($1::integer, $2::text) RETURNING to_jsonb(t)::text"
+//! );
+//! assert_eq!(insert.params, vec![Some("7".to_string()), Some("螺丝".to_string())]);
+//!
+//! let select = dml.select_by_key(&table, "7").unwrap();
+//! assert_eq!(select.sql, "SELECT to_jsonb(t)::text FROM \"T_ITEM\" AS t WHERE t.\"ID\" = $1::integer");
+//!
+//! let row = PgDml::decode_row(&table, r#"{"ID": 7, "MC": "螺丝"}"#).unwrap();
+//! assert_eq!(row["MC"], json!("螺丝"));
+//! ```
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use thiserror::Error;
+
+use crate::model::data::cell::CellValue;
+use crate::model::data::context::SVRContext;
+use crate::model::data::dataset::col::ColumnDef;
+use crate::model::data::dataset::factory::{RowFactory, RowFactoryError};
+use crate::model::data::dataset::validate::{ConstraintError, MessageLocale, Validator, Violation};
+use crate::model::data::dataset::{ColumnType, DataSet, DataSetError, TableSchema};
+
+use super::dct::DCTMeta;
+use super::ddl::{primary_key_columns, qualified_name, quote_ident, PgType};
+use super::fields::SYS_DICTS;
+
+/// 一行数据，键为列 ID
+pub type RowValues = serde_json::Map<String, CellValue>;
+
+/// 返回行的表达式，查询和写入语句都以此作为结果列
+const ROW_JSON: &str = "to_jsonb(t)::text";
+
+/// DML 生成错误
+#[derive(Debug, Error)]
+pub enum DmlError {
+    #[error("Object has no OBJ_ID")]
+    MissingObjectId,
+    #[error("'{0}' has no primary key")]
+    MissingPrimaryKey(String),
+    #[error("'{table}' has no column '{column}'")]
+    UnknownColumn { table: String, column: String },
+    #[error("Invalid value for column '{column}': {value}")]
+    InvalidValue { column: String, value: CellValue },
+    #[error("Key '{key}' of '{table}' must have {expected} comma-separated value(s) or be a JSON array of them")]
+    InvalidKey { table: String, key: String, expected: usize },
+    #[error("Update of '{0}' sets no columns")]
+    NoChanges(String),
+    #[error("Dictionary '{dct_id}' has no {field} column")]
+    MissingDictColumn { dct_id: String, field: SYS_DICTS },
+    #[error("Row violates {} constraint(s)", .0.len())]
+    Violations(Vec<Violation>),
+    #[error(transparent)]
+    Constraint(#[from] ConstraintError),
+    #[error(transparent)]
+    RowFactory(#[from] RowFactoryError),
+    #[error(transparent)]
+    DataSet(#[from] DataSetError),
+}
+
+/// 参数化语句
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Statement {
+    /// 以 `$1`、`$2`……引用参数的 SQL
+    pub sql: String,
+    /// 参数的文本表示，`None` 为 `NULL`
+    pub params: Vec<Option<String>>,
+}
+
+impl Statement {
+    fn new(sql: String, params: Vec<Option<String>>) -> Self {
+        Self { sql, params }
+    }
+}
+
+/// Postgres DML 生成器
+#[derive(Debug, Clone, Default)]
+pub struct PgDml {
+    schema: Option<String>,
+    context: SVRContext,
+    locale: MessageLocale,
+}
+
+impl PgDml {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 表所在的 Postgres schema，未设置时使用连接的 `search_path`
+    pub fn with_schema(mut self, schema: impl Into<String>) -> Self {
+        self.schema = Some(schema.into());
+        self
+    }
+
+    /// 填充上下文默认值和系统列时读取的上下文，例如当前用户和单位
+    pub fn with_context(mut self, context: SVRContext) -> Self {
+        self.context = context;
+        self
+    }
+
+    /// 违反约束时提示信息的语言
+    pub fn with_locale(mut self, locale: MessageLocale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    /// 分页查询，按主键排序
+    pub fn select(&self, table: &TableSchema, limit: u64, offset: u64) -> Result<Statement, DmlError> {
+        let mut sql = self.select_sql(table)?;
+        sql.push_str(&format!(" LIMIT {} OFFSET {}", limit, offset));
+        Ok(Statement::new(sql, Vec::new()))
+    }
+
+    /// 查询所有行，按主键排序，用于导出字典的种子数据
+    pub fn select_all(&self, table: &TableSchema) -> Result<Statement, DmlError> {
+        Ok(Statement::new(self.select_sql(table)?, Vec::new()))
+    }
+
+    /// 总行数，结果为 `bigint`
+    pub fn count(&self, table: &TableSchema) -> Result<Statement, DmlError> {
+        let name = self.table_name(table)?;
+        Ok(Statement::new(format!("SELECT count(*) FROM {}", self.qualified(&name)), Vec::new()))
+    }
+
+    /// 按主键查询一行
+    pub fn select_by_key(&self, table: &TableSchema, key: &str) -> Result<Statement, DmlError> {
+        let name = self.table_name(table)?;
+        let mut params = Vec::new();
+        let condition = Self::key_condition(&name, table, key, &mut params)?;
+        let sql = format!("SELECT {} FROM {} AS t WHERE {}", ROW_JSON, self.qualified(&name), condition);
+        Ok(Statement::new(sql, params))
+    }
+
+    /// 新增一行
+    ///
+    /// 未给出的列先按行工厂填充默认值，整行校验后写入不为 `NULL` 的列和显式给出 `null` 的列。
+    /// 显式给出的 `null` 不填充默认值，系统列（创建人、时间戳等）除外。
+    ///
+    /// # 返回值
+    ///
+    /// - `Err(DmlError::UnknownColumn)` - `values` 中有表定义中不存在的列
+    /// - `Err(DmlError::InvalidValue)` - 值无法转换为列类型
+    /// - `Err(DmlError::Violations)` - 违反列约束，包含所有违反的约束
+    pub fn insert(&self, table: &TableSchema, values: &RowValues) -> Result<Statement, DmlError> {
+        self.insert_row(table, values, false)
+    }
+
+    /// 按主键新增或覆盖一行，用于导入种子数据
+    ///
+    /// 与 [`PgDml::insert`] 一样填充默认值并校验整行，主键已存在时改写写入的非主键列。
+    ///
+    /// # 返回值
+    ///
+    /// 错误同 [`PgDml::insert`]，表没有主键时返回 `Err(DmlError::MissingPrimaryKey)`。
+    pub fn upsert(&self, table: &TableSchema, values: &RowValues) -> Result<Statement, DmlError> {
+        self.insert_row(table, values, true)
+    }
+
+    fn insert_row(&self, table: &TableSchema, values: &RowValues, upsert: bool) -> Result<Statement, DmlError> {
+        let name = self.table_name(table)?;
+        let keys = primary_key_columns(table);
+        if upsert && keys.is_empty() {
+            return Err(DmlError::MissingPrimaryKey(name));
+        }
+        let (mut row, given) = Self::row_values(&name, table, values)?;
+        let nulls: Vec<bool> = row.iter().zip(&given).map(|(value, given)| *given && value.is_null()).collect();
+        let factory = self.row_factory(table)?;
+        factory.fill_defaults(&mut row)?;
+        for (index, column) in table.columns.iter().enumerate() {
+            if nulls[index] && factory.default_value(&column.col_id()).is_some() {
+                row[index] = CellValue::Null;
+            }
+        }
+        self.check(table, &row, &vec![true; row.len()])?;
+
+        let mut params = Vec::new();
+        let mut columns = Vec::new();
+        let mut placeholders = Vec::new();
+        let mut assignments = Vec::new();
+        for ((column, value), null) in table.columns.iter().zip(&row).zip(&nulls) {
+            if value.is_null() && !null {
+                continue;
+            }
+            let col_id = column.col_id();
+            if !keys.contains(&col_id) {
+                assignments.push(format!("{0} = EXCLUDED.{0}", quote_ident(&col_id)));
+            }
+            columns.push(quote_ident(&col_id));
+            placeholders.push(Self::bind(column, value, &mut params));
+        }
+        let mut sql = if columns.is_empty() {
+            format!("INSERT INTO {} AS t DEFAULT VALUES", self.qualified(&name))
+        } else {
+            format!(
+                "INSERT INTO {} AS t ({}) VALUES ({})",
+                self.qualified(&name),
+                columns.join(", "),
+                placeholders.join(", ")
+            )
+        };
+        if upsert {
+            let target: Vec<String> = keys.iter().map(|key| quote_ident(key)).collect();
+            if assignments.is_empty() {
+                sql.push_str(&format!(" ON CONFLICT ({}) DO NOTHING", target.join(", ")));
+            } else {
+                sql.push_str(&format!(" ON CONFLICT ({}) DO UPDATE SET {}", target.join(", "), assignments.join(", ")));
+            }
+        }
+        sql.push_str(&format!(" RETURNING {}", ROW_JSON));
+        Ok(Statement::new(sql, params))
+    }
+
+    /// 按主键修改一行
+    ///
+    /// 只写入 `values` 中给出的列和修改时间、修改人，只校验写入的列。
+    ///
+    /// # 返回值
+    ///
+    /// 错误同 [`PgDml::insert`]，`values` 为空时返回 `Err(DmlError::NoChanges)`。
+    pub fn update(&self, table: &TableSchema, key: &str, values: &RowValues) -> Result<Statement, DmlError> {
+        let name = self.table_name(table)?;
+        let (mut row, mut written) = Self::row_values(&name, table, values)?;
+        if !written.contains(&true) {
+            return Err(DmlError::NoChanges(name));
+        }
+        let given = row.clone();
+        self.row_factory(table)?.touch(&mut row)?;
+        for (index, value) in row.iter().enumerate() {
+            if *value != given[index] {
+                written[index] = true;
+            }
+        }
+        self.check(table, &row, &written)?;
+
+        let mut params = Vec::new();
+        let mut assignments = Vec::new();
+        for ((column, value), _) in table.columns.iter().zip(&row).zip(&written).filter(|(_, written)| **written) {
+            let placeholder = Self::bind(column, value, &mut params);
+            assignments.push(format!("{} = {}", quote_ident(&column.col_id()), placeholder));
+        }
+        let condition = Self::key_condition(&name, table, key, &mut params)?;
+        let sql = format!(
+            "UPDATE {} AS t SET {} WHERE {} RETURNING {}",
+            self.qualified(&name),
+            assignments.join(", "),
+            condition,
+            ROW_JSON
+        );
+        Ok(Statement::new(sql, params))
+    }
+
+    /// 按主键删除一行，返回被删除的行
+    pub fn delete(&self, table: &TableSchema, key: &str) -> Result<Statement, DmlError> {
+        let name = self.table_name(table)?;
+        let mut params = Vec::new();
+        let condition = Self::key_condition(&name, table, key, &mut params)?;
+        let sql = format!("DELETE FROM {} AS t WHERE {} RETURNING {}", self.qualified(&name), condition, ROW_JSON);
+        Ok(Statement::new(sql, params))
+    }
+
+    /// 按代码批量查询字典的名称，供外键名称翻译使用（见 [`super::decorate`]）
+    ///
+    /// 代码列为 `DCT_BMCOLID`，未设置时使用单列主键；名称列为 `DCT_MCCOLID`，未设置时名称为 `null`，
+    /// 只能用于检查代码是否存在。代码按文本比较，每个找到的代码返回一行 `[代码, 名称]` 的 JSON 数组文本。
+    ///
+    /// # 返回值
+    ///
+    /// - `Err(DmlError::MissingDictColumn)` - 字典没有设置代码列，也没有单列主键
+    /// - `Err(DmlError::UnknownColumn)` - 代码列或名称列不在表定义中
+    pub fn select_names(&self, dict: &DCTMeta, codes: &[String]) -> Result<Statement, DmlError> {
+        let table = &dict.table_schema;
+        let name = self.table_name(table)?;
+        let code_column = Self::dict_column(dict, SYS_DICTS::DCT_BMCOLID).or_else(|| {
+            let mut keys = primary_key_columns(table);
+            (keys.len() == 1).then(|| keys.remove(0))
+        });
+        let code_column = code_column.ok_or_else(|| DmlError::MissingDictColumn {
+            dct_id: dict.dct_id.clone(),
+            field: SYS_DICTS::DCT_BMCOLID,
+        })?;
+        let name_column = Self::dict_column(dict, SYS_DICTS::DCT_MCCOLID);
+        for column in std::iter::once(&code_column).chain(&name_column) {
+            if table.get_column_index(column).is_none() {
+                return Err(DmlError::UnknownColumn { table: name.clone(), column: column.clone() });
+            }
+        }
+
+        let code = format!("t.{}::text", quote_ident(&code_column));
+        let params: Vec<Option<String>> = codes.iter().cloned().map(Some).collect();
+        let condition = if params.is_empty() {
+            "false".to_string()
+        } else {
+            let placeholders: Vec<String> = (1..=params.len()).map(|i| format!("${}::text", i)).collect();
+            format!("{} IN ({})", code, placeholders.join(", "))
+        };
+        let name_value = name_column.map_or_else(|| "NULL".to_string(), |column| format!("t.{}", quote_ident(&column)));
+        let sql = format!(
+            "SELECT jsonb_build_array({}, {})::text FROM {} AS t WHERE {}",
+            code,
+            name_value,
+            self.qualified(&name),
+            condition
+        );
+        Ok(Statement::new(sql, params))
+    }
+
+    /// 解析语句返回的行
+    ///
+    /// 二进制列从 Postgres 的十六进制文本转换为 Base64，与写入时的表示一致。
+    pub fn decode_row(table: &TableSchema, text: &str) -> Result<RowValues, serde_json::Error> {
+        let mut row: RowValues = serde_json::from_str(text)?;
+        for column in &table.columns {
+            if PgType::of(column) != PgType::Bytea {
+                continue;
+            }
+            if let Some(CellValue::String(value)) = row.get_mut(&column.col_id())
+                && let Some(bytes) = value.strip_prefix("\\x").and_then(decode_hex)
+            {
+                *value = BASE64.encode(bytes);
+            }
+        }
+        Ok(row)
+    }
+
+    fn qualified(&self, name: &str) -> String {
+        qualified_name(self.schema.as_deref(), name)
+    }
+
+    fn select_sql(&self, table: &TableSchema) -> Result<String, DmlError> {
+        let name = self.table_name(table)?;
+        let mut sql = format!("SELECT {} FROM {} AS t", ROW_JSON, self.qualified(&name));
+        let order: Vec<String> = primary_key_columns(table)
+            .iter()
+            .map(|column| format!("t.{}", quote_ident(column)))
+            .collect();
+        if !order.is_empty() {
+            sql.push_str(&format!(" ORDER BY {}", order.join(", ")));
+        }
+        Ok(sql)
+    }
+
+    fn table_name(&self, table: &TableSchema) -> Result<String, DmlError> {
+        table
+            .obj_id()
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .map(str::to_string)
+            .ok_or(DmlError::MissingObjectId)
+    }
+
+    fn dict_column(dict: &DCTMeta, field: SYS_DICTS) -> Option<String> {
+        dict.get_string(&field).map(|s| s.trim().to_string()).filter(|s| !s.is_empty())
+    }
+
+    fn row_factory(&self, table: &TableSchema) -> Result<RowFactory, DmlError> {
+        Ok(table.row_factory(self.context.clone())?)
+    }
+
+    /// 按表定义的列顺序排列的值，以及每一列是否在 `values` 中给出
+    fn row_values(name: &str, table: &TableSchema, values: &RowValues) -> Result<(Vec<CellValue>, Vec<bool>), DmlError> {
+        let mut row = vec![CellValue::Null; table.column_count()];
+        let mut given = vec![false; table.column_count()];
+        for (column, value) in values {
+            let index = table.get_column_index(column).ok_or_else(|| DmlError::UnknownColumn {
+                table: name.to_string(),
+                column: column.clone(),
+            })?;
+            row[index] = Self::coerce(&table.columns[index], value.clone())?;
+            given[index] = true;
+        }
+        Ok((row, given))
+    }
+
+    fn coerce(column: &ColumnDef, value: CellValue) -> Result<CellValue, DmlError> {
+        let column_type = if column.is_multilingual() { ColumnType::Json } else { column.column_type() };
+        column_type
+            .coerce(value)
+            .map_err(|value| DmlError::InvalidValue { column: column.col_id(), value })
+    }
+
+    /// 校验整行，只报告 `columns` 中标记的列违反的约束
+    fn check(&self, table: &TableSchema, row: &[CellValue], columns: &[bool]) -> Result<(), DmlError> {
+        let validator = Validator::new(table)?.with_locale(self.locale);
+        let mut dataset = DataSet::new();
+        dataset.add_row(table, row.to_vec())?;
+        let violations: Vec<_> = validator
+            .validate_dataset(&dataset)?
+            .into_iter()
+            .filter(|violation| table.get_column_index(&violation.column).is_some_and(|index| columns[index]))
+            .collect();
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(DmlError::Violations(violations))
+        }
+    }
+
+    /// 按主键定位行的条件，主键值追加到 `params`
+    fn key_condition(
+        name: &str,
+        table: &TableSchema,
+        key: &str,
+        params: &mut Vec<Option<String>>,
+    ) -> Result<String, DmlError> {
+        let columns = primary_key_columns(table);
+        if columns.is_empty() {
+            return Err(DmlError::MissingPrimaryKey(name.to_string()));
+        }
+        let invalid = || DmlError::InvalidKey { table: name.to_string(), key: key.to_string(), expected: columns.len() };
+        let parts: Vec<CellValue> = if columns.len() == 1 {
+            vec![CellValue::String(key.trim().to_string())]
+        } else if key.trim_start().starts_with('[') {
+            // JSON 数组形式，值中可以含有逗号
+            serde_json::from_str(key).map_err(|_| invalid())?
+        } else {
+            key.split(',').map(|part| CellValue::String(part.trim().to_string())).collect()
+        };
+        if parts.len() != columns.len() || parts.iter().any(|part| part.is_null() || part.is_array() || part.is_object()) {
+            return Err(invalid());
+        }
+
+        let mut conditions = Vec::with_capacity(columns.len());
+        for (column, part) in columns.iter().zip(parts) {
+            let def = table.get_column(column).ok_or_else(|| DmlError::UnknownColumn {
+                table: name.to_string(),
+                column: column.clone(),
+            })?;
+            let value = Self::coerce(def, part)?;
+            let placeholder = Self::bind(def, &value, params);
+            conditions.push(format!("t.{} = {}", quote_ident(column), placeholder));
+        }
+        Ok(conditions.join(" AND "))
+    }
+
+    /// 把值追加到 `params`，返回语句中引用它的表达式
+    fn bind(column: &ColumnDef, value: &CellValue, params: &mut Vec<Option<String>>) -> String {
+        let pg_type = PgType::of(column);
+        params.push(match value {
+            CellValue::Null => None,
+            _ if pg_type == PgType::Jsonb => Some(value.to_string()),
+            CellValue::String(s) => Some(s.clone()),
+            other => Some(other.to_string()),
+        });
+        match pg_type {
+            PgType::Bytea => format!("decode(${}, 'base64')", params.len()),
+            pg_type => format!("${}::{}", params.len(), pg_type),
+        }
+    }
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| hex.get(i..i + 2).and_then(|byte| u8::from_str_radix(byte, 16).ok()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::model::data::dataset::key::KeyDef;
+    use crate::model::data::dataset::validate::Rule;
+    use crate::model::data::dataset::TableSchemaBuilder;
+    use crate::model::meta::fields::{SYS_KEYS, SYS_OBJCOLS};
+
+    fn column(id: &str, column_type: &str) -> ColumnDef {
+        let mut column = ColumnDef::default();
+        column.set(SYS_OBJCOLS::COL_ID, json!(id));
+        column.set(SYS_OBJCOLS::COL_TYPE, json!(column_type));
+        column
+    }
+
+    fn with(mut column: ColumnDef, field: SYS_OBJCOLS, value: CellValue) -> ColumnDef {
+        column.set(field, value);
+        column
+    }
+
+    fn row(value: CellValue) -> RowValues {
+        value.as_object().cloned().unwrap()
+    }
+
+    fn items() -> TableSchema {
+        let mut key = KeyDef::default();
+        key.set(SYS_KEYS::KEY_PINDEX1, json!("DWDM"));
+        key.set(SYS_KEYS::KEY_PINDEX2, json!("DM"));
+        TableSchemaBuilder::new()
+            .with_obj_id("T_ITEM".to_string())
+            .with_columns(vec![
+                column("DWDM", "varchar"),
+                column("DM", "int"),
+                with(
+                    with(column("MC", "varchar"), SYS_OBJCOLS::COL_ISNULL, json!(false)),
+                    SYS_OBJCOLS::COL_MC,
+                    json!("名称"),
+                ),
+                with(column("SL", "int"), SYS_OBJCOLS::COL_DEFAULT, json!("1")),
+                column("TP", "binary"),
+                with(column("BZ", "varchar"), SYS_OBJCOLS::COL_LANG, json!(true)),
+                column("F_CHUSER", "varchar"),
+            ])
+            .with_keys(vec![key])
+            .build()
+    }
+
+    #[test]
+    fn test_select() {
+        let dml = PgDml::new().with_schema("biz");
+        assert_eq!(
+            dml.select(&items(), 20, 40).unwrap().sql,
+            "SELECT to_jsonb(t)::text FROM \"biz\".\"T_ITEM\" AS t ORDER BY t.\"DWDM\", t.\"DM\" LIMIT 20 OFFSET 40"
+        );
+        assert_eq!(dml.count(&items()).unwrap().sql, "SELECT count(*) FROM \"biz\".\"T_ITEM\"");
+
+        let select = dml.select_by_key(&items(), "0101,3").unwrap();
+        assert_eq!(
+            select.sql,
+            "SELECT to_jsonb(t)::text FROM \"biz\".\"T_ITEM\" AS t WHERE t.\"DWDM\" = $1::text AND t.\"DM\" = $2::integer"
+        );
+        assert_eq!(select.params, vec![Some("0101".to_string()), Some("3".to_string())]);
+
+        // 值中含有逗号时以 JSON 数组给出
+        let select = dml.select_by_key(&items(), r#"["01,02", 3]"#).unwrap();
+        assert_eq!(select.params, vec![Some("01,02".to_string()), Some("3".to_string())]);
+        assert!(matches!(dml.select_by_key(&items(), "01,02,3"), Err(DmlError::InvalidKey { expected: 2, .. })));
+        assert!(matches!(dml.select_by_key(&items(), r#"["01", null]"#), Err(DmlError::InvalidKey { .. })));
+        assert!(matches!(dml.select_by_key(&items(), "[01,3"), Err(DmlError::InvalidKey { .. })));
+
+        assert!(matches!(dml.select_by_key(&items(), "0101"), Err(DmlError::InvalidKey { expected: 2, .. })));
+        assert!(matches!(dml.delete(&items(), "0101,x"), Err(DmlError::InvalidValue { column, .. }) if column == "DM"));
+        let keyless = TableSchemaBuilder::new().with_obj_id("T_LOG".to_string()).build();
+        assert!(matches!(dml.delete(&keyless, "1"), Err(DmlError::MissingPrimaryKey(table)) if table == "T_LOG"));
+    }
+
+    #[test]
+    fn test_insert_fills_defaults() {
+        let context = SVRContext::new();
+        context.set(SVRContext::USER, json!("admin"));
+        let dml = PgDml::new().with_context(context);
+        let insert = dml
+            .insert(&items(), &row(json!({"DWDM": "0101", "DM": 3, "MC": "螺丝", "TP": [1, 2], "BZ": {"en": "Screw"}})))
+            .unwrap();
+        assert_eq!(
+            insert.sql,
+            "INSERT INTO \"T_ITEM\" AS t (\"DWDM\", \"DM\", \"MC\", \"SL\", \"TP\", \"BZ\", \"F_CHUSER\") \
+             VALUES ($1::text, $2::integer, $3::text, $4::integer, decode($5, 'base64'), $6::jsonb, $7::text) \
+             RETURNING to_jsonb(t)::text"
+        );
+        assert_eq!(
+            insert.params,
+            ["0101", "3", "螺丝", "1", "AQI=", r#"{"en":"Screw"}"#, "admin"].map(|p| Some(p.to_string()))
+        );
+
+        // 显式给出的 null 不填充默认值，按 NULL 写入
+        let insert = dml.insert(&items(), &row(json!({"DWDM": "0101", "DM": 3, "MC": "螺丝", "SL": null}))).unwrap();
+        assert_eq!(
+            insert.sql,
+            "INSERT INTO \"T_ITEM\" AS t (\"DWDM\", \"DM\", \"MC\", \"SL\", \"F_CHUSER\") \
+             VALUES ($1::text, $2::integer, $3::text, $4::integer, $5::text) \
+             RETURNING to_jsonb(t)::text"
+        );
+        assert_eq!(insert.params[3], None);
+    }
+
+    #[test]
+    fn test_upsert_updates_non_key_columns() {
+        let dml = PgDml::new();
+        let upsert = dml.upsert(&items(), &row(json!({"DWDM": "0101", "DM": 3, "MC": "螺丝"}))).unwrap();
+        assert_eq!(
+            upsert.sql,
+            "INSERT INTO \"T_ITEM\" AS t (\"DWDM\", \"DM\", \"MC\", \"SL\") \
+             VALUES ($1::text, $2::integer, $3::text, $4::integer) \
+             ON CONFLICT (\"DWDM\", \"DM\") DO UPDATE SET \"MC\" = EXCLUDED.\"MC\", \"SL\" = EXCLUDED.\"SL\" \
+             RETURNING to_jsonb(t)::text"
+        );
+        assert_eq!(
+            dml.select_all(&items()).unwrap().sql,
+            "SELECT to_jsonb(t)::text FROM \"T_ITEM\" AS t ORDER BY t.\"DWDM\", t.\"DM\""
+        );
+
+        let keyless = TableSchemaBuilder::new().with_obj_id("T_LOG".to_string()).build();
+        assert!(matches!(dml.upsert(&keyless, &RowValues::new()), Err(DmlError::MissingPrimaryKey(table)) if table == "T_LOG"));
+    }
+
+    #[test]
+    fn test_insert_rejects_invalid_rows() {
+        let dml = PgDml::new();
+        assert!(matches!(
+            dml.insert(&items(), &row(json!({"DM": 1, "XX": 1}))),
+            Err(DmlError::UnknownColumn { column, .. }) if column == "XX"
+        ));
+        assert!(matches!(
+            dml.insert(&items(), &row(json!({"DM": "abc"}))),
+            Err(DmlError::InvalidValue { column, .. }) if column == "DM"
+        ));
+        let Err(DmlError::Violations(violations)) = dml.insert(&items(), &row(json!({"DM": 1}))) else {
+            panic!("expected violations");
+        };
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].column, "MC");
+        assert_eq!(violations[0].rule, Rule::NotNull);
+        assert_eq!(violations[0].message, "名称不能为空");
+    }
+
+    #[test]
+    fn test_update_writes_given_columns() {
+        let context = SVRContext::new();
+        context.set(SVRContext::USER, json!("editor"));
+        let dml = PgDml::new().with_context(context);
+        let update = dml.update(&items(), "0101,3", &row(json!({"SL": "5"}))).unwrap();
+        assert_eq!(
+            update.sql,
+            "UPDATE \"T_ITEM\" AS t SET \"SL\" = $1::integer, \"F_CHUSER\" = $2::text \
+             WHERE t.\"DWDM\" = $3::text AND t.\"DM\" = $4::integer RETURNING to_jsonb(t)::text"
+        );
+        assert_eq!(update.params, ["5", "editor", "0101", "3"].map(|p| Some(p.to_string())));
+
+        // 未写入的非空列不校验，写入 null 时校验
+        assert!(matches!(dml.update(&items(), "0101,3", &RowValues::new()), Err(DmlError::NoChanges(_))));
+        assert!(matches!(
+            dml.update(&items(), "0101,3", &row(json!({"MC": null}))),
+            Err(DmlError::Violations(violations)) if violations[0].column == "MC"
+        ));
+    }
+
+    #[test]
+    fn test_select_names() {
+        let dept = TableSchemaBuilder::new()
+            .with_obj_id("T_DEPT".to_string())
+            .with_columns(vec![with(column("DM", "varchar"), SYS_OBJCOLS::COL_ISKEY, json!(true)), column("MC", "varchar")])
+            .build();
+        let mut dict = DCTMeta::new("DCT_DEPT".to_string(), dept);
+        let codes = ["10".to_string(), "11".to_string()];
+        let select = PgDml::new().select_names(&dict, &codes[..1]).unwrap();
+        assert_eq!(
+            select.sql,
+            "SELECT jsonb_build_array(t.\"DM\"::text, NULL)::text FROM \"T_DEPT\" AS t WHERE t.\"DM\"::text IN ($1::text)"
+        );
+
+        // 没有设置代码列时使用单列主键
+        dict.set(SYS_DICTS::DCT_MCCOLID, json!("MC"));
+        let select = PgDml::new().with_schema("app").select_names(&dict, &codes).unwrap();
+        assert_eq!(
+            select.sql,
+            "SELECT jsonb_build_array(t.\"DM\"::text, t.\"MC\")::text FROM \"app\".\"T_DEPT\" AS t \
+             WHERE t.\"DM\"::text IN ($1::text, $2::text)"
+        );
+        assert_eq!(select.params, ["10", "11"].map(|p| Some(p.to_string())));
+
+        dict.set(SYS_DICTS::DCT_BMCOLID, json!("BM"));
+        assert!(matches!(
+            PgDml::new().select_names(&dict, &codes),
+            Err(DmlError::UnknownColumn { column, .. }) if column == "BM"
+        ));
+    }
+
+    #[test]
+    fn test_decode_row() {
+        let row = PgDml::decode_row(&items(), r#"{"DM": 3, "TP": "\\x0102", "BZ": {"en": "Screw"}}"#).unwrap();
+        assert_eq!(row["TP"], json!("AQI="));
+        assert_eq!(row["BZ"], json!({"en": "Screw"}));
+        assert_eq!(row["DM"], json!(3));
+    }
+}
@@ -1,5 +1,4 @@
-use std::{collections::HashMap, sync::Arc, sync::RwLock};
-use lazy_static::lazy_static;
+use std::{collections::HashMap, sync::Arc};
 
 use crate::model::data::{dataset::TableSchema, KeyValue};
 
@@ -7,11 +6,7 @@ use super::dct::DCTMeta;
 
 use serde::{Deserialize, Serialize};
 
-lazy_static! {
-    static ref FCT_METAS: RwLock<HashMap<String, Arc<FCTMeta>>> = RwLock::new(HashMap::new());
-}
-
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FCTMeta {
     pub id: String,                // ID
     pub name: String,              // Name
@@ -22,29 +17,6 @@ pub struct FCTMeta {
     pub settings: Option<KeyValue>, // 可选的 KeyValue 成员
 }
 
-#[derive(Debug)]
-pub struct FCTMetaManager;
-
-impl FCTMetaManager {
-
-    pub fn add_meta(meta: Arc<FCTMeta>) {
-        let mut metas = FCT_METAS.write().unwrap();
-        metas.insert(meta.id.clone(), meta);
-    }
-
-    pub fn get_meta(id: &str) -> Option<Arc<FCTMeta>> {
-        let metas = FCT_METAS.read().unwrap();
-        metas.get(id).cloned()
-    }
-
-    // Optional: Add a clear method for testing
-    #[cfg(test)]
-    pub fn clear() {
-        let mut metas = FCT_METAS.write().unwrap();
-        metas.clear();
-    }
-}
-
 // #[cfg(test)]
 // mod tests {
 //     use super::*;
@@ -4,3 +4,14 @@ pub mod fbm;
 pub mod dme;
 pub mod fct;
 pub mod dct;
+pub mod tree;
+pub mod unit;
+pub mod snapshot;
+pub mod ddl;
+pub mod catalog;
+pub mod registry;
+pub mod reload;
+pub mod dml;
+pub mod decorate;
+pub mod package;
+pub mod cube;
@@ -0,0 +1,1134 @@
+//! # 元数据包
+//!
+//! 把字典（DCTMeta）、事实表（FCTMeta）、模型（DMEMeta）和它们的对象定义（TableSchema）打包为一个 zip 文件，
+//! 在环境之间迁移配置好的元数据，代替手工重写 `SYS_DICTS`、`SYS_OBJECTS`、`SYS_OBJCOLS`、`SYS_KEYS` 等目录表的行。
+//!
+//! 包的结构：
+//!
+//! | 路径 | 内容 |
+//! |------|------|
+//! | `manifest.json` | 清单，见 [`Manifest`]：格式名称、格式版本、载荷编码和每个条目的依赖 |
+//! | `schemas/<OBJ_ID>` | 对象定义 |
+//! | `dicts/<DCT_ID>` | 字典元数据 |
+//! | `facts/<FCT_ID>` | 事实表元数据 |
+//! | `models/<MDL_ID>` | 模型元数据，模型的事实表记录在清单的依赖中 |
+//! | `data/<OBJ_ID>` | 可选的种子数据，按对象定义写入 |
+//!
+//! 载荷为 JSON（`.json`）或 rkyv 归档（`.rkyv`，见 [`crate::model::data::dataset::archive`]），由清单的 `encoding` 指定。
+//! zip 条目使用 deflate 压缩，条目或包超过 4 GiB、条目超过 65535 个时使用 zip64 扩展；
+//! 读取时接受不压缩和 deflate 压缩的条目，忽略目录条目，其他工具打包的元数据包也能导入。
+//!
+//! - 导出（[`MetaPackage::export`]）从指定的条目出发，沿依赖收集：字典依赖对象定义和外键字典，
+//!   事实表依赖对象定义和外键列的字典，模型依赖事实表、关键指标字典和单位字典，对象定义依赖外键列的字典
+//! - 导入前先生成计划（[`ImportPlan::new`]）：按依赖排序，与目标目录比较每个条目，
+//!   新建、覆盖、跳过还是冲突由 [`ConflictPolicy`] 决定；依赖在包和目标目录中都找不到时记录问题。
+//!   只生成计划、不执行即为试运行，执行见 `cmx-infra` 的 `import_package`
+//!
+//! ## 示例
+//!
+//! ```rust
+//! use cmx_core::model::data::cell::CellValue;
+//! use cmx_core::model::meta::catalog::{Catalog, CatalogRows};
+//! use cmx_core::model::meta::package::{ConflictPolicy, EntryRef, ImportAction, ImportPlan, MetaPackage, PayloadEncoding};
+//! use cmx_core::model::meta::registry::MetaCatalog;
+//! use cmx_core::model::meta::tables::SYS_TABLE_NAMES;
+//! use serde_json::json;
+//!
+//! let mut rows = CatalogRows::new();
+//! rows.push_json(SYS_TABLE_NAMES::SYS_OBJECTS, json!({"OBJ_ID": "T_DEPT"}));
+//! rows.push_json(SYS_TABLE_NAMES::SYS_OBJCOLS, json!({"OBJ_ID": "T_DEPT", "COL_ID": "DM"}));
+//! rows.push_json(SYS_TABLE_NAMES::SYS_DICTS, json!({"DCT_ID": "DCT_DEPT", "OBJ_ID": "T_DEPT"}));
+//! let source = MetaCatalog::from(&Catalog::build(&rows));
+//!
+//! // 导出字典时一并导出它的对象定义
+//! let package = MetaPackage::export(&source, &[EntryRef::dict("DCT_DEPT")]).unwrap();
+//! assert!(package.schema("T_DEPT").is_some());
+//!
+//! let bytes = package.to_bytes(PayloadEncoding::Json).unwrap();
+//! let package = MetaPackage::from_bytes(&bytes).unwrap();
+//!
+//! // 试运行：目标目录为空，所有条目都是新建
+//! let plan = ImportPlan::new(&package, &MetaCatalog::new(), ConflictPolicy::Fail);
+//! assert!(!plan.is_blocked());
+//! assert!(plan.steps.iter().all(|step| step.action == ImportAction::Create));
+//!
+//! // 写入目录表的行
+//! let records = package.catalog_rows(&EntryRef::dict("DCT_DEPT"));
+//! assert_eq!(records[0].1["OBJ_ID"], CellValue::from("T_DEPT"));
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt;
+use std::io::{Cursor, Read, Write};
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use zip::result::ZipError;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::model::data::cell::CellValue;
+use crate::model::data::dataset::archive::{self, from_cell, to_cell, Archivable, ArchiveError, ArchiveKind, Cell};
+use crate::model::data::dataset::TableSchema;
+
+use super::catalog::{dict_dependencies, fact_dependencies, model_dictionaries, CatalogRecord};
+use super::dct::DCTMeta;
+use super::dme::DMEMeta;
+use super::dml::RowValues;
+use super::fct::FCTMeta;
+use super::registry::MetaCatalog;
+use super::reload::MetaChange;
+use super::tables::SYS_TABLE_NAMES;
+
+/// 清单中的格式名称
+pub const PACKAGE_FORMAT: &str = "cmx-meta-package";
+
+/// 当前包格式版本
+///
+/// 包的结构发生不兼容的变化时递增，更高版本的包会被拒绝。
+pub const PACKAGE_VERSION: u32 = 1;
+
+/// 清单在包中的路径
+pub const MANIFEST_PATH: &str = "manifest.json";
+
+/// 比较条目时忽略的审计字段，环境之间这些字段总是不同
+const AUDIT_FIELDS: [&str; 4] = ["F_CRDATE", "F_CHDATE", "F_CRUSER", "F_CHUSER"];
+
+/// 一条 `SYS_MDL_CTN` 记录最多引用的事实表数
+const FACTS_PER_CONTENT: usize = 16;
+
+impl Archivable for DMEMeta {
+    const KIND: ArchiveKind = ArchiveKind::DMEMeta;
+    type Repr = Cell;
+
+    fn to_repr(&self) -> Result<Self::Repr, ArchiveError> {
+        to_cell(self)
+    }
+
+    fn from_repr(repr: Self::Repr) -> Result<Self, ArchiveError> {
+        from_cell(repr)
+    }
+}
+
+/// 种子数据的归档表示
+struct SeedRows(Vec<RowValues>);
+
+impl Archivable for SeedRows {
+    const KIND: ArchiveKind = ArchiveKind::SeedRows;
+    type Repr = Cell;
+
+    fn to_repr(&self) -> Result<Self::Repr, ArchiveError> {
+        to_cell(&self.0)
+    }
+
+    fn from_repr(repr: Self::Repr) -> Result<Self, ArchiveError> {
+        from_cell(repr).map(Self)
+    }
+}
+
+/// 包条目的类型，按导入顺序排列
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryKind {
+    /// 对象定义，按 `OBJ_ID`
+    Schema,
+    /// 字典，按 `DCT_ID`
+    Dict,
+    /// 事实表，按 `FCT_ID`
+    Fact,
+    /// 模型，按 `MDL_ID`
+    Model,
+    /// 种子数据，按 `OBJ_ID`
+    Data,
+}
+
+impl EntryKind {
+    /// 条目在包中的目录
+    fn dir(self) -> &'static str {
+        match self {
+            Self::Schema => "schemas",
+            Self::Dict => "dicts",
+            Self::Fact => "facts",
+            Self::Model => "models",
+            Self::Data => "data",
+        }
+    }
+
+    /// 条目写入的目录表，种子数据不写目录表
+    pub fn tables(self) -> &'static [SYS_TABLE_NAMES] {
+        match self {
+            Self::Schema => &[
+                SYS_TABLE_NAMES::SYS_OBJECTS,
+                SYS_TABLE_NAMES::SYS_OBJCOLS,
+                SYS_TABLE_NAMES::SYS_KEYS,
+                SYS_TABLE_NAMES::SYS_INDEXS,
+            ],
+            Self::Dict => &[SYS_TABLE_NAMES::SYS_DICTS],
+            Self::Fact => &[SYS_TABLE_NAMES::SYS_FACTS],
+            Self::Model => &[SYS_TABLE_NAMES::SYS_MODEL, SYS_TABLE_NAMES::SYS_MDL_CTN],
+            Self::Data => &[],
+        }
+    }
+
+    /// 目录表中标识条目的列
+    pub fn key_column(self) -> &'static str {
+        match self {
+            Self::Schema | Self::Data => "OBJ_ID",
+            Self::Dict => "DCT_ID",
+            Self::Fact => "FCT_ID",
+            Self::Model => "MDL_ID",
+        }
+    }
+}
+
+impl fmt::Display for EntryKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Schema => write!(f, "object"),
+            Self::Dict => write!(f, "dictionary"),
+            Self::Fact => write!(f, "fact"),
+            Self::Model => write!(f, "model"),
+            Self::Data => write!(f, "seed data"),
+        }
+    }
+}
+
+/// 包条目的引用
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct EntryRef {
+    pub kind: EntryKind,
+    pub id: String,
+}
+
+impl EntryRef {
+    pub fn new(kind: EntryKind, id: impl Into<String>) -> Self {
+        Self { kind, id: id.into() }
+    }
+
+    pub fn schema(obj_id: impl Into<String>) -> Self {
+        Self::new(EntryKind::Schema, obj_id)
+    }
+
+    pub fn dict(dct_id: impl Into<String>) -> Self {
+        Self::new(EntryKind::Dict, dct_id)
+    }
+
+    pub fn fact(fct_id: impl Into<String>) -> Self {
+        Self::new(EntryKind::Fact, fct_id)
+    }
+
+    pub fn model(mdl_id: impl Into<String>) -> Self {
+        Self::new(EntryKind::Model, mdl_id)
+    }
+
+    pub fn data(obj_id: impl Into<String>) -> Self {
+        Self::new(EntryKind::Data, obj_id)
+    }
+}
+
+impl fmt::Display for EntryRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} '{}'", self.kind, self.id)
+    }
+}
+
+/// 载荷编码
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PayloadEncoding {
+    #[default]
+    Json,
+    Rkyv,
+}
+
+impl PayloadEncoding {
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Rkyv => "rkyv",
+        }
+    }
+}
+
+/// 清单中的条目
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PackageEntry {
+    pub kind: EntryKind,
+    pub id: String,
+    /// 载荷在包中的路径
+    pub path: String,
+    /// 条目依赖的其他条目，不一定在包中
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<EntryRef>,
+}
+
+/// 包清单
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    /// 格式名称，为 [`PACKAGE_FORMAT`]
+    pub format: String,
+    /// 格式版本，见 [`PACKAGE_VERSION`]
+    pub version: u32,
+    pub encoding: PayloadEncoding,
+    pub created: DateTime<Utc>,
+    pub entries: Vec<PackageEntry>,
+}
+
+/// 元数据包错误
+#[derive(Debug, Error)]
+pub enum PackageError {
+    #[error("Invalid package archive: {0}")]
+    Container(#[from] ZipError),
+    #[error("Package has no {MANIFEST_PATH}")]
+    MissingManifest,
+    #[error("Not a metadata package: format '{0}'")]
+    BadFormat(String),
+    #[error("Unsupported package version {found}, expected at most {PACKAGE_VERSION}")]
+    UnsupportedVersion { found: u32 },
+    #[error("Package payload '{0}' is missing")]
+    MissingPayload(String),
+    #[error("{0} not found")]
+    NotFound(EntryRef),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Archive(#[from] ArchiveError),
+}
+
+/// 元数据包
+///
+/// 包中的元数据不关联外键字典和事实表（`dct_metas`、`fct_meta_map` 等），导入后由目录重新关联。
+#[derive(Debug, Clone)]
+pub struct MetaPackage {
+    created: DateTime<Utc>,
+    /// 条目 -> 依赖的条目
+    entries: BTreeMap<EntryRef, Vec<EntryRef>>,
+    schemas: BTreeMap<String, Arc<TableSchema>>,
+    dicts: BTreeMap<String, Arc<DCTMeta>>,
+    facts: BTreeMap<String, Arc<FCTMeta>>,
+    models: BTreeMap<String, Arc<DMEMeta>>,
+    data: BTreeMap<String, Vec<RowValues>>,
+}
+
+impl Default for MetaPackage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetaPackage {
+    pub fn new() -> Self {
+        Self {
+            created: Utc::now(),
+            entries: BTreeMap::new(),
+            schemas: BTreeMap::new(),
+            dicts: BTreeMap::new(),
+            facts: BTreeMap::new(),
+            models: BTreeMap::new(),
+            data: BTreeMap::new(),
+        }
+    }
+
+    /// 从目录导出指定的条目及其所有依赖
+    ///
+    /// # 参数
+    /// - `catalog`: 源目录
+    /// - `roots`: 要导出的对象定义、字典、事实表或模型，种子数据用 [`MetaPackage::add_data`] 添加
+    ///
+    /// # 返回值
+    /// - `Ok(MetaPackage)` - 源目录中找不到的依赖只记录在清单中，导入时检查
+    /// - `Err(PackageError::NotFound)` - `roots` 中的条目不在源目录中
+    pub fn export(catalog: &MetaCatalog, roots: &[EntryRef]) -> Result<Self, PackageError> {
+        let mut package = Self::new();
+        for root in roots {
+            if !package.follow(catalog, root) {
+                return Err(PackageError::NotFound(root.clone()));
+            }
+        }
+        Ok(package)
+    }
+
+    /// 添加条目及其依赖，条目不在目录中时返回 `false`
+    fn follow(&mut self, catalog: &MetaCatalog, entry: &EntryRef) -> bool {
+        if self.entries.contains_key(entry) {
+            return true;
+        }
+        let found = match entry.kind {
+            EntryKind::Schema => catalog.schema(&entry.id).map(|schema| self.insert_schema(&entry.id, schema)),
+            EntryKind::Dict => catalog.dct_meta(&entry.id).map(|dict| self.insert_dict(dict)),
+            EntryKind::Fact => catalog.fct_meta(&entry.id).map(|fact| self.insert_fact(fact)),
+            EntryKind::Model => catalog.dme_meta(&entry.id).map(|model| self.insert_model(model)),
+            EntryKind::Data => None,
+        };
+        let Some(depends_on) = found else {
+            return false;
+        };
+        for dependency in depends_on {
+            self.follow(catalog, &dependency);
+        }
+        true
+    }
+
+    fn insert_schema(&mut self, obj_id: &str, schema: Arc<TableSchema>) -> Vec<EntryRef> {
+        let depends_on = schema_dependencies(&schema);
+        self.entries.insert(EntryRef::schema(obj_id), depends_on.clone());
+        self.schemas.insert(obj_id.to_string(), schema);
+        depends_on
+    }
+
+    // 字典和事实表的对象定义取自元数据本身，与目录中的对象定义相同
+    fn insert_dict(&mut self, dict: Arc<DCTMeta>) -> Vec<EntryRef> {
+        let mut depends_on = Vec::new();
+        if let Some(obj_id) = table_id(&dict.table_schema) {
+            self.insert_table(&obj_id, &dict.table_schema);
+            depends_on.push(EntryRef::schema(obj_id));
+        }
+        depends_on.extend(
+            dict_dependencies(&dict)
+                .into_iter()
+                .filter(|(_, target)| *target != dict.dct_id)
+                .map(|(_, target)| EntryRef::dict(target)),
+        );
+        self.entries.insert(EntryRef::dict(&dict.dct_id), depends_on.clone());
+        self.dicts.insert(dict.dct_id.clone(), dict);
+        depends_on
+    }
+
+    fn insert_fact(&mut self, fact: Arc<FCTMeta>) -> Vec<EntryRef> {
+        let mut depends_on = Vec::new();
+        if let Some(obj_id) = table_id(&fact.table_schema) {
+            self.insert_table(&obj_id, &fact.table_schema);
+            depends_on.push(EntryRef::schema(obj_id));
+        }
+        let dicts: BTreeSet<String> = fact_dependencies(&fact).into_iter().map(|(_, target)| target).collect();
+        depends_on.extend(dicts.into_iter().map(EntryRef::dict));
+        self.entries.insert(EntryRef::fact(&fact.id), depends_on.clone());
+        self.facts.insert(fact.id.clone(), fact);
+        depends_on
+    }
+
+    fn insert_model(&mut self, model: Arc<DMEMeta>) -> Vec<EntryRef> {
+        let facts: BTreeSet<&String> = model.fct_meta_map.iter().flat_map(|map| map.keys()).collect();
+        let mut depends_on: Vec<EntryRef> = facts.into_iter().map(EntryRef::fact).collect();
+        let dicts: BTreeSet<String> = model_dictionaries(&model).into_iter().collect();
+        depends_on.extend(dicts.into_iter().map(EntryRef::dict));
+        self.entries.insert(EntryRef::model(&model.id), depends_on.clone());
+        self.models.insert(model.id.clone(), model);
+        depends_on
+    }
+
+    fn insert_table(&mut self, obj_id: &str, schema: &TableSchema) {
+        if !self.schemas.contains_key(obj_id) {
+            self.insert_schema(obj_id, Arc::new(schema.clone()));
+        }
+    }
+
+    /// 添加对象的种子数据，导入时按主键新增或覆盖
+    ///
+    /// # 返回值
+    /// `Err(PackageError::NotFound)` - 对象定义不在包中
+    pub fn add_data(&mut self, obj_id: &str, rows: Vec<RowValues>) -> Result<(), PackageError> {
+        if !self.schemas.contains_key(obj_id) {
+            return Err(PackageError::NotFound(EntryRef::schema(obj_id)));
+        }
+        self.entries.insert(EntryRef::data(obj_id), vec![EntryRef::schema(obj_id)]);
+        self.data.insert(obj_id.to_string(), rows);
+        Ok(())
+    }
+
+    /// 导出时间
+    pub fn created(&self) -> DateTime<Utc> {
+        self.created
+    }
+
+    /// 所有条目，按类型和 ID 排序
+    pub fn entries(&self) -> impl Iterator<Item = &EntryRef> {
+        self.entries.keys()
+    }
+
+    /// 条目依赖的其他条目
+    pub fn depends_on(&self, entry: &EntryRef) -> &[EntryRef] {
+        self.entries.get(entry).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn contains(&self, entry: &EntryRef) -> bool {
+        self.entries.contains_key(entry)
+    }
+
+    pub fn schema(&self, obj_id: &str) -> Option<&Arc<TableSchema>> {
+        self.schemas.get(obj_id)
+    }
+
+    pub fn dict(&self, dct_id: &str) -> Option<&Arc<DCTMeta>> {
+        self.dicts.get(dct_id)
+    }
+
+    pub fn fact(&self, fct_id: &str) -> Option<&Arc<FCTMeta>> {
+        self.facts.get(fct_id)
+    }
+
+    pub fn model(&self, mdl_id: &str) -> Option<&Arc<DMEMeta>> {
+        self.models.get(mdl_id)
+    }
+
+    /// 对象的种子数据
+    pub fn data(&self, obj_id: &str) -> Option<&[RowValues]> {
+        self.data.get(obj_id).map(Vec::as_slice)
+    }
+
+    /// 模型引用的事实表，记录在模型条目的依赖中
+    pub fn model_facts(&self, mdl_id: &str) -> Vec<String> {
+        self.depends_on(&EntryRef::model(mdl_id))
+            .iter()
+            .filter(|dependency| dependency.kind == EntryKind::Fact)
+            .map(|dependency| dependency.id.clone())
+            .collect()
+    }
+
+    /// 按依赖排序的条目：被依赖的条目在前，其余按类型和 ID 排序，循环依赖按访问顺序断开
+    pub fn dependency_order(&self) -> Vec<EntryRef> {
+        let mut order = Vec::with_capacity(self.entries.len());
+        let mut visited = HashSet::new();
+        for entry in self.entries.keys() {
+            self.visit(entry, &mut visited, &mut order);
+        }
+        order
+    }
+
+    fn visit<'a>(&'a self, entry: &'a EntryRef, visited: &mut HashSet<&'a EntryRef>, order: &mut Vec<EntryRef>) {
+        if !visited.insert(entry) {
+            return;
+        }
+        for dependency in self.depends_on(entry) {
+            if self.contains(dependency) {
+                self.visit(dependency, visited, order);
+            }
+        }
+        order.push(entry.clone());
+    }
+
+    /// 条目写入目录表的行，列名为大写的字段名；种子数据和不在包中的条目没有目录行
+    ///
+    /// 模型内容（`SYS_MDL_CTN`）按模型引用的事实表重新生成，每行最多引用 16 个事实表。
+    pub fn catalog_rows(&self, entry: &EntryRef) -> Vec<(SYS_TABLE_NAMES, CatalogRecord)> {
+        match entry.kind {
+            EntryKind::Schema => self.schema(&entry.id).map(|schema| schema_rows(&entry.id, schema)),
+            EntryKind::Dict => self.dict(&entry.id).map(|dict| dict_rows(dict)),
+            EntryKind::Fact => self.fact(&entry.id).map(|fact| fact_rows(fact)),
+            EntryKind::Model => self.model(&entry.id).map(|model| model_rows(model, &self.model_facts(&entry.id))),
+            EntryKind::Data => None,
+        }
+        .unwrap_or_default()
+    }
+
+    /// 生成清单
+    pub fn manifest(&self, encoding: PayloadEncoding) -> Manifest {
+        let entries = self
+            .entries
+            .iter()
+            .map(|(entry, depends_on)| PackageEntry {
+                kind: entry.kind,
+                id: entry.id.clone(),
+                path: format!("{}/{}.{}", entry.kind.dir(), entry.id, encoding.extension()),
+                depends_on: depends_on.clone(),
+            })
+            .collect();
+        Manifest {
+            format: PACKAGE_FORMAT.to_string(),
+            version: PACKAGE_VERSION,
+            encoding,
+            created: self.created,
+            entries,
+        }
+    }
+
+    /// 写为 zip 文件的内容
+    pub fn to_bytes(&self, encoding: PayloadEncoding) -> Result<Vec<u8>, PackageError> {
+        let manifest = self.manifest(encoding);
+        let mut files = vec![(MANIFEST_PATH.to_string(), serde_json::to_vec_pretty(&manifest)?)];
+        for entry in &manifest.entries {
+            let payload = match entry.kind {
+                EntryKind::Schema => encode_payload(self.schemas[&entry.id].as_ref(), encoding)?,
+                EntryKind::Dict => encode_payload(self.dicts[&entry.id].as_ref(), encoding)?,
+                EntryKind::Fact => encode_payload(self.facts[&entry.id].as_ref(), encoding)?,
+                EntryKind::Model => encode_payload(self.models[&entry.id].as_ref(), encoding)?,
+                EntryKind::Data => {
+                    let rows = &self.data[&entry.id];
+                    match encoding {
+                        PayloadEncoding::Json => serde_json::to_vec(rows)?,
+                        PayloadEncoding::Rkyv => archive::encode(&SeedRows(rows.clone()))?.to_vec(),
+                    }
+                }
+            };
+            files.push((entry.path.clone(), payload));
+        }
+        write_zip(&files)
+    }
+
+    /// 读取 zip 文件的内容
+    ///
+    /// # 返回值
+    /// - `Err(PackageError::BadFormat)`、`Err(PackageError::UnsupportedVersion)` - 清单不是本格式或版本过高
+    /// - `Err(PackageError::MissingPayload)` - 清单中的条目在包中找不到
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, PackageError> {
+        let mut files = read_zip(bytes)?;
+        let manifest: Manifest =
+            serde_json::from_slice(&files.remove(MANIFEST_PATH).ok_or(PackageError::MissingManifest)?)?;
+        if manifest.format != PACKAGE_FORMAT {
+            return Err(PackageError::BadFormat(manifest.format));
+        }
+        if manifest.version > PACKAGE_VERSION {
+            return Err(PackageError::UnsupportedVersion { found: manifest.version });
+        }
+
+        let mut package = Self::new();
+        package.created = manifest.created;
+        let encoding = manifest.encoding;
+        for entry in manifest.entries {
+            let payload = files
+                .remove(&entry.path)
+                .ok_or_else(|| PackageError::MissingPayload(entry.path.clone()))?;
+            let id = entry.id.clone();
+            match entry.kind {
+                EntryKind::Schema => {
+                    package.schemas.insert(id, Arc::new(decode_payload(&payload, encoding)?));
+                }
+                EntryKind::Dict => {
+                    package.dicts.insert(id, Arc::new(decode_payload(&payload, encoding)?));
+                }
+                EntryKind::Fact => {
+                    package.facts.insert(id, Arc::new(decode_payload(&payload, encoding)?));
+                }
+                EntryKind::Model => {
+                    package.models.insert(id, Arc::new(decode_payload(&payload, encoding)?));
+                }
+                EntryKind::Data => {
+                    let rows = match encoding {
+                        PayloadEncoding::Json => serde_json::from_slice(&payload)?,
+                        PayloadEncoding::Rkyv => archive::decode::<SeedRows>(&payload)?.0,
+                    };
+                    package.data.insert(id, rows);
+                }
+            }
+            package.entries.insert(EntryRef::new(entry.kind, entry.id), entry.depends_on);
+        }
+        Ok(package)
+    }
+}
+
+/// 导入时与目标目录中同一条目不同的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictPolicy {
+    /// 记录冲突，不导入任何条目
+    #[default]
+    Fail,
+    /// 用包中的条目覆盖
+    Overwrite,
+    /// 保留目标目录中的条目
+    Skip,
+}
+
+/// 导入条目的方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportAction {
+    /// 目标目录中没有，新建
+    Create,
+    /// 与目标目录不同，覆盖
+    Update,
+    /// 与目标目录相同，不写入
+    Unchanged,
+    /// 与目标目录不同，按 [`ConflictPolicy::Skip`] 保留目标
+    Skip,
+    /// 与目标目录不同，按 [`ConflictPolicy::Fail`] 阻止导入
+    Conflict,
+    /// 种子数据按主键新增或覆盖
+    Upsert,
+}
+
+impl ImportAction {
+    /// 是否需要写入
+    pub fn writes(self) -> bool {
+        matches!(self, Self::Create | Self::Update | Self::Upsert)
+    }
+}
+
+/// 导入计划中的一步
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ImportStep {
+    #[serde(flatten)]
+    pub entry: EntryRef,
+    pub action: ImportAction,
+}
+
+/// 阻止导入的问题
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "issue", rename_all = "snake_case")]
+pub enum ImportIssue {
+    /// 条目与目标目录不同
+    Conflict { entry: EntryRef },
+    /// 依赖在包和目标目录中都找不到
+    MissingDependency { entry: EntryRef, dependency: EntryRef },
+}
+
+impl fmt::Display for ImportIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Conflict { entry } => write!(f, "{} differs from the target catalog", entry),
+            Self::MissingDependency { entry, dependency } => {
+                write!(f, "{} depends on {}, which is neither in the package nor in the target catalog", entry, dependency)
+            }
+        }
+    }
+}
+
+/// 导入计划，按依赖排序
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImportPlan {
+    pub steps: Vec<ImportStep>,
+    pub issues: Vec<ImportIssue>,
+}
+
+impl ImportPlan {
+    /// 与目标目录比较包中的每个条目，生成导入计划
+    ///
+    /// 条目按写入目录表的行比较，忽略空值和审计字段（创建、修改的时间和用户）。
+    ///
+    /// # 参数
+    /// - `package`: 要导入的包
+    /// - `target`: 目标环境当前的目录
+    /// - `policy`: 条目与目标不同时的处理方式
+    pub fn new(package: &MetaPackage, target: &MetaCatalog, policy: ConflictPolicy) -> Self {
+        let mut plan = Self::default();
+        for entry in package.dependency_order() {
+            for dependency in package.depends_on(&entry) {
+                if !package.contains(dependency) && !in_catalog(target, dependency) {
+                    plan.issues.push(ImportIssue::MissingDependency {
+                        entry: entry.clone(),
+                        dependency: dependency.clone(),
+                    });
+                }
+            }
+            let action = if entry.kind == EntryKind::Data {
+                ImportAction::Upsert
+            } else {
+                match target_rows(target, &entry) {
+                    None => ImportAction::Create,
+                    Some(current) if normalized(&current) == normalized(&package.catalog_rows(&entry)) => {
+                        ImportAction::Unchanged
+                    }
+                    Some(_) => match policy {
+                        ConflictPolicy::Fail => {
+                            plan.issues.push(ImportIssue::Conflict { entry: entry.clone() });
+                            ImportAction::Conflict
+                        }
+                        ConflictPolicy::Overwrite => ImportAction::Update,
+                        ConflictPolicy::Skip => ImportAction::Skip,
+                    },
+                }
+            };
+            plan.steps.push(ImportStep { entry, action });
+        }
+        plan
+    }
+
+    /// 有冲突或缺少依赖时不能导入
+    pub fn is_blocked(&self) -> bool {
+        !self.issues.is_empty()
+    }
+
+    /// 需要写入的步骤
+    pub fn writes(&self) -> impl Iterator<Item = &ImportStep> {
+        self.steps.iter().filter(|step| step.action.writes())
+    }
+
+    /// 导入后需要重新加载的字典、事实表和模型，对象定义改变时包括包中建在该对象上的字典和事实表
+    pub fn change(&self, package: &MetaPackage, tenant: &str) -> MetaChange {
+        let written: HashSet<&EntryRef> = self.writes().map(|step| &step.entry).collect();
+        let schema_written = |schema: &TableSchema| {
+            table_id(schema).is_some_and(|obj_id| written.contains(&EntryRef::schema(obj_id)))
+        };
+        let dicts = package
+            .dicts
+            .iter()
+            .filter(|(id, dict)| written.contains(&EntryRef::dict(*id)) || schema_written(&dict.table_schema))
+            .map(|(id, _)| id.clone());
+        let facts = package
+            .facts
+            .iter()
+            .filter(|(id, fact)| written.contains(&EntryRef::fact(*id)) || schema_written(&fact.table_schema))
+            .map(|(id, _)| id.clone());
+        let models = package.models.keys().filter(|id| written.contains(&EntryRef::model(*id))).cloned();
+        let objects = package.schemas.keys().filter(|id| written.contains(&EntryRef::schema(*id))).cloned();
+        MetaChange::new(tenant).with_objects(objects).with_dicts(dicts).with_facts(facts).with_models(models)
+    }
+}
+
+fn in_catalog(catalog: &MetaCatalog, entry: &EntryRef) -> bool {
+    match entry.kind {
+        EntryKind::Schema => catalog.schema(&entry.id).is_some(),
+        EntryKind::Dict => catalog.dct_meta(&entry.id).is_some(),
+        EntryKind::Fact => catalog.fct_meta(&entry.id).is_some(),
+        EntryKind::Model => catalog.dme_meta(&entry.id).is_some(),
+        EntryKind::Data => false,
+    }
+}
+
+/// 目标目录中条目的目录行，条目不存在时为 `None`
+fn target_rows(catalog: &MetaCatalog, entry: &EntryRef) -> Option<Vec<(SYS_TABLE_NAMES, CatalogRecord)>> {
+    match entry.kind {
+        EntryKind::Schema => catalog.schema(&entry.id).map(|schema| schema_rows(&entry.id, &schema)),
+        EntryKind::Dict => catalog.dct_meta(&entry.id).map(|dict| dict_rows(&dict)),
+        EntryKind::Fact => catalog.fct_meta(&entry.id).map(|fact| fact_rows(&fact)),
+        EntryKind::Model => catalog.dme_meta(&entry.id).map(|model| {
+            let mut facts: Vec<String> = model.fct_meta_map.iter().flat_map(|map| map.keys().cloned()).collect();
+            facts.sort();
+            model_rows(&model, &facts)
+        }),
+        EntryKind::Data => None,
+    }
+}
+
+/// 与行的顺序无关的比较形式，去掉空值和审计字段
+fn normalized(rows: &[(SYS_TABLE_NAMES, CatalogRecord)]) -> Vec<String> {
+    let mut rows: Vec<String> = rows
+        .iter()
+        .map(|(table, record)| {
+            let record: BTreeMap<&String, &CellValue> = record
+                .iter()
+                .filter(|(column, value)| !value.is_null() && !AUDIT_FIELDS.contains(&column.as_str()))
+                .collect();
+            format!("{}:{}", table, serde_json::to_string(&record).unwrap_or_default())
+        })
+        .collect();
+    rows.sort();
+    rows
+}
+
+fn table_id(schema: &TableSchema) -> Option<String> {
+    schema.obj_id().map(str::trim).filter(|id| !id.is_empty()).map(str::to_string)
+}
+
+/// 对象定义依赖外键列引用的字典
+fn schema_dependencies(schema: &TableSchema) -> Vec<EntryRef> {
+    let dicts: BTreeSet<String> = schema.columns.iter().filter_map(|column| column.foreign_dict()).collect();
+    dicts.into_iter().map(EntryRef::dict).collect()
+}
+
+fn record<'a, F: fmt::Display + 'a>(fields: impl Iterator<Item = (&'a F, &'a CellValue)>) -> CatalogRecord {
+    fields.map(|(field, value)| (field.to_string(), value.clone())).collect()
+}
+
+fn schema_rows(obj_id: &str, schema: &TableSchema) -> Vec<(SYS_TABLE_NAMES, CatalogRecord)> {
+    let owned = |mut record: CatalogRecord| {
+        record.insert("OBJ_ID".to_string(), CellValue::from(obj_id));
+        record
+    };
+    let mut rows = vec![(SYS_TABLE_NAMES::SYS_OBJECTS, owned(record(schema.fields())))];
+    rows.extend(schema.columns.iter().map(|column| (SYS_TABLE_NAMES::SYS_OBJCOLS, owned(record(column.fields())))));
+    rows.extend(schema.keys.iter().map(|key| (SYS_TABLE_NAMES::SYS_KEYS, owned(record(key.fields())))));
+    rows.extend(schema.indexes.iter().map(|index| (SYS_TABLE_NAMES::SYS_INDEXS, owned(record(index.fields())))));
+    rows
+}
+
+fn dict_rows(dict: &DCTMeta) -> Vec<(SYS_TABLE_NAMES, CatalogRecord)> {
+    let mut row = record(dict.fields());
+    row.insert("DCT_ID".to_string(), CellValue::from(dict.dct_id.as_str()));
+    vec![(SYS_TABLE_NAMES::SYS_DICTS, row)]
+}
+
+fn fact_rows(fact: &FCTMeta) -> Vec<(SYS_TABLE_NAMES, CatalogRecord)> {
+    let mut row: CatalogRecord = fact.info.iter().flat_map(|info| info.iter()).map(|(k, v)| (k.clone(), v.clone())).collect();
+    row.insert("FCT_ID".to_string(), CellValue::from(fact.id.as_str()));
+    if let Some(obj_id) = table_id(&fact.table_schema) {
+        row.entry("OBJ_ID".to_string()).or_insert(CellValue::from(obj_id));
+    }
+    vec![(SYS_TABLE_NAMES::SYS_FACTS, row)]
+}
+
+fn model_rows(model: &DMEMeta, facts: &[String]) -> Vec<(SYS_TABLE_NAMES, CatalogRecord)> {
+    let mut row: CatalogRecord = model.info.iter().flat_map(|info| info.iter()).map(|(k, v)| (k.clone(), v.clone())).collect();
+    row.insert("MDL_ID".to_string(), CellValue::from(model.id.as_str()));
+    row.entry("MDL_MC".to_string()).or_insert(CellValue::from(model.name.as_str()));
+    let mut rows = vec![(SYS_TABLE_NAMES::SYS_MODEL, row)];
+    for (index, chunk) in facts.chunks(FACTS_PER_CONTENT).enumerate() {
+        let mut content = CatalogRecord::new();
+        content.insert("MDL_ID".to_string(), CellValue::from(model.id.as_str()));
+        content.insert("CTN_ID".to_string(), CellValue::from((index + 1).to_string()));
+        for (slot, fct_id) in chunk.iter().enumerate() {
+            content.insert(format!("CTN_FCT{}", slot + 1), CellValue::from(fct_id.as_str()));
+        }
+        rows.push((SYS_TABLE_NAMES::SYS_MDL_CTN, content));
+    }
+    rows
+}
+
+fn encode_payload<T: Archivable + Serialize>(value: &T, encoding: PayloadEncoding) -> Result<Vec<u8>, PackageError> {
+    Ok(match encoding {
+        PayloadEncoding::Json => serde_json::to_vec(value)?,
+        PayloadEncoding::Rkyv => archive::encode(value)?.to_vec(),
+    })
+}
+
+fn decode_payload<T: Archivable<Repr = Cell> + DeserializeOwned>(payload: &[u8], encoding: PayloadEncoding) -> Result<T, PackageError> {
+    Ok(match encoding {
+        PayloadEncoding::Json => serde_json::from_slice(payload)?,
+        PayloadEncoding::Rkyv => archive::decode(payload)?,
+    })
+}
+
+// zip 容器
+
+fn write_zip(files: &[(String, Vec<u8>)]) -> Result<Vec<u8>, PackageError> {
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    for (name, data) in files {
+        // 超过 4 GiB 的条目需要事先声明 zip64，条目数和偏移量超出时由 ZipWriter 自动使用 zip64
+        let options = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .large_file(data.len() as u64 >= u32::MAX as u64);
+        writer.start_file(name.as_str(), options)?;
+        writer.write_all(data).map_err(ZipError::from)?;
+    }
+    Ok(writer.finish()?.into_inner())
+}
+
+fn read_zip(bytes: &[u8]) -> Result<BTreeMap<String, Vec<u8>>, PackageError> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes))?;
+    let mut files = BTreeMap::new();
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index)?;
+        if file.is_dir() {
+            continue;
+        }
+        // 读到末尾时校验 CRC
+        let mut data = Vec::new();
+        file.read_to_end(&mut data).map_err(ZipError::from)?;
+        files.insert(file.name().to_string(), data);
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::model::meta::catalog::{Catalog, CatalogRows};
+
+    fn rows() -> CatalogRows {
+        let mut rows = CatalogRows::new();
+        let objects = SYS_TABLE_NAMES::SYS_OBJECTS;
+        let columns = SYS_TABLE_NAMES::SYS_OBJCOLS;
+        rows.push_json(objects.clone(), json!({"OBJ_ID": "T_DEPT", "OBJ_MC": "部门"}));
+        rows.push_json(objects.clone(), json!({"OBJ_ID": "T_STAFF", "OBJ_MC": "职员"}));
+        rows.push_json(objects.clone(), json!({"OBJ_ID": "T_SALES"}));
+        rows.push_json(objects, json!({"OBJ_ID": "T_OTHER"}));
+        rows.push_json(columns.clone(), json!({"OBJ_ID": "T_DEPT", "COL_ID": "DM", "COL_DISP": 1}));
+        rows.push_json(columns.clone(), json!({"OBJ_ID": "T_DEPT", "COL_ID": "MC", "COL_DISP": 2}));
+        rows.push_json(columns.clone(), json!({"OBJ_ID": "T_STAFF", "COL_ID": "DM"}));
+        rows.push_json(columns.clone(), json!({"OBJ_ID": "T_STAFF", "COL_ID": "BM", "COL_ISFKEY": "1", "COL_FOBJ": "DCT_DEPT"}));
+        rows.push_json(columns, json!({"OBJ_ID": "T_SALES", "COL_ID": "ZY", "COL_ISFKEY": true, "COL_FOBJ": "DCT_STAFF"}));
+        rows.push_json(SYS_TABLE_NAMES::SYS_KEYS, json!({"OBJ_ID": "T_DEPT", "KEY_ID": "PK", "KEY_PINDEX1": "DM"}));
+        rows.push_json(SYS_TABLE_NAMES::SYS_INDEXS, json!({"OBJ_ID": "T_DEPT", "INX_ID": "IX_MC", "INX_COLS": "MC"}));
+        rows.push_json(SYS_TABLE_NAMES::SYS_DICTS, json!({"DCT_ID": "DCT_DEPT", "OBJ_ID": "T_DEPT", "DCT_MCCOLID": "MC"}));
+        rows.push_json(SYS_TABLE_NAMES::SYS_DICTS, json!({"DCT_ID": "DCT_STAFF", "OBJ_ID": "T_STAFF"}));
+        rows.push_json(SYS_TABLE_NAMES::SYS_DICTS, json!({"DCT_ID": "DCT_OTHER", "OBJ_ID": "T_OTHER"}));
+        rows.push_json(SYS_TABLE_NAMES::SYS_FACTS, json!({"FCT_ID": "FCT_SALES", "OBJ_ID": "T_SALES", "FCT_MC": "销售"}));
+        rows.push_json(SYS_TABLE_NAMES::SYS_MODEL, json!({"MDL_ID": "MDL_SALES", "MDL_MC": "销售模型", "MDL_UNITDCT": "DCT_DEPT"}));
+        rows.push_json(SYS_TABLE_NAMES::SYS_MDL_CTN, json!({"MDL_ID": "MDL_SALES", "CTN_ID": "1", "CTN_FCT1": "FCT_SALES"}));
+        rows
+    }
+
+    fn catalog() -> MetaCatalog {
+        MetaCatalog::from(&Catalog::build(&rows()))
+    }
+
+    fn ids(package: &MetaPackage) -> Vec<String> {
+        package.entries().map(|entry| format!("{:?}:{}", entry.kind, entry.id)).collect()
+    }
+
+    #[test]
+    fn test_export_follows_dependencies() {
+        let package = MetaPackage::export(&catalog(), &[EntryRef::model("MDL_SALES")]).unwrap();
+        assert_eq!(
+            ids(&package),
+            [
+                "Schema:T_DEPT",
+                "Schema:T_SALES",
+                "Schema:T_STAFF",
+                "Dict:DCT_DEPT",
+                "Dict:DCT_STAFF",
+                "Fact:FCT_SALES",
+                "Model:MDL_SALES",
+            ]
+        );
+        assert_eq!(package.model_facts("MDL_SALES"), ["FCT_SALES"]);
+        assert_eq!(package.depends_on(&EntryRef::dict("DCT_STAFF")), [EntryRef::schema("T_STAFF"), EntryRef::dict("DCT_DEPT")]);
+
+        // 被依赖的条目排在前面
+        let order = package.dependency_order();
+        let position = |entry: EntryRef| order.iter().position(|e| *e == entry).unwrap();
+        assert!(position(EntryRef::dict("DCT_DEPT")) < position(EntryRef::dict("DCT_STAFF")));
+        assert!(position(EntryRef::dict("DCT_STAFF")) < position(EntryRef::schema("T_SALES")));
+        assert!(position(EntryRef::fact("FCT_SALES")) < position(EntryRef::model("MDL_SALES")));
+
+        assert!(matches!(
+            MetaPackage::export(&catalog(), &[EntryRef::dict("DCT_NONE")]),
+            Err(PackageError::NotFound(entry)) if entry == EntryRef::dict("DCT_NONE")
+        ));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut package = MetaPackage::export(&catalog(), &[EntryRef::dict("DCT_STAFF"), EntryRef::model("MDL_SALES")]).unwrap();
+        let seed = vec![json!({"DM": "01", "MC": "财务部"}).as_object().cloned().unwrap()];
+        package.add_data("T_DEPT", seed.clone()).unwrap();
+        assert!(matches!(package.add_data("T_NONE", Vec::new()), Err(PackageError::NotFound(_))));
+
+        for encoding in [PayloadEncoding::Json, PayloadEncoding::Rkyv] {
+            let bytes = package.to_bytes(encoding).unwrap();
+            let restored = MetaPackage::from_bytes(&bytes).unwrap();
+            assert_eq!(ids(&restored), ids(&package));
+            assert_eq!(restored.created(), package.created());
+            assert_eq!(restored.data("T_DEPT").unwrap(), seed.as_slice());
+            assert_eq!(restored.model_facts("MDL_SALES"), ["FCT_SALES"]);
+            for entry in package.entries() {
+                assert_eq!(normalized(&restored.catalog_rows(entry)), normalized(&package.catalog_rows(entry)), "{}", entry);
+            }
+        }
+    }
+
+    #[test]
+    fn test_catalog_rows_rebuild_the_catalog() {
+        let package = MetaPackage::export(&catalog(), &[EntryRef::model("MDL_SALES")]).unwrap();
+        let mut rows = CatalogRows::new();
+        for entry in package.entries() {
+            for (table, record) in package.catalog_rows(entry) {
+                rows.push(table, record);
+            }
+        }
+        let rebuilt = Catalog::build(&rows);
+        assert!(rebuilt.issues.is_empty(), "{:?}", rebuilt.issues);
+
+        // 重新构建的目录与源目录相同，导入计划没有变化
+        let plan = ImportPlan::new(&package, &MetaCatalog::from(&rebuilt), ConflictPolicy::Fail);
+        assert!(!plan.is_blocked());
+        assert!(plan.steps.iter().all(|step| step.action == ImportAction::Unchanged));
+        assert_eq!(rebuilt.schemas["T_DEPT"].indexes.len(), 1);
+        assert_eq!(rebuilt.dme_metas["MDL_SALES"].get_fct_meta("FCT_SALES").unwrap().name, "销售");
+    }
+
+    #[test]
+    fn test_import_plan_detects_conflicts() {
+        let package = MetaPackage::export(&catalog(), &[EntryRef::dict("DCT_STAFF")]).unwrap();
+
+        let mut target_rows = rows();
+        target_rows.push_json(SYS_TABLE_NAMES::SYS_DICTS, json!({"DCT_ID": "DCT_DEPT", "OBJ_ID": "T_DEPT", "DCT_MCCOLID": "DM"}));
+        // 审计字段不同不算冲突
+        target_rows.push_json(
+            SYS_TABLE_NAMES::SYS_DICTS,
+            json!({"DCT_ID": "DCT_STAFF", "OBJ_ID": "T_STAFF", "F_CHDATE": "2026-01-01", "DCT_DES": null}),
+        );
+        let target = MetaCatalog::from(&Catalog::build(&target_rows));
+
+        let action = |plan: &ImportPlan, entry: EntryRef| plan.steps.iter().find(|step| step.entry == entry).unwrap().action;
+        let plan = ImportPlan::new(&package, &target, ConflictPolicy::Fail);
+        assert_eq!(plan.issues, [ImportIssue::Conflict { entry: EntryRef::dict("DCT_DEPT") }]);
+        assert_eq!(action(&plan, EntryRef::dict("DCT_DEPT")), ImportAction::Conflict);
+        assert_eq!(action(&plan, EntryRef::dict("DCT_STAFF")), ImportAction::Unchanged);
+        assert_eq!(plan.issues[0].to_string(), "dictionary 'DCT_DEPT' differs from the target catalog");
+
+        let plan = ImportPlan::new(&package, &target, ConflictPolicy::Overwrite);
+        assert!(!plan.is_blocked());
+        assert_eq!(action(&plan, EntryRef::dict("DCT_DEPT")), ImportAction::Update);
+        let change = plan.change(&package, "acme");
+        assert_eq!(change.dct_ids, ["DCT_DEPT"]);
+
+        let plan = ImportPlan::new(&package, &target, ConflictPolicy::Skip);
+        assert_eq!(action(&plan, EntryRef::dict("DCT_DEPT")), ImportAction::Skip);
+        assert_eq!(plan.writes().count(), 0);
+    }
+
+    #[test]
+    fn test_import_plan_reports_missing_dependencies() {
+        let package = MetaPackage::export(&catalog(), &[EntryRef::dict("DCT_STAFF")]).unwrap();
+        let bytes = package.to_bytes(PayloadEncoding::Json).unwrap();
+        let mut package = MetaPackage::from_bytes(&bytes).unwrap();
+        // 去掉被引用的字典，模拟只导出了一部分的包
+        package.entries.remove(&EntryRef::dict("DCT_DEPT"));
+        package.dicts.remove("DCT_DEPT");
+
+        let plan = ImportPlan::new(&package, &MetaCatalog::new(), ConflictPolicy::Overwrite);
+        assert!(plan.is_blocked());
+        assert!(plan.issues.contains(&ImportIssue::MissingDependency {
+            entry: EntryRef::dict("DCT_STAFF"),
+            dependency: EntryRef::dict("DCT_DEPT"),
+        }));
+
+        // 目标目录中已有的依赖不需要在包中
+        let plan = ImportPlan::new(&package, &catalog(), ConflictPolicy::Overwrite);
+        assert!(!plan.is_blocked());
+        let change = plan.change(&package, "acme");
+        assert!(change.is_empty());
+    }
+
+    #[test]
+    fn test_from_bytes_rejects_invalid_packages() {
+        let package = MetaPackage::export(&catalog(), &[EntryRef::dict("DCT_DEPT")]).unwrap();
+        let bytes = package.to_bytes(PayloadEncoding::Json).unwrap();
+
+        // 清单是第一个条目，改动它的内容（本地文件头 30 字节，之后是文件名）
+        let mut corrupted = bytes.clone();
+        corrupted[30 + MANIFEST_PATH.len() + 5] ^= 0xff;
+        assert!(matches!(MetaPackage::from_bytes(&corrupted), Err(PackageError::Container(_))));
+        assert!(matches!(MetaPackage::from_bytes(b"not a zip"), Err(PackageError::Container(_))));
+
+        let mut manifest = package.manifest(PayloadEncoding::Json);
+        manifest.version = PACKAGE_VERSION + 1;
+        let newer = write_zip(&[(MANIFEST_PATH.to_string(), serde_json::to_vec(&manifest).unwrap())]).unwrap();
+        assert!(matches!(MetaPackage::from_bytes(&newer), Err(PackageError::UnsupportedVersion { .. })));
+
+        manifest.version = PACKAGE_VERSION;
+        let incomplete = write_zip(&[(MANIFEST_PATH.to_string(), serde_json::to_vec(&manifest).unwrap())]).unwrap();
+        assert!(matches!(MetaPackage::from_bytes(&incomplete), Err(PackageError::MissingPayload(_))));
+        assert!(matches!(MetaPackage::from_bytes(&write_zip(&[]).unwrap()), Err(PackageError::MissingManifest)));
+    }
+
+    #[test]
+    fn test_from_bytes_reads_archives_of_other_tools() {
+        let package = MetaPackage::export(&catalog(), &[EntryRef::dict("DCT_STAFF")]).unwrap();
+        let files = read_zip(&package.to_bytes(PayloadEncoding::Json).unwrap()).unwrap();
+
+        // 目录条目、不压缩和 deflate 压缩的条目混在一起
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        writer.add_directory("dicts/", SimpleFileOptions::default()).unwrap();
+        for (index, (name, data)) in files.iter().enumerate() {
+            let method = if index % 2 == 0 { CompressionMethod::Stored } else { CompressionMethod::Deflated };
+            writer.start_file(name.as_str(), SimpleFileOptions::default().compression_method(method)).unwrap();
+            writer.write_all(data).unwrap();
+        }
+        let bytes = writer.finish().unwrap().into_inner();
+
+        let restored = MetaPackage::from_bytes(&bytes).unwrap();
+        assert_eq!(ids(&restored), ids(&package));
+        for entry in package.entries() {
+            assert_eq!(normalized(&restored.catalog_rows(entry)), normalized(&package.catalog_rows(entry)), "{}", entry);
+        }
+    }
+
+    #[test]
+    fn test_zip_uses_zip64_for_many_entries() {
+        // 超过 65535 个条目时目录结束记录放不下条目数
+        let files: Vec<(String, Vec<u8>)> = (0..70_000).map(|index| (format!("data/{index}"), vec![index as u8])).collect();
+        let bytes = write_zip(&files).unwrap();
+        let zip64_end_of_central = 0x0606_4b50u32.to_le_bytes();
+        assert!(bytes.windows(4).any(|window| window == zip64_end_of_central));
+        let restored = read_zip(&bytes).unwrap();
+        assert_eq!(restored.len(), files.len());
+        assert_eq!(restored["data/69999"], [69_999u32 as u8]);
+    }
+}
@@ -0,0 +1,383 @@
+//! # 元数据注册表
+//!
+//! `MetaRegistry` 按租户保存元数据目录，可以放在服务的 `AppState` 中注入使用，不同租户的目录互不影响。
+//!
+//! - 每个租户的目录是一个不可变的 [`MetaCatalog`] 版本，读取方拿到的快照在使用期间保持不变
+//! - [`MetaRegistry::swap`] 原子替换租户的整个目录，版本号递增
+//! - [`MetaRegistry::update`] 基于当前版本复制修改后替换，并发修改不会丢失
+//! - 读取不加锁，不会被写入阻塞；写入在锁外构建新版本，只原子地替换指针
+//!
+//! 注册表没有进程级的全局实例，由服务在启动时创建并注入；安装元数据、关联外键字典等操作都显式接收
+//! 注册表或目录。
+//!
+//! ## 迁移
+//!
+//! 原来的全局 `DCTMetaManager`、`FCTMetaManager`、`FDMetaManager` 已移除，依赖它们的接口改为显式接收目录：
+//!
+//! | 原接口 | 现接口 |
+//! |--------|--------|
+//! | `DCTMeta::from_json(json) -> Result<DCTMeta, serde_json::Error>` | `DCTMeta::from_json(json, &catalog) -> Result<DCTMeta, DCTMetaError>` |
+//! | `DCTMeta::process_foreign_keys()` | `DCTMeta::process_foreign_keys(&catalog) -> Result<(), ForeignKeyError>` |
+//! | `DCTMetaManager::add_meta` 等 | [`MetaRegistry::update`] 中的 [`MetaCatalog::insert_dct_meta`] 等 |
+//! | `DCTMetaManager::get_meta` 等 | [`MetaRegistry::get_dct_meta`] 或 [`MetaRegistry::snapshot`] 返回的目录 |
+//!
+//! 原接口在全局管理器中找不到外键字典时静默跳过，现在返回 `ForeignKeyError`，找到的字典仍然关联。
+//! 调用方先把外键字典注册到租户的目录，再以 `&registry.snapshot(tenant)` 或自建的 [`MetaCatalog`]
+//! 调用；需要从其他来源查找外键字典时使用 [`DCTMeta::resolve_foreign_keys`]。
+//!
+//! ## 示例
+//!
+//! ```rust
+//! use std::sync::Arc;
+//! use cmx_core::model::data::dataset::TableSchemaBuilder;
+//! use cmx_core::model::meta::dct::DCTMeta;
+//! use cmx_core::model::meta::registry::{MetaCatalog, MetaRegistry};
+//!
+//! let registry = MetaRegistry::new();
+//! let mut catalog = MetaCatalog::new();
+//! catalog.insert_dct_meta(Arc::new(DCTMeta::new("DCT_DEPT".to_string(), TableSchemaBuilder::new().build())));
+//! registry.swap("tenant_a", catalog);
+//!
+//! let snapshot = registry.snapshot("tenant_a").unwrap();
+//! assert_eq!(snapshot.version(), 1);
+//! assert!(snapshot.dct_meta("DCT_DEPT").is_some());
+//! assert!(registry.get_dct_meta("tenant_b", "DCT_DEPT").is_none());
+//! ```
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arc_swap::ArcSwap;
+
+use crate::model::data::dataset::TableSchema;
+
+use super::catalog::Catalog;
+use super::dct::DCTMeta;
+use super::dme::DMEMeta;
+use super::fct::FCTMeta;
+
+/// 默认租户，请求没有指定租户时使用
+pub const DEFAULT_TENANT: &str = "default";
+
+/// 一个租户的元数据目录版本
+///
+/// 注册到 [`MetaRegistry`] 后不再修改，修改时复制一份新的版本。
+#[derive(Debug, Clone, Default)]
+pub struct MetaCatalog {
+    version: u64,
+    pub(super) schemas: HashMap<String, Arc<TableSchema>>,
+    pub(super) dct_metas: HashMap<String, Arc<DCTMeta>>,
+    pub(super) fct_metas: HashMap<String, Arc<FCTMeta>>,
+    pub(super) dme_metas: HashMap<String, Arc<DMEMeta>>,
+}
+
+impl MetaCatalog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 目录版本号，注册时由 [`MetaRegistry`] 分配，未注册的目录为 0
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// 对象定义（`SYS_OBJECTS`），包括没有注册为字典或事实表的对象
+    pub fn schema(&self, obj_id: &str) -> Option<Arc<TableSchema>> {
+        self.schemas.get(obj_id).cloned()
+    }
+
+    pub fn dct_meta(&self, dct_id: &str) -> Option<Arc<DCTMeta>> {
+        self.dct_metas.get(dct_id).cloned()
+    }
+
+    pub fn fct_meta(&self, fct_id: &str) -> Option<Arc<FCTMeta>> {
+        self.fct_metas.get(fct_id).cloned()
+    }
+
+    pub fn dme_meta(&self, mdl_id: &str) -> Option<Arc<DMEMeta>> {
+        self.dme_metas.get(mdl_id).cloned()
+    }
+
+    pub fn dct_metas(&self) -> impl Iterator<Item = &Arc<DCTMeta>> {
+        self.dct_metas.values()
+    }
+
+    pub fn fct_metas(&self) -> impl Iterator<Item = &Arc<FCTMeta>> {
+        self.fct_metas.values()
+    }
+
+    pub fn dme_metas(&self) -> impl Iterator<Item = &Arc<DMEMeta>> {
+        self.dme_metas.values()
+    }
+
+    /// 添加或替换对象定义，返回被替换的对象定义
+    pub fn insert_schema(&mut self, obj_id: impl Into<String>, schema: Arc<TableSchema>) -> Option<Arc<TableSchema>> {
+        self.schemas.insert(obj_id.into(), schema)
+    }
+
+    /// 添加或替换字典元数据，返回被替换的字典
+    pub fn insert_dct_meta(&mut self, meta: Arc<DCTMeta>) -> Option<Arc<DCTMeta>> {
+        self.dct_metas.insert(meta.dct_id.clone(), meta)
+    }
+
+    /// 添加或替换事实表元数据，返回被替换的事实表
+    pub fn insert_fct_meta(&mut self, meta: Arc<FCTMeta>) -> Option<Arc<FCTMeta>> {
+        self.fct_metas.insert(meta.id.clone(), meta)
+    }
+
+    /// 添加或替换模型元数据，返回被替换的模型
+    pub fn insert_dme_meta(&mut self, meta: Arc<DMEMeta>) -> Option<Arc<DMEMeta>> {
+        self.dme_metas.insert(meta.id.clone(), meta)
+    }
+
+    pub fn remove_schema(&mut self, obj_id: &str) -> Option<Arc<TableSchema>> {
+        self.schemas.remove(obj_id)
+    }
+
+    pub fn remove_dct_meta(&mut self, dct_id: &str) -> Option<Arc<DCTMeta>> {
+        self.dct_metas.remove(dct_id)
+    }
+
+    pub fn remove_fct_meta(&mut self, fct_id: &str) -> Option<Arc<FCTMeta>> {
+        self.fct_metas.remove(fct_id)
+    }
+
+    pub fn remove_dme_meta(&mut self, mdl_id: &str) -> Option<Arc<DMEMeta>> {
+        self.dme_metas.remove(mdl_id)
+    }
+
+    /// 合并另一个目录，同 ID 的元数据以 `other` 为准
+    pub fn extend(&mut self, other: MetaCatalog) {
+        self.schemas.extend(other.schemas);
+        self.dct_metas.extend(other.dct_metas);
+        self.fct_metas.extend(other.fct_metas);
+        self.dme_metas.extend(other.dme_metas);
+    }
+
+    pub fn clear_dct_metas(&mut self) {
+        self.dct_metas.clear();
+    }
+
+    pub fn clear_fct_metas(&mut self) {
+        self.fct_metas.clear();
+    }
+
+    pub fn clear_dme_metas(&mut self) {
+        self.dme_metas.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.schemas.is_empty() && self.dct_metas.is_empty() && self.fct_metas.is_empty() && self.dme_metas.is_empty()
+    }
+}
+
+impl From<&Catalog> for MetaCatalog {
+    fn from(catalog: &Catalog) -> Self {
+        Self {
+            version: 0,
+            schemas: catalog
+                .schemas
+                .iter()
+                .map(|(obj_id, schema)| (obj_id.clone(), Arc::new(schema.clone())))
+                .collect(),
+            dct_metas: catalog.dct_metas.clone(),
+            fct_metas: catalog.fct_metas.clone(),
+            dme_metas: catalog.dme_metas.clone(),
+        }
+    }
+}
+
+/// 按租户保存元数据目录的注册表
+///
+/// 克隆的注册表共享同一份数据。
+#[derive(Debug, Clone, Default)]
+pub struct MetaRegistry {
+    tenants: Arc<ArcSwap<HashMap<String, Arc<MetaCatalog>>>>,
+}
+
+impl MetaRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 租户当前的目录版本
+    ///
+    /// 返回的快照不受之后的替换和修改影响。
+    pub fn snapshot(&self, tenant: &str) -> Option<Arc<MetaCatalog>> {
+        self.tenants.load().get(tenant).cloned()
+    }
+
+    /// 租户当前的目录版本号，租户不存在时为 0
+    pub fn version(&self, tenant: &str) -> u64 {
+        self.tenants.load().get(tenant).map_or(0, |catalog| catalog.version)
+    }
+
+    /// 所有已注册的租户
+    pub fn tenants(&self) -> Vec<String> {
+        self.tenants.load().keys().cloned().collect()
+    }
+
+    pub fn get_dct_meta(&self, tenant: &str, dct_id: &str) -> Option<Arc<DCTMeta>> {
+        self.tenants.load().get(tenant).and_then(|catalog| catalog.dct_meta(dct_id))
+    }
+
+    pub fn get_fct_meta(&self, tenant: &str, fct_id: &str) -> Option<Arc<FCTMeta>> {
+        self.tenants.load().get(tenant).and_then(|catalog| catalog.fct_meta(fct_id))
+    }
+
+    pub fn get_dme_meta(&self, tenant: &str, mdl_id: &str) -> Option<Arc<DMEMeta>> {
+        self.tenants.load().get(tenant).and_then(|catalog| catalog.dme_meta(mdl_id))
+    }
+
+    /// 原子替换租户的整个目录
+    ///
+    /// # 参数
+    /// - `tenant`: 租户
+    /// - `catalog`: 新的目录，版本号由注册表分配
+    ///
+    /// # 返回值
+    /// 新注册的目录版本
+    pub fn swap(&self, tenant: &str, catalog: MetaCatalog) -> Arc<MetaCatalog> {
+        self.modify(tenant, |current| current + 1, |_| catalog.clone())
+    }
+
+    /// 以指定的版本号原子替换租户的整个目录
+    ///
+    /// 多个服务实例共享同一个版本号序列时使用。版本号不会回退，`version` 小于当前版本号时保持当前版本号。
+    pub fn swap_versioned(&self, tenant: &str, catalog: MetaCatalog, version: u64) -> Arc<MetaCatalog> {
+        self.modify(tenant, |current| current.max(version), |_| catalog.clone())
+    }
+
+    /// 基于租户当前的目录修改后替换，租户不存在时基于空目录
+    ///
+    /// 与其他写入并发时 `f` 可能被调用多次，每次都基于最新的版本，因此不会丢失其他写入的修改。
+    ///
+    /// # 返回值
+    /// 新注册的目录版本
+    pub fn update(&self, tenant: &str, f: impl Fn(&mut MetaCatalog)) -> Arc<MetaCatalog> {
+        self.modify(tenant, |current| current + 1, |current| Self::modified(current, &f))
+    }
+
+    /// 以指定的版本号修改租户的目录，版本号规则同 [`MetaRegistry::swap_versioned`]
+    pub fn update_versioned(&self, tenant: &str, version: u64, f: impl Fn(&mut MetaCatalog)) -> Arc<MetaCatalog> {
+        self.modify(tenant, |current| current.max(version), |current| Self::modified(current, &f))
+    }
+
+    /// 移除租户，返回租户最后的目录版本
+    pub fn remove_tenant(&self, tenant: &str) -> Option<Arc<MetaCatalog>> {
+        let previous = self.tenants.rcu(|tenants| {
+            let mut tenants = HashMap::clone(tenants);
+            tenants.remove(tenant);
+            tenants
+        });
+        previous.get(tenant).cloned()
+    }
+
+    fn modified(current: Option<&Arc<MetaCatalog>>, f: impl Fn(&mut MetaCatalog)) -> MetaCatalog {
+        let mut catalog = current.map(|catalog| MetaCatalog::clone(catalog)).unwrap_or_default();
+        f(&mut catalog);
+        catalog
+    }
+
+    fn modify(
+        &self,
+        tenant: &str,
+        next_version: impl Fn(u64) -> u64,
+        f: impl Fn(Option<&Arc<MetaCatalog>>) -> MetaCatalog,
+    ) -> Arc<MetaCatalog> {
+        let mut installed = None;
+        self.tenants.rcu(|tenants| {
+            let current = tenants.get(tenant);
+            let mut catalog = f(current);
+            catalog.version = next_version(current.map_or(0, |catalog| catalog.version));
+            let catalog = Arc::new(catalog);
+            let mut tenants = HashMap::clone(tenants);
+            tenants.insert(tenant.to_string(), catalog.clone());
+            installed = Some(catalog);
+            tenants
+        });
+        installed.expect("rcu runs the update at least once")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+    use crate::model::data::dataset::TableSchemaBuilder;
+
+    fn dct(id: &str) -> Arc<DCTMeta> {
+        Arc::new(DCTMeta::new(id.to_string(), TableSchemaBuilder::new().build()))
+    }
+
+    #[test]
+    fn test_tenants_are_isolated() {
+        let registry = MetaRegistry::new();
+        registry.update("a", |catalog| {
+            catalog.insert_dct_meta(dct("DCT_A"));
+        });
+        registry.update("b", |catalog| {
+            catalog.insert_dct_meta(dct("DCT_B"));
+        });
+
+        assert!(registry.get_dct_meta("a", "DCT_A").is_some());
+        assert!(registry.get_dct_meta("a", "DCT_B").is_none());
+        assert!(registry.get_dct_meta("b", "DCT_B").is_some());
+        let mut tenants = registry.tenants();
+        tenants.sort();
+        assert_eq!(tenants, vec!["a", "b"]);
+
+        assert!(registry.remove_tenant("a").is_some());
+        assert!(registry.snapshot("a").is_none());
+        assert_eq!(registry.version("a"), 0);
+    }
+
+    #[test]
+    fn test_swap_keeps_old_snapshots() {
+        let registry = MetaRegistry::new();
+        let mut first = MetaCatalog::new();
+        first.insert_dct_meta(dct("DCT_OLD"));
+        registry.swap("t", first);
+        let old = registry.snapshot("t").unwrap();
+
+        let mut second = MetaCatalog::new();
+        second.insert_dct_meta(dct("DCT_NEW"));
+        let new = registry.swap("t", second);
+
+        assert_eq!(old.version(), 1);
+        assert_eq!(new.version(), 2);
+        assert!(old.dct_meta("DCT_OLD").is_some());
+        assert!(old.dct_meta("DCT_NEW").is_none());
+        assert!(registry.get_dct_meta("t", "DCT_OLD").is_none());
+        assert!(registry.get_dct_meta("t", "DCT_NEW").is_some());
+
+        // 共享的版本号不回退
+        assert_eq!(registry.swap_versioned("t", MetaCatalog::new(), 10).version(), 10);
+        assert_eq!(registry.update_versioned("t", 7, |_| {}).version(), 10);
+    }
+
+    #[test]
+    fn test_concurrent_updates_are_not_lost() {
+        let registry = MetaRegistry::new();
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let registry = registry.clone();
+                thread::spawn(move || {
+                    for j in 0..25 {
+                        registry.update("t", |catalog| {
+                            catalog.insert_dct_meta(dct(&format!("DCT_{}_{}", i, j)));
+                        });
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let snapshot = registry.snapshot("t").unwrap();
+        assert_eq!(snapshot.dct_metas().count(), 200);
+        assert_eq!(snapshot.version(), 200);
+    }
+}
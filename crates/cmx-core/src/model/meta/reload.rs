@@ -0,0 +1,512 @@
+//! # 元数据热更新
+//!
+//! 管理员修改对象、字典、事实表或模型的定义后，发布一条 [`MetaChange`] 通知所有服务实例。
+//! 每个实例只重新读取变更对象的目录行，调用 [`MetaRegistry::reload`] 更新本地目录：
+//!
+//! - 变更的对象定义、字典、事实表和模型按新的目录行重建；目录行已不存在的视为删除
+//! - 建在变更对象上的字典和事实表换用新的对象定义
+//! - 直接或间接引用了变更字典的字典重新关联外键字典，按依赖顺序进行
+//! - 引用了变更字典的事实表、引用了变更字典或事实表的模型重新关联
+//! - 其余元数据原样保留，不重新构建
+//!
+//! 变更通知带有发布方分配的目录版本号，各实例更新后使用同一个版本号，
+//! 客户端比较版本号即可判断缓存的元数据是否过期。
+//!
+//! 启动或重新订阅时先订阅变更通知，再全量加载目录，见 [`catch_up`]：
+//! 加载期间收到的通知先缓冲，加载完成后只重放比加载版本更新的通知，
+//! 避免全量加载覆盖加载期间已经应用的变更，也不会漏掉订阅前发布的变更。
+//!
+//! ## 示例
+//!
+//! ```rust
+//! use cmx_core::model::meta::catalog::CatalogRows;
+//! use cmx_core::model::meta::registry::MetaRegistry;
+//! use cmx_core::model::meta::reload::MetaChange;
+//! use cmx_core::model::meta::tables::SYS_TABLE_NAMES;
+//! use serde_json::json;
+//!
+//! let registry = MetaRegistry::new();
+//! let mut rows = CatalogRows::new();
+//! rows.push_json(SYS_TABLE_NAMES::SYS_DICTS, json!({"dct_id": "DCT_DEPT", "dct_mc": "部门"}));
+//!
+//! // 通知通常来自其他服务实例
+//! let change = MetaChange::new("default").with_dicts(["DCT_DEPT"]).with_version(3);
+//! let change = MetaChange::from_json(&change.to_json().unwrap()).unwrap();
+//!
+//! let report = registry.reload(&change, &rows);
+//! assert!(report.issues.is_empty());
+//! assert_eq!(report.catalog.version(), 3);
+//! assert!(registry.get_dct_meta("default", "DCT_DEPT").is_some());
+//! ```
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::future::Future;
+use std::sync::Arc;
+
+use futures::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+
+use super::catalog::{dict_dependencies, fact_dependencies, model_dictionaries, Catalog, CatalogIssue, CatalogRows};
+use super::dct::DCTMeta;
+use super::fct::FCTMeta;
+use super::registry::{MetaCatalog, MetaRegistry};
+use crate::model::data::dataset::TableSchema;
+
+/// 元数据变更通知
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetaChange {
+    /// 租户
+    #[serde(default)]
+    pub tenant: String,
+    /// 变更后的目录版本号，由发布方分配；为 0 时各实例在本地版本号上递增
+    #[serde(default)]
+    pub version: u64,
+    /// 变更的对象 ID（`SYS_OBJECTS`、`SYS_OBJCOLS`、`SYS_KEYS`、`SYS_INDEXS`）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub obj_ids: Vec<String>,
+    /// 变更的字典 ID
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dct_ids: Vec<String>,
+    /// 变更的事实表 ID
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fct_ids: Vec<String>,
+    /// 变更的模型 ID
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mdl_ids: Vec<String>,
+}
+
+impl MetaChange {
+    pub fn new(tenant: impl Into<String>) -> Self {
+        Self {
+            tenant: tenant.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_version(mut self, version: u64) -> Self {
+        self.version = version;
+        self
+    }
+
+    pub fn with_objects<I: IntoIterator<Item = S>, S: Into<String>>(mut self, ids: I) -> Self {
+        self.obj_ids.extend(ids.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn with_dicts<I: IntoIterator<Item = S>, S: Into<String>>(mut self, ids: I) -> Self {
+        self.dct_ids.extend(ids.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn with_facts<I: IntoIterator<Item = S>, S: Into<String>>(mut self, ids: I) -> Self {
+        self.fct_ids.extend(ids.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn with_models<I: IntoIterator<Item = S>, S: Into<String>>(mut self, ids: I) -> Self {
+        self.mdl_ids.extend(ids.into_iter().map(Into::into));
+        self
+    }
+
+    /// 全量加载 `tenant` 的目录（加载版本号为 `loaded`）之后是否仍需重放
+    ///
+    /// 同一租户版本号不大于 `loaded` 的变更已包含在加载结果中；其他租户和未带版本号的变更都需要重放。
+    pub fn is_newer_than(&self, tenant: &str, loaded: u64) -> bool {
+        self.tenant != tenant || self.version == 0 || self.version > loaded
+    }
+
+    /// 是否没有任何变更对象
+    pub fn is_empty(&self) -> bool {
+        self.obj_ids.is_empty() && self.dct_ids.is_empty() && self.fct_ids.is_empty() && self.mdl_ids.is_empty()
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+/// 重新加载的结果
+#[derive(Debug)]
+pub struct ReloadReport {
+    /// 重新加载后注册的目录版本
+    pub catalog: Arc<MetaCatalog>,
+    /// 重新关联时发现的问题，例如引用了已删除的字典
+    pub issues: Vec<CatalogIssue>,
+}
+
+impl MetaCatalog {
+    /// 按变更对象的最新目录行更新目录，并重新关联依赖它们的元数据
+    ///
+    /// # 参数
+    /// - `change`: 变更通知
+    /// - `rows`: 变更对象的目录行，包括变更的字典和事实表引用的 `SYS_OBJECTS`、`SYS_OBJCOLS`、`SYS_KEYS`、
+    ///   `SYS_INDEXS` 和 `SYS_MDL_CTN` 行；其中的对象定义更新到目录中，其余不属于变更对象的行被忽略。
+    ///   建在变更对象上、本身没有变更的字典和事实表不需要目录行
+    ///
+    /// # 返回值
+    /// 重新关联时发现的问题
+    pub fn reload(&mut self, change: &MetaChange, rows: &CatalogRows) -> Vec<CatalogIssue> {
+        let mut issues = Vec::new();
+        let schemas = Catalog::build_schemas(rows, &mut issues);
+        for (obj_id, schema) in &schemas {
+            self.schemas.insert(obj_id.clone(), Arc::new(schema.clone()));
+        }
+        for obj_id in &change.obj_ids {
+            if !schemas.contains_key(obj_id) {
+                self.schemas.remove(obj_id);
+            }
+        }
+
+        // 建在变更对象上的字典和事实表换用新的对象定义
+        let objects: HashSet<&str> = change.obj_ids.iter().map(String::as_str).collect();
+        let on_changed_object = |schema: &TableSchema| schema.obj_id().is_some_and(|obj_id| objects.contains(obj_id));
+        let mut with_new_schema = |id: &str, schema: &TableSchema| {
+            Catalog::schema_for(id, schema.obj_id().map(str::to_string), &schemas, &mut issues)
+        };
+        let mut rebuilt_dicts = HashMap::new();
+        for (id, meta) in &self.dct_metas {
+            if !change.dct_ids.contains(id) && on_changed_object(&meta.table_schema) {
+                let mut meta = DCTMeta::clone(meta);
+                meta.table_schema = with_new_schema(id, &meta.table_schema);
+                rebuilt_dicts.insert(id.clone(), meta);
+            }
+        }
+        let mut rebuilt_facts = HashMap::new();
+        for (id, meta) in &self.fct_metas {
+            if !change.fct_ids.contains(id) && on_changed_object(&meta.table_schema) {
+                let mut meta = FCTMeta::clone(meta);
+                meta.table_schema = with_new_schema(id, &meta.table_schema);
+                rebuilt_facts.insert(id.clone(), meta);
+            }
+        }
+
+        // 字典：变更的字典及所有直接或间接引用它们的字典
+        let mut changed: HashSet<&str> = change.dct_ids.iter().map(String::as_str).collect();
+        changed.extend(rebuilt_dicts.keys().map(String::as_str));
+        let mut pending: HashMap<String, DCTMeta> = Catalog::unlinked_dicts(rows, &schemas, &mut issues)
+            .into_iter()
+            .filter(|(id, _)| change.dct_ids.contains(id))
+            .collect();
+        let mut affected_dicts = self.dependent_dicts(&changed);
+        for id in &affected_dicts {
+            if let Some(meta) = self.dct_metas.get(id) {
+                pending.insert(id.clone(), DCTMeta::clone(meta));
+            }
+        }
+        affected_dicts.extend(changed.iter().map(|id| id.to_string()));
+        pending.extend(rebuilt_dicts);
+        let unaffected = self
+            .dct_metas
+            .iter()
+            .filter(|(id, _)| !affected_dicts.contains(*id))
+            .map(|(id, meta)| (id.clone(), meta.clone()))
+            .collect();
+        self.dct_metas = Catalog::link_dicts(pending, unaffected, &mut issues);
+
+        // 事实表：变更的事实表及引用了受影响字典的事实表
+        let changed: HashSet<&str> = change.fct_ids.iter().map(String::as_str).collect();
+        let mut facts: HashMap<_, _> = Catalog::unlinked_facts(rows, &schemas, &mut issues)
+            .into_iter()
+            .filter(|(id, _)| changed.contains(id.as_str()))
+            .collect();
+        for id in &change.fct_ids {
+            self.fct_metas.remove(id);
+        }
+        facts.extend(rebuilt_facts);
+        for (id, meta) in &self.fct_metas {
+            if !facts.contains_key(id) && fact_dependencies(meta).iter().any(|(_, target)| affected_dicts.contains(target)) {
+                facts.insert(id.clone(), meta.as_ref().clone());
+            }
+        }
+        let mut affected_facts: HashSet<String> = facts.keys().cloned().collect();
+        affected_facts.extend(change.fct_ids.iter().cloned());
+        for (id, meta) in facts {
+            let meta = Catalog::link_fact(meta, &self.dct_metas, &mut issues);
+            self.fct_metas.insert(id, Arc::new(meta));
+        }
+
+        // 模型：变更的模型及引用了受影响字典或事实表的模型
+        let changed: HashSet<&str> = change.mdl_ids.iter().map(String::as_str).collect();
+        let models = Catalog::build_models(rows, &self.dct_metas, &self.fct_metas, &mut issues);
+        for id in &change.mdl_ids {
+            self.dme_metas.remove(id);
+        }
+        let dependents: Vec<_> = self
+            .dme_metas
+            .values()
+            .filter(|meta| {
+                model_dictionaries(meta).iter().any(|id| affected_dicts.contains(id))
+                    || meta
+                        .fct_meta_map
+                        .iter()
+                        .flat_map(|map| map.keys())
+                        .any(|id| affected_facts.contains(id))
+            })
+            .cloned()
+            .collect();
+        for meta in dependents {
+            let meta = Catalog::relink_model(&meta, &self.dct_metas, &self.fct_metas, &mut issues);
+            self.dme_metas.insert(meta.id.clone(), Arc::new(meta));
+        }
+        for (id, meta) in models {
+            if changed.contains(id.as_str()) {
+                self.dme_metas.insert(id, meta);
+            }
+        }
+        issues
+    }
+
+    /// 直接或间接引用了 `changed` 中字典的字典，不包括 `changed` 本身
+    fn dependent_dicts(&self, changed: &HashSet<&str>) -> HashSet<String> {
+        let mut referrers: HashMap<String, Vec<&str>> = HashMap::new();
+        for (id, meta) in &self.dct_metas {
+            for (_, target) in dict_dependencies(meta) {
+                referrers.entry(target).or_default().push(id);
+            }
+        }
+
+        let mut dependents = HashSet::new();
+        let mut queue: VecDeque<&str> = changed.iter().copied().collect();
+        while let Some(id) = queue.pop_front() {
+            for referrer in referrers.get(id).into_iter().flatten() {
+                if !changed.contains(referrer) && dependents.insert(referrer.to_string()) {
+                    queue.push_back(referrer);
+                }
+            }
+        }
+        dependents
+    }
+}
+
+impl MetaRegistry {
+    /// 按变更通知重新加载租户的目录，见 [`MetaCatalog::reload`]
+    ///
+    /// 通知带有版本号时使用该版本号（不回退），否则在当前版本号上递增。
+    pub fn reload(&self, change: &MetaChange, rows: &CatalogRows) -> ReloadReport {
+        let issues = RefCell::new(Vec::new());
+        let reload = |catalog: &mut MetaCatalog| *issues.borrow_mut() = catalog.reload(change, rows);
+        let catalog = match change.version {
+            0 => self.update(&change.tenant, reload),
+            version => self.update_versioned(&change.tenant, version, reload),
+        };
+        ReloadReport {
+            catalog,
+            issues: issues.into_inner(),
+        }
+    }
+}
+
+/// 在已订阅的变更通知上全量加载租户的目录
+///
+/// `changes` 必须在调用前已经订阅，`load` 全量加载 `tenant` 的目录并返回加载时读到的版本号，
+/// 加载失败时返回 0。加载期间收到的通知按到达顺序缓冲，加载完成后丢弃已包含在加载结果中的通知。
+///
+/// # 返回值
+///
+/// 需要在加载结果上依次重放的变更通知；加载完成后才到达的通知留在 `changes` 中
+pub async fn catch_up<S, F>(changes: &mut S, tenant: &str, load: F) -> Vec<MetaChange>
+where
+    S: Stream<Item = MetaChange> + Unpin,
+    F: Future<Output = u64>,
+{
+    let mut load = std::pin::pin!(load);
+    let mut buffered = Vec::new();
+    let mut open = true;
+    let loaded = loop {
+        tokio::select! {
+            // 先取出已到达的通知，再检查加载是否完成
+            biased;
+            change = changes.next(), if open => match change {
+                Some(change) => buffered.push(change),
+                None => open = false,
+            },
+            loaded = &mut load => break loaded,
+        }
+    };
+    buffered.retain(|change| change.is_newer_than(tenant, loaded));
+    buffered
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::model::meta::fields::SYS_DICTS;
+    use crate::model::meta::tables::SYS_TABLE_NAMES;
+
+    fn full_rows() -> CatalogRows {
+        let mut rows = CatalogRows::new();
+        rows.push_json(SYS_TABLE_NAMES::SYS_OBJECTS, json!({"OBJ_ID": "T_DEPT"}));
+        rows.push_json(SYS_TABLE_NAMES::SYS_OBJECTS, json!({"OBJ_ID": "T_STAFF"}));
+        rows.push_json(SYS_TABLE_NAMES::SYS_OBJECTS, json!({"OBJ_ID": "T_SALES"}));
+        rows.push_json(SYS_TABLE_NAMES::SYS_OBJCOLS, json!({"OBJ_ID": "T_DEPT", "COL_ID": "DM"}));
+        rows.push_json(
+            SYS_TABLE_NAMES::SYS_OBJCOLS,
+            json!({"OBJ_ID": "T_STAFF", "COL_ID": "BM", "COL_ISFKEY": 1, "COL_FOBJ": "DCT_DEPT"}),
+        );
+        rows.push_json(
+            SYS_TABLE_NAMES::SYS_OBJCOLS,
+            json!({"OBJ_ID": "T_SALES", "COL_ID": "ZY", "COL_ISFKEY": 1, "COL_FOBJ": "DCT_STAFF"}),
+        );
+        rows.push_json(SYS_TABLE_NAMES::SYS_DICTS, json!({"DCT_ID": "DCT_DEPT", "OBJ_ID": "T_DEPT", "DCT_MC": "部门"}));
+        rows.push_json(SYS_TABLE_NAMES::SYS_DICTS, json!({"DCT_ID": "DCT_STAFF", "OBJ_ID": "T_STAFF"}));
+        rows.push_json(SYS_TABLE_NAMES::SYS_DICTS, json!({"DCT_ID": "DCT_YEAR"}));
+        rows.push_json(SYS_TABLE_NAMES::SYS_FACTS, json!({"FCT_ID": "FCT_SALES", "OBJ_ID": "T_SALES"}));
+        rows.push_json(SYS_TABLE_NAMES::SYS_MODEL, json!({"MDL_ID": "MDL_SALES", "MDL_UNITDCT": "DCT_DEPT"}));
+        rows.push_json(SYS_TABLE_NAMES::SYS_MDL_CTN, json!({"MDL_ID": "MDL_SALES", "CTN_FCT1": "FCT_SALES"}));
+        rows
+    }
+
+    fn registry() -> MetaRegistry {
+        let registry = MetaRegistry::new();
+        registry.swap("t", MetaCatalog::from(&Catalog::build(&full_rows())));
+        registry
+    }
+
+    fn dept_rows(name: &str) -> CatalogRows {
+        let mut rows = CatalogRows::new();
+        rows.push_json(SYS_TABLE_NAMES::SYS_OBJECTS, json!({"OBJ_ID": "T_DEPT"}));
+        rows.push_json(SYS_TABLE_NAMES::SYS_OBJCOLS, json!({"OBJ_ID": "T_DEPT", "COL_ID": "DM"}));
+        rows.push_json(SYS_TABLE_NAMES::SYS_DICTS, json!({"DCT_ID": "DCT_DEPT", "OBJ_ID": "T_DEPT", "DCT_MC": name}));
+        rows
+    }
+
+    #[test]
+    fn test_reload_relinks_dependents() {
+        let registry = registry();
+        let before = registry.snapshot("t").unwrap();
+        let change = MetaChange::new("t").with_dicts(["DCT_DEPT"]);
+        let report = registry.reload(&change, &dept_rows("组织"));
+        assert!(report.issues.is_empty());
+        assert_eq!(report.catalog.version(), 2);
+
+        let dept = registry.get_dct_meta("t", "DCT_DEPT").unwrap();
+        assert_eq!(dept.get_string(&SYS_DICTS::DCT_MC).as_deref(), Some("组织"));
+        // 引用方重新关联到新的字典
+        let staff = registry.get_dct_meta("t", "DCT_STAFF").unwrap();
+        assert!(Arc::ptr_eq(&staff.dct_metas.as_ref().unwrap()["DCT_DEPT"], &dept));
+        let sales = registry.get_fct_meta("t", "FCT_SALES").unwrap();
+        assert!(Arc::ptr_eq(&sales.dct_metas.as_ref().unwrap()["ZY"], &staff));
+        let model = registry.get_dme_meta("t", "MDL_SALES").unwrap();
+        assert_eq!(model.get_unit_dct().unwrap().get_string(&SYS_DICTS::DCT_MC).as_deref(), Some("组织"));
+        assert!(model.get_fct_meta("FCT_SALES").is_some());
+        assert!(!Arc::ptr_eq(&report.catalog.schema("T_DEPT").unwrap(), &before.schema("T_DEPT").unwrap()));
+        assert!(report.catalog.schema("T_SALES").is_some());
+
+        // 不相关的字典不重建，旧快照不受影响
+        assert!(Arc::ptr_eq(&before.dct_meta("DCT_YEAR").unwrap(), &registry.get_dct_meta("t", "DCT_YEAR").unwrap()));
+        assert_eq!(
+            before.dct_meta("DCT_DEPT").unwrap().get_string(&SYS_DICTS::DCT_MC).as_deref(),
+            Some("部门")
+        );
+    }
+
+    #[test]
+    fn test_reload_removed_dictionary() {
+        let registry = registry();
+        let change = MetaChange::new("t").with_dicts(["DCT_DEPT"]).with_version(9);
+        let report = registry.reload(&change, &CatalogRows::new());
+
+        assert_eq!(report.catalog.version(), 9);
+        assert!(registry.get_dct_meta("t", "DCT_DEPT").is_none());
+        assert!(registry.get_dct_meta("t", "DCT_STAFF").unwrap().dct_metas.is_none());
+        let mut issues: Vec<String> = report.issues.iter().map(ToString::to_string).collect();
+        issues.sort();
+        assert_eq!(
+            issues,
+            vec![
+                "column 'BM' of 'DCT_STAFF' references unknown dictionary 'DCT_DEPT'",
+                "column 'MDL_UNITDCT' of 'MDL_SALES' references unknown dictionary 'DCT_DEPT'",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reload_changed_fact_and_model() {
+        let registry = registry();
+        let mut rows = CatalogRows::new();
+        rows.push_json(SYS_TABLE_NAMES::SYS_FACTS, json!({"FCT_ID": "FCT_SALES", "FCT_MC": "销售"}));
+        rows.push_json(SYS_TABLE_NAMES::SYS_MODEL, json!({"MDL_ID": "MDL_NEW", "MDL_KEYDCT": "DCT_YEAR"}));
+        let change = MetaChange::new("t").with_facts(["FCT_SALES"]).with_models(["MDL_NEW"]);
+        let report = registry.reload(&change, &rows);
+        assert!(report.issues.is_empty());
+
+        assert_eq!(registry.get_fct_meta("t", "FCT_SALES").unwrap().name, "销售");
+        let model = registry.get_dme_meta("t", "MDL_SALES").unwrap();
+        assert_eq!(model.get_fct_meta("FCT_SALES").unwrap().name, "销售");
+        assert_eq!(registry.get_dme_meta("t", "MDL_NEW").unwrap().get_key_metric(0).unwrap().dct_id, "DCT_YEAR");
+    }
+
+    #[test]
+    fn test_reload_changed_objects() {
+        let registry = registry();
+        let mut rows = CatalogRows::new();
+        rows.push_json(SYS_TABLE_NAMES::SYS_OBJECTS, json!({"OBJ_ID": "T_DEPT"}));
+        rows.push_json(SYS_TABLE_NAMES::SYS_OBJCOLS, json!({"OBJ_ID": "T_DEPT", "COL_ID": "DM"}));
+        rows.push_json(SYS_TABLE_NAMES::SYS_OBJCOLS, json!({"OBJ_ID": "T_DEPT", "COL_ID": "MC"}));
+        let change = MetaChange::new("t").with_objects(["T_DEPT", "T_SALES"]);
+        let report = registry.reload(&change, &rows);
+
+        let catalog = registry.snapshot("t").unwrap();
+        assert_eq!(catalog.schema("T_DEPT").unwrap().column_count(), 2);
+        // 建在对象上的字典换用新的对象定义，引用它的字典重新关联
+        assert_eq!(catalog.dct_meta("DCT_DEPT").unwrap().table_schema.column_count(), 2);
+        let staff = catalog.dct_meta("DCT_STAFF").unwrap();
+        assert_eq!(staff.dct_metas.as_ref().unwrap()["DCT_DEPT"].table_schema.column_count(), 2);
+        // 目录行已不存在的对象被删除
+        assert!(catalog.schema("T_SALES").is_none());
+        assert_eq!(
+            report.issues,
+            [CatalogIssue::MissingObject { owner: "FCT_SALES".into(), obj_id: "T_SALES".into() }]
+        );
+        assert_eq!(catalog.schema("T_STAFF").unwrap().column_count(), 1);
+    }
+
+    #[test]
+    fn test_change_json() {
+        let change = MetaChange::new("t").with_dicts(["A"]);
+        assert_eq!(change.to_json().unwrap(), r#"{"tenant":"t","version":0,"dct_ids":["A"]}"#);
+        let parsed = MetaChange::from_json(r#"{"tenant":"t","fct_ids":["F"]}"#).unwrap();
+        assert_eq!(parsed, MetaChange::new("t").with_facts(["F"]));
+        assert!(MetaChange::new("t").is_empty());
+        assert!(!MetaChange::new("t").with_objects(["T"]).is_empty());
+    }
+
+    #[test]
+    fn test_catch_up_replays_changes_after_load() {
+        let buffered = vec![
+            MetaChange::new("t").with_dicts(["A"]).with_version(3),
+            MetaChange::new("t").with_dicts(["B"]).with_version(5),
+            MetaChange::new("u").with_dicts(["C"]).with_version(1),
+            MetaChange::new("t").with_dicts(["D"]),
+        ];
+        let live = MetaChange::new("t").with_dicts(["E"]).with_version(6);
+        let (sender, receiver) = futures::channel::mpsc::unbounded();
+        for change in &buffered {
+            sender.unbounded_send(change.clone()).unwrap();
+        }
+        let mut changes = receiver;
+
+        // 加载前到达的通知只重放版本更新的，加载完成时才到达的通知留给后续处理
+        let replay = futures::executor::block_on(catch_up(&mut changes, "t", async {
+            sender.unbounded_send(live.clone()).unwrap();
+            4
+        }));
+        assert_eq!(replay, buffered[1..].to_vec());
+        assert_eq!(futures::executor::block_on(changes.next()), Some(live));
+
+        // 加载失败时全部重放，订阅关闭不影响加载
+        for change in &buffered {
+            sender.unbounded_send(change.clone()).unwrap();
+        }
+        drop(sender);
+        let replay = futures::executor::block_on(catch_up(&mut changes, "t", async { 0 }));
+        assert_eq!(replay, buffered);
+    }
+}
@@ -0,0 +1,319 @@
+//! # 元数据快照
+//!
+//! 把字典（DCTMeta）和事实表（FCTMeta）元数据写入一个归档文件。服务每次从数据库全量加载目录后
+//! 写入快照，启动时先安装快照，数据库中的目录无法加载时仍然可以使用快照中的字典和事实表。
+//! 归档格式见 [`crate::model::data::dataset::archive`]。
+//!
+//! 快照中的元数据按 JSON 结构保存，不加载也可以通过 [`ArchivedMetaSnapshot`] 按 ID 查找，
+//! 读取其中的字段；加载时经过 JSON 值反序列化为结构体，并不是零拷贝的。
+//!
+//! ## 示例
+//!
+//! ```rust
+//! use cmx_core::model::data::dataset::TableSchemaBuilder;
+//! use cmx_core::model::data::dataset::archive::{self, ArchiveFile};
+//! use cmx_core::model::meta::dct::DCTMeta;
+//! use cmx_core::model::meta::registry::MetaRegistry;
+//! use cmx_core::model::meta::snapshot::MetaSnapshot;
+//!
+//! let mut snapshot = MetaSnapshot::new();
+//! snapshot.dct_metas.push(DCTMeta::new("DCT_DEPT".to_string(), TableSchemaBuilder::new().build()));
+//!
+//! let bytes = archive::encode(&snapshot).unwrap();
+//! let file = ArchiveFile::from_bytes(&bytes).unwrap();
+//! let archived = file.access::<MetaSnapshot>().unwrap();
+//! assert!(archived.dct("DCT_DEPT").is_some());
+//!
+//! // 加载后注册到注册表中的租户
+//! let registry = MetaRegistry::new();
+//! let snapshot: MetaSnapshot = file.load().unwrap();
+//! snapshot.install(&registry, "tenant_a").unwrap();
+//! assert!(registry.get_dct_meta("tenant_a", "DCT_DEPT").is_some());
+//! ```
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::model::data::dataset::archive::{
+    self, from_cell, to_cell, Archivable, ArchiveError, ArchiveFile, ArchiveKind, ArchivedCell, Cell,
+};
+
+use super::catalog::{foreign_key_error, Catalog};
+use super::dct::{DCTMeta, ForeignKeyError};
+use super::fct::FCTMeta;
+use super::registry::{MetaCatalog, MetaRegistry};
+
+impl Archivable for DCTMeta {
+    const KIND: ArchiveKind = ArchiveKind::DCTMeta;
+    type Repr = Cell;
+
+    fn to_repr(&self) -> Result<Self::Repr, ArchiveError> {
+        to_cell(self)
+    }
+
+    fn from_repr(repr: Self::Repr) -> Result<Self, ArchiveError> {
+        from_cell(repr)
+    }
+}
+
+impl Archivable for FCTMeta {
+    const KIND: ArchiveKind = ArchiveKind::FCTMeta;
+    type Repr = Cell;
+
+    fn to_repr(&self) -> Result<Self::Repr, ArchiveError> {
+        to_cell(self)
+    }
+
+    fn from_repr(repr: Self::Repr) -> Result<Self, ArchiveError> {
+        from_cell(repr)
+    }
+}
+
+/// 元数据快照
+#[derive(Debug, Default)]
+pub struct MetaSnapshot {
+    pub dct_metas: Vec<DCTMeta>,
+    pub fct_metas: Vec<FCTMeta>,
+}
+
+impl MetaSnapshot {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 目录中所有字典和事实表的快照，按 ID 排序
+    pub fn from_catalog(catalog: &MetaCatalog) -> Self {
+        let mut dct_metas: Vec<DCTMeta> = catalog.dct_metas().map(|meta| DCTMeta::clone(meta)).collect();
+        dct_metas.sort_by(|a, b| a.dct_id.cmp(&b.dct_id));
+        let mut fct_metas: Vec<FCTMeta> = catalog.fct_metas().map(|meta| FCTMeta::clone(meta)).collect();
+        fct_metas.sort_by(|a, b| a.id.cmp(&b.id));
+        Self { dct_metas, fct_metas }
+    }
+
+    /// 读取并加载快照文件
+    pub fn load_file(path: impl AsRef<Path>) -> Result<Self, ArchiveError> {
+        ArchiveFile::open(path)?.load()
+    }
+
+    /// 写入快照文件，见 [`archive::write_file`]
+    pub fn write_file(&self, path: impl AsRef<Path>) -> Result<(), ArchiveError> {
+        archive::write_file(self, path)
+    }
+
+    /// 把快照中的元数据作为一个新版本注册到 `registry` 的租户 `tenant`
+    ///
+    /// 字典按依赖顺序关联外键字典，与字典在快照中的顺序无关；快照外的外键字典从租户当前的目录中查找。
+    ///
+    /// # 返回值
+    ///
+    /// 缺少外键字典或存在循环引用时返回 `Err(ForeignKeyError)`，快照中的元数据仍然全部注册。
+    pub fn install(self, registry: &MetaRegistry, tenant: &str) -> Result<(), ForeignKeyError> {
+        self.install_with(registry, tenant, None)
+    }
+
+    /// 同 [`MetaSnapshot::install`]，以指定的版本号注册，版本号规则同 [`MetaRegistry::update_versioned`]
+    ///
+    /// 服务启动时以版本号 0 安装快照，随后从数据库加载的目录使用共享的版本号，不会被快照抬高。
+    pub fn install_versioned(self, registry: &MetaRegistry, tenant: &str, version: u64) -> Result<(), ForeignKeyError> {
+        self.install_with(registry, tenant, Some(version))
+    }
+
+    fn install_with(self, registry: &MetaRegistry, tenant: &str, version: Option<u64>) -> Result<(), ForeignKeyError> {
+        let pending: HashMap<String, DCTMeta> =
+            self.dct_metas.into_iter().map(|meta| (meta.dct_id.clone(), meta)).collect();
+        let fct_metas: Vec<Arc<FCTMeta>> = self.fct_metas.into_iter().map(Arc::new).collect();
+
+        // 在 update 中基于即将替换的目录关联外键字典，并发注册的字典不会被遗漏；
+        // update 可能重试，每次重试重新关联并覆盖上一次的问题
+        let issues = RefCell::new(Vec::new());
+        let install = |catalog: &mut MetaCatalog| {
+            let registered = catalog
+                .dct_metas()
+                .filter(|meta| !pending.contains_key(&meta.dct_id))
+                .map(|meta| (meta.dct_id.clone(), meta.clone()))
+                .collect();
+            let mut found = Vec::new();
+            let mut resolved = Catalog::link_dicts(pending.clone(), registered, &mut found);
+            for id in pending.keys() {
+                if let Some(meta) = resolved.remove(id) {
+                    catalog.insert_dct_meta(meta);
+                }
+            }
+            for meta in &fct_metas {
+                catalog.insert_fct_meta(meta.clone());
+            }
+            *issues.borrow_mut() = found;
+        };
+        match version {
+            Some(version) => registry.update_versioned(tenant, version, install),
+            None => registry.update(tenant, install),
+        };
+        let error = foreign_key_error(issues.into_inner());
+        if error.is_empty() { Ok(()) } else { Err(error) }
+    }
+}
+
+/// [`MetaSnapshot`] 的可归档表示
+#[derive(Debug, Clone, PartialEq, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+#[rkyv(archived = ArchivedMetaSnapshot)]
+pub struct MetaSnapshotRepr {
+    pub dct_metas: Vec<Cell>,
+    pub fct_metas: Vec<Cell>,
+}
+
+impl Archivable for MetaSnapshot {
+    const KIND: ArchiveKind = ArchiveKind::MetaSnapshot;
+    type Repr = MetaSnapshotRepr;
+
+    fn to_repr(&self) -> Result<Self::Repr, ArchiveError> {
+        Ok(MetaSnapshotRepr {
+            dct_metas: self.dct_metas.iter().map(to_cell).collect::<Result<_, _>>()?,
+            fct_metas: self.fct_metas.iter().map(to_cell).collect::<Result<_, _>>()?,
+        })
+    }
+
+    fn from_repr(repr: Self::Repr) -> Result<Self, ArchiveError> {
+        Ok(Self {
+            dct_metas: repr.dct_metas.into_iter().map(from_cell).collect::<Result<_, _>>()?,
+            fct_metas: repr.fct_metas.into_iter().map(from_cell).collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl ArchivedMetaSnapshot {
+    /// 快照中所有字典的 ID
+    pub fn dct_ids(&self) -> impl Iterator<Item = &str> {
+        self.dct_metas
+            .iter()
+            .filter_map(|meta| meta.get("dct_id").and_then(ArchivedCell::as_str))
+    }
+
+    /// 快照中所有事实表的 ID
+    pub fn fct_ids(&self) -> impl Iterator<Item = &str> {
+        self.fct_metas
+            .iter()
+            .filter_map(|meta| meta.get("id").and_then(ArchivedCell::as_str))
+    }
+
+    /// 按 ID 查找字典元数据
+    pub fn dct(&self, dct_id: &str) -> Option<&ArchivedCell> {
+        self.dct_metas
+            .iter()
+            .find(|meta| meta.get("dct_id").and_then(ArchivedCell::as_str) == Some(dct_id))
+    }
+
+    /// 按 ID 查找事实表元数据
+    pub fn fct(&self, id: &str) -> Option<&ArchivedCell> {
+        self.fct_metas
+            .iter()
+            .find(|meta| meta.get("id").and_then(ArchivedCell::as_str) == Some(id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::model::data::dataset::col::ColumnDef;
+    use crate::model::data::dataset::{TableSchema, TableSchemaBuilder};
+    use crate::model::meta::fields::{SYS_DICTS, SYS_OBJCOLS};
+
+    fn column(name: &str, foreign: Option<&str>) -> ColumnDef {
+        let mut column = ColumnDef::default();
+        column.set(SYS_OBJCOLS::COL_ID, json!(name));
+        column.set(SYS_OBJCOLS::COL_ISFKEY, json!(foreign.is_some()));
+        if let Some(foreign) = foreign {
+            column.set(SYS_OBJCOLS::COL_FOBJ, json!(foreign));
+        }
+        column
+    }
+
+    fn schema(columns: Vec<ColumnDef>) -> TableSchema {
+        TableSchemaBuilder::new().with_columns(columns).build()
+    }
+
+    fn snapshot() -> MetaSnapshot {
+        // 引用方排在被引用的字典之前
+        let mut staff = DCTMeta::new(
+            "SNAP_STAFF".to_string(),
+            schema(vec![column("DM", None), column("BM", Some("SNAP_DEPT"))]),
+        );
+        staff.set(SYS_DICTS::DCT_MC, json!("职员"));
+        let dept = DCTMeta::new("SNAP_DEPT".to_string(), schema(vec![column("DM", None)]));
+        let fact = FCTMeta {
+            id: "SNAP_SALES".to_string(),
+            name: "销售".to_string(),
+            info: None,
+            table_schema: schema(vec![column("JE", None)]),
+            dct_metas: None,
+            settings: None,
+        };
+        MetaSnapshot {
+            dct_metas: vec![staff, dept],
+            fct_metas: vec![fact],
+        }
+    }
+
+    #[test]
+    fn test_access_snapshot_without_loading() {
+        let bytes = archive::encode(&snapshot()).unwrap();
+        let file = ArchiveFile::from_bytes(&bytes).unwrap();
+        let archived = file.access::<MetaSnapshot>().unwrap();
+
+        assert_eq!(archived.dct_ids().collect::<Vec<_>>(), vec!["SNAP_STAFF", "SNAP_DEPT"]);
+        assert_eq!(archived.fct_ids().collect::<Vec<_>>(), vec!["SNAP_SALES"]);
+        let staff = archived.dct("SNAP_STAFF").unwrap();
+        assert_eq!(staff.get("data").and_then(|data| data.get("DCT_MC")).and_then(ArchivedCell::as_str), Some("职员"));
+        assert_eq!(archived.fct("SNAP_SALES").and_then(|fct| fct.get("name")).and_then(ArchivedCell::as_str), Some("销售"));
+        assert!(archived.dct("MISSING").is_none());
+    }
+
+    #[test]
+    fn test_single_meta_roundtrip() {
+        let meta = snapshot().dct_metas.remove(0);
+        let restored: DCTMeta = archive::decode(&archive::encode(&meta).unwrap()).unwrap();
+        assert_eq!(serde_json::to_value(&restored).unwrap(), serde_json::to_value(&meta).unwrap());
+        assert_eq!(restored.table_schema.get_column_index("BM"), Some(1));
+    }
+
+    #[test]
+    fn test_load_file_and_install() {
+        let path = std::env::temp_dir().join(format!("cmx-meta-{}.snapshot", uuid::Uuid::new_v4()));
+        snapshot().write_file(&path).unwrap();
+
+        let loaded = MetaSnapshot::load_file(&path).unwrap();
+        assert_eq!(loaded.dct_metas.len(), 2);
+        let registry = MetaRegistry::new();
+        loaded.install(&registry, "t").unwrap();
+
+        let staff = registry.get_dct_meta("t", "SNAP_STAFF").unwrap();
+        assert_eq!(staff.get_string(&SYS_DICTS::DCT_MC).as_deref(), Some("职员"));
+        assert!(staff.dct_metas.as_ref().unwrap().contains_key("SNAP_DEPT"));
+        assert_eq!(registry.get_fct_meta("t", "SNAP_SALES").unwrap().name, "销售");
+        assert!(registry.get_dct_meta("other", "SNAP_STAFF").is_none());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_snapshot_from_catalog() {
+        let registry = MetaRegistry::new();
+        snapshot().install(&registry, "t").unwrap();
+        let catalog = registry.snapshot("t").unwrap();
+
+        let taken = MetaSnapshot::from_catalog(&catalog);
+        assert_eq!(taken.dct_metas.iter().map(|meta| meta.dct_id.as_str()).collect::<Vec<_>>(), vec!["SNAP_DEPT", "SNAP_STAFF"]);
+        assert_eq!(taken.fct_metas.len(), 1);
+
+        // 以版本号 0 安装到新的注册表，外键字典重新关联
+        let restored = MetaRegistry::new();
+        let bytes = archive::encode(&taken).unwrap();
+        archive::decode::<MetaSnapshot>(&bytes).unwrap().install_versioned(&restored, "t", 0).unwrap();
+        let catalog = restored.snapshot("t").unwrap();
+        assert_eq!(catalog.version(), 0);
+        assert!(catalog.dct_meta("SNAP_STAFF").unwrap().dct_metas.as_ref().unwrap().contains_key("SNAP_DEPT"));
+    }
+}
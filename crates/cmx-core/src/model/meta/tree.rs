@@ -0,0 +1,677 @@
+//! # 编码树模块
+//!
+//! 科目、部门、地区等字典使用分级编码，编码结构由 `SYS_DICTS::DCT_BMSTRU` 描述，
+//! 例如 `"2-2-3"` 表示一级编码 2 位、二级编码 4 位、三级编码 7 位，下级编码以上级编码为前缀。
+//!
+//! - [`CodeStructure`]：编码结构，负责编码校验、级次计算和上级编码推导
+//! - [`CodeTree`]：根据一组编码建立的父子树
+//! - [`DictDataSet`]：字典数据集，增删行时维护编码树以及级数列（`DCT_JSCOLID`）和明细列（`DCT_MXCOLID`）
+//!
+//! ## 示例
+//!
+//! ```rust
+//! use cmx_core::model::meta::tree::CodeStructure;
+//!
+//! let structure: CodeStructure = "2-2-3".parse().unwrap();
+//! assert_eq!(structure.level_of("0101"), Some(2));
+//! assert_eq!(structure.parent_code("0101001"), Some("0101"));
+//! assert_eq!(structure.ancestors("0101001"), vec!["01", "0101"]);
+//! assert!(structure.validate("010").is_err());
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use thiserror::Error;
+
+use super::dct::DCTMeta;
+use super::fields::SYS_DICTS;
+use crate::model::data::cell::CellValue;
+use crate::model::data::dataset::rds::{RowData, RowDataSet};
+use crate::model::data::dataset::{ColumnType, DataSetError};
+
+/// 编码树错误
+#[derive(Error, Debug)]
+pub enum CodeTreeError {
+    #[error("Invalid code structure '{0}'")]
+    InvalidStructure(String),
+    #[error("Dictionary '{0}' has no code structure (DCT_BMSTRU)")]
+    NotHierarchical(String),
+    #[error("Dictionary '{dct_id}' has no {field} column '{column}' in dataset")]
+    MissingColumn { dct_id: String, field: String, column: String },
+    #[error("Empty code at row {0}")]
+    EmptyCode(usize),
+    #[error("Code '{code}' does not fit structure '{structure}'")]
+    InvalidCode { code: String, structure: CodeStructure },
+    #[error("Duplicate code '{0}'")]
+    DuplicateCode(String),
+    #[error("Parent code '{parent}' of '{code}' not found")]
+    MissingParent { code: String, parent: String },
+    #[error("Code '{0}' not found")]
+    CodeNotFound(String),
+    #[error("Code '{0}' has children")]
+    HasChildren(String),
+    #[error("DataSet error: {0}")]
+    DataSet(#[from] DataSetError),
+}
+
+/// 编码结构，记录每一级编码的位数
+///
+/// 支持两种写法：
+/// - 以 `-` 分隔的各级位数，如 `"4-2-2"`
+/// - 不带分隔符的一位数字序列，每个数字为一级的位数，如 `"422"`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeStructure {
+    segments: Vec<usize>,
+}
+
+impl FromStr for CodeStructure {
+    type Err = CodeTreeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || CodeTreeError::InvalidStructure(s.to_string());
+        let text = s.trim();
+        let segments: Vec<usize> = if text.contains('-') {
+            text.split('-').map(|part| part.trim().parse::<usize>().map_err(|_| invalid())).collect::<Result<_, _>>()?
+        } else {
+            text.chars().map(|c| c.to_digit(10).map(|d| d as usize).ok_or_else(invalid)).collect::<Result<_, _>>()?
+        };
+        if segments.is_empty() || segments.contains(&0) {
+            return Err(invalid());
+        }
+        Ok(Self { segments })
+    }
+}
+
+impl fmt::Display for CodeStructure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = self.segments.iter().map(usize::to_string).collect();
+        write!(f, "{}", parts.join("-"))
+    }
+}
+
+/// 编码的前 `len` 个字符
+fn char_prefix(code: &str, len: usize) -> &str {
+    code.char_indices().nth(len).map_or(code, |(end, _)| &code[..end])
+}
+
+impl CodeStructure {
+    /// 各级编码的位数
+    pub fn segments(&self) -> &[usize] {
+        &self.segments
+    }
+
+    /// 级次数
+    pub fn level_count(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// 第 `level` 级（从 1 开始）编码的总长度
+    pub fn code_length(&self, level: usize) -> Option<usize> {
+        (1..=self.segments.len()).contains(&level).then(|| self.segments[..level].iter().sum())
+    }
+
+    /// 编码所在的级次（从 1 开始），长度不符合任何一级时返回 `None`
+    pub fn level_of(&self, code: &str) -> Option<usize> {
+        if code.is_empty() || code.chars().any(char::is_whitespace) {
+            return None;
+        }
+        let len = code.chars().count();
+        let mut total = 0;
+        self.segments.iter().position(|segment| {
+            total += segment;
+            total == len
+        })
+        .map(|position| position + 1)
+    }
+
+    /// 校验编码，返回级次
+    pub fn validate(&self, code: &str) -> Result<usize, CodeTreeError> {
+        self.level_of(code).ok_or_else(|| CodeTreeError::InvalidCode { code: code.to_string(), structure: self.clone() })
+    }
+
+    /// 上级编码，一级编码或不符合结构的编码返回 `None`
+    pub fn parent_code<'a>(&self, code: &'a str) -> Option<&'a str> {
+        let level = self.level_of(code)?;
+        let len = self.code_length(level - 1)?;
+        Some(char_prefix(code, len))
+    }
+
+    /// 所有上级编码，从一级编码开始，不包含编码本身
+    pub fn ancestors<'a>(&self, code: &'a str) -> Vec<&'a str> {
+        let Some(level) = self.level_of(code) else {
+            return Vec::new();
+        };
+        (1..level).filter_map(|l| self.code_length(l)).map(|len| char_prefix(code, len)).collect()
+    }
+}
+
+/// 编码树节点
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeNode {
+    pub code: String,
+    /// 所在的数据行
+    pub row: usize,
+    /// 级次，从 1 开始
+    pub level: usize,
+    pub parent: Option<String>,
+    /// 直接下级编码，按编码排序
+    pub children: Vec<String>,
+}
+
+impl CodeNode {
+    pub fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+}
+
+/// 按编码结构建立的父子树
+#[derive(Debug, Clone)]
+pub struct CodeTree {
+    structure: CodeStructure,
+    nodes: BTreeMap<String, CodeNode>,
+    roots: Vec<String>,
+}
+
+impl CodeTree {
+    /// 根据 `(编码, 行号)` 建立编码树
+    ///
+    /// # 返回值
+    ///
+    /// - `Err(CodeTreeError::InvalidCode)` - 编码长度不符合编码结构
+    /// - `Err(CodeTreeError::DuplicateCode)` - 编码重复
+    /// - `Err(CodeTreeError::MissingParent)` - 非一级编码的上级编码不存在
+    ///
+    /// 需要一次拿到全部问题时使用 [`CodeTree::check`]。
+    pub fn build<I>(structure: CodeStructure, codes: I) -> Result<Self, CodeTreeError>
+    where
+        I: IntoIterator<Item = (String, usize)>,
+    {
+        let (tree, mut errors) = Self::collect(structure, codes);
+        if errors.is_empty() { Ok(tree) } else { Err(errors.swap_remove(0)) }
+    }
+
+    /// 检查一组编码，返回所有不符合编码结构、重复和缺少上级的问题
+    pub fn check<I>(structure: CodeStructure, codes: I) -> Vec<CodeTreeError>
+    where
+        I: IntoIterator<Item = (String, usize)>,
+    {
+        Self::collect(structure, codes).1
+    }
+
+    /// 建立编码树并收集问题，有问题的编码不进入树，缺少上级的编码作为根节点
+    fn collect<I>(structure: CodeStructure, codes: I) -> (Self, Vec<CodeTreeError>)
+    where
+        I: IntoIterator<Item = (String, usize)>,
+    {
+        let mut errors = Vec::new();
+        let mut nodes = BTreeMap::new();
+        for (code, row) in codes {
+            let level = match structure.validate(&code) {
+                Ok(level) => level,
+                Err(e) => {
+                    errors.push(e);
+                    continue;
+                }
+            };
+            if nodes.contains_key(&code) {
+                errors.push(CodeTreeError::DuplicateCode(code));
+                continue;
+            }
+            let parent = structure.parent_code(&code).map(str::to_string);
+            nodes.insert(code.clone(), CodeNode { code, row, level, parent, children: Vec::new() });
+        }
+
+        let mut roots = Vec::new();
+        let links: Vec<(String, Option<String>)> =
+            nodes.values().map(|node| (node.code.clone(), node.parent.clone())).collect();
+        for (code, parent) in links {
+            match parent {
+                Some(parent) if nodes.contains_key(&parent) => {
+                    if let Some(node) = nodes.get_mut(&parent) {
+                        node.children.push(code);
+                    }
+                }
+                Some(parent) => {
+                    errors.push(CodeTreeError::MissingParent { code: code.clone(), parent });
+                    roots.push(code);
+                }
+                None => roots.push(code),
+            }
+        }
+        (Self { structure, nodes, roots }, errors)
+    }
+
+    pub fn structure(&self) -> &CodeStructure {
+        &self.structure
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn get(&self, code: &str) -> Option<&CodeNode> {
+        self.nodes.get(code)
+    }
+
+    /// 按编码顺序遍历所有节点（即先序遍历）
+    pub fn nodes(&self) -> impl Iterator<Item = &CodeNode> {
+        self.nodes.values()
+    }
+
+    /// 根节点（一级编码），按编码排序
+    pub fn roots(&self) -> Vec<&CodeNode> {
+        self.roots.iter().filter_map(|code| self.nodes.get(code)).collect()
+    }
+
+    pub fn parent(&self, code: &str) -> Option<&CodeNode> {
+        self.nodes.get(code)?.parent.as_ref().and_then(|parent| self.nodes.get(parent))
+    }
+
+    /// 直接下级节点
+    pub fn children(&self, code: &str) -> Vec<&CodeNode> {
+        self.nodes
+            .get(code)
+            .map(|node| node.children.iter().filter_map(|child| self.nodes.get(child)).collect())
+            .unwrap_or_default()
+    }
+
+    /// 所有上级节点，从一级编码开始
+    pub fn ancestors(&self, code: &str) -> Vec<&CodeNode> {
+        self.structure.ancestors(code).into_iter().filter_map(|ancestor| self.nodes.get(ancestor)).collect()
+    }
+
+    /// 所有下级节点（不含自身），按先序排列
+    pub fn descendants(&self, code: &str) -> Vec<&CodeNode> {
+        self.nodes
+            .range::<str, _>((std::ops::Bound::Excluded(code), std::ops::Bound::Unbounded))
+            .take_while(|(key, _)| key.starts_with(code))
+            .map(|(_, node)| node)
+            .collect()
+    }
+
+    /// 是否末级节点，编码不存在时返回 `false`
+    pub fn is_leaf(&self, code: &str) -> bool {
+        self.nodes.get(code).is_some_and(CodeNode::is_leaf)
+    }
+}
+
+/// 把编码列的值转换为编码文本
+fn code_text(value: &CellValue) -> Option<String> {
+    match value {
+        CellValue::String(s) => Some(s.trim().to_string()),
+        CellValue::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+    .filter(|code| !code.is_empty())
+}
+
+/// 明细标志的单元格值，与列类型保持一致
+fn flag_cell(column_type: ColumnType, flag: bool) -> CellValue {
+    match column_type {
+        ColumnType::Bool => CellValue::Bool(flag),
+        t if t.is_numeric() => CellValue::from(u8::from(flag)),
+        _ => CellValue::String(if flag { "1" } else { "0" }.to_string()),
+    }
+}
+
+impl DCTMeta {
+    /// 编码结构（DCT_BMSTRU）
+    ///
+    /// # 返回值
+    ///
+    /// - `Err(CodeTreeError::NotHierarchical)` - 字典没有设置编码结构
+    /// - `Err(CodeTreeError::InvalidStructure)` - 编码结构格式错误
+    pub fn code_structure(&self) -> Result<CodeStructure, CodeTreeError> {
+        let text = self.non_empty(&SYS_DICTS::DCT_BMSTRU).ok_or_else(|| CodeTreeError::NotHierarchical(self.dct_id.clone()))?;
+        text.parse()
+    }
+
+    /// 编码列（DCT_BMCOLID）
+    pub fn code_column(&self) -> Option<String> {
+        self.non_empty(&SYS_DICTS::DCT_BMCOLID)
+    }
+
+    /// 级数列（DCT_JSCOLID）
+    pub fn level_column(&self) -> Option<String> {
+        self.non_empty(&SYS_DICTS::DCT_JSCOLID)
+    }
+
+    /// 明细（末级）标志列（DCT_MXCOLID）
+    pub fn leaf_column(&self) -> Option<String> {
+        self.non_empty(&SYS_DICTS::DCT_MXCOLID)
+    }
+
+    /// 校验编码是否符合编码结构，返回级次
+    pub fn validate_code(&self, code: &str) -> Result<usize, CodeTreeError> {
+        self.code_structure()?.validate(code)
+    }
+
+    /// 上级编码，一级编码返回 `Ok(None)`
+    pub fn parent_code(&self, code: &str) -> Result<Option<String>, CodeTreeError> {
+        let structure = self.code_structure()?;
+        structure.validate(code)?;
+        Ok(structure.parent_code(code).map(str::to_string))
+    }
+
+    /// 按编码列建立字典数据的编码树
+    pub fn build_code_tree(&self, data: &RowDataSet) -> Result<CodeTree, CodeTreeError> {
+        CodeTree::build(self.code_structure()?, self.row_codes(data)?)
+    }
+
+    /// 检查字典数据中的所有编码，返回发现的问题
+    ///
+    /// 外层错误表示字典本身的配置问题（没有编码结构、找不到编码列），
+    /// 内层列表为各行编码的问题，全部符合时为空。
+    pub fn check_codes(&self, data: &RowDataSet) -> Result<Vec<CodeTreeError>, CodeTreeError> {
+        let structure = self.code_structure()?;
+        let column = self.tree_column(data, "DCT_BMCOLID", self.code_column())?;
+        let mut errors = Vec::new();
+        let mut codes = Vec::new();
+        for (row, value) in data.get_column_values(&column)?.into_iter().enumerate() {
+            match code_text(value) {
+                Some(code) => codes.push((code, row)),
+                None => errors.push(CodeTreeError::EmptyCode(row)),
+            }
+        }
+        errors.extend(CodeTree::check(structure, codes));
+        Ok(errors)
+    }
+
+    fn row_codes(&self, data: &RowDataSet) -> Result<Vec<(String, usize)>, CodeTreeError> {
+        let column = self.tree_column(data, "DCT_BMCOLID", self.code_column())?;
+        data.get_column_values(&column)?
+            .into_iter()
+            .enumerate()
+            .map(|(row, value)| code_text(value).map(|code| (code, row)).ok_or(CodeTreeError::EmptyCode(row)))
+            .collect()
+    }
+
+    /// 检查字典设置的列存在于数据集中
+    fn tree_column(&self, data: &RowDataSet, field: &str, column: Option<String>) -> Result<String, CodeTreeError> {
+        let column = column.unwrap_or_default();
+        if data.get_column_info(&column).is_some() {
+            Ok(column)
+        } else {
+            Err(CodeTreeError::MissingColumn { dct_id: self.dct_id.clone(), field: field.to_string(), column })
+        }
+    }
+
+    pub(crate) fn non_empty(&self, field: &SYS_DICTS) -> Option<String> {
+        self.get_string(field).map(|s| s.trim().to_string()).filter(|s| !s.is_empty())
+    }
+}
+
+/// 字典数据集
+///
+/// 在 `RowDataSet` 之上维护编码树：新增行时校验编码和上级编码，删除行时检查下级，
+/// 每次增删之后重新计算级数列和明细列（字典未设置这两列时跳过）。
+///
+/// # 示例
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use cmx_core::model::data::dataset::{rds::RowDataSet, ColumnType, TableSchema};
+/// use cmx_core::model::meta::{dct::DCTMeta, fields::SYS_DICTS, tree::DictDataSet};
+/// use serde_json::json;
+///
+/// let mut meta = DCTMeta::new("DEPT".to_string(), TableSchema::default());
+/// meta.set(SYS_DICTS::DCT_BMSTRU, json!("2-2"));
+/// meta.set(SYS_DICTS::DCT_BMCOLID, json!("BM"));
+/// meta.set(SYS_DICTS::DCT_MXCOLID, json!("MX"));
+///
+/// let mut data = RowDataSet::new("DEPT".to_string());
+/// data.add_column("BM".to_string(), ColumnType::String).unwrap();
+/// data.add_column("MX".to_string(), ColumnType::Bool).unwrap();
+/// data.add_row(vec![json!("01"), json!(null)]).unwrap();
+///
+/// let mut dept = DictDataSet::new(Arc::new(meta), data).unwrap();
+/// assert_eq!(dept.data().get_cell(0, "MX").unwrap(), &json!(true));
+///
+/// dept.insert(vec![json!("0101"), json!(null)]).unwrap();
+/// assert_eq!(dept.data().get_cell(0, "MX").unwrap(), &json!(false));
+/// assert!(dept.insert(vec![json!("0201"), json!(null)]).is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct DictDataSet {
+    meta: Arc<DCTMeta>,
+    data: RowDataSet,
+    tree: CodeTree,
+    code_column: String,
+    level_column: Option<String>,
+    leaf_column: Option<String>,
+}
+
+impl DictDataSet {
+    /// 创建字典数据集，建立编码树并刷新级数列和明细列
+    ///
+    /// # 返回值
+    ///
+    /// - `Err(CodeTreeError::NotHierarchical)` - 字典没有设置编码结构
+    /// - `Err(CodeTreeError::MissingColumn)` - 字典设置的编码列、级数列或明细列不在数据集中
+    /// - 编码不符合结构、重复或缺少上级时返回对应的错误
+    pub fn new(meta: Arc<DCTMeta>, data: RowDataSet) -> Result<Self, CodeTreeError> {
+        let code_column = meta.tree_column(&data, "DCT_BMCOLID", meta.code_column())?;
+        let level_column = meta.level_column().map(|c| meta.tree_column(&data, "DCT_JSCOLID", Some(c))).transpose()?;
+        let leaf_column = meta.leaf_column().map(|c| meta.tree_column(&data, "DCT_MXCOLID", Some(c))).transpose()?;
+        let tree = meta.build_code_tree(&data)?;
+        let mut dict = Self { meta, data, tree, code_column, level_column, leaf_column };
+        dict.refresh_flags()?;
+        Ok(dict)
+    }
+
+    pub fn meta(&self) -> &Arc<DCTMeta> {
+        &self.meta
+    }
+
+    pub fn data(&self) -> &RowDataSet {
+        &self.data
+    }
+
+    pub fn tree(&self) -> &CodeTree {
+        &self.tree
+    }
+
+    pub fn into_data(self) -> RowDataSet {
+        self.data
+    }
+
+    /// 编码所在的行号
+    pub fn find(&self, code: &str) -> Option<usize> {
+        self.tree.get(code).map(|node| node.row)
+    }
+
+    /// 新增一行，返回行号
+    ///
+    /// 编码必须符合编码结构且不重复，非一级编码的上级编码必须已存在。
+    /// 上级节点的明细标志会随之更新为非末级。
+    pub fn insert(&mut self, values: Vec<CellValue>) -> Result<usize, CodeTreeError> {
+        let position = self.data.get_column_info(&self.code_column).map_or(0, |info| info.index);
+        let code = values.get(position).and_then(code_text).ok_or(CodeTreeError::EmptyCode(self.data.row_count()))?;
+        self.tree.structure().validate(&code)?;
+        if self.tree.get(&code).is_some() {
+            return Err(CodeTreeError::DuplicateCode(code));
+        }
+        if let Some(parent) = self.tree.structure().parent_code(&code)
+            && self.tree.get(parent).is_none()
+        {
+            return Err(CodeTreeError::MissingParent { code: code.clone(), parent: parent.to_string() });
+        }
+        self.data.add_row(values)?;
+        self.rebuild()?;
+        Ok(self.data.row_count() - 1)
+    }
+
+    /// 删除编码对应的行
+    ///
+    /// 有下级编码时返回 `CodeTreeError::HasChildren`，需要连同下级一起删除时使用
+    /// [`DictDataSet::remove_subtree`]。上级节点在没有其他下级后会变为末级。
+    pub fn remove(&mut self, code: &str) -> Result<RowData, CodeTreeError> {
+        let node = self.tree.get(code).ok_or_else(|| CodeTreeError::CodeNotFound(code.to_string()))?;
+        if !node.is_leaf() {
+            return Err(CodeTreeError::HasChildren(code.to_string()));
+        }
+        let row = self.data.remove_row(node.row)?;
+        self.rebuild()?;
+        Ok(row)
+    }
+
+    /// 删除编码及其所有下级，返回删除的行（按行号排序）
+    pub fn remove_subtree(&mut self, code: &str) -> Result<Vec<RowData>, CodeTreeError> {
+        let node = self.tree.get(code).ok_or_else(|| CodeTreeError::CodeNotFound(code.to_string()))?;
+        let mut rows: Vec<usize> = std::iter::once(node.row)
+            .chain(self.tree.descendants(code).into_iter().map(|node| node.row))
+            .collect();
+        rows.sort_unstable();
+        let mut removed = Vec::with_capacity(rows.len());
+        for row in rows.into_iter().rev() {
+            removed.push(self.data.remove_row(row)?);
+        }
+        removed.reverse();
+        self.rebuild()?;
+        Ok(removed)
+    }
+
+    /// 重新建立编码树并刷新级数列和明细列
+    ///
+    /// 通过 [`DictDataSet::into_data`] 以外的途径修改了数据后调用。
+    pub fn rebuild(&mut self) -> Result<(), CodeTreeError> {
+        self.tree = self.meta.build_code_tree(&self.data)?;
+        self.refresh_flags()
+    }
+
+    /// 按编码树写入级数和明细标志，只修改值有变化的单元格
+    fn refresh_flags(&mut self) -> Result<(), CodeTreeError> {
+        let level_column = self.level_column.as_ref().and_then(|c| self.data.get_column_info(c).map(|info| (c.clone(), info.column_type)));
+        let leaf_column = self.leaf_column.as_ref().and_then(|c| self.data.get_column_info(c).map(|info| (c.clone(), info.column_type)));
+        for node in self.tree.nodes() {
+            if let Some((column, column_type)) = &level_column {
+                let level = column_type.coerce(CellValue::from(node.level)).unwrap_or_else(|v| v);
+                if self.data.get_cell(node.row, column)? != &level {
+                    self.data.set_cell(node.row, column, level)?;
+                }
+            }
+            if let Some((column, column_type)) = &leaf_column {
+                let flag = flag_cell(*column_type, node.is_leaf());
+                if self.data.get_cell(node.row, column)? != &flag {
+                    self.data.set_cell(node.row, column, flag)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::model::data::dataset::TableSchema;
+
+    fn create_account_meta() -> Arc<DCTMeta> {
+        let mut meta = DCTMeta::new("KJKM".to_string(), TableSchema::default());
+        meta.set(SYS_DICTS::DCT_BMSTRU, json!("4-2-2"));
+        meta.set(SYS_DICTS::DCT_BMCOLID, json!("KMBM"));
+        meta.set(SYS_DICTS::DCT_JSCOLID, json!("JS"));
+        meta.set(SYS_DICTS::DCT_MXCOLID, json!("MX"));
+        Arc::new(meta)
+    }
+
+    fn create_account_data(codes: &[&str]) -> RowDataSet {
+        let mut data = RowDataSet::new("KJKM".to_string());
+        data.add_column("KMBM".to_string(), ColumnType::String).unwrap();
+        data.add_column("KMMC".to_string(), ColumnType::String).unwrap();
+        data.add_column("JS".to_string(), ColumnType::I32).unwrap();
+        data.add_column("MX".to_string(), ColumnType::String).unwrap();
+        for code in codes {
+            data.add_row(vec![json!(code), json!(format!("科目{code}")), json!(null), json!(null)]).unwrap();
+        }
+        data
+    }
+
+    #[test]
+    fn test_code_structure() {
+        let structure: CodeStructure = "4-2-2".parse().unwrap();
+        assert_eq!(structure, "422".parse().unwrap());
+        assert_eq!(structure.to_string(), "4-2-2");
+        assert_eq!(structure.code_length(2), Some(6));
+        assert_eq!(structure.code_length(4), None);
+        assert_eq!(structure.level_of("1001"), Some(1));
+        assert_eq!(structure.level_of("10010101"), Some(3));
+        assert_eq!(structure.level_of("10010"), None);
+        assert_eq!(structure.level_of("1001 1"), None);
+        assert_eq!(structure.parent_code("1001"), None);
+        assert_eq!(structure.parent_code("100101"), Some("1001"));
+        assert!("2-0".parse::<CodeStructure>().is_err());
+        assert!("a-b".parse::<CodeStructure>().is_err());
+    }
+
+    #[test]
+    fn test_code_tree() {
+        let meta = create_account_meta();
+        let data = create_account_data(&["1002", "100101", "1001", "10010101", "10010102", "100102"]);
+        let tree = meta.build_code_tree(&data).unwrap();
+
+        let roots: Vec<&str> = tree.roots().iter().map(|node| node.code.as_str()).collect();
+        assert_eq!(roots, vec!["1001", "1002"]);
+        let descendants: Vec<&str> = tree.descendants("1001").iter().map(|node| node.code.as_str()).collect();
+        assert_eq!(descendants, vec!["100101", "10010101", "10010102", "100102"]);
+        let ancestors: Vec<usize> = tree.ancestors("10010102").iter().map(|node| node.row).collect();
+        assert_eq!(ancestors, vec![2, 1]);
+        assert_eq!(tree.parent("100102").unwrap().code, "1001");
+        assert_eq!(tree.children("100101").len(), 2);
+        assert!(tree.is_leaf("1002"));
+        assert!(!tree.is_leaf("1001"));
+        assert_eq!(meta.parent_code("100101").unwrap(), Some("1001".to_string()));
+    }
+
+    #[test]
+    fn test_check_codes() {
+        let meta = create_account_meta();
+        let data = create_account_data(&["1001", "10010", "1001", "200101", "20010101"]);
+        let errors = meta.check_codes(&data).unwrap();
+        assert_eq!(errors.len(), 3);
+        assert!(matches!(&errors[0], CodeTreeError::InvalidCode { code, .. } if code == "10010"));
+        assert!(matches!(&errors[1], CodeTreeError::DuplicateCode(code) if code == "1001"));
+        assert!(matches!(&errors[2], CodeTreeError::MissingParent { parent, .. } if parent == "2001"));
+        assert!(meta.build_code_tree(&data).is_err());
+
+        let mut plain = DCTMeta::new("BZ".to_string(), TableSchema::default());
+        plain.set(SYS_DICTS::DCT_BMCOLID, json!("KMBM"));
+        assert!(matches!(plain.check_codes(&data), Err(CodeTreeError::NotHierarchical(_))));
+    }
+
+    #[test]
+    fn test_dict_dataset_flags() {
+        let mut dict = DictDataSet::new(create_account_meta(), create_account_data(&["1001", "100101"])).unwrap();
+        assert_eq!(dict.data().get_cell(1, "JS").unwrap(), &json!(2));
+        assert_eq!(dict.data().get_cell(0, "MX").unwrap(), &json!("0"));
+        assert_eq!(dict.data().get_cell(1, "MX").unwrap(), &json!("1"));
+
+        let row = dict.insert(vec![json!("10010101"), json!("现金"), json!(null), json!(null)]).unwrap();
+        assert_eq!(row, 2);
+        assert_eq!(dict.data().get_cell(2, "JS").unwrap(), &json!(3));
+        assert_eq!(dict.data().get_cell(1, "MX").unwrap(), &json!("0"));
+        assert!(matches!(
+            dict.insert(vec![json!("100201"), json!(""), json!(null), json!(null)]),
+            Err(CodeTreeError::MissingParent { .. })
+        ));
+        assert!(matches!(dict.remove("100101"), Err(CodeTreeError::HasChildren(_))));
+
+        dict.remove("10010101").unwrap();
+        assert_eq!(dict.data().get_cell(1, "MX").unwrap(), &json!("1"));
+        assert_eq!(dict.find("100101"), Some(1));
+
+        let removed = dict.remove_subtree("1001").unwrap();
+        assert_eq!(removed.len(), 2);
+        assert!(dict.tree().is_empty());
+        assert_eq!(dict.data().row_count(), 0);
+    }
+}
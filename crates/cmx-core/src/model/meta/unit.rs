@@ -0,0 +1,502 @@
+//! # 多单位模块
+//!
+//! 多单位对象（`OBJ_MUNIT`）和多单位字典（`DCT_MUNIT`）的数据按单位列（通常为 `UNIT_ID`）
+//! 分区保存。单位列为 `NULL` 或空串的行是所有单位共享的行。
+//!
+//! 单位之间的上下级关系由 [`UnitHierarchy`] 描述，通常按单位字典的编码树建立。读取时，
+//! [`UnitPartition::view`] 对每个行键只保留最具体的一行：本单位的行优先，其次是最近的上级单位，
+//! 最后是共享行。因此给某个单位单独修改共享字典行时，只需要为该单位增加一行相同键的覆盖行，
+//! 见 [`UnitPartition::override_row`]。
+//!
+//! ## 示例
+//!
+//! ```rust
+//! use cmx_core::model::data::context::SVRContext;
+//! use cmx_core::model::data::dataset::{rds::RowDataSet, ColumnType};
+//! use cmx_core::model::meta::unit::{UnitHierarchy, UnitPartition};
+//! use serde_json::json;
+//!
+//! let mut data = RowDataSet::new("currency".to_string());
+//! data.add_column("UNIT_ID".to_string(), ColumnType::String).unwrap();
+//! data.add_column("BZDM".to_string(), ColumnType::String).unwrap();
+//! data.add_column("BZMC".to_string(), ColumnType::String).unwrap();
+//! data.add_row(vec![json!(null), json!("RMB"), json!("人民币")]).unwrap();
+//! data.add_row(vec![json!(null), json!("USD"), json!("美元")]).unwrap();
+//! data.add_row(vec![json!("01"), json!("USD"), json!("美元（集团）")]).unwrap();
+//!
+//! let hierarchy = UnitHierarchy::from_pairs([("0101", "01")]);
+//! let partition = UnitPartition::new("UNIT_ID", vec!["BZDM".to_string()], hierarchy);
+//!
+//! let ctx = SVRContext::new();
+//! ctx.set(SVRContext::UNIT, json!("0101"));
+//! let view = partition.view_from_context(&data, &ctx).unwrap();
+//! assert_eq!(view.row_count(), 2);
+//! assert_eq!(view.get_cell(1, "BZMC").unwrap(), &json!("美元（集团）"));
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::model::data::cell::CellValue;
+use crate::model::data::context::SVRContext;
+use crate::model::data::dataset::col::flag_value;
+use crate::model::data::dataset::filter::{CompareOp, FilterExpr};
+use crate::model::data::dataset::rds::RowDataSet;
+use crate::model::data::dataset::{DataSetError, TableSchema};
+use crate::model::meta::dct::DCTMeta;
+use crate::model::meta::fields::SYS_DICTS;
+use crate::model::meta::tree::CodeTree;
+
+/// 默认的单位列
+pub const UNIT_COLUMN: &str = "UNIT_ID";
+
+/// 单位值，`NULL` 和空串表示共享
+fn unit_of(value: &CellValue) -> Option<String> {
+    match value {
+        CellValue::Null => None,
+        CellValue::String(s) => Some(s.trim().to_string()).filter(|s| !s.is_empty()),
+        other => Some(other.to_string()),
+    }
+}
+
+/// 单位在回退顺序中的位置，共享行排在最后；不可见的单位返回 `None`
+fn rank(chain: &[String], value: &CellValue) -> Option<usize> {
+    match unit_of(value) {
+        None => Some(chain.len()),
+        Some(owner) => chain.iter().position(|unit| *unit == owner),
+    }
+}
+
+/// 单位的上下级关系
+#[derive(Debug, Clone, Default)]
+pub struct UnitHierarchy {
+    parents: HashMap<String, String>,
+}
+
+impl UnitHierarchy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 由 `(单位, 上级单位)` 列表建立
+    pub fn from_pairs<'a>(pairs: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+        let mut hierarchy = Self::new();
+        for (unit, parent) in pairs {
+            hierarchy.set_parent(unit, parent);
+        }
+        hierarchy
+    }
+
+    /// 由单位字典的编码树建立，编码即单位 ID
+    pub fn from_code_tree(tree: &CodeTree) -> Self {
+        let parents = tree
+            .nodes()
+            .filter_map(|node| node.parent.as_ref().map(|parent| (node.code.clone(), parent.clone())))
+            .collect();
+        Self { parents }
+    }
+
+    /// 设置上级单位，会导致循环时忽略
+    pub fn set_parent(&mut self, unit: &str, parent: &str) {
+        if unit != parent && !self.ancestors(parent).iter().any(|a| a == unit) {
+            self.parents.insert(unit.to_string(), parent.to_string());
+        }
+    }
+
+    pub fn parent(&self, unit: &str) -> Option<&str> {
+        self.parents.get(unit).map(String::as_str)
+    }
+
+    /// 所有上级单位，由近到远
+    pub fn ancestors(&self, unit: &str) -> Vec<String> {
+        let mut ancestors = Vec::new();
+        let mut current = unit;
+        while let Some(parent) = self.parents.get(current) {
+            ancestors.push(parent.clone());
+            current = parent;
+        }
+        ancestors
+    }
+
+    /// 直接下级单位，按单位 ID 排序
+    pub fn children(&self, unit: &str) -> Vec<String> {
+        let mut children: Vec<String> =
+            self.parents.iter().filter(|(_, parent)| *parent == unit).map(|(child, _)| child.clone()).collect();
+        children.sort();
+        children
+    }
+
+    /// 所有下级单位，先序遍历
+    pub fn descendants(&self, unit: &str) -> Vec<String> {
+        let mut result = Vec::new();
+        for child in self.children(unit) {
+            let nested = self.descendants(&child);
+            result.push(child);
+            result.extend(nested);
+        }
+        result
+    }
+}
+
+/// 按单位分区的数据访问规则
+#[derive(Debug, Clone)]
+pub struct UnitPartition {
+    column: String,
+    key_columns: Vec<String>,
+    hierarchy: UnitHierarchy,
+}
+
+impl UnitPartition {
+    /// # 参数
+    ///
+    /// * `column` - 单位列
+    /// * `key_columns` - 行键列（不含单位列），同一键在不同单位的行互为覆盖关系
+    /// * `hierarchy` - 单位上下级关系
+    pub fn new(column: impl Into<String>, key_columns: Vec<String>, hierarchy: UnitHierarchy) -> Self {
+        Self { column: column.into(), key_columns, hierarchy }
+    }
+
+    /// 多单位对象的分区规则，行键为主键中除单位列以外的列；不是多单位对象时返回 `None`
+    pub fn from_schema(schema: &TableSchema, hierarchy: UnitHierarchy) -> Option<Self> {
+        if !schema.is_multi_unit() {
+            return None;
+        }
+        let column = schema.unit_column()?;
+        let keys = schema.primary_key_columns().into_iter().filter(|key| *key != column).collect();
+        Some(Self::new(column, keys, hierarchy))
+    }
+
+    /// 多单位字典的分区规则，行键为编码列；不是多单位字典时返回 `None`
+    pub fn from_dict(meta: &DCTMeta, hierarchy: UnitHierarchy) -> Option<Self> {
+        if !meta.is_multi_unit() {
+            return None;
+        }
+        let column = meta.table_schema.unit_column().unwrap_or_else(|| UNIT_COLUMN.to_string());
+        let keys = match meta.code_column() {
+            Some(code) => vec![code],
+            None => meta.table_schema.primary_key_columns().into_iter().filter(|key| *key != column).collect(),
+        };
+        Some(Self::new(column, keys, hierarchy))
+    }
+
+    pub fn column(&self) -> &str {
+        &self.column
+    }
+
+    pub fn key_columns(&self) -> &[String] {
+        &self.key_columns
+    }
+
+    pub fn hierarchy(&self) -> &UnitHierarchy {
+        &self.hierarchy
+    }
+
+    /// 单位可见的行：本单位、上级单位和共享的行；`unit` 为 `None` 时只有共享行
+    pub fn filter(&self, unit: Option<&str>) -> FilterExpr {
+        let shared = FilterExpr::is_null(&self.column)
+            .or(FilterExpr::compare(&self.column, CompareOp::Eq, CellValue::String(String::new())));
+        match unit {
+            Some(unit) => {
+                let units = self.chain(unit).into_iter().map(CellValue::String).collect();
+                FilterExpr::in_list(&self.column, units).or(shared)
+            }
+            None => shared,
+        }
+    }
+
+    /// 单位看到的数据：每个行键只保留最具体的一行，行顺序与原数据集一致
+    ///
+    /// # 返回值
+    ///
+    /// - `Err(DataSetError::ColumnNotFound)` - 数据集中没有单位列或行键列
+    pub fn view(&self, data: &RowDataSet, unit: Option<&str>) -> Result<RowDataSet, DataSetError> {
+        let unit_index = self.unit_index(data)?;
+        let keys = self.key_indexes(data)?;
+        let chain = unit.map(|unit| self.chain(unit)).unwrap_or_default();
+
+        let mut best: HashMap<Vec<String>, (usize, usize)> = HashMap::new();
+        for (row, values) in data.rows.iter().map(|row| row.values()).enumerate() {
+            let Some(rank) = rank(&chain, &values[unit_index]) else {
+                continue;
+            };
+            let entry = best.entry(row_key(values, &keys)).or_insert((rank, row));
+            if rank < entry.0 {
+                *entry = (rank, row);
+            }
+        }
+
+        let mut rows: Vec<usize> = best.into_values().map(|(_, row)| row).collect();
+        rows.sort_unstable();
+        let mut result = RowDataSet::new(data.dataset_id.clone());
+        result.schema = data.schema.clone();
+        result.rows = rows.into_iter().map(|row| data.rows[row].clone()).collect();
+        Ok(result)
+    }
+
+    /// 按 `SVRContext` 中的当前单位读取，见 [`UnitPartition::view`]
+    pub fn view_from_context(&self, data: &RowDataSet, context: &SVRContext) -> Result<RowDataSet, DataSetError> {
+        let unit = context.unit().as_ref().and_then(unit_of);
+        self.view(data, unit.as_deref())
+    }
+
+    /// 为单位建立覆盖行：复制该单位当前看到的同键行（上级单位或共享行），修改后作为本单位的行加入
+    ///
+    /// 单位已经有自己的行时直接修改该行。
+    ///
+    /// # 参数
+    ///
+    /// * `unit` - 单位
+    /// * `key` - 行键的值，顺序与 `key_columns` 一致
+    /// * `changes` - 要修改的列和值
+    ///
+    /// # 返回值
+    ///
+    /// - `Ok(row_index)` - 本单位的行
+    /// - `Err(DataSetError::IndexOutOfBounds)` - 单位看不到该键的行
+    pub fn override_row(
+        &self,
+        data: &mut RowDataSet,
+        unit: &str,
+        key: &[CellValue],
+        changes: &[(&str, CellValue)],
+    ) -> Result<usize, DataSetError> {
+        let unit_index = self.unit_index(data)?;
+        let row = self.visible_row(data, unit, key)?.ok_or(DataSetError::IndexOutOfBounds)?;
+        let row = if unit_of(&data.rows[row].values()[unit_index]).as_deref() == Some(unit) {
+            row
+        } else {
+            let mut values = data.rows[row].values().clone();
+            values[unit_index] = CellValue::String(unit.to_string());
+            data.add_row(values)?;
+            data.row_count() - 1
+        };
+        for (column, value) in changes {
+            data.set_cell(row, column, value.clone())?;
+        }
+        Ok(row)
+    }
+
+    /// 把一个单位的行复制到另一个单位，`from` 为 `None` 时复制共享行
+    ///
+    /// `overwrite` 为假时跳过目标单位已有的键，否则用源行覆盖目标行。返回写入的行数。
+    pub fn copy_rows(&self, data: &mut RowDataSet, from: Option<&str>, to: &str, overwrite: bool) -> Result<usize, DataSetError> {
+        let unit_index = self.unit_index(data)?;
+        let keys = self.key_indexes(data)?;
+
+        let mut existing: HashMap<Vec<String>, usize> = HashMap::new();
+        let mut sources = Vec::new();
+        for (row, values) in data.rows.iter().map(|row| row.values()).enumerate() {
+            let owner = unit_of(&values[unit_index]);
+            if owner.as_deref() == Some(to) {
+                existing.insert(row_key(values, &keys), row);
+            } else if owner.as_deref() == from {
+                sources.push(row);
+            }
+        }
+
+        let mut written = 0;
+        for row in sources {
+            let mut values = data.rows[row].values().clone();
+            values[unit_index] = CellValue::String(to.to_string());
+            match existing.get(&row_key(&values, &keys)) {
+                Some(_) if !overwrite => continue,
+                Some(target) => data.replace_values(*target, values)?,
+                None => data.add_row(values)?,
+            }
+            written += 1;
+        }
+        Ok(written)
+    }
+
+    /// 把上级单位的行复制到下级单位，`recursive` 为真时逐级复制到所有下级单位
+    ///
+    /// 返回每个下级单位写入的行数。
+    pub fn copy_to_children(
+        &self,
+        data: &mut RowDataSet,
+        parent: &str,
+        recursive: bool,
+        overwrite: bool,
+    ) -> Result<BTreeMap<String, usize>, DataSetError> {
+        let mut result = BTreeMap::new();
+        for child in self.hierarchy.children(parent) {
+            result.insert(child.clone(), self.copy_rows(data, Some(parent), &child, overwrite)?);
+            if recursive {
+                result.extend(self.copy_to_children(data, &child, true, overwrite)?);
+            }
+        }
+        Ok(result)
+    }
+
+    /// 单位及其所有上级单位，由近到远
+    fn chain(&self, unit: &str) -> Vec<String> {
+        let mut chain = vec![unit.to_string()];
+        chain.extend(self.hierarchy.ancestors(unit));
+        chain
+    }
+
+    /// 单位看到的指定键的行
+    fn visible_row(&self, data: &RowDataSet, unit: &str, key: &[CellValue]) -> Result<Option<usize>, DataSetError> {
+        let unit_index = self.unit_index(data)?;
+        let keys = self.key_indexes(data)?;
+        if key.len() != keys.len() {
+            return Err(DataSetError::ColumnCountMismatch);
+        }
+        let chain = self.chain(unit);
+        let mut best: Option<(usize, usize)> = None;
+        for (row, values) in data.rows.iter().map(|row| row.values()).enumerate() {
+            if keys.iter().zip(key).any(|(index, wanted)| values[*index] != *wanted) {
+                continue;
+            }
+            let Some(rank) = rank(&chain, &values[unit_index]) else {
+                continue;
+            };
+            if best.is_none_or(|(best_rank, _)| rank < best_rank) {
+                best = Some((rank, row));
+            }
+        }
+        Ok(best.map(|(_, row)| row))
+    }
+
+    fn unit_index(&self, data: &RowDataSet) -> Result<usize, DataSetError> {
+        data.get_column_info(&self.column).map(|info| info.index).ok_or(DataSetError::ColumnNotFound)
+    }
+
+    fn key_indexes(&self, data: &RowDataSet) -> Result<Vec<usize>, DataSetError> {
+        self.key_columns
+            .iter()
+            .map(|column| data.get_column_info(column).map(|info| info.index).ok_or(DataSetError::ColumnNotFound))
+            .collect()
+    }
+}
+
+/// 行键，用于比较不同单位的行是否相同
+fn row_key(values: &[CellValue], keys: &[usize]) -> Vec<String> {
+    keys.iter().map(|index| values[*index].to_string()).collect()
+}
+
+impl DCTMeta {
+    /// 是否多单位字典（DCT_MUNIT）
+    pub fn is_multi_unit(&self) -> bool {
+        self.get(&SYS_DICTS::DCT_MUNIT).and_then(flag_value).unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::model::data::dataset::col::ColumnDef;
+    use crate::model::data::dataset::{ColumnType, TableSchemaBuilder};
+    use crate::model::meta::fields::{SYS_OBJCOLS, SYS_OBJECTS};
+    use crate::model::meta::tree::CodeStructure;
+
+    fn hierarchy() -> UnitHierarchy {
+        UnitHierarchy::from_pairs([("01", "00"), ("0101", "01"), ("0102", "01"), ("02", "00")])
+    }
+
+    fn currencies() -> RowDataSet {
+        let mut data = RowDataSet::new("BZ".to_string());
+        data.add_column("UNIT_ID".to_string(), ColumnType::String).unwrap();
+        data.add_column("BZDM".to_string(), ColumnType::String).unwrap();
+        data.add_column("BZMC".to_string(), ColumnType::String).unwrap();
+        data.add_row(vec![json!(null), json!("RMB"), json!("人民币")]).unwrap();
+        data.add_row(vec![json!(""), json!("USD"), json!("美元")]).unwrap();
+        data.add_row(vec![json!("01"), json!("USD"), json!("美元01")]).unwrap();
+        data.add_row(vec![json!("0101"), json!("USD"), json!("美元0101")]).unwrap();
+        data.add_row(vec![json!("02"), json!("EUR"), json!("欧元02")]).unwrap();
+        data
+    }
+
+    fn names(data: &RowDataSet) -> Vec<String> {
+        data.get_column_values("BZMC").unwrap().into_iter().map(|v| v.as_str().unwrap().to_string()).collect()
+    }
+
+    #[test]
+    fn test_hierarchy() {
+        let hierarchy = hierarchy();
+        assert_eq!(hierarchy.ancestors("0101"), vec!["01", "00"]);
+        assert_eq!(hierarchy.children("01"), vec!["0101", "0102"]);
+        assert_eq!(hierarchy.descendants("00"), vec!["01", "0101", "0102", "02"]);
+
+        let mut cyclic = hierarchy.clone();
+        cyclic.set_parent("00", "0101");
+        assert_eq!(cyclic.parent("00"), None);
+
+        let structure: CodeStructure = "2-2".parse().unwrap();
+        let tree = CodeTree::build(structure, vec![("01".to_string(), 0), ("0101".to_string(), 1)]).unwrap();
+        assert_eq!(UnitHierarchy::from_code_tree(&tree).parent("0101"), Some("01"));
+    }
+
+    #[test]
+    fn test_view_and_filter() {
+        let partition = UnitPartition::new("UNIT_ID", vec!["BZDM".to_string()], hierarchy());
+        let data = currencies();
+
+        assert_eq!(names(&partition.view(&data, Some("0101")).unwrap()), vec!["人民币", "美元0101"]);
+        assert_eq!(names(&partition.view(&data, Some("0102")).unwrap()), vec!["人民币", "美元01"]);
+        assert_eq!(names(&partition.view(&data, Some("02")).unwrap()), vec!["人民币", "美元", "欧元02"]);
+        assert_eq!(names(&partition.view(&data, None).unwrap()), vec!["人民币", "美元"]);
+
+        let visible = data.filter(&partition.filter(Some("0102"))).unwrap();
+        assert_eq!(names(&visible), vec!["人民币", "美元", "美元01"]);
+
+        let ctx = SVRContext::new();
+        ctx.set(SVRContext::UNIT, json!("02"));
+        assert_eq!(partition.view_from_context(&data, &ctx).unwrap().row_count(), 3);
+
+        let missing = UnitPartition::new("DW", vec!["BZDM".to_string()], hierarchy());
+        assert!(matches!(missing.view(&data, None), Err(DataSetError::ColumnNotFound)));
+    }
+
+    #[test]
+    fn test_override_and_copy() {
+        let partition = UnitPartition::new("UNIT_ID", vec!["BZDM".to_string()], hierarchy());
+        let mut data = currencies();
+
+        let row = partition.override_row(&mut data, "0102", &[json!("RMB")], &[("BZMC", json!("人民币0102"))]).unwrap();
+        assert_eq!(row, 5);
+        assert_eq!(data.get_cell(0, "BZMC").unwrap(), &json!("人民币"));
+        assert_eq!(names(&partition.view(&data, Some("0102")).unwrap()), vec!["美元01", "人民币0102"]);
+        let again = partition.override_row(&mut data, "0102", &[json!("RMB")], &[("BZMC", json!("元"))]).unwrap();
+        assert_eq!(again, 5);
+        assert!(matches!(
+            partition.override_row(&mut data, "0101", &[json!("EUR")], &[]),
+            Err(DataSetError::IndexOutOfBounds)
+        ));
+
+        let copied = partition.copy_to_children(&mut data, "01", true, false).unwrap();
+        assert_eq!(copied, BTreeMap::from([("0101".to_string(), 0), ("0102".to_string(), 1)]));
+        assert_eq!(partition.copy_rows(&mut data, Some("01"), "0101", true).unwrap(), 1);
+        assert_eq!(data.get_cell(3, "BZMC").unwrap(), &json!("美元01"));
+        assert_eq!(partition.copy_rows(&mut data, None, "02", false).unwrap(), 2);
+        assert_eq!(data.row_count(), 9);
+    }
+
+    #[test]
+    fn test_partition_from_metadata() {
+        let column = |name: &str| {
+            let mut column = ColumnDef::default();
+            column.set(SYS_OBJCOLS::COL_ID, json!(name));
+            column
+        };
+        let mut dw = column("DWDM");
+        dw.set(SYS_OBJCOLS::COL_MUNIT, json!("1"));
+        let mut schema = TableSchemaBuilder::new().with_columns(vec![dw, column("BZDM"), column("BZMC")]).build();
+        assert!(UnitPartition::from_schema(&schema, UnitHierarchy::new()).is_none());
+        schema.set(SYS_OBJECTS::OBJ_MUNIT, json!("1"));
+        let partition = UnitPartition::from_schema(&schema, UnitHierarchy::new()).unwrap();
+        assert_eq!(partition.column(), "DWDM");
+
+        let ctx = SVRContext::new();
+        ctx.set(SVRContext::UNIT, json!("0101"));
+        assert_eq!(schema.row_factory(ctx).unwrap().new_row().unwrap(), vec![json!("0101"), json!(null), json!(null)]);
+
+        let mut meta = DCTMeta::new("BZ".to_string(), TableSchema::default());
+        meta.set(SYS_DICTS::DCT_BMCOLID, json!("BZDM"));
+        assert!(UnitPartition::from_dict(&meta, UnitHierarchy::new()).is_none());
+        meta.set(SYS_DICTS::DCT_MUNIT, json!("1"));
+        let partition = UnitPartition::from_dict(&meta, hierarchy()).unwrap();
+        assert_eq!((partition.column(), partition.key_columns()), ("UNIT_ID", &["BZDM".to_string()][..]));
+    }
+}
@@ -1,7 +1,12 @@
+use cmx_core::model::meta::catalog::Catalog;
+use cmx_core::model::meta::dml::DmlError;
+use cmx_core::model::meta::package::{ImportIssue, PackageError};
+use cmx_core::model::meta::registry::{MetaCatalog, MetaRegistry};
+use cmx_core::model::meta::reload::{MetaChange, ReloadReport};
 use sqlx::{PgConnection, PgPool};
 use thiserror::Error;
 
-use crate::database::postgres::{PostgresDatabase, PostgresOptions};
+use crate::database::postgres::{self, PostgresDatabase, PostgresOptions};
 
 pub type DatabasePool = PgPool;
 pub type DatabaseConnection = PgConnection;
@@ -39,6 +44,59 @@ impl Database {
 
         Ok(())
     }
+
+    /// Loads the metadata catalog from the `SYS_*` tables and installs the
+    /// linked dictionaries, facts and models as catalog `version` of
+    /// `tenant`, replacing the previous version atomically.
+    ///
+    /// `version` is the shared catalog version all server instances agree
+    /// on; it never moves an installed catalog backwards.
+    pub async fn load_catalog(
+        pool: &DatabasePool,
+        registry: &MetaRegistry,
+        tenant: &str,
+        version: u64,
+    ) -> Result<Catalog, DatabaseError> {
+        let catalog = postgres::load_catalog(pool).await?;
+        let installed = registry.swap_versioned(tenant, MetaCatalog::from(&catalog), version);
+        tracing::info!(
+            "Installed metadata catalog version {} for tenant {}.",
+            installed.version(),
+            tenant
+        );
+        Ok(catalog)
+    }
+
+    /// Reloads only the catalog entries named in `change` and relinks the
+    /// entries that depend on them.
+    ///
+    /// A tenant without an installed catalog has nothing to patch, so its
+    /// whole catalog is loaded at the version of the change instead.
+    pub async fn reload_catalog(
+        pool: &DatabasePool,
+        registry: &MetaRegistry,
+        change: &MetaChange,
+    ) -> Result<ReloadReport, DatabaseError> {
+        if registry.snapshot(&change.tenant).is_none() {
+            let loaded = Self::load_catalog(pool, registry, &change.tenant, change.version).await?;
+            let catalog = registry.snapshot(&change.tenant).unwrap_or_default();
+            return Ok(ReloadReport {
+                catalog,
+                issues: loaded.issues,
+            });
+        }
+        let rows = postgres::load_catalog_changes(pool, change).await?;
+        let report = registry.reload(change, &rows);
+        for issue in &report.issues {
+            tracing::warn!("Metadata catalog: {}", issue);
+        }
+        tracing::info!(
+            "Reloaded metadata catalog version {} for tenant {}.",
+            report.catalog.version(),
+            change.tenant
+        );
+        Ok(report)
+    }
 }
 
 #[derive(Error, Debug)]
@@ -47,4 +105,20 @@ pub enum DatabaseError {
     SQLxError(#[from] sqlx::Error),
     #[error(transparent)]
     SQLxMigrateError(#[from] sqlx::migrate::MigrateError),
+    #[error(transparent)]
+    JsonError(#[from] serde_json::Error),
+    #[error(transparent)]
+    PackageError(#[from] PackageError),
+    #[error(transparent)]
+    DmlError(#[from] DmlError),
+    #[error("Package import blocked: {}", issues_text(.0))]
+    ImportBlocked(Vec<ImportIssue>),
+}
+
+fn issues_text(issues: &[ImportIssue]) -> String {
+    issues
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
 }
@@ -4,4 +4,6 @@ mod postgres;
 pub use database::{
     Database, DatabaseConnection, DatabaseError, DatabaseOptions, DatabasePool, TestDatabase,
 };
-pub use postgres::PostgresOptions;
+pub use postgres::{
+    ImportOptions, PostgresOptions, export_package, import_package, load_catalog_rows,
+};
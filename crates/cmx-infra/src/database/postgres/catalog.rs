@@ -0,0 +1,181 @@
+use cmx_core::model::data::cell::CellValue;
+use cmx_core::model::meta::catalog::{Catalog, CatalogRows};
+use cmx_core::model::meta::reload::MetaChange;
+use cmx_core::model::meta::tables::SYS_TABLE_NAMES;
+use sqlx::{PgConnection, PgPool};
+
+use crate::database::DatabaseError;
+
+/// Catalog tables read at startup. Tables marked optional may be absent
+/// from older schemas and are skipped when missing.
+const CATALOG_TABLES: [(SYS_TABLE_NAMES, bool); 8] = [
+    (SYS_TABLE_NAMES::SYS_OBJECTS, false),
+    (SYS_TABLE_NAMES::SYS_OBJCOLS, false),
+    (SYS_TABLE_NAMES::SYS_KEYS, false),
+    (SYS_TABLE_NAMES::SYS_INDEXS, false),
+    (SYS_TABLE_NAMES::SYS_DICTS, false),
+    (SYS_TABLE_NAMES::SYS_FACTS, false),
+    (SYS_TABLE_NAMES::SYS_MODEL, false),
+    (SYS_TABLE_NAMES::SYS_MDL_CTN, true),
+];
+
+/// Reads every `SYS_*` catalog table and returns the raw rows.
+///
+/// Each row is fetched as a JSON object, so the column set of the catalog
+/// tables can grow without changing this query.
+pub async fn load_catalog_rows(pool: &PgPool) -> Result<CatalogRows, DatabaseError> {
+    read_catalog_rows(&mut *pool.acquire().await?).await
+}
+
+async fn read_catalog_rows(connection: &mut PgConnection) -> Result<CatalogRows, DatabaseError> {
+    let mut rows = CatalogRows::new();
+    for (table, optional) in CATALOG_TABLES {
+        fetch_rows(connection, &mut rows, table, optional, None).await?;
+    }
+    Ok(rows)
+}
+
+/// Reads the catalog rows of the objects, dictionaries, facts and models
+/// named in `change`, together with the objects they are built on.
+///
+/// Rows of other catalog entries are not read; the registry relinks the
+/// entries depending on the changed ones from what it already holds.
+pub async fn load_catalog_changes(pool: &PgPool, change: &MetaChange) -> Result<CatalogRows, DatabaseError> {
+    let mut connection = pool.acquire().await?;
+    let mut rows = CatalogRows::new();
+    let dicts = SYS_TABLE_NAMES::SYS_DICTS;
+    let facts = SYS_TABLE_NAMES::SYS_FACTS;
+    fetch_rows(&mut connection, &mut rows, dicts.clone(), false, Some(("dct_id", &change.dct_ids))).await?;
+    fetch_rows(&mut connection, &mut rows, facts.clone(), false, Some(("fct_id", &change.fct_ids))).await?;
+    fetch_rows(&mut connection, &mut rows, SYS_TABLE_NAMES::SYS_MODEL, false, Some(("mdl_id", &change.mdl_ids))).await?;
+    fetch_rows(&mut connection, &mut rows, SYS_TABLE_NAMES::SYS_MDL_CTN, true, Some(("mdl_id", &change.mdl_ids))).await?;
+
+    let mut obj_ids: Vec<String> = rows
+        .rows(&dicts)
+        .iter()
+        .chain(rows.rows(&facts))
+        .filter_map(|record| record.get("OBJ_ID").and_then(CellValue::as_str))
+        .map(str::to_string)
+        .chain(change.obj_ids.iter().cloned())
+        .collect();
+    obj_ids.sort();
+    obj_ids.dedup();
+    for table in [
+        SYS_TABLE_NAMES::SYS_OBJECTS,
+        SYS_TABLE_NAMES::SYS_OBJCOLS,
+        SYS_TABLE_NAMES::SYS_KEYS,
+        SYS_TABLE_NAMES::SYS_INDEXS,
+    ] {
+        fetch_rows(&mut connection, &mut rows, table, false, Some(("obj_id", &obj_ids))).await?;
+    }
+    Ok(rows)
+}
+
+/// Appends the rows of `table` to `rows`, optionally restricted to the rows
+/// whose `column` is one of `ids`. An empty id list reads nothing.
+///
+/// A missing optional table is checked up front rather than caught, so the
+/// read also works inside a transaction.
+async fn fetch_rows(
+    connection: &mut PgConnection,
+    rows: &mut CatalogRows,
+    table: SYS_TABLE_NAMES,
+    optional: bool,
+    filter: Option<(&str, &[String])>,
+) -> Result<(), DatabaseError> {
+    if matches!(filter, Some((_, []))) {
+        return Ok(());
+    }
+    if optional && !table_exists(connection, &table).await? {
+        tracing::debug!("Catalog table {} does not exist, skipped.", table);
+        return Ok(());
+    }
+    let records = match filter {
+        None => {
+            let query = format!("SELECT row_to_json(t)::text FROM {} t", table);
+            sqlx::query_scalar::<_, String>(&query).fetch_all(connection).await?
+        }
+        Some((column, ids)) => {
+            let query = format!("SELECT row_to_json(t)::text FROM {} t WHERE t.{}::text = ANY($1)", table, column);
+            sqlx::query_scalar::<_, String>(&query).bind(ids).fetch_all(connection).await?
+        }
+    };
+    for record in records {
+        rows.push_json(table.clone(), parse_record(&record)?);
+    }
+    Ok(())
+}
+
+async fn table_exists(connection: &mut PgConnection, table: &SYS_TABLE_NAMES) -> Result<bool, DatabaseError> {
+    let exists = sqlx::query_scalar::<_, bool>("SELECT to_regclass($1) IS NOT NULL")
+        .bind(table.to_string())
+        .fetch_one(connection)
+        .await?;
+    Ok(exists)
+}
+
+/// Locks the catalog tables against concurrent writes until the end of the
+/// current transaction. Readers are not blocked.
+///
+/// The tables are always locked in the same order, so two transactions
+/// locking the catalog wait for each other instead of deadlocking.
+pub(crate) async fn lock_catalog(connection: &mut PgConnection) -> Result<(), DatabaseError> {
+    for (table, optional) in CATALOG_TABLES {
+        if optional && !table_exists(connection, &table).await? {
+            continue;
+        }
+        let query = format!("LOCK TABLE {} IN SHARE ROW EXCLUSIVE MODE", table);
+        sqlx::query(&query).execute(&mut *connection).await?;
+    }
+    Ok(())
+}
+
+/// Loads the metadata catalog from Postgres and builds fully linked metas.
+///
+/// Problems found while linking (dangling objects, dictionaries or facts)
+/// do not fail the load; they are logged and kept in `Catalog::issues`.
+pub async fn load_catalog(pool: &PgPool) -> Result<Catalog, DatabaseError> {
+    read_catalog(&mut *pool.acquire().await?).await
+}
+
+/// Reads the metadata catalog on `connection`, see [`load_catalog`].
+pub(crate) async fn read_catalog(connection: &mut PgConnection) -> Result<Catalog, DatabaseError> {
+    let rows = read_catalog_rows(connection).await?;
+    let catalog = Catalog::build(&rows);
+    for issue in &catalog.issues {
+        tracing::warn!("Metadata catalog: {}", issue);
+    }
+    tracing::info!(
+        "Loaded metadata catalog: {} objects, {} dictionaries, {} facts, {} models.",
+        catalog.schemas.len(),
+        catalog.dct_metas.len(),
+        catalog.fct_metas.len(),
+        catalog.dme_metas.len(),
+    );
+    Ok(catalog)
+}
+
+fn parse_record(record: &str) -> Result<CellValue, DatabaseError> {
+    Ok(serde_json::from_str(record)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_record_upper_cases_columns() {
+        let mut rows = CatalogRows::new();
+        let record = parse_record(r#"{"obj_id": "T_DEPT", "obj_mc": null}"#).unwrap();
+        rows.push_json(SYS_TABLE_NAMES::SYS_OBJECTS, record);
+
+        let row = &rows.rows(&SYS_TABLE_NAMES::SYS_OBJECTS)[0];
+        assert_eq!(row["OBJ_ID"], "T_DEPT");
+        assert!(row["OBJ_MC"].is_null());
+    }
+
+    #[test]
+    fn test_parse_record_rejects_invalid_json() {
+        assert!(matches!(parse_record("{"), Err(DatabaseError::JsonError(_))));
+    }
+}
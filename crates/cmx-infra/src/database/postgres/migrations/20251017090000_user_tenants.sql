@@ -0,0 +1,2 @@
+-- bind users to the tenant whose metadata catalog they work with
+ALTER TABLE users ADD COLUMN tenant TEXT NOT NULL DEFAULT 'default';
@@ -1,5 +1,9 @@
+mod catalog;
 mod options;
+mod package;
 mod postgres;
 
 pub use options::PostgresOptions;
 pub use postgres::PostgresDatabase;
+pub use catalog::{load_catalog, load_catalog_changes, load_catalog_rows};
+pub use package::{ImportOptions, export_package, import_package};
@@ -0,0 +1,206 @@
+use std::collections::{BTreeMap, HashMap};
+
+use cmx_core::model::data::cell::CellValue;
+use cmx_core::model::meta::catalog::CatalogRecord;
+use cmx_core::model::meta::dml::{PgDml, RowValues, Statement};
+use cmx_core::model::meta::package::{ConflictPolicy, EntryKind, EntryRef, ImportPlan, MetaPackage};
+use cmx_core::model::meta::registry::MetaCatalog;
+use cmx_core::model::meta::tables::SYS_TABLE_NAMES;
+use sqlx::{PgConnection, PgPool};
+
+use crate::database::DatabaseError;
+use crate::database::postgres::catalog::{lock_catalog, read_catalog};
+
+/// How a metadata package is imported.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ImportOptions {
+    /// What to do with entries that differ from the target catalog.
+    pub policy: ConflictPolicy,
+    /// Only plan the import, write nothing.
+    pub dry_run: bool,
+}
+
+/// Exports `roots` and everything they depend on from `catalog`.
+///
+/// With `seed_data`, the rows of every dictionary table in the package are
+/// read from the database and added as seed data.
+pub async fn export_package(
+    pool: &PgPool,
+    catalog: &MetaCatalog,
+    roots: &[EntryRef],
+    seed_data: bool,
+) -> Result<MetaPackage, DatabaseError> {
+    let mut package = MetaPackage::export(catalog, roots)?;
+    if !seed_data {
+        return Ok(package);
+    }
+    let dicts: Vec<EntryRef> = package
+        .entries()
+        .filter(|entry| entry.kind == EntryKind::Dict)
+        .cloned()
+        .collect();
+    for entry in dicts {
+        let Some(schema) = package.dict(&entry.id).map(|dict| dict.table_schema.clone()) else {
+            continue;
+        };
+        let Some(obj_id) = schema.obj_id().map(str::to_string) else {
+            continue;
+        };
+        let statement = PgDml::new().select_all(&schema)?;
+        let rows = sqlx::query_scalar::<_, String>(&statement.sql)
+            .fetch_all(pool)
+            .await?
+            .iter()
+            .map(|row| PgDml::decode_row(&schema, row))
+            .collect::<Result<Vec<RowValues>, _>>()?;
+        package.add_data(&obj_id, rows)?;
+    }
+    Ok(package)
+}
+
+/// Imports a metadata package into the `SYS_*` catalog tables.
+///
+/// The package is compared with the catalog currently in the database and
+/// the resulting plan is returned. A dry run stops there. Otherwise the
+/// catalog tables are locked against concurrent writes before the catalog
+/// is read, so the plan still holds when it is applied, and the entries are
+/// written in dependency order in the same transaction: the
+/// catalog rows of a created or updated entry replace the existing ones,
+/// and seed data rows are upserted by primary key. Only the catalog columns
+/// carried by the package are written; the other columns of the catalog
+/// tables get their column defaults, and package fields without a catalog
+/// column are ignored.
+///
+/// Nothing is written when the plan has conflicts or missing dependencies.
+/// The catalog is not reloaded; publish `ImportPlan::change` for that.
+pub async fn import_package(
+    pool: &PgPool,
+    package: &MetaPackage,
+    options: ImportOptions,
+) -> Result<ImportPlan, DatabaseError> {
+    let mut tx = pool.begin().await?;
+    if !options.dry_run {
+        lock_catalog(&mut tx).await?;
+    }
+    let current = read_catalog(&mut tx).await?;
+    let plan = ImportPlan::new(package, &MetaCatalog::from(&current), options.policy);
+    if options.dry_run {
+        return Ok(plan);
+    }
+    if plan.is_blocked() {
+        return Err(DatabaseError::ImportBlocked(plan.issues));
+    }
+
+    let mut columns: HashMap<String, Vec<String>> = HashMap::new();
+    for step in plan.writes() {
+        let entry = &step.entry;
+        if entry.kind == EntryKind::Data {
+            let Some(schema) = package.schema(&entry.id) else {
+                continue;
+            };
+            let dml = PgDml::new();
+            for row in package.data(&entry.id).unwrap_or_default() {
+                execute(&mut tx, &dml.upsert(schema, row)?).await?;
+            }
+            continue;
+        }
+        for table in entry.kind.tables() {
+            let query = format!("DELETE FROM {} WHERE {}::text = $1", table, entry.kind.key_column());
+            sqlx::query(&query).bind(&entry.id).execute(&mut *tx).await?;
+        }
+        for (table, record) in package.catalog_rows(entry) {
+            let name = table.to_string();
+            if !columns.contains_key(&name) {
+                let table_columns = table_columns(&mut tx, &table).await?;
+                columns.insert(name.clone(), table_columns);
+            }
+            if let Some((query, row)) = catalog_insert(&table, &record, &columns[&name])? {
+                sqlx::query(&query).bind(row).execute(&mut *tx).await?;
+            }
+        }
+    }
+    tx.commit().await?;
+    tracing::info!(
+        "Imported metadata package: {} of {} entries written.",
+        plan.writes().count(),
+        plan.steps.len()
+    );
+    Ok(plan)
+}
+
+/// Columns of `table` as named in Postgres, in table order.
+async fn table_columns(
+    connection: &mut PgConnection,
+    table: &SYS_TABLE_NAMES,
+) -> Result<Vec<String>, DatabaseError> {
+    let columns = sqlx::query_scalar::<_, String>(
+        "SELECT attname::text FROM pg_attribute \
+         WHERE attrelid = $1::regclass AND attnum > 0 AND NOT attisdropped ORDER BY attnum",
+    )
+    .bind(table.to_string())
+    .fetch_all(connection)
+    .await?;
+    Ok(columns)
+}
+
+// Catalog columns are lower case in Postgres, the record keys are field names.
+// The columns are listed explicitly so that Postgres applies the defaults of
+// the columns left out; `None` when the record has no column of `table`.
+fn catalog_insert(
+    table: &SYS_TABLE_NAMES,
+    record: &CatalogRecord,
+    columns: &[String],
+) -> Result<Option<(String, String)>, DatabaseError> {
+    let row: BTreeMap<String, &CellValue> = record
+        .iter()
+        .map(|(column, value)| (column.to_ascii_lowercase(), value))
+        .filter(|(column, _)| columns.contains(column))
+        .collect();
+    if row.is_empty() {
+        return Ok(None);
+    }
+    let list = row
+        .keys()
+        .map(|column| format!("\"{}\"", column.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let query = format!(
+        "INSERT INTO {0} ({1}) SELECT {1} FROM json_populate_record(NULL::{0}, $1::json)",
+        table, list
+    );
+    Ok(Some((query, serde_json::to_string(&row)?)))
+}
+
+async fn execute(connection: &mut PgConnection, statement: &Statement) -> Result<(), DatabaseError> {
+    let query = statement
+        .params
+        .iter()
+        .fold(sqlx::query(&statement.sql), |query, param| query.bind(param.as_deref()));
+    query.execute(connection).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catalog_insert_lower_cases_columns() {
+        let record = CatalogRecord::from([
+            ("DCT_ID".to_string(), CellValue::from("DCT_DEPT")),
+            ("OBJ_ID".to_string(), CellValue::from("T_DEPT")),
+            ("DCT_NEW".to_string(), CellValue::from("x")),
+        ]);
+        let columns = ["dct_id", "obj_id", "dct_flag"].map(String::from);
+        let (query, row) = catalog_insert(&SYS_TABLE_NAMES::SYS_DICTS, &record, &columns)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            query,
+            "INSERT INTO SYS_DICTS (\"dct_id\", \"obj_id\") \
+             SELECT \"dct_id\", \"obj_id\" FROM json_populate_record(NULL::SYS_DICTS, $1::json)"
+        );
+        assert_eq!(row, r#"{"dct_id":"DCT_DEPT","obj_id":"T_DEPT"}"#);
+        assert!(catalog_insert(&SYS_TABLE_NAMES::SYS_DICTS, &record, &[]).unwrap().is_none());
+    }
+}
@@ -0,0 +1,66 @@
+use cmx_core::model::meta::reload::MetaChange;
+use futures::{Stream, StreamExt};
+use redis::{AsyncCommands, RedisResult, aio::MultiplexedConnection};
+
+use cmx_utils::config::Config;
+
+/// Channel the metadata change notifications are published on.
+pub const META_CHANGES_CHANNEL: &str = "cmx:meta:changes";
+
+/// Key holding the shared catalog version of a tenant.
+fn version_key(tenant: &str) -> String {
+    format!("cmx:meta:version:{}", tenant)
+}
+
+/// Returns the shared catalog version of `tenant`, 0 if nothing has been
+/// published yet.
+pub async fn catalog_version(connection: &mut MultiplexedConnection, tenant: &str) -> RedisResult<u64> {
+    let version: Option<u64> = connection.get(version_key(tenant)).await?;
+    Ok(version.unwrap_or_default())
+}
+
+/// Assigns the next shared catalog version to `change` and publishes it to
+/// every subscribed server instance.
+///
+/// Returns the assigned version.
+pub async fn publish_meta_change(
+    connection: &mut MultiplexedConnection,
+    mut change: MetaChange,
+) -> RedisResult<u64> {
+    let version: u64 = connection.incr(version_key(&change.tenant), 1).await?;
+    change.version = version;
+    let payload = change.to_json().map_err(|e| {
+        redis::RedisError::from((redis::ErrorKind::Client, "invalid meta change", e.to_string()))
+    })?;
+    let _: () = connection.publish(META_CHANGES_CHANNEL, payload).await?;
+    tracing::debug!("published meta change: {:?}", change);
+    Ok(version)
+}
+
+/// Subscribes to the metadata change notifications.
+///
+/// Payloads that are not valid changes are logged and skipped. The stream
+/// ends when the subscription connection is closed.
+pub async fn subscribe_meta_changes(config: &Config) -> RedisResult<impl Stream<Item = MetaChange>> {
+    let client = redis::Client::open(config.redis_url())?;
+    let mut pubsub = client.get_async_pubsub().await?;
+    pubsub.subscribe(META_CHANGES_CHANNEL).await?;
+    tracing::info!("subscribed to {}", META_CHANGES_CHANNEL);
+
+    Ok(pubsub.into_on_message().filter_map(|message| async move {
+        let payload: String = match message.get_payload() {
+            Ok(payload) => payload,
+            Err(e) => {
+                tracing::error!("invalid meta change payload: {}", e);
+                return None;
+            }
+        };
+        match MetaChange::from_json(&payload) {
+            Ok(change) => Some(change),
+            Err(e) => {
+                tracing::error!("invalid meta change {}: {}", payload, e);
+                None
+            }
+        }
+    }))
+}
@@ -1,2 +1,5 @@
 mod connection;
+mod meta;
+
 pub use connection::open;
+pub use meta::{META_CHANGES_CHANNEL, catalog_version, publish_meta_change, subscribe_meta_changes};
@@ -127,6 +127,13 @@ pub enum APIErrorCode {
     TransferAccountsAreSame,
     ResourceNotFound,
     ApiVersionError,
+    MetaChangeInvalid,
+    MetaNotFound,
+    RowNotFound,
+    RowInvalid,
+    RowConstraintViolation,
+    RowConflict,
+    CubeQueryInvalid,
     DatabaseError,
     RedisError,
 }
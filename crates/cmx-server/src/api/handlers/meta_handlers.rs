@@ -0,0 +1,63 @@
+use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
+use cmx_core::model::meta::reload::MetaChange;
+use serde_json::{Value, json};
+
+use crate::{
+    api::{APIError, APIErrorCode, APIErrorEntry, APIErrorKind, version::APIVersion},
+    application::{
+        security::{
+            auth::AuthError,
+            jwt::{AccessClaims, ClaimsMethods},
+        },
+        service::meta_service,
+        state::SharedState,
+    },
+};
+
+// Returns the catalog version of the caller's tenant, so clients can detect stale metadata caches.
+pub async fn catalog_version_handler(
+    api_version: APIVersion,
+    access_claims: AccessClaims,
+    State(state): State<SharedState>,
+) -> Result<Json<Value>, APIError> {
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+
+    let tenant = access_claims.tenant;
+    let version = state.meta.version(&tenant);
+    Ok(Json(json!({"tenant": tenant, "version": version})))
+}
+
+// Notifies every server instance that catalog entries have changed.
+pub async fn publish_change_handler(
+    api_version: APIVersion,
+    access_claims: AccessClaims,
+    State(state): State<SharedState>,
+    Json(mut change): Json<MetaChange>,
+) -> Result<impl IntoResponse, APIError> {
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+
+    access_claims.validate_role_admin()?;
+
+    if change.is_empty() {
+        let error_entry = APIErrorEntry::new("meta change names no catalog entries")
+            .code(APIErrorCode::MetaChangeInvalid)
+            .kind(APIErrorKind::ValidationError)
+            .reason("must name at least one of obj_ids, dct_ids, fct_ids or mdl_ids");
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, error_entry).into());
+    }
+    // Callers may only announce changes to the catalog of their own tenant.
+    if change.tenant.is_empty() {
+        change.tenant = access_claims.tenant;
+    } else if change.tenant != access_claims.tenant {
+        return Err(AuthError::Forbidden.into());
+    }
+
+    let tenant = change.tenant.clone();
+    let version = meta_service::publish_change(&state, change).await?;
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(json!({"tenant": tenant, "version": version})),
+    ))
+}
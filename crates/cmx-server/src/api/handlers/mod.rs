@@ -1,4 +1,7 @@
 pub mod account_handlers;
 pub mod auth_handlers;
+pub mod meta_handlers;
+pub mod model_handlers;
+pub mod row_handlers;
 pub mod transaction_handlers;
 pub mod user_handlers;
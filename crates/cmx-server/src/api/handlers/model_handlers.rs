@@ -0,0 +1,83 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use cmx_core::model::meta::cube::{CubeError, CubeQuery};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{
+    api::{
+        APIError, APIErrorCode, APIErrorEntry, APIErrorKind,
+        version::{self, APIVersion},
+    },
+    application::{
+        security::jwt::{AccessClaims, ClaimsMethods},
+        service::cube_service::{self, CrosstabLayout, CubeResult, ModelError},
+        state::SharedState,
+    },
+};
+
+#[derive(Debug, Deserialize)]
+pub struct CubeRequest {
+    #[serde(flatten)]
+    pub query: CubeQuery,
+    pub crosstab: Option<CrosstabLayout>,
+}
+
+pub async fn query_cube_handler(
+    access_claims: AccessClaims,
+    Path((version, mdl_id)): Path<(String, String)>,
+    State(state): State<SharedState>,
+    Json(request): Json<CubeRequest>,
+) -> Result<Json<CubeResult>, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    tracing::trace!("model: {}, request: {:?}", mdl_id, request);
+
+    access_claims.validate_role_admin()?;
+
+    let result = cube_service::query_cube(
+        &state,
+        &access_claims.tenant,
+        &mdl_id,
+        &request.query,
+        request.crosstab.as_ref(),
+    )
+    .await?;
+    Ok(Json(result))
+}
+
+impl From<ModelError> for APIError {
+    fn from(model_error: ModelError) -> Self {
+        let error = APIErrorEntry::new(&model_error.to_string());
+        let (status_code, entry) = match model_error {
+            ModelError::SQLxError(e) => return e.into(),
+            ModelError::ModelNotFound(mdl_id) => (
+                StatusCode::NOT_FOUND,
+                error
+                    .code(APIErrorCode::MetaNotFound)
+                    .kind(APIErrorKind::ResourceNotFound)
+                    .detail(json!({"source": "model", "id": mdl_id}))
+                    .reason("must be a model of the tenant's metadata catalog"),
+            ),
+            ModelError::Cube(CubeError::MissingTable(_) | CubeError::DataSet(_))
+            | ModelError::Decode(_) => {
+                // The metadata does not match the fact tables. Do not disclose the details, log them instead.
+                let error_entry = APIErrorEntry::from(StatusCode::INTERNAL_SERVER_ERROR).trace_id();
+                let trace_id = error_entry.trace_id.as_deref().unwrap_or("");
+                tracing::error!("cube error: {}, trace id: {}", error.message, trace_id);
+                return (StatusCode::INTERNAL_SERVER_ERROR, error_entry).into();
+            }
+            ModelError::Cube(_) => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                error
+                    .code(APIErrorCode::CubeQueryInvalid)
+                    .kind(APIErrorKind::ValidationError),
+            ),
+        };
+        (status_code, entry.trace_id()).into()
+    }
+}
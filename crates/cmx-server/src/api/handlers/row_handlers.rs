@@ -0,0 +1,228 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use cmx_core::model::meta::{
+    decorate::Decoration,
+    dml::{DmlError, RowValues},
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{
+    api::{
+        APIError, APIErrorCode, APIErrorEntry, APIErrorKind,
+        version::{self, APIVersion},
+    },
+    application::{
+        constants::{ROWS_DEFAULT_LIMIT, ROWS_MAX_LIMIT},
+        security::jwt::{AccessClaims, ClaimsMethods},
+        service::row_service::{self, RowError, RowPage, RowSource, RowTarget},
+        state::SharedState,
+    },
+};
+
+#[derive(Debug, Deserialize)]
+pub struct RowListQuery {
+    pub limit: Option<u64>,
+    pub offset: Option<u64>,
+    pub decorate: Option<Decoration>,
+}
+
+// The rows are looked up in the catalog of the tenant the caller is bound to.
+fn row_target(source: RowSource, access_claims: &AccessClaims, meta_id: String) -> RowTarget {
+    RowTarget {
+        source,
+        tenant: access_claims.tenant.clone(),
+        meta_id,
+    }
+}
+
+pub async fn list_rows_handler(
+    access_claims: AccessClaims,
+    Path((version, meta_id)): Path<(String, String)>,
+    Extension(source): Extension<RowSource>,
+    State(state): State<SharedState>,
+    Query(query): Query<RowListQuery>,
+) -> Result<Json<RowPage>, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    tracing::trace!("{}: {}, query: {:?}", source, meta_id, query);
+
+    access_claims.validate_role_admin()?;
+
+    let target = row_target(source, &access_claims, meta_id);
+    let limit = query
+        .limit
+        .unwrap_or(ROWS_DEFAULT_LIMIT)
+        .min(ROWS_MAX_LIMIT);
+    let offset = query.offset.unwrap_or(0);
+    let page = row_service::list_rows(&state, &target, limit, offset, query.decorate).await?;
+    Ok(Json(page))
+}
+
+pub async fn add_row_handler(
+    access_claims: AccessClaims,
+    Path((version, meta_id)): Path<(String, String)>,
+    Extension(source): Extension<RowSource>,
+    State(state): State<SharedState>,
+    Json(values): Json<RowValues>,
+) -> Result<impl IntoResponse, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    tracing::trace!("{}: {}, row: {:?}", source, meta_id, values);
+
+    access_claims.validate_role_admin()?;
+
+    let target = row_target(source, &access_claims, meta_id);
+    let row = row_service::create_row(&state, &target, &access_claims.sub, &values).await?;
+    Ok((StatusCode::CREATED, Json(row)))
+}
+
+pub async fn get_row_handler(
+    access_claims: AccessClaims,
+    Path((version, meta_id, key)): Path<(String, String, String)>,
+    Extension(source): Extension<RowSource>,
+    State(state): State<SharedState>,
+) -> Result<Json<RowValues>, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    tracing::trace!("{}: {}, key: {}", source, meta_id, key);
+
+    access_claims.validate_role_admin()?;
+
+    let target = row_target(source, &access_claims, meta_id);
+    let row = row_service::get_row(&state, &target, &key).await?;
+    Ok(Json(row))
+}
+
+pub async fn update_row_handler(
+    access_claims: AccessClaims,
+    Path((version, meta_id, key)): Path<(String, String, String)>,
+    Extension(source): Extension<RowSource>,
+    State(state): State<SharedState>,
+    Json(values): Json<RowValues>,
+) -> Result<Json<RowValues>, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    tracing::trace!("{}: {}, key: {}, row: {:?}", source, meta_id, key, values);
+
+    access_claims.validate_role_admin()?;
+
+    let target = row_target(source, &access_claims, meta_id);
+    let row = row_service::update_row(&state, &target, &key, &access_claims.sub, &values).await?;
+    Ok(Json(row))
+}
+
+pub async fn delete_row_handler(
+    access_claims: AccessClaims,
+    Path((version, meta_id, key)): Path<(String, String, String)>,
+    Extension(source): Extension<RowSource>,
+    State(state): State<SharedState>,
+) -> Result<Json<RowValues>, APIError> {
+    let api_version: APIVersion = version::parse_version(&version)?;
+    tracing::trace!("api version: {}", api_version);
+    tracing::trace!("authentication details: {:#?}", access_claims);
+    tracing::trace!("{}: {}, key: {}", source, meta_id, key);
+
+    access_claims.validate_role_admin()?;
+
+    let target = row_target(source, &access_claims, meta_id);
+    let row = row_service::delete_row(&state, &target, &key).await?;
+    Ok(Json(row))
+}
+
+impl From<RowError> for APIError {
+    fn from(row_error: RowError) -> Self {
+        if let RowError::SQLxError(e) = row_error {
+            return e.into();
+        }
+        let status_code = match &row_error {
+            RowError::MetaNotFound { .. } | RowError::RowNotFound { .. } => StatusCode::NOT_FOUND,
+            RowError::RowConflict(_) => StatusCode::CONFLICT,
+            RowError::Dml(
+                DmlError::UnknownColumn { .. }
+                | DmlError::InvalidValue { .. }
+                | DmlError::InvalidKey { .. }
+                | DmlError::NoChanges(_)
+                | DmlError::Violations(_),
+            ) => StatusCode::UNPROCESSABLE_ENTITY,
+            RowError::Dml(_) | RowError::Decode(_) | RowError::SQLxError(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        };
+        (status_code, row_error_entries(row_error)).into()
+    }
+}
+
+// One entry per violated constraint, otherwise a single entry.
+fn row_error_entries(row_error: RowError) -> Vec<APIErrorEntry> {
+    let error = APIErrorEntry::new(&row_error.to_string());
+    let invalid_row = |error: APIErrorEntry| {
+        error
+            .code(APIErrorCode::RowInvalid)
+            .kind(APIErrorKind::ValidationError)
+    };
+    let entry = match row_error {
+        RowError::MetaNotFound { kind, meta_id } => error
+            .code(APIErrorCode::MetaNotFound)
+            .kind(APIErrorKind::ResourceNotFound)
+            .detail(json!({"source": kind, "id": meta_id}))
+            .reason("must be a dictionary or object of the tenant's metadata catalog"),
+        RowError::RowNotFound { meta_id, key } => error
+            .code(APIErrorCode::RowNotFound)
+            .kind(APIErrorKind::ResourceNotFound)
+            .detail(json!({"id": meta_id, "key": key}))
+            .reason("must be the primary key of an existing row"),
+        RowError::RowConflict(table) => error
+            .code(APIErrorCode::RowConflict)
+            .kind(APIErrorKind::ValidationError)
+            .detail(json!({"table": table}))
+            .reason("must not duplicate the key of an existing row"),
+        RowError::Dml(DmlError::Violations(violations)) => {
+            return violations
+                .into_iter()
+                .map(|violation| {
+                    APIErrorEntry::new(&violation.message)
+                        .code(APIErrorCode::RowConstraintViolation)
+                        .kind(APIErrorKind::ValidationError)
+                        .detail(json!({
+                            "column": violation.column,
+                            "rule": violation.rule,
+                            "value": violation.value,
+                        }))
+                        .trace_id()
+                })
+                .collect();
+        }
+        RowError::Dml(DmlError::UnknownColumn { table, column }) => {
+            invalid_row(error).detail(json!({"table": table, "column": column}))
+        }
+        RowError::Dml(DmlError::InvalidValue { column, value }) => {
+            invalid_row(error).detail(json!({"column": column, "value": value}))
+        }
+        RowError::Dml(DmlError::InvalidKey { key, expected, .. }) => invalid_row(error)
+            .detail(json!({"key": key}))
+            .reason(&format!(
+                "must have {} comma-separated primary key value(s)",
+                expected
+            )),
+        RowError::Dml(DmlError::NoChanges(_)) => {
+            invalid_row(error).reason("must set at least one column")
+        }
+        RowError::Dml(_) | RowError::Decode(_) | RowError::SQLxError(_) => {
+            // The metadata does not match the table. Do not disclose the details, log them instead.
+            let error_entry = APIErrorEntry::from(StatusCode::INTERNAL_SERVER_ERROR).trace_id();
+            let trace_id = error_entry.trace_id.as_deref().unwrap_or("");
+            tracing::error!("row error: {}, trace id: {}", error.message, trace_id);
+            return vec![error_entry];
+        }
+    };
+    vec![entry.trace_id()]
+}
@@ -0,0 +1,15 @@
+use axum::{
+    Router,
+    routing::{get, post},
+};
+
+use crate::{
+    api::handlers::meta_handlers::{catalog_version_handler, publish_change_handler},
+    application::state::SharedState,
+};
+
+pub fn routes() -> Router<SharedState> {
+    Router::new()
+        .route("/version", get(catalog_version_handler))
+        .route("/changes", post(publish_change_handler))
+}
@@ -1,4 +1,7 @@
 pub mod account_routes;
 pub mod auth_routes;
+pub mod meta_routes;
+pub mod model_routes;
+pub mod row_routes;
 pub mod transaction_routes;
 pub mod user_routes;
@@ -0,0 +1,7 @@
+use axum::{Router, routing::post};
+
+use crate::{api::handlers::model_handlers::query_cube_handler, application::state::SharedState};
+
+pub fn routes() -> Router<SharedState> {
+    Router::new().route("/{mdl_id}/cube", post(query_cube_handler))
+}
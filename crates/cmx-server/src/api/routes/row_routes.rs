@@ -0,0 +1,22 @@
+use axum::{
+    Extension, Router,
+    routing::{delete, get, post, put},
+};
+
+use crate::{
+    api::handlers::row_handlers::{
+        add_row_handler, delete_row_handler, get_row_handler, list_rows_handler, update_row_handler,
+    },
+    application::{service::row_service::RowSource, state::SharedState},
+};
+
+// The same handlers serve dictionary and object rows; `source` tells them which catalog entry `meta_id` names.
+pub fn routes(source: RowSource) -> Router<SharedState> {
+    Router::new()
+        .route("/{meta_id}/rows", get(list_rows_handler))
+        .route("/{meta_id}/rows", post(add_row_handler))
+        .route("/{meta_id}/rows/{key}", get(get_row_handler))
+        .route("/{meta_id}/rows/{key}", put(update_row_handler))
+        .route("/{meta_id}/rows/{key}", delete(delete_row_handler))
+        .layer(Extension(source))
+}
@@ -23,9 +23,14 @@ use tower_http::cors::{Any, CorsLayer};
 use crate::{
     api::{
         error::APIError,
-        routes::{account_routes, auth_routes, transaction_routes, user_routes},
+        routes::{
+            account_routes, auth_routes, meta_routes, model_routes, row_routes, transaction_routes,
+            user_routes,
+        },
+    },
+    application::{
+        security::jwt::AccessClaims, service::row_service::RowSource, state::SharedState,
     },
-    application::{security::jwt::AccessClaims, state::SharedState},
 };
 
 pub async fn start(state: SharedState) {
@@ -61,6 +66,13 @@ pub async fn start(state: SharedState) {
         .nest("/{version}/accounts", account_routes::routes())
         // Nesting transaction routes.
         .nest("/{version}/transactions", transaction_routes::routes())
+        // Nesting metadata catalog routes.
+        .nest("/{version}/meta", meta_routes::routes())
+        // Nesting generic dictionary and object row routes.
+        .nest("/{version}/dicts", row_routes::routes(RowSource::Dict))
+        .nest("/{version}/objects", row_routes::routes(RowSource::Object))
+        // Nesting multidimensional model routes.
+        .nest("/{version}/models", model_routes::routes())
         // Add a fallback service for handling routes to unknown paths.
         .fallback(error_404_handler)
         .with_state(Arc::clone(&state))
@@ -1,13 +1,14 @@
 use std::sync::Arc;
 
+use cmx_core::model::meta::registry::{MetaRegistry, DEFAULT_TENANT};
 use cmx_utils::config;
 // use ::redis::aio::MultiplexedConnection;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, oneshot};
 
 use cmx_infra::{database::Database, redis};
 use crate::{
     api::server,
-    application::{state::AppState},
+    application::{service::meta_service, state::AppState},
     
 };
 
@@ -28,12 +29,31 @@ pub async fn run() {
         .await
         .expect("Failed to run database migrations.");
 
+    // The metadata registry is owned by the application state; handlers and
+    // services reach it through `SharedState`.
+    let meta = MetaRegistry::new();
+
     // Build the application state.
     let shared_state = Arc::new(AppState {
         config,
         db_pool,
         redis: Mutex::new(redis),
+        meta,
     });
 
+    // Install the cached metadata snapshot, if any, so it can be served when
+    // the catalog cannot be loaded from the database.
+    meta_service::load_snapshot(&shared_state, DEFAULT_TENANT);
+
+    // Subscribe to metadata changes published by any server instance, load
+    // the metadata catalog and wait for the first load before serving.
+    let (loaded, catalog_loaded) = oneshot::channel();
+    tokio::spawn(meta_service::listen_for_changes(
+        Arc::clone(&shared_state),
+        DEFAULT_TENANT,
+        loaded,
+    ));
+    let _ = catalog_loaded.await;
+
     server::start(shared_state).await;
 }
@@ -7,3 +7,10 @@ pub const USER_ROLE_GUEST: &str = "guest";
 pub const JWT_REDIS_REVOKE_GLOBAL_BEFORE_KEY: &str = "jwt.revoke.global.before";
 pub const JWT_REDIS_REVOKE_USER_BEFORE_KEY: &str = "jwt.revoke.user.before";
 pub const JWT_REDIS_REVOKED_TOKENS_KEY: &str = "jwt.revoked.tokens";
+
+// Metadata catalog related constants.
+pub const META_RESUBSCRIBE_DELAY_SECS: u64 = 5;
+
+// Generic row API related constants.
+pub const ROWS_DEFAULT_LIMIT: u64 = 100;
+pub const ROWS_MAX_LIMIT: u64 = 1000;
@@ -1,4 +1,5 @@
 pub mod account_repo;
+pub mod row_repo;
 pub mod transaction_repo;
 pub mod user_repo;
 
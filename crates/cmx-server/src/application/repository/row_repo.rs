@@ -0,0 +1,42 @@
+use cmx_core::model::meta::dml::Statement;
+use cmx_infra::database::DatabaseConnection;
+use sqlx::{Postgres, postgres::PgArguments, query::QueryScalar};
+
+use crate::application::repository::RepositoryResult;
+
+// Binds the text parameters of a generated statement; the statement casts them to the column types.
+fn scalar<'q, T>(statement: &'q Statement) -> QueryScalar<'q, Postgres, T, PgArguments>
+where
+    T: Send + Unpin + for<'r> sqlx::Decode<'r, Postgres> + sqlx::Type<Postgres>,
+{
+    statement
+        .params
+        .iter()
+        .fold(sqlx::query_scalar(&statement.sql), |query, param| {
+            query.bind(param.as_deref())
+        })
+}
+
+pub async fn fetch_all(
+    statement: &Statement,
+    connection: &mut DatabaseConnection,
+) -> RepositoryResult<Vec<String>> {
+    tracing::trace!("statement: {:?}", statement);
+    scalar(statement).fetch_all(connection).await
+}
+
+pub async fn fetch_optional(
+    statement: &Statement,
+    connection: &mut DatabaseConnection,
+) -> RepositoryResult<Option<String>> {
+    tracing::trace!("statement: {:?}", statement);
+    scalar(statement).fetch_optional(connection).await
+}
+
+pub async fn count(
+    statement: &Statement,
+    connection: &mut DatabaseConnection,
+) -> RepositoryResult<i64> {
+    tracing::trace!("statement: {:?}", statement);
+    scalar(statement).fetch_one(connection).await
+}
@@ -26,9 +26,10 @@ pub async fn add(user: User, state: &SharedState) -> RepositoryResult<User> {
          password_salt,
          active,
          roles,
+         tenant,
          created_at,
          updated_at)
-         VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9)
+         VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10)
          RETURNING users.*"#,
     )
     .bind(user.id)
@@ -38,6 +39,7 @@ pub async fn add(user: User, state: &SharedState) -> RepositoryResult<User> {
     .bind(user.password_salt)
     .bind(true)
     .bind(user.roles)
+    .bind(user.tenant)
     .bind(time_now)
     .bind(time_now)
     .fetch_one(&state.db_pool)
@@ -75,8 +77,9 @@ pub async fn update(user: User, state: &SharedState) -> RepositoryResult<User> {
          password_salt = $4,
          active = $5,
          roles = $6,
-         updated_at = $7
-         WHERE id = $8
+         tenant = $7,
+         updated_at = $8
+         WHERE id = $9
          RETURNING users.*"#,
     )
     .bind(user.username)
@@ -85,6 +88,7 @@ pub async fn update(user: User, state: &SharedState) -> RepositoryResult<User> {
     .bind(user.password_salt)
     .bind(user.active)
     .bind(user.roles)
+    .bind(user.tenant)
     .bind(time_now)
     .bind(user.id)
     .fetch_one(&state.db_pool)
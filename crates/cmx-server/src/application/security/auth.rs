@@ -103,6 +103,7 @@ pub fn generate_tokens(user: User, config: &Config) -> JwtTokens {
         exp: access_token_exp,
         typ: JwtTokenType::AccessToken as u8,
         roles: user.roles.clone(),
+        tenant: user.tenant,
     };
 
     let refresh_claims = RefreshClaims {
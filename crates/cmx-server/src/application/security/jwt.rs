@@ -25,6 +25,8 @@ pub struct AccessClaims {
     pub typ: u8,
     /// Roles.
     pub roles: String,
+    /// Tenant whose metadata catalog the subject works with.
+    pub tenant: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -0,0 +1,123 @@
+use cmx_core::model::{
+    data::dataset::rds::RowDataSet,
+    meta::{
+        cube::{Crosstab, Cube, CubeError, CubeQuery, FactQuery, FactSource, QueryError},
+        dml::RowValues,
+    },
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::application::{repository::row_repo, state::SharedState};
+
+/// How the cube result is pivoted into a crosstab.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CrosstabLayout {
+    /// Result columns forming the row headers.
+    #[serde(default)]
+    pub rows: Vec<String>,
+    /// Result columns forming the column headers.
+    #[serde(default)]
+    pub columns: Vec<String>,
+    /// Result column shown in the cells.
+    pub measure: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CubeResult {
+    /// One row per combination of dimension codes, ordered by the dimensions.
+    pub rows: Vec<RowValues>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crosstab: Option<Crosstab>,
+}
+
+#[derive(Debug, Error)]
+pub enum ModelError {
+    #[error("model not found: {0}")]
+    ModelNotFound(String),
+    #[error(transparent)]
+    Cube(#[from] CubeError),
+    #[error("invalid row returned by the database: {0}")]
+    Decode(#[from] serde_json::Error),
+    #[error(transparent)]
+    SQLxError(#[from] sqlx::Error),
+}
+
+impl From<QueryError<Self>> for ModelError {
+    fn from(e: QueryError<Self>) -> Self {
+        match e {
+            QueryError::Cube(e) => e.into(),
+            QueryError::Source { source, .. } => source,
+        }
+    }
+}
+
+// Aggregates the fact tables in the application database.
+struct FactTables<'a> {
+    state: &'a SharedState,
+}
+
+impl FactSource for FactTables<'_> {
+    type Error = ModelError;
+
+    async fn aggregate(&self, query: &FactQuery<'_>) -> Result<Vec<RowValues>, ModelError> {
+        let statement = query.statement(None)?;
+        let mut connection = self.state.db_pool.acquire().await?;
+        row_repo::fetch_all(&statement, &mut connection)
+            .await?
+            .iter()
+            .map(|row| Ok(serde_json::from_str(row)?))
+            .collect()
+    }
+}
+
+/// Runs a cube query on the model `mdl_id` of `tenant`, optionally pivoted into a crosstab.
+pub async fn query_cube(
+    state: &SharedState,
+    tenant: &str,
+    mdl_id: &str,
+    query: &CubeQuery,
+    layout: Option<&CrosstabLayout>,
+) -> Result<CubeResult, ModelError> {
+    let catalog = state.meta.snapshot(tenant);
+    let (catalog, model) = catalog
+        .and_then(|catalog| catalog.dme_meta(mdl_id).map(|model| (catalog, model)))
+        .ok_or_else(|| ModelError::ModelNotFound(mdl_id.to_owned()))?;
+
+    let result = Cube::new(&model, &catalog)
+        .query(query, &FactTables { state })
+        .await?;
+    let crosstab = match layout {
+        Some(layout) => {
+            let rows: Vec<&str> = layout.rows.iter().map(String::as_str).collect();
+            let columns: Vec<&str> = layout.columns.iter().map(String::as_str).collect();
+            Some(Crosstab::build(&result, &rows, &columns, &layout.measure)?)
+        }
+        None => None,
+    };
+    Ok(CubeResult {
+        rows: result_rows(&result),
+        crosstab,
+    })
+}
+
+fn result_rows(data: &RowDataSet) -> Vec<RowValues> {
+    let mut columns: Vec<(&String, usize)> = data
+        .schema
+        .iter()
+        .map(|(name, info)| (name, info.index))
+        .collect();
+    columns.sort_by_key(|(_, index)| *index);
+    (0..data.row_count())
+        .filter_map(|index| data.get_row(index).ok())
+        .map(|row| {
+            columns
+                .iter()
+                .map(|(name, index)| {
+                    let value = row.values().get(*index).cloned().unwrap_or_default();
+                    ((*name).clone(), value)
+                })
+                .collect()
+        })
+        .collect()
+}
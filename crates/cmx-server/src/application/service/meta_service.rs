@@ -0,0 +1,132 @@
+use std::{path::Path, time::Duration};
+
+use cmx_core::model::meta::reload::{self, MetaChange};
+use cmx_core::model::meta::snapshot::MetaSnapshot;
+use cmx_infra::{database::Database, redis};
+use futures::StreamExt;
+use ::redis::RedisResult;
+use tokio::sync::oneshot;
+
+use crate::application::{constants::META_RESUBSCRIBE_DELAY_SECS, state::SharedState};
+
+/// Loads the whole metadata catalog of `tenant` at the shared catalog version.
+///
+/// Returns the loaded version, or 0 when the catalog could not be loaded.
+pub async fn load_catalog(state: &SharedState, tenant: &str) -> u64 {
+    let version = match redis::catalog_version(&mut *state.redis.lock().await, tenant).await {
+        Ok(version) => version,
+        Err(e) => {
+            tracing::warn!("could not read the catalog version of {}: {}", tenant, e);
+            0
+        }
+    };
+    match Database::load_catalog(&state.db_pool, &state.meta, tenant, version).await {
+        Ok(_) => {
+            save_snapshot(state, tenant);
+            version
+        }
+        Err(e) => {
+            tracing::warn!("failed to load the metadata catalog of {}: {}", tenant, e);
+            0
+        }
+    }
+}
+
+/// Installs the metadata snapshot file for `tenant` at catalog version 0.
+///
+/// The dictionaries and facts in the snapshot are available before the
+/// catalog is loaded from the database, and stay available when that load
+/// fails. The loaded catalog replaces them.
+pub fn load_snapshot(state: &SharedState, tenant: &str) {
+    let Some(path) = &state.config.meta_snapshot_path else {
+        return;
+    };
+    if !Path::new(path).exists() {
+        tracing::info!("metadata snapshot {} not found", path);
+        return;
+    }
+    match MetaSnapshot::load_file(path) {
+        Ok(snapshot) => {
+            if let Err(e) = snapshot.install_versioned(&state.meta, tenant, 0) {
+                tracing::warn!(
+                    "metadata snapshot {} has unresolved dictionaries: {}",
+                    path,
+                    e
+                );
+            }
+            tracing::info!(
+                "Installed metadata snapshot {} for tenant {}.",
+                path,
+                tenant
+            );
+        }
+        Err(e) => tracing::warn!("could not load the metadata snapshot {}: {}", path, e),
+    }
+}
+
+/// Writes the installed catalog of `tenant` to the metadata snapshot file.
+fn save_snapshot(state: &SharedState, tenant: &str) {
+    let (Some(path), Some(catalog)) = (
+        &state.config.meta_snapshot_path,
+        state.meta.snapshot(tenant),
+    ) else {
+        return;
+    };
+    if let Err(e) = MetaSnapshot::from_catalog(&catalog).write_file(path) {
+        tracing::warn!("could not write the metadata snapshot {}: {}", path, e);
+    }
+}
+
+/// Publishes a metadata change to every server instance, including this one.
+///
+/// Returns the catalog version assigned to the change.
+pub async fn publish_change(state: &SharedState, change: MetaChange) -> RedisResult<u64> {
+    redis::publish_meta_change(&mut *state.redis.lock().await, change).await
+}
+
+/// Loads the metadata catalog of `tenant` and applies the published metadata
+/// changes to `state.meta` until the server stops.
+///
+/// The subscription is opened before the catalog is loaded, so changes
+/// published during the load are buffered and replayed on top of it instead
+/// of being lost or overwritten. After the connection is lost the catalog is
+/// loaded again on the new subscription. `loaded` is signalled once the
+/// first load has finished.
+pub async fn listen_for_changes(state: SharedState, tenant: &str, loaded: oneshot::Sender<()>) {
+    let mut loaded = Some(loaded);
+    loop {
+        match redis::subscribe_meta_changes(&state.config).await {
+            Ok(changes) => {
+                let mut changes = std::pin::pin!(changes);
+                let pending =
+                    reload::catch_up(&mut changes, tenant, load_catalog(&state, tenant)).await;
+                for change in &pending {
+                    apply_change(&state, change).await;
+                }
+                if let Some(loaded) = loaded.take() {
+                    let _ = loaded.send(());
+                }
+                while let Some(change) = changes.next().await {
+                    apply_change(&state, &change).await;
+                }
+                tracing::warn!("meta change subscription closed");
+            }
+            Err(e) => {
+                tracing::error!("could not subscribe to meta changes: {}", e);
+                // Serve the catalog as loaded now; it is loaded again once subscribed.
+                if let Some(loaded) = loaded.take() {
+                    load_catalog(&state, tenant).await;
+                    let _ = loaded.send(());
+                }
+            }
+        }
+        tokio::time::sleep(Duration::from_secs(META_RESUBSCRIBE_DELAY_SECS)).await;
+    }
+}
+
+async fn apply_change(state: &SharedState, change: &MetaChange) {
+    tracing::debug!("applying meta change: {:?}", change);
+    if let Err(e) = Database::reload_catalog(&state.db_pool, &state.meta, change).await {
+        tracing::error!("failed to reload meta change {:?}: {}", change, e);
+    }
+}
@@ -1,2 +1,5 @@
+pub mod cube_service;
+pub mod meta_service;
+pub mod row_service;
 pub mod token_service;
 pub mod transaction_service;
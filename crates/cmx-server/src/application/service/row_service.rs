@@ -0,0 +1,272 @@
+use std::{collections::HashMap, fmt, sync::Arc};
+
+use cmx_core::model::{
+    data::{cell::CellValue, context::SVRContext, dataset::TableSchema},
+    meta::{
+        dct::DCTMeta,
+        decorate::{DecorateError, DecorateIssue, Decoration, FkDecorator, NameSource},
+        dml::{DmlError, PgDml, RowValues, Statement},
+        registry::MetaCatalog,
+    },
+};
+use serde::Serialize;
+use serde_json::json;
+use thiserror::Error;
+
+use crate::application::{repository::row_repo, state::SharedState};
+
+/// The kind of metadata a generic row route serves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RowSource {
+    /// Rows of a dictionary, addressed by `DCT_ID`.
+    Dict,
+    /// Rows of an object, addressed by `OBJ_ID`.
+    Object,
+}
+
+impl fmt::Display for RowSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Dict => write!(f, "dictionary"),
+            Self::Object => write!(f, "object"),
+        }
+    }
+}
+
+/// The table a row request addresses.
+#[derive(Debug, Clone)]
+pub struct RowTarget {
+    pub source: RowSource,
+    pub tenant: String,
+    pub meta_id: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RowPage {
+    pub rows: Vec<RowValues>,
+    pub total: i64,
+    pub limit: u64,
+    pub offset: u64,
+    /// Dangling foreign keys and undecorated columns when the rows are decorated.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub issues: Vec<DecorateIssue>,
+}
+
+#[derive(Debug, Error)]
+pub enum RowError {
+    #[error("{kind} not found: {meta_id}")]
+    MetaNotFound { kind: RowSource, meta_id: String },
+    #[error("row not found: {key}")]
+    RowNotFound { meta_id: String, key: String },
+    #[error("row conflicts with an existing row of {0}")]
+    RowConflict(String),
+    #[error(transparent)]
+    Dml(#[from] DmlError),
+    #[error("invalid row returned by the database: {0}")]
+    Decode(#[from] serde_json::Error),
+    #[error(transparent)]
+    SQLxError(sqlx::Error),
+}
+
+impl From<sqlx::Error> for RowError {
+    fn from(e: sqlx::Error) -> Self {
+        match e {
+            sqlx::Error::Database(ref db_error) if db_error.is_unique_violation() => {
+                Self::RowConflict(db_error.table().unwrap_or_default().to_owned())
+            }
+            e => Self::SQLxError(e),
+        }
+    }
+}
+
+impl From<DecorateError<Self>> for RowError {
+    fn from(e: DecorateError<Self>) -> Self {
+        match e {
+            DecorateError::Lookup { source, .. } => source,
+            DecorateError::DataSet(e) => DmlError::from(e).into(),
+        }
+    }
+}
+
+// Keeps the dictionary alive while its schema is in use.
+enum RowTable {
+    Dict(Arc<DCTMeta>),
+    Object {
+        schema: Arc<TableSchema>,
+        catalog: Arc<MetaCatalog>,
+    },
+}
+
+impl RowTable {
+    fn resolve(state: &SharedState, target: &RowTarget) -> Result<Self, RowError> {
+        let catalog = state.meta.snapshot(&target.tenant);
+        let table = catalog.and_then(|catalog| match target.source {
+            RowSource::Dict => catalog.dct_meta(&target.meta_id).map(Self::Dict),
+            RowSource::Object => catalog
+                .schema(&target.meta_id)
+                .map(|schema| Self::Object { schema, catalog }),
+        });
+        table.ok_or_else(|| RowError::MetaNotFound {
+            kind: target.source,
+            meta_id: target.meta_id.clone(),
+        })
+    }
+
+    fn schema(&self) -> &TableSchema {
+        match self {
+            Self::Dict(meta) => &meta.table_schema,
+            Self::Object { schema, .. } => schema,
+        }
+    }
+
+    // Dictionaries resolve their foreign keys through `dct_metas`, objects through the catalog.
+    fn decorator(&self) -> FkDecorator {
+        match self {
+            Self::Dict(meta) => FkDecorator::for_dict(meta),
+            Self::Object { schema, catalog } => FkDecorator::for_schema(schema, catalog),
+        }
+    }
+}
+
+// Looks dictionary names up in the application database.
+struct DictNames<'a> {
+    state: &'a SharedState,
+}
+
+impl NameSource for DictNames<'_> {
+    type Error = RowError;
+
+    async fn lookup_names(
+        &self,
+        dict: &DCTMeta,
+        codes: &[String],
+    ) -> Result<HashMap<String, CellValue>, RowError> {
+        let statement = PgDml::new().select_names(dict, codes)?;
+        let mut connection = self.state.db_pool.acquire().await?;
+        row_repo::fetch_all(&statement, &mut connection)
+            .await?
+            .iter()
+            .map(|row| Ok(serde_json::from_str::<(String, CellValue)>(row)?))
+            .collect()
+    }
+}
+
+fn dml(user: &str) -> PgDml {
+    let context = SVRContext::new();
+    context.set(SVRContext::USER, json!(user));
+    PgDml::new().with_context(context)
+}
+
+async fn fetch_row(
+    state: &SharedState,
+    target: &RowTarget,
+    key: &str,
+    schema: &TableSchema,
+    statement: Statement,
+) -> Result<RowValues, RowError> {
+    let mut connection = state.db_pool.acquire().await?;
+    match row_repo::fetch_optional(&statement, &mut connection).await? {
+        Some(row) => Ok(PgDml::decode_row(schema, &row)?),
+        None => Err(RowError::RowNotFound {
+            meta_id: target.meta_id.clone(),
+            key: key.to_owned(),
+        }),
+    }
+}
+
+/// Lists a page of rows. With `decoration`, every foreign key column gets
+/// the name of the referenced dictionary entry next to its code.
+pub async fn list_rows(
+    state: &SharedState,
+    target: &RowTarget,
+    limit: u64,
+    offset: u64,
+    decoration: Option<Decoration>,
+) -> Result<RowPage, RowError> {
+    let table = RowTable::resolve(state, target)?;
+    let schema = table.schema();
+    let dml = PgDml::new();
+    let select = dml.select(schema, limit, offset)?;
+    let count = dml.count(schema)?;
+
+    let mut connection = state.db_pool.acquire().await?;
+    let total = row_repo::count(&count, &mut connection).await?;
+    let mut rows: Vec<RowValues> = row_repo::fetch_all(&select, &mut connection)
+        .await?
+        .iter()
+        .map(|row| PgDml::decode_row(schema, row))
+        .collect::<Result<_, _>>()?;
+    drop(connection);
+
+    let issues = match decoration {
+        Some(decoration) => {
+            let decorator = table.decorator().with_decoration(decoration);
+            decorator
+                .decorate_rows(&mut rows, &DictNames { state })
+                .await?
+                .issues
+        }
+        None => Vec::new(),
+    };
+    Ok(RowPage {
+        rows,
+        total,
+        limit,
+        offset,
+        issues,
+    })
+}
+
+pub async fn get_row(
+    state: &SharedState,
+    target: &RowTarget,
+    key: &str,
+) -> Result<RowValues, RowError> {
+    let table = RowTable::resolve(state, target)?;
+    let statement = PgDml::new().select_by_key(table.schema(), key)?;
+    fetch_row(state, target, key, table.schema(), statement).await
+}
+
+/// Validates `values` against the column metadata and inserts the row.
+/// `user` fills the creator and modifier system columns.
+pub async fn create_row(
+    state: &SharedState,
+    target: &RowTarget,
+    user: &str,
+    values: &RowValues,
+) -> Result<RowValues, RowError> {
+    let table = RowTable::resolve(state, target)?;
+    let schema = table.schema();
+    let statement = dml(user).insert(schema, values)?;
+
+    let mut connection = state.db_pool.acquire().await?;
+    let row = row_repo::fetch_optional(&statement, &mut connection)
+        .await?
+        .ok_or(sqlx::Error::RowNotFound)?;
+    Ok(PgDml::decode_row(schema, &row)?)
+}
+
+/// Validates the given columns and updates them in the row with the primary key `key`.
+pub async fn update_row(
+    state: &SharedState,
+    target: &RowTarget,
+    key: &str,
+    user: &str,
+    values: &RowValues,
+) -> Result<RowValues, RowError> {
+    let table = RowTable::resolve(state, target)?;
+    let statement = dml(user).update(table.schema(), key, values)?;
+    fetch_row(state, target, key, table.schema(), statement).await
+}
+
+/// Deletes the row with the primary key `key` and returns it.
+pub async fn delete_row(
+    state: &SharedState,
+    target: &RowTarget,
+    key: &str,
+) -> Result<RowValues, RowError> {
+    let table = RowTable::resolve(state, target)?;
+    let statement = PgDml::new().delete(table.schema(), key)?;
+    fetch_row(state, target, key, table.schema(), statement).await
+}
@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use tokio::sync::Mutex;
+use cmx_core::model::meta::registry::MetaRegistry;
 use cmx_infra::database::DatabasePool;
 use cmx_utils::config::Config;
 
@@ -10,4 +11,5 @@ pub struct AppState {
     pub config: Config,
     pub db_pool: DatabasePool,
     pub redis: Mutex<redis::aio::MultiplexedConnection>,
+    pub meta: MetaRegistry,
 }
@@ -1,4 +1,5 @@
 use chrono::NaiveDateTime;
+use cmx_core::model::meta::registry::DEFAULT_TENANT;
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, types::Uuid};
 
@@ -13,10 +14,17 @@ pub struct User {
     pub password_salt: String,
     pub active: bool,
     pub roles: String,
+    /// Tenant whose metadata catalog the user works with.
+    #[serde(default = "default_tenant")]
+    pub tenant: String,
     pub created_at: Option<NaiveDateTime>,
     pub updated_at: Option<NaiveDateTime>,
 }
 
+fn default_tenant() -> String {
+    DEFAULT_TENANT.to_owned()
+}
+
 impl User {
     pub fn is_admin(&self) -> bool {
         security::roles::contains_role_admin(&self.roles)
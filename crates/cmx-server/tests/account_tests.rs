@@ -129,6 +129,7 @@ async fn account_test() {
         password_salt: "xyz123".to_string(),
         active: true,
         roles: UserRole::Customer.to_string(),
+        tenant: "default".to_string(),
         created_at: None,
         updated_at: None,
     };
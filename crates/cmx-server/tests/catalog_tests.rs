@@ -0,0 +1,95 @@
+use cmx_core::model::meta::registry::MetaRegistry;
+use cmx_core::model::meta::reload::MetaChange;
+use cmx_infra::database::{Database, TestDatabase};
+use cmx_utils::config;
+use serial_test::serial;
+
+// The catalog tables are not created by the migrations; these carry only
+// the columns the tests need.
+const CATALOG: &str = "
+    CREATE TABLE SYS_OBJECTS (obj_id text, obj_mc text);
+    CREATE TABLE SYS_OBJCOLS (obj_id text, col_id text, col_disp integer, col_isfkey text, col_fobj text);
+    CREATE TABLE SYS_KEYS (obj_id text, key_id text);
+    CREATE TABLE SYS_INDEXS (obj_id text, idx_id text);
+    CREATE TABLE SYS_DICTS (dct_id text, obj_id text, dct_mc text);
+    CREATE TABLE SYS_FACTS (fct_id text, obj_id text);
+    CREATE TABLE SYS_MODEL (mdl_id text);
+    INSERT INTO SYS_OBJECTS VALUES ('T_DEPT', 'Departments'), ('T_STAFF', 'Staff');
+    INSERT INTO SYS_OBJCOLS VALUES
+        ('T_DEPT', 'DM', 1, NULL, NULL),
+        ('T_STAFF', 'DM', 1, NULL, NULL),
+        ('T_STAFF', 'BM', 2, '1', 'DCT_DEPT');
+    INSERT INTO SYS_DICTS VALUES ('DCT_DEPT', 'T_DEPT', 'Department'), ('DCT_STAFF', 'T_STAFF', 'Staff');
+";
+
+async fn open_catalog_database() -> TestDatabase {
+    unsafe { std::env::set_var("ENV_TEST", "1") };
+    let config = config::load();
+    let test_db = Database::open_test_database(config.into())
+        .await
+        .expect("Failed to connect to the test database.");
+    sqlx::raw_sql(CATALOG)
+        .execute(test_db.pool())
+        .await
+        .unwrap();
+    test_db
+}
+
+#[tokio::test]
+#[serial]
+async fn reload_catalog_loads_unknown_tenants_in_full() {
+    let test_db = open_catalog_database().await;
+    let registry = MetaRegistry::new();
+
+    let change = MetaChange::new("acme")
+        .with_dicts(["DCT_DEPT"])
+        .with_version(5);
+    let report = Database::reload_catalog(test_db.pool(), &registry, &change)
+        .await
+        .unwrap();
+    assert!(report.issues.is_empty());
+    assert_eq!(report.catalog.version(), 5);
+
+    // The dictionaries that were not named in the change are loaded too.
+    let staff = registry.get_dct_meta("acme", "DCT_STAFF").unwrap();
+    assert!(staff.dct_metas.as_ref().unwrap().contains_key("DCT_DEPT"));
+    assert!(
+        registry
+            .snapshot("acme")
+            .unwrap()
+            .schema("T_STAFF")
+            .is_some()
+    );
+
+    test_db.drop().await.unwrap();
+}
+
+#[tokio::test]
+#[serial]
+async fn reload_catalog_reloads_changed_objects() {
+    let test_db = open_catalog_database().await;
+    let pool = test_db.pool();
+    let registry = MetaRegistry::new();
+    Database::load_catalog(pool, &registry, "acme", 1)
+        .await
+        .unwrap();
+
+    sqlx::query("INSERT INTO SYS_OBJCOLS VALUES ('T_DEPT', 'MC', 2, NULL, NULL)")
+        .execute(pool)
+        .await
+        .unwrap();
+    let change = MetaChange::new("acme")
+        .with_objects(["T_DEPT"])
+        .with_version(2);
+    let report = Database::reload_catalog(pool, &registry, &change)
+        .await
+        .unwrap();
+    assert!(report.issues.is_empty());
+
+    let catalog = registry.snapshot("acme").unwrap();
+    assert_eq!(catalog.schema("T_DEPT").unwrap().column_count(), 2);
+    let dept = catalog.dct_meta("DCT_DEPT").unwrap();
+    assert_eq!(dept.table_schema.column_count(), 2);
+
+    test_db.drop().await.unwrap();
+}
@@ -1,5 +1,6 @@
 use std::{sync::Arc, time::Duration};
 
+use cmx_core::model::meta::registry::MetaRegistry;
 use cmx_utils::config;
 use reqwest::StatusCode;
 use tokio::{sync::Mutex, time::Instant};
@@ -37,6 +38,7 @@ pub async fn run() -> TestDatabase {
         config,
         db_pool: test_database.pool().clone(),
         redis: Mutex::new(redis),
+        meta: MetaRegistry::new(),
     });
 
     // Run the api server.
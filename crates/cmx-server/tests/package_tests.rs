@@ -0,0 +1,121 @@
+use std::time::Duration;
+
+use cmx_core::model::meta::catalog::{Catalog, CatalogRows};
+use cmx_core::model::meta::package::{EntryRef, ImportIssue, MetaPackage};
+use cmx_core::model::meta::registry::MetaCatalog;
+use cmx_core::model::meta::tables::SYS_TABLE_NAMES;
+use cmx_infra::database::{Database, DatabaseError, ImportOptions, TestDatabase, import_package};
+use cmx_utils::config;
+use serde_json::json;
+use serial_test::serial;
+
+// The catalog tables are not created by the migrations; these carry only
+// the columns the tests need, plus NOT NULL columns with defaults that the
+// metadata model does not know about.
+const CATALOG_TABLES: &str = "
+    CREATE TABLE SYS_OBJECTS (obj_id text, obj_mc text, obj_state text NOT NULL DEFAULT 'A');
+    CREATE TABLE SYS_OBJCOLS (obj_id text, col_id text, col_disp integer);
+    CREATE TABLE SYS_KEYS (obj_id text, key_id text);
+    CREATE TABLE SYS_INDEXS (obj_id text, idx_id text);
+    CREATE TABLE SYS_DICTS (dct_id text, obj_id text, dct_mc text, dct_flag integer NOT NULL DEFAULT 0);
+    CREATE TABLE SYS_FACTS (fct_id text, obj_id text);
+    CREATE TABLE SYS_MODEL (mdl_id text);
+";
+
+async fn open_catalog_database() -> TestDatabase {
+    unsafe { std::env::set_var("ENV_TEST", "1") };
+    let config = config::load();
+    let test_db = Database::open_test_database(config.into())
+        .await
+        .expect("Failed to connect to the test database.");
+    sqlx::raw_sql(CATALOG_TABLES)
+        .execute(test_db.pool())
+        .await
+        .unwrap();
+    test_db
+}
+
+fn dept_package() -> MetaPackage {
+    let mut rows = CatalogRows::new();
+    rows.push_json(
+        SYS_TABLE_NAMES::SYS_OBJECTS,
+        json!({"OBJ_ID": "T_DEPT", "OBJ_MC": "Departments"}),
+    );
+    rows.push_json(
+        SYS_TABLE_NAMES::SYS_OBJCOLS,
+        json!({"OBJ_ID": "T_DEPT", "COL_ID": "DM", "COL_DISP": 1}),
+    );
+    rows.push_json(
+        SYS_TABLE_NAMES::SYS_DICTS,
+        json!({"DCT_ID": "DCT_DEPT", "OBJ_ID": "T_DEPT", "DCT_MC": "Department"}),
+    );
+    let catalog = MetaCatalog::from(&Catalog::build(&rows));
+    MetaPackage::export(&catalog, &[EntryRef::dict("DCT_DEPT")]).unwrap()
+}
+
+#[tokio::test]
+#[serial]
+async fn import_package_keeps_column_defaults() {
+    let test_db = open_catalog_database().await;
+    let pool = test_db.pool();
+
+    let plan = import_package(pool, &dept_package(), ImportOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(plan.writes().count(), 2);
+
+    let (name, flag): (String, i32) =
+        sqlx::query_as("SELECT dct_mc, dct_flag FROM SYS_DICTS WHERE dct_id = 'DCT_DEPT'")
+            .fetch_one(pool)
+            .await
+            .unwrap();
+    assert_eq!((name.as_str(), flag), ("Department", 0));
+    let state: String =
+        sqlx::query_scalar("SELECT obj_state FROM SYS_OBJECTS WHERE obj_id = 'T_DEPT'")
+            .fetch_one(pool)
+            .await
+            .unwrap();
+    assert_eq!(state, "A");
+
+    test_db.drop().await.unwrap();
+}
+
+#[tokio::test]
+#[serial]
+async fn import_package_plans_after_concurrent_edits() {
+    let test_db = open_catalog_database().await;
+    let pool = test_db.pool();
+
+    // Another session adds the same dictionary but has not committed yet.
+    let mut edit = pool.begin().await.unwrap();
+    sqlx::query(
+        "INSERT INTO SYS_DICTS (dct_id, obj_id, dct_mc) VALUES ('DCT_DEPT', 'T_DEPT', 'Other')",
+    )
+    .execute(&mut *edit)
+    .await
+    .unwrap();
+
+    let import = tokio::spawn({
+        let pool = pool.clone();
+        async move { import_package(&pool, &dept_package(), ImportOptions::default()).await }
+    });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    edit.commit().await.unwrap();
+
+    // The import waited for the edit and planned against it.
+    let result = import.await.unwrap();
+    let Err(DatabaseError::ImportBlocked(issues)) = result else {
+        panic!(
+            "expected a conflict, got {:?}",
+            result.map(|plan| plan.steps)
+        );
+    };
+    assert_eq!(
+        issues,
+        [ImportIssue::Conflict {
+            entry: EntryRef::dict("DCT_DEPT")
+        }]
+    );
+
+    test_db.drop().await.unwrap();
+}
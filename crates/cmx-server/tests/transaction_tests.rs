@@ -33,6 +33,7 @@ async fn prepare_accounts(tokens: &AuthTokens) -> (Account, Account) {
         password_salt: "xyz123".to_string(),
         active: true,
         roles: UserRole::Customer.to_string(),
+        tenant: "default".to_string(),
         created_at: None,
         updated_at: None,
     };
@@ -65,6 +66,7 @@ async fn prepare_accounts(tokens: &AuthTokens) -> (Account, Account) {
         password_salt: "xyz123".to_string(),
         active: true,
         roles: UserRole::Customer.to_string(),
+        tenant: "default".to_string(),
         created_at: None,
         updated_at: None,
     };
@@ -26,6 +26,7 @@ fn test_user() -> User {
         password_salt: "xyz123".to_string(),
         active: true,
         roles: "guest".to_string(),
+        tenant: "default".to_string(),
         created_at: None,
         updated_at: None,
     }
@@ -170,3 +171,16 @@ async fn add_get_update_delete_user_test() {
     // Drop test database.
     test_db.drop().await.unwrap();
 }
+
+#[test]
+fn access_token_carries_user_tenant_test() {
+    unsafe { std::env::set_var("ENV_TEST", "1") };
+    let config = cmx_utils::config::load();
+
+    let mut user = test_user();
+    user.tenant = "acme".to_string();
+    let tokens = cmx_server::application::security::auth::generate_tokens(user, &config);
+
+    let access_claims: AccessClaims = jwt::decode_token(&tokens.access_token, &config).unwrap();
+    assert_eq!(access_claims.tenant, "acme");
+}
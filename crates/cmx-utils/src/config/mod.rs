@@ -29,6 +29,10 @@ pub struct Config {
     pub jwt_expire_refresh_token_seconds: i64,
     pub jwt_validation_leeway_seconds: i64,
     pub jwt_enable_revoked_tokens: bool,
+
+    // Metadata snapshot file, installed at startup and refreshed after each
+    // full catalog load. Optional.
+    pub meta_snapshot_path: Option<String>,
 }
 #[derive(Clone)]
 pub struct JwtKeys {
@@ -113,6 +117,7 @@ pub fn load() -> Config {
         jwt_expire_refresh_token_seconds: env_parse("JWT_EXPIRE_REFRESH_TOKEN_SECONDS"),
         jwt_validation_leeway_seconds: env_parse("JWT_VALIDATION_LEEWAY_SECONDS"),
         jwt_enable_revoked_tokens: env_parse("JWT_ENABLE_REVOKED_TOKENS"),
+        meta_snapshot_path: env_get_opt("META_SNAPSHOT_PATH"),
     };
 
     tracing::trace!("configuration: {:#?}", config);
@@ -141,6 +146,11 @@ fn env_get_or(key: &str, default: &str) -> String {
     default.to_owned()
 }
 
+#[inline]
+fn env_get_opt(key: &str) -> Option<String> {
+    std::env::var(key).ok().filter(|v| !v.is_empty())
+}
+
 #[inline]
 fn env_parse<T: std::str::FromStr>(key: &str) -> T {
     env_get(key).parse().map_or_else(